@@ -57,6 +57,21 @@ pub enum BeefyClientError {
 	IncompleteSignatureThreshold,
 	/// Error recovering public key from signature
 	InvalidSignature,
+	/// The signatures of a sampled signed commitment aren't those of the sampled authorities
+	InvalidSignatureSample,
+	/// The signature sample size is too small for the authority set
+	#[from(ignore)]
+	#[display(
+		fmt = "SignatureSampleTooSmall: sample_size {}, min_sample_size {}",
+		sample_size,
+		min_sample_size
+	)]
+	SignatureSampleTooSmall {
+		/// Signature sample size of the light client
+		sample_size: u32,
+		/// Smallest sample size for the authority set
+		min_sample_size: u32,
+	},
 	/// Some invalid merkle root hash
 	#[from(ignore)]
 	#[display(fmt = "InvalidRootHash with len: {}", len)]
@@ -92,6 +92,32 @@ pub struct MmrUpdateProof {
 	pub authority_proof: Vec<Hash>,
 }
 
+#[derive(Clone, sp_std::fmt::Debug, PartialEq, Eq, Encode, Decode)]
+/// Signed commitment that only carries the signatures of a pseudo-random sample of the authorities
+/// claiming to have signed it, see [`sample_signers`].
+pub struct SampledSignedCommitment {
+	/// Commitment
+	pub commitment: beefy_primitives::Commitment<u32>,
+	/// Bitfield of the authorities claiming to have signed the commitment, the bit of authority
+	/// `i` is bit `i % 8` of byte `i / 8`.
+	pub signers: Vec<u8>,
+	/// Signatures of the sampled authorities, ordered by authority index
+	pub signatures: Vec<SignatureWithAuthorityIndex>,
+}
+
+#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
+/// Mmr Update with a sampled signed commitment
+pub struct SampledMmrUpdateProof {
+	/// Sampled signed commitment
+	pub signed_commitment: SampledSignedCommitment,
+	/// Latest leaf added to mmr
+	pub latest_mmr_leaf: MmrLeaf<u32, H256, H256, H256>,
+	/// Proof for the latest mmr leaf
+	pub mmr_proof: pallet_mmr_primitives::Proof<H256>,
+	/// Proof for the sampled authorities in current session
+	pub authority_proof: Vec<Hash>,
+}
+
 #[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
 /// A partial representation of the mmr leaf
 pub struct PartialMmrLeaf {
@@ -134,6 +160,29 @@ pub struct ParachainsUpdateProof {
 	pub mmr_proof: pallet_mmr_primitives::BatchProof<H256>,
 }
 
+#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
+/// Relay chain header definition
+pub struct RelayChainHeader {
+	/// scale encoded relay chain header
+	pub relay_chain_header: Vec<u8>,
+	/// The mmr leaf appended by the child of this header, whose parent hash is the hash of this
+	/// header
+	pub mmr_leaf: MmrLeaf<u32, H256, H256, H256>,
+	/// Trie merkle proof of inclusion of the set timestamp extrinsic in header.extrinsic_root
+	pub extrinsic_proof: Vec<Vec<u8>>,
+	/// Timestamp extrinsic
+	pub timestamp_extrinsic: Vec<u8>,
+}
+
+#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
+/// Relay chain headers update with proof
+pub struct RelayChainUpdateProof {
+	/// Relay chain headers
+	pub relay_chain_headers: Vec<RelayChainHeader>,
+	/// Mmr Batch proof for the mmr leaves of the relay chain headers
+	pub mmr_proof: pallet_mmr_primitives::BatchProof<H256>,
+}
+
 /// MMR nodes & size -related utilities.
 pub struct NodesUtils {
 	no_of_leaves: u64,
@@ -193,3 +242,65 @@ pub fn get_leaf_index_for_block_number(activation_block: u32, block_number: u32)
 		activation_block - (block_number + 1)
 	}
 }
+
+/// Security level, in bits, of a sampled commitment against a relayer claiming forged signatures.
+pub const SIGNATURE_SAMPLE_SECURITY_BITS: u32 = 40;
+
+/// Bound, in bits, on the number of samples a relayer can grind through for a forged commitment.
+pub const SIGNATURE_SAMPLE_GRINDING_BITS: u32 = 80;
+
+/// Smallest sample size that keeps a forged commitment out of a light client tracking an authority
+/// set of `authority_set_len`.
+///
+/// At most `(len - 1) / 3` authorities sign a forged commitment, and the claimed signers must be
+/// more than two thirds of the set, so each sampled authority is a genuine signer with probability
+/// at most 1/2 and a sample of `s` authorities only checks genuine signatures with probability at
+/// most `2^-s`. The seed of [`sample_signers`] only depends on the commitment and the mmr root the
+/// light client already knows, but a relayer colluding with the faulty authorities can have them
+/// sign as many distinct commitments as it likes, eg. with other payloads, and look for one whose
+/// seed samples only their signatures. The sample must cover this grinding bound on top of the
+/// security level. A sample larger than the number of faulty authorities always checks a forged
+/// signature.
+pub fn min_signature_sample_size(authority_set_len: u32) -> u32 {
+	let max_faulty = authority_set_len.saturating_sub(1) / 3;
+	(max_faulty + 1).min(SIGNATURE_SAMPLE_SECURITY_BITS + SIGNATURE_SAMPLE_GRINDING_BITS)
+}
+
+/// Indices of the authorities whose signatures must be checked in a [`SampledSignedCommitment`],
+/// sorted in ascending order.
+///
+/// The sample is drawn from the claimed `signers` of an authority set of `authority_set_len`,
+/// seeded with the hash of the commitment and the mmr root known to the light client before the
+/// update. The relayer can't pick the sample directly, but it can try other seeds by forging other
+/// commitments, so `sample_size` must be at least [`min_signature_sample_size`]. At most
+/// `sample_size` authorities are sampled, or all of the claimed signers if there are fewer of them.
+pub fn sample_signers<H: HostFunctions>(
+	signers: &[u8],
+	authority_set_len: u32,
+	commitment_hash: &Hash,
+	previous_mmr_root: &H256,
+	sample_size: u32,
+) -> Vec<u32> {
+	let mut candidates = (0..authority_set_len)
+		.filter(|i| signers.get(*i as usize / 8).map_or(false, |byte| (byte >> (i % 8)) & 1 == 1))
+		.collect::<Vec<_>>();
+	let sample_size = (sample_size as usize).min(candidates.len());
+
+	let mut seed = commitment_hash.to_vec();
+	seed.extend_from_slice(previous_mmr_root.as_bytes());
+	let seed = H::keccak_256(&seed);
+
+	// partial Fisher-Yates shuffle, the first `sample_size` candidates end up being the sample.
+	for i in 0..sample_size {
+		let mut preimage = seed.to_vec();
+		preimage.extend_from_slice(&(i as u32).to_le_bytes());
+		let random = H::keccak_256(&preimage);
+		let random = u64::from_le_bytes(random[..8].try_into().expect("hash is 32 bytes; qed"));
+		let j = i + (random % (candidates.len() - i) as u64) as usize;
+		candidates.swap(i, j);
+	}
+
+	let mut sample = candidates[..sample_size].to_vec();
+	sample.sort();
+	sample
+}
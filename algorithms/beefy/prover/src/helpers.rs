@@ -14,10 +14,10 @@
 // limitations under the License.
 
 use crate::{error::Error, Crypto};
-use beefy_light_client_primitives::{MerkleHasher, SignatureWithAuthorityIndex};
+use beefy_light_client_primitives::{sample_signers, MerkleHasher, SignatureWithAuthorityIndex};
 use codec::{Decode, Encode};
 use frame_support::sp_runtime::traits::Convert;
-use sp_core::keccak_256;
+use sp_core::{keccak_256, H256};
 use sp_runtime::traits::BlakeTwo256;
 use sp_trie::{generate_trie_proof, TrieDBMut, TrieMut};
 use std::{collections::BTreeMap, sync::Arc};
@@ -41,6 +41,17 @@ pub struct AuthorityProofWithSignatures {
 	pub signatures: Vec<SignatureWithAuthorityIndex>,
 }
 
+/// This holds the signatures of a sample of the authorities that signed a BEEFY commitment,
+/// along side the bitfield of all the signers and a merkle multi-proof of the sampled authorities.
+pub struct SampledAuthorityProofWithSignatures {
+	/// Bitfield of the authorities that signed the commitment
+	pub signers: Vec<u8>,
+	/// Merkle multi-proof of the sampled authorities
+	pub authority_proof: Vec<[u8; 32]>,
+	/// The signatures of the sampled authorities alongside their authority index.
+	pub signatures: Vec<SignatureWithAuthorityIndex>,
+}
+
 /// This holds the proof that a parachain header was included in the parachain heads root (extra
 /// data) field in an mmr leaf.
 pub struct ParaHeadsProof {
@@ -188,6 +199,50 @@ pub fn prove_authority_set(
 	})
 }
 
+/// Prove a sample of the authorities that signed a commitment, see
+/// [`beefy_light_client_primitives::sample_signers`].
+pub fn prove_sampled_authority_set(
+	signed_commitment: &beefy_primitives::SignedCommitment<
+		u32,
+		beefy_primitives::crypto::Signature,
+	>,
+	authority_address_hashes: Vec<[u8; 32]>,
+	previous_mmr_root: H256,
+	sample_size: u32,
+) -> Result<SampledAuthorityProofWithSignatures, Error> {
+	let AuthorityProofWithSignatures { signatures, .. } =
+		prove_authority_set(signed_commitment, authority_address_hashes.clone())?;
+
+	let mut signers = vec![0u8; (authority_address_hashes.len() + 7) / 8];
+	for SignatureWithAuthorityIndex { index, .. } in &signatures {
+		signers[*index as usize / 8] |= 1 << (index % 8);
+	}
+
+	let commitment_hash = keccak_256(&signed_commitment.commitment.encode());
+	let sample = sample_signers::<Crypto>(
+		&signers,
+		authority_address_hashes.len() as u32,
+		&commitment_hash,
+		&previous_mmr_root,
+		sample_size,
+	);
+	let signatures = signatures
+		.into_iter()
+		.filter(|signature| sample.contains(&signature.index))
+		.collect::<Vec<_>>();
+	let signature_indices = signatures.iter().map(|x| x.index as usize).collect::<Vec<_>>();
+
+	let tree =
+		rs_merkle::MerkleTree::<MerkleHasher<Crypto>>::from_leaves(&authority_address_hashes);
+
+	let authority_proof = tree.proof(&signature_indices);
+	Ok(SampledAuthorityProofWithSignatures {
+		signers,
+		authority_proof: authority_proof.proof_hashes().to_vec(),
+		signatures,
+	})
+}
+
 /// Hash encoded authority public keys
 pub fn hash_authority_addresses(encoded_public_keys: Vec<Vec<u8>>) -> Result<Vec<[u8; 32]>, Error> {
 	let authority_address_hashes = encoded_public_keys
@@ -29,7 +29,8 @@ pub mod runtime;
 
 use beefy_light_client_primitives::{
 	get_leaf_index_for_block_number, ClientState, HostFunctions, MerkleHasher, MmrUpdateProof,
-	ParachainHeader, PartialMmrLeaf, SignedCommitment,
+	ParachainHeader, PartialMmrLeaf, RelayChainHeader, SampledMmrUpdateProof,
+	SampledSignedCommitment, SignedCommitment,
 };
 use beefy_primitives::{
 	known_payload_ids::MMR_ROOT_ID,
@@ -53,7 +54,10 @@ use crate::{
 	relay_chain_queries::parachain_header_storage_key,
 	runtime::api::runtime_types::polkadot_parachain::primitives::Id,
 };
-use helpers::{prove_authority_set, AuthorityProofWithSignatures};
+use helpers::{
+	prove_authority_set, prove_sampled_authority_set, AuthorityProofWithSignatures,
+	SampledAuthorityProofWithSignatures,
+};
 use relay_chain_queries::{
 	fetch_finalized_parachain_heads, fetch_mmr_leaf_proof, FinalizedParaHeads,
 };
@@ -303,6 +307,60 @@ where
 		Ok((parachain_headers, batch_proof))
 	}
 
+	/// This will query the finalized relay chain headers with the given numbers, including the mmr
+	/// leaves appended by their children into the [`MmrBatchProof`] for the commitment's block.
+	pub async fn query_finalized_relay_chain_headers_with_proof(
+		&self,
+		commitment_block_number: u32,
+		header_numbers: Vec<u32>,
+	) -> Result<(Vec<RelayChainHeader>, BatchProof<H256>), Error>
+	where
+		T::Hash: From<H256>,
+	{
+		let leaf_indices = header_numbers
+			.iter()
+			.map(|number| get_leaf_index_for_block_number(self.beefy_activation_block, number + 1))
+			.collect::<Vec<_>>();
+
+		let subxt_block_number: subxt::rpc::BlockNumber = commitment_block_number.into();
+		let block_hash = self.relay_client.rpc().block_hash(Some(subxt_block_number)).await?;
+
+		let batch_proof =
+			fetch_mmr_batch_proof(&self.relay_client, leaf_indices, block_hash).await?;
+
+		let leaves: Vec<Vec<u8>> = Decode::decode(&mut &*batch_proof.leaves.to_vec())?;
+
+		let mut relay_chain_headers = vec![];
+		for leaf_bytes in leaves {
+			let mmr_leaf: MmrLeaf<u32, H256, H256, H256> = Decode::decode(&mut &*leaf_bytes)?;
+			let (_, parent_hash) = mmr_leaf.parent_number_and_hash;
+			let header =
+				self.relay_client.rpc().header(Some(parent_hash.into())).await?.ok_or_else(
+					|| {
+						Error::Custom(format!(
+							"[get_relay_chain_headers] block not found {:?}",
+							parent_hash
+						))
+					},
+				)?;
+
+			let TimeStampExtWithProof { ext: timestamp_extrinsic, proof: extrinsic_proof } =
+				fetch_timestamp_extrinsic_with_proof(&self.relay_client, Some(parent_hash.into()))
+					.await?;
+
+			relay_chain_headers.push(RelayChainHeader {
+				relay_chain_header: header.encode(),
+				mmr_leaf,
+				extrinsic_proof,
+				timestamp_extrinsic,
+			});
+		}
+
+		let batch_proof: pallet_mmr_primitives::BatchProof<H256> =
+			Decode::decode(&mut batch_proof.proof.0.as_slice())?;
+		Ok((relay_chain_headers, batch_proof))
+	}
+
 	/// This will fetch the latest leaf in the mmr as well as a proof for this leaf in the latest
 	/// mmr root hash.
 	pub async fn fetch_mmr_update_proof_for(
@@ -312,6 +370,69 @@ where
 			beefy_primitives::crypto::Signature,
 		>,
 	) -> Result<MmrUpdateProof, Error> {
+		let (latest_leaf, mmr_proof, authority_address_hashes) =
+			self.fetch_latest_leaf_and_authorities(&signed_commitment).await?;
+
+		let AuthorityProofWithSignatures { authority_proof, signatures } =
+			prove_authority_set(&signed_commitment, authority_address_hashes)?;
+
+		Ok(MmrUpdateProof {
+			signed_commitment: SignedCommitment {
+				commitment: signed_commitment.commitment.clone(),
+				signatures,
+			},
+			latest_mmr_leaf: latest_leaf,
+			mmr_proof,
+			authority_proof,
+		})
+	}
+
+	/// Like [`Self::fetch_mmr_update_proof_for`], but only proves the signatures of
+	/// `sample_size` signers sampled using the light client's current mmr root hash.
+	pub async fn fetch_sampled_mmr_update_proof_for(
+		&self,
+		signed_commitment: beefy_primitives::SignedCommitment<
+			u32,
+			beefy_primitives::crypto::Signature,
+		>,
+		previous_mmr_root: H256,
+		sample_size: u32,
+	) -> Result<SampledMmrUpdateProof, Error> {
+		let (latest_leaf, mmr_proof, authority_address_hashes) =
+			self.fetch_latest_leaf_and_authorities(&signed_commitment).await?;
+
+		let SampledAuthorityProofWithSignatures { signers, authority_proof, signatures } =
+			prove_sampled_authority_set(
+				&signed_commitment,
+				authority_address_hashes,
+				previous_mmr_root,
+				sample_size,
+			)?;
+
+		Ok(SampledMmrUpdateProof {
+			signed_commitment: SampledSignedCommitment {
+				commitment: signed_commitment.commitment.clone(),
+				signers,
+				signatures,
+			},
+			latest_mmr_leaf: latest_leaf,
+			mmr_proof,
+			authority_proof,
+		})
+	}
+
+	/// Fetch the mmr leaf for the commitment, its proof and the hashed addresses of the
+	/// authorities at the commitment's block.
+	async fn fetch_latest_leaf_and_authorities(
+		&self,
+		signed_commitment: &beefy_primitives::SignedCommitment<
+			u32,
+			beefy_primitives::crypto::Signature,
+		>,
+	) -> Result<
+		(MmrLeaf<u32, H256, H256, H256>, pallet_mmr_primitives::Proof<H256>, Vec<[u8; 32]>),
+		Error,
+	> {
 		let subxt_block_number: subxt::rpc::BlockNumber =
 			signed_commitment.commitment.block_number.into();
 		let block_hash = self.relay_client.rpc().block_hash(Some(subxt_block_number)).await?;
@@ -342,18 +463,7 @@ where
 			current_authorities.into_iter().map(|x| x.encode()).collect(),
 		)?;
 
-		let AuthorityProofWithSignatures { authority_proof, signatures } =
-			prove_authority_set(&signed_commitment, authority_address_hashes)?;
-
-		Ok(MmrUpdateProof {
-			signed_commitment: SignedCommitment {
-				commitment: signed_commitment.commitment.clone(),
-				signatures,
-			},
-			latest_mmr_leaf: latest_leaf.clone(),
-			mmr_proof,
-			authority_proof,
-		})
+		Ok((latest_leaf, mmr_proof, authority_address_hashes))
 	}
 
 	/// Construct a beefy client state to be submitted to the counterparty chain
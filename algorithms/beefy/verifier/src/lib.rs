@@ -24,13 +24,16 @@ extern crate alloc;
 mod tests;
 
 use beefy_light_client_primitives::{
-	error::BeefyClientError, get_leaf_index_for_block_number, BeefyNextAuthoritySet, ClientState,
-	HostFunctions, MerkleHasher, MmrUpdateProof, NodesUtils, ParachainsUpdateProof,
-	SignatureWithAuthorityIndex, HASH_LENGTH,
+	error::BeefyClientError, get_leaf_index_for_block_number, min_signature_sample_size,
+	sample_signers, BeefyNextAuthoritySet, ClientState, HostFunctions, MerkleHasher,
+	MmrUpdateProof, NodesUtils, ParachainsUpdateProof, RelayChainUpdateProof,
+	SampledMmrUpdateProof, SampledSignedCommitment, SignatureWithAuthorityIndex, HASH_LENGTH,
 };
-use beefy_primitives::{known_payload_ids::MMR_ROOT_ID, mmr::MmrLeaf};
+use beefy_primitives::{known_payload_ids::MMR_ROOT_ID, mmr::MmrLeaf, Commitment};
 use codec::{Decode, Encode};
 use frame_support::sp_runtime::{app_crypto::ByteArray, traits::Convert};
+use hash_db::Hasher;
+use pallet_mmr_primitives::BatchProof;
 use sp_core::H256;
 
 use alloc::{format, string::ToString};
@@ -43,7 +46,7 @@ use sp_trie::LayoutV0;
 /// then using the mmr proofs, verify the latest mmr leaf,
 /// using the latest mmr leaf to rotate its view of the next authorities.
 pub fn verify_mmr_root_with_proof<H>(
-	mut trusted_client_state: ClientState,
+	trusted_client_state: ClientState,
 	mmr_update: MmrUpdateProof,
 ) -> Result<ClientState, BeefyClientError>
 where
@@ -52,7 +55,6 @@ where
 	let current_authority_set = &trusted_client_state.current_authorities;
 	let next_authority_set = &trusted_client_state.next_authorities;
 	let signatures_len = mmr_update.signed_commitment.signatures.len();
-	let validator_set_id = mmr_update.signed_commitment.commitment.validator_set_id;
 
 	// If signature threshold is not satisfied, return
 	if !validate_sigs_against_threshold(current_authority_set, signatures_len) &&
@@ -61,17 +63,113 @@ where
 		return Err(BeefyClientError::IncompleteSignatureThreshold)
 	}
 
-	if current_authority_set.id != validator_set_id && next_authority_set.id != validator_set_id {
-		return Err(BeefyClientError::AuthoritySetMismatch {
-			current_set_id: current_authority_set.id,
-			next_set_id: next_authority_set.id,
+	let commitment = mmr_update.signed_commitment.commitment;
+	let (authority_set, authorities_changed) =
+		authority_set_for_commitment(&trusted_client_state, commitment.validator_set_id)?;
+	let mmr_root_hash = mmr_root_from_commitment(&commitment)?;
+
+	// Beefy validators sign the keccak_256 hash of the scale encoded commitment
+	let commitment_hash = H::keccak_256(&*commitment.encode());
+	verify_authority_signatures::<H>(
+		authority_set,
+		&commitment_hash,
+		mmr_update.signed_commitment.signatures,
+		mmr_update.authority_proof,
+	)?;
+
+	apply_mmr_update::<H>(
+		trusted_client_state,
+		commitment.block_number,
+		mmr_root_hash,
+		mmr_update.latest_mmr_leaf,
+		mmr_update.mmr_proof,
+		authorities_changed,
+	)
+}
+
+/// Like [`verify_mmr_root_with_proof`], but the commitment only carries the signatures of
+/// `sample_size` authorities sampled from the ones claiming to have signed it, see
+/// [`beefy_light_client_primitives::sample_signers`]. The sample size must be at least
+/// [`beefy_light_client_primitives::min_signature_sample_size`] for the signing authority set and
+/// the claimed signers must still satisfy the signature threshold, an update with a false claim is
+/// rejected unless none of the sampled authorities are the ones that didn't sign.
+pub fn verify_mmr_root_with_sampled_proof<H>(
+	trusted_client_state: ClientState,
+	mmr_update: SampledMmrUpdateProof,
+	sample_size: u32,
+) -> Result<ClientState, BeefyClientError>
+where
+	H: HostFunctions + Clone,
+{
+	let SampledSignedCommitment { commitment, signers, mut signatures } =
+		mmr_update.signed_commitment;
+	let (authority_set, authorities_changed) =
+		authority_set_for_commitment(&trusted_client_state, commitment.validator_set_id)?;
+	let min_sample_size = min_signature_sample_size(authority_set.len);
+	if sample_size < min_sample_size {
+		Err(BeefyClientError::SignatureSampleTooSmall { sample_size, min_sample_size })?
+	}
+
+	let claimed_signers_len = (0..authority_set.len)
+		.filter(|i| signers.get(*i as usize / 8).map_or(false, |byte| (byte >> (i % 8)) & 1 == 1))
+		.count();
+	if !validate_sigs_against_threshold(authority_set, claimed_signers_len) {
+		return Err(BeefyClientError::IncompleteSignatureThreshold)
+	}
+
+	let mmr_root_hash = mmr_root_from_commitment(&commitment)?;
+	let commitment_hash = H::keccak_256(&*commitment.encode());
+
+	let sample = sample_signers::<H>(
+		&signers,
+		authority_set.len,
+		&commitment_hash,
+		&trusted_client_state.mmr_root_hash,
+		sample_size,
+	);
+	signatures.sort_by_key(|signature| signature.index);
+	if !signatures.iter().map(|signature| signature.index).eq(sample.into_iter()) {
+		return Err(BeefyClientError::InvalidSignatureSample)
+	}
+	verify_authority_signatures::<H>(
+		authority_set,
+		&commitment_hash,
+		signatures,
+		mmr_update.authority_proof,
+	)?;
+
+	apply_mmr_update::<H>(
+		trusted_client_state,
+		commitment.block_number,
+		mmr_root_hash,
+		mmr_update.latest_mmr_leaf,
+		mmr_update.mmr_proof,
+		authorities_changed,
+	)
+}
+
+/// Authority set that signed commitments with the given validator set id, and whether it is the
+/// next authority set.
+fn authority_set_for_commitment(
+	client_state: &ClientState,
+	validator_set_id: u64,
+) -> Result<(&BeefyNextAuthoritySet<H256>, bool), BeefyClientError> {
+	match validator_set_id {
+		id if id == client_state.current_authorities.id =>
+			Ok((&client_state.current_authorities, false)),
+		id if id == client_state.next_authorities.id => Ok((&client_state.next_authorities, true)),
+		_ => Err(BeefyClientError::AuthoritySetMismatch {
+			current_set_id: client_state.current_authorities.id,
+			next_set_id: client_state.next_authorities.id,
 			commitment_set_id: validator_set_id,
-		})
+		}),
 	}
+}
 
-	// Extract root hash from signed commitment and validate it
+/// Extract root hash from signed commitment and validate it
+fn mmr_root_from_commitment(commitment: &Commitment<u32>) -> Result<H256, BeefyClientError> {
 	let mmr_root_vec = {
-		if let Some(root) = mmr_update.signed_commitment.commitment.payload.get_raw(&MMR_ROOT_ID) {
+		if let Some(root) = commitment.payload.get_raw(&MMR_ROOT_ID) {
 			if root.len() == HASH_LENGTH {
 				root
 			} else {
@@ -85,19 +183,25 @@ where
 		}
 	};
 
-	let mmr_root_hash = H256::from_slice(&*mmr_root_vec);
-
-	// Beefy validators sign the keccak_256 hash of the scale encoded commitment
-	let encoded_commitment = mmr_update.signed_commitment.commitment.encode();
-	let commitment_hash = H::keccak_256(&*encoded_commitment);
+	Ok(H256::from_slice(&*mmr_root_vec))
+}
 
+/// Recover the authorities from their signatures of the commitment hash and verify their
+/// membership in the authority set with the authority merkle proof.
+fn verify_authority_signatures<H>(
+	authority_set: &BeefyNextAuthoritySet<H256>,
+	commitment_hash: &[u8; 32],
+	signatures: Vec<SignatureWithAuthorityIndex>,
+	authority_proof: Vec<[u8; 32]>,
+) -> Result<(), BeefyClientError>
+where
+	H: HostFunctions + Clone,
+{
 	let mut authority_indices = Vec::new();
-	let authority_leaves = mmr_update
-		.signed_commitment
-		.signatures
+	let authority_leaves = signatures
 		.into_iter()
 		.map(|SignatureWithAuthorityIndex { index, signature }| {
-			H::secp256k1_ecdsa_recover_compressed(&signature, &commitment_hash)
+			H::secp256k1_ecdsa_recover_compressed(&signature, commitment_hash)
 				.and_then(|public_key_bytes| {
 					beefy_primitives::crypto::AuthorityId::from_slice(&public_key_bytes).ok()
 				})
@@ -109,46 +213,35 @@ where
 		})
 		.collect::<Result<Vec<_>, BeefyClientError>>()?;
 
-	let mut authorities_changed = false;
-
-	let authorities_merkle_proof =
-		rs_merkle::MerkleProof::<MerkleHasher<H>>::new(mmr_update.authority_proof);
-	// Verify mmr_update.authority_proof against store root hash
-	match validator_set_id {
-		id if id == current_authority_set.id => {
-			let root_hash = current_authority_set.root;
-			if !authorities_merkle_proof.verify(
-				root_hash.into(),
-				&authority_indices,
-				&authority_leaves,
-				current_authority_set.len as usize,
-			) {
-				return Err(BeefyClientError::InvalidAuthorityProof)
-			}
-		},
-		id if id == next_authority_set.id => {
-			let root_hash = next_authority_set.root;
-			if !authorities_merkle_proof.verify(
-				root_hash.into(),
-				&authority_indices,
-				&authority_leaves,
-				next_authority_set.len as usize,
-			) {
-				return Err(BeefyClientError::InvalidAuthorityProof)
-			}
-			authorities_changed = true;
-		},
-		_ =>
-			return Err(BeefyClientError::AuthoritySetMismatch {
-				current_set_id: current_authority_set.id,
-				next_set_id: next_authority_set.id,
-				commitment_set_id: validator_set_id,
-			}),
+	// Verify the authority proof against the root hash of the signing authority set
+	let authorities_merkle_proof = rs_merkle::MerkleProof::<MerkleHasher<H>>::new(authority_proof);
+	if !authorities_merkle_proof.verify(
+		authority_set.root.into(),
+		&authority_indices,
+		&authority_leaves,
+		authority_set.len as usize,
+	) {
+		return Err(BeefyClientError::InvalidAuthorityProof)
 	}
 
+	Ok(())
+}
+
+/// Verify the latest mmr leaf against the signed mmr root hash and move the client state to the
+/// commitment, rotating the authority sets if the commitment was signed by the next authorities.
+fn apply_mmr_update<H>(
+	mut trusted_client_state: ClientState,
+	commitment_block_number: u32,
+	mmr_root_hash: H256,
+	latest_mmr_leaf: MmrLeaf<u32, H256, H256, H256>,
+	mmr_proof: pallet_mmr_primitives::Proof<H256>,
+	authorities_changed: bool,
+) -> Result<ClientState, BeefyClientError>
+where
+	H: HostFunctions + Clone,
+{
 	let latest_beefy_height = trusted_client_state.latest_beefy_height;
 
-	let commitment_block_number = mmr_update.signed_commitment.commitment.block_number;
 	if commitment_block_number <= latest_beefy_height {
 		return Err(BeefyClientError::OutdatedCommitment {
 			latest_beefy_height,
@@ -157,13 +250,12 @@ where
 	}
 
 	// Move on to verify mmr_proof
-	let node = mmr_update.latest_mmr_leaf.using_encoded(|leaf| H::keccak_256(leaf));
+	let node = latest_mmr_leaf.using_encoded(|leaf| H::keccak_256(leaf));
 
-	let mmr_size = NodesUtils::new(mmr_update.mmr_proof.leaf_count).size();
-	let proof =
-		mmr_lib::MerkleProof::<_, MerkleHasher<H>>::new(mmr_size, mmr_update.mmr_proof.items);
+	let mmr_size = NodesUtils::new(mmr_proof.leaf_count).size();
+	let proof = mmr_lib::MerkleProof::<_, MerkleHasher<H>>::new(mmr_size, mmr_proof.items);
 
-	let leaf_pos = mmr_lib::leaf_index_to_pos(mmr_update.mmr_proof.leaf_index);
+	let leaf_pos = mmr_lib::leaf_index_to_pos(mmr_proof.leaf_index);
 
 	let root = proof.calculate_root(vec![(leaf_pos, node.into())])?;
 	if root != mmr_root_hash {
@@ -174,12 +266,12 @@ where
 		})
 	}
 
-	trusted_client_state.latest_beefy_height = mmr_update.signed_commitment.commitment.block_number;
+	trusted_client_state.latest_beefy_height = commitment_block_number;
 	trusted_client_state.mmr_root_hash = mmr_root_hash;
 
 	if authorities_changed {
-		trusted_client_state.current_authorities = next_authority_set.clone();
-		trusted_client_state.next_authorities = mmr_update.latest_mmr_leaf.beefy_next_authority_set;
+		trusted_client_state.current_authorities = trusted_client_state.next_authorities.clone();
+		trusted_client_state.next_authorities = latest_mmr_leaf.beefy_next_authority_set;
 	}
 	Ok(trusted_client_state)
 }
@@ -206,16 +298,11 @@ where
 			))?
 		}
 
-		// Verify timestamp extrinsic
-		// Timestamp extrinsic should be the first inherent and hence the first extrinsic
-		// https://github.com/paritytech/substrate/blob/d602397a0bbb24b5d627795b797259a44a5e29e9/primitives/trie/src/lib.rs#L99-L101
-		let timestamp_ext_key = codec::Compact(0u32).encode();
-		sp_trie::verify_trie_proof::<LayoutV0<H::BlakeTwo256>, _, _, _>(
-			&decoded_para_header.extrinsics_root,
-			&&*parachain_header.extrinsic_proof,
-			&vec![(timestamp_ext_key, Some(&*parachain_header.timestamp_extrinsic))],
-		)
-		.map_err(|_| BeefyClientError::Custom(format!("Invalid extrinsic proof")))?;
+		verify_timestamp_extrinsic::<H>(
+			&decoded_para_header,
+			&parachain_header.extrinsic_proof,
+			&parachain_header.timestamp_extrinsic,
+		)?;
 
 		let pair = (parachain_header.para_id, parachain_header.parachain_header);
 		let leaf_bytes = pair.encode();
@@ -248,6 +335,101 @@ where
 		mmr_leaves.push((leaf_pos, H256::from_slice(&node)));
 	}
 
+	verify_mmr_leaves::<H>(
+		&trusted_client_state,
+		mmr_leaves,
+		mmr_proof,
+		"verifying_parachain_headers_inclusion",
+	)
+}
+
+/// Takes the updated client state and relay chain headers update proof
+/// and verifies inclusion in mmr of the leaves appended by their children.
+pub fn verify_relay_chain_headers<H>(
+	trusted_client_state: ClientState,
+	RelayChainUpdateProof { mmr_proof, relay_chain_headers }: RelayChainUpdateProof,
+) -> Result<(), BeefyClientError>
+where
+	H: HostFunctions + Clone,
+{
+	let mut mmr_leaves = Vec::new();
+
+	for relay_chain_header in relay_chain_headers {
+		let decoded_header =
+			Header::<u32, BlakeTwo256>::decode(&mut &*relay_chain_header.relay_chain_header)?;
+
+		// just to be safe skip genesis block if it's included, it has no timestamp
+		if decoded_header.number == 0 {
+			Err(BeefyClientError::Custom(
+				"Genesis block found, it should not be included".to_string(),
+			))?
+		}
+
+		verify_timestamp_extrinsic::<H>(
+			&decoded_header,
+			&relay_chain_header.extrinsic_proof,
+			&relay_chain_header.timestamp_extrinsic,
+		)?;
+
+		// The child of this header appends the leaf committing to its parent's hash
+		let header_hash = <H::BlakeTwo256 as Hasher>::hash(&relay_chain_header.relay_chain_header);
+		if relay_chain_header.mmr_leaf.parent_number_and_hash !=
+			(decoded_header.number, header_hash)
+		{
+			Err(BeefyClientError::Custom(format!(
+				"Mmr leaf doesn't commit to relay chain header {}",
+				decoded_header.number
+			)))?
+		}
+
+		let node = relay_chain_header.mmr_leaf.using_encoded(|leaf| H::keccak_256(leaf));
+		let leaf_index = get_leaf_index_for_block_number(
+			trusted_client_state.beefy_activation_block,
+			decoded_header.number + 1,
+		);
+
+		let leaf_pos = mmr_lib::leaf_index_to_pos(leaf_index as u64);
+		mmr_leaves.push((leaf_pos, H256::from_slice(&node)));
+	}
+
+	verify_mmr_leaves::<H>(
+		&trusted_client_state,
+		mmr_leaves,
+		mmr_proof,
+		"verifying_relay_chain_headers_inclusion",
+	)
+}
+
+/// Verify timestamp extrinsic
+fn verify_timestamp_extrinsic<H>(
+	header: &Header<u32, BlakeTwo256>,
+	extrinsic_proof: &[Vec<u8>],
+	timestamp_extrinsic: &[u8],
+) -> Result<(), BeefyClientError>
+where
+	H: HostFunctions + Clone,
+{
+	// Timestamp extrinsic should be the first inherent and hence the first extrinsic
+	// https://github.com/paritytech/substrate/blob/d602397a0bbb24b5d627795b797259a44a5e29e9/primitives/trie/src/lib.rs#L99-L101
+	let timestamp_ext_key = codec::Compact(0u32).encode();
+	sp_trie::verify_trie_proof::<LayoutV0<H::BlakeTwo256>, _, _, _>(
+		&header.extrinsics_root,
+		extrinsic_proof,
+		&vec![(timestamp_ext_key, Some(timestamp_extrinsic))],
+	)
+	.map_err(|_| BeefyClientError::Custom(format!("Invalid extrinsic proof")))
+}
+
+/// Verify the mmr leaves against the trusted mmr root hash with the batch proof
+fn verify_mmr_leaves<H>(
+	trusted_client_state: &ClientState,
+	mmr_leaves: Vec<(u64, H256)>,
+	mmr_proof: BatchProof<H256>,
+	location: &'static str,
+) -> Result<(), BeefyClientError>
+where
+	H: HostFunctions + Clone,
+{
 	let mmr_size = NodesUtils::new(mmr_proof.leaf_count).size();
 	let proof = mmr_lib::MerkleProof::<_, MerkleHasher<H>>::new(mmr_size, mmr_proof.items);
 
@@ -256,7 +438,7 @@ where
 		return Err(BeefyClientError::InvalidMmrProof {
 			expected: trusted_client_state.mmr_root_hash,
 			found: root,
-			location: "verifying_parachain_headers_inclusion",
+			location,
 		})
 	}
 	Ok(())
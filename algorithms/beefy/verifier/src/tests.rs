@@ -14,8 +14,9 @@
 // limitations under the License.
 
 use beefy_light_client_primitives::{
-	error::BeefyClientError, MmrUpdateProof, ParachainsUpdateProof, SignatureWithAuthorityIndex,
-	SignedCommitment,
+	error::BeefyClientError, ClientState, MerkleHasher, MmrUpdateProof, ParachainsUpdateProof,
+	RelayChainHeader, RelayChainUpdateProof, SampledMmrUpdateProof, SampledSignedCommitment,
+	SignatureWithAuthorityIndex, SignedCommitment,
 };
 use beefy_primitives::{
 	known_payload_ids::MMR_ROOT_ID,
@@ -23,10 +24,17 @@ use beefy_primitives::{
 	Payload,
 };
 use beefy_prover::{Crypto, Prover};
+use codec::{Decode, Encode};
 use futures::stream::StreamExt;
+use mmr_lib::{util::MemStore, MMR};
 use pallet_mmr_primitives::Proof;
 use serde_json::Value;
-use sp_core::bytes::to_hex;
+use sp_core::{bytes::to_hex, H256};
+use sp_runtime::{
+	generic::Header,
+	traits::{BlakeTwo256, Header as _},
+};
+use sp_trie::{generate_trie_proof, LayoutV0, TrieDBMut, TrieMut};
 use subxt::{
 	rpc::{rpc_params, Subscription},
 	PolkadotConfig,
@@ -201,6 +209,191 @@ async fn should_fail_with_invalid_validator_set_id() {
 	}
 }
 
+fn sampled_mmr_update(signers: u8, signatures: Vec<u32>) -> SampledMmrUpdateProof {
+	SampledMmrUpdateProof {
+		signed_commitment: SampledSignedCommitment {
+			commitment: beefy_primitives::Commitment {
+				payload: Payload::new(MMR_ROOT_ID, vec![0u8; 32]),
+				block_number: Default::default(),
+				validator_set_id: 0,
+			},
+			signers: vec![signers],
+			signatures: signatures
+				.into_iter()
+				.map(|index| SignatureWithAuthorityIndex { index, signature: [0u8; 65] })
+				.collect(),
+		},
+		latest_mmr_leaf: MmrLeaf {
+			version: Default::default(),
+			parent_number_and_hash: (Default::default(), Default::default()),
+			beefy_next_authority_set: BeefyNextAuthoritySet {
+				id: 0,
+				len: 0,
+				root: Default::default(),
+			},
+			leaf_extra: Default::default(),
+		},
+		mmr_proof: Proof { leaf_index: 0, leaf_count: 0, items: vec![] },
+		authority_proof: vec![],
+	}
+}
+
+#[tokio::test]
+async fn sampled_update_should_fail_with_incomplete_signature_threshold() {
+	// only two of the five authorities claim to have signed
+	let res = crate::verify_mmr_root_with_sampled_proof::<Crypto>(
+		Prover::<PolkadotConfig>::get_initial_client_state(None).await,
+		sampled_mmr_update(0b00011, vec![0, 1]),
+		2,
+	);
+
+	match res {
+		Err(BeefyClientError::IncompleteSignatureThreshold) => {},
+		Err(err) =>
+			panic!("Expected {:?}  found {:?}", BeefyClientError::IncompleteSignatureThreshold, err),
+		Ok(val) =>
+			panic!("Expected {:?}  found {:?}", BeefyClientError::IncompleteSignatureThreshold, val),
+	}
+}
+
+#[tokio::test]
+async fn sampled_update_should_fail_with_too_small_signature_sample() {
+	// a single faulty authority out of five could have signed any one sampled signature
+	let res = crate::verify_mmr_root_with_sampled_proof::<Crypto>(
+		Prover::<PolkadotConfig>::get_initial_client_state(None).await,
+		sampled_mmr_update(0b11111, vec![0]),
+		1,
+	);
+
+	match res {
+		Err(BeefyClientError::SignatureSampleTooSmall { sample_size: 1, min_sample_size: 2 }) => {},
+		Err(err) => panic!("Expected SignatureSampleTooSmall found {:?}", err),
+		Ok(val) => panic!("Expected SignatureSampleTooSmall found {:?}", val),
+	}
+}
+
+#[tokio::test]
+async fn sampled_update_should_fail_with_invalid_signature_sample() {
+	// all the signatures are supplied instead of the two sampled ones
+	let res = crate::verify_mmr_root_with_sampled_proof::<Crypto>(
+		Prover::<PolkadotConfig>::get_initial_client_state(None).await,
+		sampled_mmr_update(0b11111, vec![0, 1, 2, 3, 4]),
+		2,
+	);
+
+	match res {
+		Err(BeefyClientError::InvalidSignatureSample) => {},
+		Err(err) =>
+			panic!("Expected {:?}  found {:?}", BeefyClientError::InvalidSignatureSample, err),
+		Ok(val) =>
+			panic!("Expected {:?}  found {:?}", BeefyClientError::InvalidSignatureSample, val),
+	}
+}
+
+/// Builds a relay chain header at `number` with a timestamp extrinsic proof, the mmr leaf
+/// appended by its child and an mmr of `leaf_count` leaves containing that leaf.
+fn relay_chain_header_with_mmr(
+	number: u32,
+	leaf_count: u32,
+) -> (RelayChainHeader, ClientState, pallet_mmr_primitives::BatchProof<H256>) {
+	let timestamp_extrinsic = vec![4u8; 12];
+	let key = codec::Compact(0u32).encode();
+	let mut db = sp_trie::MemoryDB::<BlakeTwo256>::default();
+	let extrinsics_root = {
+		let mut root = Default::default();
+		let mut trie = <TrieDBMut<LayoutV0<BlakeTwo256>>>::new(&mut db, &mut root);
+		trie.insert(&key, &timestamp_extrinsic).unwrap();
+		*trie.root()
+	};
+	let extrinsic_proof =
+		generate_trie_proof::<LayoutV0<BlakeTwo256>, _, _, _>(&db, extrinsics_root, vec![&key])
+			.unwrap();
+
+	let header = Header::<u32, BlakeTwo256>::new(
+		number,
+		extrinsics_root,
+		H256::repeat_byte(1),
+		H256::repeat_byte(2),
+		Default::default(),
+	);
+	let mmr_leaf = MmrLeaf {
+		version: Default::default(),
+		parent_number_and_hash: (number, header.hash()),
+		beefy_next_authority_set: BeefyNextAuthoritySet { id: 0, len: 0, root: Default::default() },
+		leaf_extra: Default::default(),
+	};
+
+	let store = MemStore::default();
+	let mut mmr = MMR::<_, MerkleHasher<Crypto>, _>::new(0, &store);
+	let mut leaf_pos = 0;
+	for index in 0..leaf_count {
+		// the leaf of block `n` is at index `n - 1` with an activation block of zero
+		let node = if index == number {
+			mmr_leaf.using_encoded(|leaf| H256::from(sp_core::keccak_256(leaf)))
+		} else {
+			H256::repeat_byte(index as u8)
+		};
+		let pos = mmr.push(node).unwrap();
+		if index == number {
+			leaf_pos = pos;
+		}
+	}
+	let mmr_root_hash = mmr.get_root().unwrap();
+	let proof = mmr.gen_proof(vec![leaf_pos]).unwrap();
+
+	let client_state = ClientState {
+		latest_beefy_height: leaf_count,
+		mmr_root_hash,
+		current_authorities: BeefyNextAuthoritySet { id: 0, len: 5, root: Default::default() },
+		next_authorities: BeefyNextAuthoritySet { id: 1, len: 5, root: Default::default() },
+		beefy_activation_block: 0,
+	};
+	let batch_proof = pallet_mmr_primitives::BatchProof {
+		leaf_indices: vec![number as u64],
+		leaf_count: leaf_count as u64,
+		items: proof.proof_items().to_vec(),
+	};
+	let header = RelayChainHeader {
+		relay_chain_header: header.encode(),
+		mmr_leaf,
+		extrinsic_proof,
+		timestamp_extrinsic,
+	};
+
+	(header, client_state, batch_proof)
+}
+
+#[test]
+fn should_verify_relay_chain_headers() {
+	let (header, client_state, mmr_proof) = relay_chain_header_with_mmr(5, 8);
+
+	crate::verify_relay_chain_headers::<Crypto>(
+		client_state,
+		RelayChainUpdateProof { relay_chain_headers: vec![header], mmr_proof },
+	)
+	.unwrap();
+}
+
+#[test]
+fn should_fail_with_relay_chain_header_not_in_mmr_leaf() {
+	let (mut header, client_state, mmr_proof) = relay_chain_header_with_mmr(5, 8);
+	// a header with another state root doesn't hash to the leaf's parent hash
+	let mut decoded = Header::<u32, BlakeTwo256>::decode(&mut &*header.relay_chain_header).unwrap();
+	decoded.state_root = H256::repeat_byte(3);
+	header.relay_chain_header = decoded.encode();
+
+	let res = crate::verify_relay_chain_headers::<Crypto>(
+		client_state,
+		RelayChainUpdateProof { relay_chain_headers: vec![header], mmr_proof },
+	);
+
+	match res {
+		Err(BeefyClientError::Custom(_)) => {},
+		Err(err) => panic!("Expected BeefyClientError::Custom found {:?}", err),
+		Ok(val) => panic!("Expected BeefyClientError::Custom found {:?}", val),
+	}
+}
+
 #[tokio::test]
 #[ignore]
 async fn verify_parachain_headers() {
@@ -19,6 +19,7 @@ use anyhow::anyhow;
 use codec::{Decode, Encode};
 use core::fmt::Debug;
 use finality_grandpa::voter_set::VoterSet;
+use sp_core::ed25519;
 use sp_finality_grandpa::{
 	AuthorityId, AuthorityList, AuthoritySignature, ConsensusLog, Equivocation, RoundNumber,
 	ScheduledChange, SetId, GRANDPA_ENGINE_ID,
@@ -110,17 +111,43 @@ where
 				 qed.",
 			);
 
+		// Signatures are only checked for the precommits needed to reach the supermajority
+		// threshold, any surplus precommits are not required to prove finality of the target.
+		let threshold = voters.threshold().get();
+		let mut signed_weight = 0u64;
+		let mut signatures = Vec::new();
+		let mut seen_voters = BTreeSet::new();
 		let mut visited_hashes = BTreeSet::new();
 		for signed in self.commit.precommits.iter() {
-			let message = finality_grandpa::Message::Precommit(signed.precommit.clone());
+			// reject duplicate precommits or equivocations from the same voter.
+			if !seen_voters.insert(signed.id.clone()) {
+				Err(anyhow!(
+					"duplicate precommit from voter {:?} in grandpa justification",
+					signed.id
+				))?
+			}
 
-			check_message_signature::<Host, _, _>(
-				&message,
-				&signed.id,
-				&signed.signature,
-				self.round,
-				set_id,
-			)?;
+			let weight = voters
+				.get(&signed.id)
+				.ok_or_else(|| anyhow!("precommit from unknown voter {:?}", signed.id))?
+				.weight()
+				.get();
+
+			// only precommits for the commit target (or its descendants) count towards the
+			// threshold.
+			if signed_weight < threshold &&
+				ancestry_chain
+					.ancestry(self.commit.target_hash, signed.precommit.target_hash)
+					.is_ok()
+			{
+				let message = finality_grandpa::Message::Precommit(signed.precommit.clone());
+				signatures.push((
+					AsRef::<ed25519::Signature>::as_ref(&signed.signature).clone(),
+					(message, self.round, set_id).encode(),
+					AsRef::<ed25519::Public>::as_ref(&signed.id).clone(),
+				));
+				signed_weight = signed_weight.saturating_add(weight);
+			}
 
 			if base_hash == signed.precommit.target_hash {
 				continue
@@ -137,6 +164,16 @@ where
 			}
 		}
 
+		if signed_weight < threshold {
+			Err(anyhow!(
+				"insufficient precommit weight in grandpa justification: {signed_weight} < {threshold}"
+			))?
+		}
+
+		if !Host::ed25519_batch_verify(&signatures) {
+			Err(anyhow!("invalid signature for precommit in grandpa justification"))?
+		}
+
 		let ancestry_hashes: BTreeSet<_> =
 			self.votes_ancestries.iter().map(|h: &H| h.hash()).collect();
 
@@ -233,6 +270,42 @@ pub fn find_forced_change<H: HeaderT>(
 	header.digest().convert_first(|l| l.try_to(id).and_then(filter_log))
 }
 
+/// Finds the forced authority set change signalled by the headers on the route from `base`,
+/// excluded, to `block` in `ancestry`. Fails if the route signals more than one forced change or
+/// if `block` doesn't enact it yet.
+pub fn find_enacted_forced_change<H: HeaderT>(
+	ancestry: &AncestryChain<H>,
+	base: H::Hash,
+	block: H::Hash,
+) -> Result<Option<ScheduledChange<H::Number>>, anyhow::Error>
+where
+	H::Number: finality_grandpa::BlockNumberOps,
+{
+	use finality_grandpa::Chain;
+	use sp_runtime::traits::Saturating;
+
+	let target = ancestry.header(&block).ok_or_else(|| anyhow!("Block not found in ancestry"))?;
+	let route = ancestry.ancestry(base, block).map_err(|_| anyhow!("Invalid ancestry!"))?;
+	let mut forced_changes = route.iter().filter(|hash| **hash != base).filter_map(|hash| {
+		let header = ancestry.header(hash)?;
+		let (_, change) = find_forced_change(header)?;
+		Some((*header.number(), change))
+	});
+
+	let (number, change) = match forced_changes.next() {
+		Some(forced_change) => forced_change,
+		None => return Ok(None),
+	};
+	if forced_changes.next().is_some() {
+		Err(anyhow!("Headers can't signal more than one forced authority set change"))?
+	}
+	if number.saturating_add(change.delay) > *target.number() {
+		Err(anyhow!("Forced authority set change signalled at {number:?} isn't enacted yet"))?
+	}
+
+	Ok(Some(change))
+}
+
 /// Check a message signature by encoding the message and verifying the provided signature using the
 /// expected authority id.
 pub fn check_message_signature<Host, H, N>(
@@ -309,12 +382,183 @@ where
 	}
 }
 
+/// Precommits of an authority in a round along with their signatures.
+type RoundPrecommits<H> = BTreeMap<
+	AuthorityId,
+	(finality_grandpa::Precommit<<H as HeaderT>::Hash, <H as HeaderT>::Number>, AuthoritySignature),
+>;
+
+/// Keeps the precommits of observed GRANDPA justifications by authority set and round, to detect
+/// authorities precommitting to different blocks in the same round.
+pub struct EquivocationDetector<H: HeaderT> {
+	precommits: BTreeMap<(SetId, RoundNumber), RoundPrecommits<H>>,
+}
+
+impl<H: HeaderT> Default for EquivocationDetector<H> {
+	fn default() -> Self {
+		Self { precommits: BTreeMap::new() }
+	}
+}
+
+impl<H: HeaderT> EquivocationDetector<H> {
+	/// Records the precommits of a justification by the given authority set, returning the
+	/// equivocations of authorities that had already precommitted to another block in the same
+	/// round.
+	pub fn observe(
+		&mut self,
+		set_id: SetId,
+		justification: &GrandpaJustification<H>,
+	) -> Vec<Equivocation<H::Hash, H::Number>> {
+		let round = self.precommits.entry((set_id, justification.round)).or_default();
+		let mut equivocations = vec![];
+
+		for signed in &justification.commit.precommits {
+			match round.get(&signed.id) {
+				Some((precommit, signature)) => {
+					if precommit.target_hash == signed.precommit.target_hash &&
+						precommit.target_number == signed.precommit.target_number
+					{
+						continue
+					}
+					let equivocation = finality_grandpa::Equivocation {
+						round_number: justification.round,
+						identity: signed.id.clone(),
+						first: (precommit.clone(), signature.clone()),
+						second: (signed.precommit.clone(), signed.signature.clone()),
+					};
+					equivocations.push(Equivocation::Precommit(equivocation));
+				},
+				None => {
+					round.insert(
+						signed.id.clone(),
+						(signed.precommit.clone(), signed.signature.clone()),
+					);
+				},
+			}
+		}
+
+		equivocations
+	}
+
+	/// Forgets the rounds of the authority sets before `set_id`, their equivocations can't be
+	/// reported anymore.
+	pub fn prune(&mut self, set_id: SetId) {
+		self.precommits = self.precommits.split_off(&(set_id, 0));
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use finality_grandpa::Chain;
+	use finality_grandpa::{Chain, Precommit, SignedPrecommit};
+	use sp_core::{ed25519, Pair, H256};
 	use sp_runtime::{generic::Header, traits::BlakeTwo256};
 
+	type TestHeader = Header<u32, BlakeTwo256>;
+
+	#[derive(Clone, Debug, Default, PartialEq, Eq)]
+	struct TestHost;
+
+	impl light_client_common::HostFunctions for TestHost {
+		type BlakeTwo256 = BlakeTwo256;
+	}
+
+	impl HostFunctions for TestHost {
+		type Header = TestHeader;
+
+		fn ed25519_verify(sig: &ed25519::Signature, msg: &[u8], pub_key: &ed25519::Public) -> bool {
+			ed25519::Pair::verify(sig, msg, pub_key)
+		}
+
+		fn insert_relay_header_hashes(_headers: &[H256]) {
+			unimplemented!()
+		}
+
+		fn contains_relay_header_hash(_hash: H256) -> bool {
+			unimplemented!()
+		}
+	}
+
+	/// Builds a justification for the last of three headers, signed by every one of the given
+	/// voters.
+	fn justification_signed_by(
+		pairs: &[ed25519::Pair],
+		round: u64,
+		set_id: u64,
+	) -> (GrandpaJustification<TestHeader>, AuthorityList) {
+		let mut parent_hash = Default::default();
+		let mut headers = vec![];
+		for number in 1..=3 {
+			let header = TestHeader::new(
+				number,
+				Default::default(),
+				Default::default(),
+				parent_hash,
+				Default::default(),
+			);
+			parent_hash = header.hash();
+			headers.push(header);
+		}
+		let target = headers.last().unwrap();
+		let precommit = Precommit { target_hash: target.hash(), target_number: target.number };
+		let message = finality_grandpa::Message::Precommit(precommit.clone());
+		let precommits = pairs
+			.iter()
+			.map(|pair| SignedPrecommit {
+				precommit: precommit.clone(),
+				signature: pair.sign(&(&message, round, set_id).encode()).into(),
+				id: pair.public().into(),
+			})
+			.collect();
+		let justification = GrandpaJustification {
+			round,
+			commit: Commit::<TestHeader> {
+				target_hash: target.hash(),
+				target_number: target.number,
+				precommits,
+			},
+			votes_ancestries: vec![],
+		};
+		let authorities = pairs.iter().map(|pair| (pair.public().into(), 1)).collect();
+
+		(justification, authorities)
+	}
+
+	fn voters(count: u8) -> Vec<ed25519::Pair> {
+		(0..count).map(|i| ed25519::Pair::from_seed(&[i; 32])).collect()
+	}
+
+	#[test]
+	fn test_justification_verifies() {
+		let (justification, authorities) = justification_signed_by(&voters(4), 1, 1);
+		justification.verify::<TestHost>(1, &authorities).unwrap();
+		// signatures are bound to the authority set id.
+		assert!(justification.verify::<TestHost>(2, &authorities).is_err());
+	}
+
+	#[test]
+	fn test_justification_rejects_duplicate_voters() {
+		let (mut justification, authorities) = justification_signed_by(&voters(4), 1, 1);
+		let duplicate = justification.commit.precommits[0].clone();
+		justification.commit.precommits[3] = duplicate;
+		assert!(justification.verify::<TestHost>(1, &authorities).is_err());
+	}
+
+	#[test]
+	fn test_justification_signatures_checked_up_to_threshold() {
+		let pairs = voters(4);
+		let (mut justification, authorities) = justification_signed_by(&pairs, 1, 1);
+
+		// an invalid signature within the threshold is rejected.
+		let mut invalid = justification.clone();
+		invalid.commit.precommits[0].signature = pairs[0].sign(b"invalid").into();
+		assert!(invalid.verify::<TestHost>(1, &authorities).is_err());
+
+		// the threshold for 4 voters is 3, so the last precommit doesn't need to be checked.
+		justification.commit.precommits[3].signature = pairs[3].sign(b"invalid").into();
+		justification.verify::<TestHost>(1, &authorities).unwrap();
+	}
+
 	#[test]
 	fn test_ancestry_route() {
 		let mut headers: Vec<Header<u32, BlakeTwo256>> = vec![];
@@ -342,4 +586,36 @@ mod tests {
 
 		assert_eq!(route, expected);
 	}
+
+	#[test]
+	fn test_equivocation_detector_reports_conflicting_precommits() {
+		let pairs = voters(4);
+		let (justification, _) = justification_signed_by(&pairs, 1, 1);
+		let mut detector = EquivocationDetector::default();
+		assert!(detector.observe(1, &justification).is_empty());
+		// the same justification again isn't an equivocation.
+		assert!(detector.observe(1, &justification).is_empty());
+
+		// the first voter precommits to another block in the same round.
+		let mut conflicting = justification.clone();
+		let precommit = Precommit { target_hash: H256::repeat_byte(1), target_number: 3 };
+		let message = finality_grandpa::Message::Precommit(precommit.clone());
+		conflicting.commit.precommits[0] = SignedPrecommit {
+			precommit,
+			signature: pairs[0].sign(&(&message, 1u64, 1u64).encode()).into(),
+			id: pairs[0].public().into(),
+		};
+
+		// the conflicting precommit in another set or round isn't an equivocation.
+		assert!(detector.observe(2, &conflicting).is_empty());
+
+		let mut equivocations = detector.observe(1, &conflicting);
+		assert_eq!(equivocations.len(), 1);
+		let equivocation = equivocations.remove(0);
+		assert_eq!(equivocation.offender(), &AuthorityId::from(pairs[0].public()));
+		check_equivocation_proof::<TestHost, _, _>(1, equivocation).unwrap();
+
+		detector.prune(2);
+		assert!(detector.observe(1, &conflicting).is_empty());
+	}
 }
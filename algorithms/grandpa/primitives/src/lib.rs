@@ -94,6 +94,10 @@ pub struct ParachainHeadersWithFinalityProof<H: Header> {
 	/// finalzed at the relay chain height. We check for this parachain header finalization
 	/// via state proofs. Also contains extrinsic proof for timestamp.
 	pub parachain_headers: BTreeMap<H::Hash, ParachainHeaderProofs>,
+	/// Finality proofs for the blocks enacting the authority set changes between the latest
+	/// finalized block known to the light client and the block finalized by `finality_proof`,
+	/// ordered by block number. Each of them is justified by the authority set it hands over from.
+	pub authority_set_changes: Vec<FinalityProof<H>>,
 }
 
 /// Host functions that allow the light client perform cryptographic operations in native.
@@ -103,6 +107,12 @@ pub trait HostFunctions: light_client_common::HostFunctions + 'static {
 
 	/// Verify an ed25519 signature
 	fn ed25519_verify(sig: &ed25519::Signature, msg: &[u8], pub_key: &ed25519::Public) -> bool;
+	/// Verify a batch of ed25519 signatures, returns `true` only if all of them are valid.
+	/// Hosts with native support for batch verification should override this, the default
+	/// implementation verifies each signature individually.
+	fn ed25519_batch_verify(batch: &[(ed25519::Signature, Vec<u8>, ed25519::Public)]) -> bool {
+		batch.iter().all(|(sig, msg, pub_key)| Self::ed25519_verify(sig, msg, pub_key))
+	}
 	/// Stores the given list of RelayChain header hashes in the light client's storage.
 	fn insert_relay_header_hashes(headers: &[<Self::Header as Header>::Hash]);
 	/// Checks if a RelayChain header hash exists in the light client's storage.
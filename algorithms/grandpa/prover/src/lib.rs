@@ -27,11 +27,13 @@ use codec::{Decode, Encode};
 use finality_grandpa_rpc::GrandpaApiClient;
 use jsonrpsee::{async_client::Client, ws_client::WsClientBuilder};
 use primitives::{
-	justification::GrandpaJustification, parachain_header_storage_key, ClientState, FinalityProof,
-	ParachainHeaderProofs, ParachainHeadersWithFinalityProof,
+	justification::{find_forced_change, find_scheduled_change, GrandpaJustification},
+	parachain_header_storage_key, ClientState, FinalityProof, ParachainHeaderProofs,
+	ParachainHeadersWithFinalityProof,
 };
 use serde::{Deserialize, Serialize};
 use sp_core::H256;
+use sp_finality_grandpa::ScheduledChange;
 use sp_runtime::traits::{Header, Zero};
 use std::{
 	collections::{BTreeMap, BTreeSet},
@@ -61,6 +63,9 @@ pub struct GrandpaProver<T: Config> {
 	pub para_ws_client: Arc<Client>,
 	/// ParaId of the associated parachain
 	pub para_id: u32,
+	/// Whether the chain is a standalone chain running its own GRANDPA, the relay and parachain
+	/// clients then both point at it and its finalized headers are proven directly.
+	pub standalone: bool,
 }
 
 /// An encoded justification proving that the given header has been finalized
@@ -84,7 +89,29 @@ where
 		let para_ws_client = Arc::new(WsClientBuilder::default().build(para_ws_url).await?);
 		let para_client = OnlineClient::<T>::from_rpc_client(para_ws_client.clone()).await?;
 
-		Ok(Self { relay_ws_client, relay_client, para_ws_client, para_client, para_id })
+		Ok(Self {
+			relay_ws_client,
+			relay_client,
+			para_ws_client,
+			para_client,
+			para_id,
+			standalone: false,
+		})
+	}
+
+	/// Initializes the clients for a standalone chain given its ws url.
+	pub async fn new_standalone(ws_url: &str) -> Result<Self, anyhow::Error> {
+		let ws_client = Arc::new(WsClientBuilder::default().build(ws_url).await?);
+		let client = OnlineClient::<T>::from_rpc_client(ws_client.clone()).await?;
+
+		Ok(Self {
+			relay_ws_client: ws_client.clone(),
+			relay_client: client.clone(),
+			para_ws_client: ws_client,
+			para_client: client,
+			para_id: 0,
+			standalone: true,
+		})
 	}
 
 	/// Construct the inital client state.
@@ -132,8 +159,13 @@ where
 			.await?
 			.ok_or_else(|| anyhow!("Header not found for hash: {latest_relay_hash:?}"))?;
 		let latest_relay_height = u32::from(*header.number());
-		let finalized_para_header =
-			self.query_latest_finalized_parachain_header(latest_relay_height).await?;
+		let latest_para_height = if self.standalone {
+			latest_relay_height
+		} else {
+			let finalized_para_header =
+				self.query_latest_finalized_parachain_header(latest_relay_height).await?;
+			u32::from(*finalized_para_header.number())
+		};
 
 		Ok(ClientState {
 			current_authorities,
@@ -141,8 +173,7 @@ where
 			latest_relay_height,
 			latest_relay_hash,
 			para_id: self.para_id,
-			// we'll set this below
-			latest_para_height: u32::from(*finalized_para_header.number()),
+			latest_para_height,
 		})
 	}
 
@@ -151,6 +182,9 @@ where
 		&self,
 		latest_finalized_height: u32,
 	) -> Result<T::Header, anyhow::Error> {
+		if self.standalone {
+			return self.header_at(latest_finalized_height).await
+		}
 		let latest_finalized_hash = self
 			.relay_client
 			.rpc()
@@ -186,6 +220,21 @@ where
 		H::Number: finality_grandpa::BlockNumberOps,
 		T::BlockNumber: One,
 	{
+		if self.standalone {
+			return self
+				.query_finalized_standalone_headers_with_proof(
+					client_state,
+					latest_finalized_height,
+					header_numbers,
+				)
+				.await
+		}
+		// prove every authority set change we've missed since we last updated the relay chain on
+		// the light client, so that a single update can hand over all of them.
+		let (authority_set_changes, handed_over_client_state) = self
+			.query_authority_set_changes::<H>(client_state, latest_finalized_height)
+			.await?;
+
 		let previous_para_hash = self
 			.para_client
 			.rpc()
@@ -204,11 +253,12 @@ where
 		let previous_finalized_height =
 			validation_data.relay_parent_number.min(client_state.latest_relay_height);
 
-		// we want to know if we've missed any authority set changes since we last updated the
-		// relay chain on the light client.
-		let session_end = self.session_end_for_block(client_state.latest_relay_height).await?;
+		// the handed over authority set can't justify blocks past the end of its session.
+		let session_end =
+			self.session_end_for_block(handed_over_client_state.latest_relay_height).await?;
 
-		if client_state.latest_relay_height != session_end && latest_finalized_height > session_end
+		if handed_over_client_state.latest_relay_height != session_end &&
+			latest_finalized_height > session_end
 		{
 			latest_finalized_height = session_end
 		}
@@ -328,9 +378,226 @@ where
 		Ok(ParachainHeadersWithFinalityProof {
 			finality_proof,
 			parachain_headers: parachain_headers_with_proof,
+			authority_set_changes,
+		})
+	}
+
+	/// Returns the finality proof for the given header numbers of a standalone chain, with proofs
+	/// of their timestamp extrinsics and no state proofs, since the headers are finalized
+	/// themselves.
+	async fn query_finalized_standalone_headers_with_proof<H>(
+		&self,
+		client_state: &ClientState<T::Hash>,
+		latest_finalized_height: u32,
+		header_numbers: Vec<T::BlockNumber>,
+	) -> Result<ParachainHeadersWithFinalityProof<H>, anyhow::Error>
+	where
+		H: Header,
+		u32: From<H::Number>,
+		H::Hash: From<T::Hash>,
+		H::Number: finality_grandpa::BlockNumberOps,
+	{
+		let (authority_set_changes, handed_over_client_state) = self
+			.query_authority_set_changes::<H>(client_state, latest_finalized_height)
+			.await?;
+
+		// Justifications for the last block of every authority set are kept, so asking for the
+		// block after the latest known one yields the end of the client's authority set if it has
+		// changed since, otherwise the latest justification.
+		let next_height = handed_over_client_state.latest_relay_height + 1;
+		let encoded = GrandpaApiClient::<JustificationNotification, H256, u32>::prove_finality(
+			// we cast between the same type but different crate versions.
+			&*unsafe {
+				unsafe_arc_cast::<_, jsonrpsee_ws_client::WsClient>(self.relay_ws_client.clone())
+			},
+			next_height,
+		)
+		.await?
+		.ok_or_else(|| anyhow!("No justification found for block: {next_height}"))?
+		.0;
+		let mut finality_proof = FinalityProof::<H>::decode(&mut &encoded[..])?;
+		let justification =
+			GrandpaJustification::<H>::decode(&mut &finality_proof.justification[..])?;
+		let latest_finalized_height = u32::from(justification.commit.target_number);
+		finality_proof.block = justification.commit.target_hash;
+
+		let mut unknown_headers = vec![];
+		for height in client_state.latest_relay_height..=latest_finalized_height {
+			let header = self.header_at(height).await?;
+			unknown_headers.push(H::decode(&mut &header.encode()[..])?);
+		}
+		finality_proof.unknown_headers = unknown_headers;
+
+		let mut headers_with_proof = BTreeMap::<H::Hash, ParachainHeaderProofs>::default();
+		for number in header_numbers {
+			let number = u32::from(number);
+			if number <= client_state.latest_para_height || number > latest_finalized_height {
+				continue
+			}
+			let hash = self.header_at(number).await?.hash();
+			let TimeStampExtWithProof { ext: extrinsic, proof: extrinsic_proof } =
+				fetch_timestamp_extrinsic_with_proof(&self.para_client, Some(hash))
+					.await
+					.map_err(|err| anyhow!("Error fetching timestamp with proof: {err:?}"))?;
+			let proofs = ParachainHeaderProofs { state_proof: vec![], extrinsic, extrinsic_proof };
+			headers_with_proof.insert(hash.into(), proofs);
+		}
+
+		Ok(ParachainHeadersWithFinalityProof {
+			finality_proof,
+			parachain_headers: headers_with_proof,
+			authority_set_changes,
 		})
 	}
 
+	/// Returns the finality proofs for the blocks enacting every authority set change between the
+	/// latest relay chain height of the client state and `latest_finalized_height`, along with the
+	/// client state after handing over to the last of these authority sets.
+	async fn query_authority_set_changes<H>(
+		&self,
+		client_state: &ClientState<T::Hash>,
+		latest_finalized_height: u32,
+	) -> Result<(Vec<FinalityProof<H>>, ClientState<T::Hash>), anyhow::Error>
+	where
+		H: Header,
+		u32: From<H::Number>,
+		H::Number: finality_grandpa::BlockNumberOps,
+	{
+		let mut client_state = client_state.clone();
+		let mut authority_set_changes = vec![];
+
+		loop {
+			let next_height = client_state.latest_relay_height + 1;
+			if next_height >= latest_finalized_height {
+				break
+			}
+			// Justifications for the last block of every authority set are kept, so this yields the
+			// block enacting the next authority set change if there's one before
+			// `latest_finalized_height`.
+			let mut finality_proof = match self.prove_finality::<H>(next_height).await? {
+				Some(finality_proof) => finality_proof,
+				None => break,
+			};
+			let mut target_height = finality_proof_target::<H>(&finality_proof)?;
+			if target_height >= latest_finalized_height {
+				break
+			}
+
+			let mut unknown_headers =
+				self.headers_in(client_state.latest_relay_height, target_height).await?;
+			let mut forced_change =
+				find_forced_change_after(&unknown_headers, client_state.latest_relay_height);
+			if forced_change.is_none() &&
+				unknown_headers.last().and_then(|header| find_scheduled_change(header)).is_none()
+			{
+				// A forced change ends the authority set at the block the set was stalled at, the
+				// forced set justifies the blocks after it.
+				let mut signalled = None;
+				for height in target_height + 1..=latest_finalized_height {
+					let header = H::decode(&mut &self.header_at(height).await?.encode()[..])?;
+					if let Some((_, change)) = find_forced_change(&header) {
+						signalled = Some(height + u32::from(change.delay));
+						break
+					}
+				}
+				// otherwise the justification is for the latest finalized block of the current set.
+				let enacted_height = match signalled {
+					Some(enacted_height) => enacted_height,
+					None => break,
+				};
+				finality_proof = self
+					.prove_finality::<H>(enacted_height)
+					.await?
+					.ok_or_else(|| {
+						anyhow!("No justification found for forced authority set change enacted at {enacted_height}")
+					})?;
+				target_height = finality_proof_target::<H>(&finality_proof)?;
+				if target_height < enacted_height {
+					Err(anyhow!(
+						"Justification for block {target_height} doesn't enact the forced authority set change at {enacted_height}"
+					))?
+				}
+				// the update's own finality proof hands over to the forced set.
+				if target_height >= latest_finalized_height {
+					break
+				}
+				unknown_headers =
+					self.headers_in(client_state.latest_relay_height, target_height).await?;
+				forced_change =
+					find_forced_change_after(&unknown_headers, client_state.latest_relay_height);
+			}
+			let scheduled_change =
+				unknown_headers.last().and_then(|header| find_scheduled_change(header));
+
+			finality_proof.unknown_headers = unknown_headers;
+			authority_set_changes.push(finality_proof);
+
+			client_state.latest_relay_hash = self.header_at(target_height).await?.hash();
+			client_state.latest_relay_height = target_height;
+			if let Some(forced_change) = forced_change {
+				client_state.current_set_id += 1;
+				client_state.current_authorities = forced_change.next_authorities;
+			}
+			if let Some(scheduled_change) = scheduled_change {
+				client_state.current_set_id += 1;
+				client_state.current_authorities = scheduled_change.next_authorities;
+			}
+		}
+
+		Ok((authority_set_changes, client_state))
+	}
+
+	// Fetches the finality proof for the given height, with the target set to the block finalized
+	// by its justification.
+	async fn prove_finality<H>(&self, height: u32) -> Result<Option<FinalityProof<H>>, anyhow::Error>
+	where
+		H: Header,
+	{
+		let encoded = match GrandpaApiClient::<JustificationNotification, H256, u32>::prove_finality(
+			// we cast between the same type but different crate versions.
+			&*unsafe {
+				unsafe_arc_cast::<_, jsonrpsee_ws_client::WsClient>(self.relay_ws_client.clone())
+			},
+			height,
+		)
+		.await?
+		{
+			Some(encoded) => encoded.0,
+			None => return Ok(None),
+		};
+		let mut finality_proof = FinalityProof::<H>::decode(&mut &encoded[..])?;
+		let justification =
+			GrandpaJustification::<H>::decode(&mut &finality_proof.justification[..])?;
+		finality_proof.block = justification.commit.target_hash;
+		Ok(Some(finality_proof))
+	}
+
+	// Fetches the relay chain headers from `from` to `to`, both included.
+	async fn headers_in<H: Header>(&self, from: u32, to: u32) -> Result<Vec<H>, anyhow::Error> {
+		let mut headers = vec![];
+		for height in from..=to {
+			headers.push(H::decode(&mut &self.header_at(height).await?.encode()[..])?);
+		}
+		Ok(headers)
+	}
+
+	// Fetches the relay chain header at the given height.
+	async fn header_at(&self, height: u32) -> Result<T::Header, anyhow::Error> {
+		let hash = self
+			.relay_client
+			.rpc()
+			.block_hash(Some(height.into()))
+			.await?
+			.ok_or_else(|| anyhow!("Failed to fetch block hash for height {height}"))?;
+		let header = self
+			.relay_client
+			.rpc()
+			.header(Some(hash))
+			.await?
+			.ok_or_else(|| anyhow!("Header with hash: {hash:?} not found!"))?;
+		Ok(header)
+	}
+
 	// Queries the block at which the epoch for the given block belongs to ends.
 	async fn session_end_for_block(&self, block: u32) -> Result<u32, anyhow::Error> {
 		let epoch_addr = polkadot::api::storage().babe().epoch_start();
@@ -351,3 +618,29 @@ where
 		Ok(Decode::decode(&mut &metadata.value[..])?)
 	}
 }
+
+/// Block number of the target of the justification of a finality proof.
+fn finality_proof_target<H>(finality_proof: &FinalityProof<H>) -> Result<u32, anyhow::Error>
+where
+	H: Header,
+	u32: From<H::Number>,
+{
+	let justification =
+		GrandpaJustification::<H>::decode(&mut &finality_proof.justification[..])?;
+	Ok(u32::from(justification.commit.target_number))
+}
+
+/// Finds the forced authority set change signalled by the headers above `latest_relay_height`.
+fn find_forced_change_after<H>(
+	headers: &[H],
+	latest_relay_height: u32,
+) -> Option<ScheduledChange<H::Number>>
+where
+	H: Header,
+	u32: From<H::Number>,
+{
+	headers
+		.iter()
+		.filter(|header| u32::from(*header.number()) > latest_relay_height)
+		.find_map(|header| find_forced_change(header).map(|(_, change)| change))
+}
@@ -21,7 +21,7 @@
 
 extern crate alloc;
 
-use alloc::vec;
+use alloc::{vec, vec::Vec};
 use anyhow::anyhow;
 use codec::{Decode, Encode};
 use finality_grandpa::Chain;
@@ -30,10 +30,13 @@ use light_client_common::state_machine;
 use primitive_types::H256;
 use primitives::{
 	error,
-	justification::{find_scheduled_change, AncestryChain, GrandpaJustification},
-	parachain_header_storage_key, ClientState, HostFunctions, ParachainHeaderProofs,
+	justification::{
+		find_enacted_forced_change, find_scheduled_change, AncestryChain, GrandpaJustification,
+	},
+	parachain_header_storage_key, ClientState, FinalityProof, HostFunctions, ParachainHeaderProofs,
 	ParachainHeadersWithFinalityProof,
 };
+use sp_finality_grandpa::ScheduledChange;
 use sp_runtime::traits::Header;
 use sp_trie::{LayoutV0, StorageProof};
 
@@ -45,7 +48,69 @@ mod tests;
 /// Next, we prove the finality of parachain headers, by verifying patricia-merkle trie state proofs
 /// of these headers, stored at the recently finalized relay chain heights.
 pub fn verify_parachain_headers_with_grandpa_finality_proof<H, Host>(
-	mut client_state: ClientState<H::Hash>,
+	client_state: ClientState<H::Hash>,
+	proof: ParachainHeadersWithFinalityProof<H>,
+) -> Result<ClientState<H::Hash>, error::Error>
+where
+	H: Header<Hash = H256, Number = u32>,
+	H::Number: finality_grandpa::BlockNumberOps + Into<u32>,
+	Host: HostFunctions,
+	Host::BlakeTwo256: Hasher<Out = H256>,
+{
+	let ParachainHeadersWithFinalityProof {
+		finality_proof,
+		parachain_headers,
+		authority_set_changes,
+	} = proof;
+
+	// 1 & 2. validate unknown headers and verify justifications, handing over authority sets.
+	let (client_state, headers, finalized, forced_change) =
+		verify_grandpa_finality_proofs::<H, Host>(
+			client_state,
+			&authority_set_changes,
+			&finality_proof,
+		)?;
+
+	// 3. verify state proofs of parachain headers in finalized relay chain headers.
+	let mut para_heights = vec![];
+	for (hash, proofs) in parachain_headers {
+		if finalized.binary_search(&hash).is_err() {
+			// seems relay hash isn't in the finalized chain.
+			continue
+		}
+		let relay_chain_header =
+			headers.header(&hash).expect("Headers have been checked by AncestryChain; qed");
+
+		let ParachainHeaderProofs { extrinsic_proof, extrinsic, state_proof } = proofs;
+		let proof = StorageProof::new(state_proof);
+		let key = parachain_header_storage_key(client_state.para_id);
+		// verify patricia-merkle state proofs
+		let header = state_machine::read_proof_check::<Host::BlakeTwo256, _>(
+			relay_chain_header.state_root(),
+			proof,
+			&[key.as_ref()],
+		)
+		.map_err(|err| anyhow!("error verifying parachain header state proof: {err}"))?
+		.remove(key.as_ref())
+		.flatten()
+		.ok_or_else(|| anyhow!("Invalid proof, parachain header not found"))?;
+		let parachain_header = H::decode(&mut &header[..])?;
+		para_heights.push(parachain_header.number().clone().into());
+		verify_timestamp_extrinsic_proof::<H, Host>(&parachain_header, extrinsic, extrinsic_proof)?;
+	}
+
+	// 4. set new client state, optionally rotating authorities
+	Ok(apply_finality_proof(client_state, &headers, &finality_proof, forced_change, para_heights))
+}
+
+/// This function verifies the GRANDPA finality proof for the headers of a standalone chain, which
+/// runs its own GRANDPA instead of being secured by a relay chain.
+///
+/// The finalized headers themselves are the consensus states, so `proof.parachain_headers` maps
+/// hashes of finalized headers to the proofs of their timestamp extrinsic and the state proofs must
+/// be empty. The `latest_para_height` of the client state tracks the latest of these headers.
+pub fn verify_standalone_headers_with_grandpa_finality_proof<H, Host>(
+	client_state: ClientState<H::Hash>,
 	proof: ParachainHeadersWithFinalityProof<H>,
 ) -> Result<ClientState<H::Hash>, error::Error>
 where
@@ -54,8 +119,116 @@ where
 	Host: HostFunctions,
 	Host::BlakeTwo256: Hasher<Out = H256>,
 {
-	let ParachainHeadersWithFinalityProof { finality_proof, parachain_headers } = proof;
+	let ParachainHeadersWithFinalityProof {
+		finality_proof,
+		parachain_headers,
+		authority_set_changes,
+	} = proof;
+
+	// 1 & 2. validate unknown headers and verify justifications, handing over authority sets.
+	let (client_state, headers, finalized, forced_change) =
+		verify_grandpa_finality_proofs::<H, Host>(
+			client_state,
+			&authority_set_changes,
+			&finality_proof,
+		)?;
 
+	// 3. verify timestamp extrinsic proofs of the finalized headers.
+	let mut heights = vec![];
+	for (hash, proofs) in parachain_headers {
+		if finalized.binary_search(&hash).is_err() {
+			continue
+		}
+		let header =
+			headers.header(&hash).expect("Headers have been checked by AncestryChain; qed");
+
+		let ParachainHeaderProofs { extrinsic_proof, extrinsic, state_proof } = proofs;
+		if !state_proof.is_empty() {
+			Err(anyhow!("Standalone chain headers can't have state proofs"))?
+		}
+		heights.push(header.number().clone().into());
+		verify_timestamp_extrinsic_proof::<H, Host>(header, extrinsic, extrinsic_proof)?;
+	}
+
+	// 4. set new client state, optionally rotating authorities
+	Ok(apply_finality_proof(client_state, &headers, &finality_proof, forced_change, heights))
+}
+
+/// Verifies the proofs of the authority set changes in order, then the finality proof with the
+/// resulting authority set. Returns the client state with the authority set changes applied, the
+/// headers of all the proofs, the sorted hashes of the headers they finalize and the forced
+/// authority set change enacted by the finality proof, if any.
+fn verify_grandpa_finality_proofs<H, Host>(
+	mut client_state: ClientState<H::Hash>,
+	authority_set_changes: &[FinalityProof<H>],
+	finality_proof: &FinalityProof<H>,
+) -> Result<
+	(ClientState<H::Hash>, AncestryChain<H>, Vec<H::Hash>, Option<ScheduledChange<u32>>),
+	error::Error,
+>
+where
+	H: Header<Hash = H256, Number = u32>,
+	H::Number: finality_grandpa::BlockNumberOps + Into<u32>,
+	Host: HostFunctions,
+{
+	let from = client_state.latest_relay_hash;
+
+	for authority_set_change in authority_set_changes {
+		let (headers, _, forced_change) =
+			verify_grandpa_finality_proof::<H, Host>(&client_state, authority_set_change)?;
+		let target = headers
+			.header(&authority_set_change.block)
+			.expect("Target has been checked by verify_grandpa_finality_proof; qed");
+		if forced_change.is_none() && find_scheduled_change::<H>(target).is_none() {
+			Err(anyhow!("Authority set change proof doesn't finalize a scheduled or forced change"))?
+		}
+		client_state = apply_finality_proof(
+			client_state,
+			&headers,
+			authority_set_change,
+			forced_change,
+			vec![],
+		);
+	}
+
+	let (headers, finalized, forced_change) =
+		verify_grandpa_finality_proof::<H, Host>(&client_state, finality_proof)?;
+	if authority_set_changes.is_empty() {
+		return Ok((client_state, headers, finalized, forced_change))
+	}
+
+	// the proofs cover consecutive ranges of the same chain, so the headers finalized by all of
+	// them form a single route.
+	let unknown_headers = authority_set_changes
+		.iter()
+		.chain(core::iter::once(finality_proof))
+		.flat_map(|proof| proof.unknown_headers.iter().cloned())
+		.collect::<Vec<_>>();
+	let headers = AncestryChain::<H>::new(&unknown_headers);
+	let mut finalized = headers.ancestry(from, finality_proof.block).map_err(|_| {
+		anyhow!("[verify_grandpa_finality_proofs] Invalid ancestry across authority set changes!")
+	})?;
+	finalized.sort();
+
+	Ok((client_state, headers, finalized, forced_change))
+}
+
+/// Validates the unknown headers of the finality proof and verifies its justification, returns the
+/// headers, the sorted hashes of the headers it finalizes and the forced authority set change they
+/// enact, if any.
+///
+/// Forced changes are enacted by governance once GRANDPA has stalled, without a justification from
+/// the current authority set. If one of the newly finalized headers signals a forced change that
+/// is enacted by the target, the justification must be signed by the forced authority set instead.
+fn verify_grandpa_finality_proof<H, Host>(
+	client_state: &ClientState<H::Hash>,
+	finality_proof: &FinalityProof<H>,
+) -> Result<(AncestryChain<H>, Vec<H::Hash>, Option<ScheduledChange<u32>>), error::Error>
+where
+	H: Header<Hash = H256, Number = u32>,
+	H::Number: finality_grandpa::BlockNumberOps + Into<u32>,
+	Host: HostFunctions,
+{
 	// 1. First validate unknown headers.
 	let headers = AncestryChain::<H>::new(&finality_proof.unknown_headers);
 
@@ -97,56 +270,74 @@ where
 	})?;
 	finalized.sort();
 
-	// 2. verify justification.
-	justification.verify::<Host>(client_state.current_set_id, &client_state.current_authorities)?;
+	// 2. hand over to the forced authority set if the newly finalized headers enact one.
+	let forced_change = find_enacted_forced_change::<H>(&headers, from, target.hash())?;
 
-	// 3. verify state proofs of parachain headers in finalized relay chain headers.
-	let mut para_heights = vec![];
-	for (hash, proofs) in parachain_headers {
-		if finalized.binary_search(&hash).is_err() {
-			// seems relay hash isn't in the finalized chain.
-			continue
-		}
-		let relay_chain_header =
-			headers.header(&hash).expect("Headers have been checked by AncestryChain; qed");
-
-		let ParachainHeaderProofs { extrinsic_proof, extrinsic, state_proof } = proofs;
-		let proof = StorageProof::new(state_proof);
-		let key = parachain_header_storage_key(client_state.para_id);
-		// verify patricia-merkle state proofs
-		let header = state_machine::read_proof_check::<Host::BlakeTwo256, _>(
-			relay_chain_header.state_root(),
-			proof,
-			&[key.as_ref()],
-		)
-		.map_err(|err| anyhow!("error verifying parachain header state proof: {err}"))?
-		.remove(key.as_ref())
-		.flatten()
-		.ok_or_else(|| anyhow!("Invalid proof, parachain header not found"))?;
-		let parachain_header = H::decode(&mut &header[..])?;
-		para_heights.push(parachain_header.number().clone().into());
-		// Timestamp extrinsic should be the first inherent and hence the first extrinsic
-		// https://github.com/paritytech/substrate/blob/d602397a0bbb24b5d627795b797259a44a5e29e9/primitives/trie/src/lib.rs#L99-L101
-		let key = codec::Compact(0u32).encode();
-		// verify extrinsic proof for timestamp extrinsic
-		sp_trie::verify_trie_proof::<LayoutV0<Host::BlakeTwo256>, _, _, _>(
-			parachain_header.extrinsics_root(),
-			&extrinsic_proof,
-			&vec![(key, Some(&extrinsic[..]))],
-		)
-		.map_err(|_| anyhow!("Invalid extrinsic proof"))?;
+	// 3. verify justification.
+	match &forced_change {
+		Some(change) => justification
+			.verify::<Host>(client_state.current_set_id + 1, &change.next_authorities)?,
+		None => justification
+			.verify::<Host>(client_state.current_set_id, &client_state.current_authorities)?,
 	}
 
-	// 4. set new client state, optionally rotating authorities
+	Ok((headers, finalized, forced_change))
+}
+
+/// Verifies the proof of the timestamp extrinsic against the extrinsics root of `header`.
+fn verify_timestamp_extrinsic_proof<H, Host>(
+	header: &H,
+	extrinsic: Vec<u8>,
+	extrinsic_proof: Vec<Vec<u8>>,
+) -> Result<(), error::Error>
+where
+	H: Header<Hash = H256>,
+	Host: HostFunctions,
+	Host::BlakeTwo256: Hasher<Out = H256>,
+{
+	// Timestamp extrinsic should be the first inherent and hence the first extrinsic
+	// https://github.com/paritytech/substrate/blob/d602397a0bbb24b5d627795b797259a44a5e29e9/primitives/trie/src/lib.rs#L99-L101
+	let key = codec::Compact(0u32).encode();
+	// verify extrinsic proof for timestamp extrinsic
+	sp_trie::verify_trie_proof::<LayoutV0<Host::BlakeTwo256>, _, _, _>(
+		header.extrinsics_root(),
+		&extrinsic_proof,
+		&vec![(key, Some(&extrinsic[..]))],
+	)
+	.map_err(|_| anyhow!("Invalid extrinsic proof"))?;
+
+	Ok(())
+}
+
+/// Advances the client state to the target of a verified finality proof, handing over to the
+/// authority set of the forced change it enacts and rotating authorities if the target schedules
+/// a change.
+fn apply_finality_proof<H>(
+	mut client_state: ClientState<H::Hash>,
+	headers: &AncestryChain<H>,
+	finality_proof: &FinalityProof<H>,
+	forced_change: Option<ScheduledChange<u32>>,
+	heights: Vec<u32>,
+) -> ClientState<H::Hash>
+where
+	H: Header<Hash = H256, Number = u32>,
+{
+	let target = headers
+		.header(&finality_proof.block)
+		.expect("Target has been checked by verify_grandpa_finality_proof; qed");
 	client_state.latest_relay_hash = target.hash();
 	client_state.latest_relay_height = (*target.number()).into();
-	if let Some(max_height) = para_heights.into_iter().max() {
+	if let Some(max_height) = heights.into_iter().max() {
 		client_state.latest_para_height = max_height;
 	}
-	if let Some(scheduled_change) = find_scheduled_change::<H>(&target) {
+	if let Some(forced_change) = forced_change {
+		client_state.current_set_id += 1;
+		client_state.current_authorities = forced_change.next_authorities;
+	}
+	if let Some(scheduled_change) = find_scheduled_change::<H>(target) {
 		client_state.current_set_id += 1;
 		client_state.current_authorities = scheduled_change.next_authorities;
 	}
 
-	Ok(client_state)
+	client_state
 }
@@ -128,3 +128,67 @@ async fn follow_grandpa_justifications() {
 		println!("========= Successfully verified grandpa justification =========");
 	}
 }
+
+#[tokio::test]
+async fn hand_over_multiple_authority_sets_in_single_update() {
+	let relay = std::env::var("RELAY_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+	let para = std::env::var("PARA_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+
+	let relay_ws_url = format!("ws://{relay}:9944");
+	let para_ws_url = format!("ws://{para}:9188");
+
+	let prover = GrandpaProver::<PolkadotConfig>::new(&relay_ws_url, &para_ws_url, 2000)
+		.await
+		.unwrap();
+	let session_length = prover.session_length().await.unwrap();
+	let client_state = prover.initialize_client_state().await.unwrap();
+
+	// let the client fall behind by a few authority set changes.
+	println!("Waiting for the relay chain to go through several sessions");
+	let mut subscription =
+		GrandpaApiClient::<JustificationNotification, H256, u32>::subscribe_justifications(
+			// we cast between the same type but different crate versions.
+			&*unsafe {
+				unsafe_arc_cast::<_, jsonrpsee_ws_client::WsClient>(prover.relay_ws_client.clone())
+			},
+		)
+		.await
+		.expect("Failed to subscribe to grandpa justifications");
+	let justification = loop {
+		let JustificationNotification(sp_core::Bytes(justification)) =
+			subscription.next().await.unwrap().unwrap();
+		let justification =
+			Justification::decode(&mut &justification[..]).expect("Failed to decode justification");
+		if justification.commit.target_number >
+			client_state.latest_relay_height + session_length * 3
+		{
+			break justification
+		}
+	};
+
+	let finalized_para_header = prover
+		.query_latest_finalized_parachain_header(justification.commit.target_number)
+		.await
+		.expect("Failed to fetch finalized parachain headers");
+	let header_numbers =
+		((client_state.latest_para_height + 1)..=finalized_para_header.number).collect::<Vec<_>>();
+
+	let proof = prover
+		.query_finalized_parachain_headers_with_proof(
+			&client_state,
+			justification.commit.target_number,
+			header_numbers,
+		)
+		.await
+		.expect("Failed to fetch finalized parachain headers with proof");
+	assert!(proof.authority_set_changes.len() >= 2);
+
+	let new_client_state = verify_parachain_headers_with_grandpa_finality_proof::<
+		Header,
+		HostFunctionsProvider,
+	>(client_state.clone(), proof.clone())
+	.expect("Failed to verify parachain headers across authority set changes");
+
+	assert!(new_client_state.current_set_id >= client_state.current_set_id + 2);
+	assert!(new_client_state.latest_para_height > client_state.latest_para_height);
+}
@@ -20,7 +20,7 @@ use alloc::{
 	format,
 	string::{String, ToString},
 };
-use core::{fmt::Formatter, str::FromStr, write};
+use core::str::FromStr;
 use frame_support::dispatch::{DispatchResult, Weight};
 use ibc::{
 	core::{
@@ -32,14 +32,14 @@ use ibc::{
 			Version,
 		},
 		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
-		ics26_routing::context::{
-			Acknowledgement as GenericAcknowledgement, Module, ModuleCallbackContext,
-			ModuleOutputBuilder,
-		},
+		ics26_routing::context::Acknowledgement as GenericAcknowledgement,
 	},
 	signer::Signer,
 };
-use ibc_primitives::{port_id_from_bytes, CallbackWeight, HandlerMessage, IbcHandler, Timeout};
+use ibc_primitives::{
+	port_id_from_bytes, CallbackWeight, HandlerMessage, IbcHandler, ModuleAdaptor, ModuleCallbacks,
+	PortCapability, Timeout,
+};
 use sp_std::{marker::PhantomData, prelude::*};
 // Re-export pallet items so that they can be accessed from the crate namespace.
 pub use pallet::*;
@@ -97,6 +97,10 @@ pub mod pallet {
 	#[pallet::without_storage_info]
 	pub struct Pallet<T>(_);
 
+	#[pallet::storage]
+	/// Capability of the ping port, needed to release it
+	pub type PingPort<T: Config> = StorageValue<_, PortCapability, OptionQuery>;
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		#[pallet::weight(0)]
@@ -111,6 +115,26 @@ pub mod pallet {
 		}
 	}
 
+	#[pallet::genesis_config]
+	pub struct GenesisConfig {}
+
+	#[cfg(feature = "std")]
+	impl Default for GenesisConfig {
+		fn default() -> Self {
+			Self {}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			let port_id = PortId::from_str(PORT_ID).expect("PORT_ID is static and valid; qed");
+			let capability = T::IbcHandler::bind_port::<Pallet<T>>(port_id)
+				.expect("Ping port should be free at genesis");
+			PingPort::<T>::put(capability);
+		}
+	}
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -147,14 +171,9 @@ impl<T: Config> Pallet<T> {
 	}
 }
 
-#[derive(Clone, Eq, PartialEq)]
-pub struct IbcModule<T: Config>(PhantomData<T>);
-
-impl<T: Config> Default for IbcModule<T> {
-	fn default() -> Self {
-		Self(PhantomData::default())
-	}
-}
+/// Ibc module for the ping pallet, ping binds its port through the pallet-ibc port registry at
+/// genesis so the runtime only needs to add this adaptor to its `PortRegistryRouter`.
+pub type IbcModule<T> = ModuleAdaptor<Pallet<T>>;
 
 pub struct PingAcknowledgement(Vec<u8>);
 
@@ -166,33 +185,23 @@ impl AsRef<[u8]> for PingAcknowledgement {
 
 impl GenericAcknowledgement for PingAcknowledgement {}
 
-impl<T: Config> core::fmt::Debug for IbcModule<T> {
-	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-		write!(f, "pallet-ibc-ping")
-	}
-}
+impl<T: Config> ModuleCallbacks for Pallet<T> {
+	const MODULE_ID: &'static str = MODULE_ID;
+	type WeightHandler = WeightHandler<T>;
 
-impl<T: Config + Send + Sync> Module for IbcModule<T> {
 	fn on_chan_open_init(
-		&mut self,
-		_ctx: &dyn ModuleCallbackContext,
-		_output: &mut ModuleOutputBuilder,
 		_order: Order,
 		_connection_hops: &[ConnectionId],
 		_port_id: &PortId,
 		_channel_id: &ChannelId,
 		_counterparty: &Counterparty,
 		_version: &Version,
-		_relayer: &Signer,
 	) -> Result<(), Ics04Error> {
 		log::info!("Channel initialized");
 		Ok(())
 	}
 
 	fn on_chan_open_try(
-		&mut self,
-		_ctx: &dyn ModuleCallbackContext,
-		_output: &mut ModuleOutputBuilder,
 		order: Order,
 		_connection_hops: &[ConnectionId],
 		port_id: &PortId,
@@ -200,7 +209,6 @@ impl<T: Config + Send + Sync> Module for IbcModule<T> {
 		counterparty: &Counterparty,
 		version: &Version,
 		counterparty_version: &Version,
-		_relayer: &Signer,
 	) -> Result<Version, Ics04Error> {
 		if counterparty_version.to_string() != *VERSION || version.to_string() != *VERSION {
 			return Err(Ics04Error::no_common_version())
@@ -222,13 +230,9 @@ impl<T: Config + Send + Sync> Module for IbcModule<T> {
 	}
 
 	fn on_chan_open_ack(
-		&mut self,
-		_ctx: &dyn ModuleCallbackContext,
-		_output: &mut ModuleOutputBuilder,
 		port_id: &PortId,
 		channel_id: &ChannelId,
 		counterparty_version: &Version,
-		_relayer: &Signer,
 	) -> Result<(), Ics04Error> {
 		log::info!(
 			"Channel acknowledged {:?}, {:?}, {:?}",
@@ -239,49 +243,22 @@ impl<T: Config + Send + Sync> Module for IbcModule<T> {
 		Ok(())
 	}
 
-	fn on_chan_open_confirm(
-		&mut self,
-		_ctx: &dyn ModuleCallbackContext,
-		_output: &mut ModuleOutputBuilder,
-		port_id: &PortId,
-		channel_id: &ChannelId,
-		_relayer: &Signer,
-	) -> Result<(), Ics04Error> {
+	fn on_chan_open_confirm(port_id: &PortId, channel_id: &ChannelId) -> Result<(), Ics04Error> {
 		log::info!("Channel open confirmed {:?}, {:?}", channel_id, port_id);
 		Ok(())
 	}
 
-	fn on_chan_close_init(
-		&mut self,
-		_ctx: &dyn ModuleCallbackContext,
-		_output: &mut ModuleOutputBuilder,
-		port_id: &PortId,
-		channel_id: &ChannelId,
-		_relayer: &Signer,
-	) -> Result<(), Ics04Error> {
+	fn on_chan_close_init(port_id: &PortId, channel_id: &ChannelId) -> Result<(), Ics04Error> {
 		log::info!("Channel close started {:?} {:?}", channel_id, port_id);
 		Ok(())
 	}
 
-	fn on_chan_close_confirm(
-		&mut self,
-		_ctx: &dyn ModuleCallbackContext,
-		_output: &mut ModuleOutputBuilder,
-		port_id: &PortId,
-		channel_id: &ChannelId,
-		_relayer: &Signer,
-	) -> Result<(), Ics04Error> {
+	fn on_chan_close_confirm(port_id: &PortId, channel_id: &ChannelId) -> Result<(), Ics04Error> {
 		log::info!("Channel close confirmed\n ChannelId: {:?}, PortId: {:?}", channel_id, port_id);
 		Ok(())
 	}
 
-	fn on_recv_packet(
-		&self,
-		_ctx: &dyn ModuleCallbackContext,
-		_output: &mut ModuleOutputBuilder,
-		packet: &Packet,
-		_relayer: &Signer,
-	) -> Result<(), Ics04Error> {
+	fn on_recv_packet(packet: &Packet, _relayer: &Signer) -> Result<(), Ics04Error> {
 		let success = "ping-success".as_bytes().to_vec();
 		let data = String::from_utf8(packet.data.clone()).ok();
 		log::info!("Received Packet Sequence {:?}, Packet Data {:?}", packet.sequence, data);
@@ -292,9 +269,6 @@ impl<T: Config + Send + Sync> Module for IbcModule<T> {
 	}
 
 	fn on_acknowledgement_packet(
-		&mut self,
-		_ctx: &dyn ModuleCallbackContext,
-		_output: &mut ModuleOutputBuilder,
 		packet: &Packet,
 		acknowledgement: &Acknowledgement,
 		_relayer: &Signer,
@@ -303,13 +277,7 @@ impl<T: Config + Send + Sync> Module for IbcModule<T> {
 		Ok(())
 	}
 
-	fn on_timeout_packet(
-		&mut self,
-		_ctx: &dyn ModuleCallbackContext,
-		_output: &mut ModuleOutputBuilder,
-		packet: &Packet,
-		_relayer: &Signer,
-	) -> Result<(), Ics04Error> {
+	fn on_timeout_packet(packet: &Packet, _relayer: &Signer) -> Result<(), Ics04Error> {
 		log::info!("Timeout Packet {:?}", packet);
 		Ok(())
 	}
@@ -0,0 +1,245 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::CallbackWeight;
+use core::{fmt::Formatter, marker::PhantomData};
+use ibc::{
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			error::Error as Ics04Error,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module, ModuleCallbackContext, ModuleOutputBuilder},
+	},
+	signer::Signer,
+};
+
+/// Simplified ibc callbacks for pallets that bind their ports through the pallet-ibc port
+/// registry. Pallets are stateless so the callbacks don't take a receiver, all state should be
+/// read from and written to storage.
+pub trait ModuleCallbacks: 'static {
+	/// Module id this pallet is registered with in the ibc router, must be alphanumeric.
+	const MODULE_ID: &'static str;
+	/// Weight estimation for the callbacks of this module
+	type WeightHandler: CallbackWeight + Default;
+
+	fn on_chan_open_init(
+		_order: Order,
+		_connection_hops: &[ConnectionId],
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty: &Counterparty,
+		_version: &Version,
+	) -> Result<(), Ics04Error> {
+		Ok(())
+	}
+
+	fn on_chan_open_try(
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+	) -> Result<Version, Ics04Error>;
+
+	fn on_chan_open_ack(
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty_version: &Version,
+	) -> Result<(), Ics04Error> {
+		Ok(())
+	}
+
+	fn on_chan_open_confirm(_port_id: &PortId, _channel_id: &ChannelId) -> Result<(), Ics04Error> {
+		Ok(())
+	}
+
+	fn on_chan_close_init(_port_id: &PortId, _channel_id: &ChannelId) -> Result<(), Ics04Error> {
+		Ok(())
+	}
+
+	fn on_chan_close_confirm(_port_id: &PortId, _channel_id: &ChannelId) -> Result<(), Ics04Error> {
+		Ok(())
+	}
+
+	/// Modules should write acknowledgement to storage in this callback
+	fn on_recv_packet(_packet: &Packet, _relayer: &Signer) -> Result<(), Ics04Error> {
+		Ok(())
+	}
+
+	fn on_acknowledgement_packet(
+		_packet: &Packet,
+		_acknowledgement: &Acknowledgement,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		Ok(())
+	}
+
+	fn on_timeout_packet(_packet: &Packet, _relayer: &Signer) -> Result<(), Ics04Error> {
+		Ok(())
+	}
+}
+
+/// Adapts a type implementing [`ModuleCallbacks`] to the ibc [`Module`] interface, so it can be
+/// returned from the ibc router.
+pub struct ModuleAdaptor<M>(PhantomData<fn() -> M>);
+
+impl<M> Default for ModuleAdaptor<M> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<M> Clone for ModuleAdaptor<M> {
+	fn clone(&self) -> Self {
+		Self::default()
+	}
+}
+
+impl<M> PartialEq for ModuleAdaptor<M> {
+	fn eq(&self, _other: &Self) -> bool {
+		true
+	}
+}
+
+impl<M> Eq for ModuleAdaptor<M> {}
+
+impl<M: ModuleCallbacks> core::fmt::Debug for ModuleAdaptor<M> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		write!(f, "ModuleAdaptor({})", M::MODULE_ID)
+	}
+}
+
+impl<M: ModuleCallbacks> Module for ModuleAdaptor<M> {
+	fn on_chan_open_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		M::on_chan_open_init(order, connection_hops, port_id, channel_id, counterparty, version)
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		M::on_chan_open_try(
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+			counterparty_version,
+		)
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		M::on_chan_open_ack(port_id, channel_id, counterparty_version)
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		M::on_chan_open_confirm(port_id, channel_id)
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		M::on_chan_close_init(port_id, channel_id)
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		M::on_chan_close_confirm(port_id, channel_id)
+	}
+
+	fn on_recv_packet(
+		&self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		packet: &Packet,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		M::on_recv_packet(packet, relayer)
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		packet: &Packet,
+		acknowledgement: &Acknowledgement,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		M::on_acknowledgement_packet(packet, acknowledgement, relayer)
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		packet: &Packet,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		M::on_timeout_packet(packet, relayer)
+	}
+}
@@ -16,11 +16,17 @@
 
 extern crate alloc;
 
-use alloc::string::{String, ToString};
+use alloc::{
+	collections::VecDeque,
+	string::{String, ToString},
+};
 use codec::{Decode, Encode};
-use frame_support::{weights::Weight, RuntimeDebug};
+use frame_support::{storage::child::ChildInfo, weights::Weight, RuntimeDebug};
 use ibc::{
-	applications::transfer::{error::Error as Ics20Error, PrefixedCoin, VERSION},
+	applications::{
+		icq::packet::{QueryRequest, QueryResponse},
+		transfer::{error::Error as Ics20Error, PrefixedCoin, VERSION},
+	},
 	core::{
 		ics04_channel::{
 			channel::{ChannelEnd, Order},
@@ -28,6 +34,7 @@ use ibc::{
 			packet::{Packet, Sequence},
 		},
 		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		ics26_routing::context::ModuleId,
 	},
 	signer::Signer,
 	timestamp::Timestamp,
@@ -37,8 +44,11 @@ use scale_info::{prelude::format, TypeInfo};
 use sp_runtime::traits::IdentifyAccount;
 use sp_std::{prelude::*, str::FromStr};
 
+pub mod callbacks;
 pub mod runtime_interface;
 
+pub use callbacks::{ModuleAdaptor, ModuleCallbacks};
+
 /// Packet timeout, could be an offset, or absolute value.
 #[derive(
 	frame_support::RuntimeDebug, PartialEq, Eq, scale_info::TypeInfo, Encode, Decode, Clone,
@@ -76,6 +86,8 @@ pub enum HandlerMessage<AccountId> {
 		timeout: Timeout,
 		from: AccountId,
 		to: Signer,
+		/// Memo carried in the ics20 packet data
+		memo: String,
 	},
 	SendPacket {
 		/// packet data
@@ -172,6 +184,156 @@ pub struct IdentifiedChannel {
 	pub channel_end: Vec<u8>,
 }
 
+/// Number of items returned in a page when the request doesn't set a limit
+pub const DEFAULT_PAGE_LIMIT: u64 = 100;
+
+/// Cosmos style pagination of list queries
+#[derive(Clone, Default, Debug, codec::Encode, codec::Decode, PartialEq, Eq)]
+pub struct PageRequest {
+	/// `next_key` of the previous page, takes precedence over `offset`
+	pub key: Option<Vec<u8>>,
+	/// Number of items to skip when no `key` is set
+	pub offset: u64,
+	/// Maximum number of items in the page, [`DEFAULT_PAGE_LIMIT`] if zero
+	pub limit: u64,
+	/// Whether to return the total number of items
+	pub count_total: bool,
+	/// Whether to return items in descending key order
+	pub reverse: bool,
+}
+
+#[derive(Clone, Default, Debug, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
+pub struct PageResponse {
+	/// Key of the first item of the next page, `None` if this is the last page
+	pub next_key: Option<Vec<u8>>,
+	/// Total number of items, if it was requested
+	pub total: Option<u64>,
+}
+
+/// Returns the page of `items` selected by `pagination`, every item is returned when there's no
+/// page request. Items are ordered by their key.
+///
+/// Only meant for lists that are already held in memory, use [`paginate_child_trie`] to page
+/// through storage.
+pub fn paginate<V>(
+	mut items: Vec<(Vec<u8>, V)>,
+	pagination: Option<PageRequest>,
+) -> (Vec<V>, PageResponse) {
+	items.sort_by(|(a, _), (b, _)| a.cmp(b));
+	let pagination = match pagination {
+		Some(pagination) => pagination,
+		None => return (items.into_iter().map(|(_, item)| item).collect(), Default::default()),
+	};
+	let total = pagination.count_total.then(|| items.len() as u64);
+	if pagination.reverse {
+		items.reverse();
+	}
+	let start = match pagination.key {
+		Some(key) => items
+			.iter()
+			.position(
+				|(item_key, _)| {
+					if pagination.reverse {
+						*item_key <= key
+					} else {
+						*item_key >= key
+					}
+				},
+			)
+			.unwrap_or(items.len()),
+		None => (pagination.offset as usize).min(items.len()),
+	};
+	let limit = if pagination.limit == 0 { DEFAULT_PAGE_LIMIT } else { pagination.limit };
+	let mut items = items.into_iter().skip(start);
+	let page = items.by_ref().take(limit as usize).map(|(_, item)| item).collect();
+	let next_key = items.next().map(|(key, _)| key);
+	(page, PageResponse { next_key, total })
+}
+
+/// Returns the page selected by `pagination` of the items stored under `prefix` in a child trie,
+/// every item is returned when there's no page request. Items come in trie key order and are keyed
+/// by the rest of their trie key after `prefix`, keys `decode_key` rejects are skipped.
+///
+/// Iteration starts at the page key, or `offset` keys past `prefix`, and stops once the page is
+/// full, so values are only read for items in the page. Counting the total and reverse pages still
+/// walk every key under `prefix`, since the trie can only be iterated forward.
+pub fn paginate_child_trie<K, V>(
+	child_info: &ChildInfo,
+	prefix: &[u8],
+	pagination: Option<PageRequest>,
+	decode_key: impl Fn(&[u8]) -> Option<K>,
+	item: impl Fn(K, Vec<u8>) -> V,
+) -> (Vec<V>, PageResponse) {
+	let storage_key = child_info.storage_key();
+	let into_items = |entries: Vec<(K, Vec<u8>)>| {
+		entries
+			.into_iter()
+			.filter_map(|(decoded, key)| {
+				Some(item(decoded, sp_io::default_child_storage::get(storage_key, &key)?))
+			})
+			.collect::<Vec<_>>()
+	};
+	let pagination = match pagination {
+		Some(pagination) => pagination,
+		None => {
+			let entries = child_trie_keys(storage_key, prefix, prefix.to_vec(), &decode_key);
+			return (into_items(entries.collect()), Default::default())
+		},
+	};
+	let total = pagination
+		.count_total
+		.then(|| child_trie_keys(storage_key, prefix, prefix.to_vec(), &decode_key).count() as u64);
+	let limit =
+		(if pagination.limit == 0 { DEFAULT_PAGE_LIMIT } else { pagination.limit }) as usize;
+	let page_key = |key: Vec<u8>| key[prefix.len()..].to_vec();
+	let (page, next_key) = if pagination.reverse {
+		// keep the `offset + limit + 1` keys closest to the end of the page, the one before the
+		// page becomes the next key
+		let end = pagination.key.as_ref().map(|key| [prefix, &key[..]].concat());
+		let skip = if end.is_some() { 0 } else { pagination.offset as usize };
+		let mut window = VecDeque::new();
+		for entry in child_trie_keys(storage_key, prefix, prefix.to_vec(), &decode_key)
+			.take_while(|(_, key)| end.as_ref().map_or(true, |end| key <= end))
+		{
+			window.push_back(entry);
+			if window.len() > skip.saturating_add(limit).saturating_add(1) {
+				window.pop_front();
+			}
+		}
+		let mut entries = window.into_iter().rev().skip(skip);
+		let page: Vec<_> = entries.by_ref().take(limit).collect();
+		(page, entries.next().map(|(_, key)| page_key(key)))
+	} else {
+		let (start, skip) = match pagination.key {
+			Some(key) => ([prefix, &key[..]].concat(), 0),
+			None => (prefix.to_vec(), pagination.offset as usize),
+		};
+		let mut entries = child_trie_keys(storage_key, prefix, start, &decode_key).skip(skip);
+		let page: Vec<_> = entries.by_ref().take(limit).collect();
+		(page, entries.next().map(|(_, key)| page_key(key)))
+	};
+	(into_items(page), PageResponse { next_key, total })
+}
+
+/// Keys under `prefix` of a child trie from `start` onwards, with what `decode_key` made of them
+fn child_trie_keys<'a, K: 'a, F: Fn(&[u8]) -> Option<K> + 'a>(
+	storage_key: &'a [u8],
+	prefix: &'a [u8],
+	start: Vec<u8>,
+	decode_key: &'a F,
+) -> impl Iterator<Item = (K, Vec<u8>)> + 'a {
+	let first = if sp_io::default_child_storage::exists(storage_key, &start) {
+		Some(start)
+	} else {
+		sp_io::default_child_storage::next_key(storage_key, &start)
+	};
+	core::iter::successors(first, move |key| {
+		sp_io::default_child_storage::next_key(storage_key, key)
+	})
+	.take_while(move |key| key.starts_with(prefix))
+	.filter_map(move |key| Some((decode_key(&key[prefix.len()..])?, key)))
+}
+
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
 pub struct IdentifiedClientState {
 	pub client_id: Vec<u8>,
@@ -196,9 +358,9 @@ pub struct QueryClientStateResponse {
 
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
 pub struct QueryClientStatesResponse {
-	pub client_states: Vec<Vec<u8>>,
+	pub client_states: Vec<IdentifiedClientState>,
+	pub pagination: PageResponse,
 	pub height: u64,
-	pub trie_key: Vec<u8>,
 }
 
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
@@ -208,6 +370,27 @@ pub struct QueryConsensusStateResponse {
 	pub trie_key: Vec<u8>,
 }
 
+#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
+pub struct ConsensusStateWithHeight {
+	/// (revision_number, revision_height)
+	pub height: (u64, u64),
+	/// Protobuf encoded `AnyConsensusState`
+	pub consensus_state: Vec<u8>,
+}
+
+#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
+pub struct QueryConsensusStatesResponse {
+	pub consensus_states: Vec<ConsensusStateWithHeight>,
+	pub pagination: PageResponse,
+	pub height: u64,
+}
+
+#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
+pub struct QueryClientConnectionsResponse {
+	pub connection_ids: Vec<Vec<u8>>,
+	pub height: u64,
+}
+
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
 pub struct QueryConnectionResponse {
 	/// Protobuf encoded `ibc::core::ics03_connection::connection::ConnectionEnd`
@@ -227,12 +410,14 @@ pub struct QueryChannelResponse {
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
 pub struct QueryChannelsResponse {
 	pub channels: Vec<IdentifiedChannel>,
+	pub pagination: PageResponse,
 	pub height: u64,
 }
 
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
 pub struct QueryConnectionsResponse {
 	pub connections: Vec<IdentifiedConnection>,
+	pub pagination: PageResponse,
 	pub height: u64,
 }
 
@@ -261,6 +446,7 @@ pub struct PacketState {
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
 pub struct QueryPacketCommitmentsResponse {
 	pub commitments: Vec<PacketState>,
+	pub pagination: PageResponse,
 	pub height: u64,
 }
 
@@ -274,9 +460,47 @@ pub struct QueryPacketAcknowledgementResponse {
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
 pub struct QueryPacketAcknowledgementsResponse {
 	pub acks: Vec<PacketState>,
+	pub pagination: PageResponse,
 	pub height: u64,
 }
 
+#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
+pub struct QueryPacketReceiptsResponse {
+	pub receipts: Vec<PacketState>,
+	pub pagination: PageResponse,
+	pub height: u64,
+}
+
+/// Responses of the list queries of version 2 of the runtime api, which returned every item
+pub mod v2 {
+	use super::{IdentifiedChannel, IdentifiedConnection, PacketState};
+	use sp_std::prelude::*;
+
+	#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
+	pub struct QueryChannelsResponse {
+		pub channels: Vec<IdentifiedChannel>,
+		pub height: u64,
+	}
+
+	#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
+	pub struct QueryConnectionsResponse {
+		pub connections: Vec<IdentifiedConnection>,
+		pub height: u64,
+	}
+
+	#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
+	pub struct QueryPacketCommitmentsResponse {
+		pub commitments: Vec<PacketState>,
+		pub height: u64,
+	}
+
+	#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
+	pub struct QueryPacketAcknowledgementsResponse {
+		pub acks: Vec<PacketState>,
+		pub height: u64,
+	}
+}
+
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
 pub struct QueryPacketReceiptResponse {
 	pub receipt: bool,
@@ -296,6 +520,47 @@ pub struct QueryDenomTracesResponse {
 	pub total: Option<u64>,
 }
 
+/// A light client type that may be created or upgraded to on this chain.
+#[derive(codec::Encode, Debug, codec::Decode, Clone, PartialEq, Eq, scale_info::TypeInfo)]
+pub struct AllowedClient {
+	/// Client type as utf8 string bytes, eg. `10-grandpa`
+	pub client_type: Vec<u8>,
+	/// Chain ids the client may track as utf8 string bytes, any chain id is allowed if empty
+	pub chain_ids: Vec<Vec<u8>>,
+}
+
+impl AllowedClient {
+	/// Whether a client of `client_type` tracking `chain_id` is covered by this entry.
+	pub fn allows(&self, client_type: &str, chain_id: &str) -> bool {
+		self.client_type == client_type.as_bytes()
+			&& (self.chain_ids.is_empty()
+				|| self.chain_ids.iter().any(|allowed| allowed == chain_id.as_bytes()))
+	}
+}
+
+/// Packet data written by the pallet in a block, recorded in its events so that packets can be
+/// indexed from block history by nodes that don't have offchain indexing enabled.
+#[derive(codec::Encode, Debug, codec::Decode, Clone, PartialEq, Eq, scale_info::TypeInfo)]
+pub enum PacketRecord {
+	/// A packet was sent from this chain
+	Send(PacketInfo),
+	/// A packet was received on this chain
+	Recv(PacketInfo),
+	/// An acknowledgement was written for a received packet
+	Ack { port_id: Vec<u8>, channel_id: Vec<u8>, sequence: u64, ack: Vec<u8> },
+}
+
+#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
+pub struct SpamDepositStatus {
+	/// Scale encoded account id of the depositor
+	pub depositor: Vec<u8>,
+	pub amount: u128,
+	pub reserved_at: u32,
+	/// Earliest block at which the deposit is slashed if the handshake hasn't completed, later
+	/// if too many deposits expire at that block.
+	pub expires_at: u32,
+}
+
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
 pub struct ConnectionHandshake {
 	pub client_state: Vec<u8>,
@@ -367,6 +632,39 @@ impl TryFrom<&OpenChannelParams> for Order {
 	}
 }
 
+/// Ownership of a port bound with [`IbcHandler::bind_port`], the module that bound the port keeps
+/// it and hands it back to [`IbcHandler::release_port`] to release the port. It's only valid for
+/// the binding it was issued for, once the port is released and bound again it's stale.
+#[derive(Clone, Debug, PartialEq, Eq, codec::Encode, codec::Decode, scale_info::TypeInfo)]
+pub struct PortCapability {
+	port_id: Vec<u8>,
+	module_id: Vec<u8>,
+	nonce: u64,
+}
+
+impl PortCapability {
+	/// Issued by the ibc pallet when it binds a port, modules receive it from
+	/// [`IbcHandler::bind_port`].
+	pub fn new(port_id: Vec<u8>, module_id: Vec<u8>, nonce: u64) -> Self {
+		Self { port_id, module_id, nonce }
+	}
+
+	/// Port this capability owns, as utf8 string bytes
+	pub fn port_id(&self) -> &[u8] {
+		&self.port_id
+	}
+
+	/// Module the port is bound to, as utf8 string bytes
+	pub fn module_id(&self) -> &[u8] {
+		&self.module_id
+	}
+
+	/// Binding of the port this capability was issued for
+	pub fn nonce(&self) -> u64 {
+		self.nonce
+	}
+}
+
 /// Captures the functions modules can use to interact with the ibc pallet
 /// Currently allows modules to register packets and create channels
 pub trait IbcHandler<AccountId> {
@@ -379,6 +677,25 @@ pub trait IbcHandler<AccountId> {
 	/// Handle a message
 	fn handle_message(msg: HandlerMessage<AccountId>) -> Result<(), Error>;
 	fn write_acknowledgement(packet: &Packet, ack: Vec<u8>) -> Result<(), Error>;
+	/// Bind a port to module `M`, ibc messages for this port will be routed to the module.
+	/// Fails if `M` isn't registered in the runtime's router or the port is already bound.
+	/// Returns the capability the module needs to release the port.
+	fn bind_port<M: ModuleCallbacks>(port_id: PortId) -> Result<PortCapability, Error>;
+	/// Release a port with the capability its module received when binding it, fails if the
+	/// capability isn't the one issued for the port's current binding.
+	fn release_port(capability: PortCapability) -> Result<(), Error>;
+	/// Bind a port that module `M` sends interchain queries from, the results of queries sent
+	/// from this port are delivered to `M`. Fails if `M` isn't part of the runtime's
+	/// `IcqCallback` or the port is already bound.
+	fn bind_query_port<M: QueryCallback>(port_id: PortId) -> Result<(), Error>;
+	/// Send interchain queries over an icq channel on a port bound by `M` with
+	/// [`IbcHandler::bind_query_port`]. Returns the sequence of the query packet.
+	fn send_query<M: QueryCallback>(
+		port_id: PortId,
+		channel_id: ChannelId,
+		requests: Vec<QueryRequest>,
+		timeout: Timeout,
+	) -> Result<u64, Error>;
 	/// testing related methods
 	#[cfg(feature = "runtime-benchmarks")]
 	fn create_client() -> Result<ClientId, Error>;
@@ -386,6 +703,98 @@ pub trait IbcHandler<AccountId> {
 	fn create_connection(client_id: ClientId, connection_id: ConnectionId) -> Result<(), Error>;
 }
 
+/// Receives the results of interchain queries sent through [`IbcHandler::send_query`]
+pub trait QueryCallback {
+	/// Module id the results are routed by, must be unique among the runtime's query callbacks.
+	const MODULE_ID: &'static str;
+
+	/// Called with the host responses, in the same order as `requests`, or the error
+	/// acknowledgement returned by the host.
+	fn on_query_result(
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: u64,
+		requests: &[QueryRequest],
+		result: Result<&[QueryResponse], &str>,
+	);
+
+	/// Called when the query packet timed out before it was executed by the host.
+	fn on_query_timeout(
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: u64,
+		requests: &[QueryRequest],
+	);
+}
+
+/// A set of [`QueryCallback`]s, implemented for tuples. Results are delivered to the callback
+/// whose module id bound the port the query was sent from.
+pub trait QueryCallbacks {
+	/// Returns true if a callback in this set is registered against `module_id`
+	fn has_callback(module_id: &str) -> bool;
+
+	/// Delivers query results to the callback registered against `module_id`
+	fn on_query_result(
+		module_id: &str,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: u64,
+		requests: &[QueryRequest],
+		result: Result<&[QueryResponse], &str>,
+	);
+
+	/// Delivers a query timeout to the callback registered against `module_id`
+	fn on_query_timeout(
+		module_id: &str,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: u64,
+		requests: &[QueryRequest],
+	);
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(8)]
+#[tuple_types_custom_trait_bound(QueryCallback)]
+impl QueryCallbacks for Tuple {
+	fn has_callback(module_id: &str) -> bool {
+		for_tuples!( #(
+			if Tuple::MODULE_ID == module_id {
+				return true
+			}
+		)* );
+		false
+	}
+
+	fn on_query_result(
+		module_id: &str,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: u64,
+		requests: &[QueryRequest],
+		result: Result<&[QueryResponse], &str>,
+	) {
+		for_tuples!( #(
+			if Tuple::MODULE_ID == module_id {
+				return Tuple::on_query_result(port_id, channel_id, sequence, requests, result)
+			}
+		)* );
+	}
+
+	fn on_query_timeout(
+		module_id: &str,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: u64,
+		requests: &[QueryRequest],
+	) {
+		for_tuples!( #(
+			if Tuple::MODULE_ID == module_id {
+				return Tuple::on_query_timeout(port_id, channel_id, sequence, requests)
+			}
+		)* );
+	}
+}
+
 /// Callback Weight
 /// This trait must be implemented by module callback handlers to be able to estimate the weight
 /// of the callback function.
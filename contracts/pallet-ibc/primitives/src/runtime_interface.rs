@@ -75,6 +75,30 @@ pub fn ripemd160(message: &[u8]) -> [u8; 20] {
 	res
 }
 
+/// Verify an aggregate BLS12-381 signature of `message` by all of the given public keys, as
+/// defined by the `FastAggregateVerify` function of the BLS signature standard. This isn't a host
+/// function, it runs in the runtime and its cost is covered by the `update_ethereum_client`
+/// benchmark of pallet-ibc.
+pub fn bls12_381_fast_aggregate_verify(
+	public_keys: &[[u8; 48]],
+	message: &[u8],
+	signature: &[u8; 96],
+) -> bool {
+	let public_keys = match public_keys
+		.iter()
+		.map(|key| milagro_bls::PublicKey::from_bytes(key))
+		.collect::<Result<Vec<_>, _>>()
+	{
+		Ok(public_keys) => public_keys,
+		Err(_) => return false,
+	};
+	let signature = match milagro_bls::AggregateSignature::from_bytes(signature) {
+		Ok(signature) => signature,
+		Err(_) => return false,
+	};
+	signature.fast_aggregate_verify(message, &public_keys.iter().collect::<Vec<_>>())
+}
+
 pub fn ss58_to_account_id_32(raw_str: &str) -> Result<[u8; 32], SS58CodecError> {
 	from_ss58check_with_version::<AccountId32>(raw_str)
 		.map(|acc| acc.into())
@@ -13,17 +13,123 @@
 // limitations under the License.
 
 //! Relayer events.
-use ibc::{core::ics04_channel::packet::Packet, events::IbcEvent as RawIbcEvent};
+use crate::indexer::PacketStore;
+use ibc::{
+	core::ics04_channel::{events::Attributes as ChannelAttributes, packet::Packet},
+	events::IbcEvent as RawIbcEvent,
+};
 use ibc_runtime_api::IbcRuntimeApi;
 use pallet_ibc::events::IbcEvent;
 use sc_client_api::HeaderBackend;
+use serde::{Deserialize, Serialize};
 use sp_api::{ApiRef, BlockId, BlockT, ProvideRuntimeApi};
 
+/// Filter for the events streamed by `ibc_subscribeEvents`.
+/// Unset fields match every event, events that don't carry an identifier that is filtered on are
+/// skipped.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventFilter {
+	/// Event types to stream, e.g. `send_packet` or `update_client`
+	pub event_types: Option<Vec<String>>,
+	/// Client the events refer to
+	pub client_id: Option<String>,
+	/// Connection the events refer to
+	pub connection_id: Option<String>,
+	/// Channel the events refer to, packets match on either their source or destination channel
+	pub channel_id: Option<String>,
+	/// Port the events refer to, packets match on either their source or destination port
+	pub port_id: Option<String>,
+}
+
+impl EventFilter {
+	/// Returns true if the event passes the filter
+	pub fn matches(&self, event: &RawIbcEvent) -> bool {
+		if let Some(event_types) = &self.event_types {
+			if !event_types.iter().any(|event_type| event_type == event.event_type().as_str()) {
+				return false
+			}
+		}
+		let matches = |filter: &Option<String>, id: Option<String>| match filter {
+			Some(filter) => id.as_ref() == Some(filter),
+			None => true,
+		};
+
+		let client_id = match event {
+			RawIbcEvent::CreateClient(ev) => Some(ev.client_id().to_string()),
+			RawIbcEvent::UpdateClient(ev) => Some(ev.client_id().to_string()),
+			RawIbcEvent::UpgradeClient(ev) => Some(ev.client_id().to_string()),
+			RawIbcEvent::ClientMisbehaviour(ev) => Some(ev.client_id().to_string()),
+			_ => event.connection_attributes().map(|attributes| attributes.client_id.to_string()),
+		};
+		if !matches(&self.client_id, client_id) {
+			return false
+		}
+
+		let channel_attributes: Option<ChannelAttributes> = match event.clone() {
+			RawIbcEvent::CloseInitChannel(ev) => Some(ev.into()),
+			RawIbcEvent::CloseConfirmChannel(ev) => Some(ev.into()),
+			event => event.channel_attributes(),
+		};
+		let connection_id = match &channel_attributes {
+			Some(attributes) => Some(attributes.connection_id.to_string()),
+			None => event
+				.connection_attributes()
+				.and_then(|attributes| attributes.connection_id.as_ref())
+				.map(ToString::to_string),
+		};
+		if !matches(&self.connection_id, connection_id) {
+			return false
+		}
+
+		match (channel_attributes, event.packet()) {
+			(Some(attributes), _) =>
+				matches(&self.port_id, Some(attributes.port_id.to_string())) &&
+					matches(
+						&self.channel_id,
+						attributes.channel_id.as_ref().map(ToString::to_string),
+					),
+			(None, Some(packet)) =>
+				(matches(&self.port_id, Some(packet.source_port.to_string())) &&
+					matches(&self.channel_id, Some(packet.source_channel.to_string()))) ||
+					(matches(&self.port_id, Some(packet.destination_port.to_string())) &&
+						matches(
+							&self.channel_id,
+							Some(packet.destination_channel.to_string()),
+						)),
+			(None, None) => self.port_id.is_none() && self.channel_id.is_none(),
+		}
+	}
+}
+
+/// Relayer events emitted in the block at `at`
+pub fn block_events<C, Block, AssetId>(
+	client: &C,
+	packet_store: Option<&dyn PacketStore>,
+	at: &BlockId<Block>,
+) -> Result<Vec<RawIbcEvent>, sp_api::ApiError>
+where
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: IbcRuntimeApi<Block, AssetId>,
+	Block: BlockT,
+	AssetId: codec::Codec,
+{
+	let api = client.runtime_api();
+	let events = api.block_events(at, None)?;
+	Ok(events
+		.into_iter()
+		.filter_map(|event| {
+			filter_map_pallet_event::<C, Block, AssetId>(at, &api, packet_store, event.ok()?)
+		})
+		.collect())
+}
+
 /// Filter out none relayer events and modify
-/// Fetch actual packet and acknowledgements from off chain storage and modify packets
+/// Fetch actual packet and acknowledgements from the packet index if one is provided, otherwise
+/// from off chain storage, and modify packets
 pub fn filter_map_pallet_event<C, Block, AssetId>(
 	at: &BlockId<Block>,
 	api: &ApiRef<<C as ProvideRuntimeApi<Block>>::Api>,
+	packet_store: Option<&dyn PacketStore>,
 	ev: IbcEvent,
 ) -> Option<RawIbcEvent>
 where
@@ -38,15 +144,14 @@ where
 			let channel_id = ev.src_channel_id();
 			let port_id = ev.src_port_id();
 			let sequence = u64::from(ev.packet.sequence);
-			let packets: Vec<ibc_primitives::PacketInfo> = api
-				.query_send_packet_info(
-					at,
-					channel_id.to_string().as_bytes().to_vec(),
-					port_id.as_bytes().to_vec(),
-					vec![sequence],
-				)
-				.ok()
-				.flatten()?;
+			let packets = query_send_packet_info::<C, Block, AssetId>(
+				at,
+				api,
+				packet_store,
+				channel_id.to_string().as_bytes().to_vec(),
+				port_id.as_bytes().to_vec(),
+				sequence,
+			)?;
 			let packet = packets.get(0)?.clone();
 			let packet: Packet = packet.into();
 			ev.packet = packet;
@@ -56,15 +161,14 @@ where
 			let channel_id = ev.dst_channel_id();
 			let port_id = ev.dst_port_id();
 			let sequence = u64::from(ev.packet.sequence);
-			let packets: Vec<ibc_primitives::PacketInfo> = api
-				.query_recv_packet_info(
-					at,
-					channel_id.to_string().as_bytes().to_vec(),
-					port_id.as_bytes().to_vec(),
-					vec![sequence],
-				)
-				.ok()
-				.flatten()?;
+			let packets = query_recv_packet_info::<C, Block, AssetId>(
+				at,
+				api,
+				packet_store,
+				channel_id.to_string().as_bytes().to_vec(),
+				port_id.as_bytes().to_vec(),
+				sequence,
+			)?;
 			let packet = packets.get(0)?.clone();
 			let packet: Packet = packet.into();
 			ev.packet = packet;
@@ -74,15 +178,14 @@ where
 			let channel_id = ev.dst_channel_id();
 			let port_id = ev.dst_port_id();
 			let sequence = u64::from(ev.packet.sequence);
-			let packets: Vec<ibc_primitives::PacketInfo> = api
-				.query_recv_packet_info(
-					at,
-					channel_id.to_string().as_bytes().to_vec(),
-					port_id.as_bytes().to_vec(),
-					vec![sequence],
-				)
-				.ok()
-				.flatten()?;
+			let packets = query_recv_packet_info::<C, Block, AssetId>(
+				at,
+				api,
+				packet_store,
+				channel_id.to_string().as_bytes().to_vec(),
+				port_id.as_bytes().to_vec(),
+				sequence,
+			)?;
 			let packet_info = packets.get(0)?.clone();
 			ev.ack = packet_info.ack.clone()?;
 			ev.packet = packet_info.into();
@@ -92,15 +195,14 @@ where
 			let channel_id = ev.src_channel_id();
 			let port_id = ev.src_port_id();
 			let sequence = u64::from(ev.packet.sequence);
-			let packets: Vec<ibc_primitives::PacketInfo> = api
-				.query_send_packet_info(
-					at,
-					channel_id.to_string().as_bytes().to_vec(),
-					port_id.as_bytes().to_vec(),
-					vec![sequence],
-				)
-				.ok()
-				.flatten()?;
+			let packets = query_send_packet_info::<C, Block, AssetId>(
+				at,
+				api,
+				packet_store,
+				channel_id.to_string().as_bytes().to_vec(),
+				port_id.as_bytes().to_vec(),
+				sequence,
+			)?;
 			let packet = packets.get(0)?.clone();
 			let packet: Packet = packet.into();
 			ev.packet = packet;
@@ -110,15 +212,14 @@ where
 			let channel_id = ev.src_channel_id();
 			let port_id = ev.src_port_id();
 			let sequence = u64::from(ev.packet.sequence);
-			let packets: Vec<ibc_primitives::PacketInfo> = api
-				.query_send_packet_info(
-					at,
-					channel_id.to_string().as_bytes().to_vec(),
-					port_id.as_bytes().to_vec(),
-					vec![sequence],
-				)
-				.ok()
-				.flatten()?;
+			let packets = query_send_packet_info::<C, Block, AssetId>(
+				at,
+				api,
+				packet_store,
+				channel_id.to_string().as_bytes().to_vec(),
+				port_id.as_bytes().to_vec(),
+				sequence,
+			)?;
 			let packet = packets.get(0)?.clone();
 			let packet: Packet = packet.into();
 			ev.packet = packet;
@@ -128,15 +229,14 @@ where
 			let channel_id = ev.src_channel_id();
 			let port_id = ev.src_port_id();
 			let sequence = u64::from(ev.packet.sequence);
-			let packets: Vec<ibc_primitives::PacketInfo> = api
-				.query_send_packet_info(
-					at,
-					channel_id.to_string().as_bytes().to_vec(),
-					port_id.as_bytes().to_vec(),
-					vec![sequence],
-				)
-				.ok()
-				.flatten()?;
+			let packets = query_send_packet_info::<C, Block, AssetId>(
+				at,
+				api,
+				packet_store,
+				channel_id.to_string().as_bytes().to_vec(),
+				port_id.as_bytes().to_vec(),
+				sequence,
+			)?;
 			let packet = packets.get(0)?.clone();
 			let packet: Packet = packet.into();
 			ev.packet = packet;
@@ -149,3 +249,122 @@ where
 		_ => Some(event),
 	}
 }
+
+fn query_send_packet_info<C, Block, AssetId>(
+	at: &BlockId<Block>,
+	api: &ApiRef<<C as ProvideRuntimeApi<Block>>::Api>,
+	packet_store: Option<&dyn PacketStore>,
+	channel_id: Vec<u8>,
+	port_id: Vec<u8>,
+	sequence: u64,
+) -> Option<Vec<ibc_primitives::PacketInfo>>
+where
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: IbcRuntimeApi<Block, AssetId>,
+	Block: BlockT,
+	AssetId: codec::Codec,
+{
+	match packet_store {
+		Some(store) => Some(store.send_packets(&channel_id, &port_id, &[sequence])),
+		None => api
+			.query_send_packet_info(at, channel_id, port_id, vec![sequence])
+			.ok()
+			.flatten(),
+	}
+}
+
+fn query_recv_packet_info<C, Block, AssetId>(
+	at: &BlockId<Block>,
+	api: &ApiRef<<C as ProvideRuntimeApi<Block>>::Api>,
+	packet_store: Option<&dyn PacketStore>,
+	channel_id: Vec<u8>,
+	port_id: Vec<u8>,
+	sequence: u64,
+) -> Option<Vec<ibc_primitives::PacketInfo>>
+where
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: IbcRuntimeApi<Block, AssetId>,
+	Block: BlockT,
+	AssetId: codec::Codec,
+{
+	match packet_store {
+		Some(store) => Some(store.recv_packets(&channel_id, &port_id, &[sequence])),
+		None => api
+			.query_recv_packet_info(at, channel_id, port_id, vec![sequence])
+			.ok()
+			.flatten(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ibc::{
+		core::{
+			ics02_client::events::{Attributes as ClientAttributes, UpdateClient},
+			ics04_channel::events::SendPacket,
+			ics24_host::identifier::{ChannelId, ClientId, PortId},
+		},
+		Height,
+	};
+	use std::str::FromStr;
+
+	fn send_packet() -> RawIbcEvent {
+		RawIbcEvent::SendPacket(SendPacket {
+			height: Height::new(0, 10),
+			packet: Packet {
+				sequence: 1.into(),
+				source_port: PortId::transfer(),
+				source_channel: ChannelId::new(0),
+				destination_port: PortId::transfer(),
+				destination_channel: ChannelId::new(1),
+				..Default::default()
+			},
+		})
+	}
+
+	fn update_client() -> RawIbcEvent {
+		RawIbcEvent::UpdateClient(UpdateClient {
+			common: ClientAttributes {
+				height: Height::new(0, 10),
+				client_id: ClientId::from_str("10-grandpa-0").unwrap(),
+				client_type: "10-grandpa".to_string(),
+				consensus_height: Height::new(0, 5),
+			},
+			header: None,
+		})
+	}
+
+	#[test]
+	fn should_filter_events() {
+		assert!(EventFilter::default().matches(&send_packet()));
+		assert!(EventFilter::default().matches(&update_client()));
+
+		let filter = EventFilter {
+			event_types: Some(vec!["send_packet".to_string()]),
+			..Default::default()
+		};
+		assert!(filter.matches(&send_packet()));
+		assert!(!filter.matches(&update_client()));
+
+		// packets match on either end of the channel
+		let filter =
+			EventFilter { channel_id: Some("channel-1".to_string()), ..Default::default() };
+		assert!(filter.matches(&send_packet()));
+		assert!(!filter.matches(&update_client()));
+		let filter = EventFilter {
+			port_id: Some("transfer".to_string()),
+			channel_id: Some("channel-2".to_string()),
+			..Default::default()
+		};
+		assert!(!filter.matches(&send_packet()));
+
+		let filter =
+			EventFilter { client_id: Some("10-grandpa-0".to_string()), ..Default::default() };
+		assert!(filter.matches(&update_client()));
+		assert!(!filter.matches(&send_packet()));
+		let filter =
+			EventFilter { client_id: Some("10-grandpa-1".to_string()), ..Default::default() };
+		assert!(!filter.matches(&update_client()));
+	}
+}
@@ -0,0 +1,1128 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! gRPC query server.
+//! Serves the ibc-go `Query` services of the client, connection, channel and transfer modules on
+//! top of the [`IbcApiServer`] implementation, so tooling built for Cosmos chains can query a
+//! pallet-ibc chain. Like in the Cosmos SDK, the height to query at is read from the
+//! `x-cosmos-block-height` request metadata and defaults to the latest block.
+
+use crate::{IbcApiServer, IbcRpcHandler};
+use codec::Decode;
+use ibc::{
+	applications::transfer::PrefixedDenom,
+	core::ics24_host::identifier::{ChannelId, PortId},
+};
+use ibc_primitives::get_channel_escrow_address;
+use ibc_proto::{
+	cosmos::base::query::v1beta1::PageRequest,
+	ibc::{
+		applications::transfer::v1::{
+			query_server::{Query as TransferQuery, QueryServer as TransferQueryServer},
+			DenomTrace, QueryDenomHashRequest, QueryDenomHashResponse, QueryDenomTraceRequest,
+			QueryDenomTraceResponse, QueryDenomTracesRequest, QueryDenomTracesResponse,
+			QueryEscrowAddressRequest, QueryEscrowAddressResponse, QueryParamsRequest,
+			QueryParamsResponse,
+		},
+		core::{
+			channel::v1::{
+				query_server::{Query as ChannelQuery, QueryServer as ChannelQueryServer},
+				QueryChannelClientStateRequest, QueryChannelClientStateResponse,
+				QueryChannelConsensusStateRequest, QueryChannelConsensusStateResponse,
+				QueryChannelRequest, QueryChannelResponse, QueryChannelsRequest,
+				QueryChannelsResponse, QueryConnectionChannelsRequest,
+				QueryConnectionChannelsResponse, QueryNextSequenceReceiveRequest,
+				QueryNextSequenceReceiveResponse, QueryPacketAcknowledgementRequest,
+				QueryPacketAcknowledgementResponse, QueryPacketAcknowledgementsRequest,
+				QueryPacketAcknowledgementsResponse, QueryPacketCommitmentRequest,
+				QueryPacketCommitmentResponse, QueryPacketCommitmentsRequest,
+				QueryPacketCommitmentsResponse, QueryPacketReceiptRequest,
+				QueryPacketReceiptResponse, QueryUnreceivedAcksRequest,
+				QueryUnreceivedAcksResponse, QueryUnreceivedPacketsRequest,
+				QueryUnreceivedPacketsResponse,
+			},
+			client::v1::{
+				query_server::{Query as ClientQuery, QueryServer as ClientQueryServer},
+				Height, IdentifiedClientState, Params, QueryClientParamsRequest,
+				QueryClientParamsResponse, QueryClientStateRequest, QueryClientStateResponse,
+				QueryClientStatesRequest, QueryClientStatesResponse, QueryClientStatusRequest,
+				QueryClientStatusResponse, QueryConsensusStateHeightsRequest,
+				QueryConsensusStateHeightsResponse, QueryConsensusStateRequest,
+				QueryConsensusStateResponse, QueryConsensusStatesRequest,
+				QueryConsensusStatesResponse, QueryUpgradedClientStateRequest,
+				QueryUpgradedClientStateResponse, QueryUpgradedConsensusStateRequest,
+				QueryUpgradedConsensusStateResponse,
+			},
+			connection::v1::{
+				query_server::{Query as ConnectionQuery, QueryServer as ConnectionQueryServer},
+				QueryClientConnectionsRequest, QueryClientConnectionsResponse,
+				QueryConnectionClientStateRequest, QueryConnectionClientStateResponse,
+				QueryConnectionConsensusStateRequest, QueryConnectionConsensusStateResponse,
+				QueryConnectionRequest, QueryConnectionResponse, QueryConnectionsRequest,
+				QueryConnectionsResponse,
+			},
+		},
+	},
+};
+use ibc_runtime_api::IbcRuntimeApi;
+use jsonrpsee::core::Error as RpcError;
+use sc_client_api::{BlockBackend, BlockchainEvents, ProofProvider};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::hashing::sha2_256;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, UniqueSaturatedInto},
+};
+use std::{marker::PhantomData, net::SocketAddr, str::FromStr, sync::Arc};
+use tonic::{
+	metadata::{MetadataMap, MetadataValue},
+	transport::Server,
+	Request, Response, Status,
+};
+
+/// Request metadata holding the height to query at, set on responses to the height queried at.
+pub const BLOCK_HEIGHT_METADATA_KEY: &str = "x-cosmos-block-height";
+
+/// Allowed client type standing for any client type, as in ibc-go client params.
+const ALLOW_ALL_CLIENTS: &str = "*";
+
+/// Number of denom traces fetched per runtime call when looking up a denom trace by hash.
+const DENOM_TRACES_PAGE_SIZE: u64 = 100;
+
+/// Maximum number of pages of denom traces scanned when looking up a denom trace by hash, bounding
+/// the runtime calls a single query makes. Traces beyond it can be found with `DenomTraces`.
+const MAX_DENOM_TRACES_PAGES: usize = 10;
+
+type BlockNumber<Block> = <<Block as BlockT>::Header as HeaderT>::Number;
+
+/// Serve the ibc-go query services on `addr` until the server fails.
+pub async fn serve<C, Block, AssetId>(
+	addr: SocketAddr,
+	rpc: IbcRpcHandler<C, Block>,
+) -> Result<(), tonic::transport::Error>
+where
+	Block: BlockT,
+	C: Send
+		+ Sync
+		+ 'static
+		+ ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ ProofProvider<Block>
+		+ BlockBackend<Block>
+		+ BlockchainEvents<Block>,
+	C::Api: IbcRuntimeApi<Block, AssetId>,
+	AssetId: codec::Codec + Send + Sync + 'static,
+{
+	let service = Arc::new(IbcGrpcService::<C, Block, AssetId>::new(rpc));
+	Server::builder()
+		.add_service(ClientQueryServer::from_arc(service.clone()))
+		.add_service(ConnectionQueryServer::from_arc(service.clone()))
+		.add_service(ChannelQueryServer::from_arc(service.clone()))
+		.add_service(TransferQueryServer::from_arc(service))
+		.serve(addr)
+		.await
+}
+
+/// Implementation of the ibc-go gRPC query services.
+pub struct IbcGrpcService<C, Block, AssetId> {
+	rpc: Arc<IbcRpcHandler<C, Block>>,
+	_marker: PhantomData<AssetId>,
+}
+
+impl<C, Block, AssetId> Clone for IbcGrpcService<C, Block, AssetId> {
+	fn clone(&self) -> Self {
+		Self { rpc: self.rpc.clone(), _marker: Default::default() }
+	}
+}
+
+impl<C, Block, AssetId> IbcGrpcService<C, Block, AssetId>
+where
+	Block: BlockT,
+	C: Send
+		+ Sync
+		+ 'static
+		+ ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ ProofProvider<Block>
+		+ BlockBackend<Block>
+		+ BlockchainEvents<Block>,
+	C::Api: IbcRuntimeApi<Block, AssetId>,
+	AssetId: codec::Codec + Send + Sync + 'static,
+{
+	/// Create a service answering queries with `rpc`
+	pub fn new(rpc: IbcRpcHandler<C, Block>) -> Self {
+		Self { rpc: Arc::new(rpc), _marker: Default::default() }
+	}
+
+	fn rpc(&self) -> &impl IbcApiServer<BlockNumber<Block>, Block::Hash, AssetId> {
+		&*self.rpc
+	}
+
+	/// Runs `query` on the blocking thread pool, the rpc methods call into the runtime
+	/// synchronously and would otherwise stall the tasks of the gRPC server.
+	async fn run_blocking<R, F>(&self, query: F) -> Result<R, Status>
+	where
+		R: Send + 'static,
+		F: FnOnce(&Self) -> Result<R, Status> + Send + 'static,
+	{
+		let service = self.clone();
+		tokio::task::spawn_blocking(move || query(&service))
+			.await
+			.map_err(|e| Status::internal(format!("Query failed to complete: {}", e)))?
+	}
+
+	/// The height requested in the request metadata or the latest height.
+	fn query_height<T>(&self, request: &Request<T>) -> Result<u32, Status> {
+		match requested_height(request.metadata())? {
+			Some(height) => Ok(height),
+			None => self
+				.rpc()
+				.query_latest_height()
+				.map(UniqueSaturatedInto::unique_saturated_into)
+				.map_err(into_status),
+		}
+	}
+
+	/// The ibc height of the block at `height`.
+	fn ibc_height(&self, height: u32) -> Result<Height, Status> {
+		let para_id = self
+			.rpc
+			.client
+			.runtime_api()
+			.para_id(&BlockId::Number(height.into()))
+			.map_err(|_| Status::internal("Error getting para id"))?;
+		Ok(Height { revision_number: para_id.into(), revision_height: height.into() })
+	}
+
+	/// Looks a denom trace up by the hash of its full path.
+	fn denom_trace_by_hash(&self, hash: &str) -> Result<Option<DenomTrace>, Status> {
+		find_denom_trace(hash, |key| {
+			self.rpc()
+				.query_denom_traces(key, None, Some(DENOM_TRACES_PAGE_SIZE), false)
+				.map_err(into_status)
+		})
+	}
+}
+
+/// Scans the pages of denom traces returned by `fetch_page`, starting from the given key, for
+/// the denom trace with the given hash. Gives up after [`MAX_DENOM_TRACES_PAGES`] pages.
+fn find_denom_trace<AssetId: Decode>(
+	hash: &str,
+	mut fetch_page: impl FnMut(Option<AssetId>) -> Result<QueryDenomTracesResponse, Status>,
+) -> Result<Option<DenomTrace>, Status> {
+	let mut key = None;
+	for _ in 0..MAX_DENOM_TRACES_PAGES {
+		let page = fetch_page(key)?;
+		if let Some(denom_trace) = page
+			.denom_traces
+			.into_iter()
+			.find(|denom_trace| denom_hash(&full_denom_path(denom_trace)) == hash)
+		{
+			return Ok(Some(denom_trace))
+		}
+		key = match page.pagination {
+			Some(pagination) if !pagination.next_key.is_empty() =>
+				Some(AssetId::decode(&mut &*pagination.next_key).map_err(|_| {
+					Status::internal("Failed to decode denom traces pagination key")
+				})?),
+			_ => return Ok(None),
+		};
+	}
+	Err(Status::resource_exhausted(format!(
+		"Denom trace not found in the first {} denom traces, page through DenomTraces instead",
+		MAX_DENOM_TRACES_PAGES as u64 * DENOM_TRACES_PAGE_SIZE
+	)))
+}
+
+/// The key, offset and limit of the denom traces page requested with `pagination`.
+fn denom_traces_page<AssetId: Decode>(
+	pagination: &PageRequest,
+) -> Result<(Option<AssetId>, Option<u32>, Option<u64>), Status> {
+	let key = if pagination.key.is_empty() {
+		None
+	} else {
+		Some(
+			AssetId::decode(&mut &*pagination.key)
+				.map_err(|_| Status::invalid_argument("Invalid pagination key"))?,
+		)
+	};
+	let offset = if pagination.offset == 0 {
+		None
+	} else {
+		Some(
+			pagination
+				.offset
+				.try_into()
+				.map_err(|_| Status::invalid_argument("Invalid pagination offset"))?,
+		)
+	};
+	let limit = if pagination.limit == 0 { None } else { Some(pagination.limit) };
+	Ok((key, offset, limit))
+}
+
+/// The height set in the request metadata, if any.
+fn requested_height(metadata: &MetadataMap) -> Result<Option<u32>, Status> {
+	metadata
+		.get(BLOCK_HEIGHT_METADATA_KEY)
+		.map(|height| {
+			height
+				.to_str()
+				.ok()
+				.and_then(|height| height.parse().ok())
+				.ok_or_else(|| Status::invalid_argument("Invalid block height"))
+		})
+		.transpose()
+}
+
+fn into_status(e: RpcError) -> Status {
+	Status::internal(e.to_string())
+}
+
+/// Wraps `message` in a response carrying the height it was queried at.
+fn response<T>(message: T, height: u32) -> Response<T> {
+	let mut response = Response::new(message);
+	response
+		.metadata_mut()
+		.insert(BLOCK_HEIGHT_METADATA_KEY, MetadataValue::from(height));
+	response
+}
+
+fn full_denom_path(denom_trace: &DenomTrace) -> String {
+	if denom_trace.path.is_empty() {
+		denom_trace.base_denom.clone()
+	} else {
+		format!("{}/{}", denom_trace.path, denom_trace.base_denom)
+	}
+}
+
+/// Upper case hex encoded sha256 hash of the full denom path, as used in ibc-go denoms.
+fn denom_hash(full_denom_path: &str) -> String {
+	sha2_256(full_denom_path.as_bytes())
+		.iter()
+		.map(|byte| format!("{:02X}", byte))
+		.collect()
+}
+
+#[tonic::async_trait]
+impl<C, Block, AssetId> ClientQuery for IbcGrpcService<C, Block, AssetId>
+where
+	Block: BlockT,
+	C: Send
+		+ Sync
+		+ 'static
+		+ ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ ProofProvider<Block>
+		+ BlockBackend<Block>
+		+ BlockchainEvents<Block>,
+	C::Api: IbcRuntimeApi<Block, AssetId>,
+	AssetId: codec::Codec + Send + Sync + 'static,
+{
+	async fn client_state(
+		&self,
+		request: Request<QueryClientStateRequest>,
+	) -> Result<Response<QueryClientStateResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_client_state(height, request.client_id)
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn client_states(
+		&self,
+		request: Request<QueryClientStatesRequest>,
+	) -> Result<Response<QueryClientStatesResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_clients(request.pagination, Some(height))
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn consensus_state(
+		&self,
+		request: Request<QueryConsensusStateRequest>,
+	) -> Result<Response<QueryConsensusStateResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_client_consensus_state(
+					Some(height),
+					request.client_id,
+					request.revision_height,
+					request.revision_number,
+					request.latest_height,
+				)
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn consensus_states(
+		&self,
+		request: Request<QueryConsensusStatesRequest>,
+	) -> Result<Response<QueryConsensusStatesResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_consensus_states(height, request.client_id, request.pagination)
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn consensus_state_heights(
+		&self,
+		request: Request<QueryConsensusStateHeightsRequest>,
+	) -> Result<Response<QueryConsensusStateHeightsResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_consensus_states(height, request.client_id, request.pagination)
+				.map_err(into_status)?;
+			let consensus_state_heights = result
+				.consensus_states
+				.into_iter()
+				.filter_map(|consensus_state| consensus_state.height)
+				.collect();
+			Ok(response(
+				QueryConsensusStateHeightsResponse {
+					consensus_state_heights,
+					pagination: result.pagination,
+				},
+				height,
+			))
+		})
+		.await
+	}
+
+	async fn client_status(
+		&self,
+		_request: Request<QueryClientStatusRequest>,
+	) -> Result<Response<QueryClientStatusResponse>, Status> {
+		Err(Status::unimplemented("Client status is not exposed by the runtime"))
+	}
+
+	async fn client_params(
+		&self,
+		request: Request<QueryClientParamsRequest>,
+	) -> Result<Response<QueryClientParamsResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let params = service.rpc().query_client_params().map_err(into_status)?;
+			// ibc-go has no notion of per chain id restrictions, only the client types are listed
+			let allowed_clients = match params.allowed_clients {
+				Some(allowed_clients) => {
+					let mut client_types: Vec<_> =
+						allowed_clients.into_iter().map(|allowed| allowed.client_type).collect();
+					client_types.sort();
+					client_types.dedup();
+					client_types
+				},
+				None => vec![ALLOW_ALL_CLIENTS.to_string()],
+			};
+			Ok(response(
+				QueryClientParamsResponse { params: Some(Params { allowed_clients }) },
+				height,
+			))
+		})
+		.await
+	}
+
+	async fn upgraded_client_state(
+		&self,
+		_request: Request<QueryUpgradedClientStateRequest>,
+	) -> Result<Response<QueryUpgradedClientStateResponse>, Status> {
+		Err(Status::unimplemented("Client upgrades are not supported"))
+	}
+
+	async fn upgraded_consensus_state(
+		&self,
+		_request: Request<QueryUpgradedConsensusStateRequest>,
+	) -> Result<Response<QueryUpgradedConsensusStateResponse>, Status> {
+		Err(Status::unimplemented("Client upgrades are not supported"))
+	}
+}
+
+#[tonic::async_trait]
+impl<C, Block, AssetId> ConnectionQuery for IbcGrpcService<C, Block, AssetId>
+where
+	Block: BlockT,
+	C: Send
+		+ Sync
+		+ 'static
+		+ ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ ProofProvider<Block>
+		+ BlockBackend<Block>
+		+ BlockchainEvents<Block>,
+	C::Api: IbcRuntimeApi<Block, AssetId>,
+	AssetId: codec::Codec + Send + Sync + 'static,
+{
+	async fn connection(
+		&self,
+		request: Request<QueryConnectionRequest>,
+	) -> Result<Response<QueryConnectionResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_connection(height, request.connection_id)
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn connections(
+		&self,
+		request: Request<QueryConnectionsRequest>,
+	) -> Result<Response<QueryConnectionsResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_connections(request.pagination, Some(height))
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn client_connections(
+		&self,
+		request: Request<QueryClientConnectionsRequest>,
+	) -> Result<Response<QueryClientConnectionsResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_client_connections(height, request.client_id)
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn connection_client_state(
+		&self,
+		request: Request<QueryConnectionClientStateRequest>,
+	) -> Result<Response<QueryConnectionClientStateResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let connection = service
+				.rpc()
+				.query_connection(height, request.connection_id)
+				.map_err(into_status)?
+				.connection
+				.ok_or_else(|| Status::not_found("Connection not found"))?;
+			let result = service
+				.rpc()
+				.query_client_state(height, connection.client_id.clone())
+				.map_err(into_status)?;
+			Ok(response(
+				QueryConnectionClientStateResponse {
+					identified_client_state: Some(IdentifiedClientState {
+						client_id: connection.client_id,
+						client_state: result.client_state,
+					}),
+					proof: result.proof,
+					proof_height: result.proof_height,
+				},
+				height,
+			))
+		})
+		.await
+	}
+
+	async fn connection_consensus_state(
+		&self,
+		request: Request<QueryConnectionConsensusStateRequest>,
+	) -> Result<Response<QueryConnectionConsensusStateResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let connection = service
+				.rpc()
+				.query_connection(height, request.connection_id)
+				.map_err(into_status)?
+				.connection
+				.ok_or_else(|| Status::not_found("Connection not found"))?;
+			let result = service
+				.rpc()
+				.query_client_consensus_state(
+					Some(height),
+					connection.client_id.clone(),
+					request.revision_height,
+					request.revision_number,
+					false,
+				)
+				.map_err(into_status)?;
+			Ok(response(
+				QueryConnectionConsensusStateResponse {
+					consensus_state: result.consensus_state,
+					client_id: connection.client_id,
+					proof: result.proof,
+					proof_height: result.proof_height,
+				},
+				height,
+			))
+		})
+		.await
+	}
+}
+
+#[tonic::async_trait]
+impl<C, Block, AssetId> ChannelQuery for IbcGrpcService<C, Block, AssetId>
+where
+	Block: BlockT,
+	C: Send
+		+ Sync
+		+ 'static
+		+ ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ ProofProvider<Block>
+		+ BlockBackend<Block>
+		+ BlockchainEvents<Block>,
+	C::Api: IbcRuntimeApi<Block, AssetId>,
+	AssetId: codec::Codec + Send + Sync + 'static,
+{
+	async fn channel(
+		&self,
+		request: Request<QueryChannelRequest>,
+	) -> Result<Response<QueryChannelResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_channel(height, request.channel_id, request.port_id)
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn channels(
+		&self,
+		request: Request<QueryChannelsRequest>,
+	) -> Result<Response<QueryChannelsResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_channels(request.pagination, Some(height))
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn connection_channels(
+		&self,
+		request: Request<QueryConnectionChannelsRequest>,
+	) -> Result<Response<QueryConnectionChannelsResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_connection_channels(height, request.connection, request.pagination)
+				.map_err(into_status)?;
+			Ok(response(
+				QueryConnectionChannelsResponse {
+					channels: result.channels,
+					pagination: result.pagination,
+					height: result.height,
+				},
+				height,
+			))
+		})
+		.await
+	}
+
+	async fn channel_client_state(
+		&self,
+		request: Request<QueryChannelClientStateRequest>,
+	) -> Result<Response<QueryChannelClientStateResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let identified_client_state = service
+				.rpc()
+				.query_channel_client(height, request.channel_id, request.port_id)
+				.map_err(into_status)?;
+			// the channel client query carries no proof, it's taken from the client state query
+			let result = service
+				.rpc()
+				.query_client_state(height, identified_client_state.client_id.clone())
+				.map_err(into_status)?;
+			Ok(response(
+				QueryChannelClientStateResponse {
+					identified_client_state: Some(identified_client_state),
+					proof: result.proof,
+					proof_height: result.proof_height,
+				},
+				height,
+			))
+		})
+		.await
+	}
+
+	async fn channel_consensus_state(
+		&self,
+		request: Request<QueryChannelConsensusStateRequest>,
+	) -> Result<Response<QueryChannelConsensusStateResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let client_id = service
+				.rpc()
+				.query_channel_client(height, request.channel_id, request.port_id)
+				.map_err(into_status)?
+				.client_id;
+			let result = service
+				.rpc()
+				.query_client_consensus_state(
+					Some(height),
+					client_id.clone(),
+					request.revision_height,
+					request.revision_number,
+					false,
+				)
+				.map_err(into_status)?;
+			Ok(response(
+				QueryChannelConsensusStateResponse {
+					consensus_state: result.consensus_state,
+					client_id,
+					proof: result.proof,
+					proof_height: result.proof_height,
+				},
+				height,
+			))
+		})
+		.await
+	}
+
+	async fn packet_commitment(
+		&self,
+		request: Request<QueryPacketCommitmentRequest>,
+	) -> Result<Response<QueryPacketCommitmentResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_packet_commitment(
+					height,
+					request.channel_id,
+					request.port_id,
+					request.sequence,
+				)
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn packet_commitments(
+		&self,
+		request: Request<QueryPacketCommitmentsRequest>,
+	) -> Result<Response<QueryPacketCommitmentsResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_packet_commitments(
+					height,
+					request.channel_id,
+					request.port_id,
+					request.pagination,
+				)
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn packet_receipt(
+		&self,
+		request: Request<QueryPacketReceiptRequest>,
+	) -> Result<Response<QueryPacketReceiptResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_packet_receipt(height, request.channel_id, request.port_id, request.sequence)
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn packet_acknowledgement(
+		&self,
+		request: Request<QueryPacketAcknowledgementRequest>,
+	) -> Result<Response<QueryPacketAcknowledgementResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_packet_acknowledgement(
+					height,
+					request.channel_id,
+					request.port_id,
+					request.sequence,
+				)
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn packet_acknowledgements(
+		&self,
+		request: Request<QueryPacketAcknowledgementsRequest>,
+	) -> Result<Response<QueryPacketAcknowledgementsResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			// as in ibc-go, the acknowledgements of the given sequences are returned without
+			// pagination when sequences are provided.
+			let sequences = request.packet_commitment_sequences;
+			let pagination = if sequences.is_empty() { request.pagination } else { None };
+			let mut result = service
+				.rpc()
+				.query_packet_acknowledgements(
+					height,
+					request.channel_id,
+					request.port_id,
+					pagination,
+				)
+				.map_err(into_status)?;
+			if !sequences.is_empty() {
+				result.acknowledgements.retain(|ack| sequences.contains(&ack.sequence));
+				result.pagination = None;
+			}
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn unreceived_packets(
+		&self,
+		request: Request<QueryUnreceivedPacketsRequest>,
+	) -> Result<Response<QueryUnreceivedPacketsResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let sequences = service
+				.rpc()
+				.query_unreceived_packets(
+					height,
+					request.channel_id,
+					request.port_id,
+					request.packet_commitment_sequences,
+				)
+				.map_err(into_status)?;
+			Ok(response(
+				QueryUnreceivedPacketsResponse {
+					sequences,
+					height: Some(service.ibc_height(height)?),
+				},
+				height,
+			))
+		})
+		.await
+	}
+
+	async fn unreceived_acks(
+		&self,
+		request: Request<QueryUnreceivedAcksRequest>,
+	) -> Result<Response<QueryUnreceivedAcksResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let sequences = service
+				.rpc()
+				.query_unreceived_acknowledgements(
+					height,
+					request.channel_id,
+					request.port_id,
+					request.packet_ack_sequences,
+				)
+				.map_err(into_status)?;
+			Ok(response(
+				QueryUnreceivedAcksResponse {
+					sequences,
+					height: Some(service.ibc_height(height)?),
+				},
+				height,
+			))
+		})
+		.await
+	}
+
+	async fn next_sequence_receive(
+		&self,
+		request: Request<QueryNextSequenceReceiveRequest>,
+	) -> Result<Response<QueryNextSequenceReceiveResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let result = service
+				.rpc()
+				.query_next_seq_recv(height, request.channel_id, request.port_id)
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+}
+
+#[tonic::async_trait]
+impl<C, Block, AssetId> TransferQuery for IbcGrpcService<C, Block, AssetId>
+where
+	Block: BlockT,
+	C: Send
+		+ Sync
+		+ 'static
+		+ ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ ProofProvider<Block>
+		+ BlockBackend<Block>
+		+ BlockchainEvents<Block>,
+	C::Api: IbcRuntimeApi<Block, AssetId>,
+	AssetId: codec::Codec + Send + Sync + 'static,
+{
+	async fn denom_trace(
+		&self,
+		request: Request<QueryDenomTraceRequest>,
+	) -> Result<Response<QueryDenomTraceResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let hash = request.hash.trim_start_matches("ibc/").to_uppercase();
+			let denom_trace = service
+				.denom_trace_by_hash(&hash)?
+				.ok_or_else(|| Status::not_found(format!("Denom trace not found for {}", hash)))?;
+			Ok(response(QueryDenomTraceResponse { denom_trace: Some(denom_trace) }, height))
+		})
+		.await
+	}
+
+	async fn denom_traces(
+		&self,
+		request: Request<QueryDenomTracesRequest>,
+	) -> Result<Response<QueryDenomTracesResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let pagination = request.into_inner().pagination.unwrap_or_default();
+			let (key, offset, limit) = denom_traces_page::<AssetId>(&pagination)?;
+			let result = service
+				.rpc()
+				.query_denom_traces(key, offset, limit, pagination.count_total)
+				.map_err(into_status)?;
+			Ok(response(result, height))
+		})
+		.await
+	}
+
+	async fn params(
+		&self,
+		_request: Request<QueryParamsRequest>,
+	) -> Result<Response<QueryParamsResponse>, Status> {
+		Err(Status::unimplemented("Transfer params are not exposed by the runtime"))
+	}
+
+	async fn denom_hash(
+		&self,
+		request: Request<QueryDenomHashRequest>,
+	) -> Result<Response<QueryDenomHashResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let denom = PrefixedDenom::from_str(&request.trace)
+				.map_err(|_| Status::invalid_argument("Invalid denom trace"))?;
+			Ok(response(QueryDenomHashResponse { hash: denom_hash(&denom.to_string()) }, height))
+		})
+		.await
+	}
+
+	async fn escrow_address(
+		&self,
+		request: Request<QueryEscrowAddressRequest>,
+	) -> Result<Response<QueryEscrowAddressResponse>, Status> {
+		self.run_blocking(move |service| {
+			let height = service.query_height(&request)?;
+			let request = request.into_inner();
+			let port_id = PortId::from_str(&request.port_id)
+				.map_err(|_| Status::invalid_argument("Invalid port id"))?;
+			let channel_id = ChannelId::from_str(&request.channel_id)
+				.map_err(|_| Status::invalid_argument("Invalid channel id"))?;
+			let escrow_address = get_channel_escrow_address(&port_id, channel_id)
+				.map_err(|_| Status::internal("Failed to derive escrow address"))?;
+			Ok(response(
+				QueryEscrowAddressResponse { escrow_address: escrow_address.to_string() },
+				height,
+			))
+		})
+		.await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use ibc_proto::cosmos::base::query::v1beta1::PageResponse;
+	use tonic::Code;
+
+	fn denom_trace(index: u64) -> DenomTrace {
+		DenomTrace {
+			path: "transfer/channel-0".to_string(),
+			base_denom: format!("denom-{}", index),
+		}
+	}
+
+	/// Pages of two denom traces each, `pages` of them, keyed by the index of their first trace.
+	fn page_of_denom_traces(key: Option<u64>, pages: u64) -> QueryDenomTracesResponse {
+		let start = key.unwrap_or_default();
+		let next = start + 2;
+		QueryDenomTracesResponse {
+			denom_traces: vec![denom_trace(start), denom_trace(start + 1)],
+			pagination: Some(PageResponse {
+				next_key: if next < pages * 2 { next.encode() } else { vec![] },
+				total: 0,
+			}),
+		}
+	}
+
+	#[test]
+	fn should_hash_denoms_like_ibc_go() {
+		let denom_trace =
+			DenomTrace { path: "transfer/channel-0".to_string(), base_denom: "uatom".to_string() };
+		assert_eq!(full_denom_path(&denom_trace), "transfer/channel-0/uatom");
+		assert_eq!(
+			denom_hash(&full_denom_path(&denom_trace)),
+			"27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+		);
+	}
+
+	#[test]
+	fn should_read_the_requested_height_from_the_metadata() {
+		let mut metadata = MetadataMap::new();
+		assert_eq!(requested_height(&metadata).unwrap(), None);
+
+		metadata.insert(BLOCK_HEIGHT_METADATA_KEY, MetadataValue::from(42u32));
+		assert_eq!(requested_height(&metadata).unwrap(), Some(42));
+
+		metadata.insert(BLOCK_HEIGHT_METADATA_KEY, MetadataValue::from_static("latest"));
+		assert_eq!(requested_height(&metadata).unwrap_err().code(), Code::InvalidArgument);
+	}
+
+	#[test]
+	fn should_label_responses_with_the_height_queried_at() {
+		let labelled = response((), 42);
+		assert_eq!(requested_height(labelled.metadata()).unwrap(), Some(42));
+	}
+
+	#[test]
+	fn should_page_through_denom_traces_to_find_a_hash() {
+		let hash = denom_hash(&full_denom_path(&denom_trace(5)));
+		let mut keys = vec![];
+		let found = find_denom_trace::<u64>(&hash, |key| {
+			keys.push(key);
+			Ok(page_of_denom_traces(key, 4))
+		})
+		.unwrap();
+		assert_eq!(found, Some(denom_trace(5)));
+		assert_eq!(keys, vec![None, Some(2), Some(4)]);
+	}
+
+	#[test]
+	fn should_stop_at_the_last_page_of_denom_traces() {
+		let hash = denom_hash("transfer/channel-0/unknown");
+		let mut fetched = 0;
+		let found = find_denom_trace::<u64>(&hash, |key| {
+			fetched += 1;
+			Ok(page_of_denom_traces(key, 3))
+		})
+		.unwrap();
+		assert_eq!(found, None);
+		assert_eq!(fetched, 3);
+	}
+
+	#[test]
+	fn should_bound_the_pages_scanned_for_a_denom_trace() {
+		let hash = denom_hash("transfer/channel-0/unknown");
+		let mut fetched = 0;
+		let err = find_denom_trace::<u64>(&hash, |key| {
+			fetched += 1;
+			Ok(page_of_denom_traces(key, u64::MAX / 4))
+		})
+		.unwrap_err();
+		assert_eq!(err.code(), Code::ResourceExhausted);
+		assert_eq!(fetched, MAX_DENOM_TRACES_PAGES);
+	}
+
+	#[test]
+	fn should_read_the_requested_denom_traces_page() {
+		assert_eq!(denom_traces_page::<u64>(&PageRequest::default()).unwrap(), (None, None, None));
+
+		let pagination =
+			PageRequest { key: 7u64.encode(), offset: 3, limit: 10, ..Default::default() };
+		assert_eq!(denom_traces_page::<u64>(&pagination).unwrap(), (Some(7), Some(3), Some(10)));
+
+		let pagination = PageRequest { key: vec![1], ..Default::default() };
+		assert_eq!(
+			denom_traces_page::<u64>(&pagination).unwrap_err().code(),
+			Code::InvalidArgument
+		);
+
+		let pagination = PageRequest { offset: u64::MAX, ..Default::default() };
+		assert_eq!(
+			denom_traces_page::<u64>(&pagination).unwrap_err().code(),
+			Code::InvalidArgument
+		);
+	}
+}
@@ -0,0 +1,258 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Packet indexer.
+//! Packet data is only written to offchain indexed storage by the runtime, which is empty on nodes
+//! that didn't have offchain indexing enabled from genesis. The indexer rebuilds the same packet
+//! info from the packet records emitted in block events and keeps it in a local database.
+use codec::{Decode, Encode};
+use futures::StreamExt;
+use ibc_primitives::{PacketInfo, PacketRecord};
+use ibc_runtime_api::IbcRuntimeApi;
+use sc_client_api::BlockchainEvents;
+use sp_api::{ApiExt, BlockId, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::offchain::OffchainStorage;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, UniqueSaturatedInto};
+use std::{
+	marker::PhantomData,
+	sync::{Arc, Mutex},
+};
+
+/// Prefix of the indexed packets in the offchain database.
+pub const PACKET_INDEX_PREFIX: &[u8] = b"ibc-rpc-packet-index";
+
+const SEND_PACKET: &[u8] = b"SEND_PACKET";
+const RECV_PACKET: &[u8] = b"RECV_PACKET";
+const ACK: &[u8] = b"ACK";
+const LAST_INDEXED_BLOCK: &[u8] = b"LAST_INDEXED_BLOCK";
+
+/// Database of indexed packets.
+pub trait PacketStore: Send + Sync {
+	/// Packets sent over the given channel and port with the given sequences
+	fn send_packets(&self, channel_id: &[u8], port_id: &[u8], seqs: &[u64]) -> Vec<PacketInfo>;
+	/// Packets received over the given channel and port with the given sequences, along with
+	/// their acknowledgements
+	fn recv_packets(&self, channel_id: &[u8], port_id: &[u8], seqs: &[u64]) -> Vec<PacketInfo>;
+	/// Writes the packet records emitted in a block and marks the block as indexed
+	fn insert_block(&self, block_number: u32, records: Vec<PacketRecord>);
+	/// The last block that was indexed
+	fn last_indexed_block(&self) -> Option<u32>;
+}
+
+/// [`PacketStore`] backed by the node's offchain database.
+pub struct OffchainPacketStore<S> {
+	storage: Mutex<S>,
+}
+
+impl<S: OffchainStorage> OffchainPacketStore<S> {
+	/// Create a packet store that writes to `storage` under [`PACKET_INDEX_PREFIX`]
+	pub fn new(storage: S) -> Self {
+		Self { storage: Mutex::new(storage) }
+	}
+
+	fn key(kind: &[u8], channel_id: &[u8], port_id: &[u8], seq: u64) -> Vec<u8> {
+		(kind, channel_id, port_id, seq).encode()
+	}
+
+	fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+		self.storage
+			.lock()
+			.expect("Packet store lock is poisoned")
+			.get(PACKET_INDEX_PREFIX, key)
+	}
+}
+
+impl<S: OffchainStorage> PacketStore for OffchainPacketStore<S> {
+	fn send_packets(&self, channel_id: &[u8], port_id: &[u8], seqs: &[u64]) -> Vec<PacketInfo> {
+		seqs.iter()
+			.filter_map(|seq| {
+				let packet = self.get(&Self::key(SEND_PACKET, channel_id, port_id, *seq))?;
+				PacketInfo::decode(&mut &*packet).ok()
+			})
+			.collect()
+	}
+
+	fn recv_packets(&self, channel_id: &[u8], port_id: &[u8], seqs: &[u64]) -> Vec<PacketInfo> {
+		seqs.iter()
+			.filter_map(|seq| {
+				let packet = self.get(&Self::key(RECV_PACKET, channel_id, port_id, *seq))?;
+				let mut packet_info = PacketInfo::decode(&mut &*packet).ok()?;
+				packet_info.ack = self.get(&Self::key(ACK, channel_id, port_id, *seq));
+				Some(packet_info)
+			})
+			.collect()
+	}
+
+	fn insert_block(&self, block_number: u32, records: Vec<PacketRecord>) {
+		let mut storage = self.storage.lock().expect("Packet store lock is poisoned");
+		for record in records {
+			let (key, value) = match record {
+				PacketRecord::Send(packet) => (
+					Self::key(
+						SEND_PACKET,
+						&packet.source_channel,
+						&packet.source_port,
+						packet.sequence,
+					),
+					packet.encode(),
+				),
+				PacketRecord::Recv(packet) => (
+					Self::key(
+						RECV_PACKET,
+						&packet.destination_channel,
+						&packet.destination_port,
+						packet.sequence,
+					),
+					packet.encode(),
+				),
+				PacketRecord::Ack { port_id, channel_id, sequence, ack } =>
+					(Self::key(ACK, &channel_id, &port_id, sequence), ack),
+			};
+			storage.set(PACKET_INDEX_PREFIX, &key, &value);
+		}
+		storage.set(PACKET_INDEX_PREFIX, LAST_INDEXED_BLOCK, &block_number.encode());
+	}
+
+	fn last_indexed_block(&self) -> Option<u32> {
+		self.get(LAST_INDEXED_BLOCK).and_then(|number| u32::decode(&mut &*number).ok())
+	}
+}
+
+/// Indexes the packet records of finalized blocks into a [`PacketStore`].
+pub struct PacketIndexer<C, Block, AssetId> {
+	client: Arc<C>,
+	store: Arc<dyn PacketStore>,
+	_marker: PhantomData<(Block, AssetId)>,
+}
+
+impl<C, Block, AssetId> PacketIndexer<C, Block, AssetId>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + BlockchainEvents<Block> + Send + Sync,
+	C::Api: IbcRuntimeApi<Block, AssetId>,
+	AssetId: codec::Codec,
+{
+	/// Create a new indexer writing to `store`
+	pub fn new(client: Arc<C>, store: Arc<dyn PacketStore>) -> Self {
+		Self { client, store, _marker: Default::default() }
+	}
+
+	/// Index the packet records emitted in the finalized block at `block_number`, returns `false`
+	/// if the block's runtime predates packet records so there's nothing to index.
+	pub fn index_block(&self, block_number: u32) -> Result<bool, sp_blockchain::Error> {
+		let hash = self
+			.client
+			.hash(block_number.into())?
+			.ok_or_else(|| sp_blockchain::Error::UnknownBlock(block_number.to_string()))?;
+		let at = BlockId::Hash(hash);
+		let runtime_api = self.client.runtime_api();
+		let records_packets = runtime_api
+			.has_api_with::<dyn IbcRuntimeApi<Block, AssetId>, _>(&at, |version| version >= 2)?;
+		if !records_packets {
+			return Ok(false)
+		}
+		let records = runtime_api.block_packet_records(&at)?;
+		self.store.insert_block(block_number, records);
+		Ok(true)
+	}
+
+	/// Index every block from `from` up to the latest finalized block.
+	/// Blocks whose runtime doesn't emit packet records are skipped, the packets they sent or
+	/// received can't be indexed.
+	pub fn backfill(&self, from: u32) -> u32 {
+		let finalized = self.client.info().finalized_number.unique_saturated_into();
+		self.index_range(from, finalized)
+	}
+
+	/// Backfill from `from`, or from the block after the last indexed one if the index is
+	/// already past it, then index blocks as they get finalized.
+	pub async fn run(self, from: u32) {
+		// subscribe before backfilling so no finalized block is missed in between
+		let mut finality_notifications = self.client.finality_notification_stream();
+		let from = self.store.last_indexed_block().map_or(from, |last| from.max(last + 1));
+		let mut next = self.backfill(from);
+		while let Some(notification) = finality_notifications.next().await {
+			let finalized = (*notification.header.number()).unique_saturated_into();
+			next = self.index_range(next, finalized);
+		}
+	}
+
+	/// Index blocks `from..=to` and return the next block to index.
+	fn index_range(&self, from: u32, to: u32) -> u32 {
+		let mut skipped: Option<(u32, u32)> = None;
+		for block_number in from..=to {
+			match self.index_block(block_number) {
+				Ok(true) => {},
+				Ok(false) =>
+					skipped = Some((skipped.map_or(block_number, |(first, _)| first), block_number)),
+				Err(e) => {
+					log::warn!(target: "ibc-rpc", "Failed to index packets of block {}: {:?}", block_number, e);
+				},
+			}
+		}
+		if let Some((first, last)) = skipped {
+			log::warn!(
+				target: "ibc-rpc",
+				"Packets of blocks {}..={} can't be indexed, their runtime doesn't record packets",
+				first,
+				last
+			);
+		}
+		from.max(to.saturating_add(1))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::offchain::storage::InMemOffchainStorage;
+
+	fn packet(sequence: u64) -> PacketInfo {
+		PacketInfo {
+			height: Some(10),
+			sequence,
+			source_port: b"transfer".to_vec(),
+			source_channel: b"channel-0".to_vec(),
+			destination_port: b"transfer".to_vec(),
+			destination_channel: b"channel-1".to_vec(),
+			data: vec![sequence as u8],
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn should_index_packets_and_acknowledgements() {
+		let store = OffchainPacketStore::new(InMemOffchainStorage::default());
+		assert_eq!(store.last_indexed_block(), None);
+
+		store.insert_block(1, vec![PacketRecord::Send(packet(1)), PacketRecord::Recv(packet(2))]);
+		store.insert_block(
+			2,
+			vec![PacketRecord::Ack {
+				port_id: b"transfer".to_vec(),
+				channel_id: b"channel-1".to_vec(),
+				sequence: 2,
+				ack: vec![1],
+			}],
+		);
+
+		assert_eq!(store.last_indexed_block(), Some(2));
+		assert_eq!(store.send_packets(b"channel-0", b"transfer", &[1, 2]), vec![packet(1)]);
+		assert_eq!(
+			store.recv_packets(b"channel-1", b"transfer", &[1, 2]),
+			vec![PacketInfo { ack: Some(vec![1]), ..packet(2) }]
+		);
+	}
+}
@@ -30,8 +30,12 @@ use ibc::{
 use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState};
 use std::{collections::HashMap, fmt::Display, str::FromStr, sync::Arc};
 
+use futures::{stream, FutureExt, StreamExt};
 use ibc_proto::{
-	cosmos::base::{query::v1beta1::PageResponse, v1beta1::Coin},
+	cosmos::base::{
+		query::v1beta1::{PageRequest, PageResponse},
+		v1beta1::Coin,
+	},
 	ibc::{
 		applications::transfer::v1::{QueryDenomTraceResponse, QueryDenomTracesResponse},
 		core::{
@@ -42,11 +46,13 @@ use ibc_proto::{
 				QueryPacketCommitmentsResponse, QueryPacketReceiptResponse,
 			},
 			client::v1::{
-				Height, IdentifiedClientState, QueryClientStateResponse,
-				QueryConsensusStateResponse,
+				ConsensusStateWithHeight, Height, IdentifiedClientState, QueryClientStateResponse,
+				QueryClientStatesResponse, QueryConsensusStateResponse,
+				QueryConsensusStatesResponse,
 			},
 			connection::v1::{
-				IdentifiedConnection, QueryConnectionResponse, QueryConnectionsResponse,
+				IdentifiedConnection, QueryClientConnectionsResponse, QueryConnectionResponse,
+				QueryConnectionsResponse,
 			},
 		},
 	},
@@ -55,22 +61,27 @@ use ibc_runtime_api::IbcRuntimeApi;
 use jsonrpsee::{
 	core::{Error as RpcError, RpcResult as Result},
 	proc_macros::rpc,
-	types::{error::CallError, ErrorObject},
+	types::{error::CallError, ErrorObject, SubscriptionResult},
+	SubscriptionSink,
 };
 use pallet_ibc::events::IbcEvent;
 use sc_chain_spec::Properties;
-use sc_client_api::{BlockBackend, ProofProvider};
+use sc_client_api::{BlockBackend, BlockchainEvents, ProofProvider};
 use serde::{Deserialize, Serialize};
-use sp_api::ProvideRuntimeApi;
+use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
-use sp_core::{blake2_256, storage::ChildInfo};
+use sp_core::{blake2_256, storage::ChildInfo, traits::SpawnNamed};
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block as BlockT, Header as HeaderT},
+	traits::{Block as BlockT, Header as HeaderT, UniqueSaturatedInto},
 };
 use tendermint_proto::Protobuf;
 pub mod events;
-use events::filter_map_pallet_event;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod indexer;
+use events::{block_events, EventFilter};
+use indexer::PacketStore;
 
 /// Connection handshake proof
 #[derive(Serialize, Deserialize)]
@@ -102,6 +113,35 @@ pub struct HeightAndTimestamp {
 	pub timestamp: u64,
 }
 
+/// Spam protection deposit held for a client or connection
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpamDeposit {
+	/// Hex encoded account id of the depositor
+	pub depositor: String,
+	/// Amount reserved
+	pub amount: String,
+	/// Block number at which the deposit was reserved
+	pub reserved_at: u32,
+	/// Block number at which the deposit is slashed if the connection hasn't opened
+	pub expires_at: u32,
+}
+
+/// A light client type that may be created or upgraded to
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AllowedClient {
+	/// Client type, eg. `10-grandpa`
+	pub client_type: String,
+	/// Chain ids the client may track, any chain id is allowed if empty
+	pub chain_ids: Vec<String>,
+}
+
+/// Client params of the chain
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClientParams {
+	/// Light clients that can be created or upgraded to, `None` if any client is allowed
+	pub allowed_clients: Option<Vec<AllowedClient>>,
+}
+
 impl<Hash: std::fmt::Debug> Display for BlockNumberOrHash<Hash> {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
@@ -147,6 +187,17 @@ pub struct PacketInfo {
 	pub ack: Option<Vec<u8>>,
 }
 
+/// Packet receipts of a channel
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QueryPacketReceiptsResponse {
+	/// Packet receipts, `data` is the receipt written for the packet
+	pub receipts: Vec<PacketState>,
+	/// Pagination response
+	pub pagination: Option<PageResponse>,
+	/// Height at which the receipts were queried
+	pub height: Option<Height>,
+}
+
 /// IBC RPC methods.
 #[rpc(client, server)]
 pub trait IbcApi<BlockNumber, Hash, AssetId>
@@ -225,9 +276,34 @@ where
 	#[method(name = "ibc_queryUpgradedConnectionState")]
 	fn query_upgraded_cons_state(&self, height: u32) -> Result<QueryConsensusStateResponse>;
 
-	/// Query all client states
+	/// Query a page of client states, all client states if `pagination` is omitted.
+	/// Queries the latest block if `height` is omitted.
 	#[method(name = "ibc_queryClients")]
-	fn query_clients(&self) -> Result<Vec<IdentifiedClientState>>;
+	fn query_clients(
+		&self,
+		pagination: Option<PageRequest>,
+		height: Option<u32>,
+	) -> Result<QueryClientStatesResponse>;
+
+	/// Query a page of the consensus states stored for a client
+	#[method(name = "ibc_queryConsensusStates")]
+	fn query_consensus_states(
+		&self,
+		height: u32,
+		client_id: String,
+		pagination: Option<PageRequest>,
+	) -> Result<QueryConsensusStatesResponse>;
+
+	/// Query the ids of all connections associated with a client.
+	///
+	/// The response is unproven, `proof` is always empty: client connections aren't committed to
+	/// the ibc child trie, so there's nothing a counterparty could verify a proof against.
+	#[method(name = "ibc_queryClientConnections")]
+	fn query_client_connections(
+		&self,
+		height: u32,
+		client_id: String,
+	) -> Result<QueryClientConnectionsResponse>;
 
 	/// Query a connection state
 	#[method(name = "ibc_queryConnection")]
@@ -237,9 +313,14 @@ where
 		connection_id: String,
 	) -> Result<QueryConnectionResponse>;
 
-	/// Query all connection states
+	/// Query a page of connection states, all connection states if `pagination` is omitted.
+	/// Queries the latest block if `height` is omitted.
 	#[method(name = "ibc_queryConnections")]
-	fn query_connections(&self) -> Result<QueryConnectionsResponse>;
+	fn query_connections(
+		&self,
+		pagination: Option<PageRequest>,
+		height: Option<u32>,
+	) -> Result<QueryConnectionsResponse>;
 
 	/// Query all connection states for associated client
 	#[method(name = "ibc_queryConnectionUsingClient")]
@@ -276,36 +357,55 @@ where
 		port_id: String,
 	) -> Result<IdentifiedClientState>;
 
-	/// Query all channel states for associated connection
+	/// Query a page of channel states for associated connection, all of them if `pagination` is
+	/// omitted
 	#[method(name = "ibc_queryConnectionChannels")]
 	fn query_connection_channels(
 		&self,
 		height: u32,
 		connection_id: String,
+		pagination: Option<PageRequest>,
 	) -> Result<QueryChannelsResponse>;
 
-	/// Query all channel states
+	/// Query a page of channel states, all channel states if `pagination` is omitted.
+	/// Queries the latest block if `height` is omitted.
 	#[method(name = "ibc_queryChannels")]
-	fn query_channels(&self) -> Result<QueryChannelsResponse>;
+	fn query_channels(
+		&self,
+		pagination: Option<PageRequest>,
+		height: Option<u32>,
+	) -> Result<QueryChannelsResponse>;
 
-	/// Query packet commitments
+	/// Query a page of packet commitments, all of them if `pagination` is omitted
 	#[method(name = "ibc_queryPacketCommitments")]
 	fn query_packet_commitments(
 		&self,
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pagination: Option<PageRequest>,
 	) -> Result<QueryPacketCommitmentsResponse>;
 
-	/// Query packet acknowledgements
+	/// Query a page of packet acknowledgements, all of them if `pagination` is omitted
 	#[method(name = "ibc_queryPacketAcknowledgements")]
 	fn query_packet_acknowledgements(
 		&self,
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pagination: Option<PageRequest>,
 	) -> Result<QueryPacketAcknowledgementsResponse>;
 
+	/// Query a page of packet receipts, all of them if `pagination` is omitted
+	#[method(name = "ibc_queryPacketReceipts")]
+	fn query_packet_receipts(
+		&self,
+		height: u32,
+		channel_id: String,
+		port_id: String,
+		pagination: Option<PageRequest>,
+	) -> Result<QueryPacketReceiptsResponse>;
+
 	/// Given a list of counterparty packet commitments, the querier checks if the packet
 	/// has already been received by checking if a receipt exists on this
 	/// chain for the packet sequence. All packets that haven't been received yet
@@ -420,8 +520,32 @@ where
 		&self,
 		block_numbers: Vec<BlockNumberOrHash<Hash>>,
 	) -> Result<HashMap<String, Vec<RawIbcEvent>>>;
+
+	/// Stream the ibc events of finalized blocks that pass the filter, replaying the events of
+	/// finalized blocks from `from_height` if provided. Subscriptions replaying more than
+	/// [`MAX_EVENT_REPLAY_BLOCKS`] finalized blocks are rejected.
+	#[subscription(
+		name = "ibc_subscribeEvents" => "ibc_events",
+		unsubscribe = "ibc_unsubscribeEvents",
+		item = RawIbcEvent
+	)]
+	fn subscribe_events(&self, filter: Option<EventFilter>, from_height: Option<u32>);
+
+	/// Query the spam protection deposit still held for a client or connection.
+	/// Returns `None` once the deposit has been released or slashed.
+	#[method(name = "ibc_querySpamDeposit")]
+	fn query_spam_deposit(&self, client_or_connection_id: String) -> Result<Option<SpamDeposit>>;
+
+	/// Query the light client types, and the chain ids they may track, that clients can be
+	/// created or upgraded to.
+	#[method(name = "ibc_queryClientParams")]
+	fn query_client_params(&self) -> Result<ClientParams>;
 }
 
+/// Number of finalized blocks, counted back from the latest one, whose events
+/// `ibc_subscribeEvents` replays
+pub const MAX_EVENT_REPLAY_BLOCKS: u32 = 256;
+
 /// Converts a runtime trap into an RPC error.
 fn runtime_error_into_rpc_error(e: impl std::fmt::Display) -> RpcError {
 	RpcError::Call(CallError::Custom(ErrorObject::owned(
@@ -431,18 +555,151 @@ fn runtime_error_into_rpc_error(e: impl std::fmt::Display) -> RpcError {
 	)))
 }
 
+/// Converts a cosmos page request into the one understood by the runtime.
+fn page_request(pagination: Option<PageRequest>) -> Option<ibc_primitives::PageRequest> {
+	pagination.map(|pagination| ibc_primitives::PageRequest {
+		key: (!pagination.key.is_empty()).then(|| pagination.key),
+		offset: pagination.offset,
+		limit: pagination.limit,
+		count_total: pagination.count_total,
+		reverse: pagination.reverse,
+	})
+}
+
+/// Whether the runtime at `at` paginates list queries, runtimes implementing the runtime api before
+/// version 3 return every item instead, which is then paginated by the rpc.
+fn paginates_list_queries<Block: BlockT, AssetId: codec::Codec, Api: ApiExt<Block> + ?Sized>(
+	api: &Api,
+	at: &BlockId<Block>,
+) -> Result<bool> {
+	api.has_api_with::<dyn IbcRuntimeApi<Block, AssetId>, _>(at, |version| version >= 3)
+		.map_err(runtime_error_into_rpc_error)
+}
+
+/// Fails unless the runtime at `at` implements `version` of the runtime api onwards, which added
+/// `method`.
+fn ensure_api_version<Block: BlockT, AssetId: codec::Codec, Api: ApiExt<Block> + ?Sized>(
+	api: &Api,
+	at: &BlockId<Block>,
+	version: u32,
+	method: &str,
+) -> Result<()> {
+	let supported = api
+		.has_api_with::<dyn IbcRuntimeApi<Block, AssetId>, _>(at, |api_version| {
+			api_version >= version
+		})
+		.map_err(runtime_error_into_rpc_error)?;
+	if supported {
+		Ok(())
+	} else {
+		Err(runtime_error_into_rpc_error(format!(
+			"[ibc_rpc]: {} needs version {} of the ibc runtime api, the runtime implements an older one",
+			method, version
+		)))
+	}
+}
+
+/// Paginates the channels returned by runtimes implementing the runtime api before version 3.
+fn paginate_channels(
+	result: ibc_primitives::v2::QueryChannelsResponse,
+	pagination: Option<PageRequest>,
+) -> ibc_primitives::QueryChannelsResponse {
+	let channels = result
+		.channels
+		.into_iter()
+		.map(|channel| ((channel.port_id.clone(), channel.channel_id.clone()).encode(), channel))
+		.collect();
+	let (channels, pagination) = ibc_primitives::paginate(channels, page_request(pagination));
+	ibc_primitives::QueryChannelsResponse { channels, pagination, height: result.height }
+}
+
+/// Paginates the packet states returned by runtimes implementing the runtime api before version 3.
+fn paginate_packet_states(
+	packet_states: Vec<ibc_primitives::PacketState>,
+	pagination: Option<PageRequest>,
+) -> (Vec<ibc_primitives::PacketState>, ibc_primitives::PageResponse) {
+	let packet_states = packet_states
+		.into_iter()
+		.map(|packet_state| (packet_state.sequence.to_be_bytes().to_vec(), packet_state))
+		.collect();
+	ibc_primitives::paginate(packet_states, page_request(pagination))
+}
+
+/// Converts a runtime page response into a cosmos page response.
+fn page_response(pagination: ibc_primitives::PageResponse) -> Option<PageResponse> {
+	Some(PageResponse {
+		next_key: pagination.next_key.unwrap_or_default(),
+		total: pagination.total.unwrap_or_default(),
+	})
+}
+
+/// Converts runtime packet states into their protobuf representation.
+fn packet_states(packet_states: Vec<ibc_primitives::PacketState>) -> Result<Vec<PacketState>> {
+	packet_states
+		.into_iter()
+		.map(|packet_state| {
+			let port_id = String::from_utf8(packet_state.port_id)
+				.map_err(|_| runtime_error_into_rpc_error("Failed to decode port id"))?;
+			let channel_id = String::from_utf8(packet_state.channel_id)
+				.map_err(|_| runtime_error_into_rpc_error("Failed to decode channel id"))?;
+			Ok(PacketState {
+				port_id,
+				channel_id,
+				sequence: packet_state.sequence,
+				data: packet_state.data,
+			})
+		})
+		.collect()
+}
+
 /// An implementation of IBC specific RPC methods.
 pub struct IbcRpcHandler<C, B> {
 	client: Arc<C>,
 	/// A copy of the chain properties.
 	pub chain_props: Properties,
+	/// Packet index to serve packet queries from instead of offchain indexed storage.
+	packet_store: Option<Arc<dyn PacketStore>>,
+	/// Executor driving event subscriptions.
+	subscription_executor: Option<Arc<dyn SpawnNamed>>,
 	_marker: std::marker::PhantomData<B>,
 }
 
 impl<C, B> IbcRpcHandler<C, B> {
 	/// Create new `IbcRpcHandler` with the given reference to the client.
 	pub fn new(client: Arc<C>, chain_props: Properties) -> Self {
-		Self { client, chain_props, _marker: Default::default() }
+		Self {
+			client,
+			chain_props,
+			packet_store: None,
+			subscription_executor: None,
+			_marker: Default::default(),
+		}
+	}
+
+	/// Serve packet queries from the packet index built by [`indexer::PacketIndexer`].
+	pub fn with_packet_store(mut self, packet_store: Arc<dyn PacketStore>) -> Self {
+		self.packet_store = Some(packet_store);
+		self
+	}
+
+	/// Enable event subscriptions, driven by `executor`.
+	pub fn with_subscription_executor(mut self, executor: Arc<dyn SpawnNamed>) -> Self {
+		self.subscription_executor = Some(executor);
+		self
+	}
+}
+
+impl<C, Block> IbcRpcHandler<C, Block>
+where
+	Block: BlockT,
+	C: HeaderBackend<Block>,
+{
+	/// The block at `height`, or the latest block if no height is given.
+	fn block_at(&self, height: Option<u32>) -> BlockId<Block> {
+		match height {
+			Some(height) => BlockId::Number(height.into()),
+			None => BlockId::Hash(self.client.info().best_hash),
+		}
 	}
 }
 
@@ -457,7 +714,8 @@ where
 		+ ProvideRuntimeApi<Block>
 		+ HeaderBackend<Block>
 		+ ProofProvider<Block>
-		+ BlockBackend<Block>,
+		+ BlockBackend<Block>
+		+ BlockchainEvents<Block>,
 	C::Api: IbcRuntimeApi<Block, AssetId>,
 	AssetId: codec::Codec,
 {
@@ -467,18 +725,22 @@ where
 		port_id: String,
 		seqs: Vec<u64>,
 	) -> Result<Vec<PacketInfo>> {
-		let api = self.client.runtime_api();
-		let at = BlockId::Hash(self.client.info().best_hash);
-		let packets: Vec<ibc_primitives::PacketInfo> = api
-			.query_send_packet_info(
-				&at,
-				channel_id.as_bytes().to_vec(),
-				port_id.as_bytes().to_vec(),
-				seqs,
-			)
-			.ok()
-			.flatten()
-			.ok_or_else(|| runtime_error_into_rpc_error("Error fetching packets"))?;
+		let packets: Vec<ibc_primitives::PacketInfo> = match &self.packet_store {
+			Some(store) => store.send_packets(channel_id.as_bytes(), port_id.as_bytes(), &seqs),
+			None => {
+				let api = self.client.runtime_api();
+				let at = BlockId::Hash(self.client.info().best_hash);
+				api.query_send_packet_info(
+					&at,
+					channel_id.as_bytes().to_vec(),
+					port_id.as_bytes().to_vec(),
+					seqs,
+				)
+				.ok()
+				.flatten()
+				.ok_or_else(|| runtime_error_into_rpc_error("Error fetching packets"))?
+			},
+		};
 
 		packets
 			.into_iter()
@@ -527,18 +789,22 @@ where
 		port_id: String,
 		seqs: Vec<u64>,
 	) -> Result<Vec<PacketInfo>> {
-		let api = self.client.runtime_api();
-		let at = BlockId::Hash(self.client.info().best_hash);
-		let packets: Vec<ibc_primitives::PacketInfo> = api
-			.query_recv_packet_info(
-				&at,
-				channel_id.as_bytes().to_vec(),
-				port_id.as_bytes().to_vec(),
-				seqs,
-			)
-			.ok()
-			.flatten()
-			.ok_or_else(|| runtime_error_into_rpc_error("Error fetching packets"))?;
+		let packets: Vec<ibc_primitives::PacketInfo> = match &self.packet_store {
+			Some(store) => store.recv_packets(channel_id.as_bytes(), port_id.as_bytes(), &seqs),
+			None => {
+				let api = self.client.runtime_api();
+				let at = BlockId::Hash(self.client.info().best_hash);
+				api.query_recv_packet_info(
+					&at,
+					channel_id.as_bytes().to_vec(),
+					port_id.as_bytes().to_vec(),
+					seqs,
+				)
+				.ok()
+				.flatten()
+				.ok_or_else(|| runtime_error_into_rpc_error("Error fetching packets"))?
+			},
+		};
 
 		packets
 			.into_iter()
@@ -707,11 +973,7 @@ where
 		latest_cs: bool,
 	) -> Result<QueryConsensusStateResponse> {
 		let api = self.client.runtime_api();
-		let at = if let Some(height) = height {
-			BlockId::Number(height.into())
-		} else {
-			BlockId::Hash(self.client.info().best_hash)
-		};
+		let at = self.block_at(height);
 		let para_id = api
 			.para_id(&at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
@@ -758,28 +1020,124 @@ where
 		Err(runtime_error_into_rpc_error("Unimplemented"))
 	}
 
-	fn query_clients(&self) -> Result<Vec<IdentifiedClientState>> {
+	fn query_clients(
+		&self,
+		pagination: Option<PageRequest>,
+		height: Option<u32>,
+	) -> Result<QueryClientStatesResponse> {
 		let api = self.client.runtime_api();
-		let at = BlockId::Hash(self.client.info().best_hash);
+		let at = self.block_at(height);
 
-		let client_states: Option<Vec<(Vec<u8>, Vec<u8>)>> = api.clients(&at).ok().flatten();
-		match client_states {
-			Some(client_states) => client_states
-				.into_iter()
-				.map(|(client_id, client_state)| {
-					let client_state = AnyClientState::decode_vec(&client_state).map_err(|_| {
-						runtime_error_into_rpc_error("Failed to decode client state")
-					})?;
-					Ok(IdentifiedClientState {
-						client_id: String::from_utf8(client_id).map_err(|_| {
-							runtime_error_into_rpc_error("Failed to decode client id")
-						})?,
-						client_state: Some(client_state.into()),
+		let result: ibc_primitives::QueryClientStatesResponse =
+			if paginates_list_queries::<Block, AssetId, _>(&*api, &at)? {
+				api.clients(&at, page_request(pagination))
+					.ok()
+					.flatten()
+					.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch client states"))?
+			} else {
+				#[allow(deprecated)]
+				let client_states = api
+					.clients_before_version_3(&at)
+					.ok()
+					.flatten()
+					.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch client states"))?
+					.into_iter()
+					.map(|(client_id, client_state)| {
+						let client_state =
+							ibc_primitives::IdentifiedClientState { client_id, client_state };
+						(client_state.client_id.clone(), client_state)
 					})
+					.collect();
+				let (client_states, pagination) =
+					ibc_primitives::paginate(client_states, page_request(pagination));
+				ibc_primitives::QueryClientStatesResponse { client_states, pagination, height: 0 }
+			};
+		let client_states = result
+			.client_states
+			.into_iter()
+			.map(|identified_client| {
+				let client_state = AnyClientState::decode_vec(&identified_client.client_state)
+					.map_err(|_| runtime_error_into_rpc_error("Failed to decode client state"))?;
+				Ok(IdentifiedClientState {
+					client_id: String::from_utf8(identified_client.client_id)
+						.map_err(|_| runtime_error_into_rpc_error("Failed to decode client id"))?,
+					client_state: Some(client_state.into()),
 				})
-				.collect(),
-			_ => Err(runtime_error_into_rpc_error("Failed to fetch client states")),
-		}
+			})
+			.collect::<Result<Vec<_>>>()?;
+		Ok(QueryClientStatesResponse {
+			client_states,
+			pagination: page_response(result.pagination),
+		})
+	}
+
+	fn query_consensus_states(
+		&self,
+		height: u32,
+		client_id: String,
+		pagination: Option<PageRequest>,
+	) -> Result<QueryConsensusStatesResponse> {
+		let api = self.client.runtime_api();
+		let at = BlockId::Number(height.into());
+
+		let result: ibc_primitives::QueryConsensusStatesResponse = api
+			.consensus_states(&at, client_id.as_bytes().to_vec(), page_request(pagination))
+			.ok()
+			.flatten()
+			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch consensus states"))?;
+		let consensus_states = result
+			.consensus_states
+			.into_iter()
+			.map(|consensus_state| {
+				let (revision_number, revision_height) = consensus_state.height;
+				let any_consensus_state = AnyConsensusState::decode_vec(
+					&consensus_state.consensus_state,
+				)
+				.map_err(|_| runtime_error_into_rpc_error("Failed to decode consensus state"))?;
+				Ok(ConsensusStateWithHeight {
+					height: Some(Height { revision_number, revision_height }),
+					consensus_state: Some(any_consensus_state.into()),
+				})
+			})
+			.collect::<Result<Vec<_>>>()?;
+		Ok(QueryConsensusStatesResponse {
+			consensus_states,
+			pagination: page_response(result.pagination),
+		})
+	}
+
+	fn query_client_connections(
+		&self,
+		height: u32,
+		client_id: String,
+	) -> Result<QueryClientConnectionsResponse> {
+		let api = self.client.runtime_api();
+		let at = BlockId::Number(height.into());
+		let para_id = api
+			.para_id(&at)
+			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
+		let result: ibc_primitives::QueryClientConnectionsResponse = api
+			.client_connections(&at, client_id.as_bytes().to_vec())
+			.ok()
+			.flatten()
+			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch client connections"))?;
+		let connection_paths = result
+			.connection_ids
+			.into_iter()
+			.map(|connection_id| {
+				String::from_utf8(connection_id)
+					.map_err(|_| runtime_error_into_rpc_error("Failed to decode connection id"))
+			})
+			.collect::<Result<Vec<_>>>()?;
+		// client connections aren't committed to the ibc child trie, so the response is unproven
+		Ok(QueryClientConnectionsResponse {
+			connection_paths,
+			proof: vec![],
+			proof_height: Some(Height {
+				revision_number: para_id.into(),
+				revision_height: result.height,
+			}),
+		})
 	}
 
 	fn query_connection(
@@ -823,15 +1181,40 @@ where
 		})
 	}
 
-	fn query_connections(&self) -> Result<QueryConnectionsResponse> {
+	fn query_connections(
+		&self,
+		pagination: Option<PageRequest>,
+		height: Option<u32>,
+	) -> Result<QueryConnectionsResponse> {
 		let api = self.client.runtime_api();
 
-		let at = BlockId::Hash(self.client.info().best_hash);
-		let result: ibc_primitives::QueryConnectionsResponse = api
-			.connections(&at)
-			.ok()
-			.flatten()
-			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch connections"))?;
+		let at = self.block_at(height);
+		let result: ibc_primitives::QueryConnectionsResponse =
+			if paginates_list_queries::<Block, AssetId, _>(&*api, &at)? {
+				api.connections(&at, page_request(pagination))
+					.ok()
+					.flatten()
+					.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch connections"))?
+			} else {
+				#[allow(deprecated)]
+				let result = api
+					.connections_before_version_3(&at)
+					.ok()
+					.flatten()
+					.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch connections"))?;
+				let connections = result
+					.connections
+					.into_iter()
+					.map(|connection| (connection.connection_id.clone(), connection))
+					.collect();
+				let (connections, pagination) =
+					ibc_primitives::paginate(connections, page_request(pagination));
+				ibc_primitives::QueryConnectionsResponse {
+					connections,
+					pagination,
+					height: result.height,
+				}
+			};
 		let para_id = api
 			.para_id(&at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
@@ -858,7 +1241,7 @@ where
 			.collect::<Result<Vec<_>>>()?;
 		Ok(QueryConnectionsResponse {
 			connections,
-			pagination: None,
+			pagination: page_response(result.pagination),
 			height: Some(ibc_proto::ibc::core::client::v1::Height {
 				revision_number: para_id.into(),
 				revision_height: result.height,
@@ -1016,6 +1399,7 @@ where
 		&self,
 		height: u32,
 		connection_id: String,
+		pagination: Option<PageRequest>,
 	) -> Result<QueryChannelsResponse> {
 		let api = self.client.runtime_api();
 
@@ -1023,13 +1407,31 @@ where
 		let para_id = api
 			.para_id(&at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
-		let result: ibc_primitives::QueryChannelsResponse = api
-			.connection_channels(&at, connection_id.as_bytes().to_vec())
-			.ok()
-			.flatten()
-			.ok_or_else(|| {
-				runtime_error_into_rpc_error("Failed to fetch channels state for connection")
-			})?;
+		let result: ibc_primitives::QueryChannelsResponse =
+			if paginates_list_queries::<Block, AssetId, _>(&*api, &at)? {
+				api.connection_channels(
+					&at,
+					connection_id.as_bytes().to_vec(),
+					page_request(pagination),
+				)
+				.ok()
+				.flatten()
+				.ok_or_else(|| {
+					runtime_error_into_rpc_error("Failed to fetch channels state for connection")
+				})?
+			} else {
+				#[allow(deprecated)]
+				let result = api
+					.connection_channels_before_version_3(&at, connection_id.as_bytes().to_vec())
+					.ok()
+					.flatten()
+					.ok_or_else(|| {
+						runtime_error_into_rpc_error(
+							"Failed to fetch channels state for connection",
+						)
+					})?;
+				paginate_channels(result, pagination)
+			};
 		let channels = result
 			.channels
 			.into_iter()
@@ -1056,7 +1458,7 @@ where
 
 		Ok(QueryChannelsResponse {
 			channels,
-			pagination: None,
+			pagination: page_response(result.pagination),
 			height: Some(ibc_proto::ibc::core::client::v1::Height {
 				revision_number: para_id.into(),
 				revision_height: result.height,
@@ -1064,17 +1466,31 @@ where
 		})
 	}
 
-	fn query_channels(&self) -> Result<QueryChannelsResponse> {
+	fn query_channels(
+		&self,
+		pagination: Option<PageRequest>,
+		height: Option<u32>,
+	) -> Result<QueryChannelsResponse> {
 		let api = self.client.runtime_api();
-		let at = BlockId::Hash(self.client.info().best_hash);
+		let at = self.block_at(height);
 		let para_id = api
 			.para_id(&at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
-		let result: ibc_primitives::QueryChannelsResponse = api
-			.channels(&at)
-			.ok()
-			.flatten()
-			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch channels"))?;
+		let result: ibc_primitives::QueryChannelsResponse =
+			if paginates_list_queries::<Block, AssetId, _>(&*api, &at)? {
+				api.channels(&at, page_request(pagination))
+					.ok()
+					.flatten()
+					.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch channels"))?
+			} else {
+				#[allow(deprecated)]
+				let result = api
+					.channels_before_version_3(&at)
+					.ok()
+					.flatten()
+					.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch channels"))?;
+				paginate_channels(result, pagination)
+			};
 		let channels = result
 			.channels
 			.into_iter()
@@ -1101,7 +1517,7 @@ where
 
 		Ok(QueryChannelsResponse {
 			channels,
-			pagination: None,
+			pagination: page_response(result.pagination),
 			height: Some(ibc_proto::ibc::core::client::v1::Height {
 				revision_number: para_id.into(),
 				revision_height: result.height,
@@ -1114,6 +1530,7 @@ where
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pagination: Option<PageRequest>,
 	) -> Result<QueryPacketCommitmentsResponse> {
 		let api = self.client.runtime_api();
 
@@ -1121,30 +1538,39 @@ where
 		let para_id = api
 			.para_id(&at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
-		let result: ibc_primitives::QueryPacketCommitmentsResponse = api
-			.packet_commitments(&at, channel_id.as_bytes().to_vec(), port_id.as_bytes().to_vec())
-			.ok()
-			.flatten()
-			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch commitments"))?;
-		let commitments = result
-			.commitments
-			.into_iter()
-			.map(|packet_state| {
-				let port_id = String::from_utf8(packet_state.port_id)
-					.map_err(|_| runtime_error_into_rpc_error("Failed to decode port id"))?;
-				let channel_id = String::from_utf8(packet_state.channel_id)
-					.map_err(|_| runtime_error_into_rpc_error("Failed to decode port id"))?;
-				Ok(PacketState {
-					port_id,
-					channel_id,
-					sequence: packet_state.sequence,
-					data: packet_state.data,
-				})
-			})
-			.collect::<Result<Vec<_>>>()?;
+		let result: ibc_primitives::QueryPacketCommitmentsResponse =
+			if paginates_list_queries::<Block, AssetId, _>(&*api, &at)? {
+				api.packet_commitments(
+					&at,
+					channel_id.as_bytes().to_vec(),
+					port_id.as_bytes().to_vec(),
+					page_request(pagination),
+				)
+				.ok()
+				.flatten()
+				.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch commitments"))?
+			} else {
+				#[allow(deprecated)]
+				let result = api
+					.packet_commitments_before_version_3(
+						&at,
+						channel_id.as_bytes().to_vec(),
+						port_id.as_bytes().to_vec(),
+					)
+					.ok()
+					.flatten()
+					.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch commitments"))?;
+				let (commitments, pagination) =
+					paginate_packet_states(result.commitments, pagination);
+				ibc_primitives::QueryPacketCommitmentsResponse {
+					commitments,
+					pagination,
+					height: result.height,
+				}
+			};
 		Ok(QueryPacketCommitmentsResponse {
-			commitments,
-			pagination: None,
+			commitments: packet_states(result.commitments)?,
+			pagination: page_response(result.pagination),
 			height: Some(ibc_proto::ibc::core::client::v1::Height {
 				revision_number: para_id.into(),
 				revision_height: result.height,
@@ -1157,6 +1583,7 @@ where
 		height: u32,
 		channel_id: String,
 		port_id: String,
+		pagination: Option<PageRequest>,
 	) -> Result<QueryPacketAcknowledgementsResponse> {
 		let api = self.client.runtime_api();
 
@@ -1164,34 +1591,73 @@ where
 		let para_id = api
 			.para_id(&at)
 			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
-		let result: ibc_primitives::QueryPacketAcknowledgementsResponse = api
-			.packet_acknowledgements(
+		let result: ibc_primitives::QueryPacketAcknowledgementsResponse =
+			if paginates_list_queries::<Block, AssetId, _>(&*api, &at)? {
+				api.packet_acknowledgements(
+					&at,
+					channel_id.as_bytes().to_vec(),
+					port_id.as_bytes().to_vec(),
+					page_request(pagination),
+				)
+				.ok()
+				.flatten()
+				.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch acknowledgements"))?
+			} else {
+				#[allow(deprecated)]
+				let result = api
+					.packet_acknowledgements_before_version_3(
+						&at,
+						channel_id.as_bytes().to_vec(),
+						port_id.as_bytes().to_vec(),
+					)
+					.ok()
+					.flatten()
+					.ok_or_else(|| {
+						runtime_error_into_rpc_error("Failed to fetch acknowledgements")
+					})?;
+				let (acks, pagination) = paginate_packet_states(result.acks, pagination);
+				ibc_primitives::QueryPacketAcknowledgementsResponse {
+					acks,
+					pagination,
+					height: result.height,
+				}
+			};
+		Ok(QueryPacketAcknowledgementsResponse {
+			acknowledgements: packet_states(result.acks)?,
+			pagination: page_response(result.pagination),
+			height: Some(ibc_proto::ibc::core::client::v1::Height {
+				revision_number: para_id.into(),
+				revision_height: result.height,
+			}),
+		})
+	}
+
+	fn query_packet_receipts(
+		&self,
+		height: u32,
+		channel_id: String,
+		port_id: String,
+		pagination: Option<PageRequest>,
+	) -> Result<QueryPacketReceiptsResponse> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::Number(height.into());
+		let para_id = api
+			.para_id(&at)
+			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
+		let result: ibc_primitives::QueryPacketReceiptsResponse = api
+			.packet_receipts(
 				&at,
 				channel_id.as_bytes().to_vec(),
 				port_id.as_bytes().to_vec(),
+				page_request(pagination),
 			)
 			.ok()
 			.flatten()
-			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch acknowledgements"))?;
-		let acknowledgements = result
-			.acks
-			.into_iter()
-			.map(|packet_state| {
-				let port_id = String::from_utf8(packet_state.port_id)
-					.map_err(|_| runtime_error_into_rpc_error("Failed to decode port id"))?;
-				let channel_id = String::from_utf8(packet_state.channel_id)
-					.map_err(|_| runtime_error_into_rpc_error("Failed to decode port id"))?;
-				Ok(PacketState {
-					port_id,
-					channel_id,
-					sequence: packet_state.sequence,
-					data: packet_state.data,
-				})
-			})
-			.collect::<Result<Vec<_>>>()?;
-		Ok(QueryPacketAcknowledgementsResponse {
-			acknowledgements,
-			pagination: None,
+			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch packet receipts"))?;
+		Ok(QueryPacketReceiptsResponse {
+			receipts: packet_states(result.receipts)?,
+			pagination: page_response(result.pagination),
 			height: Some(ibc_proto::ibc::core::client::v1::Height {
 				revision_number: para_id.into(),
 				revision_height: result.height,
@@ -1554,7 +2020,6 @@ where
 		&self,
 		block_numbers: Vec<BlockNumberOrHash<Block::Hash>>,
 	) -> Result<HashMap<String, Vec<RawIbcEvent>>> {
-		let api = self.client.runtime_api();
 		let mut events = HashMap::new();
 		for block_number_or_hash in block_numbers {
 			let at = match block_number_or_hash {
@@ -1562,17 +2027,124 @@ where
 				BlockNumberOrHash::Number(block_number) => BlockId::Number(block_number.into()),
 			};
 
-			let temp = api.block_events(&at, None).map_err(|_| {
-				runtime_error_into_rpc_error("[ibc_rpc]: failed to read block events")
-			})?;
-			let temp = temp
-				.into_iter()
-				.filter_map(|event| {
-					filter_map_pallet_event::<C, Block, AssetId>(&at, &api, event.ok()?)
-				})
-				.collect();
+			let temp =
+				block_events::<C, Block, AssetId>(&*self.client, self.packet_store.as_deref(), &at)
+					.map_err(|_| {
+						runtime_error_into_rpc_error("[ibc_rpc]: failed to read block events")
+					})?;
 			events.insert(block_number_or_hash.to_string(), temp);
 		}
 		Ok(events)
 	}
+
+	fn subscribe_events(
+		&self,
+		mut sink: SubscriptionSink,
+		filter: Option<EventFilter>,
+		from_height: Option<u32>,
+	) -> SubscriptionResult {
+		let executor = match &self.subscription_executor {
+			Some(executor) => executor.clone(),
+			None => {
+				let _ = sink.reject(runtime_error_into_rpc_error(
+					"[ibc_rpc]: event subscriptions are not enabled on this node",
+				));
+				return Ok(())
+			},
+		};
+		let client = self.client.clone();
+		let packet_store = self.packet_store.clone();
+		let filter = filter.unwrap_or_default();
+
+		// subscribe before reading the finalized block so no finalized block is missed in between
+		let finality_notifications = client
+			.finality_notification_stream()
+			.map(|notification| (*notification.header.number()).unique_saturated_into());
+		let finalized: u32 = client.info().finalized_number.unique_saturated_into();
+		let oldest = finalized.saturating_sub(MAX_EVENT_REPLAY_BLOCKS);
+		if matches!(from_height, Some(from_height) if from_height < oldest) {
+			let _ = sink.reject(runtime_error_into_rpc_error(format!(
+				"[ibc_rpc]: events can only be replayed from height {} onwards",
+				oldest
+			)));
+			return Ok(())
+		}
+		let mut next = from_height.unwrap_or_else(|| finalized.saturating_add(1));
+		let stream = stream::once(async move { finalized })
+			.chain(finality_notifications)
+			.flat_map(move |finalized: u32| {
+				let blocks = next..=finalized;
+				next = next.max(finalized.saturating_add(1));
+				let (client, packet_store, filter) =
+					(client.clone(), packet_store.clone(), filter.clone());
+				// blocks are only read once the subscriber has taken the events of the previous one
+				stream::iter(blocks).flat_map(move |block_number| {
+					let at = BlockId::Number(block_number.into());
+					let events =
+						block_events::<C, Block, AssetId>(&*client, packet_store.as_deref(), &at)
+							.unwrap_or_else(|e| {
+								log::warn!(target: "ibc-rpc", "Failed to read events of block {}: {:?}", block_number, e);
+								vec![]
+							})
+							.into_iter()
+							.filter(|event| filter.matches(event))
+							.collect::<Vec<_>>();
+					stream::iter(events)
+				})
+			})
+			.boxed();
+
+		let fut = async move {
+			sink.pipe_from_stream(stream).await;
+		};
+		executor.spawn("ibc-rpc-event-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+
+	fn query_spam_deposit(&self, client_or_connection_id: String) -> Result<Option<SpamDeposit>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::Hash(self.client.info().best_hash);
+		ensure_api_version::<Block, AssetId, _>(&*api, &at, 4, "spam_deposit")?;
+		let deposit = api
+			.spam_deposit(&at, client_or_connection_id.as_bytes().to_vec())
+			.map_err(|_| runtime_error_into_rpc_error("[ibc_rpc]: failed to read spam deposit"))?;
+		Ok(deposit.map(|deposit| SpamDeposit {
+			depositor: sp_core::bytes::to_hex(&deposit.depositor, false),
+			amount: deposit.amount.to_string(),
+			reserved_at: deposit.reserved_at,
+			expires_at: deposit.expires_at,
+		}))
+	}
+
+	fn query_client_params(&self) -> Result<ClientParams> {
+		let api = self.client.runtime_api();
+		let at = BlockId::Hash(self.client.info().best_hash);
+		ensure_api_version::<Block, AssetId, _>(&*api, &at, 5, "allowed_clients")?;
+		let allowed_clients = api.allowed_clients(&at).map_err(|_| {
+			runtime_error_into_rpc_error("[ibc_rpc]: failed to read allowed clients")
+		})?;
+		let allowed_clients = allowed_clients
+			.map(|allowed_clients| {
+				allowed_clients
+					.into_iter()
+					.map(|allowed| {
+						Ok(AllowedClient {
+							client_type: String::from_utf8(allowed.client_type).map_err(|_| {
+								runtime_error_into_rpc_error("Failed to decode client type")
+							})?,
+							chain_ids: allowed
+								.chain_ids
+								.into_iter()
+								.map(String::from_utf8)
+								.collect::<std::result::Result<_, _>>()
+								.map_err(|_| {
+									runtime_error_into_rpc_error("Failed to decode chain id")
+								})?,
+						})
+					})
+					.collect::<Result<Vec<_>>>()
+			})
+			.transpose()?;
+		Ok(ClientParams { allowed_clients })
+	}
 }
@@ -21,6 +21,11 @@ use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
 	/// IBC Runtime Apis
+	///
+	/// Version 2 added `block_packet_records`. Version 3 paginated the list queries and added
+	/// `consensus_states`, `client_connections` and `packet_receipts`. Version 4 added
+	/// `spam_deposit` and version 5 `allowed_clients`.
+	#[api_version(5)]
 	pub trait IbcRuntimeApi<AssetId: codec::Codec> {
 		/// Get Ibc child trie prefix
 		fn child_trie_key() -> Vec<u8>;
@@ -46,13 +51,28 @@ sp_api::decl_runtime_apis! {
 		fn client_consensus_state(client_id: Vec<u8>, revision_number: u64, revision_height: u64, latest_cs: bool) -> Option<QueryConsensusStateResponse>;
 
 		/// Returns client states for all clients on chain
+		#[changed_in(3)]
 		fn clients() -> Option<Vec<(Vec<u8>, Vec<u8>)>>;
 
+		/// Returns a page of client states, all client states if there's no page request
+		fn clients(pagination: Option<PageRequest>) -> Option<QueryClientStatesResponse>;
+
+		/// Returns a page of the consensus states stored for the given client
+		fn consensus_states(client_id: Vec<u8>, pagination: Option<PageRequest>) -> Option<QueryConsensusStatesResponse>;
+
+		/// Returns the ids of all connections associated with the given client. They aren't
+		/// committed to the ibc child trie, so there's no proof for them
+		fn client_connections(client_id: Vec<u8>) -> Option<QueryClientConnectionsResponse>;
+
 		/// Query the given connection state with proof
 		fn connection(connection_id: Vec<u8>) -> Option<QueryConnectionResponse>;
 
 		/// Returns all connections registered on chain
-		fn connections() -> Option<QueryConnectionsResponse>;
+		#[changed_in(3)]
+		fn connections() -> Option<v2::QueryConnectionsResponse>;
+
+		/// Returns a page of the connections registered on chain
+		fn connections(pagination: Option<PageRequest>) -> Option<QueryConnectionsResponse>;
 
 		/// Returns all connections associated with the given client
 		fn connection_using_client(client_id: Vec<u8>) -> Option<Vec<IdentifiedConnection>>;
@@ -63,16 +83,33 @@ sp_api::decl_runtime_apis! {
 		fn channel_client(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<IdentifiedClientState>;
 
 		/// Returns all channels associated with this connection
-		fn connection_channels(connection_id: Vec<u8>) -> Option<QueryChannelsResponse>;
+		#[changed_in(3)]
+		fn connection_channels(connection_id: Vec<u8>) -> Option<v2::QueryChannelsResponse>;
+
+		/// Returns a page of the channels associated with this connection
+		fn connection_channels(connection_id: Vec<u8>, pagination: Option<PageRequest>) -> Option<QueryChannelsResponse>;
 
 		/// Returns all channels registered on chain
-		fn channels() -> Option<QueryChannelsResponse>;
+		#[changed_in(3)]
+		fn channels() -> Option<v2::QueryChannelsResponse>;
+
+		/// Returns a page of the channels registered on chain
+		fn channels(pagination: Option<PageRequest>) -> Option<QueryChannelsResponse>;
 
 		fn connection_handshake(client_id: Vec<u8>, connection_id: Vec<u8>) -> Option<ConnectionHandshake>;
 
-		fn packet_commitments(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryPacketCommitmentsResponse>;
+		#[changed_in(3)]
+		fn packet_commitments(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<v2::QueryPacketCommitmentsResponse>;
 
-		fn packet_acknowledgements(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryPacketAcknowledgementsResponse>;
+		fn packet_commitments(channel_id: Vec<u8>, port_id: Vec<u8>, pagination: Option<PageRequest>) -> Option<QueryPacketCommitmentsResponse>;
+
+		#[changed_in(3)]
+		fn packet_acknowledgements(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<v2::QueryPacketAcknowledgementsResponse>;
+
+		fn packet_acknowledgements(channel_id: Vec<u8>, port_id: Vec<u8>, pagination: Option<PageRequest>) -> Option<QueryPacketAcknowledgementsResponse>;
+
+		/// Returns a page of the packet receipts written on this channel
+		fn packet_receipts(channel_id: Vec<u8>, port_id: Vec<u8>, pagination: Option<PageRequest>) -> Option<QueryPacketReceiptsResponse>;
 
 		fn unreceived_packets(channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<u64>>;
 
@@ -92,5 +129,17 @@ sp_api::decl_runtime_apis! {
 		fn denom_traces(key: Option<AssetId>, offset: Option<u32>, limit: u64, count_total: bool) -> QueryDenomTracesResponse;
 
 		fn block_events(extrinsic_index: Option<u32>) -> Vec<Result<pallet_ibc::events::IbcEvent, pallet_ibc::errors::IbcError>>;
+
+		/// Packet data and acknowledgements written in this block, only recorded by runtimes
+		/// implementing version 2 of this api onwards
+		fn block_packet_records() -> Vec<PacketRecord>;
+
+		/// Spam protection deposit still reserved for a client or connection id, only implemented by
+		/// runtimes implementing version 4 of this api onwards
+		fn spam_deposit(client_or_connection_id: Vec<u8>) -> Option<SpamDepositStatus>;
+
+		/// Light clients allowed to be created or upgraded to, `None` if any client is allowed. Only
+		/// implemented by runtimes implementing version 5 of this api onwards
+		fn allowed_clients() -> Option<Vec<AllowedClient>>;
 	}
 }
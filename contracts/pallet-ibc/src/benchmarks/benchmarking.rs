@@ -23,13 +23,23 @@ use crate::{
 	},
 	ics20::IbcModule,
 	ics23::client_states::ClientStates,
-	light_clients::{AnyClientState, AnyConsensusState},
+	light_clients::{AnyClientMessage, AnyClientState, AnyConsensusState, HostFunctionsManager},
 	Any, Config,
 };
+use grandpa_client_primitives::justification::GrandpaJustification;
+use ics10_grandpa::client_message::{ClientMessage as GrandpaClientMessage, RelayChainHeader};
+use ics_ethereum::{
+	beacon::SYNC_COMMITTEE_SIZE as ETHEREUM_SYNC_COMMITTEE_SIZE,
+	client_message::ClientMessage as EthereumClientMessage,
+	consensus_state::ConsensusState as EthereumConsensusState, mock as ethereum_mock,
+};
 
 use core::str::FromStr;
 use frame_benchmarking::{benchmarks, whitelisted_caller};
-use frame_support::traits::fungibles::{Inspect, Mutate};
+use frame_support::traits::{
+	fungibles::{Inspect, Mutate},
+	Get,
+};
 use frame_system::RawOrigin;
 use ibc_primitives::IbcHandler;
 use sp_runtime::traits::IdentifyAccount;
@@ -876,6 +886,7 @@ benchmarks! {
 			to:  MultiAddress::Raw("bob".to_string().as_bytes().to_vec()),
 			source_channel: channel_id.sequence(),
 			timeout,
+			memo: vec![],
 		};
 
 		<Params<T>>::put(PalletParams {
@@ -1042,6 +1053,7 @@ benchmarks! {
 			token: coin,
 			sender: Signer::from_str("alice").unwrap(),
 			receiver: Signer::from_str(&hex_string).unwrap(),
+			memo: Default::default(),
 		};
 
 		let data = serde_json::to_vec(&packet_data).unwrap();
@@ -1121,6 +1133,7 @@ benchmarks! {
 			token: coin,
 			sender: Signer::from_str(&hex_string).unwrap(),
 			receiver: Signer::from_str("alice").unwrap(),
+			memo: Default::default(),
 		};
 
 		let data = serde_json::to_vec(&packet_data).unwrap();
@@ -1200,6 +1213,7 @@ benchmarks! {
 			token: coin,
 			sender: Signer::from_str(&hex_string).unwrap(),
 			receiver: Signer::from_str("alice").unwrap(),
+			memo: Default::default(),
 		};
 
 		let data = serde_json::to_vec(&packet_data).unwrap();
@@ -1229,6 +1243,9 @@ benchmarks! {
 	}
 
 	// update_grandpa_client
+	// `i` is the number of precommits in the justification, all of them from distinct voters.
+	// Only the signatures needed to reach the 2/3+1 threshold are verified, so the signature
+	// verification cost grows with the threshold rather than with `i`.
 	update_grandpa_client {
 		let i in 1..100u32;
 		let mut ctx = routing::Context::<T>::new();
@@ -1262,4 +1279,133 @@ benchmarks! {
 		let client_state = AnyClientState::decode_vec(&*client_state).unwrap();
 		assert_eq!(client_state.latest_height(), Height::new(2000, 2));
 	}
+
+	// verify_grandpa_justification
+	// `i` is the number of precommits in the justification, isolates the signature verification
+	// part of `update_grandpa_client` from the storage accesses of the update.
+	verify_grandpa_justification {
+		let i in 1..100u32;
+		let (client_state, _, client_message) = generate_finality_proof(i);
+		let justification = match client_message {
+			AnyClientMessage::Grandpa(GrandpaClientMessage::Header(header)) =>
+				GrandpaJustification::<RelayChainHeader>::decode(
+					&mut &*header.finality_proof.justification,
+				)
+				.unwrap(),
+			_ => unreachable!("generate_finality_proof returns a grandpa header; qed"),
+		};
+	}: {
+		justification
+			.verify::<HostFunctionsManager>(
+				client_state.current_set_id,
+				&client_state.current_authorities,
+			)
+			.unwrap();
+	}
+
+	// update_ethereum_client
+	// The whole sync committee signs the update, the cost is dominated by decompressing the
+	// public keys of the participants and verifying their aggregate BLS signature.
+	update_ethereum_client {
+		let mut ctx = routing::Context::<T>::new();
+		let client_state = ethereum_mock::client_state::<HostFunctionsManager>(
+			ethereum_mock::SLOTS_PER_PERIOD + 64,
+		);
+		let latest_height = client_state.latest_height();
+		let consensus_state = EthereumConsensusState::new([2; 32], 1_700_000_000, client_state.latest_slot).unwrap();
+		let now: <T as pallet_timestamp::Config>::Moment = 1_700_000_000u64.saturating_mul(1000);
+		pallet_timestamp::Pallet::<T>::set_timestamp(now);
+		let mock_client_state = AnyClientState::Ethereum(client_state);
+		let client_id = ClientId::new(&mock_client_state.client_type(), 0).unwrap();
+		ctx.store_client_type(client_id.clone(), mock_client_state.client_type()).unwrap();
+		ctx.store_client_state(client_id.clone(), mock_client_state).unwrap();
+		ctx.store_consensus_state(client_id.clone(), latest_height, AnyConsensusState::Ethereum(consensus_state)).unwrap();
+		let time = Timestamp::from_nanoseconds(1_700_000_000 * 1_000_000_000).unwrap();
+		ctx.store_update_time(client_id.clone(), latest_height, time).unwrap();
+		let header = ethereum_mock::light_client_update::<HostFunctionsManager>(
+			ethereum_mock::SLOTS_PER_PERIOD + 128,
+			120,
+			[3; 32],
+			&ethereum_mock::sync_committee(2),
+			1,
+			ETHEREUM_SYNC_COMMITTEE_SIZE,
+		);
+		let msg = MsgUpdateAnyClient::<routing::Context<T>> {
+			client_id: client_id.clone(),
+			client_message: AnyClientMessage::Ethereum(EthereumClientMessage::Header(header)),
+			signer: Signer::from_str("relayer").unwrap()
+		};
+
+		let msg = Any { type_url: UPDATE_CLIENT_TYPE_URL.to_string().as_bytes().to_vec(), value: msg.encode_vec() };
+		let caller: T::AccountId = whitelisted_caller();
+	}: deliver(RawOrigin::Signed(caller), vec![msg])
+	verify {
+		let client_state = ClientStates::<T>::get(&client_id).unwrap();
+		let client_state = AnyClientState::decode_vec(&*client_state).unwrap();
+		assert_eq!(client_state.latest_height(), Height::new(0, 120));
+	}
+
+	// redundancy_check
+	// `i` is the size of the packet data, the bulk of a delivered `MsgRecvPacket`, the message is
+	// decoded and its channel end and packet receipt are read to find that it was relayed already.
+	redundancy_check {
+		let i in 1..1000u32;
+		let mut ctx = routing::Context::<T>::new();
+		let port_id = PortId::from_str(pallet_ibc_ping::PORT_ID).unwrap();
+		let counterparty_channel = ibc::core::ics04_channel::channel::Counterparty::new(port_id.clone(), Some(ChannelId::new(0)));
+		let channel_end = ChannelEnd::new(
+			ibc::core::ics04_channel::channel::State::Open,
+			ibc::core::ics04_channel::channel::Order::Unordered,
+			counterparty_channel,
+			vec![ConnectionId::new(0)],
+			ibc::core::ics04_channel::Version::default()
+		);
+		ctx.store_channel((port_id.clone(), ChannelId::new(0)), &channel_end).unwrap();
+		ctx.store_packet_receipt((port_id, ChannelId::new(0), 1u64.into()), Receipt::Ok).unwrap();
+		let (_, value) = create_recv_packet::<T>(vec![0u8; i as usize]);
+		let msg = Any {
+			type_url: RECV_PACKET_TYPE_URL.as_bytes().to_vec(),
+			value: value.encode_vec()
+		};
+	}: {
+		assert!(crate::redundancy::is_redundant(&ctx, &msg));
+	}
+
+	// prune_consensus_states
+	// `i` is the number of stale consensus states pruned from a single tendermint client, on top
+	// of the ones it always retains.
+	prune_consensus_states {
+		let i in 1..100u32;
+		let mut ctx = routing::Context::<T>::new();
+		let now: <T as pallet_timestamp::Config>::Moment = TIMESTAMP.saturating_mul(1000);
+		pallet_timestamp::Pallet::<T>::set_timestamp(now);
+		let (mock_client_state, mock_cs_state) = create_mock_state();
+		let mock_client_state = AnyClientState::Tendermint(mock_client_state);
+		let client_id = ClientId::new(&mock_client_state.client_type(), 0).unwrap();
+		ctx.store_client_type(client_id.clone(), mock_client_state.client_type()).unwrap();
+		ctx.store_client_state(client_id.clone(), mock_client_state).unwrap();
+		let retained = <T as Config>::ConsensusStatesToRetain::get().max(1);
+		// every consensus state was stored long before the client's trusting period
+		let update_time = Timestamp::from_nanoseconds(1).unwrap();
+		for revision_height in 1..=(i + retained) as u64 {
+			let height = Height::new(0, revision_height);
+			ctx.store_consensus_state(
+				client_id.clone(),
+				height,
+				AnyConsensusState::Tendermint(mock_cs_state.clone()),
+			)
+			.unwrap();
+			ctx.store_update_time(client_id.clone(), height, update_time).unwrap();
+			ctx.store_update_height(client_id.clone(), height, Height::new(0, 1)).unwrap();
+		}
+		let client_id = client_id.as_bytes().to_vec();
+	}: {
+		Pallet::<T>::prune_client_consensus_states(
+			&client_id,
+			&mut crate::pruning::WeightMeter::new(u64::MAX),
+		);
+	}
+	verify {
+		assert_eq!(ClientUpdateTime::<T>::iter_prefix(&client_id).count(), retained as usize);
+	}
 }
@@ -139,7 +139,8 @@ pub fn generate_finality_proof(
 		);
 	}
 
-	let grandpa_header = GrandpaHeader { finality_proof, parachain_headers };
+	let grandpa_header =
+		GrandpaHeader { finality_proof, parachain_headers, authority_set_changes: vec![] };
 	let client_message = AnyClientMessage::Grandpa(ClientMessage::Header(grandpa_header));
 
 	let client_state = ClientState {
@@ -149,6 +150,8 @@ pub fn generate_finality_proof(
 		frozen_height: None,
 		latest_para_height,
 		para_id,
+		standalone: false,
+		standalone_revision_number: 0,
 		current_set_id: set_id,
 		current_authorities: authorities.into_iter().map(|authority| (authority, 100)).collect(),
 		_phantom: Default::default(),
@@ -150,6 +150,8 @@ pub(crate) fn create_mock_beefy_client_state(
 		para_id: 2087,
 		authority: Default::default(),
 		next_authority_set: Default::default(),
+		signature_sample_size: 0,
+		track_relay_chain: false,
 		_phantom: Default::default(),
 	};
 
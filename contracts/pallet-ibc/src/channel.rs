@@ -13,9 +13,9 @@
 // limitations under the License.
 
 use super::*;
-use core::{str::FromStr, time::Duration};
+use core::time::Duration;
 use frame_support::traits::Get;
-use ibc_primitives::PacketInfo;
+use ibc_primitives::{PacketInfo, PacketRecord};
 use scale_info::prelude::string::ToString;
 use sp_core::crypto::AccountId32;
 
@@ -68,41 +68,8 @@ where
 		&self,
 		conn_id: &ConnectionId,
 	) -> Result<Vec<(PortId, ChannelId)>, ICS04Error> {
-		if <ChannelsConnection<T>>::contains_key(conn_id.as_bytes()) {
-			let port_and_channel_id = <ChannelsConnection<T>>::get(conn_id.as_bytes());
-
-			let mut result = vec![];
-
-			for item in port_and_channel_id {
-				let port_id = String::from_utf8(item.0).map_err(|e| {
-					ICS04Error::implementation_specific(format!(
-						"[connection_channels]: error decoding port_id: {}",
-						e
-					))
-				})?;
-				let port_id = PortId::from_str(port_id.as_str()).map_err(|e| {
-					ICS04Error::implementation_specific(format!(
-						"[connection_channels]: invalid port id string: {}",
-						e
-					))
-				})?;
-
-				let channel_id = String::from_utf8(item.1).map_err(|e| {
-					ICS04Error::implementation_specific(format!(
-						"[connection_channels]: error decoding channel_id: {}",
-						e
-					))
-				})?;
-				let channel_id = ChannelId::from_str(channel_id.as_str()).map_err(|e| {
-					ICS04Error::implementation_specific(format!(
-						"[connection_channels]: error decoding channel_id: {}",
-						e
-					))
-				})?;
-
-				result.push((port_id, channel_id));
-			}
-
+		if <ChannelsConnection<T>>::contains_key(conn_id) {
+			let result = crate::migrations::v2::connection_channels::<T>(conn_id);
 			log::trace!(target: "pallet_ibc",
 				"in channel : [connection_channels] >> Vector<(PortId, ChannelId)> =  {:?}",
 				result
@@ -314,6 +281,9 @@ where
 
 		sp_io::offchain_index::set(&key, packet_info.encode().as_slice());
 		log::trace!(target: "pallet_ibc", "in channel: [store_send_packet] >> writing packet {:?} {:?}", key, packet_info);
+		Pallet::<T>::deposit_event(Event::<T>::PacketRecorded {
+			record: PacketRecord::Send(packet_info),
+		});
 		Ok(())
 	}
 
@@ -333,6 +303,9 @@ where
 		packet_info.channel_order = channel_end.ordering as u8;
 		sp_io::offchain_index::set(&key, packet_info.encode().as_slice());
 		log::trace!(target: "pallet_ibc", "in channel: [store_recv_packet] >> writing packet {:?} {:?}", key, packet_info);
+		Pallet::<T>::deposit_event(Event::<T>::PacketRecorded {
+			record: PacketRecord::Recv(packet_info),
+		});
 		Ok(())
 	}
 
@@ -401,25 +374,7 @@ where
 		conn_id: ConnectionId,
 		port_channel_id: &(PortId, ChannelId),
 	) -> Result<(), ICS04Error> {
-		let conn_id = conn_id.as_bytes().to_vec();
-
-		let port_channel_id = (
-			port_channel_id.0.as_bytes().to_vec(),
-			port_channel_id.1.to_string().as_bytes().to_vec(),
-		);
-
-		if <ChannelsConnection<T>>::contains_key(conn_id.clone()) {
-			log::trace!(target: "pallet_ibc", "in channel: [store_connection_channels] >> insert port_channel_id");
-			<ChannelsConnection<T>>::try_mutate(conn_id, |val| -> Result<(), &'static str> {
-				val.push(port_channel_id);
-				Ok(())
-			})
-			.expect("store connection channels error");
-		} else {
-			log::trace!(target: "pallet_ibc", "in channel: [store_connection_channels] >> init ChannelsConnection");
-			let temp_connection_channels = vec![port_channel_id];
-			<ChannelsConnection<T>>::insert(conn_id, temp_connection_channels);
-		}
+		crate::migrations::v2::append_connection_channel::<T>(&conn_id, port_channel_id.clone());
 
 		Ok(())
 	}
@@ -106,7 +106,7 @@ where
 
 	fn host_client_type(&self) -> String {
 		// todo: https://github.com/cosmos/ibc/pull/839
-		if cfg!(any(test, feature = "runtime-benchmarks")) {
+		if cfg!(feature = "runtime-benchmarks") {
 			"tendermint".to_string()
 		} else {
 			"beefy".to_string()
@@ -361,8 +361,9 @@ where
 			client_id, height, consensus_state);
 
 		let data = consensus_state.encode_to_vec();
-		// todo: pruning
 		ConsensusStates::<T>::insert(client_id.clone(), height, data);
+		// stale consensus states are pruned in `on_idle`
+		ConsensusStateClients::<T>::insert(client_id.as_bytes().to_vec(), ());
 		// We do not need this hack for neither beefy nor grandpa clients
 		if !client_id.as_str().starts_with("10-grandpa") &&
 			!client_id.as_str().starts_with("11-beefy")
@@ -400,10 +401,13 @@ where
 		timestamp: Timestamp,
 	) -> Result<(), ICS02Error> {
 		log::trace!(target: "pallet_ibc", "in client: [store_update_time] >> Client Height {:?}, Host Timestamp {:?} ", height, timestamp);
+		let height_key = crate::pruning::height_key(height);
 		let height = height.encode_vec();
 		let timestamp = timestamp.nanoseconds();
 		let client_id = client_id.as_bytes().to_vec();
-		ClientUpdateTime::<T>::insert(client_id, height, timestamp);
+		ClientUpdateTime::<T>::insert(client_id.clone(), height, timestamp);
+		// stale consensus states are pruned in height order
+		ConsensusStateHeights::<T>::insert(client_id, height_key, ());
 		Ok(())
 	}
 
@@ -462,3 +466,67 @@ where
 		Ok(())
 	}
 }
+
+/// Rejects messages creating or upgrading clients to a light client type or chain id missing
+/// from [`AllowedClients`], other messages are left to their handlers.
+pub(crate) fn ensure_client_allowed<T: Config + Send + Sync>(
+	message: &ibc_proto::google::protobuf::Any,
+) -> Result<(), ICS02Error>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	use ibc::core::{
+		ics02_client::msgs::{create_client, upgrade_client, ClientMsg},
+		ics26_routing::msgs::Ics26Envelope,
+	};
+
+	if message.type_url != create_client::TYPE_URL && message.type_url != upgrade_client::TYPE_URL {
+		return Ok(())
+	}
+	let allowed_clients = match AllowedClients::<T>::get() {
+		Some(allowed_clients) => allowed_clients,
+		None => return Ok(()),
+	};
+	let client_state = match Ics26Envelope::<Context<T>>::try_from(message.clone()) {
+		Ok(Ics26Envelope::Ics2Msg(ClientMsg::CreateClient(msg))) => msg.client_state,
+		Ok(Ics26Envelope::Ics2Msg(ClientMsg::UpgradeClient(msg))) => msg.client_state,
+		// malformed messages are rejected by the handler
+		_ => return Ok(()),
+	};
+
+	let client_type = client_state.client_type();
+	let chain_id = client_state.chain_id().to_string();
+	if allowed_clients.iter().any(|allowed| allowed.allows(&client_type, &chain_id)) {
+		Ok(())
+	} else {
+		Err(ICS02Error::implementation_specific(format!(
+			"client type {client_type} for chain {chain_id} is not allowed"
+		)))
+	}
+}
+
+/// Rejects messages creating wasm light clients whose cached latest height doesn't match the
+/// client state wrapped by the wasm code, other messages are left to their handlers.
+pub(crate) fn validate_wasm_client<T: Config + Send + Sync>(
+	message: &ibc_proto::google::protobuf::Any,
+) -> Result<(), ICS02Error>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	use ibc::core::{
+		ics02_client::msgs::{create_client, ClientMsg},
+		ics26_routing::msgs::Ics26Envelope,
+	};
+
+	if message.type_url != create_client::TYPE_URL {
+		return Ok(())
+	}
+	match Ics26Envelope::<Context<T>>::try_from(message.clone()) {
+		Ok(Ics26Envelope::Ics2Msg(ClientMsg::CreateClient(msg))) => match msg.client_state {
+			AnyClientState::Wasm(client_state) => Ok(client_state.validate()?),
+			_ => Ok(()),
+		},
+		// malformed messages are rejected by the handler
+		_ => Ok(()),
+	}
+}
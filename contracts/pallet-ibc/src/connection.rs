@@ -117,13 +117,8 @@ where
 			client_id
 		);
 
-		ConnectionClient::<T>::try_mutate::<_, _, ICS03Error, _>(
-			client_id.as_bytes().to_vec(),
-			|val| {
-				val.push(connection_id.as_bytes().to_vec());
-				Ok(())
-			},
-		)
+		ConnectionClient::<T>::append(client_id, connection_id);
+		Ok(())
 	}
 
 	fn increase_connection_counter(&mut self) {
@@ -13,7 +13,39 @@
 // limitations under the License.
 
 use super::*;
-use ibc::core::ics26_routing::error::{Error as RoutingError, ErrorDetail};
+use ibc::core::{
+	ics02_client::error::ErrorDetail as ClientErrorDetail,
+	ics03_connection::error::ErrorDetail as ConnectionErrorDetail,
+	ics04_channel::error::ErrorDetail as ChannelErrorDetail,
+	ics26_routing::error::{Error as RoutingError, ErrorDetail},
+};
+
+/// Reasons a message failed for, the last two digits of [`failure_code`].
+pub mod failure_reason {
+	/// Any reason not listed below
+	pub const OTHER: u32 = 0;
+	/// The client doesn't exist
+	pub const CLIENT_NOT_FOUND: u32 = 1;
+	/// The client has no consensus state at the proof height
+	pub const CONSENSUS_STATE_NOT_FOUND: u32 = 2;
+	/// The connection doesn't exist
+	pub const CONNECTION_NOT_FOUND: u32 = 3;
+	/// The channel doesn't exist
+	pub const CHANNEL_NOT_FOUND: u32 = 4;
+	/// The client is frozen
+	pub const CLIENT_FROZEN: u32 = 5;
+	/// A proof of the message failed to verify
+	pub const PROOF_VERIFICATION_FAILED: u32 = 6;
+
+	/// Whether a message failing for `reason` may succeed once the messages it depends on, eg. an
+	/// update of its client, have been executed.
+	pub fn is_transient(reason: u32) -> bool {
+		matches!(
+			reason,
+			CLIENT_NOT_FOUND | CONSENSUS_STATE_NOT_FOUND | CONNECTION_NOT_FOUND | CHANNEL_NOT_FOUND
+		)
+	}
+}
 
 #[derive(
 	PartialEq, Eq, Clone, frame_support::RuntimeDebug, scale_info::TypeInfo, Encode, Decode,
@@ -33,6 +65,72 @@ pub enum IbcError {
 	MalformedMessageBytes { message: Vec<u8> },
 }
 
+impl IbcError {
+	/// Numeric code of the error, the position of its variant in this enum starting from 1.
+	pub fn code(&self) -> u32 {
+		match self {
+			IbcError::Ics02Client { .. } => 1,
+			IbcError::Ics03Connection { .. } => 2,
+			IbcError::Ics04Channel { .. } => 3,
+			IbcError::Ics20FungibleTokenTransfer { .. } => 4,
+			IbcError::UnknownMessageTypeUrl { .. } => 5,
+			IbcError::MalformedMessageBytes { .. } => 6,
+		}
+	}
+}
+
+/// Code of the error a message failed with, `100 * category + reason` where the category is the
+/// [`IbcError::code`] the error converts to and the reason one of [`failure_reason`].
+pub fn failure_code(err: &RoutingError) -> u32 {
+	use failure_reason::*;
+	let client_reason = |detail: &ClientErrorDetail| match detail {
+		ClientErrorDetail::ClientNotFound(_) => CLIENT_NOT_FOUND,
+		ClientErrorDetail::ConsensusStateNotFound(_) |
+		ClientErrorDetail::MissingLocalConsensusState(_) => CONSENSUS_STATE_NOT_FOUND,
+		ClientErrorDetail::ClientFrozen(_) => CLIENT_FROZEN,
+		ClientErrorDetail::HeaderVerificationFailure(_) |
+		ClientErrorDetail::InvalidCommitmentProof(_) => PROOF_VERIFICATION_FAILED,
+		_ => OTHER,
+	};
+	let connection_reason = |detail: &ConnectionErrorDetail| match detail {
+		ConnectionErrorDetail::Ics02Client(e) => client_reason(&e.source),
+		ConnectionErrorDetail::ConnectionNotFound(_) => CONNECTION_NOT_FOUND,
+		ConnectionErrorDetail::FrozenClient(_) => CLIENT_FROZEN,
+		// the client's consensus state at the proof height is looked up before verifying
+		ConnectionErrorDetail::ConsensusStateVerificationFailure(e)
+			if client_reason(&e.source) == CONSENSUS_STATE_NOT_FOUND =>
+			CONSENSUS_STATE_NOT_FOUND,
+		ConnectionErrorDetail::ClientStateVerificationFailure(e)
+			if client_reason(&e.source) == CONSENSUS_STATE_NOT_FOUND =>
+			CONSENSUS_STATE_NOT_FOUND,
+		ConnectionErrorDetail::VerifyConnectionState(_) |
+		ConnectionErrorDetail::ConnectionVerificationFailure(_) |
+		ConnectionErrorDetail::ConsensusStateVerificationFailure(_) |
+		ConnectionErrorDetail::ClientStateVerificationFailure(_) => PROOF_VERIFICATION_FAILED,
+		_ => OTHER,
+	};
+	let channel_reason = |detail: &ChannelErrorDetail| match detail {
+		ChannelErrorDetail::Ics02Client(e) => client_reason(&e.source),
+		ChannelErrorDetail::Ics03Connection(e) => connection_reason(&e.source),
+		ChannelErrorDetail::ChannelNotFound(_) => CHANNEL_NOT_FOUND,
+		ChannelErrorDetail::ErrorInvalidConsensusState(_) => CONSENSUS_STATE_NOT_FOUND,
+		ChannelErrorDetail::FrozenClient(_) => CLIENT_FROZEN,
+		ChannelErrorDetail::PacketVerificationFailed(_) |
+		ChannelErrorDetail::VerifyChannelFailed(_) |
+		ChannelErrorDetail::ChanOpenAckProofVerification(_) => PROOF_VERIFICATION_FAILED,
+		_ => OTHER,
+	};
+	let (category, reason) = match &err.0 {
+		ErrorDetail::Ics02Client(e) => (1, client_reason(&e.source)),
+		ErrorDetail::Ics03Connection(e) => (2, connection_reason(&e.source)),
+		ErrorDetail::Ics04Channel(e) => (3, channel_reason(&e.source)),
+		ErrorDetail::Ics20FungibleTokenTransfer(_) => (4, OTHER),
+		ErrorDetail::UnknownMessageTypeUrl(_) => (5, OTHER),
+		ErrorDetail::MalformedMessageBytes(_) => (6, OTHER),
+	};
+	100 * category + reason
+}
+
 impl From<RoutingError> for IbcError {
 	fn from(err: RoutingError) -> Self {
 		match err.0 {
@@ -52,11 +150,3 @@ impl From<RoutingError> for IbcError {
 		}
 	}
 }
-
-impl<T: Config> From<Vec<RoutingError>> for Event<T> {
-	fn from(errors: Vec<RoutingError>) -> Self {
-		let errors: Vec<Result<_, IbcError>> =
-			errors.into_iter().map(|err| Err(err.into())).collect();
-		Event::<T>::Events { events: errors }
-	}
-}
@@ -0,0 +1,373 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interchain queries over the `icqhost` port. As a host, this chain serves reads of raw
+//! storage keys under the prefixes in [`IcqAllowedPrefixes`]. As a controller, modules bind a
+//! port with [`IbcHandler::bind_query_port`] and send queries from it with
+//! [`IbcHandler::send_query`], results are delivered to the module that bound the port.
+
+use crate::{Config, Event, IcqAllowedPrefixes, IcqControllerPorts, Pallet, WeightInfo};
+use alloc::{
+	format,
+	string::{String, ToString},
+};
+use core::fmt::Formatter;
+use frame_support::weights::Weight;
+use ibc::{
+	applications::icq::{
+		acknowledgement::Acknowledgement as IcqAcknowledgement,
+		context::{
+			on_acknowledgement_packet, on_chan_close_confirm, on_chan_close_init, on_chan_open_ack,
+			on_chan_open_confirm, on_chan_open_init, on_chan_open_try, on_recv_packet,
+			on_timeout_packet, InterchainQueryContext,
+		},
+		error::Error as IcqError,
+		packet::{InterchainQueryPacketData, QueryRequest, QueryResponse},
+		PORT_ID_STR,
+	},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			error::Error as Ics04Error,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module, ModuleCallbackContext, ModuleOutputBuilder},
+	},
+	signer::Signer,
+};
+use ibc_primitives::{CallbackWeight, IbcHandler, QueryCallbacks};
+use sp_core::crypto::AccountId32;
+use sp_runtime::traits::{Get, UniqueSaturatedInto};
+use sp_std::{marker::PhantomData, prelude::*, str::FromStr};
+
+/// Query path for reading a raw storage key, the request data is the full storage key.
+pub const STORAGE_QUERY_PATH: &str = "/substrate/storage";
+
+/// Host side context for interchain queries, reads are served from the runtime storage.
+pub struct IcqContext<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for IcqContext<T> {
+	fn default() -> Self {
+		Self(PhantomData::default())
+	}
+}
+
+impl<T: Config> InterchainQueryContext for IcqContext<T> {
+	fn get_port(&self) -> Result<PortId, IcqError> {
+		PortId::from_str(PORT_ID_STR)
+			.map_err(|e| IcqError::query(format!("invalid icq port: {:?}", e)))
+	}
+
+	fn is_controller_port(&self, port_id: &PortId) -> bool {
+		port_id.as_str() != PORT_ID_STR && IcqControllerPorts::<T>::contains_key(port_id.as_bytes())
+	}
+
+	fn is_query_allowed(&self, request: &QueryRequest) -> bool {
+		request.path == STORAGE_QUERY_PATH &&
+			IcqAllowedPrefixes::<T>::get()
+				.iter()
+				.any(|prefix| request.data.starts_with(prefix))
+	}
+
+	fn query(&self, request: &QueryRequest) -> Result<QueryResponse, IcqError> {
+		// Historical state is not available to the runtime
+		if request.height != 0 {
+			return Err(IcqError::unsupported_height(request.height))
+		}
+		// State proofs can only be generated outside the runtime
+		if request.prove {
+			return Err(IcqError::query("state proofs are not supported".to_string()))
+		}
+		let height: u64 = frame_system::Pallet::<T>::block_number().unique_saturated_into();
+		Ok(QueryResponse {
+			code: 0,
+			value: sp_io::storage::get(&request.data).unwrap_or_default(),
+			height,
+			proof: vec![],
+			log: String::new(),
+		})
+	}
+}
+
+/// Module id of the [`QueryCallback`](ibc_primitives::QueryCallback) that bound `port_id`
+fn controller_module<T: Config>(port_id: &PortId) -> Result<String, Ics04Error> {
+	IcqControllerPorts::<T>::get(port_id.as_bytes())
+		.and_then(|module_id| String::from_utf8(module_id).ok())
+		.ok_or_else(|| {
+			Ics04Error::implementation_specific(format!("{} isn't an icq controller port", port_id))
+		})
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct IbcModule<T: Config>(PhantomData<T>);
+
+impl<T: Config> core::fmt::Debug for IbcModule<T> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		write!(f, "ibc-icq")
+	}
+}
+
+impl<T: Config> Default for IbcModule<T> {
+	fn default() -> Self {
+		Self(PhantomData::default())
+	}
+}
+
+impl<T: Config + Send + Sync> Module for IbcModule<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn on_chan_open_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		on_chan_open_init(
+			&IcqContext::<T>::default(),
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+		)
+		.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		on_chan_open_try(
+			&IcqContext::<T>::default(),
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+			counterparty_version,
+		)
+		.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		on_chan_open_ack(
+			&IcqContext::<T>::default(),
+			output,
+			port_id,
+			channel_id,
+			counterparty_version,
+		)
+		.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		on_chan_open_confirm(&IcqContext::<T>::default(), output, port_id, channel_id)
+			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		on_chan_close_init(&IcqContext::<T>::default(), output, port_id, channel_id)
+			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		_relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		on_chan_close_confirm(&IcqContext::<T>::default(), output, port_id, channel_id)
+			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
+	}
+
+	fn on_recv_packet(
+		&self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &Packet,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let ack = on_recv_packet(&IcqContext::<T>::default(), output, packet, relayer);
+		Pallet::<T>::write_acknowledgement(packet, ack.to_bytes())
+			.map_err(|e| Ics04Error::implementation_specific(format!("[on_recv_packet] {:#?}", e)))
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &Packet,
+		acknowledgement: &Acknowledgement,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let (requests, ack) = on_acknowledgement_packet(
+			&IcqContext::<T>::default(),
+			output,
+			packet,
+			acknowledgement,
+			relayer,
+		)
+		.map_err(|e| Ics04Error::implementation_specific(e.to_string()))?;
+		let module_id = controller_module::<T>(&packet.source_port)?;
+		let sequence = u64::from(packet.sequence);
+		let result = match &ack {
+			IcqAcknowledgement::Result(responses) => Ok(responses.as_slice()),
+			IcqAcknowledgement::Error(err) => Err(err.as_str()),
+		};
+		T::IcqCallback::on_query_result(
+			&module_id,
+			&packet.source_port,
+			&packet.source_channel,
+			sequence,
+			&requests,
+			result,
+		);
+		Pallet::<T>::deposit_event(Event::<T>::QueryResultReceived {
+			port_id: packet.source_port.as_bytes().to_vec(),
+			channel_id: packet.source_channel.to_string().as_bytes().to_vec(),
+			sequence,
+			success: ack.is_successful(),
+		});
+		Ok(())
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &Packet,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let requests = on_timeout_packet(&IcqContext::<T>::default(), output, packet, relayer)
+			.map_err(|e| Ics04Error::app_module(e.to_string()))?;
+		let module_id = controller_module::<T>(&packet.source_port)?;
+		let sequence = u64::from(packet.sequence);
+		T::IcqCallback::on_query_timeout(
+			&module_id,
+			&packet.source_port,
+			&packet.source_channel,
+			sequence,
+			&requests,
+		);
+		Pallet::<T>::deposit_event(Event::<T>::QueryTimedOut {
+			port_id: packet.source_port.as_bytes().to_vec(),
+			channel_id: packet.source_channel.to_string().as_bytes().to_vec(),
+			sequence,
+		});
+		Ok(())
+	}
+}
+
+pub struct WeightHandler<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for WeightHandler<T> {
+	fn default() -> Self {
+		Self(PhantomData::default())
+	}
+}
+
+impl<T: Config> CallbackWeight for WeightHandler<T> {
+	fn on_chan_open_init(&self) -> Weight {
+		<T as Config>::WeightInfo::on_chan_open_init()
+	}
+
+	fn on_chan_open_try(&self) -> Weight {
+		<T as Config>::WeightInfo::on_chan_open_try()
+	}
+
+	fn on_chan_open_ack(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		<T as Config>::WeightInfo::on_chan_open_ack()
+	}
+
+	fn on_chan_open_confirm(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		<T as Config>::WeightInfo::on_chan_open_confirm()
+	}
+
+	fn on_chan_close_init(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		<T as Config>::WeightInfo::on_chan_close_init()
+	}
+
+	fn on_chan_close_confirm(&self, _port_id: &PortId, _channel_id: &ChannelId) -> Weight {
+		<T as Config>::WeightInfo::on_chan_close_confirm()
+	}
+
+	fn on_recv_packet(&self, packet: &Packet) -> Weight {
+		// One read for the allow list and one read per query request
+		let requests = serde_json::from_slice::<InterchainQueryPacketData>(&packet.data)
+			.map(|data| data.requests.len() as u64)
+			.unwrap_or_default();
+		<T as Config>::WeightInfo::on_recv_packet()
+			.saturating_add(T::DbWeight::get().reads(requests.saturating_add(1)))
+	}
+
+	fn on_acknowledgement_packet(
+		&self,
+		_packet: &Packet,
+		_acknowledgement: &Acknowledgement,
+	) -> Weight {
+		<T as Config>::WeightInfo::on_acknowledgement_packet()
+	}
+
+	fn on_timeout_packet(&self, _packet: &Packet) -> Weight {
+		<T as Config>::WeightInfo::on_timeout_packet()
+	}
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 pub mod context;
+pub mod xcm;
 
 use crate::{routing::Context, ChannelIds, Config, DenomToAssetId, Event, Pallet, WeightInfo};
 use alloc::{
@@ -20,7 +21,10 @@ use alloc::{
 	string::{String, ToString},
 };
 use core::fmt::Formatter;
-use frame_support::weights::Weight;
+use frame_support::{
+	storage::{with_transaction, TransactionOutcome},
+	weights::Weight,
+};
 use ibc::{
 	applications::transfer::{
 		acknowledgement::{Acknowledgement as Ics20Acknowledgement, ACK_ERR_STR, ACK_SUCCESS_B64},
@@ -28,6 +32,7 @@ use ibc::{
 			on_chan_close_confirm, on_chan_close_init, on_chan_open_ack, on_chan_open_confirm,
 			on_chan_open_init, on_chan_open_try,
 		},
+		error::Error as Ics20Error,
 		is_receiver_chain_source, is_sender_chain_source,
 		packet::PacketData,
 		relay::{
@@ -51,7 +56,9 @@ use ibc::{
 };
 use ibc_primitives::{CallbackWeight, IbcHandler};
 use sp_core::crypto::AccountId32;
+use sp_runtime::{traits::IdentifyAccount, DispatchError};
 use sp_std::marker::PhantomData;
+use xcm::XcmTransfer;
 
 #[derive(Clone, Eq, PartialEq)]
 pub struct IbcModule<T: Config>(PhantomData<T>);
@@ -218,18 +225,33 @@ where
 		_relayer: &Signer,
 	) -> Result<(), Ics04Error> {
 		let mut ctx = Context::<T>::default();
-		let result = serde_json::from_slice(packet.data.as_slice())
-			.map_err(|e| {
-				Ics04Error::implementation_specific(format!("Failed to decode packet data {:?}", e))
-			})
-			.and_then(|packet_data: PacketData| {
-				process_recv_packet(&mut ctx, output, packet, packet_data.clone())
-					.map(|_| packet_data)
-					.map_err(|e| {
-						log::trace!(target: "pallet_ibc", "[on_recv_packet]: {:?}", e);
-						Ics04Error::implementation_specific(e.to_string())
-					})
-			});
+		// Crediting the receiver and forwarding over xcm either both happen or neither does, so
+		// that an error acknowledgement refunds the sender in full.
+		let result = with_transaction(|| {
+			let result = serde_json::from_slice(packet.data.as_slice())
+				.map_err(|e| {
+					Ics04Error::implementation_specific(format!(
+						"Failed to decode packet data {:?}",
+						e
+					))
+				})
+				.and_then(|packet_data: PacketData| {
+					process_recv_packet(&mut ctx, output, packet, packet_data.clone())
+						.and_then(|_| forward_over_xcm::<T>(packet, &packet_data))
+						.map(|_| packet_data)
+						.map_err(|e| {
+							log::trace!(target: "pallet_ibc", "[on_recv_packet]: {:?}", e);
+							Ics04Error::implementation_specific(e.to_string())
+						})
+				});
+			match result {
+				Ok(_) => TransactionOutcome::Commit(Ok(result)),
+				Err(_) => TransactionOutcome::Rollback(Ok(result)),
+			}
+		})
+		.map_err(|e: DispatchError| {
+			Ics04Error::implementation_specific(format!("[on_recv_packet] {:?}", e))
+		})?;
 		match result {
 			Err(err) => {
 				Pallet::<T>::write_acknowledgement(
@@ -391,8 +413,12 @@ impl<T: Config> CallbackWeight for WeightHandler<T> {
 		<T as Config>::WeightInfo::on_chan_close_confirm()
 	}
 
-	fn on_recv_packet(&self, _packet: &Packet) -> Weight {
-		<T as Config>::WeightInfo::on_recv_packet()
+	fn on_recv_packet(&self, packet: &Packet) -> Weight {
+		let forwards_over_xcm = serde_json::from_slice::<PacketData>(packet.data.as_slice())
+			.map(|data| matches!(xcm::xcm_destination::<T>(&data.memo), Ok(Some(_))))
+			.unwrap_or(false);
+		let xcm_weight = if forwards_over_xcm { T::XcmTransfer::weight() } else { 0 };
+		<T as Config>::WeightInfo::on_recv_packet().saturating_add(xcm_weight)
 	}
 
 	fn on_acknowledgement_packet(
@@ -408,6 +434,30 @@ impl<T: Config> CallbackWeight for WeightHandler<T> {
 	}
 }
 
+/// Reserve transfers the received tokens onward if the packet memo names an xcm destination.
+fn forward_over_xcm<T: Config>(
+	packet: &Packet,
+	packet_data: &PacketData,
+) -> Result<(), Ics20Error> {
+	let destination = match xcm::xcm_destination::<T>(&packet_data.memo)? {
+		Some(destination) => destination,
+		None => return Ok(()),
+	};
+	let receiver = T::AccountIdConversion::try_from(packet_data.receiver.clone())
+		.map_err(|_| Ics20Error::parse_account_failure())?
+		.into_account();
+	let denom = full_ibc_denom(packet, packet_data.token.clone());
+	let asset_id = T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(&denom)
+		.map_err(|_| Ics20Error::invalid_token())?;
+	let amount: T::Balance = u128::try_from(packet_data.token.amount.as_u256())
+		.map_err(|_| {
+			Ics20Error::implementation_specific("Amount does not fit in u128".to_string())
+		})?
+		.into();
+	T::XcmTransfer::reserve_transfer(receiver, asset_id, amount, destination)
+		.map_err(|e| Ics20Error::implementation_specific(format!("Xcm transfer failed: {:?}", e)))
+}
+
 pub fn full_ibc_denom(packet: &Packet, mut token: PrefixedCoin) -> String {
 	if is_receiver_chain_source(packet.source_port.clone(), packet.source_channel, &token.denom) {
 		let prefix = TracePrefix::new(packet.source_port.clone(), packet.source_channel);
@@ -0,0 +1,110 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Onward routing of ICS-20 transfers to sibling parachains over XCM.
+//!
+//! A received packet whose memo has the shape
+//! `{"xcm": {"para_id": 2000, "beneficiary": "<ss58 or hex account>"}}` is credited to the packet
+//! receiver as usual, after which the tokens are reserve transferred from the receiver to the
+//! beneficiary on the given parachain.
+
+use crate::Config;
+use alloc::format;
+use frame_support::weights::Weight;
+use ibc::{applications::transfer::error::Error as Ics20Error, signer::Signer};
+use sp_runtime::{traits::IdentifyAccount, DispatchError};
+use sp_std::str::FromStr;
+
+/// Key of the memo object holding the XCM destination.
+pub const XCM_MEMO_KEY: &str = "xcm";
+
+/// Sibling parachain account that received tokens should be forwarded to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XcmDestination<AccountId> {
+	/// Parachain the tokens are sent to.
+	pub para_id: u32,
+	/// Account credited on the destination parachain.
+	pub beneficiary: AccountId,
+}
+
+/// Decodes the destination from an ICS-20 packet memo. Returns `None` for memos that carry no
+/// XCM destination.
+pub fn xcm_destination<T: Config>(
+	memo: &str,
+) -> Result<Option<XcmDestination<T::AccountId>>, Ics20Error> {
+	if memo.is_empty() {
+		return Ok(None)
+	}
+	// Memos that aren't json are left to other applications.
+	let value = match serde_json::from_str::<serde_json::Value>(memo) {
+		Ok(value) => value,
+		Err(_) => return Ok(None),
+	};
+	let destination = match value.get(XCM_MEMO_KEY) {
+		Some(destination) => destination,
+		None => return Ok(None),
+	};
+	let para_id = destination
+		.get("para_id")
+		.and_then(|para_id| para_id.as_u64())
+		.and_then(|para_id| u32::try_from(para_id).ok())
+		.ok_or_else(|| invalid_memo("missing or invalid para_id"))?;
+	let beneficiary = destination
+		.get("beneficiary")
+		.and_then(|beneficiary| beneficiary.as_str())
+		.ok_or_else(|| invalid_memo("missing beneficiary"))?;
+	let beneficiary = Signer::from_str(beneficiary)
+		.ok()
+		.and_then(|signer| T::AccountIdConversion::try_from(signer).ok())
+		.ok_or_else(|| invalid_memo("invalid beneficiary"))?
+		.into_account();
+
+	Ok(Some(XcmDestination { para_id, beneficiary }))
+}
+
+fn invalid_memo(reason: &str) -> Ics20Error {
+	Ics20Error::implementation_specific(format!("Invalid xcm memo: {}", reason))
+}
+
+/// Reserve transfers tokens received over ICS-20 to a sibling parachain.
+pub trait XcmTransfer<T: Config> {
+	/// Sends `amount` of `asset_id` held by `from` to `destination`. An error causes the packet to
+	/// be acknowledged with an error, which refunds the sender on the source chain. Failures while
+	/// executing the message on the destination happen after the acknowledgement and are not
+	/// refunded.
+	fn reserve_transfer(
+		from: T::AccountId,
+		asset_id: T::AssetId,
+		amount: T::Balance,
+		destination: XcmDestination<T::AccountId>,
+	) -> Result<(), DispatchError>;
+
+	/// Weight of a single [`XcmTransfer::reserve_transfer`], charged on top of the ics20 receive.
+	fn weight() -> Weight;
+}
+
+impl<T: Config> XcmTransfer<T> for () {
+	fn reserve_transfer(
+		_from: T::AccountId,
+		_asset_id: T::AssetId,
+		_amount: T::Balance,
+		_destination: XcmDestination<T::AccountId>,
+	) -> Result<(), DispatchError> {
+		Err(DispatchError::Other("Xcm transfers are not supported"))
+	}
+
+	fn weight() -> Weight {
+		0
+	}
+}
@@ -23,7 +23,7 @@ use ibc::core::{
 		Path,
 	},
 };
-use ibc_primitives::apply_prefix;
+use ibc_primitives::{apply_prefix, paginate_child_trie, PageRequest, PageResponse};
 use sp_std::{marker::PhantomData, prelude::*, str::FromStr};
 
 /// (port_id, channel_id, sequence) => hash
@@ -77,4 +77,21 @@ impl<T: Config> Acknowledgements<T> {
 				None
 			})
 	}
+
+	/// Page of the acknowledgements of a channel, in trie key order
+	pub fn page_channel(
+		port_id: PortId,
+		channel_id: ChannelId,
+		pagination: Option<PageRequest>,
+	) -> (Vec<(Sequence, Vec<u8>)>, PageResponse) {
+		let prefix = format!("acks/ports/{}/channels/{}/sequences/", port_id, channel_id);
+		let prefix_key = apply_prefix(T::PALLET_PREFIX, vec![prefix]);
+		paginate_child_trie(
+			&ChildInfo::new_default(T::PALLET_PREFIX),
+			&prefix_key,
+			pagination,
+			|remaining_key| Sequence::from_str(sp_std::str::from_utf8(remaining_key).ok()?).ok(),
+			|sequence, value| (sequence, value),
+		)
+	}
 }
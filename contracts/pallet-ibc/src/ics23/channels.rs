@@ -23,7 +23,7 @@ use ibc::core::{
 		Path,
 	},
 };
-use ibc_primitives::apply_prefix;
+use ibc_primitives::{apply_prefix, paginate_child_trie, PageRequest, PageResponse};
 use sp_std::{marker::PhantomData, prelude::*, str::FromStr};
 use tendermint_proto::Protobuf;
 
@@ -68,4 +68,26 @@ impl<T: Config> Channels<T> {
 				None
 			})
 	}
+
+	/// Page of the channel ends, in trie key order
+	pub fn page(
+		pagination: Option<PageRequest>,
+	) -> (Vec<(PortId, ChannelId, Vec<u8>)>, PageResponse) {
+		let prefix = "channelEnds/ports/".to_string();
+		let key = apply_prefix(T::PALLET_PREFIX, vec![prefix.clone()]);
+		paginate_child_trie(
+			&ChildInfo::new_default(T::PALLET_PREFIX),
+			&key,
+			pagination,
+			|remaining_key| {
+				let path = format!("{prefix}{}", sp_std::str::from_utf8(remaining_key).ok()?);
+				match Path::from_str(&path).ok()? {
+					Path::ChannelEnds(ChannelEndsPath(port_id, channel_id)) =>
+						Some((port_id, channel_id)),
+					_ => None,
+				}
+			},
+			|(port_id, channel_id), channel_end| (port_id, channel_id, channel_end),
+		)
+	}
 }
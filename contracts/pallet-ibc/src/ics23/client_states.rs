@@ -16,7 +16,7 @@ use crate::{format, Config};
 use alloc::string::{String, ToString};
 use frame_support::storage::{child, child::ChildInfo, ChildTriePrefixIterator};
 use ibc::core::ics24_host::{identifier::ClientId, path::ClientStatePath, Path};
-use ibc_primitives::apply_prefix;
+use ibc_primitives::{apply_prefix, paginate_child_trie, PageRequest, PageResponse};
 use sp_std::{marker::PhantomData, prelude::*, str::FromStr};
 
 /// client_id => client_states
@@ -56,4 +56,23 @@ impl<T: Config> ClientStates<T> {
 				None
 			})
 	}
+
+	/// Page of the client states, in trie key order
+	pub fn page(pagination: Option<PageRequest>) -> (Vec<(ClientId, Vec<u8>)>, PageResponse) {
+		let prefix_path = "clients/".to_string();
+		let key = apply_prefix(T::PALLET_PREFIX, vec![prefix_path.clone()]);
+		paginate_child_trie(
+			&ChildInfo::new_default(T::PALLET_PREFIX),
+			&key,
+			pagination,
+			|remaining_key| {
+				let path = format!("{prefix_path}{}", sp_std::str::from_utf8(remaining_key).ok()?);
+				match Path::from_str(&path).ok()? {
+					Path::ClientState(ClientStatePath(client_id)) => Some(client_id),
+					_ => None,
+				}
+			},
+			|client_id, client_state| (client_id, client_state),
+		)
+	}
 }
@@ -19,8 +19,8 @@ use ibc::core::{
 	ics03_connection::connection::ConnectionEnd,
 	ics24_host::{identifier::ConnectionId, path::ConnectionsPath},
 };
-use ibc_primitives::apply_prefix;
-use sp_std::{marker::PhantomData, prelude::*};
+use ibc_primitives::{apply_prefix, paginate_child_trie, PageRequest, PageResponse};
+use sp_std::{marker::PhantomData, prelude::*, str::FromStr};
 use tendermint_proto::Protobuf;
 
 // todo: pruning
@@ -51,4 +51,19 @@ impl<T: Config> Connections<T> {
 		let key = apply_prefix(T::PALLET_PREFIX, vec![prefix_path]);
 		ChildTriePrefixIterator::with_prefix(&ChildInfo::new_default(T::PALLET_PREFIX), &key)
 	}
+
+	/// Page of the connection ends, in trie key order
+	pub fn page(pagination: Option<PageRequest>) -> (Vec<(ConnectionId, Vec<u8>)>, PageResponse) {
+		let prefix_path = "connections/".to_string();
+		let key = apply_prefix(T::PALLET_PREFIX, vec![prefix_path]);
+		paginate_child_trie(
+			&ChildInfo::new_default(T::PALLET_PREFIX),
+			&key,
+			pagination,
+			|remaining_key| {
+				ConnectionId::from_str(sp_std::str::from_utf8(remaining_key).ok()?).ok()
+			},
+			|connection_id, connection_end| (connection_id, connection_end),
+		)
+	}
 }
@@ -18,12 +18,12 @@ use ibc::{
 	core::ics24_host::{identifier::ClientId, path::ClientConsensusStatePath},
 	Height,
 };
-use ibc_primitives::apply_prefix;
-use sp_std::{marker::PhantomData, prelude::*};
+use ibc_primitives::{apply_prefix, paginate_child_trie, PageRequest, PageResponse};
+use sp_std::{marker::PhantomData, prelude::*, str::FromStr};
 
 /// client_id, height => consensus_state
 /// trie key path: "clients/{client_id}/consensusStates/{height}"
-/// todo: only store up to 250 (height => consensus_state) per client_id
+/// stale consensus states are pruned in `on_idle`, see `Pallet::prune_consensus_states`
 pub struct ConsensusStates<T>(PhantomData<T>);
 
 impl<T: Config> ConsensusStates<T> {
@@ -48,4 +48,31 @@ impl<T: Config> ConsensusStates<T> {
 		let key = apply_prefix(T::PALLET_PREFIX, vec![path]);
 		child::put(&ChildInfo::new_default(T::PALLET_PREFIX), &key, &consensus_state)
 	}
+
+	pub fn remove(client_id: ClientId, height: Height) {
+		let consensus_path = ClientConsensusStatePath {
+			client_id,
+			epoch: height.revision_number,
+			height: height.revision_height,
+		};
+		let path = format!("{}", consensus_path);
+		let key = apply_prefix(T::PALLET_PREFIX, vec![path]);
+		child::kill(&ChildInfo::new_default(T::PALLET_PREFIX), &key)
+	}
+
+	/// Page of the consensus states of a client, in trie key order
+	pub fn page_client(
+		client_id: ClientId,
+		pagination: Option<PageRequest>,
+	) -> (Vec<(Height, Vec<u8>)>, PageResponse) {
+		let prefix = format!("clients/{}/consensusStates/", client_id);
+		let prefix_key = apply_prefix(T::PALLET_PREFIX, vec![prefix]);
+		paginate_child_trie(
+			&ChildInfo::new_default(T::PALLET_PREFIX),
+			&prefix_key,
+			pagination,
+			|remaining_key| Height::from_str(sp_std::str::from_utf8(remaining_key).ok()?).ok(),
+			|height, value| (height, value),
+		)
+	}
 }
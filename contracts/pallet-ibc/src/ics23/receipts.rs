@@ -21,8 +21,8 @@ use ibc::core::{
 		path::ReceiptsPath,
 	},
 };
-use ibc_primitives::apply_prefix;
-use sp_std::{marker::PhantomData, prelude::*};
+use ibc_primitives::{apply_prefix, paginate_child_trie, PageRequest, PageResponse};
+use sp_std::{marker::PhantomData, prelude::*, str::FromStr};
 
 // todo: pruning
 /// (port_id, channel_id, sequence) => hash
@@ -60,4 +60,21 @@ impl<T: Config> PacketReceipt<T> {
 		let receipt_key = apply_prefix(T::PALLET_PREFIX, vec![receipt_path]);
 		child::exists(&ChildInfo::new_default(T::PALLET_PREFIX), &receipt_key)
 	}
+
+	/// Page of the packet receipts of a channel, in trie key order
+	pub fn page_channel(
+		port_id: PortId,
+		channel_id: ChannelId,
+		pagination: Option<PageRequest>,
+	) -> (Vec<(Sequence, Vec<u8>)>, PageResponse) {
+		let prefix = format!("receipts/ports/{}/channels/{}/sequences/", port_id, channel_id);
+		let prefix_key = apply_prefix(T::PALLET_PREFIX, vec![prefix]);
+		paginate_child_trie(
+			&ChildInfo::new_default(T::PALLET_PREFIX),
+			&prefix_key,
+			pagination,
+			|remaining_key| Sequence::from_str(sp_std::str::from_utf8(remaining_key).ok()?).ok(),
+			|sequence, value| (sequence, value),
+		)
+	}
 }
@@ -25,13 +25,25 @@ use crate::{
 		receipts::PacketReceipt,
 	},
 	light_clients::AnyClientState,
-	routing::Context,
+	routing::{Context, ModuleRouter},
 };
 use codec::{Decode, Encode};
-use frame_support::traits::Currency;
+use frame_support::{
+	dispatch::{DispatchError, DispatchResult, DispatchResultWithPostInfo},
+	storage::{with_transaction, TransactionOutcome},
+	traits::{Currency, ReservableCurrency},
+	weights::Weight,
+};
 use ibc::{
-	applications::transfer::{
-		msgs::transfer::MsgTransfer, relay::send_transfer::send_transfer, PrefixedCoin,
+	applications::{
+		icq::{
+			packet::{InterchainQueryPacketData, QueryRequest},
+			MODULE_ID_STR as ICQ_MODULE_ID, PORT_ID_STR as ICQ_PORT_ID,
+		},
+		transfer::{
+			msgs::transfer::MsgTransfer, relay::send_transfer::send_transfer, PrefixedCoin,
+			PORT_ID_STR as TRANSFER_PORT_ID,
+		},
 	},
 	core::{
 		ics02_client::{
@@ -52,8 +64,9 @@ use ibc::{
 				CommitmentsPath, ConnectionsPath, ReceiptsPath, SeqRecvsPath,
 			},
 		},
-		ics26_routing::handler::MsgReceipt,
+		ics26_routing::{context::ModuleId, error::Error as RoutingError, handler::MsgReceipt},
 	},
+	events::IbcEvent as RawIbcEvent,
 	handler::HandlerOutputBuilder,
 	signer::Signer,
 	timestamp::Timestamp,
@@ -62,26 +75,33 @@ use ibc::{
 };
 use ibc_primitives::{
 	apply_prefix, channel_id_from_bytes, client_id_from_bytes, connection_id_from_bytes,
-	get_channel_escrow_address, port_id_from_bytes, runtime_interface,
-	runtime_interface::SS58CodecError, ConnectionHandshake, Error as IbcHandlerError,
-	HandlerMessage, IbcHandler, IdentifiedChannel, IdentifiedClientState, IdentifiedConnection,
-	PacketInfo, PacketState, QueryChannelResponse, QueryChannelsResponse, QueryClientStateResponse,
-	QueryConnectionResponse, QueryConnectionsResponse, QueryConsensusStateResponse,
+	get_channel_escrow_address, paginate, port_id_from_bytes, runtime_interface,
+	runtime_interface::SS58CodecError, AllowedClient, ConnectionHandshake,
+	ConsensusStateWithHeight, Error as IbcHandlerError, HandlerMessage, IbcHandler,
+	IdentifiedChannel, IdentifiedClientState, IdentifiedConnection, ModuleCallbacks, PacketInfo,
+	PacketRecord, PacketState, PageRequest, PortCapability, QueryCallback, QueryCallbacks,
+	QueryChannelResponse, QueryChannelsResponse, QueryClientConnectionsResponse,
+	QueryClientStateResponse, QueryClientStatesResponse, QueryConnectionResponse,
+	QueryConnectionsResponse, QueryConsensusStateResponse, QueryConsensusStatesResponse,
 	QueryNextSequenceReceiveResponse, QueryPacketAcknowledgementResponse,
 	QueryPacketAcknowledgementsResponse, QueryPacketCommitmentResponse,
-	QueryPacketCommitmentsResponse, QueryPacketReceiptResponse,
+	QueryPacketCommitmentsResponse, QueryPacketReceiptResponse, QueryPacketReceiptsResponse,
+	SpamDepositStatus,
 };
 use scale_info::prelude::string::ToString;
 use sp_core::{crypto::AccountId32, offchain::StorageKind};
 use sp_runtime::{
 	offchain::storage::StorageValueRef,
-	traits::{Get, IdentifyAccount},
+	traits::{Get, IdentifyAccount, One, Saturating, Zero},
+	SaturatedConversion,
 };
 use tendermint_proto::Protobuf;
 
 pub const OFFCHAIN_SEND_PACKET_SEQS: &[u8] = b"pallet_ibc:pending_send_packet_sequences";
 pub const OFFCHAIN_RECV_PACKET_SEQS: &[u8] = b"pallet_ibc:pending_recv_packet_sequences";
 const PACKET_CLEANUP_PER_CYCLE: u64 = 1001;
+/// Maximum number of spam protection deposits slashed in a block.
+pub const MAX_SPAM_DEPOSIT_EXPIRIES_PER_BLOCK: u32 = 64;
 
 impl<T: Config> Pallet<T>
 where
@@ -89,30 +109,210 @@ where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
 	AccountId32: From<T::AccountId>,
 {
+	/// Executes the messages of a `deliver` call, packets relayed already, eg. by a competing
+	/// relayer, would be no-ops so they're not executed and only charged for the redundancy
+	/// check. Failed messages are charged in full, since they may fail after verifying their
+	/// proofs, which dominates their weight.
+	/// With `atomic` set, none of the messages take effect if any of them fails, their results
+	/// are still reported so the failing message and its error can be told apart.
+	pub(crate) fn deliver_messages(
+		sender: &T::AccountId,
+		messages: Vec<Any>,
+		atomic: bool,
+	) -> DispatchResultWithPostInfo {
+		let mut ctx = Context::<T>::new();
+		let redundancy_checks = crate::weight::redundancy_checks::<T>(&messages);
+		let mut results = vec![];
+		let mut indices = vec![];
+		let mut to_execute = vec![];
+		for (index, message) in messages.into_iter().enumerate() {
+			if crate::redundancy::is_redundant(&ctx, &message) {
+				results.push(MessageResult {
+					index: index as u32,
+					type_url: message.type_url,
+					outcome: MessageOutcome::Skipped,
+					error_code: None,
+				});
+			} else {
+				indices.push(index as u32);
+				to_execute.push(message);
+			}
+		}
+		let actual_weight =
+			crate::weight::deliver::<T>(&to_execute).saturating_add(redundancy_checks);
+
+		let messages = indices.into_iter().zip(to_execute).collect();
+		let (events, executed) = if atomic {
+			with_transaction(|| {
+				let (events, mut executed) = Self::execute_ibc_messages(&mut ctx, messages);
+				if executed.iter().all(|result| result.outcome != MessageOutcome::Failure) {
+					return TransactionOutcome::Commit(Ok::<_, DispatchError>((events, executed)))
+				}
+				for result in &mut executed {
+					if result.outcome == MessageOutcome::Success {
+						result.outcome = MessageOutcome::Reverted;
+					}
+				}
+				TransactionOutcome::Rollback(Ok((vec![], executed)))
+			})?
+		} else {
+			Self::execute_ibc_messages(&mut ctx, messages)
+		};
+		results.extend(executed);
+		results.sort_by_key(|result| result.index);
+
+		if !results.is_empty() {
+			Self::deposit_event(Event::<T>::MessagesDelivered { results });
+		}
+
+		// reserve a fixed deposit for every client and connection created
+		// so people don't spam our chain with useless clients, it's released
+		// once the connection handshake completes.
+		Self::settle_spam_deposits(sender, &events)?;
+
+		Ok(Some(actual_weight).into())
+	}
+
+	/// Executes `messages` paired with their index in the delivered batch, returning the events
+	/// they emitted and a result for each of them.
 	pub(crate) fn execute_ibc_messages(
 		ctx: &mut Context<T>,
-		messages: Vec<ibc_proto::google::protobuf::Any>,
-	) {
-		let (events, logs) =
-			messages.into_iter().fold((vec![], vec![]), |(mut events, mut logs), msg| {
-				match ibc::core::ics26_routing::handler::deliver(ctx, msg) {
+		messages: Vec<(u32, Any)>,
+	) -> (Vec<RawIbcEvent>, Vec<MessageResult>) {
+		let mut results = Vec::with_capacity(messages.len());
+		let (events, logs) = messages.into_iter().fold(
+			(vec![], vec![]),
+			|(mut events, mut logs), (index, Any { type_url, value })| {
+				let result = match String::from_utf8(type_url.clone()) {
+					Ok(type_url) => {
+						let message = ibc_proto::google::protobuf::Any { type_url, value };
+						crate::client::ensure_client_allowed::<T>(&message)
+							.and_then(|_| crate::client::validate_wasm_client::<T>(&message))
+							.map_err(RoutingError::ics02_client)
+							.and_then(|_| ibc::core::ics26_routing::handler::deliver(ctx, message))
+					},
+					Err(_) => Err(RoutingError::unknown_message_type_url(
+						String::from_utf8_lossy(&type_url).to_string(),
+					)),
+				};
+				match result {
 					Ok(MsgReceipt { events: temp_events, log: temp_logs }) => {
-						events.extend(temp_events.into_iter().map(Ok));
+						events.extend(temp_events);
 						logs.extend(temp_logs);
+						results.push(MessageResult {
+							index,
+							type_url,
+							outcome: MessageOutcome::Success,
+							error_code: None,
+						});
 					},
 					Err(e) => {
 						log::trace!(target: "pallet_ibc", "execution error: {}", e);
-						events.push(Err(e));
+						let error_code = crate::errors::failure_code(&e);
+						results.push(MessageResult {
+							index,
+							type_url,
+							outcome: MessageOutcome::Failure,
+							error_code: Some(error_code),
+						});
 					},
 				}
 				(events, logs)
-			});
+			},
+		);
 
 		log::trace!(target: "pallet_ibc", "logs: {:#?}", logs);
-		// todo: consolidate into one.
+		// failures are reported in `MessagesDelivered`, only the events of executed messages are
+		// emitted here
 		if !events.is_empty() {
-			Self::deposit_event(events.into())
+			let events = events.iter().cloned().map(|event| Ok(event.into())).collect();
+			Self::deposit_event(Event::<T>::Events { events })
 		};
+		(events, results)
+	}
+
+	/// Reserves the spam protection deposit from `depositor` for every client created and
+	/// connection initialized in `events`, and releases the deposits of connections that have
+	/// been opened along with those of their clients.
+	pub(crate) fn settle_spam_deposits(
+		depositor: &T::AccountId,
+		events: &[RawIbcEvent],
+	) -> DispatchResult {
+		for event in events {
+			match event {
+				RawIbcEvent::CreateClient(ev) =>
+					Self::reserve_spam_deposit(depositor, ev.client_id().as_bytes().to_vec())?,
+				RawIbcEvent::OpenInitConnection(ev) =>
+					if let Some(connection_id) = ev.connection_id() {
+						Self::reserve_spam_deposit(depositor, connection_id.as_bytes().to_vec())?
+					},
+				RawIbcEvent::OpenAckConnection(ev) => {
+					let attributes = ev.attributes();
+					if let Some(connection_id) = &attributes.connection_id {
+						Self::release_spam_deposit(connection_id.as_bytes().to_vec());
+					}
+					Self::release_spam_deposit(attributes.client_id.as_bytes().to_vec());
+				},
+				RawIbcEvent::OpenConfirmConnection(ev) => {
+					let attributes = ev.attributes();
+					if let Some(connection_id) = &attributes.connection_id {
+						Self::release_spam_deposit(connection_id.as_bytes().to_vec());
+					}
+					Self::release_spam_deposit(attributes.client_id.as_bytes().to_vec());
+				},
+				_ => {},
+			}
+		}
+		Ok(())
+	}
+
+	fn reserve_spam_deposit(depositor: &T::AccountId, id: Vec<u8>) -> DispatchResult {
+		let amount = T::SpamProtectionDeposit::get();
+		if amount.is_zero() {
+			return Ok(())
+		}
+		T::NativeCurrency::reserve(depositor, amount)?;
+		let reserved_at = frame_system::Pallet::<T>::block_number();
+		// deposits expiring at a block that's full are slashed in the following blocks, so the
+		// work of `on_initialize` stays bounded.
+		let mut expires_at = reserved_at.saturating_add(T::SpamDepositExpiry::get());
+		while SpamDepositExpiries::<T>::decode_len(expires_at).unwrap_or_default() >=
+			MAX_SPAM_DEPOSIT_EXPIRIES_PER_BLOCK as usize
+		{
+			expires_at = expires_at.saturating_add(One::one());
+		}
+		SpamDepositExpiries::<T>::append(expires_at, id.clone());
+		SpamDeposits::<T>::insert(
+			id,
+			SpamDeposit { depositor: depositor.clone(), amount, reserved_at },
+		);
+		Ok(())
+	}
+
+	fn release_spam_deposit(id: Vec<u8>) {
+		if let Some(SpamDeposit { depositor, amount, .. }) = SpamDeposits::<T>::take(&id) {
+			T::NativeCurrency::unreserve(&depositor, amount);
+			Self::deposit_event(Event::<T>::SpamDepositReleased { id, depositor, amount });
+		}
+	}
+
+	/// Slashes the deposits of clients and connections whose handshakes didn't complete before
+	/// `now`, at most [`MAX_SPAM_DEPOSIT_EXPIRIES_PER_BLOCK`] of them expire at a block.
+	pub(crate) fn slash_expired_spam_deposits(now: T::BlockNumber) -> Weight {
+		let ids = SpamDepositExpiries::<T>::take(now);
+		let mut slashed = 0u64;
+		for id in ids.iter() {
+			if let Some(SpamDeposit { depositor, amount, .. }) = SpamDeposits::<T>::take(id) {
+				let _ = T::NativeCurrency::slash_reserved(&depositor, amount);
+				Self::deposit_event(Event::<T>::SpamDepositSlashed {
+					id: id.clone(),
+					depositor,
+					amount,
+				});
+				slashed += 1;
+			}
+		}
+		T::DbWeight::get().reads_writes(1 + ids.len() as u64, 1 + slashed * 3)
 	}
 }
 
@@ -164,12 +364,39 @@ where
 		Ok(QueryClientStateResponse { client_state, trie_key: key, height: host_height::<T>() })
 	}
 
-	/// Get all client states
-	/// Returns a Vec of (client_id, client_state)
-	pub fn clients() -> Vec<(Vec<u8>, Vec<u8>)> {
-		ClientStates::<T>::iter()
-			.map(|(client_id, client_state)| (client_id.as_bytes().to_vec(), client_state))
-			.collect::<Vec<_>>()
+	/// Get a page of client states
+	pub fn clients(pagination: Option<PageRequest>) -> QueryClientStatesResponse {
+		let (client_states, pagination) = ClientStates::<T>::page(pagination);
+		let client_states = client_states
+			.into_iter()
+			.map(|(client_id, client_state)| IdentifiedClientState {
+				client_id: client_id.as_bytes().to_vec(),
+				client_state,
+			})
+			.collect();
+		QueryClientStatesResponse { client_states, pagination, height: host_height::<T>() }
+	}
+
+	/// Get a page of the consensus states stored for a client
+	pub fn consensus_states(
+		client_id: Vec<u8>,
+		pagination: Option<PageRequest>,
+	) -> Result<QueryConsensusStatesResponse, Error<T>> {
+		let client_id = client_id_from_bytes(client_id).map_err(|_| Error::<T>::DecodingError)?;
+		let (consensus_states, pagination) =
+			ConsensusStates::<T>::page_client(client_id, pagination);
+		let consensus_states = consensus_states
+			.into_iter()
+			.map(|(height, consensus_state)| ConsensusStateWithHeight {
+				height: (height.revision_number, height.revision_height),
+				consensus_state,
+			})
+			.collect();
+		Ok(QueryConsensusStatesResponse {
+			consensus_states,
+			pagination,
+			height: host_height::<T>(),
+		})
 	}
 
 	/// Get a consensus state for client
@@ -212,15 +439,14 @@ where
 	pub fn connection_using_client(
 		client_id: Vec<u8>,
 	) -> Result<Vec<IdentifiedConnection>, Error<T>> {
+		let client_id = client_id_from_bytes(client_id).map_err(|_| Error::<T>::DecodingError)?;
 		let connection_ids = ConnectionClient::<T>::get(client_id);
 		let connections = connection_ids
 			.into_iter()
 			.filter_map(|connection_id| {
-				let conn_id = connection_id_from_bytes(connection_id.clone()).ok()?;
-
 				Some(IdentifiedConnection {
-					connection_end: Connections::<T>::get(&conn_id)?,
-					connection_id,
+					connection_end: Connections::<T>::get(&connection_id)?,
+					connection_id: connection_id.as_bytes().to_vec(),
 				})
 			})
 			.collect::<Vec<_>>();
@@ -228,6 +454,17 @@ where
 		Ok(connections)
 	}
 
+	/// Get the ids of all connections of a client
+	pub fn client_connections(client_id: Vec<u8>) -> QueryClientConnectionsResponse {
+		let connection_ids = client_id_from_bytes(client_id)
+			.map(ConnectionClient::<T>::get)
+			.unwrap_or_default()
+			.into_iter()
+			.map(|connection_id| connection_id.as_bytes().to_vec())
+			.collect();
+		QueryClientConnectionsResponse { connection_ids, height: host_height::<T>() }
+	}
+
 	fn channel_client_id(channel_end: &ChannelEnd) -> Result<ClientId, Error<T>> {
 		let ctx = Context::<T>::default();
 		let connection_id =
@@ -255,103 +492,128 @@ where
 		Ok(IdentifiedClientState { client_id: client_id.as_bytes().to_vec(), client_state })
 	}
 
-	/// Get all channel states
-	pub fn channels() -> Result<QueryChannelsResponse, Error<T>> {
-		let channels = Channels::<T>::iter()
-			.map(|(port_id, channel_id, channel_end)| {
-				Ok(IdentifiedChannel { channel_id, port_id, channel_end })
+	/// Get a page of channel states
+	pub fn channels(pagination: Option<PageRequest>) -> Result<QueryChannelsResponse, Error<T>> {
+		let (channels, pagination) = Channels::<T>::page(pagination);
+		let channels = channels
+			.into_iter()
+			.map(|(port_id, channel_id, channel_end)| IdentifiedChannel {
+				channel_id: channel_id.to_string().as_bytes().to_vec(),
+				port_id: port_id.as_bytes().to_vec(),
+				channel_end,
 			})
-			.collect::<Result<Vec<_>, Error<T>>>()?;
+			.collect();
 
-		Ok(QueryChannelsResponse { channels, height: host_height::<T>() })
+		Ok(QueryChannelsResponse { channels, pagination, height: host_height::<T>() })
 	}
 
-	/// Get all connection states
-	pub fn connections() -> Result<QueryConnectionsResponse, Error<T>> {
-		let connections = Connections::<T>::iter()
-			.map(|(connection_id, connection_end)| {
-				Ok(IdentifiedConnection { connection_id, connection_end })
+	/// Get a page of connection states
+	pub fn connections(
+		pagination: Option<PageRequest>,
+	) -> Result<QueryConnectionsResponse, Error<T>> {
+		let (connections, pagination) = Connections::<T>::page(pagination);
+		let connections = connections
+			.into_iter()
+			.map(|(connection_id, connection_end)| IdentifiedConnection {
+				connection_id: connection_id.as_bytes().to_vec(),
+				connection_end,
 			})
-			.collect::<Result<Vec<_>, Error<T>>>()?;
+			.collect();
 
-		Ok(QueryConnectionsResponse { connections, height: host_height::<T>() })
+		Ok(QueryConnectionsResponse { connections, pagination, height: host_height::<T>() })
 	}
 
-	/// Get all channels bound to this connection
-	pub fn connection_channels(connection_id: Vec<u8>) -> Result<QueryChannelsResponse, Error<T>> {
-		let identifiers = ChannelsConnection::<T>::get(connection_id);
+	/// Get a page of the channels bound to this connection
+	pub fn connection_channels(
+		connection_id: Vec<u8>,
+		pagination: Option<PageRequest>,
+	) -> Result<QueryChannelsResponse, Error<T>> {
+		let connection_id =
+			connection_id_from_bytes(connection_id).map_err(|_| Error::<T>::DecodingError)?;
+		let identifiers = crate::migrations::v2::connection_channels::<T>(&connection_id);
 
 		let channels = identifiers
 			.into_iter()
-			.map(|(port_id_bytes, channel_id_bytes)| {
-				let channel_id = channel_id_from_bytes(channel_id_bytes.clone())
-					.map_err(|_| Error::<T>::DecodingError)?;
-				let port_id = port_id_from_bytes(port_id_bytes.clone())
-					.map_err(|_| Error::<T>::DecodingError)?;
-
+			.map(|(port_id, channel_id)| {
+				let port_id_bytes = port_id.as_bytes().to_vec();
+				let channel_id_bytes = channel_id.to_string().as_bytes().to_vec();
 				let channel_end =
 					Channels::<T>::get(port_id, channel_id).ok_or(Error::<T>::ChannelNotFound)?;
-				Ok(IdentifiedChannel {
-					channel_id: channel_id_bytes,
-					port_id: port_id_bytes,
-					channel_end,
-				})
+				let key = (port_id_bytes.clone(), channel_id_bytes.clone()).encode();
+				Ok((
+					key,
+					IdentifiedChannel {
+						channel_id: channel_id_bytes,
+						port_id: port_id_bytes,
+						channel_end,
+					},
+				))
 			})
 			.collect::<Result<Vec<_>, Error<T>>>()?;
-		Ok(QueryChannelsResponse { channels, height: host_height::<T>() })
+		let (channels, pagination) = paginate(channels, pagination);
+		Ok(QueryChannelsResponse { channels, pagination, height: host_height::<T>() })
+	}
+
+	fn packet_states(
+		channel_id_bytes: &[u8],
+		port_id_bytes: &[u8],
+		states: Vec<(Sequence, Vec<u8>)>,
+	) -> Vec<PacketState> {
+		states
+			.into_iter()
+			.map(|(sequence, data)| PacketState {
+				port_id: port_id_bytes.to_vec(),
+				channel_id: channel_id_bytes.to_vec(),
+				sequence: sequence.into(),
+				data,
+			})
+			.collect()
 	}
 
 	pub fn packet_commitments(
 		channel_id_bytes: Vec<u8>,
 		port_id_bytes: Vec<u8>,
+		pagination: Option<PageRequest>,
 	) -> Result<QueryPacketCommitmentsResponse, Error<T>> {
 		let channel_id = channel_id_from_bytes(channel_id_bytes.clone())
 			.map_err(|_| Error::<T>::DecodingError)?;
 		let port_id =
 			port_id_from_bytes(port_id_bytes.clone()).map_err(|_| Error::<T>::DecodingError)?;
-		let commitments = PacketCommitment::<T>::iter()
-			.filter_map(|((p, c, s), commitment)| {
-				if p == port_id && c == channel_id {
-					let packet_state = PacketState {
-						port_id: port_id_bytes.clone(),
-						channel_id: channel_id_bytes.clone(),
-						sequence: s.into(),
-						data: commitment,
-					};
-					Some(packet_state)
-				} else {
-					None
-				}
-			})
-			.collect::<Vec<_>>();
+		let (commitments, pagination) =
+			PacketCommitment::<T>::page_channel(port_id, channel_id, pagination);
+		let commitments = Self::packet_states(&channel_id_bytes, &port_id_bytes, commitments);
 
-		Ok(QueryPacketCommitmentsResponse { commitments, height: host_height::<T>() })
+		Ok(QueryPacketCommitmentsResponse { commitments, pagination, height: host_height::<T>() })
 	}
 
 	pub fn packet_acknowledgements(
 		channel_id_bytes: Vec<u8>,
 		port_id_bytes: Vec<u8>,
+		pagination: Option<PageRequest>,
 	) -> Result<QueryPacketAcknowledgementsResponse, Error<T>> {
 		let channel_id = channel_id_from_bytes(channel_id_bytes.clone())
 			.map_err(|_| Error::<T>::DecodingError)?;
 		let port_id =
 			port_id_from_bytes(port_id_bytes.clone()).map_err(|_| Error::<T>::DecodingError)?;
-		let acks = Acknowledgements::<T>::iter()
-			.filter_map(|((p, c, s), ack)| {
-				if p == port_id && c == channel_id {
-					let packet_state = PacketState {
-						port_id: port_id_bytes.clone(),
-						channel_id: channel_id_bytes.clone(),
-						sequence: s.into(),
-						data: ack,
-					};
-					Some(packet_state)
-				} else {
-					None
-				}
-			})
-			.collect::<Vec<_>>();
-		Ok(QueryPacketAcknowledgementsResponse { acks, height: host_height::<T>() })
+		let (acks, pagination) =
+			Acknowledgements::<T>::page_channel(port_id, channel_id, pagination);
+		let acks = Self::packet_states(&channel_id_bytes, &port_id_bytes, acks);
+		Ok(QueryPacketAcknowledgementsResponse { acks, pagination, height: host_height::<T>() })
+	}
+
+	pub fn packet_receipts(
+		channel_id_bytes: Vec<u8>,
+		port_id_bytes: Vec<u8>,
+		pagination: Option<PageRequest>,
+	) -> Result<QueryPacketReceiptsResponse, Error<T>> {
+		let channel_id = channel_id_from_bytes(channel_id_bytes.clone())
+			.map_err(|_| Error::<T>::DecodingError)?;
+		let port_id =
+			port_id_from_bytes(port_id_bytes.clone()).map_err(|_| Error::<T>::DecodingError)?;
+		let (receipts, pagination) =
+			PacketReceipt::<T>::page_channel(port_id, channel_id, pagination);
+		let receipts = Self::packet_states(&channel_id_bytes, &port_id_bytes, receipts);
+		Ok(QueryPacketReceiptsResponse { receipts, pagination, height: host_height::<T>() })
 	}
 
 	pub fn unreceived_packets(
@@ -453,6 +715,37 @@ where
 		Ok(QueryPacketReceiptResponse { receipt, trie_key: key, height: host_height::<T>() })
 	}
 
+	pub fn spam_deposit(client_or_connection_id: Vec<u8>) -> Option<SpamDepositStatus> {
+		let SpamDeposit { depositor, amount, reserved_at } =
+			SpamDeposits::<T>::get(client_or_connection_id)?;
+		Some(SpamDepositStatus {
+			depositor: depositor.encode(),
+			amount: amount.saturated_into::<u128>(),
+			reserved_at: reserved_at.into(),
+			expires_at: reserved_at.saturating_add(T::SpamDepositExpiry::get()).into(),
+		})
+	}
+
+	/// Module id of `M` as it's stored in the port registry, fails if `M` isn't registered in the
+	/// runtime's router.
+	fn registered_module_id<M: ModuleCallbacks>() -> Result<Vec<u8>, IbcHandlerError> {
+		let module_id =
+			ModuleId::from_str(M::MODULE_ID).map_err(|_| IbcHandlerError::BindPortError {
+				msg: Some(format!("Invalid module id {}", M::MODULE_ID)),
+			})?;
+		if !T::Router::has_route(&module_id) {
+			return Err(IbcHandlerError::BindPortError {
+				msg: Some(format!("Module {} isn't registered in the router", module_id)),
+			})
+		}
+		Ok(module_id.to_string().as_bytes().to_vec())
+	}
+
+	/// Light clients allowed to be created or upgraded to, `None` if any client is allowed.
+	pub fn allowed_clients() -> Option<Vec<AllowedClient>> {
+		AllowedClients::<T>::get()
+	}
+
 	pub fn connection_handshake(
 		client_id: Vec<u8>,
 		connection_id: Vec<u8>,
@@ -517,9 +810,12 @@ where
 		let port_id = key.0.as_bytes().to_vec();
 		let seq = u64::from(key.2);
 
-		let key = Pallet::<T>::offchain_ack_key(channel_id, port_id, seq);
+		let key = Pallet::<T>::offchain_ack_key(channel_id.clone(), port_id.clone(), seq);
 		sp_io::offchain_index::set(&key, &ack);
 		log::trace!(target: "pallet_ibc", "in channel: [store_raw_acknowledgement] >> writing acknowledgement {:?} {:?}", key, ack);
+		Self::deposit_event(Event::<T>::PacketRecorded {
+			record: PacketRecord::Ack { port_id, channel_id, sequence: seq, ack },
+		});
 		Ok(())
 	}
 
@@ -812,8 +1108,8 @@ where
 				Pallet::<T>::open_channel(port_id, channel_end),
 			HandlerMessage::CloseChannel { channel_id, port_id } =>
 				Pallet::<T>::close_channel(port_id, channel_id),
-			HandlerMessage::Transfer { timeout, to, from, channel_id, coin } => {
-				let msg = Pallet::<T>::to_msg_transfer(coin, from, to, timeout, channel_id)?;
+			HandlerMessage::Transfer { timeout, to, from, channel_id, coin, memo } => {
+				let msg = Pallet::<T>::to_msg_transfer(coin, from, to, timeout, channel_id, memo)?;
 				Pallet::<T>::send_transfer(msg)
 			},
 			HandlerMessage::SendPacket { data, timeout, port_id, channel_id } =>
@@ -850,6 +1146,119 @@ where
 		Ok(())
 	}
 
+	fn bind_port<M: ModuleCallbacks>(port_id: PortId) -> Result<PortCapability, IbcHandlerError> {
+		if matches!(port_id.as_str(), TRANSFER_PORT_ID | ICQ_PORT_ID) {
+			return Err(IbcHandlerError::BindPortError {
+				msg: Some(format!("Port {} is reserved by pallet-ibc", port_id)),
+			})
+		}
+		let module_id = Self::registered_module_id::<M>()?;
+		let port_id = port_id.as_bytes().to_vec();
+		if PortBindings::<T>::contains_key(&port_id) {
+			return Err(IbcHandlerError::BindPortError {
+				msg: Some("Port is already bound".to_string()),
+			})
+		}
+		let nonce = PortCapabilityCounter::<T>::mutate(|counter| {
+			*counter = counter.saturating_add(1);
+			*counter
+		});
+		PortBindings::<T>::insert(&port_id, module_id.clone());
+		PortCapabilities::<T>::insert(&port_id, nonce);
+		Self::deposit_event(Event::<T>::PortBound {
+			port_id: port_id.clone(),
+			module_id: module_id.clone(),
+		});
+		Ok(PortCapability::new(port_id, module_id, nonce))
+	}
+
+	fn release_port(capability: PortCapability) -> Result<(), IbcHandlerError> {
+		let port_id = capability.port_id().to_vec();
+		let owner = PortBindings::<T>::get(&port_id).zip(PortCapabilities::<T>::get(&port_id));
+		match owner {
+			Some((module_id, nonce))
+				if module_id == capability.module_id() && nonce == capability.nonce() =>
+			{
+				PortBindings::<T>::remove(&port_id);
+				PortCapabilities::<T>::remove(&port_id);
+				Self::deposit_event(Event::<T>::PortReleased { port_id, module_id });
+				Ok(())
+			},
+			Some(_) => Err(IbcHandlerError::BindPortError {
+				msg: Some("Capability wasn't issued for the port's binding".to_string()),
+			}),
+			None => Err(IbcHandlerError::BindPortError {
+				msg: Some("Port isn't bound with a capability".to_string()),
+			}),
+		}
+	}
+
+	fn bind_query_port<M: QueryCallback>(port_id: PortId) -> Result<(), IbcHandlerError> {
+		if matches!(port_id.as_str(), TRANSFER_PORT_ID | ICQ_PORT_ID) {
+			return Err(IbcHandlerError::BindPortError {
+				msg: Some(format!("Port {} is reserved by pallet-ibc", port_id)),
+			})
+		}
+		if !T::IcqCallback::has_callback(M::MODULE_ID) {
+			return Err(IbcHandlerError::BindPortError {
+				msg: Some(format!("Query callback {} isn't registered", M::MODULE_ID)),
+			})
+		}
+		let port_id = port_id.as_bytes().to_vec();
+		let module_id = M::MODULE_ID.as_bytes().to_vec();
+		if PortBindings::<T>::contains_key(&port_id) {
+			return Err(IbcHandlerError::BindPortError {
+				msg: Some("Port is already bound to another module".to_string()),
+			})
+		}
+		// query channels on this port are handled by the icq module, which delivers the results
+		// to the module in `IcqControllerPorts`
+		PortBindings::<T>::insert(&port_id, ICQ_MODULE_ID.as_bytes().to_vec());
+		IcqControllerPorts::<T>::insert(&port_id, module_id.clone());
+		Self::deposit_event(Event::<T>::PortBound { port_id, module_id });
+		Ok(())
+	}
+
+	fn send_query<M: QueryCallback>(
+		port_id: PortId,
+		channel_id: ChannelId,
+		requests: Vec<QueryRequest>,
+		timeout: Timeout,
+	) -> Result<u64, IbcHandlerError> {
+		if IcqControllerPorts::<T>::get(port_id.as_bytes()).as_deref() !=
+			Some(M::MODULE_ID.as_bytes())
+		{
+			return Err(IbcHandlerError::ChannelOrPortError {
+				msg: Some(format!("Port {} isn't bound by {}", port_id, M::MODULE_ID)),
+			})
+		}
+		if requests.is_empty() {
+			return Err(IbcHandlerError::SendPacketError {
+				msg: Some("Interchain query has no requests".to_string()),
+			})
+		}
+		let ctx = Context::<T>::new();
+		let sequence =
+			ctx.get_next_sequence_send(&(port_id.clone(), channel_id)).map_err(|_| {
+				IbcHandlerError::SendPacketError {
+					msg: Some(format!("Failed to get next_sequence_send for {}", channel_id)),
+				}
+			})?;
+		let data = serde_json::to_vec(&InterchainQueryPacketData { requests }).map_err(|e| {
+			IbcHandlerError::SendPacketError {
+				msg: Some(format!("Failed to encode query packet {:?}", e)),
+			}
+		})?;
+		Pallet::<T>::send_packet(data, timeout, port_id.clone(), channel_id)?;
+		let sequence = u64::from(sequence);
+		Self::deposit_event(Event::<T>::QuerySent {
+			port_id: port_id.as_bytes().to_vec(),
+			channel_id: channel_id.to_string().as_bytes().to_vec(),
+			sequence,
+		});
+		Ok(sequence)
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	fn create_client() -> Result<ClientId, IbcHandlerError> {
 		use crate::{
@@ -1026,6 +1435,7 @@ where
 		to: Signer,
 		timeout: Timeout,
 		channel_id: ChannelId,
+		memo: String,
 	) -> Result<MsgTransfer<PrefixedCoin>, IbcHandlerError> {
 		let account_id_32: AccountId32 = from.into();
 		let from = runtime_interface::account_id_to_ss58(
@@ -1089,6 +1499,7 @@ where
 			receiver: to,
 			timeout_height,
 			timeout_timestamp,
+			memo,
 		};
 		Ok(msg)
 	}
@@ -63,14 +63,19 @@ mod client;
 mod connection;
 pub mod errors;
 pub mod events;
+pub mod icq;
 pub mod ics20;
 mod ics23;
 pub mod light_clients;
+pub mod migrations;
 mod port;
+mod pruning;
+pub mod redundancy;
 pub mod routing;
 pub use client::HostConsensusProof;
 pub use ibc_primitives::Timeout;
 pub use light_client_common;
+pub use redundancy::CheckRedundantRelay;
 
 pub const MODULE_ID: &str = "pallet_ibc";
 
@@ -132,6 +137,61 @@ pub struct TransferParams<AccountId> {
 	pub source_channel: u64,
 	/// Timeout for this packet
 	pub timeout: Timeout,
+	/// Memo for the ics20 packet as valid utf8 string bytes, empty for none
+	pub memo: Vec<u8>,
+}
+
+/// Deposit reserved from the account that created a client or initialized a connection.
+#[derive(
+	frame_support::RuntimeDebug, PartialEq, Eq, scale_info::TypeInfo, Encode, Decode, Clone,
+)]
+pub struct SpamDeposit<AccountId, Balance, BlockNumber> {
+	/// Account the deposit was reserved from
+	pub depositor: AccountId,
+	/// Amount reserved
+	pub amount: Balance,
+	/// Block at which the deposit was reserved
+	pub reserved_at: BlockNumber,
+}
+
+/// Outcome of a single message delivered to the pallet.
+#[derive(
+	frame_support::RuntimeDebug, PartialEq, Eq, scale_info::TypeInfo, Encode, Decode, Clone,
+)]
+pub enum MessageOutcome {
+	/// The message was executed
+	Success,
+	/// Executing the message failed, see [`MessageResult::error_code`]
+	Failure,
+	/// The message wasn't executed because it had been relayed already
+	Skipped,
+	/// The message was executed but rolled back, because another message of an atomic batch
+	/// failed
+	Reverted,
+}
+
+/// Execution result of a message at `index` in a delivered batch.
+#[derive(
+	frame_support::RuntimeDebug, PartialEq, Eq, scale_info::TypeInfo, Encode, Decode, Clone,
+)]
+pub struct MessageResult {
+	/// Position of the message in the delivered batch
+	pub index: u32,
+	/// Type url of the message
+	pub type_url: Vec<u8>,
+	/// What happened to the message
+	pub outcome: MessageOutcome,
+	/// [`errors::failure_code`] of the error the message failed with
+	pub error_code: Option<u32>,
+}
+
+impl MessageResult {
+	/// Whether the message failed for a reason that may go away once the messages it depends on
+	/// have been executed, see [`errors::failure_reason::is_transient`].
+	pub fn is_transient_failure(&self) -> bool {
+		self.outcome == MessageOutcome::Failure &&
+			self.error_code.map_or(false, |code| errors::failure_reason::is_transient(code % 100))
+	}
 }
 
 pub enum LightClientProtocol {
@@ -180,12 +240,15 @@ pub mod pallet {
 		core::{
 			ics02_client::context::{ClientKeeper, ClientReader},
 			ics04_channel::context::ChannelReader,
-			ics24_host::identifier::{ChannelId, PortId},
+			ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
 		},
 		timestamp::Timestamp,
 		Height,
 	};
-	use ibc_primitives::{client_id_from_bytes, get_channel_escrow_address, IbcHandler};
+	use ibc_primitives::{
+		client_id_from_bytes, get_channel_escrow_address, AllowedClient, IbcHandler, PacketRecord,
+		QueryCallbacks,
+	};
 	use light_clients::AnyClientState;
 	use sp_runtime::{
 		traits::{IdentifyAccount, Saturating},
@@ -246,10 +309,31 @@ pub mod pallet {
 		/// Amount to be reserved for client and connection creation
 		#[pallet::constant]
 		type SpamProtectionDeposit: Get<Self::Balance>;
+		/// Number of blocks after which the deposit of a client or connection whose handshake
+		/// never reached `Open` is slashed
+		#[pallet::constant]
+		type SpamDepositExpiry: Get<Self::BlockNumber>;
+		/// Number of the latest consensus states of every client that are never pruned
+		#[pallet::constant]
+		type ConsensusStatesToRetain: Get<u32>;
+		/// Maximum weight spent pruning stale consensus states, and migrating storage beforehand,
+		/// in a block's idle time
+		#[pallet::constant]
+		type MaxPruningWeight: Get<Weight>;
+		/// Modules sending interchain queries, results are delivered to the module that bound the
+		/// port the query was sent from
+		type IcqCallback: QueryCallbacks;
+		/// Forwards tokens received over ics20 to sibling parachains when the packet memo names
+		/// an xcm destination
+		type XcmTransfer: crate::ics20::xcm::XcmTransfer<Self>;
 	}
 
+	/// The in-code storage version, see [`crate::migrations`] for the changes in every version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub (super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	#[pallet::without_storage_info]
 	pub struct Pallet<T>(_);
 
@@ -282,7 +366,7 @@ pub mod pallet {
 	#[allow(clippy::disallowed_types)]
 	/// connection_identifier => Vec<(port_id, channel_id)>
 	pub type ChannelsConnection<T: Config> =
-		StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>, ValueQuery>;
+		StorageMap<_, Blake2_128Concat, ConnectionId, Vec<(PortId, ChannelId)>, ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
@@ -308,7 +392,7 @@ pub mod pallet {
 	#[allow(clippy::disallowed_types)]
 	/// client_id => Vec<Connection_id>
 	pub type ConnectionClient<T: Config> =
-		StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<Vec<u8>>, ValueQuery>;
+		StorageMap<_, Blake2_128Concat, ClientId, Vec<ConnectionId>, ValueQuery>;
 
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
@@ -349,6 +433,85 @@ pub mod pallet {
 		ValueQuery,
 	>;
 
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// port_id => module_id
+	/// Ports bound by pallets through [`IbcHandler::bind_port`]
+	pub type PortBindings<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<u8>, OptionQuery>;
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// port_id => nonce of the [`PortCapability`](ibc_primitives::PortCapability) issued when the
+	/// port was bound, needed to release it
+	pub type PortCapabilities<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, u64, OptionQuery>;
+
+	#[pallet::storage]
+	/// Nonce of the last port capability issued
+	pub type PortCapabilityCounter<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// port_id => module_id
+	/// Ports bound through [`IbcHandler::bind_query_port`], interchain query results received on
+	/// these ports are delivered to the module's [`QueryCallback`](ibc_primitives::QueryCallback)
+	pub type IcqControllerPorts<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<u8>, OptionQuery>;
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// Storage key prefixes that counterparty chains are allowed to read through interchain
+	/// queries
+	pub type IcqAllowedPrefixes<T: Config> = StorageValue<_, Vec<Vec<u8>>, ValueQuery>;
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// Light client types, and the chain ids they may track, that clients can be created or
+	/// upgraded to, any client is allowed when unset
+	pub type AllowedClients<T: Config> = StorageValue<_, Vec<AllowedClient>, OptionQuery>;
+
+	#[pallet::storage]
+	/// client_id or connection_id => deposit reserved for its creation
+	pub type SpamDeposits<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		SpamDeposit<T::AccountId, T::Balance, T::BlockNumber>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// block_number => client and connection ids whose deposits expire at that block
+	pub type SpamDepositExpiries<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, Vec<Vec<u8>>, ValueQuery>;
+
+	#[pallet::storage]
+	/// client_id => () for every client with consensus states stored, walked when pruning them
+	pub type ConsensusStateClients<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, (), OptionQuery>;
+
+	#[pallet::storage]
+	/// client_id, big-endian (revision_number, revision_height) => () for every update time in
+	/// [`ClientUpdateTime`], walked in height order when pruning consensus states
+	pub type ConsensusStateHeights<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Identity, [u8; 16], (), OptionQuery>;
+
+	#[pallet::storage]
+	/// Raw key in [`ConsensusStateClients`] of the client consensus state pruning last finished
+	pub type PruningCursor<T: Config> = StorageValue<_, Vec<u8>, OptionQuery>;
+
+	#[pallet::storage]
+	/// Raw key in [`ClientUpdateTime`] the migration to v1 indexed consensus states up to, empty
+	/// before it indexed any and removed once it indexed all of them
+	pub type PruningIndexCursor<T: Config> = StorageValue<_, Vec<u8>, OptionQuery>;
+
+	#[pallet::storage]
+	/// Raw key in [`ChannelsConnection`], then in [`ConnectionClient`], the migration to v2
+	/// rewrote entries up to, removed once it rewrote all of them
+	pub type IdentifierMigrationCursor<T: Config> = StorageValue<_, Vec<u8>, OptionQuery>;
+
 	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 	pub struct AssetConfig<AssetId> {
 		pub id: AssetId,
@@ -385,8 +548,12 @@ pub mod pallet {
 	#[pallet::event]
 	#[pallet::generate_deposit(pub (super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		/// Events emitted by the ibc subsystem
+		/// Events emitted by the ibc subsystem. Failed messages are reported in
+		/// [`Event::MessagesDelivered`], errors are no longer emitted here and the `Err` variant
+		/// is deprecated.
 		Events { events: Vec<Result<events::IbcEvent, errors::IbcError>> },
+		/// Results of every message in a delivered batch, ordered by their index in the batch
+		MessagesDelivered { results: Vec<MessageResult> },
 		/// An Ibc token transfer has been started
 		TokenTransferInitiated {
 			from: Vec<u8>,
@@ -443,6 +610,30 @@ pub mod pallet {
 		ClientFrozen { client_id: Vec<u8>, height: u64, revision_number: u64 },
 		/// Asset Admin Account Updated
 		AssetAdminUpdated { admin_account: T::AccountId },
+		/// A port has been bound to a module
+		PortBound { port_id: Vec<u8>, module_id: Vec<u8> },
+		/// A port has been released by its module
+		PortReleased { port_id: Vec<u8>, module_id: Vec<u8> },
+		/// The storage prefixes allowed for interchain queries have been updated
+		IcqAllowListUpdated { prefixes: Vec<Vec<u8>> },
+		/// The light clients allowed to be created or upgraded to have been updated
+		AllowedClientsUpdated { allowed_clients: Option<Vec<AllowedClient>> },
+		/// An interchain query was sent to a counterparty chain
+		QuerySent { port_id: Vec<u8>, channel_id: Vec<u8>, sequence: u64 },
+		/// The result of an interchain query has been received
+		QueryResultReceived { port_id: Vec<u8>, channel_id: Vec<u8>, sequence: u64, success: bool },
+		/// An interchain query timed out
+		QueryTimedOut { port_id: Vec<u8>, channel_id: Vec<u8>, sequence: u64 },
+		/// Light client wasm code has been stored
+		WasmCodeStored { code_id: Vec<u8> },
+		/// A client or connection deposit has been unreserved
+		SpamDepositReleased { id: Vec<u8>, depositor: T::AccountId, amount: T::Balance },
+		/// A client or connection deposit has been slashed because its handshake never completed
+		SpamDepositSlashed { id: Vec<u8>, depositor: T::AccountId, amount: T::Balance },
+		/// Stale consensus states of a client have been pruned
+		ConsensusStatesPruned { client_id: Vec<u8>, count: u32 },
+		/// Packet data or an acknowledgement has been written
+		PacketRecorded { record: PacketRecord },
 	}
 
 	/// Errors inform users that something went wrong.
@@ -510,6 +701,8 @@ pub mod pallet {
 		ClientUpdateNotFound,
 		/// Error Freezing client
 		ClientFreezeFailed,
+		/// Light client wasm code is invalid or too large
+		InvalidWasmCode,
 	}
 
 	#[pallet::hooks]
@@ -519,6 +712,22 @@ pub mod pallet {
 		T: Send + Sync,
 		AccountId32: From<T::AccountId>,
 	{
+		fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+			Pallet::<T>::slash_expired_spam_deposits(n)
+		}
+
+		fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			let budget = remaining_weight.min(T::MaxPruningWeight::get());
+			let used = crate::migrations::v2::migrate_identifiers::<T>(budget);
+			let budget = budget.saturating_sub(used);
+			// consensus states are pruned once they're all indexed
+			used.saturating_add(if PruningIndexCursor::<T>::exists() {
+				crate::migrations::v1::index_consensus_states::<T>(budget)
+			} else {
+				Pallet::<T>::prune_consensus_states(budget)
+			})
+		}
+
 		fn offchain_worker(_n: BlockNumberFor<T>) {
 			let _ = Pallet::<T>::packet_cleanup();
 		}
@@ -534,43 +743,11 @@ pub mod pallet {
 		AccountId32: From<T::AccountId>,
 		u32: From<<T as frame_system::Config>::BlockNumber>,
 	{
-		#[pallet::weight(crate::weight::deliver::< T > (messages))]
+		#[pallet::weight(crate::weight::deliver_checked::< T > (messages))]
 		#[frame_support::transactional]
-		pub fn deliver(origin: OriginFor<T>, messages: Vec<Any>) -> DispatchResult {
-			use ibc::core::{
-				ics02_client::msgs::create_client, ics03_connection::msgs::conn_open_init,
-			};
+		pub fn deliver(origin: OriginFor<T>, messages: Vec<Any>) -> DispatchResultWithPostInfo {
 			let sender = ensure_signed(origin)?;
-
-			// reserve a fixed deposit for every client and connection created
-			// so people don't spam our chain with useless clients.
-			let mut ctx = routing::Context::<T>::new();
-			let mut reserve_count = 0u128;
-			let messages = messages
-				.into_iter()
-				.filter_map(|message| {
-					let type_url = String::from_utf8(message.type_url.clone()).ok()?;
-					if matches!(
-						type_url.as_str(),
-						create_client::TYPE_URL | conn_open_init::TYPE_URL
-					) {
-						reserve_count += 1;
-					}
-
-					Some(Ok(ibc_proto::google::protobuf::Any { type_url, value: message.value }))
-				})
-				.collect::<Result<Vec<ibc_proto::google::protobuf::Any>, Error<T>>>()?;
-			let reserve_amt = T::SpamProtectionDeposit::get().saturating_mul(reserve_count.into());
-
-			if reserve_amt >= T::SpamProtectionDeposit::get() {
-				<T::NativeCurrency as ReservableCurrency<T::AccountId>>::reserve(
-					&sender,
-					reserve_amt.into(),
-				)?;
-			}
-			Self::execute_ibc_messages(&mut ctx, messages);
-
-			Ok(())
+			Self::deliver_messages(&sender, messages, false)
 		}
 
 		#[frame_support::transactional]
@@ -647,6 +824,7 @@ pub mod pallet {
 				receiver: Signer::from_str(&to).map_err(|_| Error::<T>::Utf8Error)?,
 				timeout_height,
 				timeout_timestamp,
+				memo: String::from_utf8(params.memo).map_err(|_| Error::<T>::Utf8Error)?,
 			};
 			let is_sender_source = is_sender_chain_source(
 				msg.source_port.clone(),
@@ -731,6 +909,46 @@ pub mod pallet {
 			Ok(())
 		}
 
+		/// Set the storage key prefixes counterparty chains are allowed to read through
+		/// interchain queries, an empty list disables the icq host.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_icq_allowlist(origin: OriginFor<T>, prefixes: Vec<Vec<u8>>) -> DispatchResult {
+			<T as Config>::AdminOrigin::ensure_origin(origin)?;
+			<IcqAllowedPrefixes<T>>::put(prefixes.clone());
+			Self::deposit_event(Event::<T>::IcqAllowListUpdated { prefixes });
+			Ok(())
+		}
+
+		/// Set the light client types, and the chain ids for each of them, that clients can be
+		/// created or upgraded to, `None` allows any client.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_allowed_clients(
+			origin: OriginFor<T>,
+			allowed_clients: Option<Vec<AllowedClient>>,
+		) -> DispatchResult {
+			<T as Config>::AdminOrigin::ensure_origin(origin)?;
+			<AllowedClients<T>>::set(allowed_clients.clone());
+			Self::deposit_event(Event::<T>::AllowedClientsUpdated { allowed_clients });
+			Ok(())
+		}
+
+		/// Store light client wasm code, clients can be created for the code by setting its
+		/// blake2-256 hash as the `code_id` of a wasm client state.
+		#[pallet::weight(T::DbWeight::get().writes(1).saturating_add(
+			(code.len() as Weight).saturating_mul(light_clients::wasm::WEIGHT_PER_GAS),
+		))]
+		pub fn store_wasm_client_code(origin: OriginFor<T>, code: Vec<u8>) -> DispatchResult {
+			<T as Config>::AdminOrigin::ensure_origin(origin)?;
+			let code_id = light_clients::wasm::store_code(&code).map_err(|e| {
+				log::trace!(target: "pallet_ibc", "Failed to store wasm code: {:?}", e);
+				Error::<T>::InvalidWasmCode
+			})?;
+			Self::deposit_event(Event::<T>::WasmCodeStored {
+				code_id: code_id.as_bytes().to_vec(),
+			});
+			Ok(())
+		}
+
 		/// Freeze a client at a specific height
 		#[pallet::weight(0)]
 		pub fn freeze_client(
@@ -769,6 +987,25 @@ pub mod pallet {
 							.map_err(|_| Error::<T>::ClientFreezeFailed)?,
 					)
 				},
+				AnyClientState::Wasm(wasm) => {
+					let latest_height = wasm.latest_height();
+					AnyClientState::wrap(
+						&wasm
+							.with_frozen_height(Height::new(latest_height.revision_number, height))
+							.map_err(|_| Error::<T>::ClientFreezeFailed)?,
+					)
+				},
+				// A solo machine is always frozen at its current sequence
+				AnyClientState::SoloMachine(solo_machine) =>
+					AnyClientState::wrap(&solo_machine.frozen()),
+				AnyClientState::Ethereum(ethereum) => {
+					let latest_height = ethereum.latest_height();
+					AnyClientState::wrap(
+						&ethereum
+							.with_frozen_height(Height::new(latest_height.revision_number, height))
+							.map_err(|_| Error::<T>::ClientFreezeFailed)?,
+					)
+				},
 				#[cfg(test)]
 				AnyClientState::Mock(mut ms) => {
 					ms.frozen_height =
@@ -789,6 +1026,20 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		/// Same as [`Pallet::deliver`], but none of the messages take effect if any of them fails.
+		/// The results in [`Event::MessagesDelivered`] still report the failing message and its
+		/// error code, the messages that were executed before rolling back are reported as
+		/// [`MessageOutcome::Reverted`].
+		#[pallet::weight(crate::weight::deliver_checked::< T > (messages))]
+		#[frame_support::transactional]
+		pub fn deliver_atomic(
+			origin: OriginFor<T>,
+			messages: Vec<Any>,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			Self::deliver_messages(&sender, messages, true)
+		}
 	}
 }
 
@@ -12,7 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod wasm;
+
 use alloc::{borrow::ToOwned, format, string::ToString, vec::Vec};
+use core::time::Duration;
 use frame_support::{
 	pallet_prelude::{StorageValue, ValueQuery},
 	traits::StorageInstance,
@@ -24,6 +27,15 @@ use ibc::core::{
 use ibc_derive::{ClientDef, ClientMessage, ClientState, ConsensusState, Protobuf};
 use ibc_primitives::runtime_interface;
 use ibc_proto::google::protobuf::Any;
+use ics06_solomachine::{
+	client_message::SOLOMACHINE_CLIENT_MESSAGE_TYPE_URL,
+	client_state::SOLOMACHINE_CLIENT_STATE_TYPE_URL,
+	consensus_state::SOLOMACHINE_CONSENSUS_STATE_TYPE_URL,
+};
+use ics08_wasm::{
+	client_message::WASM_CLIENT_MESSAGE_TYPE_URL, client_state::WASM_CLIENT_STATE_TYPE_URL,
+	consensus_state::WASM_CONSENSUS_STATE_TYPE_URL,
+};
 use ics10_grandpa::{
 	client_message::{RelayChainHeader, GRANDPA_CLIENT_MESSAGE_TYPE_URL},
 	client_state::GRANDPA_CLIENT_STATE_TYPE_URL,
@@ -33,6 +45,10 @@ use ics11_beefy::{
 	client_message::BEEFY_CLIENT_MESSAGE_TYPE_URL, client_state::BEEFY_CLIENT_STATE_TYPE_URL,
 	consensus_state::BEEFY_CONSENSUS_STATE_TYPE_URL,
 };
+use ics_ethereum::{
+	client_message::ETHEREUM_CLIENT_MESSAGE_TYPE_URL, client_state::ETHEREUM_CLIENT_STATE_TYPE_URL,
+	consensus_state::ETHEREUM_CONSENSUS_STATE_TYPE_URL,
+};
 use sp_core::{ed25519, H256};
 use sp_runtime::{
 	app_crypto::RuntimePublic,
@@ -132,6 +148,9 @@ const GRANDPA_BLOCK_HASHES_CACHE_SIZE: u32 = 500;
 impl grandpa_client_primitives::HostFunctions for HostFunctionsManager {
 	type Header = RelayChainHeader;
 
+	// `ed25519_batch_verify` keeps the default sequential verification, the host's batch
+	// verification only queues signatures when a batch is open and accepts them until it's
+	// finished, so whether a justification is checked would depend on the execution context.
 	fn ed25519_verify(sig: &ed25519::Signature, msg: &[u8], pub_key: &ed25519::Public) -> bool {
 		pub_key.verify(&msg, sig)
 	}
@@ -170,6 +189,48 @@ impl grandpa_client_primitives::HostFunctions for HostFunctionsManager {
 	}
 }
 
+impl ics06_solomachine::HostFunctions for HostFunctionsManager {
+	fn sha2_256(message: &[u8]) -> [u8; 32] {
+		sp_io::hashing::sha2_256(message)
+	}
+
+	fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool {
+		let signature = ed25519::Signature::from_raw(*signature);
+		let public_key = ed25519::Public::from_raw(*public_key);
+		sp_io::crypto::ed25519_verify(&signature, message, &public_key)
+	}
+
+	fn secp256k1_verify(signature: &[u8; 64], message: &[u8; 32], public_key: &[u8; 33]) -> bool {
+		// the host only exposes recovery, so try both recovery ids against the expected key
+		(0..2).any(|recovery_id| {
+			let mut signature_with_id = [0u8; 65];
+			signature_with_id[..64].copy_from_slice(signature);
+			signature_with_id[64] = recovery_id;
+			sp_io::crypto::secp256k1_ecdsa_recover_compressed(&signature_with_id, message)
+				.map(|recovered| &recovered == public_key)
+				.unwrap_or(false)
+		})
+	}
+}
+
+impl ics_ethereum::HostFunctions for HostFunctionsManager {
+	fn sha256(message: &[u8]) -> [u8; 32] {
+		sp_io::hashing::sha2_256(message)
+	}
+
+	fn keccak_256(message: &[u8]) -> [u8; 32] {
+		sp_io::hashing::keccak_256(message)
+	}
+
+	fn bls_fast_aggregate_verify(
+		public_keys: &[[u8; 48]],
+		message: &[u8; 32],
+		signature: &[u8; 96],
+	) -> bool {
+		runtime_interface::bls12_381_fast_aggregate_verify(public_keys, message, signature)
+	}
+}
+
 impl light_client_common::HostFunctions for HostFunctionsManager {
 	type BlakeTwo256 = BlakeTwo256;
 }
@@ -191,6 +252,9 @@ pub enum AnyClient {
 	Grandpa(ics10_grandpa::client_def::GrandpaClient<HostFunctionsManager>),
 	Beefy(ics11_beefy::client_def::BeefyClient<HostFunctionsManager>),
 	Tendermint(ics07_tendermint::client_def::TendermintClient<HostFunctionsManager>),
+	Wasm(ics08_wasm::client_def::WasmClient<HostFunctionsManager>),
+	SoloMachine(ics06_solomachine::client_def::SoloMachineClient<HostFunctionsManager>),
+	Ethereum(ics_ethereum::client_def::EthereumClient<HostFunctionsManager>),
 	#[cfg(test)]
 	Mock(ibc::mock::client_def::MockClient),
 }
@@ -200,6 +264,9 @@ pub enum AnyUpgradeOptions {
 	Grandpa(ics10_grandpa::client_state::UpgradeOptions),
 	Beefy(ics11_beefy::client_state::UpgradeOptions),
 	Tendermint(ics07_tendermint::client_state::UpgradeOptions),
+	Wasm(Vec<u8>),
+	SoloMachine(()),
+	Ethereum(()),
 	#[cfg(test)]
 	Mock(()),
 }
@@ -212,11 +279,33 @@ pub enum AnyClientState {
 	Beefy(ics11_beefy::client_state::ClientState<HostFunctionsManager>),
 	#[ibc(proto_url = "TENDERMINT_CLIENT_STATE_TYPE_URL")]
 	Tendermint(ics07_tendermint::client_state::ClientState<HostFunctionsManager>),
+	#[ibc(proto_url = "WASM_CLIENT_STATE_TYPE_URL")]
+	Wasm(ics08_wasm::client_state::ClientState<HostFunctionsManager>),
+	#[ibc(proto_url = "SOLOMACHINE_CLIENT_STATE_TYPE_URL")]
+	SoloMachine(ics06_solomachine::client_state::ClientState<HostFunctionsManager>),
+	#[ibc(proto_url = "ETHEREUM_CLIENT_STATE_TYPE_URL")]
+	Ethereum(ics_ethereum::client_state::ClientState<HostFunctionsManager>),
 	#[cfg(test)]
 	#[ibc(proto_url = "MOCK_CLIENT_STATE_TYPE_URL")]
 	Mock(ibc::mock::client_state::MockClientState),
 }
 
+impl AnyClientState {
+	/// How long the client can go without updates before it expires, `None` if it doesn't expire
+	/// or only its contract knows.
+	pub fn trusting_period(&self) -> Option<Duration> {
+		match self {
+			Self::Grandpa(client_state) => Some(client_state.relay_chain.trusting_period()),
+			Self::Beefy(client_state) => Some(client_state.relay_chain.trusting_period()),
+			Self::Tendermint(client_state) => Some(client_state.trusting_period),
+			Self::Ethereum(client_state) => Some(client_state.trusting_period),
+			Self::Wasm(_) | Self::SoloMachine(_) => None,
+			#[cfg(test)]
+			Self::Mock(_) => None,
+		}
+	}
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, ConsensusState, Protobuf)]
 pub enum AnyConsensusState {
 	#[ibc(proto_url = "GRANDPA_CONSENSUS_STATE_TYPE_URL")]
@@ -225,6 +314,12 @@ pub enum AnyConsensusState {
 	Beefy(ics11_beefy::consensus_state::ConsensusState),
 	#[ibc(proto_url = "TENDERMINT_CONSENSUS_STATE_TYPE_URL")]
 	Tendermint(ics07_tendermint::consensus_state::ConsensusState),
+	#[ibc(proto_url = "WASM_CONSENSUS_STATE_TYPE_URL")]
+	Wasm(ics08_wasm::consensus_state::ConsensusState),
+	#[ibc(proto_url = "SOLOMACHINE_CONSENSUS_STATE_TYPE_URL")]
+	SoloMachine(ics06_solomachine::consensus_state::ConsensusState),
+	#[ibc(proto_url = "ETHEREUM_CONSENSUS_STATE_TYPE_URL")]
+	Ethereum(ics_ethereum::consensus_state::ConsensusState),
 	#[cfg(test)]
 	#[ibc(proto_url = "MOCK_CONSENSUS_STATE_TYPE_URL")]
 	Mock(ibc::mock::client_state::MockConsensusState),
@@ -239,6 +334,12 @@ pub enum AnyClientMessage {
 	Beefy(ics11_beefy::client_message::ClientMessage),
 	#[ibc(proto_url = "TENDERMINT_CLIENT_MESSAGE_TYPE_URL")]
 	Tendermint(ics07_tendermint::client_message::ClientMessage),
+	#[ibc(proto_url = "WASM_CLIENT_MESSAGE_TYPE_URL")]
+	Wasm(ics08_wasm::client_message::ClientMessage),
+	#[ibc(proto_url = "SOLOMACHINE_CLIENT_MESSAGE_TYPE_URL")]
+	SoloMachine(ics06_solomachine::client_message::ClientMessage),
+	#[ibc(proto_url = "ETHEREUM_CLIENT_MESSAGE_TYPE_URL")]
+	Ethereum(ics_ethereum::client_message::ClientMessage),
 	#[cfg(test)]
 	#[ibc(proto_url = "MOCK_CLIENT_MESSAGE_TYPE_URL")]
 	Mock(ibc::mock::header::MockClientMessage),
@@ -263,6 +364,18 @@ impl TryFrom<Any> for AnyClientMessage {
 				ics07_tendermint::client_message::ClientMessage::decode_vec(&value.value)
 					.map_err(ics02_client::error::Error::decode_raw_header)?,
 			)),
+			WASM_CLIENT_MESSAGE_TYPE_URL => Ok(Self::Wasm(
+				ics08_wasm::client_message::ClientMessage::decode_vec(&value.value)
+					.map_err(ics02_client::error::Error::decode_raw_header)?,
+			)),
+			SOLOMACHINE_CLIENT_MESSAGE_TYPE_URL => Ok(Self::SoloMachine(
+				ics06_solomachine::client_message::ClientMessage::decode_vec(&value.value)
+					.map_err(ics02_client::error::Error::decode_raw_header)?,
+			)),
+			ETHEREUM_CLIENT_MESSAGE_TYPE_URL => Ok(Self::Ethereum(
+				ics_ethereum::client_message::ClientMessage::decode_vec(&value.value)
+					.map_err(ics02_client::error::Error::decode_raw_header)?,
+			)),
 			_ => Err(ics02_client::error::Error::unknown_consensus_state_type(value.type_url)),
 		}
 	}
@@ -281,6 +394,16 @@ impl From<AnyClientMessage> for Any {
 				type_url: TENDERMINT_CLIENT_MESSAGE_TYPE_URL.to_string(),
 				value: msg.encode_vec(),
 			},
+			AnyClientMessage::Wasm(msg) =>
+				Any { type_url: WASM_CLIENT_MESSAGE_TYPE_URL.to_string(), value: msg.encode_vec() },
+			AnyClientMessage::SoloMachine(msg) => Any {
+				type_url: SOLOMACHINE_CLIENT_MESSAGE_TYPE_URL.to_string(),
+				value: msg.encode_vec(),
+			},
+			AnyClientMessage::Ethereum(msg) => Any {
+				type_url: ETHEREUM_CLIENT_MESSAGE_TYPE_URL.to_string(),
+				value: msg.encode_vec(),
+			},
 			#[cfg(test)]
 			AnyClientMessage::Mock(_msg) => panic!("MockHeader can't be serialized"),
 		}
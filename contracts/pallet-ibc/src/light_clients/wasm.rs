@@ -0,0 +1,347 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage and sandboxed execution of ICS-08 light client wasm code.
+
+use super::HostFunctionsManager;
+use alloc::{format, string::String, vec, vec::Vec};
+use codec::{Decode, Encode};
+use frame_support::{
+	pallet_prelude::{OptionQuery, StorageMap},
+	traits::StorageInstance,
+	weights::Weight,
+	Identity,
+};
+use ibc::Height;
+use ics08_wasm::{
+	contract::{host_fn, ContractCall, WasmExecutor, WasmHostContext, DISPATCH_EXPORT, RETURN_OK},
+	error::Error,
+};
+use sp_core::{ed25519, H256};
+use sp_sandbox::{
+	default_executor::{EnvironmentDefinitionBuilder, Instance, Memory},
+	HostError, ReturnValue, SandboxEnvironmentBuilder, SandboxInstance, SandboxMemory, Value,
+};
+use wasm_instrument::{gas_metering, parity_wasm::elements};
+
+/// Maximum number of wasm instructions a single call into light client code may execute.
+pub const WASM_CLIENT_GAS_LIMIT: u64 = 100_000_000;
+/// Weight charged per unit of gas, one unit of gas is one wasm instruction.
+pub const WEIGHT_PER_GAS: Weight = 1_000;
+/// Maximum size of light client wasm code before instrumentation.
+pub const MAX_WASM_CODE_SIZE: usize = 3 * 1024 * 1024;
+/// Maximum number of 64KiB memory pages light client code can use.
+const MAX_MEMORY_PAGES: u32 = 64;
+/// Maximum stack height of light client code, in the units of
+/// [`wasm_instrument::inject_stack_limiter`].
+const MAX_STACK_HEIGHT: u32 = 64 * 1024;
+/// Gas charged for an ed25519 signature verification.
+const ED25519_VERIFY_GAS: u64 = 50_000;
+/// Gas charged for a storage read or write.
+const STORAGE_ACCESS_GAS: u64 = 25_000;
+/// Prefix of the storage light client code can write to, followed by the client id.
+const CLIENT_STORAGE_PREFIX: &[u8] = b"ibc.lightclients.wasm/";
+
+/// Instrumented light client wasm code.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct WasmCode {
+	/// Code with gas metering and stack limiting injected.
+	pub code: Vec<u8>,
+	/// Number of memory pages the code imports.
+	pub initial_pages: u32,
+	/// Maximum number of memory pages the code can grow its memory to.
+	pub maximum_pages: u32,
+}
+
+pub struct WasmClientCodesStorageInstance;
+impl StorageInstance for WasmClientCodesStorageInstance {
+	fn pallet_prefix() -> &'static str {
+		"ibc.lightclients.wasm"
+	}
+
+	const STORAGE_PREFIX: &'static str = "Codes";
+}
+/// Light client wasm code by the blake2-256 hash of the original code.
+pub type WasmClientCodesStorage =
+	StorageMap<WasmClientCodesStorageInstance, Identity, H256, WasmCode, OptionQuery>;
+
+/// Weight of `calls` calls into light client code. The gas used by a call is only known after
+/// it's executed, so every call is charged at [`WASM_CLIENT_GAS_LIMIT`].
+pub fn calls_weight(calls: u64) -> Weight {
+	calls.saturating_mul(WASM_CLIENT_GAS_LIMIT).saturating_mul(WEIGHT_PER_GAS)
+}
+
+/// Weight of the light client code execution in a client creation, the code is called for the
+/// chain id checked against the allowed clients and to validate the latest height.
+pub fn create_client_weight() -> Weight {
+	calls_weight(2)
+}
+
+/// Weight of the light client code execution in a client update, the code is called to check
+/// whether the client expired, verify the client message, check it for misbehaviour and update
+/// the client state.
+pub fn update_client_weight() -> Weight {
+	calls_weight(4)
+}
+
+/// Weight of the light client code execution in a client upgrade, the code is called for the
+/// chain id checked against the allowed clients and to verify the upgrade.
+pub fn upgrade_client_weight() -> Weight {
+	calls_weight(2)
+}
+
+/// Weight of verifying `proofs` membership or non-membership proofs with light client code.
+pub fn verify_proofs_weight(proofs: u64) -> Weight {
+	calls_weight(proofs)
+}
+
+/// Instrument and store light client wasm code, returns the id of the code.
+pub fn store_code(code: &[u8]) -> Result<H256, Error> {
+	if code.len() > MAX_WASM_CODE_SIZE {
+		Err(Error::Custom(format!("Wasm code is larger than {MAX_WASM_CODE_SIZE} bytes")))?
+	}
+	let code_id = H256(sp_io::hashing::blake2_256(code));
+	let module = elements::deserialize_buffer::<elements::Module>(code)
+		.map_err(|e| Error::Custom(format!("Invalid wasm code: {e:?}")))?;
+	if module.start_section().is_some() {
+		Err(Error::Custom("Wasm code must not have a start function".into()))?
+	}
+	let (initial_pages, maximum_pages) = memory_limits(&module)?;
+	let module = gas_metering::inject(module, &gas_metering::ConstantCostRules::default(), "env")
+		.map_err(|_| Error::Custom("Failed to inject gas metering".into()))?;
+	let module = wasm_instrument::inject_stack_limiter(module, MAX_STACK_HEIGHT)
+		.map_err(|e| Error::Custom(format!("Failed to inject stack limiter: {e}")))?;
+	let code = elements::serialize(module)
+		.map_err(|e| Error::Custom(format!("Failed to serialize wasm code: {e:?}")))?;
+
+	WasmClientCodesStorage::insert(code_id, WasmCode { code, initial_pages, maximum_pages });
+
+	Ok(code_id)
+}
+
+/// Limits of the `env.memory` import, capped at [`MAX_MEMORY_PAGES`].
+fn memory_limits(module: &elements::Module) -> Result<(u32, u32), Error> {
+	let limits = module
+		.import_section()
+		.and_then(|section| {
+			section.entries().iter().find_map(|entry| match entry.external() {
+				elements::External::Memory(memory)
+					if entry.module() == "env" && entry.field() == "memory" =>
+					Some(*memory.limits()),
+				_ => None,
+			})
+		})
+		.ok_or_else(|| Error::Custom("Wasm code must import env.memory".into()))?;
+	let maximum = limits.maximum().unwrap_or(MAX_MEMORY_PAGES).min(MAX_MEMORY_PAGES);
+	if limits.initial() > maximum {
+		Err(Error::Custom(format!("Wasm code requires more than {maximum} memory pages")))?
+	}
+
+	Ok((limits.initial(), maximum))
+}
+
+/// State of a single call into light client code.
+struct Runtime<'a> {
+	host: &'a dyn WasmHostContext,
+	memory: Memory,
+	/// Encoded [`ContractCall`]
+	input: Vec<u8>,
+	/// Output set by the code
+	output: Vec<u8>,
+	/// Result of the last host function that returns a variable length value
+	buffer: Vec<u8>,
+	gas_left: u64,
+}
+
+impl<'a> Runtime<'a> {
+	fn charge(&mut self, gas: u64) -> Result<(), HostError> {
+		self.gas_left = self.gas_left.checked_sub(gas).ok_or(HostError)?;
+		Ok(())
+	}
+
+	fn read(&mut self, ptr: u32, len: u32) -> Result<Vec<u8>, HostError> {
+		if len > MAX_MEMORY_PAGES * 64 * 1024 {
+			return Err(HostError)
+		}
+		self.charge(len as u64)?;
+		let mut buf = vec![0u8; len as usize];
+		self.memory.get(ptr, &mut buf).map_err(|_| HostError)?;
+		Ok(buf)
+	}
+
+	fn write(&mut self, ptr: u32, data: &[u8]) -> Result<(), HostError> {
+		self.charge(data.len() as u64)?;
+		self.memory.set(ptr, data).map_err(|_| HostError)
+	}
+
+	/// Load a variable length value into the buffer, returns its length or -1 if there's none.
+	fn load(&mut self, value: Option<Vec<u8>>) -> Result<ReturnValue, HostError> {
+		let len = match value {
+			Some(value) => {
+				self.charge(value.len() as u64)?;
+				let len = value.len() as i32;
+				self.buffer = value;
+				len
+			},
+			None => -1,
+		};
+		Ok(ReturnValue::Value(Value::I32(len)))
+	}
+
+	/// Storage key of `key` in the private storage of the executing client.
+	fn storage_key(&self, key: &[u8]) -> Result<Vec<u8>, HostError> {
+		let client_id = self.host.client_id().ok_or(HostError)?;
+		Ok([CLIENT_STORAGE_PREFIX, client_id.as_bytes(), b"/", key].concat())
+	}
+}
+
+fn arg_u32(args: &[Value], index: usize) -> Result<u32, HostError> {
+	match args.get(index) {
+		Some(Value::I32(value)) => Ok(*value as u32),
+		_ => Err(HostError),
+	}
+}
+
+fn arg_u64(args: &[Value], index: usize) -> Result<u64, HostError> {
+	match args.get(index) {
+		Some(Value::I64(value)) => Ok(*value as u64),
+		_ => Err(HostError),
+	}
+}
+
+fn gas(rt: &mut Runtime, args: &[Value]) -> Result<ReturnValue, HostError> {
+	rt.charge(arg_u32(args, 0)? as u64)?;
+	Ok(ReturnValue::Unit)
+}
+
+fn input_len(rt: &mut Runtime, _args: &[Value]) -> Result<ReturnValue, HostError> {
+	Ok(ReturnValue::Value(Value::I32(rt.input.len() as i32)))
+}
+
+fn input(rt: &mut Runtime, args: &[Value]) -> Result<ReturnValue, HostError> {
+	let input = core::mem::take(&mut rt.input);
+	rt.write(arg_u32(args, 0)?, &input)?;
+	rt.input = input;
+	Ok(ReturnValue::Unit)
+}
+
+fn set_output(rt: &mut Runtime, args: &[Value]) -> Result<ReturnValue, HostError> {
+	rt.output = rt.read(arg_u32(args, 0)?, arg_u32(args, 1)?)?;
+	Ok(ReturnValue::Unit)
+}
+
+fn read_buffer(rt: &mut Runtime, args: &[Value]) -> Result<ReturnValue, HostError> {
+	let buffer = core::mem::take(&mut rt.buffer);
+	rt.write(arg_u32(args, 0)?, &buffer)?;
+	Ok(ReturnValue::Unit)
+}
+
+fn consensus_state(rt: &mut Runtime, args: &[Value]) -> Result<ReturnValue, HostError> {
+	rt.charge(STORAGE_ACCESS_GAS)?;
+	let height = Height::new(arg_u64(args, 0)?, arg_u64(args, 1)?);
+	let consensus_state = rt.host.consensus_state(height);
+	rt.load(consensus_state)
+}
+
+fn storage_get(rt: &mut Runtime, args: &[Value]) -> Result<ReturnValue, HostError> {
+	rt.charge(STORAGE_ACCESS_GAS)?;
+	let key = rt.read(arg_u32(args, 0)?, arg_u32(args, 1)?)?;
+	let value = sp_io::storage::get(&rt.storage_key(&key)?).map(|value| value.to_vec());
+	rt.load(value)
+}
+
+fn storage_set(rt: &mut Runtime, args: &[Value]) -> Result<ReturnValue, HostError> {
+	rt.charge(STORAGE_ACCESS_GAS)?;
+	let key = rt.read(arg_u32(args, 0)?, arg_u32(args, 1)?)?;
+	let value = rt.read(arg_u32(args, 2)?, arg_u32(args, 3)?)?;
+	sp_io::storage::set(&rt.storage_key(&key)?, &value);
+	Ok(ReturnValue::Unit)
+}
+
+fn host_timestamp(rt: &mut Runtime, _args: &[Value]) -> Result<ReturnValue, HostError> {
+	Ok(ReturnValue::Value(Value::I64(rt.host.host_timestamp() as i64)))
+}
+
+fn ed25519_verify(rt: &mut Runtime, args: &[Value]) -> Result<ReturnValue, HostError> {
+	rt.charge(ED25519_VERIFY_GAS)?;
+	let mut signature = [0u8; 64];
+	signature.copy_from_slice(&rt.read(arg_u32(args, 0)?, 64)?);
+	let message = rt.read(arg_u32(args, 1)?, arg_u32(args, 2)?)?;
+	let mut public_key = [0u8; 32];
+	public_key.copy_from_slice(&rt.read(arg_u32(args, 3)?, 32)?);
+	let valid = sp_io::crypto::ed25519_verify(
+		&ed25519::Signature::from_raw(signature),
+		&message,
+		&ed25519::Public::from_raw(public_key),
+	);
+	Ok(ReturnValue::Value(Value::I32(valid as i32)))
+}
+
+fn blake2_256(rt: &mut Runtime, args: &[Value]) -> Result<ReturnValue, HostError> {
+	let data = rt.read(arg_u32(args, 0)?, arg_u32(args, 1)?)?;
+	rt.write(arg_u32(args, 2)?, &sp_io::hashing::blake2_256(&data))?;
+	Ok(ReturnValue::Unit)
+}
+
+impl WasmExecutor for HostFunctionsManager {
+	fn execute(
+		code_id: &[u8],
+		host: &dyn WasmHostContext,
+		call: &ContractCall,
+	) -> Result<Vec<u8>, Error> {
+		if code_id.len() != 32 {
+			Err(Error::Executor(format!("Invalid code id length: {}", code_id.len())))?
+		}
+		let code_id = H256::from_slice(code_id);
+		let code = WasmClientCodesStorage::get(code_id)
+			.ok_or_else(|| Error::Executor(format!("No wasm code stored with id {code_id:?}")))?;
+		let memory = Memory::new(code.initial_pages, Some(code.maximum_pages))
+			.map_err(|e| Error::Executor(format!("Failed to allocate memory: {e:?}")))?;
+
+		let mut env = EnvironmentDefinitionBuilder::new();
+		env.add_memory("env", "memory", memory.clone());
+		env.add_host_func("env", "gas", gas);
+		env.add_host_func("env", host_fn::INPUT_LEN, input_len);
+		env.add_host_func("env", host_fn::INPUT, input);
+		env.add_host_func("env", host_fn::SET_OUTPUT, set_output);
+		env.add_host_func("env", host_fn::CONSENSUS_STATE, consensus_state);
+		env.add_host_func("env", host_fn::STORAGE_GET, storage_get);
+		env.add_host_func("env", host_fn::STORAGE_SET, storage_set);
+		env.add_host_func("env", host_fn::READ_BUFFER, read_buffer);
+		env.add_host_func("env", host_fn::HOST_TIMESTAMP, host_timestamp);
+		env.add_host_func("env", host_fn::ED25519_VERIFY, ed25519_verify);
+		env.add_host_func("env", host_fn::BLAKE2_256, blake2_256);
+
+		let mut runtime = Runtime {
+			host,
+			memory,
+			input: call.encode(),
+			output: vec![],
+			buffer: vec![],
+			gas_left: WASM_CLIENT_GAS_LIMIT,
+		};
+		let mut instance = Instance::new(&code.code, &env, &mut runtime)
+			.map_err(|e| Error::Executor(format!("Failed to instantiate wasm code: {e:?}")))?;
+		let result = instance
+			.invoke(DISPATCH_EXPORT, &[], &mut runtime)
+			.map_err(|e| Error::Executor(format!("Wasm code execution failed: {e:?}")))?;
+
+		match result {
+			ReturnValue::Value(Value::I32(RETURN_OK)) => Ok(runtime.output),
+			ReturnValue::Value(Value::I32(_)) =>
+				Err(Error::Contract(String::from_utf8_lossy(&runtime.output).into_owned())),
+			_ => Err(Error::Executor("Unexpected return value from wasm code".into())),
+		}
+	}
+}
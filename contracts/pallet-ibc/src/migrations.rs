@@ -0,0 +1,40 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Migrations of the pallet's storage, to be included in a runtime's `Executive`.
+//!
+//! Every migration moves storage from the previous version to its own and is a no-op on storage
+//! at any other version, so [`Migrations`] can always be included as a whole:
+//! - `0`: storage layout before versioning
+//! - `1`: [`v1`], consensus states are indexed for pruning, over several blocks
+//! - `2`: [`v2`], connections and channels are keyed by typed identifiers
+//!
+//! Storage items still holding raw identifier bytes are left to later versions, each needs its own
+//! migration:
+//! - [`ClientUpdateTime`](crate::ClientUpdateTime) and
+//!   [`ClientUpdateHeight`](crate::ClientUpdateHeight) are keyed by raw client ids and encoded
+//!   heights, their keys are also the pruning cursors' positions, so they're migrated along with
+//!   [`PruningCursor`](crate::PruningCursor) once no migration to v1 is pending on any chain
+//! - [`SpamDeposits`](crate::SpamDeposits) is keyed by client and connection ids alike, typing it
+//!   needs a key telling the two apart
+//! - [`ChannelIds`](crate::ChannelIds) holds raw channel ids in a single value, so it's migrated
+//!   within a block once the runtime reads it typed
+//! - [`IbcDenoms`](crate::IbcDenoms) is keyed by denominations, which aren't identifiers and stay
+//!   raw
+
+pub mod v1;
+pub mod v2;
+
+/// All migrations of the pallet's storage, in version order.
+pub type Migrations<T> = (v1::MigrateToV1<T>, v2::MigrateToV2<T>);
@@ -0,0 +1,138 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Indexes the clients whose consensus states were stored before they were pruned, and the
+//! heights of those consensus states, so that their stale consensus states are pruned as well.
+//!
+//! There can be any number of consensus states stored, so they're indexed over as many blocks as
+//! needed in the idle time of every block, see [`index_consensus_states`], and only pruned once
+//! they're all indexed.
+
+use crate::{
+	pruning::{height_key, WeightMeter},
+	*,
+};
+#[cfg(feature = "try-runtime")]
+use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+use frame_support::{
+	traits::{Get, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use ibc::Height;
+use tendermint_proto::Protobuf;
+
+/// Migrates storage from version 0 to 1 by starting to fill [`ConsensusStateClients`] and
+/// [`ConsensusStateHeights`].
+pub struct MigrateToV1<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+	fn on_runtime_upgrade() -> Weight {
+		let on_chain_version = StorageVersion::get::<Pallet<T>>();
+		if on_chain_version != 0 {
+			log::info!(target: "pallet_ibc", "Skipping migration to v1, storage is at {:?}", on_chain_version);
+			return T::DbWeight::get().reads(1)
+		}
+
+		PruningIndexCursor::<T>::put(Vec::<u8>::new());
+		StorageVersion::new(1).put::<Pallet<T>>();
+		log::info!(target: "pallet_ibc", "Indexing consensus states for pruning in idle time");
+		T::DbWeight::get().reads_writes(1, 2)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		if StorageVersion::get::<Pallet<T>>() == 0 {
+			let update_times = ClientUpdateTime::<T>::iter_keys().count() as u32;
+			Self::set_temp_storage(update_times, "update_times");
+		}
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		frame_support::ensure!(
+			StorageVersion::get::<Pallet<T>>() >= 1,
+			"pallet-ibc storage wasn't migrated to v1"
+		);
+		let update_times = match Self::get_temp_storage::<u32>("update_times") {
+			Some(update_times) => update_times,
+			None => return Ok(()),
+		};
+		// index every consensus state, as the following blocks would in their idle time
+		index_consensus_states::<T>(Weight::MAX);
+		frame_support::ensure!(
+			!PruningIndexCursor::<T>::exists(),
+			"consensus states weren't all indexed for pruning"
+		);
+		let mut indexed = 0u32;
+		for (client_id, encoded_height) in ClientUpdateTime::<T>::iter_keys() {
+			frame_support::ensure!(
+				ConsensusStateClients::<T>::contains_key(&client_id),
+				"client of a consensus state wasn't indexed for pruning"
+			);
+			if let Ok(height) = Height::decode_vec(&encoded_height) {
+				frame_support::ensure!(
+					ConsensusStateHeights::<T>::contains_key(&client_id, height_key(height)),
+					"height of a consensus state wasn't indexed for pruning"
+				);
+			}
+			indexed += 1;
+		}
+		frame_support::ensure!(
+			indexed == update_times,
+			"consensus states lost in migration to v1"
+		);
+		Ok(())
+	}
+}
+
+/// Indexes the consensus states in [`ClientUpdateTime`] within `budget`, resuming from
+/// [`PruningIndexCursor`] and removing it once every consensus state is indexed.
+pub(crate) fn index_consensus_states<T: Config>(budget: Weight) -> Weight {
+	let db_weight = T::DbWeight::get();
+	let mut meter = WeightMeter::new(budget);
+	if !meter.charge(db_weight.reads_writes(1, 1)) {
+		return meter.used
+	}
+	let cursor = PruningIndexCursor::<T>::get().unwrap_or_default();
+	let mut updates = if cursor.is_empty() {
+		ClientUpdateTime::<T>::iter_keys()
+	} else {
+		ClientUpdateTime::<T>::iter_keys_from(cursor)
+	};
+
+	let mut indexed = 0u32;
+	// reading an update time, indexing its client and height and moving the cursor past it
+	while meter.charge(db_weight.reads_writes(1, 3)) {
+		let (client_id, encoded_height) = match updates.next() {
+			Some(key) => key,
+			None => {
+				PruningIndexCursor::<T>::kill();
+				log::info!(target: "pallet_ibc", "Indexed every consensus state for pruning");
+				return meter.used
+			},
+		};
+		if let Ok(height) = Height::decode_vec(&encoded_height) {
+			ConsensusStateHeights::<T>::insert(&client_id, height_key(height), ());
+		}
+		ConsensusStateClients::<T>::insert(&client_id, ());
+		PruningIndexCursor::<T>::put(ClientUpdateTime::<T>::hashed_key_for(
+			&client_id,
+			&encoded_height,
+		));
+		indexed += 1;
+	}
+	log::debug!(target: "pallet_ibc", "Indexed {} consensus states for pruning", indexed);
+	meter.used
+}
@@ -0,0 +1,246 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Keys [`ConnectionClient`] and [`ChannelsConnection`] by typed identifiers instead of their raw
+//! bytes, entries with identifiers that don't parse are dropped.
+//!
+//! There can be any number of entries, so they're rewritten over as many blocks as needed in the
+//! idle time of every block, see [`migrate_identifiers`]. Channel identifiers are encoded
+//! differently once typed, so until an entry of [`ChannelsConnection`] is rewritten it's read and
+//! appended to in its old layout, see [`connection_channels`] and [`append_connection_channel`].
+//! [`ConnectionClient`] keeps its layout, only entries with invalid identifiers are rewritten.
+
+use crate::{pruning::WeightMeter, *};
+#[cfg(feature = "try-runtime")]
+use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+use frame_support::{
+	storage::StoragePrefixedMap,
+	traits::{Get, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use ibc::core::ics24_host::identifier::{ChannelId, ConnectionId, PortId};
+use ibc_primitives::{
+	channel_id_from_bytes, client_id_from_bytes, connection_id_from_bytes, port_id_from_bytes,
+};
+
+/// Storage items as they were laid out before this migration.
+pub mod v1 {
+	use crate::{Config, Pallet};
+	use frame_support::Blake2_128Concat;
+	use sp_std::prelude::*;
+
+	#[frame_support::storage_alias]
+	/// client_id => Vec<connection_id>
+	pub type ConnectionClient<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, Vec<u8>, Vec<Vec<u8>>, ValueQuery>;
+
+	#[frame_support::storage_alias]
+	/// connection_id => Vec<(port_id, channel_id)>
+	pub type ChannelsConnection<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>, ValueQuery>;
+}
+
+/// Migrates storage from version 1 to 2 by starting to rewrite [`ChannelsConnection`] and
+/// [`ConnectionClient`].
+pub struct MigrateToV2<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+	fn on_runtime_upgrade() -> Weight {
+		let on_chain_version = StorageVersion::get::<Pallet<T>>();
+		if on_chain_version != 1 {
+			log::info!(target: "pallet_ibc", "Skipping migration to v2, storage is at {:?}", on_chain_version);
+			return T::DbWeight::get().reads(1)
+		}
+
+		IdentifierMigrationCursor::<T>::put(v1::ChannelsConnection::<T>::final_prefix().to_vec());
+		StorageVersion::new(2).put::<Pallet<T>>();
+		log::info!(target: "pallet_ibc", "Migrating to typed identifiers in idle time");
+		T::DbWeight::get().reads_writes(1, 2)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		// runs before the migration to v1 as well, the layout migrated here is the same in both
+		if StorageVersion::get::<Pallet<T>>() < 2 {
+			let connection_clients = v1::ConnectionClient::<T>::iter_keys()
+				.filter(|client_id| client_id_from_bytes(client_id.clone()).is_ok())
+				.count() as u32;
+			let channels_connections = v1::ChannelsConnection::<T>::iter_keys()
+				.filter(|connection_id| connection_id_from_bytes(connection_id.clone()).is_ok())
+				.count() as u32;
+			Self::set_temp_storage(connection_clients, "connection_clients");
+			Self::set_temp_storage(channels_connections, "channels_connections");
+		}
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		frame_support::ensure!(
+			StorageVersion::get::<Pallet<T>>() >= 2,
+			"pallet-ibc storage wasn't migrated to v2"
+		);
+		let (connection_clients, channels_connections) = match (
+			Self::get_temp_storage::<u32>("connection_clients"),
+			Self::get_temp_storage::<u32>("channels_connections"),
+		) {
+			(Some(connection_clients), Some(channels_connections)) =>
+				(connection_clients, channels_connections),
+			_ => return Ok(()),
+		};
+		// rewrite every entry, as the following blocks would in their idle time
+		migrate_identifiers::<T>(Weight::MAX);
+		frame_support::ensure!(
+			!IdentifierMigrationCursor::<T>::exists(),
+			"entries weren't all rewritten with typed identifiers"
+		);
+		// entries that don't decode with typed identifiers are skipped
+		frame_support::ensure!(
+			ConnectionClient::<T>::iter().count() as u32 == connection_clients,
+			"connection clients lost in migration to v2"
+		);
+		frame_support::ensure!(
+			ChannelsConnection::<T>::iter().count() as u32 == channels_connections,
+			"connection channels lost in migration to v2"
+		);
+		Ok(())
+	}
+}
+
+/// Rewrites the entries of [`ChannelsConnection`], then those of [`ConnectionClient`], within
+/// `budget`, resuming from [`IdentifierMigrationCursor`] and removing it once every entry is
+/// rewritten.
+pub(crate) fn migrate_identifiers<T: Config>(budget: Weight) -> Weight {
+	let db_weight = T::DbWeight::get();
+	let mut meter = WeightMeter::new(budget);
+	if !meter.charge(db_weight.reads_writes(1, 1)) {
+		return meter.used
+	}
+	let mut cursor = match IdentifierMigrationCursor::<T>::get() {
+		Some(cursor) => cursor,
+		None => return meter.used,
+	};
+
+	let mut rewritten = 0u32;
+	let mut dropped = 0u32;
+	if cursor.starts_with(&v1::ChannelsConnection::<T>::final_prefix()) {
+		let mut entries = v1::ChannelsConnection::<T>::iter_from(cursor.clone());
+		// reading an entry and rewriting it
+		while meter.charge(db_weight.reads_writes(1, 1)) {
+			let (raw_connection_id, channels) = match entries.next() {
+				Some(entry) => entry,
+				None => {
+					cursor = v1::ConnectionClient::<T>::final_prefix().to_vec();
+					break
+				},
+			};
+			cursor = v1::ChannelsConnection::<T>::hashed_key_for(&raw_connection_id);
+			match connection_id_from_bytes(raw_connection_id.clone()) {
+				Ok(connection_id) => {
+					let channels = channels
+						.into_iter()
+						.filter_map(|channel| {
+							let channel = typed_channel(channel);
+							dropped += channel.is_none() as u32;
+							channel
+						})
+						.collect::<Vec<_>>();
+					ChannelsConnection::<T>::insert(connection_id, channels);
+				},
+				Err(_) => {
+					v1::ChannelsConnection::<T>::remove(&raw_connection_id);
+					dropped += 1;
+				},
+			}
+			rewritten += 1;
+		}
+	}
+
+	if cursor.starts_with(&v1::ConnectionClient::<T>::final_prefix()) {
+		let mut entries = v1::ConnectionClient::<T>::iter_from(cursor.clone());
+		// reading an entry and rewriting it if it has invalid identifiers
+		while meter.charge(db_weight.reads_writes(1, 1)) {
+			let (raw_client_id, connection_ids) = match entries.next() {
+				Some(entry) => entry,
+				None => {
+					IdentifierMigrationCursor::<T>::kill();
+					log::info!(target: "pallet_ibc", "Migrated to typed identifiers, dropped {} invalid identifiers", dropped);
+					return meter.used
+				},
+			};
+			cursor = v1::ConnectionClient::<T>::hashed_key_for(&raw_client_id);
+			if client_id_from_bytes(raw_client_id.clone()).is_err() {
+				v1::ConnectionClient::<T>::remove(&raw_client_id);
+				dropped += 1;
+				continue
+			}
+			let count = connection_ids.len();
+			let connection_ids = connection_ids
+				.into_iter()
+				.filter(|connection_id| connection_id_from_bytes(connection_id.clone()).is_ok())
+				.collect::<Vec<_>>();
+			if connection_ids.len() != count {
+				dropped += (count - connection_ids.len()) as u32;
+				v1::ConnectionClient::<T>::insert(&raw_client_id, connection_ids);
+				rewritten += 1;
+			}
+		}
+	}
+
+	IdentifierMigrationCursor::<T>::put(cursor);
+	log::debug!(target: "pallet_ibc", "Rewrote {} entries keyed by identifiers, dropped {} invalid identifiers", rewritten, dropped);
+	meter.used
+}
+
+/// Whether the entry of `connection_id` in [`ChannelsConnection`] is still in its old layout.
+fn channels_pending<T: Config>(connection_id: &ConnectionId) -> bool {
+	IdentifierMigrationCursor::<T>::get().map_or(false, |cursor| {
+		cursor.starts_with(&v1::ChannelsConnection::<T>::final_prefix()) &&
+			ChannelsConnection::<T>::hashed_key_for(connection_id) > cursor
+	})
+}
+
+fn typed_channel((port_id, channel_id): (Vec<u8>, Vec<u8>)) -> Option<(PortId, ChannelId)> {
+	port_id_from_bytes(port_id).ok().zip(channel_id_from_bytes(channel_id).ok())
+}
+
+/// The channels of `connection_id` in [`ChannelsConnection`], in whichever layout they are.
+pub(crate) fn connection_channels<T: Config>(
+	connection_id: &ConnectionId,
+) -> Vec<(PortId, ChannelId)> {
+	if channels_pending::<T>(connection_id) {
+		v1::ChannelsConnection::<T>::get(connection_id.as_bytes().to_vec())
+			.into_iter()
+			.filter_map(typed_channel)
+			.collect()
+	} else {
+		ChannelsConnection::<T>::get(connection_id)
+	}
+}
+
+/// Appends a channel to the entry of `connection_id` in [`ChannelsConnection`], in whichever
+/// layout it is.
+pub(crate) fn append_connection_channel<T: Config>(
+	connection_id: &ConnectionId,
+	channel: (PortId, ChannelId),
+) {
+	if channels_pending::<T>(connection_id) {
+		v1::ChannelsConnection::<T>::append(
+			connection_id.as_bytes().to_vec(),
+			(channel.0.as_bytes().to_vec(), channel.1.to_string().into_bytes()),
+		)
+	} else {
+		ChannelsConnection::<T>::append(connection_id, channel)
+	}
+}
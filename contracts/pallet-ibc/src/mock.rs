@@ -12,18 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{self as pallet_ibc, routing::ModuleRouter};
+use crate::{self as pallet_ibc, routing::PortRegistryRouter};
 use cumulus_primitives_core::ParaId;
 use frame_support::{
 	pallet_prelude::ConstU32,
 	parameter_types,
 	traits::{
-		fungibles::{metadata::Mutate, Create, InspectMetadata},
-		ConstU64, Everything,
+		fungibles::{metadata::Mutate, Create, InspectMetadata, Transfer},
+		ConstU64, Everything, GenesisBuild,
 	},
+	weights::{RuntimeDbWeight, Weight},
 };
 use frame_system as system;
-use ibc_primitives::IbcAccount;
+use ibc::{
+	applications::icq::packet::{QueryRequest, QueryResponse},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			error::Error as Ics04Error,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+	},
+};
+use ibc_primitives::{IbcAccount, ModuleAdaptor, ModuleCallbacks, QueryCallback};
 use light_client_common::RelayChain;
 use orml_traits::parameter_type_with_key;
 use sp_core::{
@@ -34,9 +46,9 @@ use sp_keystore::{testing::KeyStore, KeystoreExt};
 use sp_runtime::{
 	generic,
 	traits::{BlakeTwo256, IdentityLookup},
-	MultiSignature,
+	DispatchError, MultiSignature,
 };
-use std::sync::Arc;
+use std::{cell::RefCell, sync::Arc};
 use system::EnsureRoot;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -88,13 +100,14 @@ parameter_types! {
 	pub const SS58Prefix: u8 = 49;
 	pub const ExpectedBlockTime: u64 = 1000;
 	pub const ExistentialDeposit: u128 = 10000;
+	pub static DbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 0, write: 0 };
 }
 
 impl system::Config for Test {
 	type BaseCallFilter = Everything;
 	type BlockWeights = ();
 	type BlockLength = ();
-	type DbWeight = ();
+	type DbWeight = DbWeight;
 	type Origin = Origin;
 	type Call = Call;
 	type Index = u64;
@@ -154,7 +167,10 @@ parameter_types! {
 	pub const MaxLocks: u32 = 256;
 	pub static ParachainId: ParaId = ParaId::from(2087);
 	pub static RelayChainId: RelayChain = RelayChain::Rococo;
-	pub const SpamProtectionDeposit: u128 = 0;
+	pub static SpamProtectionDeposit: u128 = 0;
+	pub const SpamDepositExpiry: u32 = 100;
+	pub const ConsensusStatesToRetain: u32 = 2;
+	pub const MaxPruningWeight: u64 = u64::MAX;
 }
 
 parameter_type_with_key! {
@@ -201,6 +217,11 @@ impl Config for Test {
 	type AdminOrigin = EnsureRoot<AccountId>;
 	type SentryOrigin = EnsureRoot<AccountId>;
 	type SpamProtectionDeposit = SpamProtectionDeposit;
+	type SpamDepositExpiry = SpamDepositExpiry;
+	type ConsensusStatesToRetain = ConsensusStatesToRetain;
+	type MaxPruningWeight = MaxPruningWeight;
+	type IcqCallback = (MockQuerier,);
+	type XcmTransfer = MockXcmTransfer;
 }
 
 impl pallet_timestamp::Config for Test {
@@ -218,8 +239,13 @@ fn register_offchain_ext(ext: &mut sp_io::TestExternalities) {
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	let mut ext: sp_io::TestExternalities =
-		system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	GenesisBuild::<Test>::assimilate_storage(
+		&pallet_ibc_ping::GenesisConfig::default(),
+		&mut storage,
+	)
+	.unwrap();
+	let mut ext: sp_io::TestExternalities = storage.into();
 	register_offchain_ext(&mut ext);
 	ext.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
 	ext
@@ -264,34 +290,116 @@ where
 	}
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Default)]
-pub struct Router {
-	ibc_ping: pallet_ibc_ping::IbcModule<Test>,
+/// Callback module that accepts every channel, used to test port ownership in the registry.
+pub struct MockModule;
+
+impl ModuleCallbacks for MockModule {
+	const MODULE_ID: &'static str = "MockModule";
+	type WeightHandler = ();
+
+	fn on_chan_open_try(
+		_order: Order,
+		_connection_hops: &[ConnectionId],
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty: &Counterparty,
+		version: &Version,
+		_counterparty_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		Ok(version.clone())
+	}
 }
 
-impl ModuleRouter for Router {
-	fn get_route_mut(
-		&mut self,
-		module_id: &ibc::core::ics26_routing::context::ModuleId,
-	) -> Option<&mut dyn ibc::core::ics26_routing::context::Module> {
-		match module_id.as_ref() {
-			pallet_ibc_ping::MODULE_ID => Some(&mut self.ibc_ping),
-			&_ => None,
-		}
+/// Callback module that isn't listed in [`Router`], it can't bind ports.
+pub struct UnroutedModule;
+
+impl ModuleCallbacks for UnroutedModule {
+	const MODULE_ID: &'static str = "UnroutedModule";
+	type WeightHandler = ();
+
+	fn on_chan_open_try(
+		_order: Order,
+		_connection_hops: &[ConnectionId],
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty: &Counterparty,
+		version: &Version,
+		_counterparty_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		Ok(version.clone())
 	}
+}
+
+thread_local! {
+	/// Interchain query results delivered to [`MockQuerier`], timeouts are recorded as errors
+	pub static QUERY_RESULTS: RefCell<Vec<(PortId, u64, Result<Vec<QueryResponse>, String>)>> =
+		RefCell::new(vec![]);
+}
+
+/// Query callback that records the results it receives in [`QUERY_RESULTS`].
+pub struct MockQuerier;
 
-	fn has_route(module_id: &ibc::core::ics26_routing::context::ModuleId) -> bool {
-		matches!(module_id.as_ref(), pallet_ibc_ping::MODULE_ID,)
+impl QueryCallback for MockQuerier {
+	const MODULE_ID: &'static str = "MockQuerier";
+
+	fn on_query_result(
+		port_id: &PortId,
+		_channel_id: &ChannelId,
+		sequence: u64,
+		_requests: &[QueryRequest],
+		result: Result<&[QueryResponse], &str>,
+	) {
+		let result = result.map(|responses| responses.to_vec()).map_err(|e| e.to_string());
+		QUERY_RESULTS
+			.with(|results| results.borrow_mut().push((port_id.clone(), sequence, result)));
+	}
+
+	fn on_query_timeout(
+		port_id: &PortId,
+		_channel_id: &ChannelId,
+		sequence: u64,
+		_requests: &[QueryRequest],
+	) {
+		QUERY_RESULTS.with(|results| {
+			results
+				.borrow_mut()
+				.push((port_id.clone(), sequence, Err("timeout".to_string())))
+		});
 	}
+}
+
+pub type Router = PortRegistryRouter<(pallet_ibc_ping::IbcModule<Test>, ModuleAdaptor<MockModule>)>;
 
-	fn lookup_module_by_port(
-		port_id: &ibc::core::ics24_host::identifier::PortId,
-	) -> Option<ibc::core::ics26_routing::context::ModuleId> {
-		match port_id.as_str() {
-			pallet_ibc_ping::PORT_ID =>
-				ibc::core::ics26_routing::context::ModuleId::from_str(pallet_ibc_ping::MODULE_ID)
-					.ok(),
-			_ => None,
+parameter_types! {
+	pub const SiblingParaId: u32 = 2000;
+	pub const XcmTransferWeight: Weight = 1_000_000;
+}
+
+/// Delivers forwarded tokens straight to the beneficiary when sent to [`SiblingParaId`], every
+/// other destination is unroutable.
+pub struct MockXcmTransfer;
+
+impl ics20::xcm::XcmTransfer<Test> for MockXcmTransfer {
+	fn reserve_transfer(
+		from: AccountId,
+		asset_id: AssetId,
+		amount: Balance,
+		destination: ics20::xcm::XcmDestination<AccountId>,
+	) -> Result<(), DispatchError> {
+		if destination.para_id != SiblingParaId::get() {
+			return Err(DispatchError::Other("Unroutable"))
 		}
+		<<Test as Config>::Fungibles as Transfer<AccountId>>::transfer(
+			asset_id,
+			&from,
+			&destination.beneficiary,
+			amount,
+			false,
+		)?;
+		Ok(())
+	}
+
+	fn weight() -> Weight {
+		XcmTransferWeight::get()
 	}
 }
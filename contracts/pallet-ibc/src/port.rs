@@ -16,8 +16,9 @@ use super::*;
 
 use crate::routing::{Context, ModuleRouter};
 use ibc::{
-	applications::transfer::{
-		MODULE_ID_STR as TRANSFER_MODULE_ID, PORT_ID_STR as TRANSFER_PORT_ID,
+	applications::{
+		icq::{MODULE_ID_STR as ICQ_MODULE_ID, PORT_ID_STR as ICQ_PORT_ID},
+		transfer::{MODULE_ID_STR as TRANSFER_MODULE_ID, PORT_ID_STR as TRANSFER_PORT_ID},
 	},
 	core::{
 		ics05_port::{context::PortReader, error::Error as ICS05Error},
@@ -28,6 +29,14 @@ use ibc::{
 
 impl<T: Config + Sync + Send> PortReader for Context<T> {
 	fn lookup_module_by_port(&self, port_id: &PortId) -> Result<ModuleId, ICS05Error> {
+		// check if a module has bound this port through the registry
+		if let Some(module_id) = PortBindings::<T>::get(port_id.as_bytes()) {
+			return String::from_utf8(module_id)
+				.ok()
+				.and_then(|module_id| ModuleId::from_str(&module_id).ok())
+				.ok_or_else(|| ICS05Error::module_not_found(port_id.clone()))
+		}
+
 		// check if the user has defined any custom modules
 		if let Some(module_id) = T::Router::lookup_module_by_port(port_id) {
 			return Ok(module_id)
@@ -36,6 +45,8 @@ impl<T: Config + Sync + Send> PortReader for Context<T> {
 		match port_id.as_str() {
 			TRANSFER_PORT_ID => Ok(ModuleId::from_str(TRANSFER_MODULE_ID)
 				.map_err(|_| ICS05Error::module_not_found(port_id.clone()))?),
+			ICQ_PORT_ID => Ok(ModuleId::from_str(ICQ_MODULE_ID)
+				.map_err(|_| ICS05Error::module_not_found(port_id.clone()))?),
 			_ => Err(ICS05Error::module_not_found(port_id.clone())),
 		}
 	}
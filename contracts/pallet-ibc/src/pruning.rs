@@ -0,0 +1,225 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pruning of the consensus states clients no longer need, along with the update time and height
+//! stored for them.
+
+use super::*;
+use crate::{
+	ics23::{
+		client_states::ClientStates, connections::Connections, consensus_states::ConsensusStates,
+	},
+	light_clients::AnyClientState,
+};
+use core::time::Duration;
+use frame_support::{
+	traits::{Get, UnixTime},
+	weights::Weight,
+};
+use ibc::{core::ics03_connection::connection::ConnectionEnd, Height};
+use ibc_primitives::client_id_from_bytes;
+use sp_std::collections::vec_deque::VecDeque;
+use tendermint_proto::Protobuf;
+
+/// Key of `height` in [`ConsensusStateHeights`], its big-endian encoding sorts in height order.
+pub(crate) fn height_key(height: Height) -> [u8; 16] {
+	let mut key = [0u8; 16];
+	key[..8].copy_from_slice(&height.revision_number.to_be_bytes());
+	key[8..].copy_from_slice(&height.revision_height.to_be_bytes());
+	key
+}
+
+/// Height of a key in [`ConsensusStateHeights`].
+pub(crate) fn height_from_key(key: [u8; 16]) -> Height {
+	let mut revision_number = [0u8; 8];
+	let mut revision_height = [0u8; 8];
+	revision_number.copy_from_slice(&key[..8]);
+	revision_height.copy_from_slice(&key[8..]);
+	Height::new(u64::from_be_bytes(revision_number), u64::from_be_bytes(revision_height))
+}
+
+/// Weight used out of a budget, every read and write is charged before it happens.
+pub(crate) struct WeightMeter {
+	pub used: Weight,
+	pub budget: Weight,
+}
+
+impl WeightMeter {
+	pub fn new(budget: Weight) -> Self {
+		Self { used: 0, budget }
+	}
+
+	/// Charges `weight` unless it would exceed the budget, returns whether it was charged.
+	pub fn charge(&mut self, weight: Weight) -> bool {
+		if self.used.saturating_add(weight) > self.budget {
+			return false
+		}
+		self.used = self.used.saturating_add(weight);
+		true
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Prunes stale consensus states within `budget`, resuming from the client it stopped at in
+	/// the previous block.
+	pub(crate) fn prune_consensus_states(budget: Weight) -> Weight {
+		let db_weight = T::DbWeight::get();
+		let mut meter = WeightMeter::new(budget);
+		if !meter.charge(db_weight.reads(1)) {
+			return meter.used
+		}
+
+		let mut clients = match PruningCursor::<T>::get() {
+			Some(cursor) => ConsensusStateClients::<T>::iter_keys_from(cursor),
+			None => ConsensusStateClients::<T>::iter_keys(),
+		};
+		// reading the next client and moving the cursor past it
+		while meter.charge(db_weight.reads_writes(1, 1)) {
+			let client_id = match clients.next() {
+				Some(client_id) => client_id,
+				None => {
+					// every client has been visited, start over in the next block.
+					PruningCursor::<T>::kill();
+					break
+				},
+			};
+
+			if !Self::prune_client_consensus_states(&client_id, &mut meter) {
+				// the client might have stale consensus states left, so it's visited again first
+				// in the next block.
+				break
+			}
+			PruningCursor::<T>::put(ConsensusStateClients::<T>::hashed_key_for(&client_id));
+		}
+
+		meter.used
+	}
+
+	/// Removes the oldest consensus states of the client that are older than both its trusting
+	/// period and the delay period of its connections, a packet proof at those heights couldn't be
+	/// submitted anymore. The latest [`Config::ConsensusStatesToRetain`] consensus states are
+	/// always kept. Heights are walked in order from the oldest through [`ConsensusStateHeights`],
+	/// the walk stops at the first consensus state that isn't stale or once `meter` runs out of
+	/// budget. Returns whether the walk finished.
+	pub(crate) fn prune_client_consensus_states(
+		client_id_bytes: &[u8],
+		meter: &mut WeightMeter,
+	) -> bool {
+		let db_weight = T::DbWeight::get();
+		let base_weight = <T as Config>::WeightInfo::prune_consensus_states(0);
+		let per_state_weight =
+			<T as Config>::WeightInfo::prune_consensus_states(1).saturating_sub(base_weight);
+		// the consensus state, update time and height, and the height index entry
+		let prune_weight = per_state_weight.saturating_add(db_weight.writes(4));
+		// updating `ConsensusHeights` and depositing the event once something was pruned
+		let finalize_weight = base_weight.saturating_add(db_weight.reads_writes(1, 2));
+
+		let client_id = match client_id_from_bytes(client_id_bytes.to_vec()) {
+			Ok(client_id) => client_id,
+			Err(_) => return true,
+		};
+		if !meter.charge(db_weight.reads(2)) {
+			return false
+		}
+		let client_state = match ClientStates::<T>::get(&client_id)
+			.and_then(|client_state| AnyClientState::decode_vec(&client_state).ok())
+		{
+			Some(client_state) => client_state,
+			None => return true,
+		};
+		// without a trusting period we can't tell which consensus states are stale.
+		let trusting_period = match client_state.trusting_period() {
+			Some(trusting_period) => trusting_period,
+			None => return true,
+		};
+
+		let mut max_delay_period = Duration::default();
+		for connection_id in ConnectionClient::<T>::get(&client_id) {
+			if !meter.charge(db_weight.reads(1)) {
+				return false
+			}
+			if let Some(connection_end) = Connections::<T>::get(&connection_id)
+				.and_then(|connection_end| ConnectionEnd::decode_vec(&connection_end).ok())
+			{
+				max_delay_period = max_delay_period.max(connection_end.delay_period());
+			}
+		}
+		let retention_period = trusting_period.max(max_delay_period);
+		let now = T::TimeProvider::now();
+
+		// a height is only a candidate once `retained` newer ones have been read.
+		let retained = T::ConsensusStatesToRetain::get().max(1) as usize;
+		let mut heights = ConsensusStateHeights::<T>::iter_key_prefix(client_id_bytes);
+		let mut newer = VecDeque::with_capacity(retained + 1);
+		let mut pruned = Vec::new();
+		let finished = loop {
+			if !meter.charge(db_weight.reads(1)) {
+				break false
+			}
+			match heights.next() {
+				Some(key) => newer.push_back(key),
+				None => break true,
+			}
+			if newer.len() <= retained {
+				continue
+			}
+			let key = newer.pop_front().expect("more than `retained` heights were read; qed");
+			let height = height_from_key(key);
+			let encoded_height = height.encode_vec();
+			if !meter.charge(db_weight.reads(1)) {
+				break false
+			}
+			let stale = ClientUpdateTime::<T>::get(client_id_bytes, &encoded_height)
+				.map(|update_time| {
+					now.saturating_sub(Duration::from_nanos(update_time)) > retention_period
+				})
+				.unwrap_or(true);
+			// newer consensus states were updated later.
+			if !stale {
+				break true
+			}
+			let weight = if pruned.is_empty() {
+				prune_weight.saturating_add(finalize_weight)
+			} else {
+				prune_weight
+			};
+			if !meter.charge(weight) {
+				break false
+			}
+			ConsensusStates::<T>::remove(client_id.clone(), height);
+			ClientUpdateTime::<T>::remove(client_id_bytes, &encoded_height);
+			ClientUpdateHeight::<T>::remove(client_id_bytes, &encoded_height);
+			ConsensusStateHeights::<T>::remove(client_id_bytes, key);
+			pruned.push(height);
+		};
+		if pruned.is_empty() {
+			return finished
+		}
+
+		let mut consensus_heights = ConsensusHeights::<T>::get(client_id_bytes);
+		let mut consensus_heights_changed = false;
+		for height in pruned.iter() {
+			consensus_heights_changed |= consensus_heights.remove(height);
+		}
+		if consensus_heights_changed {
+			ConsensusHeights::<T>::insert(client_id_bytes.to_vec(), consensus_heights);
+		}
+
+		Self::deposit_event(Event::<T>::ConsensusStatesPruned {
+			client_id: client_id_bytes.to_vec(),
+			count: pruned.len() as u32,
+		});
+		finished
+	}
+}
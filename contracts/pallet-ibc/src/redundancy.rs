@@ -0,0 +1,168 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Filtering of packet messages that were already relayed, so competing relayers don't pay for
+//! each other's work.
+
+use super::*;
+use crate::routing::Context;
+use frame_support::traits::IsSubType;
+use ibc::core::{
+	ics04_channel::{channel::Order, context::ChannelReader, msgs::PacketMsg, packet::Packet},
+	ics26_routing::msgs::Ics26Envelope,
+};
+use sp_runtime::{
+	traits::{DispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+};
+
+/// Whether the message is a packet message that has already been relayed, executing it would
+/// be a no-op.
+pub fn is_redundant<T: Config + Send + Sync>(ctx: &Context<T>, message: &Any) -> bool
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	let type_url = match String::from_utf8(message.type_url.clone()) {
+		Ok(type_url) => type_url,
+		Err(_) => return false,
+	};
+	let msg = ibc_proto::google::protobuf::Any { type_url, value: message.value.clone() };
+	let msg = match Ics26Envelope::<Context<T>>::try_from(msg) {
+		Ok(Ics26Envelope::Ics4PacketMsg(msg)) => msg,
+		_ => return false,
+	};
+
+	match msg {
+		PacketMsg::RecvPacket(msg) => {
+			let packet = &msg.packet;
+			let port_channel_id = (packet.destination_port.clone(), packet.destination_channel);
+			match ctx.channel_end(&port_channel_id) {
+				Ok(channel_end) if channel_end.ordering == Order::Ordered => ctx
+					.get_next_sequence_recv(&port_channel_id)
+					.map(|next_sequence_recv| packet.sequence < next_sequence_recv)
+					.unwrap_or(false),
+				Ok(_) => ctx
+					.get_packet_receipt(&(port_channel_id.0, port_channel_id.1, packet.sequence))
+					.is_ok(),
+				Err(_) => false,
+			}
+		},
+		PacketMsg::AckPacket(msg) => !has_commitment(ctx, &msg.packet),
+		PacketMsg::ToPacket(msg) => !has_commitment(ctx, &msg.packet),
+		PacketMsg::ToClosePacket(msg) => !has_commitment(ctx, &msg.packet),
+	}
+}
+
+/// Whether the commitment of a packet sent from this chain is still stored, it's removed once the
+/// packet is acknowledged or timed out.
+fn has_commitment<T: Config + Send + Sync>(ctx: &Context<T>, packet: &Packet) -> bool
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	ctx.get_packet_commitment(&(packet.source_port.clone(), packet.source_channel, packet.sequence))
+		.is_ok()
+}
+
+/// Whether the messages contain packet messages and all of them were already relayed.
+pub fn all_packets_redundant<T: Config + Send + Sync>(ctx: &Context<T>, messages: &[Any]) -> bool
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	let mut packets = messages.iter().filter(|message| is_packet_message(message)).peekable();
+	packets.peek().is_some() && packets.all(|message| is_redundant(ctx, message))
+}
+
+/// Whether the message is one of the packet messages checked for redundancy.
+pub(crate) fn is_packet_message(message: &Any) -> bool {
+	use ibc::core::ics04_channel::msgs::{acknowledgement, recv_packet, timeout, timeout_on_close};
+	[
+		recv_packet::TYPE_URL,
+		acknowledgement::TYPE_URL,
+		timeout::TYPE_URL,
+		timeout_on_close::TYPE_URL,
+	]
+	.iter()
+	.any(|type_url| type_url.as_bytes() == message.type_url.as_slice())
+}
+
+/// Rejects `deliver` and `deliver_atomic` transactions whose packet messages were all relayed
+/// already, eg. by a competing relayer, before they're included and charged.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckRedundantRelay<T>(PhantomData<T>);
+
+impl<T> CheckRedundantRelay<T> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T> Default for CheckRedundantRelay<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T> Debug for CheckRedundantRelay<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(f, "CheckRedundantRelay")
+	}
+}
+
+impl<T> SignedExtension for CheckRedundantRelay<T>
+where
+	T: Config + Send + Sync,
+	<T as frame_system::Config>::Call: IsSubType<Call<T>>,
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	const IDENTIFIER: &'static str = "CheckRedundantRelay";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		_who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		if let Some(Call::deliver { messages } | Call::deliver_atomic { messages }) =
+			call.is_sub_type()
+		{
+			if all_packets_redundant(&Context::<T>::new(), messages) {
+				return Err(InvalidTransaction::Stale.into())
+			}
+		}
+		Ok(ValidTransaction::default())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| ())
+	}
+}
@@ -15,7 +15,9 @@
 use super::*;
 use core::fmt::Debug;
 use ibc::{
-	applications::transfer::MODULE_ID_STR as IBC_TRANSFER_MODULE_ID,
+	applications::{
+		icq::MODULE_ID_STR as IBC_ICQ_MODULE_ID, transfer::MODULE_ID_STR as IBC_TRANSFER_MODULE_ID,
+	},
 	core::{
 		ics24_host::identifier::PortId,
 		ics26_routing::context::{
@@ -23,6 +25,7 @@ use ibc::{
 		},
 	},
 };
+use ibc_primitives::{CallbackWeight, ModuleAdaptor, ModuleCallbacks};
 use scale_info::prelude::string::ToString;
 use sp_core::crypto::AccountId32;
 
@@ -47,12 +50,17 @@ impl<T: Config + Send + Sync> Context<T> {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct IbcRouter<T: Config> {
 	ibc_transfer: ics20::IbcModule<T>,
+	ibc_icq: icq::IbcModule<T>,
 	sub_router: T::Router,
 }
 
 impl<T: Config> Default for IbcRouter<T> {
 	fn default() -> Self {
-		Self { ibc_transfer: ics20::IbcModule::<T>::default(), sub_router: Default::default() }
+		Self {
+			ibc_transfer: ics20::IbcModule::<T>::default(),
+			ibc_icq: icq::IbcModule::<T>::default(),
+			sub_router: Default::default(),
+		}
 	}
 }
 
@@ -64,6 +72,92 @@ pub trait ModuleRouter: Default + Clone + Eq + PartialEq + Debug {
 	fn has_route(module_id: &ModuleId) -> bool;
 	/// Should return the module_id associated with a given port_id
 	fn lookup_module_by_port(port_id: &PortId) -> Option<ModuleId>;
+	/// Returns the callback weight handler for the `Module` registered against the specified
+	/// `ModuleId`
+	fn callback_weight(_module_id: &ModuleId) -> Option<Box<dyn CallbackWeight>> {
+		None
+	}
+}
+
+/// A set of modules implementing [`ModuleCallbacks`], implemented for tuples of
+/// [`ModuleAdaptor`]s.
+pub trait CallbackModules: Default + Clone + Eq + PartialEq + Debug {
+	/// Returns a mutable reference to the module registered against the specified `ModuleId`
+	fn get_route_mut(&mut self, module_id: &ModuleId) -> Option<&mut dyn Module>;
+	/// Returns true if a module in this set is registered against the specified `ModuleId`
+	fn has_route(module_id: &ModuleId) -> bool;
+	/// Returns the callback weight handler of the module registered against `ModuleId`
+	fn callback_weight(module_id: &ModuleId) -> Option<Box<dyn CallbackWeight>>;
+}
+
+impl<M: ModuleCallbacks> CallbackModules for ModuleAdaptor<M> {
+	fn get_route_mut(&mut self, module_id: &ModuleId) -> Option<&mut dyn Module> {
+		(module_id.as_ref() == M::MODULE_ID).then(|| self as &mut dyn Module)
+	}
+
+	fn has_route(module_id: &ModuleId) -> bool {
+		module_id.as_ref() == M::MODULE_ID
+	}
+
+	fn callback_weight(module_id: &ModuleId) -> Option<Box<dyn CallbackWeight>> {
+		(module_id.as_ref() == M::MODULE_ID)
+			.then(|| Box::new(M::WeightHandler::default()) as Box<dyn CallbackWeight>)
+	}
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(1, 16)]
+impl CallbackModules for Tuple {
+	fn get_route_mut(&mut self, module_id: &ModuleId) -> Option<&mut dyn Module> {
+		for_tuples!( #(
+			if Tuple::has_route(module_id) {
+				return self.Tuple.get_route_mut(module_id)
+			}
+		)* );
+		None
+	}
+
+	fn has_route(module_id: &ModuleId) -> bool {
+		for_tuples!( #(
+			if Tuple::has_route(module_id) {
+				return true
+			}
+		)* );
+		false
+	}
+
+	fn callback_weight(module_id: &ModuleId) -> Option<Box<dyn CallbackWeight>> {
+		for_tuples!( #(
+			if let Some(weight) = Tuple::callback_weight(module_id) {
+				return Some(weight)
+			}
+		)* );
+		None
+	}
+}
+
+/// Module router for pallets using the port registry, ports are bound to modules at genesis or at
+/// runtime through [`IbcHandler::bind_port`](ibc_primitives::IbcHandler::bind_port), so adding a
+/// new ibc application only requires adding its [`ModuleAdaptor`] to `M`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PortRegistryRouter<M>(M);
+
+impl<M: CallbackModules> ModuleRouter for PortRegistryRouter<M> {
+	fn get_route_mut(&mut self, module_id: &ModuleId) -> Option<&mut dyn Module> {
+		self.0.get_route_mut(module_id)
+	}
+
+	fn has_route(module_id: &ModuleId) -> bool {
+		M::has_route(module_id)
+	}
+
+	fn lookup_module_by_port(_port_id: &PortId) -> Option<ModuleId> {
+		// ports bound in the registry are resolved by the `PortReader` before reaching the router
+		None
+	}
+
+	fn callback_weight(module_id: &ModuleId) -> Option<Box<dyn CallbackWeight>> {
+		M::callback_weight(module_id)
+	}
 }
 
 impl<T: Config + Send + Sync> Router for IbcRouter<T>
@@ -79,6 +173,7 @@ where
 
 		match module_id.as_ref() {
 			IBC_TRANSFER_MODULE_ID => Some(&mut self.ibc_transfer),
+			IBC_ICQ_MODULE_ID => Some(&mut self.ibc_icq),
 			&_ => None,
 		}
 	}
@@ -89,7 +184,7 @@ where
 			return true
 		}
 
-		matches!(module_id.to_string().as_str(), IBC_TRANSFER_MODULE_ID,)
+		matches!(module_id.to_string().as_str(), IBC_TRANSFER_MODULE_ID | IBC_ICQ_MODULE_ID)
 	}
 }
 
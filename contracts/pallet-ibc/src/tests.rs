@@ -13,25 +13,49 @@
 // limitations under the License.
 
 use crate::{
-	impls::{OFFCHAIN_RECV_PACKET_SEQS, OFFCHAIN_SEND_PACKET_SEQS},
-	light_clients::{AnyClientState, AnyConsensusState},
+	client::HostConsensusProof,
+	errors::failure_reason,
+	icq::{self, STORAGE_QUERY_PATH},
+	ics20,
+	ics23::{client_states::ClientStates, consensus_states::ConsensusStates},
+	impls::{
+		MAX_SPAM_DEPOSIT_EXPIRIES_PER_BLOCK, OFFCHAIN_RECV_PACKET_SEQS, OFFCHAIN_SEND_PACKET_SEQS,
+	},
+	light_clients::{AnyClientState, AnyConsensusState, HostFunctionsManager},
+	migrations::{v1::MigrateToV1, v2, Migrations},
 	mock::*,
+	redundancy,
 	routing::Context,
-	Any, Config, ConsensusHeights, DenomToAssetId, MultiAddress, Pallet, PalletParams, Timeout,
-	TransferParams, MODULE_ID,
+	weight::WeightInfo,
+	Any, ChannelsConnection, CheckRedundantRelay, ClientCounter, ClientUpdateTime, Config,
+	ConnectionClient, ConsensusHeights, ConsensusStateClients, ConsensusStateHeights, DenomToAssetId,
+	IdentifierMigrationCursor, MessageOutcome, MessageResult, MultiAddress, Pallet, PalletParams,
+	PruningIndexCursor, SpamDepositExpiries, Timeout, TransferParams, MODULE_ID,
 };
+use codec::Encode;
 use core::time::Duration;
 use frame_support::{
 	assert_ok,
+	dispatch::GetDispatchInfo,
 	traits::{
 		fungibles::{Inspect, Mutate},
-		Len,
+		Currency, Hooks, Len, OnRuntimeUpgrade, ReservableCurrency, StorageVersion,
 	},
+	weights::RuntimeDbWeight,
 };
 use ibc::{
-	applications::transfer::{packet::PacketData, Coin, PrefixedDenom, VERSION},
+	applications::{
+		icq::{
+			acknowledgement::Acknowledgement as IcqAcknowledgement,
+			error::Error as IcqError,
+			packet::{InterchainQueryPacketData, QueryRequest, QueryResponse},
+			MODULE_ID_STR as ICQ_MODULE_ID, PORT_ID_STR as ICQ_PORT_ID,
+		},
+		transfer::{packet::PacketData, Coin, PrefixedDenom, VERSION},
+	},
 	core::{
 		ics02_client::{
+			client_def::ClientDef,
 			client_state::ClientState,
 			context::{ClientKeeper, ClientReader},
 			height::Height,
@@ -40,30 +64,50 @@ use ibc::{
 		ics03_connection::{
 			connection::{ConnectionEnd, Counterparty, State as ConnState},
 			context::{ConnectionKeeper, ConnectionReader},
-			msgs::conn_open_init,
+			handler::verify::ConsensusProofwithHostConsensusStateProof,
+			msgs::{conn_open_ack, conn_open_init},
 			version::Version as ConnVersion,
 		},
 		ics04_channel::{
 			channel::{ChannelEnd, Counterparty as ChanCounterParty, Order, State},
-			context::ChannelKeeper,
+			context::{ChannelKeeper, ChannelReader},
 			msgs::recv_packet::MsgRecvPacket,
 			packet::Packet,
 			Version as ChanVersion,
 		},
+		ics05_port::context::PortReader,
 		ics23_commitment::commitment::CommitmentPrefix,
 		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		ics26_routing::context::{Module, ModuleId},
 	},
+	handler::HandlerOutputBuilder,
 	mock::{
 		client_state::{MockClientState, MockConsensusState},
 		header::{MockClientMessage, MockHeader},
 	},
-	proofs::Proofs,
+	proofs::{ConsensusProof, Proofs},
 	signer::Signer,
+	timestamp::Timestamp,
 	tx_msg::Msg,
 };
-use ibc_primitives::{get_channel_escrow_address, IbcHandler};
+use ibc_primitives::{
+	get_channel_escrow_address, AllowedClient, CallbackWeight, IbcHandler, ModuleCallbacks,
+	PacketRecord, PageRequest, PortCapability,
+};
+use ics08_wasm::{
+	client_def::WasmClient, client_message::ClientMessage as WasmClientMessage,
+	client_state::ClientState as WasmClientState,
+	consensus_state::ConsensusState as WasmConsensusState,
+};
+use ics10_grandpa::client_state::ClientState as GrandpaClientState;
+use light_client_common::RelayChain;
 use sp_core::Pair;
-use sp_runtime::{offchain::storage::StorageValueRef, traits::IdentifyAccount, AccountId32};
+use sp_runtime::{
+	offchain::storage::StorageValueRef,
+	traits::{BlakeTwo256, Header as _, IdentifyAccount, SignedExtension},
+	transaction_validity::InvalidTransaction,
+	AccountId32,
+};
 use std::{
 	collections::{BTreeMap, BTreeSet},
 	str::FromStr,
@@ -214,6 +258,377 @@ fn initialize_connection_with_low_delay() {
 	})
 }
 
+#[test]
+fn should_report_the_result_of_every_delivered_message() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let mock_client_state =
+			MockClientState::new(MockClientMessage::from(MockHeader::default()));
+		let mock_cs_state = MockConsensusState::new(MockHeader::default());
+		let msg = MsgCreateAnyClient::<Context<Test>>::new(
+			AnyClientState::Mock(mock_client_state),
+			AnyConsensusState::Mock(mock_cs_state),
+			Signer::from_str(MODULE_ID).unwrap(),
+		)
+		.unwrap()
+		.encode_vec();
+		let create_client = Any { type_url: TYPE_URL.as_bytes().to_vec(), value: msg };
+		let unknown = Any { type_url: b"/ibc.unknown.MsgUnknown".to_vec(), value: vec![] };
+		let messages = vec![unknown.clone(), create_client.clone()];
+
+		let delivered = || {
+			System::events()
+				.into_iter()
+				.filter_map(|record| match record.event {
+					Event::Ibc(crate::Event::MessagesDelivered { results }) => Some(results),
+					_ => None,
+				})
+				.last()
+				.unwrap()
+		};
+
+		// the atomic batch is rolled back but still tells which message failed and why
+		assert_ok!(Ibc::deliver_atomic(
+			Origin::signed(AccountId32::new([0; 32])),
+			messages.clone()
+		));
+		assert_eq!(ClientCounter::<Test>::get(), 0);
+		assert_eq!(
+			delivered(),
+			vec![
+				MessageResult {
+					index: 0,
+					type_url: unknown.type_url.clone(),
+					outcome: MessageOutcome::Failure,
+					error_code: Some(500),
+				},
+				MessageResult {
+					index: 1,
+					type_url: create_client.type_url.clone(),
+					outcome: MessageOutcome::Reverted,
+					error_code: None,
+				},
+			]
+		);
+
+		assert_ok!(Ibc::deliver(Origin::signed(AccountId32::new([0; 32])), messages));
+		let results = delivered();
+		assert_eq!(
+			results,
+			vec![
+				MessageResult {
+					index: 0,
+					type_url: unknown.type_url,
+					outcome: MessageOutcome::Failure,
+					error_code: Some(500),
+				},
+				MessageResult {
+					index: 1,
+					type_url: create_client.type_url,
+					outcome: MessageOutcome::Success,
+					error_code: None,
+				},
+			]
+		);
+		assert!(!results[0].is_transient_failure());
+		assert_eq!(ClientCounter::<Test>::get(), 1);
+	})
+}
+
+#[test]
+fn should_report_failures_on_missing_dependencies_as_transient() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let mock_client_state =
+			MockClientState::new(MockClientMessage::from(MockHeader::new(Height::new(0, 1))));
+		let mock_cs_state = MockConsensusState::new(MockHeader::new(Height::new(0, 1)));
+		let client_id = ClientId::new(&mock_client_state.client_type(), 0).unwrap();
+		let msg = MsgCreateAnyClient::<Context<Test>>::new(
+			AnyClientState::Mock(mock_client_state.clone()),
+			AnyConsensusState::Mock(mock_cs_state),
+			Signer::from_str(MODULE_ID).unwrap(),
+		)
+		.unwrap()
+		.encode_vec();
+		let create_client = Any { type_url: TYPE_URL.as_bytes().to_vec(), value: msg };
+		let commitment_prefix: CommitmentPrefix =
+			<Test as Config>::PALLET_PREFIX.to_vec().try_into().unwrap();
+		let value = conn_open_init::MsgConnectionOpenInit {
+			client_id,
+			counterparty: Counterparty::new(
+				ClientId::new(&mock_client_state.client_type(), 1).unwrap(),
+				Some(ConnectionId::new(1)),
+				commitment_prefix,
+			),
+			version: Some(ConnVersion::default()),
+			delay_period: Duration::from_nanos(1000),
+			signer: Signer::from_str(MODULE_ID).unwrap(),
+		};
+		let init_connection =
+			Any { type_url: conn_open_init::TYPE_URL.as_bytes().to_vec(), value: value.encode_vec() };
+
+		// the connection's client is created after it in the batch
+		assert_ok!(Ibc::deliver(
+			Origin::signed(AccountId32::new([0; 32])),
+			vec![init_connection.clone(), create_client]
+		));
+		let results = System::events()
+			.into_iter()
+			.find_map(|record| match record.event {
+				Event::Ibc(crate::Event::MessagesDelivered { results }) => Some(results),
+				_ => None,
+			})
+			.unwrap();
+		assert_eq!(results[0].error_code, Some(200 + failure_reason::CLIENT_NOT_FOUND));
+		assert!(results[0].is_transient_failure());
+		assert_eq!(results[1].outcome, MessageOutcome::Success);
+
+		assert_ok!(Ibc::deliver(Origin::signed(AccountId32::new([0; 32])), vec![init_connection]));
+		let ctx = Context::<Test>::default();
+		assert!(ConnectionReader::connection_end(&ctx, &ConnectionId::new(0)).is_ok());
+	})
+}
+
+#[test]
+fn should_only_create_allowed_clients() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let mock_client_state =
+			MockClientState::new(MockClientMessage::from(MockHeader::default()));
+		let mock_cs_state = MockConsensusState::new(MockHeader::default());
+		let chain_id = mock_client_state.chain_id().to_string();
+		let msg = MsgCreateAnyClient::<Context<Test>>::new(
+			AnyClientState::Mock(mock_client_state),
+			AnyConsensusState::Mock(mock_cs_state),
+			Signer::from_str(MODULE_ID).unwrap(),
+		)
+		.unwrap()
+		.encode_vec();
+		let create_client = Any { type_url: TYPE_URL.as_bytes().to_vec(), value: msg };
+
+		assert!(Ibc::set_allowed_clients(Origin::signed(AccountId32::new([0; 32])), None).is_err());
+		let grandpa_only = AllowedClient { client_type: b"10-grandpa".to_vec(), chain_ids: vec![] };
+		assert_ok!(Ibc::set_allowed_clients(Origin::root(), Some(vec![grandpa_only])));
+		assert_ok!(Ibc::deliver_atomic(
+			Origin::signed(AccountId32::new([0; 32])),
+			vec![create_client.clone()]
+		));
+		assert_eq!(ClientCounter::<Test>::get(), 0);
+
+		let other_chain = AllowedClient {
+			client_type: b"9999-mock".to_vec(),
+			chain_ids: vec![b"other".to_vec()],
+		};
+		assert_ok!(Ibc::set_allowed_clients(Origin::root(), Some(vec![other_chain])));
+		assert_ok!(Ibc::deliver_atomic(
+			Origin::signed(AccountId32::new([0; 32])),
+			vec![create_client.clone()]
+		));
+		assert_eq!(ClientCounter::<Test>::get(), 0);
+
+		let mock = AllowedClient {
+			client_type: b"9999-mock".to_vec(),
+			chain_ids: vec![chain_id.as_bytes().to_vec()],
+		};
+		assert_ok!(Ibc::set_allowed_clients(Origin::root(), Some(vec![mock.clone()])));
+		assert_eq!(Ibc::allowed_clients(), Some(vec![mock]));
+		assert_ok!(Ibc::deliver_atomic(
+			Origin::signed(AccountId32::new([0; 32])),
+			vec![create_client.clone()]
+		));
+		assert_eq!(ClientCounter::<Test>::get(), 1);
+
+		assert_ok!(Ibc::set_allowed_clients(Origin::root(), None));
+		assert_ok!(Ibc::deliver_atomic(
+			Origin::signed(AccountId32::new([0; 32])),
+			vec![create_client]
+		));
+		assert_eq!(ClientCounter::<Test>::get(), 2);
+	})
+}
+
+// Create a mock client and initialize a connection on it, paid for by `depositor`
+fn create_client_and_init_connection(depositor: &AccountId32) -> (ClientId, ConnectionId) {
+	let mock_client_state =
+		MockClientState::new(MockClientMessage::from(MockHeader::new(Height::new(0, 1))));
+	let mock_cs_state = MockConsensusState::new(MockHeader::new(Height::new(0, 1)));
+	let client_id = ClientId::new(&mock_client_state.client_type(), 0).unwrap();
+	let counterparty_client_id = ClientId::new(&mock_client_state.client_type(), 1).unwrap();
+	let msg = MsgCreateAnyClient::<Context<Test>>::new(
+		AnyClientState::Mock(mock_client_state),
+		AnyConsensusState::Mock(mock_cs_state),
+		Signer::from_str(MODULE_ID).unwrap(),
+	)
+	.unwrap()
+	.encode_vec();
+	let msg = Any { type_url: TYPE_URL.to_string().as_bytes().to_vec(), value: msg };
+	assert_ok!(Ibc::deliver(Origin::signed(depositor.clone()), vec![msg]));
+
+	let commitment_prefix: CommitmentPrefix =
+		<Test as Config>::PALLET_PREFIX.to_vec().try_into().unwrap();
+	let value = conn_open_init::MsgConnectionOpenInit {
+		client_id: client_id.clone(),
+		counterparty: Counterparty::new(
+			counterparty_client_id,
+			Some(ConnectionId::new(1)),
+			commitment_prefix,
+		),
+		version: Some(ConnVersion::default()),
+		delay_period: Duration::from_nanos(1000),
+		signer: Signer::from_str(MODULE_ID).unwrap(),
+	};
+	let msg =
+		Any { type_url: conn_open_init::TYPE_URL.as_bytes().to_vec(), value: value.encode_vec() };
+	assert_ok!(Ibc::deliver(Origin::signed(depositor.clone()), vec![msg]));
+
+	(client_id, ConnectionId::new(0))
+}
+
+// Proof of the host consensus state at `height`, from the header of the block stored at `height`
+// and its timestamp inherent, followed by `consensus_proof` as the counterparty would send them
+fn host_consensus_state_proof(height: u32, consensus_proof: Vec<u8>) -> Vec<u8> {
+	use sp_trie::{LayoutV0, MemoryDB, TrieDBMut, TrieMut};
+
+	// the encoded timestamp inherent, the first extrinsic of the block
+	let extrinsic = (4u8, 3u8, 0u8, codec::Compact(1_000u64)).encode().encode();
+	let key = codec::Compact(0u32).encode();
+	let mut db = MemoryDB::<BlakeTwo256>::default();
+	let mut extrinsics_root = Default::default();
+	{
+		let mut trie = TrieDBMut::<LayoutV0<BlakeTwo256>>::new(&mut db, &mut extrinsics_root);
+		trie.insert(&key, &extrinsic).unwrap();
+	}
+	let extrinsic_proof = sp_trie::generate_trie_proof::<LayoutV0<BlakeTwo256>, _, _, _>(
+		&db,
+		extrinsics_root,
+		&[key],
+	)
+	.unwrap();
+	let header = sp_runtime::generic::Header::<u32, BlakeTwo256>::new(
+		height,
+		extrinsics_root,
+		Default::default(),
+		Default::default(),
+		Default::default(),
+	);
+	frame_system::BlockHash::<Test>::insert(height, header.hash());
+
+	let host_consensus_state_proof =
+		HostConsensusProof { header: header.encode(), extrinsic, extrinsic_proof }.encode();
+	ConsensusProofwithHostConsensusStateProof { host_consensus_state_proof, consensus_proof }
+		.encode()
+}
+
+#[test]
+fn should_release_spam_deposits_once_connection_opens() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(2);
+		SpamProtectionDeposit::set(1_000);
+		let depositor = AccountId32::new([1; 32]);
+		let _ = Balances::deposit_creating(&depositor, 10_000);
+
+		let (client_id, connection_id) = create_client_and_init_connection(&depositor);
+		assert_eq!(Balances::reserved_balance(&depositor), 2_000);
+		let status = Pallet::<Test>::spam_deposit(connection_id.as_bytes().to_vec()).unwrap();
+		assert_eq!(status.depositor, depositor.encode());
+		assert_eq!(status.amount, 1_000);
+		assert_eq!(status.expires_at, status.reserved_at + SpamDepositExpiry::get());
+
+		// the counterparty's client of this chain, as checked by `validate_self_client`
+		let self_client_state = GrandpaClientState::<HostFunctionsManager> {
+			relay_chain: RelayChainId::get(),
+			para_id: ParachainId::get().into(),
+			latest_para_height: 1,
+			..Default::default()
+		};
+		let para_id: u32 = ParachainId::get().into();
+		let value = conn_open_ack::MsgConnectionOpenAck::<Context<Test>> {
+			connection_id: connection_id.clone(),
+			counterparty_connection_id: ConnectionId::new(1),
+			client_state: Some(AnyClientState::Grandpa(self_client_state)),
+			proofs: Proofs::new(
+				vec![1u8; 32].try_into().unwrap(),
+				Some(vec![1u8; 32].try_into().unwrap()),
+				Some(
+					ConsensusProof::new(
+						host_consensus_state_proof(1, vec![1u8; 32]).try_into().unwrap(),
+						Height::new(para_id.into(), 1),
+					)
+					.unwrap(),
+				),
+				None,
+				Height::new(0, 1),
+			)
+			.unwrap(),
+			version: ConnVersion::default(),
+			signer: Signer::from_str(MODULE_ID).unwrap(),
+		};
+		let msg =
+			Any { type_url: conn_open_ack::TYPE_URL.as_bytes().to_vec(), value: value.encode_vec() };
+		assert_ok!(Ibc::deliver(Origin::signed(depositor.clone()), vec![msg]));
+		assert_eq!(
+			ConnectionReader::connection_end(&Context::<Test>::default(), &connection_id)
+				.unwrap()
+				.state(),
+			&ConnState::Open
+		);
+
+		assert_eq!(Balances::reserved_balance(&depositor), 0);
+		assert_eq!(Balances::free_balance(&depositor), 10_000);
+		assert!(Pallet::<Test>::spam_deposit(client_id.as_bytes().to_vec()).is_none());
+		assert!(Pallet::<Test>::spam_deposit(connection_id.as_bytes().to_vec()).is_none());
+	})
+}
+
+#[test]
+fn should_slash_spam_deposits_of_expired_handshakes() {
+	new_test_ext().execute_with(|| {
+		SpamProtectionDeposit::set(1_000);
+		let depositor = AccountId32::new([1; 32]);
+		let _ = Balances::deposit_creating(&depositor, 10_000);
+
+		let (client_id, connection_id) = create_client_and_init_connection(&depositor);
+		let expires_at =
+			Pallet::<Test>::spam_deposit(client_id.as_bytes().to_vec()).unwrap().expires_at;
+
+		Ibc::on_initialize(expires_at - 1);
+		assert_eq!(Balances::reserved_balance(&depositor), 2_000);
+
+		Ibc::on_initialize(expires_at);
+		assert_eq!(Balances::reserved_balance(&depositor), 0);
+		assert_eq!(Balances::free_balance(&depositor), 8_000);
+		assert!(Pallet::<Test>::spam_deposit(client_id.as_bytes().to_vec()).is_none());
+		assert!(Pallet::<Test>::spam_deposit(connection_id.as_bytes().to_vec()).is_none());
+	})
+}
+
+#[test]
+fn should_carry_spam_deposit_expiries_over_full_blocks() {
+	new_test_ext().execute_with(|| {
+		SpamProtectionDeposit::set(1_000);
+		let depositor = AccountId32::new([1; 32]);
+		let _ = Balances::deposit_creating(&depositor, 10_000);
+		let expires_at = System::block_number() + SpamDepositExpiry::get();
+		SpamDepositExpiries::<Test>::insert(
+			expires_at,
+			vec![vec![0u8]; MAX_SPAM_DEPOSIT_EXPIRIES_PER_BLOCK as usize],
+		);
+
+		let (client_id, connection_id) = create_client_and_init_connection(&depositor);
+		assert_eq!(
+			SpamDepositExpiries::<Test>::get(expires_at + 1),
+			vec![client_id.as_bytes().to_vec(), connection_id.as_bytes().to_vec()]
+		);
+
+		Ibc::on_initialize(expires_at);
+		assert_eq!(Balances::reserved_balance(&depositor), 2_000);
+
+		Ibc::on_initialize(expires_at + 1);
+		assert_eq!(Balances::reserved_balance(&depositor), 0);
+		assert!(Pallet::<Test>::spam_deposit(client_id.as_bytes().to_vec()).is_none());
+	})
+}
+
 const MILLIS: u128 = 1000000;
 #[test]
 fn send_transfer() {
@@ -245,6 +660,7 @@ fn send_transfer() {
 				to: MultiAddress::Raw(ss58_address.as_bytes().to_vec()),
 				source_channel: 0,
 				timeout,
+				memo: b"memo".to_vec(),
 			},
 			asset_id,
 			balance,
@@ -266,7 +682,8 @@ fn send_transfer() {
 		.get(0)
 		.unwrap()
 		.clone();
-		assert!(!packet_info.data.is_empty())
+		let packet_data: PacketData = serde_json::from_slice(&packet_info.data).unwrap();
+		assert_eq!(packet_data.memo, "memo")
 	})
 }
 
@@ -321,6 +738,7 @@ fn on_deliver_ics20_recv_packet() {
 			token: coin,
 			sender: Signer::from_str("alice").unwrap(),
 			receiver: Signer::from_str(&ss58_address).unwrap(),
+			memo: Default::default(),
 		};
 
 		let data = serde_json::to_vec(&packet_data).unwrap();
@@ -364,6 +782,179 @@ fn on_deliver_ics20_recv_packet() {
 	})
 }
 
+/// Delivers an ics20 packet returning tokens to this chain from `channel-1`, tokens are taken out
+/// of the `channel-0` escrow account which holds `escrowed` of asset `2`. Returns the delivered
+/// message.
+fn deliver_ics20_packet_with_memo(
+	receiver: &str,
+	amount: u128,
+	escrowed: u128,
+	memo: String,
+) -> Any {
+	let asset_id =
+		<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+			&"PICA".to_string(),
+		)
+		.unwrap();
+	setup_client_and_consensus_state(PortId::transfer());
+
+	let channel_escrow_address =
+		get_channel_escrow_address(&PortId::transfer(), ChannelId::new(0)).unwrap();
+	let channel_escrow_address =
+		<Test as Config>::AccountIdConversion::try_from(channel_escrow_address)
+			.map_err(|_| ())
+			.unwrap()
+			.into_account();
+	<<Test as Config>::Fungibles as Mutate<<Test as frame_system::Config>::AccountId>>::mint_into(
+		asset_id,
+		&channel_escrow_address,
+		escrowed,
+	)
+	.unwrap();
+
+	Ibc::set_params(Origin::root(), PalletParams { send_enabled: true, receive_enabled: true })
+		.unwrap();
+
+	let packet_data = PacketData {
+		token: Coin {
+			denom: PrefixedDenom::from_str("transfer/channel-1/PICA").unwrap(),
+			amount: ibc::applications::transfer::Amount::from_str(&amount.to_string()).unwrap(),
+		},
+		sender: Signer::from_str("alice").unwrap(),
+		receiver: Signer::from_str(receiver).unwrap(),
+		memo,
+	};
+	let packet = Packet {
+		sequence: 1u64.into(),
+		source_port: PortId::transfer(),
+		source_channel: ChannelId::new(1),
+		destination_port: PortId::transfer(),
+		destination_channel: ChannelId::new(0),
+		data: serde_json::to_vec(&packet_data).unwrap(),
+		timeout_height: Height::new(2000, 5),
+		timeout_timestamp: ibc::timestamp::Timestamp::from_nanoseconds(
+			1690894363u64.saturating_mul(1000000000),
+		)
+		.unwrap(),
+	};
+	let msg = MsgRecvPacket {
+		packet,
+		proofs: Proofs::new(vec![0u8; 32].try_into().unwrap(), None, None, None, Height::new(0, 1))
+			.unwrap(),
+		signer: Signer::from_str(MODULE_ID).unwrap(),
+	};
+	let msg = Any { type_url: msg.type_url().as_bytes().to_vec(), value: msg.encode_vec() };
+
+	Ibc::deliver(Origin::signed(AccountId32::new([0; 32])), vec![msg.clone()]).unwrap();
+	msg
+}
+
+#[test]
+fn should_reject_and_only_charge_checks_for_redundant_packets() {
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let receiver = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let receiver_ss58 = String::from_utf8(
+			ibc_primitives::runtime_interface::account_id_to_ss58(receiver.public().0, 49).unwrap(),
+		)
+		.unwrap();
+		let msg = deliver_ics20_packet_with_memo(
+			&receiver_ss58,
+			1000 * MILLIS,
+			100000 * MILLIS,
+			Default::default(),
+		);
+		assert!(redundancy::is_redundant(&Context::<Test>::new(), &msg));
+
+		// another relayer submitting the same packet is rejected before inclusion
+		let call = Call::Ibc(crate::Call::deliver { messages: vec![msg.clone()] });
+		let info = call.get_dispatch_info();
+		assert_eq!(
+			CheckRedundantRelay::<Test>::new().validate(
+				&AccountId32::new([1; 32]),
+				&call,
+				&info,
+				0
+			),
+			Err(InvalidTransaction::Stale.into())
+		);
+
+		// and if it's included anyway it isn't executed, it's only charged for the check
+		let check_weight = crate::weight::redundancy_check::<Test>(&msg);
+		let post_info =
+			Ibc::deliver(Origin::signed(AccountId32::new([1; 32])), vec![msg.clone()]).unwrap();
+		assert_eq!(post_info.actual_weight, Some(check_weight));
+
+		// so padding a batch with stale packets isn't free
+		let post_info =
+			Ibc::deliver(Origin::signed(AccountId32::new([1; 32])), vec![msg.clone(); 3]).unwrap();
+		assert_eq!(post_info.actual_weight, Some(check_weight * 3));
+	})
+}
+
+#[test]
+fn should_forward_received_tokens_to_sibling_parachain() {
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let receiver = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let receiver_ss58 = String::from_utf8(
+			ibc_primitives::runtime_interface::account_id_to_ss58(receiver.public().0, 49).unwrap(),
+		)
+		.unwrap();
+		let beneficiary = AccountId32::new([7; 32]);
+		let memo = format!(
+			r#"{{"xcm":{{"para_id":{},"beneficiary":"0x{}"}}}}"#,
+			SiblingParaId::get(),
+			hex::encode(&beneficiary)
+		);
+		let amount = 1000 * MILLIS;
+
+		let msg = deliver_ics20_packet_with_memo(&receiver_ss58, amount, 100000 * MILLIS, memo);
+		let packet = MsgRecvPacket::decode_vec(&msg.value).unwrap().packet;
+		assert_eq!(
+			ics20::WeightHandler::<Test>::default().on_recv_packet(&packet),
+			<() as WeightInfo>::on_recv_packet() + XcmTransferWeight::get()
+		);
+
+		let receiver = AccountId32::new(receiver.public().0);
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(2, &receiver), 0);
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(2, &beneficiary), amount);
+	})
+}
+
+#[test]
+fn should_refund_received_tokens_when_xcm_forwarding_fails() {
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let receiver = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let receiver_ss58 = String::from_utf8(
+			ibc_primitives::runtime_interface::account_id_to_ss58(receiver.public().0, 49).unwrap(),
+		)
+		.unwrap();
+		let beneficiary = AccountId32::new([7; 32]);
+		// No route to this parachain
+		let memo = format!(
+			r#"{{"xcm":{{"para_id":3000,"beneficiary":"0x{}"}}}}"#,
+			hex::encode(&beneficiary)
+		);
+		let escrowed = 100000 * MILLIS;
+
+		deliver_ics20_packet_with_memo(&receiver_ss58, 1000 * MILLIS, escrowed, memo);
+
+		// Tokens stay in escrow, the error acknowledgement refunds the sender on the source chain
+		let escrow_address = <Test as Config>::AccountIdConversion::try_from(
+			get_channel_escrow_address(&PortId::transfer(), ChannelId::new(0)).unwrap(),
+		)
+		.map_err(|_| ())
+		.unwrap()
+		.into_account();
+		let receiver = AccountId32::new(receiver.public().0);
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(2, &escrow_address), escrowed);
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(2, &receiver), 0);
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(2, &beneficiary), 0);
+	})
+}
+
 #[test]
 fn should_fetch_recv_packet_with_acknowledgement() {
 	let mut ext = new_test_ext();
@@ -412,6 +1003,55 @@ fn should_fetch_recv_packet_with_acknowledgement() {
 	})
 }
 
+#[test]
+fn should_record_received_packets_and_acknowledgements_in_events() {
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let channel_id = ChannelId::new(0);
+		let port_id = PortId::transfer();
+
+		let mut ctx = Context::<Test>::default();
+		ctx.store_channel((port_id.clone(), channel_id), &ChannelEnd::default())
+			.unwrap();
+		let packet = Packet {
+			sequence: 1u64.into(),
+			source_port: port_id.clone(),
+			source_channel: channel_id,
+			destination_port: port_id.clone(),
+			destination_channel: channel_id,
+			data: "hello".as_bytes().to_vec(),
+			timeout_height: Default::default(),
+			timeout_timestamp: Default::default(),
+		};
+		ctx.store_recv_packet((port_id.clone(), channel_id, packet.sequence), packet.clone())
+			.unwrap();
+		let ack = "success".as_bytes().to_vec();
+		Pallet::<Test>::write_acknowledgement(&packet, ack.clone()).unwrap();
+
+		let records = System::events()
+			.into_iter()
+			.filter_map(|record| match record.event {
+				Event::Ibc(crate::Event::PacketRecorded { record }) => Some(record),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(records.len(), 2);
+		assert!(matches!(
+			&records[0],
+			PacketRecord::Recv(packet_info) if packet_info.sequence == 1 && packet_info.data == packet.data
+		));
+		assert_eq!(
+			records[1],
+			PacketRecord::Ack {
+				port_id: port_id.as_bytes().to_vec(),
+				channel_id: channel_id.to_string().as_bytes().to_vec(),
+				sequence: 1,
+				ack,
+			}
+		);
+	})
+}
+
 #[test]
 fn should_cleanup_offchain_packets_correctly() {
 	let mut ext = new_test_ext();
@@ -571,6 +1211,184 @@ fn should_cleanup_offchain_packets_correctly() {
 	});
 }
 
+#[test]
+fn should_prune_stale_consensus_states_on_idle() {
+	new_test_ext().execute_with(|| {
+		let mut ctx = Context::<Test>::default();
+		let client_state = GrandpaClientState::<HostFunctionsManager> {
+			relay_chain: RelayChain::Rococo,
+			para_id: 2000,
+			latest_para_height: 5,
+			latest_relay_height: 100,
+			..Default::default()
+		};
+		let now = RelayChain::Rococo.trusting_period() + Duration::from_secs(60);
+		pallet_timestamp::Pallet::<Test>::set_timestamp(now.as_millis() as u64);
+		let client_state = AnyClientState::Grandpa(client_state);
+		let client_id = ClientId::new(&client_state.client_type(), 0).unwrap();
+		ctx.store_client_type(client_id.clone(), client_state.client_type()).unwrap();
+		ctx.store_client_state(client_id.clone(), client_state).unwrap();
+		let consensus_state = MockConsensusState::new(MockHeader::default());
+
+		let heights = (1..=5).map(|height| Height::new(0, height)).collect::<Vec<_>>();
+		for height in heights.iter() {
+			// only the latest consensus state is within the trusting period
+			let update_time = if height.revision_height == 5 { now.as_nanos() as u64 } else { 1 };
+			ctx.store_consensus_state(
+				client_id.clone(),
+				*height,
+				AnyConsensusState::Mock(consensus_state.clone()),
+			)
+			.unwrap();
+			ctx.store_update_time(
+				client_id.clone(),
+				*height,
+				Timestamp::from_nanoseconds(update_time).unwrap(),
+			)
+			.unwrap();
+			ctx.store_update_height(client_id.clone(), *height, Height::new(0, 1)).unwrap();
+		}
+
+		// consensus states stored before the index existed are indexed, then pruned
+		ConsensusStateClients::<Test>::remove(client_id.as_bytes().to_vec());
+		let _ = ConsensusStateHeights::<Test>::clear_prefix(client_id.as_bytes(), u32::MAX, None);
+		StorageVersion::new(0).put::<Ibc>();
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(StorageVersion::get::<Ibc>(), 1);
+		Ibc::on_idle(1, u64::MAX);
+		assert!(PruningIndexCursor::<Test>::get().is_none());
+		assert_eq!(
+			ConsensusStateHeights::<Test>::iter_prefix(client_id.as_bytes()).count(),
+			heights.len()
+		);
+		Ibc::on_idle(2, u64::MAX);
+
+		// the latest `ConsensusStatesToRetain` are kept even if they're stale
+		for height in &heights[..3] {
+			assert!(ctx.consensus_state(&client_id, *height).is_err());
+			assert!(ctx.client_update_time(&client_id, *height).is_err());
+		}
+		for height in &heights[3..] {
+			assert!(ctx.consensus_state(&client_id, *height).is_ok());
+			assert!(ctx.client_update_time(&client_id, *height).is_ok());
+		}
+	})
+}
+
+#[test]
+fn should_prune_consensus_states_within_budget() {
+	new_test_ext().execute_with(|| {
+		DbWeight::set(RuntimeDbWeight { read: 1, write: 1 });
+		let mut ctx = Context::<Test>::default();
+		let client_state = AnyClientState::Grandpa(GrandpaClientState::<HostFunctionsManager> {
+			relay_chain: RelayChain::Rococo,
+			..Default::default()
+		});
+		let now = RelayChain::Rococo.trusting_period() + Duration::from_secs(60);
+		pallet_timestamp::Pallet::<Test>::set_timestamp(now.as_millis() as u64);
+		let client_id = ClientId::new(&client_state.client_type(), 0).unwrap();
+		ctx.store_client_type(client_id.clone(), client_state.client_type()).unwrap();
+		ctx.store_client_state(client_id.clone(), client_state).unwrap();
+		let consensus_state = MockConsensusState::new(MockHeader::default());
+		// stored out of order, they're still pruned from the oldest
+		let heights = [4, 1, 6, 3, 5, 2].map(|height| Height::new(0, height));
+		for height in heights.iter() {
+			let update_time = if height.revision_height == 6 { now.as_nanos() as u64 } else { 1 };
+			ctx.store_consensus_state(
+				client_id.clone(),
+				*height,
+				AnyConsensusState::Mock(consensus_state.clone()),
+			)
+			.unwrap();
+			ctx.store_update_time(
+				client_id.clone(),
+				*height,
+				Timestamp::from_nanoseconds(update_time).unwrap(),
+			)
+			.unwrap();
+		}
+
+		// reading up to the first stale consensus state takes 9 reads, pruning it 4 writes on
+		// top of the 3 reads and writes done once per client, and every other one 2 reads and 4
+		// writes
+		assert_eq!(Ibc::on_idle(1, 16), 16);
+		assert_eq!(Ibc::on_idle(2, 22), 22);
+		let remaining = (1..=6)
+			.filter(|height| ctx.consensus_state(&client_id, Height::new(0, *height)).is_ok())
+			.collect::<Vec<_>>();
+		assert_eq!(remaining, vec![4, 5, 6]);
+
+		Ibc::on_idle(3, u64::MAX);
+		let remaining = (1..=6)
+			.filter(|height| ctx.consensus_state(&client_id, Height::new(0, *height)).is_ok())
+			.collect::<Vec<_>>();
+		assert_eq!(remaining, vec![5, 6]);
+	})
+}
+
+#[test]
+fn should_migrate_a_snapshot_of_unversioned_storage() {
+	new_test_ext().execute_with(|| {
+		let (client_id, connection_id) =
+			create_client_and_init_connection(&AccountId32::new([0; 32]));
+		let mut ctx = Context::<Test>::default();
+		let channel = (PortId::transfer(), ChannelId::new(0));
+		ctx.store_connection_channels(connection_id.clone(), &channel).unwrap();
+		let connection_clients = ConnectionClient::<Test>::iter().collect::<Vec<_>>();
+		let channels_connections = ChannelsConnection::<Test>::iter().collect::<Vec<_>>();
+		assert_eq!(connection_clients, vec![(client_id.clone(), vec![connection_id.clone()])]);
+		assert_eq!(channels_connections, vec![(connection_id.clone(), vec![channel.clone()])]);
+
+		// lay the snapshot out as it was stored before storage was versioned, raw identifiers are
+		// hashed into the same keys as typed ones so the old entries replace the new ones
+		for client_id in ConsensusStateClients::<Test>::iter_keys().collect::<Vec<_>>() {
+			ConsensusStateClients::<Test>::remove(client_id);
+		}
+		v2::v1::ConnectionClient::<Test>::insert(
+			client_id.as_bytes().to_vec(),
+			vec![connection_id.as_bytes().to_vec(), b"not a connection id".to_vec()],
+		);
+		v2::v1::ConnectionClient::<Test>::insert(vec![0xff], vec![b"connection-1".to_vec()]);
+		v2::v1::ChannelsConnection::<Test>::insert(
+			connection_id.as_bytes().to_vec(),
+			vec![(channel.0.as_bytes().to_vec(), channel.1.to_string().as_bytes().to_vec())],
+		);
+		let _ = ConsensusStateHeights::<Test>::clear(u32::MAX, None);
+		StorageVersion::new(0).put::<Ibc>();
+
+		Migrations::<Test>::on_runtime_upgrade();
+		assert_eq!(StorageVersion::get::<Ibc>(), 2);
+		assert!(IdentifierMigrationCursor::<Test>::get().is_some());
+		// channels not rewritten yet are read and bound in their old layout
+		assert_eq!(ctx.connection_channels(&connection_id).unwrap(), vec![channel.clone()]);
+		let other_channel = (PortId::transfer(), ChannelId::new(1));
+		ctx.store_connection_channels(connection_id.clone(), &other_channel).unwrap();
+		// identifiers are rewritten and consensus states are indexed in idle time
+		Ibc::on_idle(1, u64::MAX);
+
+		assert!(IdentifierMigrationCursor::<Test>::get().is_none());
+		assert_eq!(ConnectionClient::<Test>::iter().collect::<Vec<_>>(), connection_clients);
+		assert_eq!(
+			ChannelsConnection::<Test>::iter().collect::<Vec<_>>(),
+			vec![(connection_id.clone(), vec![channel.clone(), other_channel.clone()])]
+		);
+		assert_eq!(
+			ConsensusStateClients::<Test>::iter_keys().collect::<Vec<_>>(),
+			ClientUpdateTime::<Test>::iter_keys()
+				.map(|(client_id, _)| client_id)
+				.collect::<BTreeSet<_>>()
+				.into_iter()
+				.collect::<Vec<_>>()
+		);
+		assert_eq!(ctx.connection_channels(&connection_id).unwrap(), vec![channel, other_channel]);
+
+		// up to date storage is left alone
+		Migrations::<Test>::on_runtime_upgrade();
+		assert_eq!(StorageVersion::get::<Ibc>(), 2);
+		assert_eq!(ConnectionClient::<Test>::iter().collect::<Vec<_>>(), connection_clients);
+	})
+}
+
 #[test]
 fn test_next_and_previous_consensus_state_for_beefy_and_grandpa_clients() {
 	new_test_ext().execute_with(|| {
@@ -636,3 +1454,371 @@ fn test_next_and_previous_consensus_state_for_other_client_types() {
 		assert!(ctx.next_consensus_state(&client_id, Height::new(0, 400)).unwrap().is_some());
 	})
 }
+
+#[test]
+fn should_bind_and_release_ports_through_the_registry() {
+	new_test_ext().execute_with(|| {
+		let ctx = Context::<Test>::default();
+		let ping_port = PortId::from_str(pallet_ibc_ping::PORT_ID).unwrap();
+		let ping_module = ModuleId::from_str(pallet_ibc_ping::MODULE_ID).unwrap();
+		let mock_module = ModuleId::from_str(MockModule::MODULE_ID).unwrap();
+		// ping binds its port at genesis
+		assert_eq!(ctx.lookup_module_by_port(&ping_port).unwrap(), ping_module);
+
+		// modules missing from the router can't bind ports
+		let free_port = PortId::from_str("free").unwrap();
+		assert!(Ibc::bind_port::<UnroutedModule>(free_port.clone()).is_err());
+		assert!(ctx.lookup_module_by_port(&free_port).is_err());
+
+		// ports can't be taken over by another module or released without their capability
+		let capability = pallet_ibc_ping::PingPort::<Test>::get().unwrap();
+		assert!(Ibc::bind_port::<MockModule>(ping_port.clone()).is_err());
+		let forged = PortCapability::new(
+			ping_port.as_bytes().to_vec(),
+			mock_module.to_string().into_bytes(),
+			capability.nonce(),
+		);
+		assert!(Ibc::release_port(forged).is_err());
+		let guessed = PortCapability::new(
+			ping_port.as_bytes().to_vec(),
+			capability.module_id().to_vec(),
+			capability.nonce() + 1,
+		);
+		assert!(Ibc::release_port(guessed).is_err());
+		// the transfer port is reserved for ics20
+		assert!(Ibc::bind_port::<MockModule>(PortId::transfer()).is_err());
+
+		assert_ok!(Ibc::release_port(capability.clone()));
+		assert!(ctx.lookup_module_by_port(&ping_port).is_err());
+
+		let mock_capability = Ibc::bind_port::<MockModule>(ping_port.clone()).unwrap();
+		assert_eq!(ctx.lookup_module_by_port(&ping_port).unwrap(), mock_module);
+		// the capability of an earlier binding is stale
+		assert!(Ibc::release_port(capability).is_err());
+		assert_ok!(Ibc::release_port(mock_capability));
+	})
+}
+
+#[test]
+fn should_execute_allowed_interchain_queries_as_host() {
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let ctx = Context::<Test>::default();
+		let port_id = PortId::from_str(ICQ_PORT_ID).unwrap();
+		let channel_id = ChannelId::new(0);
+		sp_io::storage::set(b"icq-test/alice", b"100");
+		sp_io::storage::set(b"private/alice", b"secret");
+		assert_ok!(Ibc::set_icq_allowlist(Origin::root(), vec![b"icq-test/".to_vec()]));
+
+		let query = |sequence: u64, key: &[u8]| {
+			let requests = vec![QueryRequest {
+				path: STORAGE_QUERY_PATH.to_string(),
+				data: key.to_vec(),
+				height: 0,
+				prove: false,
+			}];
+			let packet = Packet {
+				sequence: sequence.into(),
+				source_port: port_id.clone(),
+				source_channel: channel_id,
+				destination_port: port_id.clone(),
+				destination_channel: channel_id,
+				data: serde_json::to_vec(&InterchainQueryPacketData { requests }).unwrap(),
+				timeout_height: Default::default(),
+				timeout_timestamp: Default::default(),
+			};
+			let mut output = HandlerOutputBuilder::new();
+			icq::IbcModule::<Test>::default()
+				.on_recv_packet(&ctx, &mut output, &packet, &Signer::from_str("relayer").unwrap())
+				.unwrap();
+			ctx.get_packet_acknowledgement(&(port_id.clone(), channel_id, sequence.into()))
+				.unwrap()
+		};
+
+		let expected = IcqAcknowledgement::Result(vec![QueryResponse {
+			code: 0,
+			value: b"100".to_vec(),
+			height: 1,
+			proof: vec![],
+			log: String::new(),
+		}]);
+		assert_eq!(query(1, b"icq-test/alice"), ctx.ack_commitment(expected.to_bytes().into()));
+
+		// keys outside the allow list are rejected with an error acknowledgement
+		let expected = IcqAcknowledgement::from_error(IcqError::query_not_allowed(
+			STORAGE_QUERY_PATH.to_string(),
+		));
+		assert_eq!(query(2, b"private/alice"), ctx.ack_commitment(expected.to_bytes().into()));
+	})
+}
+
+#[test]
+fn should_deliver_interchain_query_results_to_the_sending_module() {
+	new_test_ext().execute_with(|| {
+		let ctx = Context::<Test>::default();
+		let port_id = PortId::from_str("querier").unwrap();
+		let channel_id = ChannelId::new(0);
+		// the icq host port is reserved and query ports can't be bound twice
+		assert!(
+			Ibc::bind_query_port::<MockQuerier>(PortId::from_str(ICQ_PORT_ID).unwrap()).is_err()
+		);
+		assert_ok!(Ibc::bind_query_port::<MockQuerier>(port_id.clone()));
+		assert!(Ibc::bind_query_port::<MockQuerier>(port_id.clone()).is_err());
+		assert_eq!(
+			ctx.lookup_module_by_port(&port_id).unwrap(),
+			ModuleId::from_str(ICQ_MODULE_ID).unwrap()
+		);
+		setup_client_and_consensus_state(port_id.clone());
+
+		let requests = vec![QueryRequest {
+			path: STORAGE_QUERY_PATH.to_string(),
+			data: b"icq-test/alice".to_vec(),
+			height: 0,
+			prove: false,
+		}];
+		let timeout = Timeout::Offset { timestamp: Some(1000), height: Some(5) };
+		// queries can only be sent from ports bound by the sending module
+		assert!(Ibc::send_query::<MockQuerier>(
+			PortId::transfer(),
+			channel_id,
+			requests.clone(),
+			timeout.clone()
+		)
+		.is_err());
+		let sequence =
+			Ibc::send_query::<MockQuerier>(port_id.clone(), channel_id, requests.clone(), timeout)
+				.unwrap();
+
+		let packet = Packet {
+			sequence: sequence.into(),
+			source_port: port_id.clone(),
+			source_channel: channel_id,
+			destination_port: PortId::from_str(ICQ_PORT_ID).unwrap(),
+			destination_channel: ChannelId::new(1),
+			data: serde_json::to_vec(&InterchainQueryPacketData { requests }).unwrap(),
+			timeout_height: Default::default(),
+			timeout_timestamp: Default::default(),
+		};
+		let response = QueryResponse {
+			code: 0,
+			value: b"100".to_vec(),
+			height: 10,
+			proof: vec![],
+			log: String::new(),
+		};
+		let ack = IcqAcknowledgement::Result(vec![response.clone()]);
+		let mut output = HandlerOutputBuilder::new();
+		icq::IbcModule::<Test>::default()
+			.on_acknowledgement_packet(
+				&ctx,
+				&mut output,
+				&packet,
+				&ack.to_bytes().into(),
+				&Signer::from_str("relayer").unwrap(),
+			)
+			.unwrap();
+
+		QUERY_RESULTS.with(|results| {
+			assert_eq!(*results.borrow(), vec![(port_id.clone(), sequence, Ok(vec![response]))])
+		});
+	})
+}
+
+#[test]
+fn should_create_and_execute_wasm_clients_from_stored_code() {
+	new_test_ext().execute_with(|| {
+		let code = ics10_grandpa_wasm::WASM_BINARY_BLOATY.expect("light client wasm code is built");
+		// only the admin can store light client code and the code must be valid wasm
+		assert!(Ibc::store_wasm_client_code(
+			Origin::signed(AccountId32::new([0; 32])),
+			code.to_vec()
+		)
+		.is_err());
+		assert!(Ibc::store_wasm_client_code(Origin::root(), b"not wasm".to_vec()).is_err());
+		assert_ok!(Ibc::store_wasm_client_code(Origin::root(), code.to_vec()));
+
+		let grandpa_client_state = GrandpaClientState::<HostFunctionsManager> {
+			relay_chain: RelayChain::Rococo,
+			para_id: 2000,
+			latest_para_height: 10,
+			latest_relay_height: 100,
+			..Default::default()
+		};
+		let client_state = WasmClientState::<HostFunctionsManager> {
+			data: grandpa_client_state.encode_vec(),
+			code_id: sp_io::hashing::blake2_256(code).to_vec(),
+			latest_height: grandpa_client_state.latest_height(),
+			frozen_height: None,
+			_phantom: Default::default(),
+		};
+		let consensus_state = WasmConsensusState {
+			data: vec![],
+			timestamp: Timestamp::from_nanoseconds(1).unwrap(),
+			root: vec![0; 32].into(),
+		};
+		// the chain id and expiry are computed by the wasm code
+		assert_eq!(
+			AnyClientState::Wasm(client_state.clone()).chain_id(),
+			grandpa_client_state.chain_id()
+		);
+		assert!(!client_state.expired(Duration::from_secs(60)));
+		// failed calls into the code don't expire the client and fail upgrades
+		let undecodable = WasmClientState { data: vec![1, 2, 3], ..client_state.clone() };
+		assert!(!undecodable.expired(Duration::from_secs(60)));
+		assert!(client_state
+			.clone()
+			.upgrade(Height::new(0, 20), vec![], grandpa_client_state.chain_id())
+			.is_err());
+
+		let create_client = |client_state: WasmClientState<HostFunctionsManager>| {
+			let msg = MsgCreateAnyClient::<Context<Test>>::new(
+				AnyClientState::Wasm(client_state),
+				AnyConsensusState::Wasm(consensus_state.clone()),
+				Signer::from_str(MODULE_ID).unwrap(),
+			)
+			.unwrap()
+			.encode_vec();
+			let msg = Any { type_url: TYPE_URL.to_string().as_bytes().to_vec(), value: msg };
+			assert_ok!(Ibc::deliver(Origin::signed(AccountId32::new([0; 32])), vec![msg]));
+		};
+		let ctx = Context::<Test>::default();
+		let client_id = ClientId::new(&client_state.client_type(), 0).unwrap();
+
+		// the cached latest height must match the wrapped client state
+		create_client(WasmClientState {
+			latest_height: Height::new(0, 20),
+			..client_state.clone()
+		});
+		assert!(ctx.client_state(&client_id).is_err());
+
+		create_client(client_state.clone());
+		assert_eq!(
+			ctx.client_state(&client_id).unwrap(),
+			AnyClientState::Wasm(client_state.clone())
+		);
+
+		// messages the wasm code can't decode are rejected
+		let client_message = WasmClientMessage { data: vec![1, 2, 3] };
+		assert!(WasmClient::<HostFunctionsManager>::default()
+			.verify_client_message(&ctx, client_id, client_state, client_message)
+			.is_err());
+	})
+}
+
+#[test]
+fn should_paginate_packet_commitments() {
+	new_test_ext().execute_with(|| {
+		let port_id = PortId::transfer();
+		let mut ctx = Context::<Test>::default();
+		for i in 1..=5u64 {
+			ctx.store_packet_commitment(
+				(port_id.clone(), ChannelId::new(1), i.into()),
+				"commitment".as_bytes().to_vec().into(),
+			)
+			.unwrap();
+		}
+		// commitments of a channel whose id shares a prefix with the queried one
+		ctx.store_packet_commitment(
+			(port_id.clone(), ChannelId::new(10), 1.into()),
+			"commitment".as_bytes().to_vec().into(),
+		)
+		.unwrap();
+
+		let query = |pagination| {
+			let response = Pallet::<Test>::packet_commitments(
+				ChannelId::new(1).to_string().as_bytes().to_vec(),
+				port_id.as_bytes().to_vec(),
+				pagination,
+			)
+			.unwrap();
+			let sequences = response
+				.commitments
+				.into_iter()
+				.map(|commitment| commitment.sequence)
+				.collect::<Vec<_>>();
+			(sequences, response.pagination)
+		};
+
+		let (sequences, pagination) = query(None);
+		assert_eq!(sequences, vec![1, 2, 3, 4, 5]);
+		assert_eq!(pagination.next_key, None);
+
+		let (sequences, pagination) =
+			query(Some(PageRequest { limit: 2, count_total: true, ..Default::default() }));
+		assert_eq!(sequences, vec![1, 2]);
+		assert_eq!(pagination.total, Some(5));
+
+		let (sequences, pagination) =
+			query(Some(PageRequest { key: pagination.next_key, limit: 2, ..Default::default() }));
+		assert_eq!(sequences, vec![3, 4]);
+		assert_eq!(pagination.total, None);
+
+		let (sequences, pagination) =
+			query(Some(PageRequest { key: pagination.next_key, limit: 2, ..Default::default() }));
+		assert_eq!(sequences, vec![5]);
+		assert_eq!(pagination.next_key, None);
+
+		let (sequences, _) =
+			query(Some(PageRequest { offset: 1, limit: 2, reverse: true, ..Default::default() }));
+		assert_eq!(sequences, vec![4, 3]);
+	})
+}
+
+#[test]
+fn should_paginate_client_states_past_other_client_keys() {
+	new_test_ext().execute_with(|| {
+		let client_ids = [ClientId::new("07-tendermint", 0), ClientId::new("07-tendermint", 1)]
+			.map(|client_id| client_id.unwrap());
+		for client_id in &client_ids {
+			ClientStates::<Test>::insert(client_id, vec![1]);
+			// consensus states share the `clients/` prefix and must not count towards the page
+			ConsensusStates::<Test>::insert(client_id.clone(), Height::new(0, 1), vec![2]);
+		}
+
+		let page = |key| {
+			let response = Pallet::<Test>::clients(Some(PageRequest {
+				key,
+				limit: 1,
+				count_total: true,
+				..Default::default()
+			}));
+			let client_ids = response
+				.client_states
+				.into_iter()
+				.map(|client| String::from_utf8(client.client_id).unwrap())
+				.collect::<Vec<_>>();
+			(client_ids, response.pagination)
+		};
+
+		let (ids, pagination) = page(None);
+		assert_eq!(ids, vec![client_ids[0].to_string()]);
+		assert_eq!(pagination.total, Some(2));
+		let (ids, pagination) = page(pagination.next_key);
+		assert_eq!(ids, vec![client_ids[1].to_string()]);
+		assert_eq!(pagination.next_key, None);
+	})
+}
+
+#[test]
+fn should_reject_a_forged_grandpa_precommit_inside_a_batch() {
+	use grandpa_client_primitives::HostFunctions;
+	use sp_core::{ed25519, testing::TaskExecutor, traits::TaskExecutorExt};
+
+	let mut ext = new_test_ext();
+	ext.register_extension(TaskExecutorExt::new(TaskExecutor::new()));
+	ext.execute_with(|| {
+		let voter = ed25519::Pair::from_seed(&[1; 32]);
+		let message = b"precommit".to_vec();
+		let forged = ed25519::Pair::from_seed(&[2; 32]).sign(&message);
+		let batch = vec![
+			(voter.sign(&message), message.clone(), voter.public()),
+			(forged, message, voter.public()),
+		];
+
+		// the block executor keeps a batch open while it imports a block
+		sp_io::crypto::start_batch_verify();
+		assert!(HostFunctionsManager::ed25519_batch_verify(&batch[..1]));
+		assert!(!HostFunctionsManager::ed25519_batch_verify(&batch));
+		assert!(sp_io::crypto::finish_batch_verify());
+	})
+}
@@ -13,19 +13,23 @@
 // limitations under the License.
 
 use super::*;
-use crate::{light_clients::AnyClientMessage, routing::Context};
+use crate::{
+	light_clients::{wasm, AnyClientMessage, AnyClientState},
+	routing::{Context, ModuleRouter},
+};
 use core::marker::PhantomData;
-use frame_support::pallet_prelude::Weight;
+use frame_support::{pallet_prelude::Weight, traits::Get};
 use grandpa_client_primitives::justification::GrandpaJustification;
 use ibc::core::{
 	ics02_client::msgs::ClientMsg,
 	ics03_connection::{context::ConnectionReader, msgs::ConnectionMsg},
 	ics04_channel::msgs::{ChannelMsg, PacketMsg},
-	ics24_host::identifier::ClientId,
-	ics26_routing::msgs::Ics26Envelope,
+	ics24_host::identifier::{ClientId, PortId},
+	ics26_routing::{context::ModuleId, msgs::Ics26Envelope},
 };
-use ibc_primitives::{client_id_from_bytes, CallbackWeight};
+use ibc_primitives::{channel_id_from_bytes, port_id_from_bytes, CallbackWeight};
 use ics10_grandpa::client_message::{ClientMessage, RelayChainHeader};
+use ics_ethereum::client_message::ClientMessage as EthereumClientMessage;
 use scale_info::prelude::string::ToString;
 
 pub trait WeightInfo {
@@ -56,6 +60,9 @@ pub trait WeightInfo {
 	fn on_acknowledgement_packet() -> Weight;
 	fn on_timeout_packet() -> Weight;
 	fn update_grandpa_client(i: u32) -> Weight;
+	fn update_ethereum_client() -> Weight;
+	fn prune_consensus_states(i: u32) -> Weight;
+	fn redundancy_check(i: u32) -> Weight;
 }
 
 impl WeightInfo for () {
@@ -166,6 +173,18 @@ impl WeightInfo for () {
 	fn update_grandpa_client(_i: u32) -> Weight {
 		0
 	}
+
+	fn update_ethereum_client() -> Weight {
+		0
+	}
+
+	fn prune_consensus_states(_i: u32) -> Weight {
+		0
+	}
+
+	fn redundancy_check(_i: u32) -> Weight {
+		0
+	}
 }
 
 pub struct WeightRouter<T: Config>(PhantomData<T>);
@@ -175,25 +194,62 @@ impl<T: Config> WeightRouter<T> {
 		match port_id {
 			ibc::applications::transfer::PORT_ID_STR =>
 				Some(Box::new(ics20::WeightHandler::<T>::default())),
-			_ => None,
+			ibc::applications::icq::PORT_ID_STR =>
+				Some(Box::new(icq::WeightHandler::<T>::default())),
+			_ if IcqControllerPorts::<T>::contains_key(port_id.as_bytes()) =>
+				Some(Box::new(icq::WeightHandler::<T>::default())),
+			_ => {
+				let module_id = match PortBindings::<T>::get(port_id.as_bytes()) {
+					Some(module_id) =>
+						ModuleId::from_str(&String::from_utf8(module_id).ok()?).ok()?,
+					None => T::Router::lookup_module_by_port(&PortId::from_str(port_id).ok()?)?,
+				};
+				T::Router::callback_weight(&module_id)
+			},
 		}
 	}
 }
 
 /// Get client id for a port and channel combination
 pub fn channel_client<T: Config>(channel_id: &[u8], port_id: &[u8]) -> Result<ClientId, Error<T>> {
-	for (connection_id, channels) in ChannelsConnection::<T>::iter() {
-		if channels.contains(&(port_id.to_vec(), channel_id.to_vec())) {
+	let port_id = port_id_from_bytes(port_id.to_vec()).map_err(|_| Error::<T>::Other)?;
+	let channel_id = channel_id_from_bytes(channel_id.to_vec()).map_err(|_| Error::<T>::Other)?;
+	for connection_id in ChannelsConnection::<T>::iter_keys() {
+		let channels = crate::migrations::v2::connection_channels::<T>(&connection_id);
+		if channels.contains(&(port_id.clone(), channel_id)) {
 			if let Some((client_id, ..)) = ConnectionClient::<T>::iter()
 				.find(|(.., connection_ids)| connection_ids.contains(&connection_id))
 			{
-				return client_id_from_bytes(client_id).map_err(|_| Error::<T>::Other)
+				return Ok(client_id)
 			}
 		}
 	}
 	Err(Error::<T>::Other)
 }
 
+/// Weight of checking whether a packet message was relayed already, it's decoded and checked
+/// against its channel end and its receipt, commitment or next sequence to receive. Decoding
+/// dominates, so it's benchmarked by the size of the message.
+pub fn redundancy_check<T: Config>(msg: &Any) -> Weight {
+	T::WeightInfo::redundancy_check(msg.value.len() as u32)
+}
+
+/// Weight of a `deliver` call, the packet messages are all checked for redundancy before the
+/// ones that weren't relayed yet are executed.
+pub(crate) fn deliver_checked<T: Config + Send + Sync>(msgs: &[Any]) -> Weight
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+{
+	deliver::<T>(msgs).saturating_add(redundancy_checks::<T>(msgs))
+}
+
+/// Weight of checking the packet messages among `msgs` for redundancy.
+pub(crate) fn redundancy_checks<T: Config>(msgs: &[Any]) -> Weight {
+	msgs.iter()
+		.filter(|msg| crate::redundancy::is_packet_message(msg))
+		.fold(Weight::default(), |acc, msg| acc.saturating_add(redundancy_check::<T>(msg)))
+}
+
 pub(crate) fn deliver<T: Config + Send + Sync>(msgs: &[Any]) -> Weight
 where
 	u32: From<<T as frame_system::Config>::BlockNumber>,
@@ -210,7 +266,11 @@ where
 			// Add benchmarked weight for module callback
 			let temp = match msg {
 				Ics26Envelope::Ics2Msg(msgs) => match msgs {
-					ClientMsg::CreateClient(_) => <T as Config>::WeightInfo::create_client(),
+					ClientMsg::CreateClient(msg) => match msg.client_state {
+						AnyClientState::Wasm(_) => <T as Config>::WeightInfo::create_client()
+							.saturating_add(wasm::create_client_weight()),
+						_ => <T as Config>::WeightInfo::create_client(),
+					},
 					ClientMsg::UpdateClient(msg) => {
 						let client_type = msg
 							.client_id
@@ -232,14 +292,30 @@ where
 											justification.commit.precommits.len() as u32,
 										)
 									},
-									ClientMessage::Misbehaviour(_) => Weight::default(),
+									ClientMessage::Misbehaviour(_) |
+									ClientMessage::Equivocation(_) => Weight::default(),
 								},
 								_ => return Weight::MAX,
 							},
+							Some(ty) if ty.contains("ethereum") => match msg.client_message {
+								// a misbehaviour carries two updates signed by the sync committee
+								AnyClientMessage::Ethereum(
+									EthereumClientMessage::Misbehaviour(_),
+								) => <T as Config>::WeightInfo::update_ethereum_client()
+									.saturating_mul(2),
+								AnyClientMessage::Ethereum(_) =>
+									<T as Config>::WeightInfo::update_ethereum_client(),
+								_ => return Weight::MAX,
+							},
+							Some(ty) if ty.contains("wasm") => wasm::update_client_weight(),
 							_ => Weight::default(),
 						}
 					},
-					ClientMsg::UpgradeClient(_) => Weight::default(),
+					ClientMsg::UpgradeClient(msg) =>
+						match msg.client_id.as_str().rsplit_once('-') {
+							Some((ty, ..)) if ty.contains("wasm") => wasm::upgrade_client_weight(),
+							_ => Weight::default(),
+						},
 				},
 				Ics26Envelope::Ics3Msg(msgs) => match msgs {
 					ConnectionMsg::ConnectionOpenInit(_) =>
@@ -253,6 +329,8 @@ where
 						match client_type {
 							Some(ty) if ty.contains("tendermint") =>
 								<T as Config>::WeightInfo::conn_try_open_tendermint(),
+							// connection, client and consensus state proofs
+							Some(ty) if ty.contains("wasm") => wasm::verify_proofs_weight(3),
 							_ => Weight::default(),
 						}
 					},
@@ -268,6 +346,8 @@ where
 						match client_type {
 							Some(ty) if ty.contains("tendermint") =>
 								<T as Config>::WeightInfo::conn_open_ack_tendermint(),
+							// connection, client and consensus state proofs
+							Some(ty) if ty.contains("wasm") => wasm::verify_proofs_weight(3),
 							_ => Weight::default(),
 						}
 					},
@@ -283,6 +363,7 @@ where
 						match client_type {
 							Some(ty) if ty.contains("tendermint") =>
 								<T as Config>::WeightInfo::conn_open_confirm_tendermint(),
+							Some(ty) if ty.contains("wasm") => wasm::verify_proofs_weight(1),
 							_ => Weight::default(),
 						}
 					},
@@ -331,6 +412,8 @@ where
 									match client_type {
 										Some(ty) if ty.contains("tendermint") =>
 											<T as Config>::WeightInfo::channel_open_try_tendermint(),
+										Some(ty) if ty.contains("wasm") =>
+											wasm::verify_proofs_weight(1),
 										_ => Weight::default(),
 									}
 								},
@@ -355,6 +438,8 @@ where
 								match client_type {
 									Some(ty) if ty.contains("tendermint") =>
 										<T as Config>::WeightInfo::channel_open_ack_tendermint(),
+									Some(ty) if ty.contains("wasm") =>
+										wasm::verify_proofs_weight(1),
 									_ => Weight::default(),
 								}
 							},
@@ -379,6 +464,8 @@ where
 								match client_type {
 									Some(ty) if ty.contains("tendermint") =>
 										<T as Config>::WeightInfo::channel_open_confirm_tendermint(),
+									Some(ty) if ty.contains("wasm") =>
+										wasm::verify_proofs_weight(1),
 									_ => Weight::default(),
 								}
 							},
@@ -428,6 +515,8 @@ where
 									Some(ty) if ty.contains("tendermint") =>
 										<T as Config>::WeightInfo::channel_close_confirm_tendermint(
 										),
+									Some(ty) if ty.contains("wasm") =>
+										wasm::verify_proofs_weight(1),
 									_ => Weight::default(),
 								}
 							},
@@ -457,6 +546,8 @@ where
 										<T as Config>::WeightInfo::recv_packet_tendermint(
 											packet_msg.packet.data.len() as u32,
 										),
+									Some(ty) if ty.contains("wasm") =>
+										wasm::verify_proofs_weight(1),
 									_ => Weight::default(),
 								}
 							},
@@ -488,6 +579,8 @@ where
 											packet_msg.packet.data.len() as u32,
 											packet_msg.acknowledgement.into_bytes().len() as u32,
 										),
+									Some(ty) if ty.contains("wasm") =>
+										wasm::verify_proofs_weight(1),
 									_ => Weight::default(),
 								}
 							},
@@ -515,6 +608,8 @@ where
 										<T as Config>::WeightInfo::timeout_packet_tendermint(
 											packet_msg.packet.data.len() as u32,
 										),
+									Some(ty) if ty.contains("wasm") =>
+										wasm::verify_proofs_weight(1),
 									_ => Weight::default(),
 								}
 							},
@@ -542,6 +637,9 @@ where
 										<T as Config>::WeightInfo::timeout_packet_tendermint(
 											packet_msg.packet.data.len() as u32,
 										),
+									// channel state and packet receipt absence proofs
+									Some(ty) if ty.contains("wasm") =>
+										wasm::verify_proofs_weight(2),
 									_ => Weight::default(),
 								}
 							},
@@ -109,6 +109,16 @@ pub enum AnyConfig {
 #[derive(Deserialize)]
 pub struct CoreConfig {
 	pub prometheus_endpoint: Option<String>,
+	/// RPC urls of independent full nodes of chain A, if it's a tendermint chain. The fisherman
+	/// checks the updates of its client on chain B against them.
+	#[cfg(feature = "cosmos")]
+	#[serde(default)]
+	pub chain_a_tendermint_witnesses: Vec<String>,
+	/// RPC urls of independent full nodes of chain B, if it's a tendermint chain. The fisherman
+	/// checks the updates of its client on chain A against them.
+	#[cfg(feature = "cosmos")]
+	#[serde(default)]
+	pub chain_b_tendermint_witnesses: Vec<String>,
 }
 
 #[derive(Clone)]
@@ -166,9 +176,11 @@ impl IbcProvider for AnyChain {
 		}
 	}
 
-	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
+	async fn ibc_events(
+		&self,
+	) -> Result<Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>>, Self::Error> {
 		match self {
-			Self::Parachain(chain) => chain.ibc_events().await,
+			Self::Parachain(chain) => chain.ibc_events().await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -561,6 +573,13 @@ impl MisbehaviourHandler for AnyChain {
 			_ => unreachable!(),
 		}
 	}
+
+	async fn report_equivocations<C: Chain>(&self, counterparty: &C) -> Result<(), anyhow::Error> {
+		match self {
+			AnyChain::Parachain(parachain) => parachain.report_equivocations(counterparty).await,
+			_ => unreachable!(),
+		}
+	}
 }
 
 impl KeyProvider for AnyChain {
@@ -588,6 +607,13 @@ impl Chain for AnyChain {
 		}
 	}
 
+	fn client_refresh_fraction(&self) -> f64 {
+		match self {
+			Self::Parachain(chain) => chain.client_refresh_fraction(),
+			_ => unreachable!(),
+		}
+	}
+
 	async fn estimate_weight(&self, msg: Vec<Any>) -> Result<u64, Self::Error> {
 		match self {
 			Self::Parachain(chain) => chain.estimate_weight(msg).await.map_err(Into::into),
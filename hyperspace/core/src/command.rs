@@ -18,7 +18,7 @@ use primitives::Chain;
 use prometheus::Registry;
 use std::{path::PathBuf, str::FromStr, time::Duration};
 
-use crate::{chain::Config, fish, relay};
+use crate::{chain::Config, fish, relay, TendermintWitnesses};
 use ibc::core::{ics04_channel::channel::Order, ics24_host::identifier::PortId};
 use metrics::{data::Metrics, handler::MetricsHandler, init_prometheus};
 use primitives::{
@@ -100,10 +100,18 @@ impl Cmd {
 		let path: PathBuf = self.config.parse()?;
 		let file_content = tokio::fs::read_to_string(path).await?;
 		let config: Config = toml::from_str(&file_content)?;
+		#[cfg(feature = "cosmos")]
+		let tendermint_witnesses = TendermintWitnesses::connect(
+			&config.core.chain_a_tendermint_witnesses,
+			&config.core.chain_b_tendermint_witnesses,
+		)
+		.await?;
+		#[cfg(not(feature = "cosmos"))]
+		let tendermint_witnesses = TendermintWitnesses::default();
 		let any_chain_a = config.chain_a.into_client().await?;
 		let any_chain_b = config.chain_b.into_client().await?;
 
-		fish(any_chain_a, any_chain_b).await
+		fish(any_chain_a, any_chain_b, tendermint_witnesses).await
 	}
 
 	pub async fn create_clients(&self) -> Result<()> {
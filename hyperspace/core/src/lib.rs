@@ -15,7 +15,7 @@
 #![warn(unused_variables)]
 
 use futures::{future::ready, StreamExt};
-use primitives::Chain;
+use primitives::{query_client_expiry, Chain};
 
 pub mod chain;
 pub mod command;
@@ -60,7 +60,66 @@ where
 	Ok(())
 }
 
-pub async fn fish<A, B>(chain_a: A, chain_b: B) -> Result<(), anyhow::Error>
+/// Independent full nodes of the tendermint chains being relayed, checked by the fisherman
+/// against the updates of their clients on the counterparty.
+#[derive(Default)]
+pub struct TendermintWitnesses {
+	/// Witnesses of chain A, checked against the updates of its client on chain B
+	#[cfg(feature = "cosmos")]
+	pub chain_a: Vec<cosmos::provider::RpcLightBlockProvider>,
+	/// Witnesses of chain B, checked against the updates of its client on chain A
+	#[cfg(feature = "cosmos")]
+	pub chain_b: Vec<cosmos::provider::RpcLightBlockProvider>,
+}
+
+#[cfg(feature = "cosmos")]
+impl TendermintWitnesses {
+	/// Connects to the witnesses of chain A and chain B at the given RPC urls.
+	pub async fn connect(
+		chain_a_urls: &[String],
+		chain_b_urls: &[String],
+	) -> Result<Self, anyhow::Error> {
+		let connect = |urls: &[String]| {
+			let urls = urls.to_vec();
+			async move {
+				let mut witnesses = vec![];
+				for url in urls {
+					let witness = cosmos::provider::RpcLightBlockProvider::new(&url)
+						.await
+						.map_err(|e| anyhow::anyhow!("Failed to connect to witness {url}: {e}"))?;
+					witnesses.push(witness);
+				}
+				Ok::<_, anyhow::Error>(witnesses)
+			}
+		};
+		Ok(Self { chain_a: connect(chain_a_urls).await?, chain_b: connect(chain_b_urls).await? })
+	}
+}
+
+/// Checks the updates of the tendermint client `client_id` on the counterparty against the
+/// witnesses of the chain it tracks, until the counterparty's event stream ends.
+#[cfg(feature = "cosmos")]
+async fn fish_tendermint<C: Chain>(
+	counterparty: &C,
+	client_id: ibc::core::ics24_host::identifier::ClientId,
+	witnesses: Vec<cosmos::provider::RpcLightBlockProvider>,
+) -> Result<(), anyhow::Error> {
+	if witnesses.is_empty() {
+		return Ok(())
+	}
+	cosmos::fisherman::Fisherman::<_, pallet_ibc::light_clients::HostFunctionsManager>::new(
+		witnesses,
+	)
+	.fish(counterparty, client_id)
+	.await
+	.map_err(|e| anyhow::anyhow!("Tendermint fisherman failed: {e}"))
+}
+
+pub async fn fish<A, B>(
+	chain_a: A,
+	chain_b: B,
+	tendermint_witnesses: TendermintWitnesses,
+) -> Result<(), anyhow::Error>
 where
 	A: Chain,
 	A::Error: From<B::Error>,
@@ -69,14 +128,14 @@ where
 {
 	// we only care about events where the counterparty light client is updated.
 	let (mut chain_a_client_updates, mut chain_b_client_updates) = (
-		chain_a.ibc_events().await.filter_map(|ev| {
+		chain_a.ibc_events().await?.filter_map(|ev| {
 			ready(match ev {
 				IbcEvent::UpdateClient(update) if chain_b.client_id() == *update.client_id() =>
 					Some(update),
 				_ => None,
 			})
 		}),
-		chain_b.ibc_events().await.filter_map(|ev| {
+		chain_b.ibc_events().await?.filter_map(|ev| {
 			ready(match ev {
 				IbcEvent::UpdateClient(update) if chain_a.client_id() == *update.client_id() =>
 					Some(update),
@@ -85,29 +144,54 @@ where
 		}),
 	);
 
-	// loop forever
-	loop {
-		tokio::select! {
-			// new finality event from chain A
-			update = chain_a_client_updates.next() => {
-				let update = match update {
-					Some(update) => update,
-					None => break,
-				};
-				let message = chain_a.query_client_message(update).await?;
-				chain_b.check_for_misbehaviour(&chain_a, message).await?;
-			}
-			// new finality event from chain B
-			update = chain_b_client_updates.next() => {
-				let update = match update {
-					Some(update) => update,
-					None => break,
-				};
-				let message = chain_b.query_client_message(update).await?;
-				chain_a.check_for_misbehaviour(&chain_b, message).await?;
+	let check_client_updates = async {
+		loop {
+			tokio::select! {
+				// new finality event from chain A
+				update = chain_a_client_updates.next() => {
+					let update = match update {
+						Some(update) => update,
+						None => break,
+					};
+					let message = chain_a.query_client_message(update).await?;
+					chain_b.check_for_misbehaviour(&chain_a, message).await?;
+				}
+				// new finality event from chain B
+				update = chain_b_client_updates.next() => {
+					let update = match update {
+						Some(update) => update,
+						None => break,
+					};
+					let message = chain_b.query_client_message(update).await?;
+					chain_a.check_for_misbehaviour(&chain_b, message).await?;
+				}
 			}
 		}
-	}
+		Ok::<_, anyhow::Error>(())
+	};
+
+	// headers of tendermint chains are also checked against independent witnesses.
+	#[cfg(feature = "cosmos")]
+	let check_tendermint_updates = async {
+		tokio::try_join!(
+			fish_tendermint(&chain_a, chain_b.client_id(), tendermint_witnesses.chain_b),
+			fish_tendermint(&chain_b, chain_a.client_id(), tendermint_witnesses.chain_a),
+		)
+		.map(|_| ())
+	};
+	#[cfg(not(feature = "cosmos"))]
+	let check_tendermint_updates = async {
+		let _ = tendermint_witnesses;
+		Ok::<_, anyhow::Error>(())
+	};
+
+	// the chains also watch their own finality votes for equivocations.
+	tokio::try_join!(
+		check_client_updates,
+		check_tendermint_updates,
+		chain_a.report_equivocations(&chain_b),
+		chain_b.report_equivocations(&chain_a),
+	)?;
 
 	Ok(())
 }
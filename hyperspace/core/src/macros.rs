@@ -50,10 +50,31 @@ macro_rules! process_finality_event {
 					log::info!("Submitting timeout messages to {}: {type_urls:#?}", $source.name());
 					queue::flush_message_batch(timeouts, $metrics.as_ref(), &$source).await?;
 				}
+				// We also want to send the client update if the client is getting close to expiry
+				let refresh_due = match query_client_expiry(&$source, &$sink).await {
+					Ok(Some(expiry)) => {
+						if let Some(metrics) = $metrics.as_ref() {
+							metrics.handle_client_expiry(&$source.client_id(), expiry.time_until_expiry());
+						}
+						expiry.refresh_due($source.client_refresh_fraction())
+					},
+					Ok(None) => false,
+					Err(err) => {
+						log::error!(
+							"Failed to query client expiry for {} {:?}",
+							$source.name(),
+							err
+						);
+						false
+					},
+				};
+				if refresh_due {
+					log::info!("Refreshing client of {} on {}", $source.name(), $sink.name());
+				}
 				// We want to send client update if packet messages exist but where not sent due to
 				// a connection delay even if client update message is optional
 				match (
-					update_type.is_optional(),
+					update_type.is_optional() && !refresh_due,
 					has_packet_events(&event_types),
 					messages.is_empty(),
 				) {
@@ -0,0 +1,183 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::Error, provider::LightBlockProvider};
+use ibc::{
+	core::{
+		ics02_client::msgs::update_client::MsgUpdateAnyClient,
+		ics24_host::identifier::{ChainId, ClientId},
+	},
+	signer::Signer,
+	tx_msg::Msg,
+	Height,
+};
+use ibc_proto::google::protobuf::Any;
+use ics07_tendermint::client_message::{ClientMessage, Header};
+use pallet_ibc::light_clients::AnyClientMessage;
+use primitives::mock::LocalClientTypes;
+use std::{collections::BTreeMap, marker::PhantomData};
+use tendermint::Time;
+use tendermint_light_client_verifier::{
+	host_functions::CryptoProvider, options::Options, types::LightBlock, ProdVerifier, Verdict,
+	Verifier,
+};
+
+/// Verifies tendermint light blocks by skipping from a trusted light block straight to a target,
+/// bisecting the height range whenever the trusted validators didn't sign enough of the target's
+/// commit, eg. because the validator set changed by more than the trust threshold.
+pub struct SkippingVerifier<P, H> {
+	/// Source of the light blocks
+	provider: P,
+	/// Trust threshold, trusting period and clock drift of the counterparty's client
+	options: Options,
+	/// Light blocks fetched from the provider, by height. The block at a height never changes, so
+	/// blocks fetched while bisecting are kept for later attempts and the next update.
+	light_blocks: BTreeMap<u64, LightBlock>,
+	_phantom: PhantomData<H>,
+}
+
+impl<P, H> SkippingVerifier<P, H>
+where
+	P: LightBlockProvider,
+	H: CryptoProvider + Default,
+{
+	/// Creates a verifier using the light client options of the client being updated.
+	pub fn new(provider: P, options: Options) -> Self {
+		Self { provider, options, light_blocks: BTreeMap::new(), _phantom: PhantomData }
+	}
+
+	/// Light block at the given height, fetched from the provider if it isn't cached.
+	pub async fn light_block(&mut self, height: u64) -> Result<LightBlock, Error> {
+		if let Some(light_block) = self.light_blocks.get(&height) {
+			return Ok(light_block.clone())
+		}
+		let light_block = self.provider.light_block(height).await?;
+		self.light_blocks.insert(height, light_block.clone());
+		Ok(light_block)
+	}
+
+	/// Drops the cached light blocks below the given height.
+	pub fn prune_below(&mut self, height: u64) {
+		self.light_blocks = self.light_blocks.split_off(&height);
+	}
+
+	/// Verifies the light block at `target_height` from the trusted one at `trusted_height`,
+	/// returning the verified light blocks needed to get there in ascending order. Each block is
+	/// verified from the previous one, or the trusted block for the first, and the last one is
+	/// the target.
+	pub async fn verify_to_target(
+		&mut self,
+		trusted_height: u64,
+		target_height: u64,
+		now: Time,
+	) -> Result<Vec<LightBlock>, Error> {
+		if target_height <= trusted_height {
+			Err(Error::Custom(format!(
+				"Target height {target_height} must be above the trusted height {trusted_height}"
+			)))?
+		}
+
+		let verifier = ProdVerifier::<H>::default();
+		let mut trusted = self.light_block(trusted_height).await?;
+		let mut trace = vec![];
+		// heights left to verify, the last one is tried next
+		let mut pending = vec![target_height];
+
+		while let Some(&height) = pending.last() {
+			let untrusted = self.light_block(height).await?;
+			let trusted_height = trusted.height().value();
+			match verifier.verify(
+				untrusted.as_untrusted_state(),
+				trusted.as_trusted_state(),
+				&self.options,
+				now,
+			) {
+				Verdict::Success => {
+					pending.pop();
+					trace.push(untrusted.clone());
+					trusted = untrusted;
+				},
+				Verdict::NotEnoughTrust(tally) => {
+					let pivot = trusted_height + (height - trusted_height) / 2;
+					if pivot == trusted_height {
+						Err(Error::BisectionFailed { height, trusted_height })?
+					}
+					log::debug!(
+						target: "hyperspace_cosmos",
+						"Not enough trust to verify {height} from {trusted_height} ({tally}), bisecting at {pivot}"
+					);
+					pending.push(pivot);
+				},
+				Verdict::Invalid(detail) => Err(Error::InvalidLightBlock {
+					height,
+					trusted_height,
+					reason: format!("{detail:?}"),
+				})?,
+			}
+		}
+
+		Ok(trace)
+	}
+
+	/// Messages updating the client on the counterparty from its latest height to
+	/// `target_height`, through as few intermediate headers as the trust threshold allows.
+	pub async fn update_client_msgs(
+		&mut self,
+		client_id: ClientId,
+		trusted_height: u64,
+		target_height: u64,
+		signer: Signer,
+		now: Time,
+	) -> Result<Vec<Any>, Error> {
+		let trusted = self.light_block(trusted_height).await?;
+		let trace = self.verify_to_target(trusted_height, target_height, now).await?;
+		// the next update is verified from the target
+		self.prune_below(target_height);
+
+		Ok(update_client_msgs(client_id, headers_for_trace(&trusted, &trace), signer))
+	}
+}
+
+/// ICS-07 headers for the light blocks returned by [`SkippingVerifier::verify_to_target`], each
+/// trusting the previous light block, or `trusted` for the first one.
+pub fn headers_for_trace(trusted: &LightBlock, trace: &[LightBlock]) -> Vec<Header> {
+	let revision_number = ChainId::chain_version(trusted.signed_header.header.chain_id.as_str());
+	let mut trusted = trusted;
+	let mut headers = vec![];
+	for light_block in trace {
+		headers.push(Header {
+			signed_header: light_block.signed_header.clone(),
+			validator_set: light_block.validators.clone(),
+			trusted_height: Height::new(revision_number, trusted.height().value()),
+			trusted_validator_set: trusted.next_validators.clone(),
+		});
+		trusted = light_block;
+	}
+	headers
+}
+
+/// A batch of `MsgUpdateClient`s submitting the given headers in order.
+pub fn update_client_msgs(client_id: ClientId, headers: Vec<Header>, signer: Signer) -> Vec<Any> {
+	headers
+		.into_iter()
+		.map(|header| {
+			MsgUpdateAnyClient::<LocalClientTypes>::new(
+				client_id.clone(),
+				AnyClientMessage::Tendermint(ClientMessage::Header(header)),
+				signer.clone(),
+			)
+			.to_any()
+		})
+		.collect()
+}
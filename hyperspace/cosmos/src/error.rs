@@ -0,0 +1,41 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use thiserror::Error;
+
+/// Error definition for the Cosmos client
+#[derive(Error, Debug)]
+pub enum Error {
+	/// Tendermint RPC error
+	#[error("RPC error: {0}")]
+	Rpc(#[from] tendermint_rpc::Error),
+	/// Tendermint error
+	#[error("Tendermint error: {0}")]
+	Tendermint(#[from] tendermint::Error),
+	/// The light block at the given height couldn't be verified from the trusted one
+	#[error("Invalid light block at height {height} trusting height {trusted_height}: {reason}")]
+	InvalidLightBlock { height: u64, trusted_height: u64, reason: String },
+	/// Bisection can't make progress, adjacent light blocks don't trust each other
+	#[error("Bisection failed to verify height {height} from trusted height {trusted_height}")]
+	BisectionFailed { height: u64, trusted_height: u64 },
+	/// Custom error
+	#[error("{0}")]
+	Custom(String),
+}
+
+impl From<String> for Error {
+	fn from(error: String) -> Self {
+		Self::Custom(error)
+	}
+}
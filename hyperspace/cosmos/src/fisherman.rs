@@ -0,0 +1,254 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	bisection::{headers_for_trace, update_client_msgs, SkippingVerifier},
+	error::Error,
+	provider::LightBlockProvider,
+};
+use futures::{future::ready, StreamExt};
+use ibc::{
+	core::{
+		ics02_client::msgs::update_client::MsgUpdateAnyClient, ics24_host::identifier::ClientId,
+	},
+	events::IbcEvent,
+	signer::Signer,
+	tx_msg::Msg,
+};
+use ibc_proto::google::protobuf::Any;
+use ics07_tendermint::client_message::{ClientMessage, Header, Misbehaviour};
+use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState};
+use primitives::{mock::LocalClientTypes, Chain};
+use std::marker::PhantomData;
+use tendermint::Time;
+use tendermint_light_client_verifier::{
+	host_functions::CryptoProvider, options::Options, types::LightBlock,
+};
+
+/// The kind of attack a conflicting header is evidence of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MisbehaviourKind {
+	/// Validators signed two different blocks for the same height and round.
+	DuplicateVote,
+	/// The header commits to state that can't be derived from the witness' chain, eg. a made up
+	/// validator set or app hash.
+	Lunatic,
+	/// Validators signed a different block in a later round without having unlocked.
+	Amnesia,
+}
+
+/// Misbehaviour found by the [`Fisherman`], ready to be submitted to freeze the client.
+#[derive(Clone, Debug)]
+pub struct Evidence {
+	pub kind: MisbehaviourKind,
+	/// Headers of the witness' chain the client must hold before the misbehaviour, the witness
+	/// header trusts the last of them.
+	pub supporting_headers: Vec<Header>,
+	pub misbehaviour: Misbehaviour,
+}
+
+/// Checks the tendermint headers submitted to a client on the counterparty against the light
+/// blocks of independent witnesses, building misbehaviour evidence on any conflict.
+pub struct Fisherman<W, H> {
+	witnesses: Vec<W>,
+	_phantom: PhantomData<H>,
+}
+
+impl<W, H> Fisherman<W, H>
+where
+	W: LightBlockProvider,
+	H: CryptoProvider + Default,
+{
+	/// Creates a fisherman checking headers against the given witnesses.
+	pub fn new(witnesses: Vec<W>) -> Self {
+		Self { witnesses, _phantom: PhantomData }
+	}
+
+	/// Compares the header with the light block at the same height from each witness, returning
+	/// evidence for the first conflict found. The witness header is verified from the trusted
+	/// height of the conflicting header, which the client holds, with the light client `options`
+	/// of the client. Witnesses that fail to respond or to verify their own light block are
+	/// skipped.
+	pub async fn check_header(
+		&self,
+		client_id: &ClientId,
+		header: &Header,
+		options: Options,
+		now: Time,
+	) -> Result<Option<Evidence>, Error> {
+		let height = header.signed_header.header.height.value();
+		let header_hash = header.signed_header.header.hash();
+		let trusted_height = header.trusted_height.revision_height;
+
+		for witness in &self.witnesses {
+			let light_block = match witness.light_block(height).await {
+				Ok(light_block) => light_block,
+				Err(e) => {
+					log::warn!(target: "hyperspace_cosmos", "Witness failed to provide light block {height}: {e}");
+					continue
+				},
+			};
+			if light_block.signed_header.header.hash() == header_hash {
+				continue
+			}
+
+			let kind = classify(header, &light_block);
+			log::warn!(
+				target: "hyperspace_cosmos",
+				"Found {kind:?} misbehaviour on client {client_id} at {height}: {header_hash} != {}",
+				light_block.signed_header.header.hash()
+			);
+
+			// the witness header must be verifiable by the client, bisect the witness' chain
+			// from the trusted height of the conflicting header to the conflicting height.
+			let mut verifier = SkippingVerifier::<_, H>::new(witness, options);
+			let trace = match verifier.verify_to_target(trusted_height, height, now).await {
+				Ok(trace) => trace,
+				Err(e) => {
+					log::warn!(target: "hyperspace_cosmos", "Witness failed to verify its light block {height} from {trusted_height}: {e}");
+					continue
+				},
+			};
+			let trusted = verifier.light_block(trusted_height).await?;
+			let mut supporting_headers = headers_for_trace(&trusted, &trace);
+			let witness_header = supporting_headers
+				.pop()
+				.expect("the trace ends with the target light block; qed");
+			let misbehaviour = Misbehaviour {
+				client_id: client_id.clone(),
+				header1: witness_header,
+				header2: header.clone(),
+			};
+			return Ok(Some(Evidence { kind, supporting_headers, misbehaviour }))
+		}
+
+		Ok(None)
+	}
+
+	/// Checks the client message of an `UpdateClient`, ignoring anything but tendermint headers.
+	pub async fn check_client_message(
+		&self,
+		client_id: &ClientId,
+		client_message: AnyClientMessage,
+		options: Options,
+		now: Time,
+	) -> Result<Option<Evidence>, Error> {
+		match client_message {
+			AnyClientMessage::Tendermint(ClientMessage::Header(header)) =>
+				self.check_header(client_id, &header, options, now).await,
+			_ => Ok(None),
+		}
+	}
+
+	/// Checks every update of `client_id` on the counterparty, submitting evidence of any
+	/// misbehaviour to freeze the client. Updates that can't be queried or checked are logged
+	/// and skipped. Runs until the counterparty's event stream ends.
+	pub async fn fish<C: Chain>(&self, counterparty: &C, client_id: ClientId) -> Result<(), Error> {
+		let mut client_updates = counterparty
+			.ibc_events()
+			.await
+			.map_err(|e| Error::Custom(format!("Failed to subscribe to ibc events: {e:?}")))?
+			.filter_map(|ev| {
+				ready(match ev {
+					IbcEvent::UpdateClient(update) if *update.client_id() == client_id =>
+						Some(update),
+					_ => None,
+				})
+			});
+
+		while let Some(update) = client_updates.next().await {
+			let height = update.height();
+			let evidence = async {
+				let options = client_options(counterparty, &client_id).await?;
+				let client_message = counterparty
+					.query_client_message(update)
+					.await
+					.map_err(|e| Error::Custom(format!("Failed to query client message: {e:?}")))?;
+				self.check_client_message(&client_id, client_message, options, Time::now())
+					.await
+			};
+			let evidence = match evidence.await {
+				Ok(Some(evidence)) => evidence,
+				Ok(None) => continue,
+				Err(e) => {
+					log::error!(target: "hyperspace_cosmos", "Failed to check the update of client {client_id} at {height}: {e}");
+					continue
+				},
+			};
+
+			let signer = counterparty.account_id();
+			let mut messages =
+				update_client_msgs(client_id.clone(), evidence.supporting_headers, signer.clone());
+			messages.push(misbehaviour_msg(evidence.misbehaviour, signer));
+			counterparty.submit(messages).await.map_err(|e| {
+				Error::Custom(format!("Failed to submit misbehaviour report: {e:?}"))
+			})?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Light client options of the tendermint client on the counterparty.
+async fn client_options<C: Chain>(
+	counterparty: &C,
+	client_id: &ClientId,
+) -> Result<Options, Error> {
+	let (latest_height, _) = counterparty
+		.latest_height_and_timestamp()
+		.await
+		.map_err(|e| Error::Custom(format!("Failed to query latest height: {e:?}")))?;
+	let client_state = counterparty
+		.query_client_state(latest_height, client_id.clone())
+		.await
+		.map_err(|e| Error::Custom(format!("Failed to query client state: {e:?}")))?
+		.client_state
+		.ok_or_else(|| Error::Custom(format!("Client state of {client_id} not found")))?;
+	match AnyClientState::try_from(client_state) {
+		Ok(AnyClientState::Tendermint(client_state)) => client_state
+			.as_light_client_options()
+			.map_err(|e| Error::Custom(format!("Invalid client options: {e}"))),
+		_ => Err(Error::Custom(format!("Client {client_id} isn't a tendermint client"))),
+	}
+}
+
+/// Classifies the attack behind a header conflicting with the witness' light block at the same
+/// height.
+pub fn classify(header: &Header, witness: &LightBlock) -> MisbehaviourKind {
+	let (conflicting, trusted) = (&header.signed_header.header, &witness.signed_header.header);
+	let derived_fields_match = conflicting.validators_hash == trusted.validators_hash &&
+		conflicting.next_validators_hash == trusted.next_validators_hash &&
+		conflicting.consensus_hash == trusted.consensus_hash &&
+		conflicting.app_hash == trusted.app_hash &&
+		conflicting.last_results_hash == trusted.last_results_hash;
+
+	if !derived_fields_match {
+		MisbehaviourKind::Lunatic
+	} else if header.signed_header.commit.round == witness.signed_header.commit.round {
+		MisbehaviourKind::DuplicateVote
+	} else {
+		MisbehaviourKind::Amnesia
+	}
+}
+
+/// A `MsgUpdateClient` submitting the misbehaviour, which freezes the client.
+pub fn misbehaviour_msg(misbehaviour: Misbehaviour, signer: Signer) -> Any {
+	let client_id = misbehaviour.client_id.clone();
+	MsgUpdateAnyClient::<LocalClientTypes>::new(
+		client_id,
+		AnyClientMessage::Tendermint(ClientMessage::Misbehaviour(misbehaviour)),
+		signer,
+	)
+	.to_any()
+}
@@ -11,3 +11,13 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+//! Hyperspace support for Cosmos chains tracked with the ICS-07 tendermint light client.
+
+pub mod bisection;
+pub mod error;
+pub mod fisherman;
+pub mod provider;
+
+#[cfg(test)]
+mod tests;
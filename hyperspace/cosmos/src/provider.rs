@@ -0,0 +1,80 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::Error;
+use async_trait::async_trait;
+use tendermint::{
+	block::Height as BlockHeight, node::Id as PeerId, validator::Set as ValidatorSet,
+};
+use tendermint_light_client_verifier::types::LightBlock;
+use tendermint_rpc::{Client, HttpClient, Paging};
+
+/// Source of the light blocks of a tendermint chain.
+#[async_trait]
+pub trait LightBlockProvider: Send + Sync {
+	/// Light block at the given height, with the validator sets of the block and its child.
+	async fn light_block(&self, height: u64) -> Result<LightBlock, Error>;
+}
+
+#[async_trait]
+impl<P: LightBlockProvider> LightBlockProvider for &P {
+	async fn light_block(&self, height: u64) -> Result<LightBlock, Error> {
+		(**self).light_block(height).await
+	}
+}
+
+/// [`LightBlockProvider`] backed by the RPC endpoint of a full node.
+pub struct RpcLightBlockProvider {
+	/// Tendermint RPC client
+	pub client: HttpClient,
+	/// Id of the full node, recorded as the provider of its light blocks
+	pub peer_id: PeerId,
+}
+
+impl RpcLightBlockProvider {
+	/// Connects to the full node at the given RPC url.
+	pub async fn new(rpc_url: &str) -> Result<Self, Error> {
+		let client = HttpClient::new(rpc_url)?;
+		let peer_id = client.status().await?.node_info.id;
+		Ok(Self { client, peer_id })
+	}
+
+	async fn validator_set(
+		&self,
+		height: u64,
+		proposer_address: Option<tendermint::account::Id>,
+	) -> Result<ValidatorSet, Error> {
+		let validators = self
+			.client
+			.validators(BlockHeight::try_from(height)?, Paging::All)
+			.await?
+			.validators;
+		let proposer = proposer_address.and_then(|address| {
+			validators.iter().find(|validator| validator.address == address).cloned()
+		});
+		Ok(ValidatorSet::new(validators, proposer))
+	}
+}
+
+#[async_trait]
+impl LightBlockProvider for RpcLightBlockProvider {
+	async fn light_block(&self, height: u64) -> Result<LightBlock, Error> {
+		let signed_header = self.client.commit(BlockHeight::try_from(height)?).await?.signed_header;
+		let validators =
+			self.validator_set(height, Some(signed_header.header.proposer_address)).await?;
+		let next_validators = self.validator_set(height + 1, None).await?;
+
+		Ok(LightBlock::new(signed_header, validators, next_validators, self.peer_id))
+	}
+}
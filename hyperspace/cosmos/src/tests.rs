@@ -0,0 +1,242 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	bisection::{headers_for_trace, SkippingVerifier},
+	error::Error,
+	fisherman::{Fisherman, MisbehaviourKind},
+	provider::LightBlockProvider,
+};
+use ibc::{core::ics24_host::identifier::ClientId, Height};
+use ics07_tendermint::client_message::Header as Ics07Header;
+use pallet_ibc::light_clients::HostFunctionsManager;
+use std::{collections::BTreeMap, time::Duration};
+use tendermint::Time;
+use tendermint_light_client_verifier::{
+	options::Options,
+	types::{LightBlock, TrustThreshold},
+};
+use tendermint_testgen::{Commit, Generator, Header, LightBlock as TestgenLightBlock, Validator};
+
+const CHAIN_ID: &str = "test-chain-1";
+
+struct MockProvider {
+	light_blocks: BTreeMap<u64, LightBlock>,
+}
+
+#[async_trait::async_trait]
+impl LightBlockProvider for MockProvider {
+	async fn light_block(&self, height: u64) -> Result<LightBlock, Error> {
+		self.light_blocks
+			.get(&height)
+			.cloned()
+			.ok_or_else(|| Error::Custom(format!("No light block at {height}")))
+	}
+}
+
+fn header_time(height: u64) -> Time {
+	Time::from_unix_timestamp(1_600_000_000 + height as i64, 0).unwrap()
+}
+
+fn mock_light_block(
+	height: u64,
+	validators: &[Validator],
+	next_validators: &[Validator],
+	time: Time,
+	round: u32,
+) -> LightBlock {
+	let header = Header::new(validators)
+		.next_validators(next_validators)
+		.height(height)
+		.chain_id(CHAIN_ID)
+		.time(time);
+	let commit = Commit::new(header.clone(), round);
+	let block = TestgenLightBlock::new(header, commit).generate().unwrap();
+	LightBlock::new(block.signed_header, block.validators, block.next_validators, block.provider)
+}
+
+/// Light blocks for heights `1..=heights`, signed by the validator set returned for each height.
+fn mock_provider(heights: u64, validators: impl Fn(u64) -> Vec<Validator>) -> MockProvider {
+	let light_blocks = (1..=heights)
+		.map(|height| {
+			let light_block = mock_light_block(
+				height,
+				&validators(height),
+				&validators(height + 1),
+				header_time(height),
+				1,
+			);
+			(height, light_block)
+		})
+		.collect();
+	MockProvider { light_blocks }
+}
+
+fn options() -> Options {
+	Options {
+		trust_threshold: TrustThreshold::ONE_THIRD,
+		trusting_period: Duration::from_secs(60 * 60),
+		clock_drift: Duration::from_secs(5),
+	}
+}
+
+fn verifier(provider: MockProvider) -> SkippingVerifier<MockProvider, HostFunctionsManager> {
+	SkippingVerifier::new(provider, options())
+}
+
+fn fisherman(witness: MockProvider) -> Fisherman<MockProvider, HostFunctionsManager> {
+	Fisherman::new(vec![witness])
+}
+
+fn validator_set(names: &[&str]) -> Vec<Validator> {
+	names.iter().map(|name| Validator::new(name).voting_power(50)).collect()
+}
+
+#[tokio::test]
+async fn skips_to_target_with_unchanged_validators() {
+	let provider = mock_provider(8, |_| validator_set(&["a", "b", "c"]));
+	let mut verifier = verifier(provider);
+
+	let trace = verifier.verify_to_target(1, 8, header_time(9)).await.unwrap();
+	let heights = trace.iter().map(|block| block.height().value()).collect::<Vec<_>>();
+	assert_eq!(heights, vec![8]);
+}
+
+#[tokio::test]
+async fn bisects_across_validator_set_changes() {
+	let provider = mock_provider(8, |height| {
+		if height <= 4 {
+			validator_set(&["a", "b", "c"])
+		} else {
+			validator_set(&["d", "e", "f"])
+		}
+	});
+	let mut verifier = verifier(provider);
+
+	let trace = verifier.verify_to_target(1, 8, header_time(9)).await.unwrap();
+	let heights = trace.iter().map(|block| block.height().value()).collect::<Vec<_>>();
+	assert_eq!(heights, vec![4, 8]);
+
+	let trusted = verifier.light_block(1).await.unwrap();
+	let headers = headers_for_trace(&trusted, &trace);
+	assert_eq!(headers.len(), 2);
+	assert_eq!(headers[0].trusted_height.revision_height, 1);
+	assert_eq!(headers[1].trusted_height.revision_height, 4);
+	assert_eq!(headers[1].trusted_validator_set, trace[0].next_validators);
+}
+
+#[tokio::test]
+async fn rejects_target_below_trusted_height() {
+	let provider = mock_provider(4, |_| validator_set(&["a", "b", "c"]));
+	let mut verifier = verifier(provider);
+
+	assert!(verifier.verify_to_target(4, 4, header_time(5)).await.is_err());
+	assert!(verifier.verify_to_target(4, 2, header_time(5)).await.is_err());
+}
+
+/// An ICS-07 header for the light block, trusting the given light block.
+fn ics07_header(light_block: LightBlock, trusted: &LightBlock) -> Ics07Header {
+	Ics07Header {
+		signed_header: light_block.signed_header,
+		validator_set: light_block.validators,
+		trusted_height: Height::new(1, trusted.height().value()),
+		trusted_validator_set: trusted.next_validators.clone(),
+	}
+}
+
+#[tokio::test]
+async fn fisherman_accepts_headers_matching_witnesses() {
+	let validators = validator_set(&["a", "b", "c"]);
+	let witness = mock_provider(4, |_| validators.clone());
+	let trusted = witness.light_block(1).await.unwrap();
+	let header = ics07_header(witness.light_block(4).await.unwrap(), &trusted);
+	let fisherman = fisherman(witness);
+
+	let client_id = ClientId::new("07-tendermint", 0).unwrap();
+	let evidence = fisherman.check_header(&client_id, &header, options(), header_time(5));
+	assert!(evidence.await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn fisherman_detects_conflicting_headers() {
+	let validators = validator_set(&["a", "b", "c"]);
+	let client_id = ClientId::new("07-tendermint", 0).unwrap();
+	let witness = mock_provider(4, |_| validators.clone());
+	let trusted = witness.light_block(1).await.unwrap();
+	let fisherman = fisherman(witness);
+
+	// same validators signing another block in the same round
+	let conflicting = mock_light_block(4, &validators, &validators, header_time(5), 1);
+	let evidence = fisherman
+		.check_header(&client_id, &ics07_header(conflicting, &trusted), options(), header_time(5))
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(evidence.kind, MisbehaviourKind::DuplicateVote);
+	assert_eq!(evidence.misbehaviour.client_id, client_id);
+
+	// same validators signing another block in a later round
+	let conflicting = mock_light_block(4, &validators, &validators, header_time(5), 2);
+	let evidence = fisherman
+		.check_header(&client_id, &ics07_header(conflicting, &trusted), options(), header_time(5))
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(evidence.kind, MisbehaviourKind::Amnesia);
+
+	// a validator set the chain never had
+	let forged = validator_set(&["d", "e", "f"]);
+	let conflicting = mock_light_block(4, &forged, &forged, header_time(4), 1);
+	let evidence = fisherman
+		.check_header(&client_id, &ics07_header(conflicting, &trusted), options(), header_time(5))
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(evidence.kind, MisbehaviourKind::Lunatic);
+	assert!(evidence.supporting_headers.is_empty());
+	assert_eq!(
+		evidence.misbehaviour.header1.trusted_height,
+		evidence.misbehaviour.header2.trusted_height
+	);
+}
+
+#[tokio::test]
+async fn fisherman_builds_witness_header_from_its_own_trace() {
+	let witness = mock_provider(8, |height| {
+		if height <= 4 {
+			validator_set(&["a", "b", "c"])
+		} else {
+			validator_set(&["d", "e", "f"])
+		}
+	});
+	let client_id = ClientId::new("07-tendermint", 0).unwrap();
+	let trusted = witness.light_block(1).await.unwrap();
+	let fisherman = fisherman(witness);
+
+	// the original validators forking off after handing over to a new set
+	let validators = validator_set(&["a", "b", "c"]);
+	let conflicting = mock_light_block(8, &validators, &validators, header_time(8), 1);
+	let evidence = fisherman
+		.check_header(&client_id, &ics07_header(conflicting, &trusted), options(), header_time(9))
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(evidence.kind, MisbehaviourKind::Lunatic);
+
+	// the witness header can't be verified from height 1, the client is first updated to 4
+	assert_eq!(evidence.supporting_headers.len(), 1);
+	assert_eq!(evidence.supporting_headers[0].trusted_height.revision_height, 1);
+	assert_eq!(evidence.misbehaviour.header1.trusted_height.revision_height, 4);
+	assert_eq!(evidence.misbehaviour.header2.trusted_height.revision_height, 1);
+}
@@ -0,0 +1,383 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Client of the light client endpoints of the beacon node API, and conversions of their responses
+//! to the types of the ethereum light client.
+
+use crate::{error::Error, HostFunctionsManager};
+use ics_ethereum::{
+	beacon::{
+		BeaconBlockHeader, ExecutionPayloadProof, Fork, ForkParameters, SyncAggregate,
+		SyncCommittee,
+	},
+	client_message::Header,
+	ssz::{self, Root},
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::collections::HashMap;
+
+/// Most light client updates the beacon API returns in a single request.
+pub const MAX_REQUEST_LIGHT_CLIENT_UPDATES: u64 = 128;
+
+/// Indices of the execution payload fields proven by the light client.
+const STATE_ROOT_INDEX: usize = 2;
+const BLOCK_NUMBER_INDEX: usize = 6;
+const TIMESTAMP_INDEX: usize = 9;
+
+/// Response of the beacon API, wrapping its data.
+#[derive(Deserialize)]
+struct Response<T> {
+	data: T,
+}
+
+/// Light client update of the beacon API, tagged with its fork.
+#[derive(Deserialize)]
+struct VersionedUpdate {
+	data: LightClientUpdate,
+}
+
+#[derive(Deserialize)]
+pub struct Genesis {
+	pub genesis_time: String,
+	pub genesis_validators_root: String,
+}
+
+#[derive(Deserialize)]
+struct FinalizedHeader {
+	root: String,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct BeaconHeaderJson {
+	pub slot: String,
+	pub proposer_index: String,
+	pub parent_root: String,
+	pub state_root: String,
+	pub body_root: String,
+}
+
+/// Execution payload header, the blob fields are only present from Deneb.
+#[derive(Clone, Deserialize)]
+pub struct ExecutionPayloadHeaderJson {
+	pub parent_hash: String,
+	pub fee_recipient: String,
+	pub state_root: String,
+	pub receipts_root: String,
+	pub logs_bloom: String,
+	pub prev_randao: String,
+	pub block_number: String,
+	pub gas_limit: String,
+	pub gas_used: String,
+	pub timestamp: String,
+	pub extra_data: String,
+	pub base_fee_per_gas: String,
+	pub block_hash: String,
+	pub transactions_root: String,
+	pub withdrawals_root: String,
+	pub blob_gas_used: Option<String>,
+	pub excess_blob_gas: Option<String>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct LightClientHeaderJson {
+	pub beacon: BeaconHeaderJson,
+	pub execution: ExecutionPayloadHeaderJson,
+	pub execution_branch: Vec<String>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SyncCommitteeJson {
+	pub pubkeys: Vec<String>,
+	pub aggregate_pubkey: String,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SyncAggregateJson {
+	pub sync_committee_bits: String,
+	pub sync_committee_signature: String,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct LightClientBootstrap {
+	pub header: LightClientHeaderJson,
+	pub current_sync_committee: SyncCommitteeJson,
+}
+
+/// Light client update or finality update, finality updates don't carry a next sync committee.
+#[derive(Clone, Deserialize)]
+pub struct LightClientUpdate {
+	pub attested_header: LightClientHeaderJson,
+	pub next_sync_committee: Option<SyncCommitteeJson>,
+	#[serde(default)]
+	pub next_sync_committee_branch: Vec<String>,
+	pub finalized_header: LightClientHeaderJson,
+	pub finality_branch: Vec<String>,
+	pub sync_aggregate: SyncAggregateJson,
+	pub signature_slot: String,
+}
+
+/// Client of a beacon node API.
+#[derive(Clone)]
+pub struct BeaconApi {
+	client: reqwest::Client,
+	url: String,
+}
+
+impl BeaconApi {
+	pub fn new(url: &str) -> Self {
+		Self { client: reqwest::Client::new(), url: url.trim_end_matches('/').to_string() }
+	}
+
+	async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+		let response = self.client.get(format!("{}{path}", self.url)).send().await?;
+		Ok(response.error_for_status()?.json().await?)
+	}
+
+	pub async fn genesis(&self) -> Result<Genesis, Error> {
+		Ok(self.get::<Response<Genesis>>("/eth/v1/beacon/genesis").await?.data)
+	}
+
+	/// Fork versions and epochs from the chain spec, forks missing from the spec are unscheduled.
+	pub async fn fork_parameters(&self) -> Result<ForkParameters, Error> {
+		let spec = self.get::<Response<HashMap<String, String>>>("/eth/v1/config/spec").await?.data;
+		let version = |name: &str| -> Result<[u8; 4], Error> {
+			let version = spec
+				.get(&format!("{name}_FORK_VERSION"))
+				.ok_or_else(|| Error::Custom(format!("Missing {name} fork version in the spec")))?;
+			fixed_bytes(version)
+		};
+		let fork = |name: &str| -> Result<Fork, Error> {
+			let epoch = match spec.get(&format!("{name}_FORK_EPOCH")) {
+				Some(epoch) => number(epoch)?,
+				None => return Ok(Fork { version: [0; 4], epoch: u64::MAX }),
+			};
+			Ok(Fork { version: version(name)?, epoch })
+		};
+		Ok(ForkParameters {
+			genesis_fork_version: version("GENESIS")?,
+			altair: fork("ALTAIR")?,
+			bellatrix: fork("BELLATRIX")?,
+			capella: fork("CAPELLA")?,
+			deneb: fork("DENEB")?,
+			electra: fork("ELECTRA")?,
+		})
+	}
+
+	/// Root of the latest finalized beacon block.
+	pub async fn finalized_block_root(&self) -> Result<Root, Error> {
+		let header = self
+			.get::<Response<FinalizedHeader>>("/eth/v1/beacon/headers/finalized")
+			.await?
+			.data;
+		fixed_bytes(&header.root)
+	}
+
+	pub async fn bootstrap(&self, block_root: &Root) -> Result<LightClientBootstrap, Error> {
+		let path = format!("/eth/v1/beacon/light_client/bootstrap/0x{}", hex::encode(block_root));
+		Ok(self.get::<Response<LightClientBootstrap>>(&path).await?.data)
+	}
+
+	/// Best light client updates of `count` sync committee periods from `start_period`.
+	pub async fn updates(
+		&self,
+		start_period: u64,
+		count: u64,
+	) -> Result<Vec<LightClientUpdate>, Error> {
+		let path = format!(
+			"/eth/v1/beacon/light_client/updates?start_period={start_period}&count={}",
+			count.min(MAX_REQUEST_LIGHT_CLIENT_UPDATES)
+		);
+		let updates = self.get::<Vec<VersionedUpdate>>(&path).await?;
+		Ok(updates.into_iter().map(|update| update.data).collect())
+	}
+
+	/// Update finalizing the latest finalized header.
+	pub async fn finality_update(&self) -> Result<LightClientUpdate, Error> {
+		let path = "/eth/v1/beacon/light_client/finality_update";
+		Ok(self.get::<Response<LightClientUpdate>>(path).await?.data)
+	}
+}
+
+pub fn number(value: &str) -> Result<u64, Error> {
+	value.parse().map_err(|e| Error::Custom(format!("Invalid number {value}: {e}")))
+}
+
+pub fn bytes(value: &str) -> Result<Vec<u8>, Error> {
+	Ok(hex::decode(value.trim_start_matches("0x"))?)
+}
+
+pub fn fixed_bytes<const N: usize>(value: &str) -> Result<[u8; N], Error> {
+	let bytes = bytes(value)?;
+	let len = bytes.len();
+	bytes
+		.try_into()
+		.map_err(|_| Error::Custom(format!("Invalid length of {value}: expected {N}, got {len}")))
+}
+
+fn branch(branch: &[String]) -> Result<Vec<Root>, Error> {
+	branch.iter().map(|node| fixed_bytes(node)).collect()
+}
+
+/// Chunk of a byte vector of up to 32 bytes, right padded with zeros.
+fn chunk(value: &str) -> Result<Root, Error> {
+	let bytes = bytes(value)?;
+	if bytes.len() > 32 {
+		Err(Error::Custom(format!("Invalid length of {value}: expected at most 32")))?
+	}
+	let mut chunk = Root::default();
+	chunk[..bytes.len()].copy_from_slice(&bytes);
+	Ok(chunk)
+}
+
+impl TryFrom<&BeaconHeaderJson> for BeaconBlockHeader {
+	type Error = Error;
+
+	fn try_from(header: &BeaconHeaderJson) -> Result<Self, Error> {
+		Ok(Self {
+			slot: number(&header.slot)?,
+			proposer_index: number(&header.proposer_index)?,
+			parent_root: fixed_bytes(&header.parent_root)?,
+			state_root: fixed_bytes(&header.state_root)?,
+			body_root: fixed_bytes(&header.body_root)?,
+		})
+	}
+}
+
+impl TryFrom<&SyncCommitteeJson> for SyncCommittee {
+	type Error = Error;
+
+	fn try_from(committee: &SyncCommitteeJson) -> Result<Self, Error> {
+		Ok(Self {
+			pubkeys: committee
+				.pubkeys
+				.iter()
+				.map(|key| fixed_bytes(key))
+				.collect::<Result<_, _>>()?,
+			aggregate_pubkey: fixed_bytes(&committee.aggregate_pubkey)?,
+		})
+	}
+}
+
+impl ExecutionPayloadHeaderJson {
+	/// Hash tree roots of the fields of the execution payload header.
+	pub(crate) fn field_roots(&self) -> Result<Vec<Root>, Error> {
+		// `base_fee_per_gas` is a uint256, base fees never get close to the range of a u128
+		let base_fee = self.base_fee_per_gas.parse::<u128>().map_err(|e| {
+			Error::Custom(format!("Invalid base fee {}: {e}", self.base_fee_per_gas))
+		})?;
+		let mut base_fee_root = Root::default();
+		base_fee_root[..16].copy_from_slice(&base_fee.to_le_bytes());
+		// `extra_data` is a byte list of up to 32 bytes, merkleized in a single chunk
+		let extra_data = bytes(&self.extra_data)?;
+		let extra_data_root = ssz::hash::<HostFunctionsManager>(
+			&chunk(&self.extra_data)?,
+			&ssz::uint64_root(extra_data.len() as u64),
+		);
+
+		let mut roots = vec![
+			fixed_bytes(&self.parent_hash)?,
+			chunk(&self.fee_recipient)?,
+			fixed_bytes(&self.state_root)?,
+			fixed_bytes(&self.receipts_root)?,
+			ssz::bytes_root::<HostFunctionsManager>(&bytes(&self.logs_bloom)?),
+			fixed_bytes(&self.prev_randao)?,
+			ssz::uint64_root(number(&self.block_number)?),
+			ssz::uint64_root(number(&self.gas_limit)?),
+			ssz::uint64_root(number(&self.gas_used)?),
+			ssz::uint64_root(number(&self.timestamp)?),
+			extra_data_root,
+			base_fee_root,
+			fixed_bytes(&self.block_hash)?,
+			fixed_bytes(&self.transactions_root)?,
+			fixed_bytes(&self.withdrawals_root)?,
+		];
+		if let (Some(blob_gas_used), Some(excess_blob_gas)) =
+			(&self.blob_gas_used, &self.excess_blob_gas)
+		{
+			roots.push(ssz::uint64_root(number(blob_gas_used)?));
+			roots.push(ssz::uint64_root(number(excess_blob_gas)?));
+		}
+		Ok(roots)
+	}
+}
+
+/// Layers of the merkle tree of the given leaves padded to the next power of two, from the leaves
+/// to the root.
+pub(crate) fn merkle_tree(mut leaves: Vec<Root>) -> Vec<Vec<Root>> {
+	leaves.resize(leaves.len().next_power_of_two(), Root::default());
+	let mut layers = vec![leaves];
+	while layers[layers.len() - 1].len() > 1 {
+		let layer = layers[layers.len() - 1]
+			.chunks(2)
+			.map(|pair| ssz::hash::<HostFunctionsManager>(&pair[0], &pair[1]))
+			.collect();
+		layers.push(layer);
+	}
+	layers
+}
+
+pub(crate) fn merkle_branch(layers: &[Vec<Root>], mut index: usize) -> Vec<Root> {
+	let mut branch = vec![];
+	for layer in &layers[..layers.len() - 1] {
+		branch.push(layer[index ^ 1]);
+		index /= 2;
+	}
+	branch
+}
+
+impl TryFrom<&LightClientHeaderJson> for ExecutionPayloadProof {
+	type Error = Error;
+
+	/// Proves the execution payload fields tracked by the light client, the API only proves the
+	/// payload root against the block body.
+	fn try_from(header: &LightClientHeaderJson) -> Result<Self, Error> {
+		let execution = &header.execution;
+		let tree = merkle_tree(execution.field_roots()?);
+		Ok(Self {
+			state_root: fixed_bytes(&execution.state_root)?,
+			state_root_branch: merkle_branch(&tree, STATE_ROOT_INDEX),
+			block_number: number(&execution.block_number)?,
+			block_number_branch: merkle_branch(&tree, BLOCK_NUMBER_INDEX),
+			timestamp: number(&execution.timestamp)?,
+			timestamp_branch: merkle_branch(&tree, TIMESTAMP_INDEX),
+			execution_branch: branch(&header.execution_branch)?,
+		})
+	}
+}
+
+impl TryFrom<&LightClientUpdate> for Header {
+	type Error = Error;
+
+	fn try_from(update: &LightClientUpdate) -> Result<Self, Error> {
+		Ok(Self {
+			attested_header: (&update.attested_header.beacon).try_into()?,
+			next_sync_committee: update
+				.next_sync_committee
+				.as_ref()
+				.map(TryInto::try_into)
+				.transpose()?,
+			next_sync_committee_branch: branch(&update.next_sync_committee_branch)?,
+			finalized_header: (&update.finalized_header.beacon).try_into()?,
+			finality_branch: branch(&update.finality_branch)?,
+			finalized_execution: (&update.finalized_header).try_into()?,
+			sync_aggregate: SyncAggregate {
+				sync_committee_bits: bytes(&update.sync_aggregate.sync_committee_bits)?,
+				sync_committee_signature: fixed_bytes(
+					&update.sync_aggregate.sync_committee_signature,
+				)?,
+			},
+			signature_slot: number(&update.signature_slot)?,
+		})
+	}
+}
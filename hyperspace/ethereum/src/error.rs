@@ -0,0 +1,50 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use thiserror::Error;
+
+/// Error definition for the Ethereum client
+#[derive(Error, Debug)]
+pub enum Error {
+	/// HTTP error of the beacon API or the execution RPC
+	#[error("HTTP error: {0}")]
+	Http(#[from] reqwest::Error),
+	/// Invalid JSON response
+	#[error("JSON error: {0}")]
+	Json(#[from] serde_json::Error),
+	/// Invalid hex string in a response
+	#[error("Hex error: {0}")]
+	Hex(#[from] hex::FromHexError),
+	/// Error of the execution RPC
+	#[error("Execution RPC error {code}: {message}")]
+	Rpc { code: i64, message: String },
+	/// A response can't be converted to the types of the light client
+	#[error("Light client error: {0}")]
+	LightClient(String),
+	/// Custom error
+	#[error("{0}")]
+	Custom(String),
+}
+
+impl From<ics_ethereum::error::Error> for Error {
+	fn from(error: ics_ethereum::error::Error) -> Self {
+		Self::LightClient(error.to_string())
+	}
+}
+
+impl From<String> for Error {
+	fn from(error: String) -> Self {
+		Self::Custom(error)
+	}
+}
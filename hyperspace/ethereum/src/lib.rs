@@ -0,0 +1,57 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hyperspace support for Ethereum chains tracked with the ethereum sync committee light client.
+
+pub mod beacon;
+pub mod error;
+pub mod provider;
+
+#[cfg(test)]
+mod tests;
+
+use milagro_bls::{AggregateSignature, PublicKey};
+use sha2::Digest;
+
+/// Native implementation of the host functions of the ethereum light client.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HostFunctionsManager;
+
+impl ics_ethereum::HostFunctions for HostFunctionsManager {
+	fn sha256(message: &[u8]) -> [u8; 32] {
+		sha2::Sha256::digest(message).into()
+	}
+
+	fn keccak_256(message: &[u8]) -> [u8; 32] {
+		sha3::Keccak256::digest(message).into()
+	}
+
+	fn bls_fast_aggregate_verify(
+		public_keys: &[[u8; 48]],
+		message: &[u8; 32],
+		signature: &[u8; 96],
+	) -> bool {
+		let public_keys = match public_keys
+			.iter()
+			.map(|key| PublicKey::from_bytes(key))
+			.collect::<Result<Vec<_>, _>>()
+		{
+			Ok(keys) => keys,
+			Err(_) => return false,
+		};
+		let public_keys = public_keys.iter().collect::<Vec<_>>();
+		AggregateSignature::from_bytes(signature)
+			.map_or(false, |signature| signature.fast_aggregate_verify(message, &public_keys))
+	}
+}
@@ -0,0 +1,238 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Relayer backend of an ethereum chain, reading light client updates from a beacon node and
+//! storage proofs of the IBC handler from an execution node.
+
+use crate::{
+	beacon::{self, BeaconApi, LightClientUpdate},
+	error::Error,
+	HostFunctionsManager,
+};
+use ibc::core::{ics23_commitment::commitment::CommitmentPrefix, ics24_host::Path};
+use ics_ethereum::{
+	beacon::compute_sync_committee_period_at_slot, client_def::commitment_slot,
+	client_message::Header, client_state::ClientState, consensus_state::ConsensusState,
+	proto::StorageProof, ssz::Root,
+};
+use prost::Message;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Configuration of an ethereum chain.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EthereumClientConfig {
+	/// Url of the beacon node API
+	pub beacon_url: String,
+	/// Url of the JSON-RPC endpoint of the execution node
+	pub execution_url: String,
+	/// EIP-155 chain id of the execution chain
+	pub chain_id: u64,
+	/// Address of the IBC handler contract, hex encoded
+	pub ibc_handler_address: String,
+	/// Storage slot of the IBC handler's commitments mapping, hex encoded
+	pub ibc_commitments_slot: String,
+	/// Trusting period of the light client in seconds
+	pub trusting_period_secs: u64,
+}
+
+/// Client of an ethereum chain.
+#[derive(Clone)]
+pub struct EthereumClient {
+	/// Beacon node API
+	pub beacon: BeaconApi,
+	/// HTTP client of the execution node
+	pub execution: reqwest::Client,
+	/// Chain configuration
+	pub config: EthereumClientConfig,
+	ibc_handler_address: [u8; 20],
+	ibc_commitments_slot: Root,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+	code: i64,
+	message: String,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+	result: Option<T>,
+	error: Option<RpcError>,
+}
+
+/// Response of `eth_getProof`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountProof {
+	account_proof: Vec<String>,
+	storage_proof: Vec<StorageSlotProof>,
+}
+
+#[derive(Deserialize)]
+struct StorageSlotProof {
+	proof: Vec<String>,
+}
+
+impl EthereumClient {
+	pub fn new(config: EthereumClientConfig) -> Result<Self, Error> {
+		Ok(Self {
+			beacon: BeaconApi::new(&config.beacon_url),
+			execution: reqwest::Client::new(),
+			ibc_handler_address: beacon::fixed_bytes(&config.ibc_handler_address)?,
+			ibc_commitments_slot: beacon::fixed_bytes(&config.ibc_commitments_slot)?,
+			config,
+		})
+	}
+
+	/// Client and consensus state of a new light client, trusting the sync committee of the latest
+	/// finalized block. The next sync committee is learned from the first update.
+	pub async fn initialize_client_state(
+		&self,
+	) -> Result<(ClientState<HostFunctionsManager>, ConsensusState), Error> {
+		let genesis = self.beacon.genesis().await?;
+		let fork_parameters = self.beacon.fork_parameters().await?;
+		let block_root = self.beacon.finalized_block_root().await?;
+		let bootstrap = self.beacon.bootstrap(&block_root).await?;
+		let slot = beacon::number(&bootstrap.header.beacon.slot)?;
+		let execution = &bootstrap.header.execution;
+
+		let client_state = ClientState {
+			chain_id: self.config.chain_id,
+			genesis_validators_root: beacon::fixed_bytes(&genesis.genesis_validators_root)?,
+			genesis_time: beacon::number(&genesis.genesis_time)?,
+			fork_parameters,
+			trusting_period: Duration::from_secs(self.config.trusting_period_secs),
+			ibc_handler_address: self.ibc_handler_address,
+			ibc_commitments_slot: self.ibc_commitments_slot,
+			latest_slot: slot,
+			latest_execution_block_number: beacon::number(&execution.block_number)?,
+			current_sync_committee: (&bootstrap.current_sync_committee).try_into()?,
+			next_sync_committee: None,
+			frozen_height: None,
+			_phantom: Default::default(),
+		};
+		let consensus_state = ConsensusState::new(
+			beacon::fixed_bytes(&execution.state_root)?,
+			beacon::number(&execution.timestamp)?,
+			slot,
+		)?;
+		Ok((client_state, consensus_state))
+	}
+
+	/// Light client updates taking the client from its latest slot to the latest finalized block,
+	/// in the order they must be submitted: the best update of every sync committee period the
+	/// client has to go through, then the latest finality update.
+	pub async fn query_client_updates(
+		&self,
+		client_state: &ClientState<HostFunctionsManager>,
+	) -> Result<Vec<Header>, Error> {
+		let finality_update = self.beacon.finality_update().await?;
+		let store_period = compute_sync_committee_period_at_slot(client_state.latest_slot);
+		let current_period = compute_sync_committee_period_at_slot(beacon::number(
+			&finality_update.attested_header.beacon.slot,
+		)?);
+
+		let mut updates =
+			if current_period > store_period || client_state.next_sync_committee.is_none() {
+				self.beacon.updates(store_period, current_period - store_period + 1).await?
+			} else {
+				vec![]
+			};
+		updates.push(finality_update);
+
+		let mut latest_slot = client_state.latest_slot;
+		let mut headers = vec![];
+		for update in updates {
+			let finalized_slot = finalized_slot(&update)?;
+			if finalized_slot <= latest_slot {
+				log::debug!(
+					target: "hyperspace_ethereum",
+					"Skipping light client update finalizing slot {finalized_slot}, the client is at slot {latest_slot}"
+				);
+				continue
+			}
+			latest_slot = finalized_slot;
+			headers.push(Header::try_from(&update)?);
+		}
+		Ok(headers)
+	}
+
+	/// Proof of the IBC handler account and of the commitment of `path` in its storage at the given
+	/// execution block, encoded for the light client.
+	pub async fn query_storage_proof(
+		&self,
+		client_state: &ClientState<HostFunctionsManager>,
+		prefix: &CommitmentPrefix,
+		path: Path,
+		block_number: u64,
+	) -> Result<Vec<u8>, Error> {
+		let slot = commitment_slot(client_state, prefix, path);
+		let proof: AccountProof = self
+			.execution_request(
+				"eth_getProof",
+				serde_json::json!([
+					format!("0x{}", hex::encode(self.ibc_handler_address)),
+					[format!("0x{}", hex::encode(slot))],
+					format!("0x{block_number:x}"),
+				]),
+			)
+			.await?;
+		let storage_proof = proof
+			.storage_proof
+			.into_iter()
+			.next()
+			.ok_or_else(|| Error::Custom("eth_getProof returned no storage proof".into()))?;
+
+		let decode = |nodes: Vec<String>| -> Result<Vec<Vec<u8>>, Error> {
+			nodes.iter().map(|node| beacon::bytes(node)).collect()
+		};
+		Ok(StorageProof {
+			account_proof: decode(proof.account_proof)?,
+			storage_proof: decode(storage_proof.proof)?,
+		}
+		.encode_to_vec())
+	}
+
+	async fn execution_request<T: serde::de::DeserializeOwned>(
+		&self,
+		method: &str,
+		params: serde_json::Value,
+	) -> Result<T, Error> {
+		let request = serde_json::json!({
+			"jsonrpc": "2.0",
+			"id": 1,
+			"method": method,
+			"params": params,
+		});
+		let response: RpcResponse<T> = self
+			.execution
+			.post(&self.config.execution_url)
+			.json(&request)
+			.send()
+			.await?
+			.error_for_status()?
+			.json()
+			.await?;
+		match (response.result, response.error) {
+			(_, Some(RpcError { code, message })) => Err(Error::Rpc { code, message }),
+			(Some(result), None) => Ok(result),
+			(None, None) => Err(Error::Custom(format!("{method} returned no result"))),
+		}
+	}
+}
+
+fn finalized_slot(update: &LightClientUpdate) -> Result<u64, Error> {
+	beacon::number(&update.finalized_header.beacon.slot)
+}
@@ -0,0 +1,97 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	beacon::{
+		merkle_branch, merkle_tree, BeaconHeaderJson, ExecutionPayloadHeaderJson,
+		LightClientHeaderJson,
+	},
+	HostFunctionsManager,
+};
+use ics_ethereum::beacon::{BeaconBlockHeader, ExecutionPayloadProof, Fork, ForkParameters};
+
+fn hex32(byte: u8) -> String {
+	format!("0x{}", hex::encode([byte; 32]))
+}
+
+fn execution_header(deneb: bool) -> ExecutionPayloadHeaderJson {
+	ExecutionPayloadHeaderJson {
+		parent_hash: hex32(1),
+		fee_recipient: format!("0x{}", hex::encode([2u8; 20])),
+		state_root: hex32(3),
+		receipts_root: hex32(4),
+		logs_bloom: format!("0x{}", hex::encode([5u8; 256])),
+		prev_randao: hex32(6),
+		block_number: "19000000".into(),
+		gas_limit: "30000000".into(),
+		gas_used: "12000000".into(),
+		timestamp: "1700000000".into(),
+		extra_data: "0x6265617665726275696c642e6f7267".into(),
+		base_fee_per_gas: "25000000000".into(),
+		block_hash: hex32(7),
+		transactions_root: hex32(8),
+		withdrawals_root: hex32(9),
+		blob_gas_used: deneb.then(|| "131072".into()),
+		excess_blob_gas: deneb.then(|| "0".into()),
+	}
+}
+
+/// Converts a light client header at a slot of the given fork and verifies the execution payload
+/// proof against a block body holding the payload.
+fn verify_execution_proof(deneb: bool) {
+	let fork = |version: u8, epoch| Fork { version: [version, 0, 0, 0], epoch };
+	let fork_parameters = ForkParameters {
+		genesis_fork_version: [0; 4],
+		altair: fork(1, 0),
+		bellatrix: fork(2, 0),
+		capella: fork(3, 0),
+		deneb: fork(4, if deneb { 0 } else { u64::MAX }),
+		electra: fork(5, u64::MAX),
+	};
+	let execution = execution_header(deneb);
+	let payload_root = merkle_tree(execution.field_roots().unwrap()).last().unwrap()[0];
+	let mut body = vec![[0u8; 32]; 16];
+	body[9] = payload_root;
+	let body = merkle_tree(body);
+	let header = LightClientHeaderJson {
+		beacon: BeaconHeaderJson {
+			slot: "8000000".into(),
+			proposer_index: "1".into(),
+			parent_root: hex32(10),
+			state_root: hex32(11),
+			body_root: format!("0x{}", hex::encode(body.last().unwrap()[0])),
+		},
+		execution,
+		execution_branch: merkle_branch(&body, 9)
+			.iter()
+			.map(|node| format!("0x{}", hex::encode(node)))
+			.collect(),
+	};
+
+	let proof = ExecutionPayloadProof::try_from(&header).unwrap();
+	assert_eq!(proof.block_number, 19_000_000);
+	assert_eq!(proof.timestamp, 1_700_000_000);
+	let beacon_header = BeaconBlockHeader::try_from(&header.beacon).unwrap();
+	proof.verify::<HostFunctionsManager>(&fork_parameters, &beacon_header).unwrap();
+}
+
+#[test]
+fn capella_execution_payload_proof_verifies() {
+	verify_execution_proof(false);
+}
+
+#[test]
+fn deneb_execution_payload_proof_verifies() {
+	verify_execution_proof(true);
+}
@@ -122,6 +122,8 @@ pub struct Metrics {
 
 	/// Light client height.
 	pub light_client_height: HashMap<ClientId, LightClientMetrics>,
+	/// Seconds until the light clients of this chain on the counterparty expire, by client id.
+	pub light_client_time_until_expiry: GaugeVec<U64>,
 
 	/// Average time between "send packet" events.
 	pub send_packet_event_time: Histogram,
@@ -231,6 +233,16 @@ impl Metrics {
 				registry,
 			)?,
 			light_client_height: HashMap::new(),
+			light_client_time_until_expiry: register(
+				GaugeVec::new(
+					Opts::new(
+						format!("hyperspace_{}_light_client_time_until_expiry", prefix),
+						"Seconds until the light client on the counterparty expires",
+					),
+					&["client_id"],
+				)?,
+				registry,
+			)?,
 			send_packet_event_time: register(
 				Histogram::with_opts(
 					HistogramOpts::new(
@@ -19,7 +19,7 @@ use ibc::{
 			events::{TimeoutOnClosePacket, TimeoutPacket},
 			packet::{Packet, Sequence},
 		},
-		ics24_host::identifier::{ChannelId, PortId},
+		ics24_host::identifier::{ChannelId, ClientId, PortId},
 	},
 	events::IbcEvent,
 };
@@ -29,7 +29,7 @@ use std::{
 	collections::HashMap,
 	ops::DerefMut,
 	sync::{Arc, Mutex},
-	time::Instant,
+	time::{Duration, Instant},
 };
 
 #[derive(Eq, PartialEq, Hash)]
@@ -185,6 +185,13 @@ impl MetricsHandler {
 		}
 	}
 
+	pub fn handle_client_expiry(&self, client_id: &ClientId, time_until_expiry: Duration) {
+		self.metrics
+			.light_client_time_until_expiry
+			.with_label_values(&[client_id.as_str()])
+			.set(time_until_expiry.as_secs());
+	}
+
 	pub async fn handle_transaction_costs(&self, batch_weight: u64, messages: &[Any]) {
 		let batch_size = messages.iter().map(|x| x.value.len()).sum::<usize>();
 		self.metrics.gas_cost_for_sent_tx_bundle.observe(batch_weight as f64);
@@ -290,7 +290,9 @@ impl IbcProvider for Client {
 	}
 
 	#[cfg(feature = "testing")]
-	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + Sync>> {
+	async fn ibc_events(
+		&self,
+	) -> Result<Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>>, Self::Error> {
 		todo!()
 	}
 }
@@ -19,7 +19,9 @@ use std::{collections::BTreeMap, fmt::Display, pin::Pin, time::Duration};
 use beefy_gadget_rpc::BeefyApiClient;
 use finality_grandpa::BlockNumberOps;
 use futures::{Stream, StreamExt, TryFutureExt};
-use grandpa_light_client_primitives::{FinalityProof, ParachainHeaderProofs};
+use grandpa_light_client_primitives::{
+	justification::EquivocationDetector, FinalityProof, ParachainHeaderProofs,
+};
 use ibc_proto::google::protobuf::Any;
 use sp_runtime::{
 	generic::Era,
@@ -40,8 +42,9 @@ use crate::{
 		api::runtime_types::{frame_system::Phase, pallet_ibc::Any as RawAny},
 		UncheckedExtrinsic,
 	},
+	polkadot,
 	provider::TransactionId,
-	utils::MetadataIbcEventWrapper,
+	utils::{messages_to_retry, MessagesDelivered, MetadataIbcEventWrapper},
 	FinalityProtocol,
 };
 use finality_grandpa_rpc::GrandpaApiClient;
@@ -56,10 +59,13 @@ use ibc::{
 	events::IbcEvent,
 	tx_msg::Msg,
 };
-use ics10_grandpa::client_message::{ClientMessage, Misbehaviour, RelayChainHeader};
-use pallet_ibc::light_clients::AnyClientMessage;
+use ics10_grandpa::client_message::{
+	ClientMessage, EquivocationProof, Misbehaviour, RelayChainHeader,
+};
+use pallet_ibc::{light_clients::AnyClientMessage, MessageOutcome};
 use primitives::mock::LocalClientTypes;
 use sp_core::{twox_128, H256};
+use sp_finality_grandpa::{AuthorityId, AuthorityList, Equivocation};
 use subxt::tx::{PlainTip, PolkadotExtrinsicParamsBuilder};
 use tokio::time::sleep;
 
@@ -70,6 +76,9 @@ type GrandpaJustification = grandpa_light_client_primitives::justification::Gran
 type BeefyJustification =
 	beefy_primitives::SignedCommitment<u32, beefy_primitives::crypto::Signature>;
 
+/// Number of times the failed messages of a batch are resubmitted
+const MAX_DELIVERY_RETRIES: usize = 2;
+
 /// An encoded justification proving that the given header has been finalized
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 struct JustificationNotification(sp_core::Bytes);
@@ -102,6 +111,10 @@ where
 		self.max_extrinsic_weight
 	}
 
+	fn client_refresh_fraction(&self) -> f64 {
+		self.client_refresh_fraction
+	}
+
 	async fn estimate_weight(&self, messages: Vec<Any>) -> Result<u64, Self::Error> {
 		let extrinsic = {
 			// todo: put this in utils
@@ -203,16 +216,35 @@ where
 		}
 	}
 
-	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Error> {
-		let messages = messages
-			.into_iter()
-			.map(|msg| RawAny { type_url: msg.type_url.as_bytes().to_vec(), value: msg.value })
-			.collect::<Vec<_>>();
-
-		let call = api::tx().ibc().deliver(messages);
-		let (ext_hash, block_hash) = self.submit_call(call).await?;
-
-		Ok(TransactionId { ext_hash, block_hash })
+	async fn submit(&self, mut messages: Vec<Any>) -> Result<Self::TransactionId, Error> {
+		let mut retries = 0;
+		loop {
+			let raw_messages = messages
+				.iter()
+				.map(|msg| RawAny {
+					type_url: msg.type_url.as_bytes().to_vec(),
+					value: msg.value.clone(),
+				})
+				.collect::<Vec<_>>();
+			let call = api::tx().ibc().deliver(raw_messages);
+			let (ext_hash, block_hash, events) = self.submit_call_with_events(call).await?;
+			let results = events
+				.find_first::<MessagesDelivered>()?
+				.map(|ev| ev.results)
+				.unwrap_or_default();
+			let retry = messages_to_retry(&messages, &results);
+			let succeeded = results.iter().any(|result| result.outcome != MessageOutcome::Failure);
+
+			// messages can fail because the ones they depend on weren't executed yet, eg. an
+			// update of the same client included before ours, so the ones that failed for such a
+			// transient reason are resubmitted for as long as some messages keep succeeding.
+			if retry.is_empty() || !succeeded || retries == MAX_DELIVERY_RETRIES {
+				return Ok(TransactionId { ext_hash, block_hash })
+			}
+			log::info!("Resubmitting {} out of {} messages", retry.len(), messages.len());
+			messages = retry;
+			retries += 1;
+		}
 	}
 
 	async fn query_client_message(&self, update: UpdateClient) -> Result<AnyClientMessage, Error> {
@@ -447,4 +479,105 @@ where
 		}
 		Ok(())
 	}
+
+	async fn report_equivocations<C: Chain>(&self, counterparty: &C) -> Result<(), anyhow::Error> {
+		// the relay chain's votes only matter to a GRANDPA client.
+		if !matches!(self.finality_protocol, FinalityProtocol::Grandpa) {
+			return Ok(())
+		}
+
+		let mut justifications =
+			GrandpaApiClient::<JustificationNotification, H256, u32>::subscribe_justifications(
+				&*self.relay_ws_client,
+			)
+			.await?;
+		let mut detector = EquivocationDetector::<polkadot_core_primitives::Header>::default();
+		// equivocations of each authority set, reported once they hold a third of its weight.
+		let mut pending = BTreeMap::<u64, BTreeMap<AuthorityId, Equivocation<H256, u32>>>::new();
+
+		while let Some(notification) = justifications.next().await {
+			let JustificationNotification(sp_core::Bytes(encoded)) = notification?;
+			let justification = GrandpaJustification::decode(&mut &*encoded)?;
+
+			// the set that finalized the target is the one active at its parent, the target may
+			// enact the next one.
+			let target = self
+				.relay_client
+				.rpc()
+				.header(Some(justification.commit.target_hash.into()))
+				.await?
+				.ok_or_else(|| {
+					anyhow!("No header found for hash: {:?}", justification.commit.target_hash)
+				})?;
+			let set_id = self
+				.relay_client
+				.storage()
+				.fetch(
+					&polkadot::api::storage().grandpa().current_set_id(),
+					Some(*target.parent_hash()),
+				)
+				.await?
+				.ok_or_else(|| {
+					anyhow!("No authority set id found at {:?}", target.parent_hash())
+				})?;
+
+			detector.prune(set_id);
+			pending = pending.split_off(&set_id);
+			let equivocations = detector.observe(set_id, &justification);
+			if equivocations.is_empty() {
+				continue
+			}
+
+			let set_equivocations = pending.entry(set_id).or_default();
+			for equivocation in equivocations {
+				log::warn!(
+					"Found equivocation by {:?} in round {} of authority set {set_id}",
+					equivocation.offender(),
+					equivocation.round_number(),
+				);
+				set_equivocations.entry(equivocation.offender().clone()).or_insert(equivocation);
+			}
+
+			let authorities = {
+				let bytes = self
+					.relay_client
+					.rpc()
+					.request::<String>(
+						"state_call",
+						subxt::rpc_params!(
+							"GrandpaApi_grandpa_authorities",
+							"0x",
+							target.parent_hash()
+						),
+					)
+					.await
+					.map(|res| hex::decode(&res[2..]))??;
+				AuthorityList::decode(&mut &bytes[..])?
+			};
+			let total_weight = authorities.iter().map(|(_, weight)| *weight as u128).sum::<u128>();
+			let equivocating_weight = authorities
+				.iter()
+				.filter(|(id, _)| set_equivocations.contains_key(id))
+				.map(|(_, weight)| *weight as u128)
+				.sum::<u128>();
+			// the client rejects reports that can't have forked finality.
+			if equivocating_weight * 3 < total_weight {
+				continue
+			}
+
+			let equivocations = set_equivocations.values().cloned().collect();
+			let report = ClientMessage::Equivocation(EquivocationProof { set_id, equivocations });
+			counterparty
+				.submit(vec![MsgUpdateAnyClient::<LocalClientTypes>::new(
+					self.client_id(),
+					AnyClientMessage::Grandpa(report),
+					counterparty.account_id(),
+				)
+				.to_any()])
+				.map_err(|e| anyhow!("Failed to submit equivocation report: {:?}", e))
+				.await?;
+		}
+
+		Ok(())
+	}
 }
@@ -33,6 +33,7 @@ use ibc_rpc::{BlockNumberOrHash, IbcApiClient};
 use ics10_grandpa::client_message::{ClientMessage, Header as GrandpaHeader};
 use ics11_beefy::client_message::{
 	BeefyHeader, ClientMessage as BeefyClientMessage, ParachainHeadersWithProof,
+	RelayChainHeadersWithProof,
 };
 use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState};
 use primitives::{
@@ -142,14 +143,17 @@ where
 	})?;
 	let client_state = AnyClientState::try_from(client_state)
 		.map_err(|_| Error::Custom("Failed to decode client state".to_string()))?;
-	let beefy_client_state = match &client_state {
-		AnyClientState::Beefy(client_state) => BeefyPrimitivesClientState {
-			latest_beefy_height: client_state.latest_beefy_height,
-			mmr_root_hash: client_state.mmr_root_hash,
-			current_authorities: client_state.authority.clone(),
-			next_authorities: client_state.next_authority_set.clone(),
-			beefy_activation_block: client_state.beefy_activation_block,
-		},
+	let (beefy_client_state, signature_sample_size) = match &client_state {
+		AnyClientState::Beefy(client_state) => (
+			BeefyPrimitivesClientState {
+				latest_beefy_height: client_state.latest_beefy_height,
+				mmr_root_hash: client_state.mmr_root_hash,
+				current_authorities: client_state.authority.clone(),
+				next_authorities: client_state.next_authority_set.clone(),
+				beefy_activation_block: client_state.beefy_activation_block,
+			},
+			client_state.signature_sample_size,
+		),
 		c => Err(Error::ClientStateRehydration(format!(
 			"Expected AnyClientState::Beefy found: {:?}",
 			c
@@ -171,28 +175,34 @@ where
 		Err(Error::HeaderConstruction("Received an outdated beefy commitment".to_string()))?
 	}
 
-	// fetch the new parachain headers that have been finalized
-	let headers = source
-		.query_beefy_finalized_parachain_headers_between(
-			signed_commitment.commitment.block_number,
-			&beefy_client_state,
-		)
-		.await?;
+	let finalized_blocks = if source.standalone {
+		// the source is the relay chain itself, the mmr at the commitment's block proves the
+		// relay chain headers up to the commitment's parent.
+		(beefy_client_state.latest_beefy_height..signed_commitment.commitment.block_number)
+			.collect::<Vec<_>>()
+	} else {
+		// fetch the new parachain headers that have been finalized
+		let headers = source
+			.query_beefy_finalized_parachain_headers_between(
+				signed_commitment.commitment.block_number,
+				&beefy_client_state,
+			)
+			.await?;
+
+		headers.iter().map(|header| u32::from(*header.number())).collect::<Vec<_>>()
+	};
 
 	log::info!(
 		"Fetching events from {} for blocks {}..{}",
 		source.name(),
-		headers[0].number(),
-		headers.last().unwrap().number()
+		finalized_blocks.first().copied().unwrap_or_default(),
+		finalized_blocks.last().copied().unwrap_or_default()
 	);
 
 	// Get finalized parachain block numbers, but only those higher than the latest para
 	// height recorded in the on-chain client state, because in some cases a parachain
 	// block that was already finalized in a former beefy block might still be part of
 	// the parachain headers in a later beefy block, discovered this from previous logs
-	let finalized_blocks =
-		headers.iter().map(|header| u32::from(*header.number())).collect::<Vec<_>>();
-
 	let finalized_block_numbers = finalized_blocks
 		.iter()
 		.filter_map(|block_number| {
@@ -268,7 +278,25 @@ where
 	}
 
 	// only query proofs for headers that actually have events or are mandatory
-	let headers_with_proof = if !headers_with_events.is_empty() {
+	let (headers_with_proof, relay_chain_headers_with_proof) = if headers_with_events.is_empty() {
+		(None, None)
+	} else if source.standalone {
+		let (headers, batch_proof) = source
+			.query_beefy_finalized_relay_chain_headers_with_proof(
+				signed_commitment.commitment.block_number,
+				&beefy_client_state,
+				headers_with_events.into_iter().map(u32::from).collect(),
+			)
+			.await?;
+		let mmr_size = NodesUtils::new(batch_proof.leaf_count).size();
+
+		let headers_with_proof = RelayChainHeadersWithProof {
+			headers,
+			mmr_size,
+			mmr_proofs: batch_proof.items.into_iter().map(|item| item.encode()).collect(),
+		};
+		(None, Some(headers_with_proof))
+	} else {
 		let (headers, batch_proof) = source
 			.query_beefy_finalized_parachain_headers_with_proof(
 				signed_commitment.commitment.block_number,
@@ -278,17 +306,16 @@ where
 			.await?;
 		let mmr_size = NodesUtils::new(batch_proof.leaf_count).size();
 
-		Some(ParachainHeadersWithProof {
+		let headers_with_proof = ParachainHeadersWithProof {
 			headers,
 			mmr_size,
 			mmr_proofs: batch_proof.items.into_iter().map(|item| item.encode()).collect(),
-		})
-	} else {
-		None
+		};
+		(Some(headers_with_proof), None)
 	};
 
 	let mmr_update = source
-		.query_beefy_mmr_update_proof(signed_commitment, &beefy_client_state)
+		.query_beefy_mmr_update_proof(signed_commitment, &beefy_client_state, signature_sample_size)
 		.await?;
 
 	let update_header = {
@@ -297,6 +324,7 @@ where
 			client_message: AnyClientMessage::Beefy(BeefyClientMessage::Header(BeefyHeader {
 				headers_with_proof,
 				mmr_update_proof: Some(mmr_update),
+				relay_chain_headers_with_proof,
 			})),
 			signer: counterparty.account_id(),
 		};
@@ -381,6 +409,10 @@ where
 		finalized_blocks.last().unwrap(),
 	);
 
+	// Get finalized parachain block numbers, but only those higher than the latest para
+	// height recorded in the on-chain client state, because in some cases a parachain
+	// block that was already finalized in a former beefy block might still be part of
+	// the parachain headers in a later beefy block, discovered this from previous logs
 	let finalized_block_numbers = finalized_blocks
 		.iter()
 		.map(|h| BlockNumberOrHash::Number(*h))
@@ -447,7 +479,11 @@ where
 		latest_para_height: client_state.latest_para_height,
 		para_id: client_state.para_id,
 	};
-	let ParachainHeadersWithFinalityProof { finality_proof, parachain_headers } = prover
+	let ParachainHeadersWithFinalityProof {
+		finality_proof,
+		parachain_headers,
+		authority_set_changes,
+	} = prover
 		.query_finalized_parachain_headers_with_proof(
 			&cs,
 			justification.commit.target_number.into(),
@@ -467,7 +503,8 @@ where
 				)
 			})?;
 
-	let authority_set_changed_scheduled = find_scheduled_change(&target).is_some();
+	let authority_set_changed_scheduled =
+		!authority_set_changes.is_empty() || find_scheduled_change(&target).is_some();
 	// if validator set has changed this is a mandatory update
 	let update_type =
 		match authority_set_changed_scheduled || timeout_update_required || is_update_required {
@@ -478,6 +515,7 @@ where
 	let grandpa_header = GrandpaHeader {
 		finality_proof: finality_proof.into(),
 		parachain_headers: parachain_headers.into(),
+		authority_set_changes: authority_set_changes.into_iter().map(Into::into).collect(),
 	};
 
 	let update_header = {
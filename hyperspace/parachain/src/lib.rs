@@ -33,10 +33,10 @@ pub mod test_provider;
 use error::Error;
 use serde::Deserialize;
 
-use beefy_light_client_primitives::{ClientState, MmrUpdateProof};
+use beefy_light_client_primitives::{min_signature_sample_size, ClientState};
 use beefy_prover::Prover;
 use ibc::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
-use ics11_beefy::client_message::ParachainHeader;
+use ics11_beefy::client_message::{MmrUpdate, ParachainHeader, RelayChainHeader};
 use pallet_mmr_primitives::BatchProof;
 use sp_core::{ecdsa, ed25519, sr25519, Bytes, Pair, H256};
 use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
@@ -68,7 +68,7 @@ use jsonrpsee_ws_client::WsClientBuilder;
 use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState, HostFunctionsManager};
 use sp_keystore::testing::KeyStore;
 use sp_runtime::traits::One;
-use subxt::tx::TxPayload;
+use subxt::tx::{TxEvents, TxPayload};
 
 /// Implements the [`crate::Chain`] trait for parachains.
 /// This is responsible for:
@@ -87,6 +87,8 @@ pub struct ParachainClient<T: config::Config> {
 	pub relay_ws_client: Arc<jsonrpsee_ws_client::WsClient>,
 	/// Parachain ws client
 	pub para_ws_client: Arc<jsonrpsee_ws_client::WsClient>,
+	/// Parachain rpc url, the ibc event subscription reconnects to it
+	pub para_rpc_url: String,
 	/// Parachain Id
 	pub para_id: u32,
 	/// Beefy activation block
@@ -111,6 +113,14 @@ pub struct ParachainClient<T: config::Config> {
 	pub channel_whitelist: Vec<(ChannelId, PortId)>,
 	/// Finality protocol to use, eg Beefy, Grandpa
 	pub finality_protocol: FinalityProtocol,
+	/// Whether this is a standalone chain running its own GRANDPA or a relay chain tracked with
+	/// BEEFY, the relay chain clients then point at the chain itself.
+	pub standalone: bool,
+	/// Revision number of the heights of a standalone chain
+	pub revision_number: u64,
+	/// Fraction of the trusting period of the client on the counterparty after which it's
+	/// updated even if there's nothing to relay.
+	pub client_refresh_fraction: f64,
 }
 
 enum KeyType {
@@ -151,7 +161,7 @@ pub struct ParachainClientConfig {
 	pub para_id: u32,
 	/// rpc url for parachain
 	pub parachain_rpc_url: String,
-	/// rpc url for relay chain
+	/// rpc url for relay chain, ignored for standalone chains
 	pub relay_chain_rpc_url: String,
 	/// Light client id on counterparty chain
 	pub client_id: Option<ClientId>,
@@ -171,6 +181,39 @@ pub struct ParachainClientConfig {
 	pub finality_protocol: FinalityProtocol,
 	/// Digital signature scheme
 	pub key_type: String,
+	/// Set for standalone chains that run their own GRANDPA instead of being a parachain. With
+	/// the BEEFY finality protocol the chain is a relay chain, tracked by the counterparty's BEEFY
+	/// client through its own mmr.
+	#[serde(default)]
+	pub standalone: bool,
+	/// Revision number of the heights of a standalone chain, parachain heights use the para id.
+	#[serde(default)]
+	pub revision_number: u64,
+	/// Fraction of the trusting period of the client on the counterparty after which it's
+	/// updated even if there's nothing to relay, defaults to
+	/// [`primitives::DEFAULT_CLIENT_REFRESH_FRACTION`]. Must be in `(0, 1]`.
+	#[serde(default, deserialize_with = "deserialize_client_refresh_fraction")]
+	pub client_refresh_fraction: Option<f64>,
+}
+
+/// Rejects refresh fractions that would update the client on every finality event or never
+/// before it expires.
+fn validate_client_refresh_fraction(fraction: f64) -> Result<f64, Error> {
+	if fraction > 0.0 && fraction <= 1.0 {
+		Ok(fraction)
+	} else {
+		Err(Error::Custom(format!("client_refresh_fraction must be in (0, 1], got {fraction}")))
+	}
+}
+
+fn deserialize_client_refresh_fraction<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+	D: serde::Deserializer<'de>,
+{
+	Option::<f64>::deserialize(deserializer)?
+		.map(validate_client_refresh_fraction)
+		.transpose()
+		.map_err(serde::de::Error::custom)
 }
 
 impl<T> ParachainClient<T>
@@ -179,9 +222,14 @@ where
 {
 	/// Initializes a [`ParachainClient`] given a [`ParachainConfig`]
 	pub async fn new(config: ParachainClientConfig) -> Result<Self, Error> {
+		let client_refresh_fraction = validate_client_refresh_fraction(
+			config.client_refresh_fraction.unwrap_or(primitives::DEFAULT_CLIENT_REFRESH_FRACTION),
+		)?;
+		let relay_chain_rpc_url =
+			if config.standalone { &config.parachain_rpc_url } else { &config.relay_chain_rpc_url };
 		let relay_ws_client = Arc::new(
 			WsClientBuilder::default()
-				.build(&config.relay_chain_rpc_url)
+				.build(relay_chain_rpc_url)
 				.await
 				.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
 		);
@@ -248,10 +296,14 @@ where
 			key_type_id,
 			max_extrinsic_weight,
 			para_ws_client,
+			para_rpc_url: config.parachain_rpc_url,
 			relay_ws_client,
 			ss58_version: Ss58AddressFormat::from(config.ss58_version),
 			channel_whitelist: config.channel_whitelist,
 			finality_protocol: config.finality_protocol,
+			standalone: config.standalone,
+			revision_number: config.revision_number,
+			client_refresh_fraction,
 		})
 	}
 }
@@ -277,6 +329,7 @@ where
 			para_client: self.para_client.clone(),
 			para_ws_client,
 			para_id: self.para_id,
+			standalone: self.standalone,
 		}
 	}
 
@@ -358,7 +411,53 @@ where
 		Ok((parachain_headers, batch_proof))
 	}
 
-	/// Queries for the BEEFY mmr update proof for the given signed commitment height.
+	/// Construct the relay chain headers with the given numbers and the mmr batch proof of the
+	/// leaves appended by their children, for clients tracking the relay chain with BEEFY.
+	pub async fn query_beefy_finalized_relay_chain_headers_with_proof(
+		&self,
+		commitment_block_number: u32,
+		client_state: &ClientState,
+		headers: Vec<u32>,
+	) -> Result<(Vec<RelayChainHeader>, BatchProof<H256>), Error>
+	where
+		T::Hash: From<H256>,
+	{
+		let client_wrapper = Prover {
+			relay_client: self.relay_client.clone(),
+			para_client: self.para_client.clone(),
+			beefy_activation_block: client_state.beefy_activation_block,
+			para_id: self.para_id,
+		};
+
+		let (relay_chain_headers, batch_proof) = client_wrapper
+			.query_finalized_relay_chain_headers_with_proof(commitment_block_number, headers)
+			.await
+			.map_err(|e| {
+				Error::from(format!(
+					"[query_finalized_relay_chain_headers_with_proof] Failed due to {:?}",
+					e
+				))
+			})?;
+
+		let relay_chain_headers = relay_chain_headers
+			.into_iter()
+			.map(|header| {
+				Ok(RelayChainHeader {
+					relay_chain_header: codec::Decode::decode(&mut &*header.relay_chain_header)?,
+					mmr_leaf: header.mmr_leaf,
+					extrinsic_proof: header.extrinsic_proof,
+					timestamp_extrinsic: header.timestamp_extrinsic,
+				})
+			})
+			.collect::<Result<Vec<_>, codec::Error>>()?;
+
+		Ok((relay_chain_headers, batch_proof))
+	}
+
+	/// Queries for the BEEFY mmr update proof for the given signed commitment height, only proving
+	/// a sample of the signatures if `signature_sample_size` is not zero. Falls back to proving all
+	/// the signatures if the sample size is too small for the signing authority set, the light
+	/// client rejects such samples.
 	pub async fn query_beefy_mmr_update_proof(
 		&self,
 		signed_commitment: beefy_primitives::SignedCommitment<
@@ -366,7 +465,8 @@ where
 			beefy_primitives::crypto::Signature,
 		>,
 		client_state: &ClientState,
-	) -> Result<MmrUpdateProof, Error> {
+		signature_sample_size: u32,
+	) -> Result<MmrUpdate, Error> {
 		let prover = Prover {
 			relay_client: self.relay_client.clone(),
 			para_client: self.para_client.clone(),
@@ -374,11 +474,41 @@ where
 			para_id: self.para_id,
 		};
 
+		let validator_set_id = signed_commitment.commitment.validator_set_id;
+		let authority_set_len = if validator_set_id == client_state.next_authorities.id {
+			client_state.next_authorities.len
+		} else {
+			client_state.current_authorities.len
+		};
+		let min_sample_size = min_signature_sample_size(authority_set_len);
+		if signature_sample_size > 0 && signature_sample_size < min_sample_size {
+			log::warn!(
+				"Signature sample size {signature_sample_size} is below {min_sample_size} for authority set {validator_set_id}, proving all signatures"
+			);
+		}
+
+		if signature_sample_size >= min_sample_size {
+			let mmr_update = prover
+				.fetch_sampled_mmr_update_proof_for(
+					signed_commitment,
+					client_state.mmr_root_hash,
+					signature_sample_size,
+				)
+				.await
+				.map_err(|e| {
+					Error::from(format!(
+						"[fetch_sampled_mmr_update_proof_for] Failed due to {:?}",
+						e
+					))
+				})?;
+			return Ok(MmrUpdate::Sampled(mmr_update))
+		}
+
 		let mmr_update =
 			prover.fetch_mmr_update_proof_for(signed_commitment).await.map_err(|e| {
 				Error::from(format!("[fetch_mmr_update_proof_for] Failed due to {:?}", e))
 			})?;
-		Ok(mmr_update)
+		Ok(mmr_update.into())
 	}
 
 	/// Submits the given transaction to the parachain node, waits for it to be included in a block
@@ -387,6 +517,15 @@ where
 	/// We retry sending the transaction up to 5 times in the case where the transaction pool might
 	/// reject the transaction because of conflicting nonces.
 	pub async fn submit_call<C: TxPayload>(&self, call: C) -> Result<(T::Hash, T::Hash), Error> {
+		let (ext_hash, block_hash, _) = self.submit_call_with_events(call).await?;
+		Ok((ext_hash, block_hash))
+	}
+
+	/// Submits the call like [`Self::submit_call`], also returning the events it emitted.
+	pub async fn submit_call_with_events<C: TxPayload>(
+		&self,
+		call: C,
+	) -> Result<(T::Hash, T::Hash, TxEvents<T>), Error> {
 		let signer = ExtrinsicSigner::<T, Self>::new(
 			self.key_store.clone(),
 			self.key_type_id.clone(),
@@ -417,8 +556,8 @@ where
 		};
 
 		let tx_in_block = progress.wait_for_in_block().await?;
-		tx_in_block.wait_for_success().await?;
-		Ok((tx_in_block.extrinsic_hash(), tx_in_block.block_hash()))
+		let events = tx_in_block.wait_for_success().await?;
+		Ok((tx_in_block.extrinsic_hash(), tx_in_block.block_hash(), events))
 	}
 
 	pub fn client_id(&self) -> ClientId {
@@ -478,20 +617,29 @@ where
 			let subxt_block_number: subxt::rpc::BlockNumber =
 				beefy_state.latest_beefy_height.into();
 			let block_hash = self.relay_client.rpc().block_hash(Some(subxt_block_number)).await?;
-			let heads_addr = polkadot::api::storage().paras().heads(
-				&polkadot::api::runtime_types::polkadot_parachain::primitives::Id(self.para_id),
-			);
-			let head_data = api.fetch(&heads_addr, block_hash).await?.ok_or_else(|| {
-				Error::Custom(format!(
-					"Couldn't find header for ParaId({}) at relay block {:?}",
-					self.para_id, block_hash
-				))
-			})?;
-			let decoded_para_head = sp_runtime::generic::Header::<
-				u32,
-				sp_runtime::traits::BlakeTwo256,
-			>::decode(&mut &*head_data.0)?;
-			let block_number = decoded_para_head.number;
+			let (block_number, state_root) = if self.standalone {
+				// the client tracks the relay chain, whose finalized header is the consensus state
+				let header =
+					self.relay_client.rpc().header(block_hash).await?.ok_or_else(|| {
+						Error::Custom(format!("Couldn't find header for block {:?}", block_hash))
+					})?;
+				(u32::from(*header.number()), H256::from(*header.state_root()))
+			} else {
+				let heads_addr = polkadot::api::storage().paras().heads(
+					&polkadot::api::runtime_types::polkadot_parachain::primitives::Id(self.para_id),
+				);
+				let head_data = api.fetch(&heads_addr, block_hash).await?.ok_or_else(|| {
+					Error::Custom(format!(
+						"Couldn't find header for ParaId({}) at relay block {:?}",
+						self.para_id, block_hash
+					))
+				})?;
+				let decoded_para_head = sp_runtime::generic::Header::<
+					u32,
+					sp_runtime::traits::BlakeTwo256,
+				>::decode(&mut &*head_data.0)?;
+				(decoded_para_head.number, decoded_para_head.state_root)
+			};
 			let client_state = BeefyClientState::<HostFunctionsManager> {
 				chain_id: ChainId::new("relay-chain".to_string(), 0),
 				relay_chain: Default::default(),
@@ -503,6 +651,8 @@ where
 				para_id: self.para_id,
 				authority: beefy_state.current_authorities,
 				next_authority_set: beefy_state.next_authorities,
+				signature_sample_size: 0,
+				track_relay_chain: self.standalone,
 				_phantom: Default::default(),
 			};
 			// we can't use the genesis block to construct the initial state.
@@ -524,7 +674,7 @@ where
 					.unwrap()
 					.into_tm_time()
 					.unwrap(),
-				root: decoded_para_head.state_root.as_bytes().to_vec().into(),
+				root: state_root.as_bytes().to_vec().into(),
 			});
 
 			return Ok((AnyClientState::Beefy(client_state), consensus_state))
@@ -550,6 +700,7 @@ where
 			para_client: self.para_client.clone(),
 			para_ws_client,
 			para_id: self.para_id,
+			standalone: self.standalone,
 		};
 		let api = self.relay_client.storage();
 		let para_client_api = self.para_client.storage();
@@ -559,23 +710,39 @@ where
 				.await
 				.map_err(|e| Error::from(format!("Error constructing client state: {e}")))?;
 
-			let heads_addr = polkadot::api::storage().paras().heads(
-				&polkadot::api::runtime_types::polkadot_parachain::primitives::Id(self.para_id),
-			);
-			let head_data = api
-				.fetch(&heads_addr, Some(light_client_state.latest_relay_hash))
-				.await?
-				.ok_or_else(|| {
-					Error::Custom(format!(
-						"Couldn't find header for ParaId({}) at relay block {:?}",
-						self.para_id, light_client_state.latest_relay_hash
-					))
-				})?;
-			let decoded_para_head = sp_runtime::generic::Header::<
-				u32,
-				sp_runtime::traits::BlakeTwo256,
-			>::decode(&mut &*head_data.0)?;
-			let block_number = decoded_para_head.number;
+			let (block_number, state_root) = if self.standalone {
+				// the finalized header of a standalone chain is the consensus state itself
+				let header = self
+					.relay_client
+					.rpc()
+					.header(Some(light_client_state.latest_relay_hash))
+					.await?
+					.ok_or_else(|| {
+						Error::Custom(format!(
+							"Couldn't find header for block {:?}",
+							light_client_state.latest_relay_hash
+						))
+					})?;
+				(u32::from(*header.number()), H256::from(*header.state_root()))
+			} else {
+				let heads_addr = polkadot::api::storage().paras().heads(
+					&polkadot::api::runtime_types::polkadot_parachain::primitives::Id(self.para_id),
+				);
+				let head_data = api
+					.fetch(&heads_addr, Some(light_client_state.latest_relay_hash))
+					.await?
+					.ok_or_else(|| {
+						Error::Custom(format!(
+							"Couldn't find header for ParaId({}) at relay block {:?}",
+							self.para_id, light_client_state.latest_relay_hash
+						))
+					})?;
+				let decoded_para_head = sp_runtime::generic::Header::<
+					u32,
+					sp_runtime::traits::BlakeTwo256,
+				>::decode(&mut &*head_data.0)?;
+				(decoded_para_head.number, decoded_para_head.state_root)
+			};
 			// we can't use the genesis block to construct the initial state.
 			if block_number == 0 {
 				continue
@@ -590,6 +757,8 @@ where
 			client_state.frozen_height = None;
 			client_state.latest_para_height = block_number;
 			client_state.para_id = self.para_id;
+			client_state.standalone = self.standalone;
+			client_state.standalone_revision_number = self.revision_number;
 			client_state.latest_relay_height = light_client_state.latest_relay_height;
 
 			let subxt_block_number: subxt::rpc::BlockNumber = block_number.into();
@@ -607,7 +776,7 @@ where
 					.unwrap()
 					.into_tm_time()
 					.unwrap(),
-				root: decoded_para_head.state_root.as_bytes().to_vec().into(),
+				root: state_root.as_bytes().to_vec().into(),
 			});
 
 			return Ok((AnyClientState::Grandpa(client_state), consensus_state))
@@ -14,8 +14,7 @@
 
 use super::{error::Error, ParachainClient};
 use crate::{
-	config, finality_protocol::FinalityEvent, parachain, utils::MetadataIbcEventWrapper,
-	FinalityProtocol, GrandpaClientState,
+	config, finality_protocol::FinalityEvent, parachain, FinalityProtocol, GrandpaClientState,
 };
 use beefy_prover::helpers::fetch_timestamp_extrinsic_with_proof;
 use codec::Encode;
@@ -41,15 +40,14 @@ use ibc_proto::{
 			QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
 			QueryPacketReceiptResponse,
 		},
-		client::v1::{
-			IdentifiedClientState, QueryClientStateResponse, QueryConsensusStateResponse,
-		},
+		client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
 		connection::v1::{IdentifiedConnection, QueryConnectionResponse},
 	},
 };
 use ibc_rpc::{IbcApiClient, PacketInfo};
 use ics10_grandpa::client_message::RelayChainHeader;
 use ics11_beefy::client_state::ClientState as BeefyClientState;
+use jsonrpsee_ws_client::{WsClient, WsClientBuilder};
 use pallet_ibc::{
 	light_clients::{AnyClientState, AnyConsensusState, HostFunctionsManager},
 	HostConsensusProof,
@@ -60,7 +58,7 @@ use sp_runtime::{
 	traits::{Header as HeaderT, IdentifyAccount, One, Verify},
 	MultiSignature, MultiSigner,
 };
-use std::{collections::BTreeMap, fmt::Display, pin::Pin, str::FromStr, time::Duration};
+use std::{collections::BTreeMap, fmt::Display, pin::Pin, str::FromStr, sync::Arc, time::Duration};
 use subxt::tx::{BaseExtrinsicParamsBuilder, ExtrinsicParams, PlainTip};
 
 pub struct TransactionId<Hash> {
@@ -107,51 +105,63 @@ where
 			.await
 	}
 
-	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
-		use futures::{stream, StreamExt};
-		use pallet_ibc::events::IbcEvent as RawIbcEvent;
-
-		let stream = self
-			.para_client
-			.events()
-			.subscribe()
-			.await
-			.expect("Failed to subscribe to events")
-			.filter_events::<(parachain::api::ibc::events::Events,)>()
-			.filter_map(|result| {
-				let events = match result {
-					Ok(ev) => ev,
-					Err(err) => {
-						log::error!("Error in IbcEvent stream: {err:?}");
-						return futures::future::ready(None)
-					},
-				};
-				let result = events
-					.event
-					.events
-					.into_iter()
-					.filter_map(|ev| {
-						Some(
-							IbcEvent::try_from(RawIbcEvent::from(MetadataIbcEventWrapper(
-								ev.ok()?,
-							)))
-							.map_err(|e| subxt::Error::Other(e.to_string())),
-						)
-					})
-					.collect::<Result<Vec<_>, _>>();
-
-				let events = match result {
-					Ok(ev) => ev,
-					Err(err) => {
-						log::error!("Failed to decode event: {err:?}");
-						return futures::future::ready(None)
-					},
-				};
-				futures::future::ready(Some(stream::iter(events)))
-			})
-			.flatten();
-		Box::pin(stream)
+	async fn ibc_events(
+		&self,
+	) -> Result<Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>>, Self::Error> {
+		use futures::StreamExt;
+
+		let subscription = subscribe_ibc_events::<T>(&self.para_ws_client, None).await?;
+		let state: (Arc<WsClient>, String, _, Option<u32>) =
+			(self.para_ws_client.clone(), self.para_rpc_url.clone(), Some(subscription), None);
+		// The subscription ends when the connection to the node drops, it's then resumed from the
+		// block after the last one events were received for, within the blocks the node replays.
+		let stream = futures::stream::unfold(
+			state,
+			|(mut client, url, mut subscription, mut last_seen)| async move {
+				loop {
+					if let Some(events) = subscription.as_mut() {
+						match events.next().await {
+							Some(event) => {
+								last_seen = Some(event.height().revision_height as u32);
+								return Some((event, (client, url, subscription, last_seen)))
+							},
+							None => {
+								log::warn!("IbcEvent subscription ended, resubscribing");
+								subscription = None;
+							},
+						}
+					}
+					if !client.is_connected() {
+						match WsClientBuilder::default().build(&url).await {
+							Ok(reconnected) => client = Arc::new(reconnected),
+							Err(err) => {
+								log::error!("Failed to reconnect to {url}: {err:?}");
+								tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+								continue
+							},
+						}
+					}
+					let from_height = last_seen.map(|height| height + 1);
+					match subscribe_ibc_events::<T>(&client, from_height).await {
+						Ok(events) => subscription = Some(events),
+						// The node is up but won't replay from there, the events in between are lost.
+						Err(err) if from_height.is_some() && client.is_connected() => {
+							log::error!(
+								"Failed to resume IbcEvent subscription from {from_height:?}, events since are lost: {err:?}"
+							);
+							last_seen = None;
+						},
+						Err(err) => {
+							log::error!("Failed to resubscribe to IbcEvents: {err:?}");
+							tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+						},
+					}
+				}
+			},
+		);
+		Ok(Box::pin(stream))
 	}
+
 	async fn query_client_consensus(
 		&self,
 		at: Height,
@@ -314,7 +324,9 @@ where
 			.await?
 			.ok_or_else(|| Error::Custom("Latest height query returned None".to_string()))?;
 		let latest_height: u64 = (*finalized_header.number()).into();
-		let height = Height::new(self.para_id.into(), latest_height.into());
+		let revision_number =
+			if self.standalone { self.revision_number } else { self.para_id.into() };
+		let height = Height::new(revision_number, latest_height.into());
 
 		let subxt_block_number: subxt::rpc::BlockNumber = latest_height.into();
 		let block_hash = self.para_client.rpc().block_hash(Some(subxt_block_number)).await.unwrap();
@@ -342,6 +354,7 @@ where
 				at.revision_height as u32,
 				channel_id.to_string(),
 				port_id.to_string(),
+				None,
 			)
 			.await
 			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -359,6 +372,7 @@ where
 			at.revision_height as u32,
 			channel_id.to_string(),
 			port_id.to_string(),
+			None,
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -422,6 +436,7 @@ where
 				&*self.para_ws_client,
 				at.revision_height as u32,
 				connection_id.to_string(),
+				None,
 			)
 			.await
 			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -565,13 +580,15 @@ where
 	}
 
 	async fn query_clients(&self) -> Result<Vec<ClientId>, Self::Error> {
-		let response: Vec<IdentifiedClientState> =
-			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_clients(
-				&*self.para_ws_client,
-			)
-			.await
-			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		let response = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_clients(
+			&*self.para_ws_client,
+			None,
+			None,
+		)
+		.await
+		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
 		response
+			.client_states
 			.into_iter()
 			.map(|client| {
 				ClientId::from_str(&client.client_id)
@@ -583,6 +600,8 @@ where
 	async fn query_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error> {
 		let response = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_channels(
 			&*self.para_ws_client,
+			None,
+			None,
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -660,3 +679,34 @@ where
 		Ok(client_id)
 	}
 }
+
+/// Delay before retrying to resubscribe to the ibc events of a node that can't be reached.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+
+/// Subscribes to the ibc events of finalized blocks, replaying them from `from_height` if given.
+/// Events that fail to decode are logged and skipped.
+async fn subscribe_ibc_events<T: config::Config>(
+	client: &WsClient,
+	from_height: Option<u32>,
+) -> Result<Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>>, Error> {
+	use futures::StreamExt;
+
+	let stream = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::subscribe_events(
+		client,
+		None,
+		from_height,
+	)
+	.await
+	.map_err(|e| Error::from(format!("Failed to subscribe to ibc events {:?}", e)))?
+	.filter_map(|result| {
+		let event = match result {
+			Ok(event) => Some(event),
+			Err(err) => {
+				log::error!("Error in IbcEvent stream: {err:?}");
+				None
+			},
+		};
+		futures::future::ready(event)
+	});
+	Ok(Box::pin(stream))
+}
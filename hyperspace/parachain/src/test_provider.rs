@@ -191,6 +191,7 @@ where
 				timestamp: Some(transfer.timeout_timestamp.nanoseconds()),
 				height: Some(transfer.timeout_height.revision_height),
 			},
+			memo: transfer.memo.into_bytes(),
 		};
 		let amount = str::parse::<u128>(&transfer.token.amount.to_string()).expect("Infallible!");
 		dbg!(&amount);
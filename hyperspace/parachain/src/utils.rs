@@ -22,8 +22,13 @@ use beefy_prover::helpers::unsafe_arc_cast;
 use codec::Decode;
 use frame_support::weights::DispatchClass;
 use frame_system::limits::BlockWeights;
-use pallet_ibc::events::IbcEvent as RawIbcEvent;
+use ibc::core::ics02_client::msgs::update_client::TYPE_URL as UPDATE_CLIENT_TYPE_URL;
+use ibc_proto::google::protobuf::Any;
+use pallet_ibc::{
+	errors::failure_reason, events::IbcEvent as RawIbcEvent, MessageOutcome, MessageResult,
+};
 use sp_core::H256;
+use subxt::events::StaticEvent;
 
 /// Allows to implement traits for the subxt generated code
 pub struct MetadataIbcEventWrapper(pub MetadataIbcEvent);
@@ -390,6 +395,49 @@ pub async fn fetch_max_extrinsic_weight<T: config::Config>(
 	Ok(max_extrinsic_weight)
 }
 
+/// The `MessagesDelivered` event pallet-ibc emits with the result of every message in a batch.
+#[derive(Decode)]
+pub struct MessagesDelivered {
+	pub results: Vec<MessageResult>,
+}
+
+impl StaticEvent for MessagesDelivered {
+	const PALLET: &'static str = "Ibc";
+	const EVENT: &'static str = "MessagesDelivered";
+}
+
+/// Returns the messages of a delivered batch worth resubmitting according to `results`, in the
+/// order they were delivered. Those are the messages that failed for a transient reason and, if
+/// any of them lacked a consensus state for its proof, the client updates of the batch that failed
+/// so the consensus states are resubmitted along with the proofs depending on them.
+pub fn messages_to_retry(messages: &[Any], results: &[MessageResult]) -> Vec<Any> {
+	let failed = results
+		.iter()
+		.filter(|result| result.outcome == MessageOutcome::Failure)
+		.inspect(|result| {
+			log::warn!(
+				"Message {} ({}) failed with error code {:?}",
+				result.index,
+				String::from_utf8_lossy(&result.type_url),
+				result.error_code
+			)
+		})
+		.collect::<Vec<_>>();
+	let missing_consensus_state = failed.iter().any(|result| {
+		result.is_transient_failure() &&
+			result.error_code.map(|code| code % 100) ==
+				Some(failure_reason::CONSENSUS_STATE_NOT_FOUND)
+	});
+	failed
+		.into_iter()
+		.filter(|result| {
+			result.is_transient_failure() ||
+				(missing_consensus_state && result.type_url == UPDATE_CLIENT_TYPE_URL.as_bytes())
+		})
+		.filter_map(|result| messages.get(result.index as usize).cloned())
+		.collect()
+}
+
 pub unsafe fn unsafe_cast_to_jsonrpsee_client(
 	client: &Arc<jsonrpsee_ws_client::WsClient>,
 ) -> Arc<jsonrpsee::core::client::Client> {
@@ -116,7 +116,9 @@ pub trait IbcProvider {
 		T: Chain;
 
 	/// Return a stream that yields when new [`IbcEvents`] are parsed from a finality notification
-	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>>;
+	async fn ibc_events(
+		&self,
+	) -> Result<Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>>, Self::Error>;
 
 	/// Query client consensus state with proof
 	/// return the consensus height for the client along with the response
@@ -368,6 +370,10 @@ pub trait MisbehaviourHandler {
 		counterparty: &C,
 		client_message: AnyClientMessage,
 	) -> Result<(), anyhow::Error>;
+
+	/// Watch this chain's finality votes for equivocations and report them to the client on the
+	/// counterparty. Runs until the subscription ends.
+	async fn report_equivocations<C: Chain>(&self, counterparty: &C) -> Result<(), anyhow::Error>;
 }
 
 /// Provides an interface for the chain to the relayer core for submitting IbcEvents as well as
@@ -380,6 +386,10 @@ pub trait Chain: IbcProvider + MisbehaviourHandler + KeyProvider + Send + Sync {
 	/// Should return a numerical value for the max weight of transactions allowed in a block.
 	fn block_max_weight(&self) -> u64;
 
+	/// Fraction of the trusting period of this chain's client on the counterparty after which the
+	/// client is updated, even if there's nothing to relay.
+	fn client_refresh_fraction(&self) -> f64;
+
 	/// Should return an estimate of the weight of a batch of messages.
 	async fn estimate_weight(&self, msg: Vec<Any>) -> Result<u64, Self::Error>;
 
@@ -400,6 +410,55 @@ pub trait Chain: IbcProvider + MisbehaviourHandler + KeyProvider + Send + Sync {
 	) -> Result<AnyClientMessage, Self::Error>;
 }
 
+/// Default for [`Chain::client_refresh_fraction`].
+pub const DEFAULT_CLIENT_REFRESH_FRACTION: f64 = 2.0 / 3.0;
+
+/// How long ago a client was last updated, against how long it can go without updates.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientExpiry {
+	/// Time since the client was last updated, by the clock of the chain hosting it.
+	pub since_update: Duration,
+	/// Trusting period of the client.
+	pub trusting_period: Duration,
+}
+
+impl ClientExpiry {
+	/// Time left before the client expires.
+	pub fn time_until_expiry(&self) -> Duration {
+		self.trusting_period.saturating_sub(self.since_update)
+	}
+
+	/// Whether the given fraction of the trusting period has passed since the last update.
+	pub fn refresh_due(&self, fraction: f64) -> bool {
+		self.since_update >= self.trusting_period.mul_f64(fraction)
+	}
+}
+
+/// Queries when the client of the `source` chain on the `sink` chain was last updated, returns
+/// `None` for clients that don't expire.
+pub async fn query_client_expiry(
+	source: &impl Chain,
+	sink: &impl Chain,
+) -> Result<Option<ClientExpiry>, anyhow::Error> {
+	let (latest_height, now) = sink.latest_height_and_timestamp().await?;
+	let response = sink.query_client_state(latest_height, source.client_id()).await?;
+	let client_state = response.client_state.ok_or_else(|| {
+		Error::Custom("Received an empty client state from counterparty".to_string())
+	})?;
+	let client_state = AnyClientState::try_from(client_state)
+		.map_err(|_| Error::Custom("Failed to decode client state".to_string()))?;
+	let trusting_period = match client_state.trusting_period() {
+		Some(trusting_period) => trusting_period,
+		None => return Ok(None),
+	};
+	let (_, update_time) = sink
+		.query_client_update_time_and_height(source.client_id(), client_state.latest_height())
+		.await?;
+	let since_update = now.duration_since(&update_time).unwrap_or_default();
+
+	Ok(Some(ClientExpiry { since_update, trusting_period }))
+}
+
 /// Returns undelivered packet sequences that have been sent out from
 /// the `source` chain to the `sink` chain
 /// works for both ordered and unordered channels
@@ -625,3 +684,35 @@ pub async fn query_maximum_height_for_timeout_proofs(
 	}
 	min_timeout_height
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn expiry(since_update: u64, trusting_period: u64) -> ClientExpiry {
+		ClientExpiry {
+			since_update: Duration::from_secs(since_update),
+			trusting_period: Duration::from_secs(trusting_period),
+		}
+	}
+
+	#[test]
+	fn time_until_expiry_saturates_once_expired() {
+		assert_eq!(expiry(0, 300).time_until_expiry(), Duration::from_secs(300));
+		assert_eq!(expiry(100, 300).time_until_expiry(), Duration::from_secs(200));
+		assert_eq!(expiry(300, 300).time_until_expiry(), Duration::ZERO);
+		assert_eq!(expiry(400, 300).time_until_expiry(), Duration::ZERO);
+	}
+
+	#[test]
+	fn refresh_is_due_after_fraction_of_trusting_period() {
+		assert!(!expiry(199, 300).refresh_due(DEFAULT_CLIENT_REFRESH_FRACTION));
+		assert!(expiry(200, 300).refresh_due(DEFAULT_CLIENT_REFRESH_FRACTION));
+		assert!(expiry(400, 300).refresh_due(DEFAULT_CLIENT_REFRESH_FRACTION));
+
+		assert!(!expiry(299, 300).refresh_due(1.0));
+		assert!(expiry(300, 300).refresh_due(1.0));
+		assert!(!expiry(149, 300).refresh_due(0.5));
+		assert!(expiry(150, 300).refresh_due(0.5));
+	}
+}
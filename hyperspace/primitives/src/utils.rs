@@ -97,7 +97,7 @@ pub async fn create_connection(
 	// wait till both chains have completed connection handshake
 	let future = chain_b
 		.ibc_events()
-		.await
+		.await?
 		.skip_while(|ev| future::ready(!matches!(ev, IbcEvent::OpenConfirmConnection(_))))
 		.take(1)
 		.collect::<Vec<_>>();
@@ -148,7 +148,7 @@ pub async fn create_channel(
 
 	let future = chain_b
 		.ibc_events()
-		.await
+		.await?
 		.skip_while(|ev| future::ready(!matches!(ev, IbcEvent::OpenConfirmChannel(_))))
 		.take(1)
 		.collect::<Vec<_>>();
@@ -198,6 +198,7 @@ where
 		receiver: chain_b.account_id(),
 		timeout_height,
 		timeout_timestamp,
+		memo: String::new(),
 	};
 	chain_a.send_transfer(msg.clone()).await.expect("Failed to send transfer: ");
 	(amount, msg)
@@ -212,6 +213,7 @@ where
 	let future = chain
 		.ibc_events()
 		.await
+		.unwrap()
 		.skip_while(|ev| future::ready(!matches!(ev, IbcEvent::AcknowledgePacket(_))))
 		.take(1)
 		.collect::<Vec<_>>();
@@ -379,6 +381,7 @@ async fn send_channel_close_init_and_assert_channel_close_confirm<A, B>(
 	let future = chain_b
 		.ibc_events()
 		.await
+		.unwrap()
 		.skip_while(|ev| future::ready(!matches!(ev, IbcEvent::CloseConfirmChannel(_))))
 		.take(1)
 		.collect::<Vec<_>>();
@@ -43,7 +43,9 @@ where
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::fish(client_a_clone, client_b_clone).await.unwrap()
+		hyperspace_core::fish(client_a_clone, client_b_clone, Default::default())
+			.await
+			.unwrap()
 	});
 	info!("Waiting for the next block...");
 
@@ -190,7 +192,8 @@ where
 		);
 	}
 
-	let grandpa_header = GrandpaHeader { finality_proof, parachain_headers };
+	let grandpa_header =
+		GrandpaHeader { finality_proof, parachain_headers, authority_set_changes: vec![] };
 	let client_message = AnyClientMessage::Grandpa(ClientMessage::Header(grandpa_header));
 
 	let msg =
@@ -198,7 +201,7 @@ where
 
 	let client_a_clone = chain_a.clone();
 	let misbehavour_event_handle = tokio::task::spawn(async move {
-		let mut events = client_a_clone.ibc_events().await;
+		let mut events = client_a_clone.ibc_events().await.unwrap();
 		while let Some(event) = events.next().await {
 			match event {
 				IbcEvent::ClientMisbehaviour { .. } => return,
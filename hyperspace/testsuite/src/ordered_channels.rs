@@ -157,6 +157,7 @@ async fn send_ordered_packet_and_assert_acknowledgement<A, B>(
 	let future = chain_b
 		.ibc_events()
 		.await
+		.unwrap()
 		.skip_while(|ev| future::ready(!matches!(ev, IbcEvent::AcknowledgePacket(_))))
 		.take(2)
 		.collect::<Vec<_>>();
@@ -30,6 +30,7 @@ where
 	let future = chain
 		.ibc_events()
 		.await
+		.unwrap()
 		.skip_while(|ev| {
 			future::ready(!matches!(
 				ev,
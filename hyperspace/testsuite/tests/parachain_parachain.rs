@@ -107,6 +107,9 @@ async fn setup_clients() -> (ParachainClient<DefaultConfig>, ParachainClient<Def
 		finality_protocol: FinalityProtocol::Grandpa,
 		private_key: "//Alice".to_string(),
 		key_type: "sr25519".to_string(),
+		standalone: false,
+		revision_number: 0,
+		client_refresh_fraction: None,
 	};
 	let config_b = ParachainClientConfig {
 		name: format!("9188"),
@@ -122,6 +125,9 @@ async fn setup_clients() -> (ParachainClient<DefaultConfig>, ParachainClient<Def
 		channel_whitelist: vec![],
 		finality_protocol: FinalityProtocol::Grandpa,
 		key_type: "sr25519".to_string(),
+		standalone: false,
+		revision_number: 0,
+		client_refresh_fraction: None,
 	};
 
 	let mut chain_a = ParachainClient::<DefaultConfig>::new(config_a).await.unwrap();
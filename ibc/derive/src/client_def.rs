@@ -247,6 +247,39 @@ impl State {
 		}
 	}
 
+	fn impl_fn_client_state_after_proof(&self) -> proc_macro2::TokenStream {
+		let crate_ = &self.crate_ident;
+		let trait_ = &self.current_impl_trait;
+		let error = &self.current_impl_error;
+		let client_state_trait = &self.client_state_trait;
+		let cases = self.clients.iter().map(|client| {
+			let variant_ident = &client.variant_ident;
+			let attrs = &client.attrs;
+			quote! {
+				#(#attrs)*
+				Self::#variant_ident(client) => {
+					let client_type = #client_state_trait::client_type(client_state).to_owned();
+					let client_state = #crate_::downcast!(client_state => Self::ClientState::#variant_ident)
+						.ok_or_else(|| #error::client_args_type_mismatch(client_type))?;
+
+					let client_state = #trait_::client_state_after_proof(client, client_state)?;
+					Ok(client_state.map(Self::ClientState::#variant_ident))
+				}
+			}
+		});
+
+		quote! {
+			fn client_state_after_proof(
+				&self,
+				client_state: &Self::ClientState,
+			) -> ::core::result::Result<::core::option::Option<Self::ClientState>, #error> {
+				match self {
+					#(#cases)*
+				}
+			}
+		}
+	}
+
 	fn impl_fn_verify_client_consensus_state(&self) -> proc_macro2::TokenStream {
 		let crate_ = &self.crate_ident;
 		let trait_ = &self.current_impl_trait;
@@ -699,6 +732,7 @@ impl State {
 		let fn_update_state_on_misbehaviour = self.impl_fn_update_state_on_misbehaviour();
 		let fn_check_for_misbehaviour = self.impl_fn_check_for_misbehaviour();
 		let fn_verify_upgrade_and_update_state = self.impl_fn_verify_upgrade_and_update_state();
+		let fn_client_state_after_proof = self.impl_fn_client_state_after_proof();
 		let fn_verify_client_consensus_state = self.impl_fn_verify_client_consensus_state();
 		let fn_verify_connection_state = self.impl_fn_verify_connection_state();
 		let fn_verify_channel_state = self.impl_fn_verify_channel_state();
@@ -719,6 +753,7 @@ impl State {
 				#fn_update_state_on_misbehaviour
 				#fn_check_for_misbehaviour
 				#fn_verify_upgrade_and_update_state
+				#fn_client_state_after_proof
 				#fn_verify_client_consensus_state
 				#fn_verify_connection_state
 				#fn_verify_channel_state
@@ -133,12 +133,13 @@ impl State {
 					let upgrade_options = #crate_::downcast!(upgrade_options => Self::UpgradeOptions::#variant_ident)
 						.expect(&format!("upgrade options should be {}", stringify!(#variant_ident)));
 
-					Self::#variant_ident(#trait_::upgrade(
+					#trait_::upgrade(
 						state,
 						upgrade_height,
 						upgrade_options,
 						chain_id,
-					))
+					)
+					.map(Self::#variant_ident)
 				}
 			}
 		});
@@ -149,7 +150,7 @@ impl State {
 				upgrade_height: #crate_::core::ics02_client::height::Height,
 				upgrade_options: Self::UpgradeOptions,
 				chain_id: #crate_::core::ics24_host::identifier::ChainId,
-			) -> Self {
+			) -> Result<Self, #crate_::core::ics02_client::error::Error> {
 				match self {
 					#(#cases)*
 				}
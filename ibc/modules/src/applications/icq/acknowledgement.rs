@@ -0,0 +1,65 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{error::Error, packet::QueryResponse};
+use crate::prelude::*;
+use core::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+/// A string constant included in error acknowledgements.
+/// NOTE: Changing this const is state machine breaking as acknowledgements are written into state
+pub const ACK_ERR_STR: &str = "error handling query packet on host chain: see events for details";
+
+/// Interchain query acknowledgement, the responses are in the same order as the requests in the
+/// packet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Acknowledgement {
+	/// Responses to the query requests
+	Result(Vec<QueryResponse>),
+	/// Error Acknowledgement
+	Error(String),
+}
+
+impl Acknowledgement {
+	pub fn success(responses: Vec<QueryResponse>) -> Self {
+		Self::Result(responses)
+	}
+
+	pub fn from_error(err: Error) -> Self {
+		Self::Error(format!("{}: {}", ACK_ERR_STR, err))
+	}
+
+	pub fn is_successful(&self) -> bool {
+		matches!(self, Self::Result(_))
+	}
+
+	/// The json encoding of this acknowledgement, this is what gets written to state.
+	pub fn to_bytes(&self) -> Vec<u8> {
+		serde_json::to_vec(self).expect("acknowledgement serialization cannot fail")
+	}
+
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+		serde_json::from_slice(bytes).map_err(|_| Error::ack_deserialization())
+	}
+}
+
+impl Display for Acknowledgement {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		match self {
+			Acknowledgement::Result(responses) => write!(f, "{} query responses", responses.len()),
+			Acknowledgement::Error(err_str) => write!(f, "{}", err_str),
+		}
+	}
+}
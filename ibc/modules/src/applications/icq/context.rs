@@ -0,0 +1,374 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::error::Error;
+use crate::{
+	applications::icq::{
+		acknowledgement::Acknowledgement,
+		events::{AckEvent, RecvEvent, TimeoutEvent},
+		packet::{InterchainQueryPacketData, QueryRequest, QueryResponse},
+	},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			msgs::acknowledgement::Acknowledgement as GenericAcknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::ModuleOutputBuilder,
+	},
+	prelude::*,
+	signer::Signer,
+};
+
+/// Functional dependencies of the interchain queries application
+pub trait InterchainQueryContext {
+	/// Returns the port the host side of this application is bound to
+	fn get_port(&self) -> Result<PortId, Error>;
+
+	/// Controller side check, returns true if queries are sent from `port_id`. Acknowledgements
+	/// received on a controller port are delivered to the module that bound it.
+	fn is_controller_port(&self, port_id: &PortId) -> bool;
+
+	/// Host side check, queries for paths that are not allowed are rejected without being executed
+	fn is_query_allowed(&self, request: &QueryRequest) -> bool;
+
+	/// Host side execution of a single query request
+	fn query(&self, request: &QueryRequest) -> Result<QueryResponse, Error>;
+}
+
+fn validate_icq_channel_params(
+	ctx: &impl InterchainQueryContext,
+	order: Order,
+	port_id: &PortId,
+	version: &Version,
+) -> Result<(), Error> {
+	if order != Order::Unordered {
+		return Err(Error::channel_not_unordered(order))
+	}
+
+	let host_port = ctx.get_port()?;
+	if port_id != &host_port && !ctx.is_controller_port(port_id) {
+		return Err(Error::invalid_port(port_id.clone(), host_port))
+	}
+
+	if version != &Version::icq() {
+		return Err(Error::invalid_version(version.clone()))
+	}
+
+	Ok(())
+}
+
+fn validate_counterparty_version(counterparty_version: &Version) -> Result<(), Error> {
+	if counterparty_version == &Version::icq() {
+		Ok(())
+	} else {
+		Err(Error::invalid_counterparty_version(counterparty_version.clone()))
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_init(
+	ctx: &impl InterchainQueryContext,
+	_output: &mut ModuleOutputBuilder,
+	order: Order,
+	_connection_hops: &[ConnectionId],
+	port_id: &PortId,
+	_channel_id: &ChannelId,
+	_counterparty: &Counterparty,
+	version: &Version,
+) -> Result<(), Error> {
+	validate_icq_channel_params(ctx, order, port_id, version)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_try(
+	ctx: &impl InterchainQueryContext,
+	_output: &mut ModuleOutputBuilder,
+	order: Order,
+	_connection_hops: &[ConnectionId],
+	port_id: &PortId,
+	_channel_id: &ChannelId,
+	_counterparty: &Counterparty,
+	version: &Version,
+	counterparty_version: &Version,
+) -> Result<Version, Error> {
+	validate_icq_channel_params(ctx, order, port_id, version)?;
+	validate_counterparty_version(counterparty_version)?;
+	Ok(Version::icq())
+}
+
+pub fn on_chan_open_ack(
+	_ctx: &impl InterchainQueryContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+	counterparty_version: &Version,
+) -> Result<(), Error> {
+	validate_counterparty_version(counterparty_version)
+}
+
+pub fn on_chan_open_confirm(
+	_ctx: &impl InterchainQueryContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Ok(())
+}
+
+pub fn on_chan_close_init(
+	_ctx: &impl InterchainQueryContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Err(Error::cant_close_channel())
+}
+
+pub fn on_chan_close_confirm(
+	_ctx: &impl InterchainQueryContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Ok(())
+}
+
+fn process_recv_packet(
+	ctx: &impl InterchainQueryContext,
+	packet: &Packet,
+	data: &InterchainQueryPacketData,
+) -> Result<Vec<QueryResponse>, Error> {
+	// Controller ports only send queries, they never execute them
+	let host_port = ctx.get_port()?;
+	if packet.destination_port != host_port {
+		return Err(Error::invalid_port(packet.destination_port.clone(), host_port))
+	}
+
+	if data.requests.is_empty() {
+		return Err(Error::empty_query())
+	}
+
+	// Validate all requests before executing any of them
+	for request in &data.requests {
+		if !ctx.is_query_allowed(request) {
+			return Err(Error::query_not_allowed(request.path.clone()))
+		}
+	}
+
+	data.requests.iter().map(|request| ctx.query(request)).collect()
+}
+
+/// Host side handling of a query packet, executes the queries and returns the acknowledgement
+/// that should be written for the packet.
+pub fn on_recv_packet(
+	ctx: &impl InterchainQueryContext,
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+	_relayer: &Signer,
+) -> Acknowledgement {
+	let data = match serde_json::from_slice::<InterchainQueryPacketData>(&packet.data) {
+		Ok(data) => data,
+		Err(_) => return Acknowledgement::from_error(Error::packet_data_deserialization()),
+	};
+
+	let ack = match process_recv_packet(ctx, packet, &data) {
+		Ok(responses) => Acknowledgement::success(responses),
+		Err(e) => Acknowledgement::from_error(e),
+	};
+
+	output.emit(RecvEvent { requests: data.requests.len(), success: ack.is_successful() }.into());
+
+	ack
+}
+
+/// Controller side handling of a query acknowledgement, returns the original requests along with
+/// the decoded acknowledgement so they can be handed to the querying module.
+pub fn on_acknowledgement_packet(
+	_ctx: &impl InterchainQueryContext,
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+	acknowledgement: &GenericAcknowledgement,
+	_relayer: &Signer,
+) -> Result<(Vec<QueryRequest>, Acknowledgement), Error> {
+	let data = serde_json::from_slice::<InterchainQueryPacketData>(&packet.data)
+		.map_err(|_| Error::packet_data_deserialization())?;
+
+	let acknowledgement = Acknowledgement::from_bytes(acknowledgement.as_ref())?;
+
+	output.emit(
+		AckEvent { sequence: packet.sequence, acknowledgement: acknowledgement.clone() }.into(),
+	);
+
+	Ok((data.requests, acknowledgement))
+}
+
+/// Controller side handling of a query packet timeout, returns the requests that timed out.
+pub fn on_timeout_packet(
+	_ctx: &impl InterchainQueryContext,
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+	_relayer: &Signer,
+) -> Result<Vec<QueryRequest>, Error> {
+	let data = serde_json::from_slice::<InterchainQueryPacketData>(&packet.data)
+		.map_err(|_| Error::packet_data_deserialization())?;
+
+	output.emit(TimeoutEvent { sequence: packet.sequence }.into());
+
+	Ok(data.requests)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		applications::icq::{MODULE_ID_STR, PORT_ID_STR},
+		core::{ics04_channel::packet::Sequence, ics26_routing::context::ModuleId},
+		handler::HandlerOutput,
+		mock::context::MockContext,
+		test_utils::get_dummy_account_id,
+	};
+
+	fn host() -> MockContext {
+		let ctx = MockContext::default();
+		{
+			let mut store = ctx.ibc_store.lock().unwrap();
+			store.icq_allowed_paths.push("/store/bank".to_string());
+			store.icq_store.insert(b"alice".to_vec(), b"100".to_vec());
+		}
+		ctx
+	}
+
+	fn query_packet(requests: Vec<QueryRequest>) -> Packet {
+		Packet {
+			sequence: Sequence::from(1),
+			source_port: PORT_ID_STR.parse().unwrap(),
+			destination_port: PORT_ID_STR.parse().unwrap(),
+			data: serde_json::to_vec(&InterchainQueryPacketData { requests }).unwrap(),
+			..Default::default()
+		}
+	}
+
+	fn request(path: &str, key: &[u8]) -> QueryRequest {
+		QueryRequest { path: path.to_string(), data: key.to_vec(), height: 0, prove: false }
+	}
+
+	#[test]
+	fn host_executes_allowed_queries() {
+		let host = host();
+		let packet =
+			query_packet(vec![request("/store/bank", b"alice"), request("/store/bank", b"bob")]);
+		let mut output = HandlerOutput::builder();
+		let ack = on_recv_packet(&host, &mut output, &packet, &get_dummy_account_id());
+
+		let responses = match ack {
+			Acknowledgement::Result(responses) => responses,
+			Acknowledgement::Error(e) => panic!("query failed: {}", e),
+		};
+		assert_eq!(responses.len(), 2);
+		assert_eq!(responses[0].value, b"100".to_vec());
+		assert_eq!(responses[0].height, host.latest_height().revision_height);
+		assert!(responses[1].value.is_empty());
+		assert_eq!(output.with_result(()).events.len(), 1);
+	}
+
+	#[test]
+	fn host_rejects_packet_with_disallowed_query() {
+		let host = host();
+		let packet = query_packet(vec![
+			request("/store/bank", b"alice"),
+			request("/store/staking", b"alice"),
+		]);
+		let mut output = HandlerOutput::builder();
+		let ack = on_recv_packet(&host, &mut output, &packet, &get_dummy_account_id());
+		assert!(!ack.is_successful());
+
+		let packet = query_packet(vec![]);
+		let ack = on_recv_packet(&host, &mut output, &packet, &get_dummy_account_id());
+		assert!(!ack.is_successful());
+	}
+
+	#[test]
+	fn controller_ports_do_not_execute_queries() {
+		let mut host = host();
+		let controller_port: PortId = "querier".parse().unwrap();
+		host.scope_port_to_module(
+			controller_port.clone(),
+			ModuleId::new(MODULE_ID_STR.into()).unwrap(),
+		);
+		let mut packet = query_packet(vec![request("/store/bank", b"alice")]);
+		packet.destination_port = controller_port;
+		let mut output = HandlerOutput::builder();
+		let ack = on_recv_packet(&host, &mut output, &packet, &get_dummy_account_id());
+		assert!(!ack.is_successful());
+	}
+
+	#[test]
+	fn controller_decodes_acknowledgement() {
+		let host = host();
+		let packet = query_packet(vec![request("/store/bank", b"alice")]);
+		let mut output = HandlerOutput::builder();
+		let ack = on_recv_packet(&host, &mut output, &packet, &get_dummy_account_id());
+
+		let controller = MockContext::default();
+		let generic_ack = GenericAcknowledgement::from_bytes(ack.to_bytes());
+		let (requests, decoded) = on_acknowledgement_packet(
+			&controller,
+			&mut output,
+			&packet,
+			&generic_ack,
+			&get_dummy_account_id(),
+		)
+		.unwrap();
+		assert_eq!(requests, vec![request("/store/bank", b"alice")]);
+		assert_eq!(decoded, ack);
+	}
+
+	#[test]
+	fn channel_handshake_requires_icq_version_and_port() {
+		let mut ctx = MockContext::default();
+		let mut output = HandlerOutput::builder();
+		let port_id: PortId = PORT_ID_STR.parse().unwrap();
+		let controller_port: PortId = "querier".parse().unwrap();
+		let channel_id = ChannelId::default();
+		let counterparty = Counterparty::new(port_id.clone(), None);
+
+		let mut open_init =
+			|ctx: &MockContext, order: Order, port_id: &PortId, version: Version| {
+				on_chan_open_init(
+					ctx,
+					&mut output,
+					order,
+					&[],
+					port_id,
+					&channel_id,
+					&counterparty,
+					&version,
+				)
+			};
+		assert!(open_init(&ctx, Order::Unordered, &port_id, Version::icq()).is_ok());
+		assert!(open_init(&ctx, Order::Unordered, &port_id, Version::ics20()).is_err());
+		assert!(open_init(&ctx, Order::Ordered, &port_id, Version::icq()).is_err());
+
+		// channels can only be opened on controller ports bound to the icq module
+		assert!(open_init(&ctx, Order::Unordered, &controller_port, Version::icq()).is_err());
+		ctx.scope_port_to_module(
+			controller_port.clone(),
+			ModuleId::new(MODULE_ID_STR.into()).unwrap(),
+		);
+		assert!(open_init(&ctx, Order::Unordered, &controller_port, Version::icq()).is_ok());
+	}
+}
@@ -0,0 +1,67 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	core::{
+		ics04_channel::{channel::Order, Version},
+		ics24_host::identifier::PortId,
+	},
+	prelude::*,
+};
+use flex_error::define_error;
+
+define_error! {
+	#[derive(Debug, PartialEq, Eq)]
+	Error {
+		ChannelNotUnordered
+			{ order: Order }
+			| e | { format_args!("expected '{0}' channel, got '{1}'", Order::Unordered, e.order) },
+
+		InvalidPort
+			{ port_id: PortId, exp_port_id: PortId }
+			| e | { format_args!("invalid port: '{0}', expected '{1}'", e.port_id, e.exp_port_id) },
+
+		InvalidVersion
+			{ version: Version }
+			| e | { format_args!("expected version '{0}', got '{1}'", Version::icq(), e.version) },
+
+		InvalidCounterpartyVersion
+			{ version: Version }
+			| e | { format_args!("expected counterparty version '{0}', got '{1}'", Version::icq(), e.version) },
+
+		CantCloseChannel
+			| _ | { "channel cannot be closed" },
+
+		PacketDataDeserialization
+			| _ | { "failed to deserialize packet data" },
+
+		AckDeserialization
+			| _ | { "failed to deserialize acknowledgement" },
+
+		EmptyQuery
+			| _ | { "packet contains no query requests" },
+
+		QueryNotAllowed
+			{ path: String }
+			| e | { format_args!("query path '{0}' is not allowed by the host", e.path) },
+
+		UnsupportedHeight
+			{ height: u64 }
+			| e | { format_args!("query height {0} is not supported by the host, only the latest height (0) is", e.height) },
+
+		Query
+			{ reason: String }
+			| e | { format_args!("query failed: {0}", e.reason) },
+	}
+}
@@ -0,0 +1,96 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	applications::icq::{acknowledgement::Acknowledgement, MODULE_ID_STR},
+	core::ics04_channel::packet::Sequence,
+	events::ModuleEvent,
+	prelude::*,
+};
+
+const EVENT_TYPE_PACKET: &str = "icq_packet";
+const EVENT_TYPE_TIMEOUT: &str = "icq_timeout";
+
+pub enum Event {
+	Recv(RecvEvent),
+	Ack(AckEvent),
+	Timeout(TimeoutEvent),
+}
+
+/// Emitted by the host after executing a query packet
+pub struct RecvEvent {
+	pub requests: usize,
+	pub success: bool,
+}
+
+impl From<RecvEvent> for ModuleEvent {
+	fn from(ev: RecvEvent) -> Self {
+		let RecvEvent { requests, success } = ev;
+		Self {
+			kind: EVENT_TYPE_PACKET.to_string(),
+			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![("requests", requests).into(), ("success", success).into()],
+		}
+	}
+}
+
+/// Emitted by the controller when the query responses are received
+pub struct AckEvent {
+	pub sequence: Sequence,
+	pub acknowledgement: Acknowledgement,
+}
+
+impl From<AckEvent> for ModuleEvent {
+	fn from(ev: AckEvent) -> Self {
+		let AckEvent { sequence, acknowledgement } = ev;
+		let attr_label = match acknowledgement {
+			Acknowledgement::Result(_) => "success",
+			Acknowledgement::Error(_) => "error",
+		};
+		Self {
+			kind: EVENT_TYPE_PACKET.to_string(),
+			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![
+				("sequence", sequence).into(),
+				(attr_label, acknowledgement.to_string()).into(),
+			],
+		}
+	}
+}
+
+/// Emitted by the controller when a query packet times out
+pub struct TimeoutEvent {
+	pub sequence: Sequence,
+}
+
+impl From<TimeoutEvent> for ModuleEvent {
+	fn from(ev: TimeoutEvent) -> Self {
+		let TimeoutEvent { sequence } = ev;
+		Self {
+			kind: EVENT_TYPE_TIMEOUT.to_string(),
+			module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![("sequence", sequence).into()],
+		}
+	}
+}
+
+impl From<Event> for ModuleEvent {
+	fn from(ev: Event) -> Self {
+		match ev {
+			Event::Recv(ev) => ev.into(),
+			Event::Ack(ev) => ev.into(),
+			Event::Timeout(ev) => ev.into(),
+		}
+	}
+}
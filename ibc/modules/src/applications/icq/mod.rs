@@ -0,0 +1,30 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Interchain Queries (ICQ) implementation, allows a controller chain to read the state of a host
+//! chain asynchronously. Query requests are sent in packets and executed by the host against an
+//! allowlist of query paths, the responses are returned in the packet acknowledgement.
+pub mod acknowledgement;
+pub mod context;
+pub mod error;
+pub mod events;
+pub mod packet;
+
+/// Module identifier for the ICQ application.
+pub const MODULE_ID_STR: &str = "icq";
+
+/// The port identifier that the ICQ host binds with, controllers send queries from their own ports.
+pub const PORT_ID_STR: &str = "icqhost";
+
+/// ICQ application current version.
+pub const VERSION: &str = "icq-1";
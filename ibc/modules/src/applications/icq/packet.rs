@@ -0,0 +1,50 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A single query to be executed on the host chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryRequest {
+	/// The query path, e.g. `/store/bank/key`, the host decides which paths it serves.
+	pub path: String,
+	/// Path specific query data, e.g. the raw storage key.
+	pub data: Vec<u8>,
+	/// The height to query at, `0` means the latest height.
+	pub height: u64,
+	/// Whether the host should include a proof of the returned value.
+	pub prove: bool,
+}
+
+/// The result of executing a [`QueryRequest`] on the host chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryResponse {
+	/// Host specific result code, `0` means success.
+	pub code: u32,
+	/// The queried value, empty if the value does not exist.
+	pub value: Vec<u8>,
+	/// The host height at which the query was executed.
+	pub height: u64,
+	/// Proof of the queried value, empty if it was not requested.
+	pub proof: Vec<u8>,
+	/// Optional log from the host.
+	pub log: String,
+}
+
+/// Packet data for interchain queries, the host executes all requests or none.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterchainQueryPacketData {
+	pub requests: Vec<QueryRequest>,
+}
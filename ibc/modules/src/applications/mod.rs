@@ -14,4 +14,5 @@
 
 //! Various packet encoding semantics which underpin the various types of transactions.
 
+pub mod icq;
 pub mod transfer;
@@ -60,6 +60,11 @@ pub struct MsgTransfer<C = Coin> {
 	/// Timeout timestamp relative to the current block timestamp.
 	/// The timeout is disabled when set to 0.
 	pub timeout_timestamp: Timestamp,
+	/// optional memo carried in the packet data
+	///
+	/// Not part of the v1 `MsgTransfer` of the pinned ibc-go protos, so it is empty when decoded
+	/// from and dropped when encoded to the raw message.
+	pub memo: String,
 }
 
 impl Msg for MsgTransfer {
@@ -103,6 +108,7 @@ impl TryFrom<RawMsgTransfer> for MsgTransfer {
 			receiver: raw_msg.receiver.parse().map_err(Error::signer)?,
 			timeout_height,
 			timeout_timestamp,
+			memo: String::new(),
 		})
 	}
 }
@@ -167,6 +173,7 @@ pub mod test_util {
 			receiver: address,
 			timeout_timestamp: Timestamp::now().add(Duration::from_secs(10)).unwrap(),
 			timeout_height: Height { revision_number: 0, revision_height: height },
+			memo: String::new(),
 		}
 	}
 }
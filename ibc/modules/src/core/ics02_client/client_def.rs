@@ -16,7 +16,11 @@ use crate::core::ics02_client::{client_consensus::ConsensusState, client_state::
 
 use crate::{
 	core::{
-		ics02_client::{client_message::ClientMessage, context::ClientTypes, error::Error},
+		ics02_client::{
+			client_message::ClientMessage,
+			context::{ClientReader, ClientTypes},
+			error::Error,
+		},
 		ics03_connection::connection::ConnectionEnd,
 		ics04_channel::{
 			channel::ChannelEnd,
@@ -97,6 +101,16 @@ pub trait ClientDef: Clone {
 		proof_upgrade_consensus_state: Vec<u8>,
 	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Error>;
 
+	/// Client state after a proof was verified against `client_state`, used to verify the next
+	/// proof of the message and stored once the message is processed. Only needed by clients
+	/// whose proofs are single use, like solo machines; `None` leaves the client state unchanged.
+	fn client_state_after_proof(
+		&self,
+		_client_state: &Self::ClientState,
+	) -> Result<Option<Self::ClientState>, Error> {
+		Ok(None)
+	}
+
 	/// Verification functions as specified in:
 	/// <https://github.com/cosmos/ibc/tree/master/spec/core/ics-002-client-semantics>
 	///
@@ -229,3 +243,42 @@ pub trait ClientDef: Clone {
 		sequence: Sequence,
 	) -> Result<(), Error>;
 }
+
+/// Client state the proofs of a single message are verified against, see
+/// [`ClientDef::client_state_after_proof`].
+#[derive(Clone, Debug)]
+pub struct ProofVerifyingClient<C> {
+	pub client_id: ClientId,
+	pub client_state: C,
+	changed: bool,
+}
+
+impl<C: ClientState> ProofVerifyingClient<C> {
+	pub fn new(client_id: ClientId, client_state: C) -> Self {
+		Self { client_id, client_state, changed: false }
+	}
+
+	/// Loads the client state of `client_id` from the host.
+	pub fn load<Ctx>(ctx: &Ctx, client_id: &ClientId) -> Result<Self, Error>
+	where
+		Ctx: ReaderContext<AnyClientState = C>,
+	{
+		Ok(Self::new(client_id.clone(), ctx.client_state(client_id)?))
+	}
+
+	/// Advances the client state past a verified proof.
+	pub fn proof_verified(&mut self) -> Result<(), Error> {
+		if let Some(client_state) =
+			self.client_state.client_def().client_state_after_proof(&self.client_state)?
+		{
+			self.client_state = client_state;
+			self.changed = true;
+		}
+		Ok(())
+	}
+
+	/// The client state to store once the message is processed, if verifying proofs changed it.
+	pub fn into_update(self) -> Option<(ClientId, C)> {
+		self.changed.then(|| (self.client_id, self.client_state))
+	}
+}
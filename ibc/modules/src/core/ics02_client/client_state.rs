@@ -13,7 +13,10 @@
 // limitations under the License.
 
 use crate::{
-	core::{ics02_client::client_def::ClientDef, ics24_host::identifier::ChainId},
+	core::{
+		ics02_client::{client_def::ClientDef, error::Error},
+		ics24_host::identifier::ChainId,
+	},
 	prelude::*,
 	Height,
 };
@@ -60,7 +63,7 @@ pub trait ClientState: Clone + Debug + Send + Sync {
 		upgrade_height: Height,
 		upgrade_options: Self::UpgradeOptions,
 		chain_id: ChainId,
-	) -> Self;
+	) -> Result<Self, Error>;
 
 	/// Helper function to verify the upgrade client procedure.
 	fn expired(&self, elapsed: Duration) -> bool;
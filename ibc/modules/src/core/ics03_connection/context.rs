@@ -72,7 +72,9 @@ pub trait ConnectionReader {
 /// A context supplying all the necessary write-only dependencies (i.e., storage writing facility)
 /// for processing any `ConnectionMsg`.
 pub trait ConnectionKeeper {
-	fn store_connection_result(&mut self, result: ConnectionResult) -> Result<(), Error> {
+	/// Client state updates of the result are stored by the caller, see
+	/// [`ConnectionResult::client_state_update`].
+	fn store_connection_result<C>(&mut self, result: ConnectionResult<C>) -> Result<(), Error> {
 		self.store_connection(result.connection_id.clone(), &result.connection_end)?;
 
 		// If we generated an identifier, increase the counter & associate this new identifier
@@ -17,7 +17,7 @@
 use crate::{
 	core::{
 		ics03_connection::{connection::ConnectionEnd, error::Error, msgs::ConnectionMsg},
-		ics24_host::identifier::ConnectionId,
+		ics24_host::identifier::{ClientId, ConnectionId},
 		ics26_routing::context::ReaderContext,
 	},
 	handler::HandlerOutput,
@@ -43,7 +43,7 @@ pub enum ConnectionIdState {
 }
 
 #[derive(Clone, Debug)]
-pub struct ConnectionResult {
+pub struct ConnectionResult<C> {
 	/// The identifier for the connection which the handler processed. Typically this represents
 	/// the newly-generated connection id (e.g., when processing `MsgConnectionOpenInit`) or
 	/// an existing connection id (e.g., for `MsgConnectionOpenAck`).
@@ -54,6 +54,9 @@ pub struct ConnectionResult {
 
 	/// The connection end, which the handler produced as a result of processing the message.
 	pub connection_end: ConnectionEnd,
+
+	/// The client state changed by verifying the message proofs, if any.
+	pub client_state_update: Option<(ClientId, C)>,
 }
 
 /// General entry point for processing any type of message related to the ICS3 connection open
@@ -61,7 +64,7 @@ pub struct ConnectionResult {
 pub fn dispatch<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: ConnectionMsg<Ctx>,
-) -> Result<HandlerOutput<ConnectionResult>, Error> {
+) -> Result<HandlerOutput<ConnectionResult<Ctx::AnyClientState>>, Error> {
 	match msg {
 		ConnectionMsg::ConnectionOpenInit(msg) => conn_open_init::process(ctx, msg),
 		ConnectionMsg::ConnectionOpenTry(msg) => conn_open_try::process::<_>(ctx, *msg),
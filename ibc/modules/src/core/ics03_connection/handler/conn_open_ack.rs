@@ -16,6 +16,7 @@
 
 use crate::{
 	core::{
+		ics02_client::client_def::ProofVerifyingClient,
 		ics03_connection::{
 			connection::{ConnectionEnd, Counterparty, State},
 			error::Error,
@@ -39,7 +40,7 @@ use crate::{
 pub(crate) fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: MsgConnectionOpenAck<Ctx>,
-) -> HandlerResult<ConnectionResult, Error> {
+) -> HandlerResult<ConnectionResult<Ctx::AnyClientState>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	// Check the client's (consensus state) proof height if it consensus proof is provided
@@ -104,8 +105,12 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	ctx.validate_self_client(&client_state).map_err(Error::ics02_client)?;
 
+	let mut client =
+		ProofVerifyingClient::load(ctx, conn_end.client_id()).map_err(Error::ics02_client)?;
+
 	verify_connection_proof::<Ctx>(
 		ctx,
+		&mut client,
 		msg.proofs.height(),
 		&conn_end,
 		&expected_conn,
@@ -115,6 +120,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	verify_client_proof::<Ctx>(
 		ctx,
+		&mut client,
 		msg.proofs.height(),
 		&conn_end,
 		client_state,
@@ -122,7 +128,13 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		client_proof,
 	)?;
 
-	verify_consensus_proof::<Ctx>(ctx, msg.proofs.height(), &conn_end, &consensus_proof)?;
+	verify_consensus_proof::<Ctx>(
+		ctx,
+		&mut client,
+		msg.proofs.height(),
+		&conn_end,
+		&consensus_proof,
+	)?;
 
 	output.log("success: connection verification passed");
 
@@ -138,6 +150,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		connection_id: msg.connection_id,
 		connection_id_state: ConnectionIdState::Reused,
 		connection_end: conn_end,
+		client_state_update: client.into_update(),
 	};
 
 	output.emit(IbcEvent::OpenAckConnection(event_attributes.into()));
@@ -303,7 +316,7 @@ mod tests {
 					assert!(!proto_output.events.is_empty()); // Some events must exist.
 
 					// The object in the output is a ConnectionEnd, should have OPEN state.
-					let res: ConnectionResult = proto_output.result;
+					let res = proto_output.result;
 					assert_eq!(res.connection_end.state().clone(), State::Open);
 
 					// assert that counterparty connection id is correct
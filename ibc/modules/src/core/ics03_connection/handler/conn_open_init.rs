@@ -34,7 +34,7 @@ use crate::{
 pub(crate) fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: MsgConnectionOpenInit,
-) -> HandlerResult<ConnectionResult, Error> {
+) -> HandlerResult<ConnectionResult<Ctx::AnyClientState>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	let minimum_delay_period = ctx.minimum_delay_period();
@@ -85,6 +85,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		connection_id: conn_id,
 		connection_id_state: ConnectionIdState::Generated,
 		connection_end: new_connection_end,
+		client_state_update: None,
 	};
 
 	output.emit(IbcEvent::OpenInitConnection(event_attributes.into()));
@@ -189,7 +190,7 @@ mod tests {
 					assert!(!proto_output.events.is_empty()); // Some events must exist.
 
 					// The object in the output is a ConnectionEnd, should have init state.
-					let res: ConnectionResult = proto_output.result;
+					let res = proto_output.result;
 					assert_eq!(res.connection_end.state().clone(), State::Init);
 
 					for e in proto_output.events.iter() {
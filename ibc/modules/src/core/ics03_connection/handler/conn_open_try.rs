@@ -16,6 +16,7 @@
 
 use crate::{
 	core::{
+		ics02_client::client_def::ProofVerifyingClient,
 		ics03_connection::{
 			connection::{ConnectionEnd, Counterparty, State},
 			error::Error,
@@ -40,7 +41,7 @@ use crate::{
 pub(crate) fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: MsgConnectionOpenTry<Ctx>,
-) -> HandlerResult<ConnectionResult, Error> {
+) -> HandlerResult<ConnectionResult<Ctx::AnyClientState>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	let minimum_delay_period = ctx.minimum_delay_period();
@@ -98,8 +99,12 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	ctx.validate_self_client(&client_state).map_err(Error::ics02_client)?;
 
+	let mut client =
+		ProofVerifyingClient::load(ctx, &msg.client_id).map_err(Error::ics02_client)?;
+
 	verify_connection_proof::<_>(
 		ctx,
+		&mut client,
 		msg.proofs.height(),
 		&new_connection_end,
 		&expected_conn,
@@ -109,6 +114,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	verify_client_proof::<_>(
 		ctx,
+		&mut client,
 		msg.proofs.height(),
 		&new_connection_end,
 		client_state,
@@ -116,7 +122,13 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		client_proof,
 	)?;
 
-	verify_consensus_proof::<_>(ctx, msg.proofs.height(), &new_connection_end, &consensus_proof)?;
+	verify_consensus_proof::<_>(
+		ctx,
+		&mut client,
+		msg.proofs.height(),
+		&new_connection_end,
+		&consensus_proof,
+	)?;
 
 	// Transition the connection end to the new state & pick a version.
 	new_connection_end.set_state(State::TryOpen);
@@ -142,6 +154,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		connection_id: conn_id,
 		connection_id_state: ConnectionIdState::Generated,
 		connection_end: new_connection_end,
+		client_state_update: client.into_update(),
 	};
 
 	output.emit(IbcEvent::OpenTryConnection(event_attributes.into()));
@@ -272,7 +285,7 @@ mod tests {
 					assert!(!proto_output.events.is_empty()); // Some events must exist.
 
 					// The object in the output is a ConnectionEnd, should have TryOpen state.
-					let res: ConnectionResult = proto_output.result;
+					let res = proto_output.result;
 					assert_eq!(res.connection_end.state().clone(), State::TryOpen);
 
 					for e in proto_output.events.iter() {
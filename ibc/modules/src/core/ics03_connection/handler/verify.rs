@@ -15,7 +15,9 @@
 //! ICS3 verification functions, common across all four handlers of ICS3.
 
 use crate::core::ics02_client::{
-	client_consensus::ConsensusState, client_def::ClientDef, client_state::ClientState,
+	client_consensus::ConsensusState,
+	client_def::{ClientDef, ProofVerifyingClient},
+	client_state::ClientState,
 };
 
 use crate::{
@@ -39,14 +41,14 @@ pub struct ConsensusProofwithHostConsensusStateProof {
 /// which created this proof). This object must match the state of `expected_conn`.
 pub fn verify_connection_proof<Ctx: ReaderContext>(
 	ctx: &Ctx,
+	client: &mut ProofVerifyingClient<Ctx::AnyClientState>,
 	height: Height,
 	connection_end: &ConnectionEnd,
 	expected_conn: &ConnectionEnd,
 	proof_height: Height,
 	proof: &CommitmentProofBytes,
 ) -> Result<(), Error> {
-	// Fetch the client state (IBC client on the local/host chain).
-	let client_state = ctx.client_state(connection_end.client_id()).map_err(Error::ics02_client)?;
+	let client_state = &client.client_state;
 
 	// The client must not be frozen.
 	if client_state.is_frozen() {
@@ -72,7 +74,7 @@ pub fn verify_connection_proof<Ctx: ReaderContext>(
 		.verify_connection_state(
 			ctx,
 			connection_end.client_id(),
-			&client_state,
+			client_state,
 			height,
 			connection_end.counterparty().prefix(),
 			proof,
@@ -80,7 +82,9 @@ pub fn verify_connection_proof<Ctx: ReaderContext>(
 			connection_id,
 			expected_conn,
 		)
-		.map_err(Error::verify_connection_state)
+		.map_err(Error::verify_connection_state)?;
+
+	client.proof_verified().map_err(Error::ics02_client)
 }
 
 /// Verifies the client `proof` from a connection handshake message, typically from a
@@ -92,14 +96,14 @@ pub fn verify_connection_proof<Ctx: ReaderContext>(
 /// `proof` is correct.
 pub fn verify_client_proof<Ctx: ReaderContext>(
 	ctx: &Ctx,
+	client: &mut ProofVerifyingClient<Ctx::AnyClientState>,
 	height: Height,
 	connection_end: &ConnectionEnd,
 	expected_client_state: Ctx::AnyClientState,
 	proof_height: Height,
 	proof: &CommitmentProofBytes,
 ) -> Result<(), Error> {
-	// Fetch the local client state (IBC client running on the host chain).
-	let client_state = ctx.client_state(connection_end.client_id()).map_err(Error::ics02_client)?;
+	let client_state = &client.client_state;
 
 	if client_state.is_frozen() {
 		return Err(Error::frozen_client(connection_end.client_id().clone()))
@@ -114,7 +118,7 @@ pub fn verify_client_proof<Ctx: ReaderContext>(
 	client_def
 		.verify_client_full_state(
 			ctx,
-			&client_state,
+			client_state,
 			height,
 			connection_end.counterparty().prefix(),
 			proof,
@@ -124,17 +128,19 @@ pub fn verify_client_proof<Ctx: ReaderContext>(
 		)
 		.map_err(|e| {
 			Error::client_state_verification_failure(connection_end.client_id().clone(), e)
-		})
+		})?;
+
+	client.proof_verified().map_err(Error::ics02_client)
 }
 
 pub fn verify_consensus_proof<Ctx: ReaderContext>(
 	ctx: &Ctx,
+	client: &mut ProofVerifyingClient<Ctx::AnyClientState>,
 	height: Height,
 	connection_end: &ConnectionEnd,
 	proof: &ConsensusProof,
 ) -> Result<(), Error> {
-	// Fetch the client state (IBC client on the local chain).
-	let client_state = ctx.client_state(connection_end.client_id()).map_err(Error::ics02_client)?;
+	let client_state = &client.client_state;
 
 	if client_state.is_frozen() {
 		return Err(Error::frozen_client(connection_end.client_id().clone()))
@@ -144,7 +150,7 @@ pub fn verify_consensus_proof<Ctx: ReaderContext>(
 		.consensus_state(connection_end.client_id(), height)
 		.map_err(|e| Error::consensus_state_verification_failure(height, e))?;
 
-	let client = client_state.client_def();
+	let client_def = client_state.client_def();
 
 	// todo: we can remove this hack, once this is merged https://github.com/cosmos/ibc/pull/839
 	let (consensus_proof, expected_consensus) = match ctx.host_client_type() {
@@ -175,10 +181,10 @@ pub fn verify_consensus_proof<Ctx: ReaderContext>(
 		),
 	};
 
-	client
+	client_def
 		.verify_client_consensus_state(
 			ctx,
-			&client_state,
+			client_state,
 			height,
 			connection_end.counterparty().prefix(),
 			&consensus_proof,
@@ -189,7 +195,7 @@ pub fn verify_consensus_proof<Ctx: ReaderContext>(
 		)
 		.map_err(|e| Error::consensus_state_verification_failure(proof.height(), e))?;
 
-	Ok(())
+	client.proof_verified().map_err(Error::ics02_client)
 }
 
 /// Checks that `claimed_height` is within normal bounds, i.e., fresh enough so that the chain has
@@ -119,7 +119,9 @@ pub trait ChannelReader {
 /// A context supplying all the necessary write-only dependencies (i.e., storage writing facility)
 /// for processing any `ChannelMsg`.
 pub trait ChannelKeeper {
-	fn store_channel_result(&mut self, result: ChannelResult) -> Result<(), Error> {
+	/// Client state updates of the result are stored by the caller, see
+	/// [`ChannelResult::client_state_update`].
+	fn store_channel_result<C>(&mut self, result: ChannelResult<C>) -> Result<(), Error> {
 		// The handler processed this channel & some modifications occurred, store the new end.
 		self.store_channel((result.port_id.clone(), result.channel_id), &result.channel_end)?;
 
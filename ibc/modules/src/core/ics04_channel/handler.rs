@@ -22,7 +22,7 @@ use crate::{
 			msgs::{ChannelMsg, PacketMsg},
 			packet::PacketResult,
 		},
-		ics24_host::identifier::{ChannelId, PortId},
+		ics24_host::identifier::{ChannelId, ClientId, PortId},
 		ics26_routing::context::{
 			Ics26Context, ModuleId, ModuleOutputBuilder, ReaderContext, Router,
 		},
@@ -58,13 +58,19 @@ pub enum ChannelIdState {
 }
 
 #[derive(Clone, Debug)]
-pub struct ChannelResult {
+pub struct ChannelResult<C> {
 	pub port_id: PortId,
 	pub channel_id: ChannelId,
 	pub channel_id_state: ChannelIdState,
 	pub channel_end: ChannelEnd,
+	/// The client state changed by verifying the message proofs, if any.
+	pub client_state_update: Option<(ClientId, C)>,
 }
 
+/// Result of a packet handler that verifies proofs, along with the client state changed by
+/// verifying them, if any.
+pub type VerifiedPacketResult<C> = (PacketResult, Option<(ClientId, C)>);
+
 pub fn channel_validate<Ctx>(ctx: &Ctx, msg: &ChannelMsg) -> Result<ModuleId, Error>
 where
 	Ctx: Ics26Context,
@@ -82,7 +88,7 @@ where
 pub fn channel_dispatch<Ctx>(
 	ctx: &Ctx,
 	msg: &ChannelMsg,
-) -> Result<(HandlerOutputBuilder<()>, ChannelResult), Error>
+) -> Result<(HandlerOutputBuilder<()>, ChannelResult<Ctx::AnyClientState>), Error>
 where
 	Ctx: ReaderContext,
 {
@@ -103,9 +109,9 @@ pub fn channel_callback<Ctx>(
 	ctx: &mut Ctx,
 	module_id: &ModuleId,
 	msg: &ChannelMsg,
-	mut result: ChannelResult,
+	mut result: ChannelResult<Ctx::AnyClientState>,
 	module_output: &mut ModuleOutputBuilder,
-) -> Result<ChannelResult, Error>
+) -> Result<ChannelResult<Ctx::AnyClientState>, Error>
 where
 	Ctx: Ics26Context,
 {
@@ -200,7 +206,7 @@ where
 pub fn packet_dispatch<Ctx>(
 	ctx: &Ctx,
 	msg: &PacketMsg,
-) -> Result<(HandlerOutputBuilder<()>, PacketResult), Error>
+) -> Result<(HandlerOutputBuilder<()>, VerifiedPacketResult<Ctx::AnyClientState>), Error>
 where
 	Ctx: ReaderContext,
 {
@@ -14,12 +14,13 @@
 
 use crate::{
 	core::{
+		ics02_client::client_def::ProofVerifyingClient,
 		ics03_connection::connection::State as ConnectionState,
 		ics04_channel::{
 			channel::{Counterparty, Order, State},
 			error::Error,
 			events::AcknowledgePacket,
-			handler::verify::verify_packet_acknowledgement_proofs,
+			handler::{verify::verify_packet_acknowledgement_proofs, VerifiedPacketResult},
 			msgs::acknowledgement::MsgAcknowledgement,
 			packet::{PacketResult, Sequence},
 		},
@@ -43,7 +44,7 @@ pub struct AckPacketResult {
 pub fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: &MsgAcknowledgement,
-) -> HandlerResult<PacketResult, Error> {
+) -> HandlerResult<VerifiedPacketResult<Ctx::AnyClientState>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	let packet = &msg.packet;
@@ -90,8 +91,11 @@ pub fn process<Ctx: ReaderContext>(
 	}
 
 	// Verify the acknowledgement proof
+	let mut client =
+		ProofVerifyingClient::load(ctx, connection_end.client_id()).map_err(Error::ics02_client)?;
 	verify_packet_acknowledgement_proofs::<Ctx>(
 		ctx,
+		&mut client,
 		msg.proofs.height(),
 		packet,
 		msg.acknowledgement.clone(),
@@ -129,7 +133,7 @@ pub fn process<Ctx: ReaderContext>(
 		packet: packet.clone(),
 	}));
 
-	Ok(output.with_result(result))
+	Ok(output.with_result((result, client.into_update())))
 }
 
 #[cfg(test)]
@@ -16,6 +16,7 @@
 
 use crate::{
 	core::{
+		ics02_client::client_def::ProofVerifyingClient,
 		ics03_connection::connection::State as ConnectionState,
 		ics04_channel::{
 			channel::{ChannelEnd, Counterparty, State},
@@ -34,7 +35,7 @@ use crate::{
 pub(crate) fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: &MsgChannelCloseConfirm,
-) -> HandlerResult<ChannelResult, Error> {
+) -> HandlerResult<ChannelResult<Ctx::AnyClientState>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	// Retrieve the old channel end and validate it against the message.
@@ -78,8 +79,11 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		channel_end.version().clone(),
 	);
 
+	let mut client =
+		ProofVerifyingClient::load(ctx, conn.client_id()).map_err(Error::ics02_client)?;
 	verify_channel_proofs::<Ctx>(
 		ctx,
+		&mut client,
 		msg.proofs.height(),
 		&channel_end,
 		&conn,
@@ -106,6 +110,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		channel_id: msg.channel_id,
 		channel_id_state: ChannelIdState::Reused,
 		channel_end,
+		client_state_update: client.into_update(),
 	};
 
 	output.emit(IbcEvent::CloseConfirmChannel(
@@ -33,7 +33,7 @@ use crate::{
 pub(crate) fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: &MsgChannelCloseInit,
-) -> HandlerResult<ChannelResult, Error> {
+) -> HandlerResult<ChannelResult<Ctx::AnyClientState>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	// Unwrap the old channel end and validate it against the message.
@@ -76,6 +76,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		channel_id: msg.channel_id,
 		channel_id_state: ChannelIdState::Reused,
 		channel_end,
+		client_state_update: None,
 	};
 
 	output.emit(IbcEvent::CloseInitChannel(
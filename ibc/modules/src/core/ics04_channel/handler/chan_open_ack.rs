@@ -16,6 +16,7 @@
 
 use crate::{
 	core::{
+		ics02_client::client_def::ProofVerifyingClient,
 		ics03_connection::connection::State as ConnectionState,
 		ics04_channel::{
 			channel::{ChannelEnd, Counterparty, State},
@@ -34,7 +35,7 @@ use crate::{
 pub(crate) fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: &MsgChannelOpenAck,
-) -> HandlerResult<ChannelResult, Error> {
+) -> HandlerResult<ChannelResult<Ctx::AnyClientState>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	// Unwrap the old channel end and validate it against the message.
@@ -82,9 +83,12 @@ pub(crate) fn process<Ctx: ReaderContext>(
 	// set the counterparty channel id to verify against it
 	channel_end.set_counterparty_channel_id(msg.counterparty_channel_id);
 
-	//2. Verify proofs
+	// 2. Verify proofs
+	let mut client =
+		ProofVerifyingClient::load(ctx, conn.client_id()).map_err(Error::ics02_client)?;
 	verify_channel_proofs::<Ctx>(
 		ctx,
+		&mut client,
 		msg.proofs.height(),
 		&channel_end,
 		&conn,
@@ -112,6 +116,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		channel_id: msg.channel_id,
 		channel_id_state: ChannelIdState::Reused,
 		channel_end,
+		client_state_update: client.into_update(),
 	};
 
 	output.emit(IbcEvent::OpenAckChannel(
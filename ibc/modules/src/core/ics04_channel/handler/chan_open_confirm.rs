@@ -16,6 +16,7 @@
 
 use crate::{
 	core::{
+		ics02_client::client_def::ProofVerifyingClient,
 		ics03_connection::connection::State as ConnectionState,
 		ics04_channel::{
 			channel::{ChannelEnd, Counterparty, State},
@@ -34,7 +35,7 @@ use crate::{
 pub(crate) fn process<Ctx>(
 	ctx: &Ctx,
 	msg: &MsgChannelOpenConfirm,
-) -> HandlerResult<ChannelResult, Error>
+) -> HandlerResult<ChannelResult<Ctx::AnyClientState>, Error>
 where
 	Ctx: ReaderContext,
 {
@@ -80,9 +81,12 @@ where
 		expected_connection_hops,
 		channel_end.version().clone(),
 	);
-	//2. Verify proofs
+	// 2. Verify proofs
+	let mut client =
+		ProofVerifyingClient::load(ctx, conn.client_id()).map_err(Error::ics02_client)?;
 	verify_channel_proofs::<Ctx>(
 		ctx,
+		&mut client,
 		msg.proofs.height(),
 		&channel_end,
 		&conn,
@@ -110,6 +114,7 @@ where
 		channel_id: msg.channel_id,
 		channel_id_state: ChannelIdState::Reused,
 		channel_end,
+		client_state_update: client.into_update(),
 	};
 
 	output.emit(IbcEvent::OpenConfirmChannel(
@@ -16,6 +16,7 @@
 
 use crate::{
 	core::{
+		ics02_client::client_def::ProofVerifyingClient,
 		ics03_connection::connection::State as ConnectionState,
 		ics04_channel::{
 			channel::{ChannelEnd, Counterparty, State},
@@ -35,7 +36,7 @@ use crate::{
 pub(crate) fn process<Ctx>(
 	ctx: &Ctx,
 	msg: &MsgChannelOpenTry,
-) -> HandlerResult<ChannelResult, Error>
+) -> HandlerResult<ChannelResult<Ctx::AnyClientState>, Error>
 where
 	Ctx: ReaderContext,
 {
@@ -84,9 +85,9 @@ where
 	}
 
 	// Proof verification in two steps:
-	// 1. Setup: build the Channel as we expect to find it on the other party.
-	//      the port should be identical with the port we're using; the channel id should not be set
-	//      since the counterparty cannot know yet which ID did we choose.
+	// 1. Setup: build the Channel as we expect to find it on the other party. the port should be
+	//    identical with the port we're using; the channel id should not be set since the
+	//    counterparty cannot know yet which ID did we choose.
 	let expected_counterparty = Counterparty::new(msg.port_id.clone(), None);
 	let counterparty = conn.counterparty();
 	let ccid = counterparty.connection_id().ok_or_else(|| {
@@ -104,8 +105,11 @@ where
 	);
 
 	// 2. Actual proofs are verified now.
+	let mut client =
+		ProofVerifyingClient::load(ctx, conn.client_id()).map_err(Error::ics02_client)?;
 	verify_channel_proofs::<Ctx>(
 		ctx,
+		&mut client,
 		msg.proofs.height(),
 		&new_channel_end,
 		&conn,
@@ -132,6 +136,7 @@ where
 		channel_id_state: ChannelIdState::Generated,
 		channel_id,
 		channel_end: new_channel_end,
+		client_state_update: client.into_update(),
 	};
 
 	output.emit(IbcEvent::OpenTryChannel(
@@ -14,12 +14,13 @@
 
 use crate::{
 	core::{
+		ics02_client::client_def::ProofVerifyingClient,
 		ics03_connection::connection::State as ConnectionState,
 		ics04_channel::{
 			channel::{Counterparty, Order, State},
 			error::Error,
 			events::ReceivePacket,
-			handler::verify::verify_packet_recv_proofs,
+			handler::{verify::verify_packet_recv_proofs, VerifiedPacketResult},
 			msgs::recv_packet::MsgRecvPacket,
 			packet::{Packet, PacketResult, Receipt, Sequence},
 		},
@@ -55,7 +56,7 @@ pub enum RecvPacketResult {
 pub fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: &MsgRecvPacket,
-) -> HandlerResult<PacketResult, Error> {
+) -> HandlerResult<VerifiedPacketResult<Ctx::AnyClientState>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	let packet = &msg.packet;
@@ -94,8 +95,11 @@ pub fn process<Ctx: ReaderContext>(
 		return Err(Error::low_packet_timestamp())
 	}
 
+	let mut client =
+		ProofVerifyingClient::load(ctx, connection_end.client_id()).map_err(Error::ics02_client)?;
 	verify_packet_recv_proofs::<Ctx>(
 		ctx,
+		&mut client,
 		msg.proofs.height(),
 		packet,
 		&connection_end,
@@ -113,7 +117,7 @@ pub fn process<Ctx: ReaderContext>(
 				height: Height::zero(),
 				packet: msg.packet.clone(),
 			}));
-			return Ok(output.with_result(PacketResult::Recv(RecvPacketResult::NoOp)))
+			return Ok(output.with_result((PacketResult::Recv(RecvPacketResult::NoOp), None)))
 		} else if packet.sequence != next_seq_recv {
 			return Err(Error::invalid_packet_sequence(packet.sequence, next_seq_recv))
 		}
@@ -137,7 +141,7 @@ pub fn process<Ctx: ReaderContext>(
 					height: Height::zero(),
 					packet: msg.packet.clone(),
 				}));
-				return Ok(output.with_result(PacketResult::Recv(RecvPacketResult::NoOp)))
+				return Ok(output.with_result((PacketResult::Recv(RecvPacketResult::NoOp), None)))
 			},
 			Err(e) if e.detail() == Error::packet_receipt_not_found(packet.sequence).detail() => {
 				// store a receipt that does not contain any data
@@ -160,7 +164,7 @@ pub fn process<Ctx: ReaderContext>(
 		packet: msg.packet.clone(),
 	}));
 
-	Ok(output.with_result(result))
+	Ok(output.with_result((result, client.into_update())))
 }
 
 #[cfg(test)]
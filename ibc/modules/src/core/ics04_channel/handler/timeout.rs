@@ -14,12 +14,15 @@
 
 use crate::{
 	core::{
-		ics02_client::client_consensus::ConsensusState,
+		ics02_client::{client_consensus::ConsensusState, client_def::ProofVerifyingClient},
 		ics04_channel::{
 			channel::{ChannelEnd, Counterparty, Order, State},
 			error::Error,
 			events::TimeoutPacket,
-			handler::verify::{verify_next_sequence_recv, verify_packet_receipt_absence},
+			handler::{
+				verify::{verify_next_sequence_recv, verify_packet_receipt_absence},
+				VerifiedPacketResult,
+			},
 			msgs::timeout::MsgTimeout,
 			packet::{PacketResult, Sequence},
 		},
@@ -40,7 +43,10 @@ pub struct TimeoutPacketResult {
 	pub channel: Option<ChannelEnd>,
 }
 
-pub fn process<Ctx>(ctx: &Ctx, msg: &MsgTimeout) -> HandlerResult<PacketResult, Error>
+pub fn process<Ctx>(
+	ctx: &Ctx,
+	msg: &MsgTimeout,
+) -> HandlerResult<VerifiedPacketResult<Ctx::AnyClientState>, Error>
 where
 	Ctx: ReaderContext,
 {
@@ -102,12 +108,15 @@ where
 		return Err(Error::incorrect_packet_commitment(packet.sequence))
 	}
 
+	let mut client =
+		ProofVerifyingClient::load(ctx, connection_end.client_id()).map_err(Error::ics02_client)?;
 	let result = if source_channel_end.order_matches(&Order::Ordered) {
 		if packet.sequence < msg.next_sequence_recv {
 			return Err(Error::invalid_packet_sequence(packet.sequence, msg.next_sequence_recv))
 		}
 		verify_next_sequence_recv::<Ctx>(
 			ctx,
+			&mut client,
 			msg.proofs.height(),
 			&connection_end,
 			packet.clone(),
@@ -125,6 +134,7 @@ where
 	} else {
 		verify_packet_receipt_absence::<Ctx>(
 			ctx,
+			&mut client,
 			msg.proofs.height(),
 			&connection_end,
 			packet.clone(),
@@ -146,7 +156,7 @@ where
 		packet: packet.clone(),
 	}));
 
-	Ok(output.with_result(result))
+	Ok(output.with_result((result, client.into_update())))
 }
 
 #[cfg(test)]
@@ -14,6 +14,7 @@
 
 use crate::{
 	core::{
+		ics02_client::client_def::ProofVerifyingClient,
 		ics04_channel::{
 			channel::{ChannelEnd, Counterparty, Order, State},
 			error::Error,
@@ -23,6 +24,7 @@ use crate::{
 				verify::{
 					verify_channel_proofs, verify_next_sequence_recv, verify_packet_receipt_absence,
 				},
+				VerifiedPacketResult,
 			},
 			msgs::timeout_on_close::MsgTimeoutOnClose,
 			packet::PacketResult,
@@ -37,7 +39,7 @@ use crate::{
 pub fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: &MsgTimeoutOnClose,
-) -> HandlerResult<PacketResult, Error> {
+) -> HandlerResult<VerifiedPacketResult<Ctx::AnyClientState>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	let packet = &msg.packet;
@@ -90,8 +92,11 @@ pub fn process<Ctx: ReaderContext>(
 		source_channel_end.version().clone(),
 	);
 
+	let mut client =
+		ProofVerifyingClient::load(ctx, connection_end.client_id()).map_err(Error::ics02_client)?;
 	verify_channel_proofs::<Ctx>(
 		ctx,
+		&mut client,
 		msg.proofs.height(),
 		&source_channel_end,
 		&connection_end,
@@ -108,6 +113,7 @@ pub fn process<Ctx: ReaderContext>(
 		}
 		verify_next_sequence_recv::<Ctx>(
 			ctx,
+			&mut client,
 			msg.proofs.height(),
 			&connection_end,
 			packet.clone(),
@@ -124,6 +130,7 @@ pub fn process<Ctx: ReaderContext>(
 	} else {
 		verify_packet_receipt_absence::<Ctx>(
 			ctx,
+			&mut client,
 			msg.proofs.height(),
 			&connection_end,
 			packet.clone(),
@@ -145,7 +152,7 @@ pub fn process<Ctx: ReaderContext>(
 		packet: packet.clone(),
 	}));
 
-	Ok(output.with_result(result))
+	Ok(output.with_result((result, client.into_update())))
 }
 
 #[cfg(test)]
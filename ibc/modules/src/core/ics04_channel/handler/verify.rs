@@ -15,7 +15,9 @@
 use crate::{
 	core::{
 		ics02_client::{
-			client_consensus::ConsensusState, client_def::ClientDef, client_state::ClientState,
+			client_consensus::ConsensusState,
+			client_def::{ClientDef, ProofVerifyingClient},
+			client_state::ClientState,
 		},
 		ics03_connection::connection::ConnectionEnd,
 		ics04_channel::{
@@ -35,6 +37,7 @@ use crate::{
 /// Entry point for verifying all proofs bundled in any ICS4 message for channel protocols.
 pub fn verify_channel_proofs<Ctx>(
 	ctx: &Ctx,
+	client: &mut ProofVerifyingClient<Ctx::AnyClientState>,
 	height: Height,
 	channel_end: &ChannelEnd,
 	connection_end: &ConnectionEnd,
@@ -46,8 +49,7 @@ where
 {
 	// This is the client which will perform proof verification.
 	let client_id = connection_end.client_id().clone();
-
-	let client_state = ctx.client_state(&client_id).map_err(Error::ics02_client)?;
+	let client_state = &client.client_state;
 
 	// The client must not be frozen.
 	if client_state.is_frozen() {
@@ -66,7 +68,7 @@ where
 		.verify_channel_state(
 			ctx,
 			&client_id,
-			&client_state,
+			client_state,
 			height,
 			connection_end.counterparty().prefix(),
 			&proof,
@@ -78,19 +80,22 @@ where
 				.ok_or_else(|| Error::missing_channel_id())?,
 			expected_chan,
 		)
-		.map_err(Error::verify_channel_failed)
+		.map_err(Error::verify_channel_failed)?;
+
+	client.proof_verified().map_err(Error::ics02_client)
 }
 
 /// Entry point for verifying all proofs bundled in a ICS4 packet recv. message.
 pub fn verify_packet_recv_proofs<Ctx: ReaderContext>(
 	ctx: &Ctx,
+	client: &mut ProofVerifyingClient<Ctx::AnyClientState>,
 	height: Height,
 	packet: &Packet,
 	connection_end: &ConnectionEnd,
 	proofs: &Proofs,
 ) -> Result<(), Error> {
 	let client_id = connection_end.client_id();
-	let client_state = ctx.client_state(client_id).map_err(Error::ics02_client)?;
+	let client_state = &client.client_state;
 
 	// The client must not be frozen.
 	if client_state.is_frozen() {
@@ -111,7 +116,7 @@ pub fn verify_packet_recv_proofs<Ctx: ReaderContext>(
 		.verify_packet_data(
 			ctx,
 			client_id,
-			&client_state,
+			client_state,
 			height,
 			connection_end,
 			proofs.object_proof(),
@@ -123,12 +128,13 @@ pub fn verify_packet_recv_proofs<Ctx: ReaderContext>(
 		)
 		.map_err(|e| Error::packet_verification_failed(packet.sequence, e))?;
 
-	Ok(())
+	client.proof_verified().map_err(Error::ics02_client)
 }
 
 /// Entry point for verifying all proofs bundled in an ICS4 packet ack message.
 pub fn verify_packet_acknowledgement_proofs<Ctx: ReaderContext>(
 	ctx: &Ctx,
+	client: &mut ProofVerifyingClient<Ctx::AnyClientState>,
 	height: Height,
 	packet: &Packet,
 	acknowledgement: Acknowledgement,
@@ -136,7 +142,7 @@ pub fn verify_packet_acknowledgement_proofs<Ctx: ReaderContext>(
 	proofs: &Proofs,
 ) -> Result<(), Error> {
 	let client_id = connection_end.client_id();
-	let client_state = ctx.client_state(client_id).map_err(Error::ics02_client)?;
+	let client_state = &client.client_state;
 
 	// The client must not be frozen.
 	if client_state.is_frozen() {
@@ -156,7 +162,7 @@ pub fn verify_packet_acknowledgement_proofs<Ctx: ReaderContext>(
 		.verify_packet_acknowledgement(
 			ctx,
 			client_id,
-			&client_state,
+			client_state,
 			height,
 			connection_end,
 			proofs.object_proof(),
@@ -168,12 +174,13 @@ pub fn verify_packet_acknowledgement_proofs<Ctx: ReaderContext>(
 		)
 		.map_err(|e| Error::packet_verification_failed(packet.sequence, e))?;
 
-	Ok(())
+	client.proof_verified().map_err(Error::ics02_client)
 }
 
 /// Entry point for verifying all timeout proofs.
 pub fn verify_next_sequence_recv<Ctx>(
 	ctx: &Ctx,
+	client: &mut ProofVerifyingClient<Ctx::AnyClientState>,
 	height: Height,
 	connection_end: &ConnectionEnd,
 	packet: Packet,
@@ -184,7 +191,7 @@ where
 	Ctx: ReaderContext,
 {
 	let client_id = connection_end.client_id();
-	let client_state = ctx.client_state(client_id).map_err(Error::ics02_client)?;
+	let client_state = &client.client_state;
 
 	// The client must not be frozen.
 	if client_state.is_frozen() {
@@ -202,7 +209,7 @@ where
 		.verify_next_sequence_recv(
 			ctx,
 			client_id,
-			&client_state,
+			client_state,
 			height,
 			connection_end,
 			proofs.object_proof(),
@@ -213,11 +220,12 @@ where
 		)
 		.map_err(|e| Error::packet_verification_failed(seq, e))?;
 
-	Ok(())
+	client.proof_verified().map_err(Error::ics02_client)
 }
 
 pub fn verify_packet_receipt_absence<Ctx>(
 	ctx: &Ctx,
+	client: &mut ProofVerifyingClient<Ctx::AnyClientState>,
 	height: Height,
 	connection_end: &ConnectionEnd,
 	packet: Packet,
@@ -227,7 +235,7 @@ where
 	Ctx: ReaderContext,
 {
 	let client_id = connection_end.client_id();
-	let client_state = ctx.client_state(client_id).map_err(Error::ics02_client)?;
+	let client_state = &client.client_state;
 
 	// The client must not be frozen.
 	if client_state.is_frozen() {
@@ -245,7 +253,7 @@ where
 		.verify_packet_receipt_absence(
 			ctx,
 			client_id,
-			&client_state,
+			client_state,
 			height,
 			connection_end,
 			proofs.object_proof(),
@@ -256,5 +264,5 @@ where
 		)
 		.map_err(|e| Error::packet_verification_failed(packet.sequence, e))?;
 
-	Ok(())
+	client.proof_verified().map_err(Error::ics02_client)
 }
@@ -18,7 +18,10 @@
 use core::{convert::Infallible, fmt, str::FromStr};
 use serde_derive::{Deserialize, Serialize};
 
-use crate::{applications::transfer, prelude::*};
+use crate::{
+	applications::{icq, transfer},
+	prelude::*,
+};
 
 /// The version field for a `ChannelEnd`.
 ///
@@ -37,6 +40,10 @@ impl Version {
 		Self::new(transfer::VERSION.to_string())
 	}
 
+	pub fn icq() -> Self {
+		Self::new(icq::VERSION.to_string())
+	}
+
 	pub fn empty() -> Self {
 		Self::new("".to_string())
 	}
@@ -140,7 +140,19 @@ impl From<String> for ChainId {
 	}
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+	Clone,
+	Debug,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Hash,
+	Serialize,
+	Deserialize,
+	codec::Encode,
+	scale_info::TypeInfo,
+)]
 pub struct ClientId(String);
 
 impl ClientId {
@@ -186,6 +198,14 @@ impl FromStr for ClientId {
 	}
 }
 
+/// Decoding validates the identifier, like parsing it does.
+impl codec::Decode for ClientId {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let id = <String as codec::Decode>::decode(input)?;
+		Self::from_str(&id).map_err(|_| "Invalid client identifier".into())
+	}
+}
+
 #[cfg(not(test))]
 impl Default for ClientId {
 	fn default() -> Self {
@@ -207,7 +227,19 @@ impl PartialEq<str> for ClientId {
 	}
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+	Clone,
+	Debug,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Hash,
+	Serialize,
+	Deserialize,
+	codec::Encode,
+	scale_info::TypeInfo,
+)]
 pub struct ConnectionId(String);
 
 impl ConnectionId {
@@ -257,6 +289,14 @@ impl FromStr for ConnectionId {
 	}
 }
 
+/// Decoding validates the identifier, like parsing it does.
+impl codec::Decode for ConnectionId {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let id = <String as codec::Decode>::decode(input)?;
+		Self::from_str(&id).map_err(|_| "Invalid connection identifier".into())
+	}
+}
+
 impl Default for ConnectionId {
 	fn default() -> Self {
 		Self::new(0)
@@ -277,7 +317,19 @@ impl PartialEq<str> for ConnectionId {
 	}
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+	Clone,
+	Debug,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Hash,
+	Serialize,
+	Deserialize,
+	codec::Encode,
+	scale_info::TypeInfo,
+)]
 pub struct PortId(String);
 
 impl PortId {
@@ -312,6 +364,14 @@ impl FromStr for PortId {
 	}
 }
 
+/// Decoding validates the identifier, like parsing it does.
+impl codec::Decode for PortId {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let id = <String as codec::Decode>::decode(input)?;
+		Self::from_str(&id).map_err(|_| "Invalid port identifier".into())
+	}
+}
+
 impl AsRef<str> for PortId {
 	fn as_ref(&self) -> &str {
 		self.0.as_str()
@@ -327,7 +387,18 @@ impl Default for PortId {
 	}
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(
+	Copy,
+	Clone,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Hash,
+	codec::Encode,
+	codec::Decode,
+	scale_info::TypeInfo,
+)]
 pub struct ChannelId(u64);
 
 impl ChannelId {
@@ -422,3 +493,25 @@ impl Display for PortChannelId {
 		write!(f, "{}/{}", self.port_id, self.channel_id)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::{Decode, Encode};
+
+	#[test]
+	fn decoding_validates_identifiers() {
+		let client_id = ClientId::new("07-tendermint", 0).unwrap();
+		assert_eq!(ClientId::decode(&mut &*client_id.encode()).unwrap(), client_id);
+		let connection_id = ConnectionId::new(0);
+		assert_eq!(ConnectionId::decode(&mut &*connection_id.encode()).unwrap(), connection_id);
+		let port_id = PortId::transfer();
+		assert_eq!(PortId::decode(&mut &*port_id.encode()).unwrap(), port_id);
+
+		// path separators aren't allowed in any identifier
+		let invalid = "ports/transfer".encode();
+		assert!(ClientId::decode(&mut &*invalid).is_err());
+		assert!(ConnectionId::decode(&mut &*invalid).is_err());
+		assert!(PortId::decode(&mut &*invalid).is_err());
+	}
+}
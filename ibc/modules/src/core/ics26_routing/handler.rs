@@ -15,6 +15,7 @@
 use crate::{
 	core::{
 		ics02_client::{
+			client_def::ProofVerifyingClient,
 			context::{ClientKeeper, ClientTypes},
 			handler::dispatch as ics2_msg_dispatcher,
 		},
@@ -28,6 +29,7 @@ use crate::{
 			},
 			packet::PacketResult,
 		},
+		ics24_host::identifier::ClientId,
 		ics26_routing::{
 			context::{Ics26Context, ModuleOutputBuilder, ReaderContext},
 			error::Error,
@@ -100,10 +102,11 @@ where
 		},
 
 		Ics3Msg(msg) => {
-			let handler_output =
+			let mut handler_output =
 				ics3_msg_dispatcher::<_>(ctx, msg).map_err(Error::ics03_connection)?;
 
 			// Apply any results to the host chain store.
+			store_client_state_update(ctx, handler_output.result.client_state_update.take())?;
 			ctx.store_connection_result(handler_output.result)
 				.map_err(Error::ics03_connection)?;
 
@@ -122,9 +125,10 @@ where
 			let cb_result =
 				ics4_callback(ctx, &module_id, &msg, channel_result, &mut module_output);
 			handler_builder.merge(module_output);
-			let channel_result = cb_result.map_err(Error::ics04_channel)?;
+			let mut channel_result = cb_result.map_err(Error::ics04_channel)?;
 
 			// Apply any results to the host chain store.
+			store_client_state_update(ctx, channel_result.client_state_update.take())?;
 			ctx.store_channel_result(channel_result).map_err(Error::ics04_channel)?;
 
 			handler_builder.with_result(())
@@ -132,7 +136,7 @@ where
 
 		Ics4PacketMsg(msg) => {
 			let module_id = get_module_for_packet_msg(ctx, &msg).map_err(Error::ics04_channel)?;
-			let (mut handler_builder, packet_result) =
+			let (mut handler_builder, (packet_result, client_state_update)) =
 				ics4_packet_msg_dispatcher::<_>(ctx, &msg).map_err(Error::ics04_channel)?;
 
 			if matches!(packet_result, PacketResult::Recv(RecvPacketResult::NoOp)) {
@@ -145,6 +149,7 @@ where
 			cb_result.map_err(Error::ics04_channel)?;
 
 			// Apply any results to the host chain store.
+			store_client_state_update(ctx, client_state_update)?;
 			ctx.store_packet_result(packet_result).map_err(Error::ics04_channel)?;
 
 			handler_builder.with_result(())
@@ -154,6 +159,18 @@ where
 	Ok(output)
 }
 
+/// Stores the client state changed by verifying the proofs of a message, see
+/// [`ProofVerifyingClient`].
+fn store_client_state_update<Ctx: ClientKeeper>(
+	ctx: &mut Ctx,
+	update: Option<(ClientId, Ctx::AnyClientState)>,
+) -> Result<(), Error> {
+	if let Some((client_id, client_state)) = update {
+		ctx.store_client_state(client_id, client_state).map_err(Error::ics02_client)?;
+	}
+	Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 	use crate::prelude::*;
@@ -349,6 +366,7 @@ mod tests {
 				token: PrefixedCoin { denom, amount: msg_transfer_two.token.amount },
 				sender: msg_transfer_two.sender.clone(),
 				receiver: msg_transfer_two.receiver.clone(),
+				memo: String::new(),
 			};
 			serde_json::to_vec(&data).expect("PacketData's infallible Serialize impl failed")
 		};
@@ -144,8 +144,13 @@ impl ClientState for MockClientState {
 		self.frozen_height()
 	}
 
-	fn upgrade(self, _upgrade_height: Height, _upgrade_options: (), _chain_id: ChainId) -> Self {
-		self.upgrade(_upgrade_height, _upgrade_options, _chain_id)
+	fn upgrade(
+		self,
+		_upgrade_height: Height,
+		_upgrade_options: (),
+		_chain_id: ChainId,
+	) -> Result<Self, Error> {
+		Ok(self.upgrade(_upgrade_height, _upgrade_options, _chain_id))
 	}
 
 	fn expired(&self, elapsed: Duration) -> bool {
@@ -31,6 +31,12 @@ use tracing::debug;
 #[cfg(test)]
 use crate::core::ics02_client::events::Attributes;
 use crate::{
+	applications::icq::{
+		context::InterchainQueryContext,
+		error::Error as IcqError,
+		packet::{QueryRequest, QueryResponse},
+		MODULE_ID_STR as ICQ_MODULE_ID, PORT_ID_STR as ICQ_PORT_ID,
+	},
 	core::{
 		ics02_client::{
 			client_state::{ClientState, ClientType},
@@ -541,6 +547,12 @@ pub struct MockIbcStore<C: HostBlockType> {
 
 	// Used by unordered channel
 	pub packet_receipt: BTreeMap<(PortId, ChannelId, Sequence), Receipt>,
+
+	/// Values served to interchain queries, indexed by the query data
+	pub icq_store: BTreeMap<Vec<u8>, Vec<u8>>,
+
+	/// Query paths the interchain queries host executes
+	pub icq_allowed_paths: Vec<String>,
 }
 
 #[derive(Default)]
@@ -625,6 +637,48 @@ impl<C: HostBlockType> PortReader for MockContext<C> {
 	}
 }
 
+impl<C: HostBlockType> InterchainQueryContext for MockContext<C> {
+	fn get_port(&self) -> Result<PortId, IcqError> {
+		ICQ_PORT_ID
+			.parse()
+			.map_err(|e| IcqError::query(format!("invalid icq port: {:?}", e)))
+	}
+
+	fn is_controller_port(&self, port_id: &PortId) -> bool {
+		port_id.as_str() != ICQ_PORT_ID &&
+			self.ibc_store
+				.lock()
+				.unwrap()
+				.port_to_module
+				.get(port_id)
+				.map_or(false, |module_id| module_id.as_ref() == ICQ_MODULE_ID)
+	}
+
+	fn is_query_allowed(&self, request: &QueryRequest) -> bool {
+		self.ibc_store.lock().unwrap().icq_allowed_paths.contains(&request.path)
+	}
+
+	fn query(&self, request: &QueryRequest) -> Result<QueryResponse, IcqError> {
+		if request.height != 0 {
+			return Err(IcqError::unsupported_height(request.height))
+		}
+		Ok(QueryResponse {
+			code: 0,
+			value: self
+				.ibc_store
+				.lock()
+				.unwrap()
+				.icq_store
+				.get(&request.data)
+				.cloned()
+				.unwrap_or_default(),
+			height: self.latest_height().revision_height,
+			proof: vec![],
+			log: String::new(),
+		})
+	}
+}
+
 impl<C: HostBlockType> ChannelReader for MockContext<C> {
 	fn channel_end(&self, pcid: &(PortId, ChannelId)) -> Result<ChannelEnd, Ics04Error> {
 		match self.ibc_store.lock().unwrap().channels.get(pcid) {
@@ -64,13 +64,20 @@ impl CompileCmd {
 	fn compile_ibc_protos(ibc_dir: &Path, out_dir: &Path) {
 		println!("[info ] Compiling IBC .proto files to Rust into '{}'...", out_dir.display());
 
+		let root = env!("CARGO_MANIFEST_DIR");
+		// Vendored definitions that take precedence over the ibc-go ones with the same path
+		let overrides_dir = PathBuf::from(format!("{}/../proto/definitions", root));
+
 		// Paths
 		let proto_paths = [
 			// ibc-go proto files
 			format!("{}/proto/ibc", ibc_dir.display()),
+			// vendored ibc proto files
+			format!("{}/ibc", overrides_dir.display()),
 		];
 
 		let proto_includes_paths = [
+			overrides_dir.display().to_string(),
 			format!("{}/proto", ibc_dir.display()),
 			format!("{}/third_party/proto", ibc_dir.display()),
 		];
@@ -89,6 +96,12 @@ impl CompileCmd {
 							e.path().extension().unwrap() == "proto"
 					})
 					.map(|e| e.into_path())
+					.filter(|path| {
+						// protoc rejects inputs shadowed by an earlier include path
+						path.strip_prefix(format!("{}/proto", ibc_dir.display()))
+							.map(|relative| !overrides_dir.join(relative).exists())
+							.unwrap_or(true)
+					})
 					.collect(),
 			);
 		}
@@ -0,0 +1,394 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Beacon chain types verified by the light client.
+
+use crate::{
+	error::Error,
+	proto,
+	ssz::{self, Root},
+	HostFunctions,
+};
+use alloc::{format, vec::Vec};
+
+/// Number of members of a sync committee.
+pub const SYNC_COMMITTEE_SIZE: usize = 512;
+/// Number of slots in an epoch.
+pub const SLOTS_PER_EPOCH: u64 = 32;
+/// Number of epochs a sync committee serves for.
+pub const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: u64 = 256;
+
+/// Index of the execution payload in the beacon block body.
+const EXECUTION_PAYLOAD_GINDEX: u64 = 25;
+/// Indices of the fields of the execution payload proven by the light client.
+const EXECUTION_STATE_ROOT_INDEX: u64 = 2;
+const EXECUTION_BLOCK_NUMBER_INDEX: u64 = 6;
+const EXECUTION_TIMESTAMP_INDEX: u64 = 9;
+
+/// Epoch of the given slot.
+pub fn compute_epoch_at_slot(slot: u64) -> u64 {
+	slot / SLOTS_PER_EPOCH
+}
+
+/// Sync committee period of the given slot.
+pub fn compute_sync_committee_period_at_slot(slot: u64) -> u64 {
+	compute_epoch_at_slot(slot) / EPOCHS_PER_SYNC_COMMITTEE_PERIOD
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct BeaconBlockHeader {
+	pub slot: u64,
+	pub proposer_index: u64,
+	pub parent_root: Root,
+	pub state_root: Root,
+	pub body_root: Root,
+}
+
+impl BeaconBlockHeader {
+	pub fn hash_tree_root<H: HostFunctions>(&self) -> Root {
+		ssz::merkleize::<H>(&[
+			ssz::uint64_root(self.slot),
+			ssz::uint64_root(self.proposer_index),
+			self.parent_root,
+			self.state_root,
+			self.body_root,
+		])
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyncCommittee {
+	/// BLS public keys of the committee members
+	pub pubkeys: Vec<[u8; 48]>,
+	/// Aggregate of the public keys
+	pub aggregate_pubkey: [u8; 48],
+}
+
+impl SyncCommittee {
+	pub fn hash_tree_root<H: HostFunctions>(&self) -> Root {
+		let pubkeys = self.pubkeys.iter().map(|key| ssz::bytes_root::<H>(key)).collect::<Vec<_>>();
+		ssz::hash::<H>(
+			&ssz::merkleize::<H>(&pubkeys),
+			&ssz::bytes_root::<H>(&self.aggregate_pubkey),
+		)
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyncAggregate {
+	/// Bitvector of the participating sync committee members
+	pub sync_committee_bits: Vec<u8>,
+	/// Aggregate signature of the participants
+	pub sync_committee_signature: [u8; 96],
+}
+
+impl SyncAggregate {
+	/// Indices of the participating sync committee members.
+	pub fn participants(&self) -> impl Iterator<Item = usize> + '_ {
+		(0..SYNC_COMMITTEE_SIZE).filter(|i| {
+			self.sync_committee_bits
+				.get(i / 8)
+				.map_or(false, |byte| (byte >> (i % 8)) & 1 == 1)
+		})
+	}
+}
+
+/// Fields of the execution payload of a beacon block, with their merkle branches.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutionPayloadProof {
+	pub state_root: Root,
+	pub state_root_branch: Vec<Root>,
+	pub block_number: u64,
+	pub block_number_branch: Vec<Root>,
+	/// Timestamp in seconds
+	pub timestamp: u64,
+	pub timestamp_branch: Vec<Root>,
+	/// Branch of the execution payload root to the beacon block body root
+	pub execution_branch: Vec<Root>,
+}
+
+impl ExecutionPayloadProof {
+	/// Verify the execution payload fields against the body root of a beacon block header at the
+	/// given fork.
+	pub fn verify<H: HostFunctions>(
+		&self,
+		fork_parameters: &ForkParameters,
+		header: &BeaconBlockHeader,
+	) -> Result<(), Error> {
+		let field_gindex =
+			|index| fork_parameters.execution_payload_field_gindex(header.slot, index);
+		// the execution payload root isn't part of the proof, it's computed from the state root.
+		let execution_root = ssz::merkle_root::<H>(
+			&self.state_root,
+			&self.state_root_branch,
+			field_gindex(EXECUTION_STATE_ROOT_INDEX),
+		)
+		.ok_or_else(|| Error::InvalidMerkleBranch("Invalid execution state root branch".into()))?;
+
+		if !ssz::is_valid_merkle_branch::<H>(
+			&ssz::uint64_root(self.block_number),
+			&self.block_number_branch,
+			field_gindex(EXECUTION_BLOCK_NUMBER_INDEX),
+			&execution_root,
+		) {
+			Err(Error::InvalidMerkleBranch("Invalid execution block number branch".into()))?
+		}
+		if !ssz::is_valid_merkle_branch::<H>(
+			&ssz::uint64_root(self.timestamp),
+			&self.timestamp_branch,
+			field_gindex(EXECUTION_TIMESTAMP_INDEX),
+			&execution_root,
+		) {
+			Err(Error::InvalidMerkleBranch("Invalid execution timestamp branch".into()))?
+		}
+		if !ssz::is_valid_merkle_branch::<H>(
+			&execution_root,
+			&self.execution_branch,
+			EXECUTION_PAYLOAD_GINDEX,
+			&header.body_root,
+		) {
+			Err(Error::InvalidMerkleBranch("Invalid execution payload branch".into()))?
+		}
+
+		Ok(())
+	}
+}
+
+/// A fork of the beacon chain, unscheduled forks have an epoch of `u64::MAX`.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Fork {
+	pub version: [u8; 4],
+	pub epoch: u64,
+}
+
+/// Fork versions and activation epochs of the beacon chain.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct ForkParameters {
+	pub genesis_fork_version: [u8; 4],
+	pub altair: Fork,
+	pub bellatrix: Fork,
+	pub capella: Fork,
+	pub deneb: Fork,
+	pub electra: Fork,
+}
+
+impl ForkParameters {
+	/// Version of the fork active at the given epoch.
+	pub fn fork_version(&self, epoch: u64) -> [u8; 4] {
+		[&self.electra, &self.deneb, &self.capella, &self.bellatrix, &self.altair]
+			.into_iter()
+			.find(|fork| epoch >= fork.epoch)
+			.map_or(self.genesis_fork_version, |fork| fork.version)
+	}
+
+	fn is_active(fork: &Fork, slot: u64) -> bool {
+		compute_epoch_at_slot(slot) >= fork.epoch
+	}
+
+	/// Generalized index of the finalized checkpoint root in the beacon state at the given slot.
+	pub fn finalized_root_gindex(&self, slot: u64) -> u64 {
+		if Self::is_active(&self.electra, slot) {
+			169
+		} else {
+			105
+		}
+	}
+
+	/// Generalized index of the next sync committee in the beacon state at the given slot.
+	pub fn next_sync_committee_gindex(&self, slot: u64) -> u64 {
+		if Self::is_active(&self.electra, slot) {
+			87
+		} else {
+			55
+		}
+	}
+
+	/// Generalized index of a field of the execution payload of a block at the given slot, the
+	/// payload grew past 16 fields in Deneb.
+	pub fn execution_payload_field_gindex(&self, slot: u64, index: u64) -> u64 {
+		let depth = if Self::is_active(&self.deneb, slot) { 5 } else { 4 };
+		(1 << depth) + index
+	}
+}
+
+pub(crate) fn fixed_bytes<const N: usize>(bytes: Vec<u8>, name: &str) -> Result<[u8; N], Error> {
+	let len = bytes.len();
+	bytes
+		.try_into()
+		.map_err(|_| Error::Custom(format!("Invalid {name} length: expected {N}, got {len}")))
+}
+
+pub(crate) fn branch(branch: Vec<Vec<u8>>, name: &str) -> Result<Vec<Root>, Error> {
+	ssz::branch_from_bytes(branch)
+		.ok_or_else(|| Error::InvalidMerkleBranch(format!("Invalid {name} node length")))
+}
+
+impl TryFrom<proto::BeaconBlockHeader> for BeaconBlockHeader {
+	type Error = Error;
+
+	fn try_from(raw: proto::BeaconBlockHeader) -> Result<Self, Self::Error> {
+		Ok(Self {
+			slot: raw.slot,
+			proposer_index: raw.proposer_index,
+			parent_root: fixed_bytes(raw.parent_root, "parent root")?,
+			state_root: fixed_bytes(raw.state_root, "state root")?,
+			body_root: fixed_bytes(raw.body_root, "body root")?,
+		})
+	}
+}
+
+impl From<BeaconBlockHeader> for proto::BeaconBlockHeader {
+	fn from(header: BeaconBlockHeader) -> Self {
+		Self {
+			slot: header.slot,
+			proposer_index: header.proposer_index,
+			parent_root: header.parent_root.to_vec(),
+			state_root: header.state_root.to_vec(),
+			body_root: header.body_root.to_vec(),
+		}
+	}
+}
+
+impl TryFrom<proto::SyncCommittee> for SyncCommittee {
+	type Error = Error;
+
+	fn try_from(raw: proto::SyncCommittee) -> Result<Self, Self::Error> {
+		if raw.pubkeys.len() != SYNC_COMMITTEE_SIZE {
+			Err(Error::Custom(format!(
+				"Invalid sync committee size: expected {SYNC_COMMITTEE_SIZE}, got {}",
+				raw.pubkeys.len()
+			)))?
+		}
+		Ok(Self {
+			pubkeys: raw
+				.pubkeys
+				.into_iter()
+				.map(|key| fixed_bytes(key, "public key"))
+				.collect::<Result<_, _>>()?,
+			aggregate_pubkey: fixed_bytes(raw.aggregate_pubkey, "aggregate public key")?,
+		})
+	}
+}
+
+impl From<SyncCommittee> for proto::SyncCommittee {
+	fn from(committee: SyncCommittee) -> Self {
+		Self {
+			pubkeys: committee.pubkeys.into_iter().map(|key| key.to_vec()).collect(),
+			aggregate_pubkey: committee.aggregate_pubkey.to_vec(),
+		}
+	}
+}
+
+impl TryFrom<proto::SyncAggregate> for SyncAggregate {
+	type Error = Error;
+
+	fn try_from(raw: proto::SyncAggregate) -> Result<Self, Self::Error> {
+		if raw.sync_committee_bits.len() != SYNC_COMMITTEE_SIZE / 8 {
+			Err(Error::Custom(format!(
+				"Invalid sync committee bits length: {}",
+				raw.sync_committee_bits.len()
+			)))?
+		}
+		Ok(Self {
+			sync_committee_bits: raw.sync_committee_bits,
+			sync_committee_signature: fixed_bytes(raw.sync_committee_signature, "signature")?,
+		})
+	}
+}
+
+impl From<SyncAggregate> for proto::SyncAggregate {
+	fn from(aggregate: SyncAggregate) -> Self {
+		Self {
+			sync_committee_bits: aggregate.sync_committee_bits,
+			sync_committee_signature: aggregate.sync_committee_signature.to_vec(),
+		}
+	}
+}
+
+impl TryFrom<proto::ExecutionPayloadProof> for ExecutionPayloadProof {
+	type Error = Error;
+
+	fn try_from(raw: proto::ExecutionPayloadProof) -> Result<Self, Self::Error> {
+		Ok(Self {
+			state_root: fixed_bytes(raw.state_root, "execution state root")?,
+			state_root_branch: branch(raw.state_root_branch, "execution state root branch")?,
+			block_number: raw.block_number,
+			block_number_branch: branch(raw.block_number_branch, "block number branch")?,
+			timestamp: raw.timestamp,
+			timestamp_branch: branch(raw.timestamp_branch, "timestamp branch")?,
+			execution_branch: branch(raw.execution_branch, "execution branch")?,
+		})
+	}
+}
+
+impl From<ExecutionPayloadProof> for proto::ExecutionPayloadProof {
+	fn from(proof: ExecutionPayloadProof) -> Self {
+		let branch = |branch: Vec<Root>| branch.into_iter().map(|node| node.to_vec()).collect();
+		Self {
+			state_root: proof.state_root.to_vec(),
+			state_root_branch: branch(proof.state_root_branch),
+			block_number: proof.block_number,
+			block_number_branch: branch(proof.block_number_branch),
+			timestamp: proof.timestamp,
+			timestamp_branch: branch(proof.timestamp_branch),
+			execution_branch: branch(proof.execution_branch),
+		}
+	}
+}
+
+impl TryFrom<proto::Fork> for Fork {
+	type Error = Error;
+
+	fn try_from(raw: proto::Fork) -> Result<Self, Self::Error> {
+		Ok(Self { version: fixed_bytes(raw.version, "fork version")?, epoch: raw.epoch })
+	}
+}
+
+impl From<Fork> for proto::Fork {
+	fn from(fork: Fork) -> Self {
+		Self { version: fork.version.to_vec(), epoch: fork.epoch }
+	}
+}
+
+impl TryFrom<proto::ForkParameters> for ForkParameters {
+	type Error = Error;
+
+	fn try_from(raw: proto::ForkParameters) -> Result<Self, Self::Error> {
+		let fork = |fork: Option<proto::Fork>, name: &str| {
+			fork.ok_or_else(|| Error::Custom(format!("Missing {name} fork")))?.try_into()
+		};
+		Ok(Self {
+			genesis_fork_version: fixed_bytes(raw.genesis_fork_version, "genesis fork version")?,
+			altair: fork(raw.altair, "altair")?,
+			bellatrix: fork(raw.bellatrix, "bellatrix")?,
+			capella: fork(raw.capella, "capella")?,
+			deneb: fork(raw.deneb, "deneb")?,
+			electra: fork(raw.electra, "electra")?,
+		})
+	}
+}
+
+impl From<ForkParameters> for proto::ForkParameters {
+	fn from(parameters: ForkParameters) -> Self {
+		Self {
+			genesis_fork_version: parameters.genesis_fork_version.to_vec(),
+			altair: Some(parameters.altair.into()),
+			bellatrix: Some(parameters.bellatrix.into()),
+			capella: Some(parameters.capella.into()),
+			deneb: Some(parameters.deneb.into()),
+			electra: Some(parameters.electra.into()),
+		}
+	}
+}
@@ -0,0 +1,555 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	beacon::{compute_epoch_at_slot, compute_sync_committee_period_at_slot, SYNC_COMMITTEE_SIZE},
+	client_message::{ClientMessage, Header, Misbehaviour},
+	client_state::ClientState,
+	consensus_state::ConsensusState,
+	error::Error,
+	proto::StorageProof,
+	ssz::{self, Root, DOMAIN_SYNC_COMMITTEE},
+	trie, HostFunctions,
+};
+use alloc::{format, string::ToString, vec::Vec};
+use core::marker::PhantomData;
+use ibc::{
+	core::{
+		ics02_client::{
+			client_consensus::ConsensusState as _,
+			client_def::{ClientDef, ConsensusUpdateResult},
+			client_state::ClientState as _,
+			error::Error as Ics02Error,
+		},
+		ics03_connection::connection::ConnectionEnd,
+		ics04_channel::{
+			channel::ChannelEnd,
+			commitment::{AcknowledgementCommitment, PacketCommitment},
+			packet::Sequence,
+		},
+		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+		ics24_host::{
+			identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			path::{
+				AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath,
+				CommitmentsPath, ConnectionsPath, ReceiptsPath, SeqRecvsPath,
+			},
+			Path,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	Height,
+};
+use light_client_common::verify_delay_passed;
+use prost::Message;
+use rlp::Rlp;
+use tendermint_proto::Protobuf;
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct EthereumClient<H>(PhantomData<H>);
+
+impl<H> ClientDef for EthereumClient<H>
+where
+	H: HostFunctions,
+{
+	type ClientMessage = ClientMessage;
+	type ClientState = ClientState<H>;
+	type ConsensusState = ConsensusState;
+
+	fn verify_client_message<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<(), Ics02Error> {
+		match client_message {
+			ClientMessage::Header(header) => verify_header(&client_state, &header)?,
+			ClientMessage::Misbehaviour(misbehaviour) =>
+				verify_misbehaviour(&client_state, &misbehaviour)?,
+		}
+
+		Ok(())
+	}
+
+	fn update_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
+		let header = match client_message {
+			ClientMessage::Header(header) => header,
+			_ => unreachable!("02-client will check for Header before calling update_state; qed"),
+		};
+		let (client_state, _, consensus_state) = apply_header(client_state, header)?;
+		let cs = Ctx::AnyConsensusState::wrap(&consensus_state).ok_or_else(|| {
+			Ics02Error::unknown_consensus_state_type("Ctx::AnyConsensusState".to_string())
+		})?;
+		Ok((client_state, ConsensusUpdateResult::Single(cs)))
+	}
+
+	fn update_state_on_misbehaviour(
+		&self,
+		client_state: Self::ClientState,
+		_client_message: Self::ClientMessage,
+	) -> Result<Self::ClientState, Ics02Error> {
+		let height = client_state.latest_height();
+		Ok(client_state.with_frozen_height(height)?)
+	}
+
+	/// A valid header is misbehaviour if the client already has a different consensus state at
+	/// the execution block number it finalizes.
+	fn check_for_misbehaviour<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		client_id: ClientId,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<bool, Ics02Error> {
+		let header = match client_message {
+			ClientMessage::Header(header) => header,
+			ClientMessage::Misbehaviour(_) => return Ok(true),
+		};
+		let height = Height::new(0, header.finalized_execution.block_number);
+		let consensus_state = match ctx.maybe_consensus_state(&client_id, height)? {
+			Some(cs) => cs
+				.downcast::<ConsensusState>()
+				.ok_or_else(|| Ics02Error::client_args_type_mismatch(client_state.client_type()))?,
+			None => return Ok(false),
+		};
+		Ok(consensus_state.root.as_bytes() != &header.finalized_execution.state_root[..])
+	}
+
+	fn verify_upgrade_and_update_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		_old_client_state: &Self::ClientState,
+		_upgrade_client_state: &Self::ClientState,
+		_upgrade_consensus_state: &Self::ConsensusState,
+		_proof_upgrade_client: Vec<u8>,
+		_proof_upgrade_consensus_state: Vec<u8>,
+	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
+		Err(Error::Custom("Ethereum clients can't be upgraded".to_string()).into())
+	}
+
+	fn verify_client_consensus_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		client_id: &ClientId,
+		consensus_height: Height,
+		expected_consensus_state: &Ctx::AnyConsensusState,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		let path = ClientConsensusStatePath {
+			client_id: client_id.clone(),
+			epoch: consensus_height.revision_number,
+			height: consensus_height.revision_height,
+		};
+		let value = expected_consensus_state.encode_to_vec();
+		verify_storage::<H, _>(client_state, prefix, proof, root, path, Some(value))?;
+		Ok(())
+	}
+
+	fn verify_connection_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		connection_id: &ConnectionId,
+		expected_connection_end: &ConnectionEnd,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		let path = ConnectionsPath(connection_id.clone());
+		let value = expected_connection_end.encode_vec();
+		verify_storage::<H, _>(client_state, prefix, proof, root, path, Some(value))?;
+		Ok(())
+	}
+
+	fn verify_channel_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		expected_channel_end: &ChannelEnd,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		let path = ChannelEndsPath(port_id.clone(), *channel_id);
+		let value = expected_channel_end.encode_vec();
+		verify_storage::<H, _>(client_state, prefix, proof, root, path, Some(value))?;
+		Ok(())
+	}
+
+	fn verify_client_full_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		client_id: &ClientId,
+		expected_client_state: &Ctx::AnyClientState,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		let path = ClientStatePath(client_id.clone());
+		let value = expected_client_state.encode_to_vec();
+		verify_storage::<H, _>(client_state, prefix, proof, root, path, Some(value))?;
+		Ok(())
+	}
+
+	fn verify_packet_data<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		commitment: PacketCommitment,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_delay_passed::<H, _>(ctx, height, connection_end)
+			.map_err(|e| Error::Custom(e.to_string()))?;
+
+		let path = CommitmentsPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		let prefix = connection_end.counterparty().prefix();
+		let value = commitment.into_vec();
+		verify_storage::<H, _>(client_state, prefix, proof, root, path, Some(value))?;
+		Ok(())
+	}
+
+	fn verify_packet_acknowledgement<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		ack: AcknowledgementCommitment,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_delay_passed::<H, _>(ctx, height, connection_end)
+			.map_err(|e| Error::Custom(e.to_string()))?;
+
+		let path = AcksPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		let prefix = connection_end.counterparty().prefix();
+		let value = ack.into_vec();
+		verify_storage::<H, _>(client_state, prefix, proof, root, path, Some(value))?;
+		Ok(())
+	}
+
+	fn verify_next_sequence_recv<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_delay_passed::<H, _>(ctx, height, connection_end)
+			.map_err(|e| Error::Custom(e.to_string()))?;
+
+		let path = SeqRecvsPath(port_id.clone(), *channel_id);
+		let prefix = connection_end.counterparty().prefix();
+		let value = u64::from(sequence).to_be_bytes().to_vec();
+		verify_storage::<H, _>(client_state, prefix, proof, root, path, Some(value))?;
+		Ok(())
+	}
+
+	fn verify_packet_receipt_absence<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_delay_passed::<H, _>(ctx, height, connection_end)
+			.map_err(|e| Error::Custom(e.to_string()))?;
+
+		let path = ReceiptsPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		let prefix = connection_end.counterparty().prefix();
+		verify_storage::<H, _>(client_state, prefix, proof, root, path, None)?;
+		Ok(())
+	}
+}
+
+/// Verify that the header finalizes a beacon block newer than the latest one known to the client.
+pub fn verify_header<H: HostFunctions>(
+	client_state: &ClientState<H>,
+	header: &Header,
+) -> Result<(), Error> {
+	if client_state.frozen_height.is_some() {
+		Err(Error::Frozen)?
+	}
+	if header.finalized_header.slot <= client_state.latest_slot {
+		Err(Error::Custom(format!(
+			"Finalized header slot {} isn't newer than the latest slot {}",
+			header.finalized_header.slot, client_state.latest_slot
+		)))?
+	}
+	verify_light_client_update(client_state, header)
+}
+
+/// Verify a light client update against the sync committees known to the client, following the
+/// `validate_light_client_update` function of the consensus specs.
+pub fn verify_light_client_update<H: HostFunctions>(
+	client_state: &ClientState<H>,
+	header: &Header,
+) -> Result<(), Error> {
+	let participants = header.sync_aggregate.participants().collect::<Vec<_>>();
+	if participants.len() * 3 < SYNC_COMMITTEE_SIZE * 2 {
+		Err(Error::Custom(format!(
+			"Insufficient sync committee participation: {}/{SYNC_COMMITTEE_SIZE}",
+			participants.len()
+		)))?
+	}
+
+	let attested_header = &header.attested_header;
+	let finalized_header = &header.finalized_header;
+	if !(header.signature_slot > attested_header.slot &&
+		attested_header.slot >= finalized_header.slot)
+	{
+		Err(Error::Custom(format!(
+			"Invalid slots, signature: {}, attested: {}, finalized: {}",
+			header.signature_slot, attested_header.slot, finalized_header.slot
+		)))?
+	}
+
+	let store_period = compute_sync_committee_period_at_slot(client_state.latest_slot);
+	let signature_period = compute_sync_committee_period_at_slot(header.signature_slot);
+	let sync_committee = if signature_period == store_period {
+		&client_state.current_sync_committee
+	} else if signature_period == store_period + 1 {
+		client_state.next_sync_committee.as_ref().ok_or_else(|| {
+			Error::Custom(format!("Next sync committee of period {store_period} is unknown"))
+		})?
+	} else {
+		Err(Error::Custom(format!(
+			"Signature period {signature_period} isn't the store period {store_period} or the next one"
+		)))?
+	};
+
+	let fork_parameters = &client_state.fork_parameters;
+	if !ssz::is_valid_merkle_branch::<H>(
+		&finalized_header.hash_tree_root::<H>(),
+		&header.finality_branch,
+		fork_parameters.finalized_root_gindex(attested_header.slot),
+		&attested_header.state_root,
+	) {
+		Err(Error::InvalidMerkleBranch("Invalid finality branch".into()))?
+	}
+
+	if let Some(next_sync_committee) = &header.next_sync_committee {
+		let attested_period = compute_sync_committee_period_at_slot(attested_header.slot);
+		match &client_state.next_sync_committee {
+			Some(known) if attested_period == store_period && known != next_sync_committee =>
+				Err(Error::Custom("Next sync committee doesn't match the known one".into()))?,
+			_ => {},
+		}
+		if !ssz::is_valid_merkle_branch::<H>(
+			&next_sync_committee.hash_tree_root::<H>(),
+			&header.next_sync_committee_branch,
+			fork_parameters.next_sync_committee_gindex(attested_header.slot),
+			&attested_header.state_root,
+		) {
+			Err(Error::InvalidMerkleBranch("Invalid next sync committee branch".into()))?
+		}
+	}
+
+	header.finalized_execution.verify::<H>(fork_parameters, finalized_header)?;
+
+	// the sync aggregate signs the block of the previous slot, with the fork version at that slot.
+	let fork_version =
+		fork_parameters.fork_version(compute_epoch_at_slot(header.signature_slot.max(1) - 1));
+	let domain = ssz::compute_domain::<H>(
+		DOMAIN_SYNC_COMMITTEE,
+		fork_version,
+		&client_state.genesis_validators_root,
+	);
+	let signing_root =
+		ssz::compute_signing_root::<H>(&attested_header.hash_tree_root::<H>(), &domain);
+	let public_keys =
+		participants.into_iter().map(|i| sync_committee.pubkeys[i]).collect::<Vec<_>>();
+	if !H::bls_fast_aggregate_verify(
+		&public_keys,
+		&signing_root,
+		&header.sync_aggregate.sync_committee_signature,
+	) {
+		Err(Error::InvalidSignature)?
+	}
+
+	Ok(())
+}
+
+/// Advance the client to the finalized header of a verified update, rotating the sync committees
+/// when it enters the next sync committee period. Returns the updated client state with the height
+/// and consensus state of the finalized execution payload.
+pub fn apply_header<H>(
+	mut client_state: ClientState<H>,
+	header: Header,
+) -> Result<(ClientState<H>, Height, ConsensusState), Error> {
+	let store_period = compute_sync_committee_period_at_slot(client_state.latest_slot);
+	let attested_period = compute_sync_committee_period_at_slot(header.attested_header.slot);
+	let finalized_period = compute_sync_committee_period_at_slot(header.finalized_header.slot);
+
+	if client_state.next_sync_committee.is_none() {
+		if attested_period == store_period {
+			client_state.next_sync_committee = header.next_sync_committee;
+		}
+	} else if finalized_period == store_period + 1 {
+		client_state.current_sync_committee = client_state
+			.next_sync_committee
+			.take()
+			.expect("next sync committee is known in this branch; qed");
+		// the attested state only holds the committee after the new one if it's in the same period
+		if attested_period == finalized_period {
+			client_state.next_sync_committee = header.next_sync_committee;
+		}
+	}
+
+	let execution = header.finalized_execution;
+	client_state.latest_slot = header.finalized_header.slot;
+	client_state.latest_execution_block_number = execution.block_number;
+	let consensus_state = ConsensusState::new(
+		execution.state_root,
+		execution.timestamp,
+		header.finalized_header.slot,
+	)?;
+
+	Ok((client_state, Height::new(0, execution.block_number), consensus_state))
+}
+
+/// Verify that both updates of the misbehaviour are valid and finalize different beacon blocks at
+/// the same slot.
+pub fn verify_misbehaviour<H: HostFunctions>(
+	client_state: &ClientState<H>,
+	misbehaviour: &Misbehaviour,
+) -> Result<(), Error> {
+	if client_state.frozen_height.is_some() {
+		Err(Error::Frozen)?
+	}
+	let first = &misbehaviour.first_header;
+	let second = &misbehaviour.second_header;
+	if first.finalized_header.slot != second.finalized_header.slot {
+		Err(Error::Custom("Misbehaviour headers must finalize the same slot".into()))?
+	}
+	if first.finalized_header.hash_tree_root::<H>() == second.finalized_header.hash_tree_root::<H>()
+	{
+		Err(Error::Custom("Misbehaviour headers finalize the same block".into()))?
+	}
+	verify_light_client_update(client_state, first)?;
+	verify_light_client_update(client_state, second)
+}
+
+/// Storage slot of `path` in the IBC handler's commitments mapping, the mapping is keyed by the
+/// keccak hash of the prefixed path.
+pub fn commitment_slot<H: HostFunctions>(
+	client_state: &ClientState<H>,
+	prefix: &CommitmentPrefix,
+	path: Path,
+) -> Root {
+	let mut key = prefix.as_bytes().to_vec();
+	key.extend(path.to_string().as_bytes());
+	let mut preimage = H::keccak_256(&key).to_vec();
+	preimage.extend_from_slice(&client_state.ibc_commitments_slot);
+	H::keccak_256(&preimage)
+}
+
+/// Verify the account proof of the IBC handler against the execution state root, then the storage
+/// proof of the commitment of `path`. The handler stores the keccak hash of values, `None` proves
+/// that nothing is stored at `path`.
+pub(crate) fn verify_storage<H, P>(
+	client_state: &ClientState<H>,
+	prefix: &CommitmentPrefix,
+	proof: &CommitmentProofBytes,
+	root: &CommitmentRoot,
+	path: P,
+	value: Option<Vec<u8>>,
+) -> Result<(), Error>
+where
+	H: HostFunctions,
+	P: Into<Path>,
+{
+	let path: Path = path.into();
+	let state_root: Root = root.as_bytes().try_into().map_err(|_| {
+		Error::Custom(format!("invalid commitment root length: {}", root.as_bytes().len()))
+	})?;
+	let proof = StorageProof::decode(proof.as_bytes())?;
+
+	let account_key = H::keccak_256(&client_state.ibc_handler_address);
+	let account = trie::get::<H>(&state_root, &account_key, &proof.account_proof)?
+		.ok_or_else(|| Error::InvalidStorageProof("IBC handler account doesn't exist".into()))?;
+	let storage_root = trie::account_storage_root(&account)?;
+
+	let slot = commitment_slot(client_state, prefix, path.clone());
+	let stored = trie::get::<H>(&storage_root, &H::keccak_256(&slot), &proof.storage_proof)?
+		.map(|value| -> Result<Root, Error> {
+			// storage values are rlp encoded integers without leading zeros
+			let bytes = Rlp::new(&value).data()?;
+			if bytes.len() > 32 {
+				Err(Error::InvalidStorageProof("Invalid storage value length".into()))?
+			}
+			let mut word = Root::default();
+			word[32 - bytes.len()..].copy_from_slice(bytes);
+			Ok(word)
+		})
+		.transpose()?;
+
+	let expected = value.map(|value| H::keccak_256(&value));
+	if stored != expected {
+		Err(Error::InvalidStorageProof(format!("Invalid storage proof for path: {path}")))?
+	}
+	Ok(())
+}
@@ -0,0 +1,177 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	beacon::{self, BeaconBlockHeader, ExecutionPayloadProof, SyncAggregate, SyncCommittee},
+	error::Error,
+	proto::{
+		client_message, ClientMessage as RawClientMessage, Header as RawHeader,
+		Misbehaviour as RawMisbehaviour,
+	},
+	ssz::Root,
+};
+use alloc::vec::Vec;
+use tendermint_proto::Protobuf;
+
+/// Protobuf type url for Ethereum ClientMessage
+pub const ETHEREUM_CLIENT_MESSAGE_TYPE_URL: &str = "/ibc.lightclients.ethereum.v1.ClientMessage";
+
+/// Light client update, proving the finality of a beacon block header and its execution payload
+/// with the signature of the sync committee over a descendant header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+	/// Header signed by the sync committee
+	pub attested_header: BeaconBlockHeader,
+	/// Next sync committee in the state of the attested header, with its merkle branch
+	pub next_sync_committee: Option<SyncCommittee>,
+	pub next_sync_committee_branch: Vec<Root>,
+	/// Finalized header in the state of the attested header, with its merkle branch
+	pub finalized_header: BeaconBlockHeader,
+	pub finality_branch: Vec<Root>,
+	/// Execution payload of the finalized header
+	pub finalized_execution: ExecutionPayloadProof,
+	pub sync_aggregate: SyncAggregate,
+	/// Slot at which the sync aggregate was included
+	pub signature_slot: u64,
+}
+
+/// Two valid light client updates finalizing different headers at the same slot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Misbehaviour {
+	pub first_header: Header,
+	pub second_header: Header,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClientMessage {
+	Header(Header),
+	Misbehaviour(Misbehaviour),
+}
+
+impl ibc::core::ics02_client::client_message::ClientMessage for ClientMessage {
+	fn encode_to_vec(&self) -> Vec<u8> {
+		self.encode_vec()
+	}
+}
+
+impl Protobuf<RawClientMessage> for ClientMessage {}
+
+impl TryFrom<RawClientMessage> for ClientMessage {
+	type Error = Error;
+
+	fn try_from(raw: RawClientMessage) -> Result<Self, Self::Error> {
+		let message = match raw.message.ok_or_else(|| {
+			Error::Custom("Must supply either Header or Misbehaviour type!".into())
+		})? {
+			client_message::Message::Header(header) => ClientMessage::Header(header.try_into()?),
+			client_message::Message::Misbehaviour(misbehaviour) =>
+				ClientMessage::Misbehaviour(misbehaviour.try_into()?),
+		};
+
+		Ok(message)
+	}
+}
+
+impl From<ClientMessage> for RawClientMessage {
+	fn from(client_message: ClientMessage) -> Self {
+		let message = match client_message {
+			ClientMessage::Header(header) => client_message::Message::Header(header.into()),
+			ClientMessage::Misbehaviour(misbehaviour) =>
+				client_message::Message::Misbehaviour(misbehaviour.into()),
+		};
+		RawClientMessage { message: Some(message) }
+	}
+}
+
+impl TryFrom<RawHeader> for Header {
+	type Error = Error;
+
+	fn try_from(raw: RawHeader) -> Result<Self, Self::Error> {
+		let attested_header = raw
+			.attested_header
+			.ok_or_else(|| Error::Custom("Invalid header: missing attested header".into()))?
+			.try_into()?;
+		let next_sync_committee =
+			raw.next_sync_committee.map(SyncCommittee::try_from).transpose()?;
+		let finalized_header = raw
+			.finalized_header
+			.ok_or_else(|| Error::Custom("Invalid header: missing finalized header".into()))?
+			.try_into()?;
+		let finalized_execution = raw
+			.finalized_execution
+			.ok_or_else(|| Error::Custom("Invalid header: missing finalized execution".into()))?
+			.try_into()?;
+		let sync_aggregate = raw
+			.sync_aggregate
+			.ok_or_else(|| Error::Custom("Invalid header: missing sync aggregate".into()))?
+			.try_into()?;
+
+		Ok(Self {
+			attested_header,
+			next_sync_committee,
+			next_sync_committee_branch: beacon::branch(
+				raw.next_sync_committee_branch,
+				"next sync committee branch",
+			)?,
+			finalized_header,
+			finality_branch: beacon::branch(raw.finality_branch, "finality branch")?,
+			finalized_execution,
+			sync_aggregate,
+			signature_slot: raw.signature_slot,
+		})
+	}
+}
+
+impl From<Header> for RawHeader {
+	fn from(header: Header) -> Self {
+		let branch = |branch: Vec<Root>| branch.into_iter().map(|node| node.to_vec()).collect();
+		RawHeader {
+			attested_header: Some(header.attested_header.into()),
+			next_sync_committee: header.next_sync_committee.map(Into::into),
+			next_sync_committee_branch: branch(header.next_sync_committee_branch),
+			finalized_header: Some(header.finalized_header.into()),
+			finality_branch: branch(header.finality_branch),
+			finalized_execution: Some(header.finalized_execution.into()),
+			sync_aggregate: Some(header.sync_aggregate.into()),
+			signature_slot: header.signature_slot,
+		}
+	}
+}
+
+impl TryFrom<RawMisbehaviour> for Misbehaviour {
+	type Error = Error;
+
+	fn try_from(raw: RawMisbehaviour) -> Result<Self, Self::Error> {
+		let first_header = raw
+			.first_header
+			.ok_or_else(|| Error::Custom("Invalid misbehaviour: missing first header".into()))?
+			.try_into()?;
+		let second_header = raw
+			.second_header
+			.ok_or_else(|| Error::Custom("Invalid misbehaviour: missing second header".into()))?
+			.try_into()?;
+
+		Ok(Self { first_header, second_header })
+	}
+}
+
+impl From<Misbehaviour> for RawMisbehaviour {
+	fn from(misbehaviour: Misbehaviour) -> Self {
+		RawMisbehaviour {
+			first_header: Some(misbehaviour.first_header.into()),
+			second_header: Some(misbehaviour.second_header.into()),
+		}
+	}
+}
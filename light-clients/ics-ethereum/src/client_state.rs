@@ -0,0 +1,218 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	beacon::{ForkParameters, SyncCommittee},
+	client_def::EthereumClient,
+	error::Error,
+	proto::ClientState as RawClientState,
+	ssz::Root,
+	HostFunctions,
+};
+use alloc::{format, string::ToString, vec::Vec};
+use core::{marker::PhantomData, time::Duration};
+use ibc::{
+	core::{
+		ics02_client::{client_state::ClientType, error::Error as Ics02Error},
+		ics24_host::identifier::ChainId,
+	},
+	Height,
+};
+use tendermint_proto::Protobuf;
+
+/// Protobuf type url for Ethereum ClientState
+pub const ETHEREUM_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.ethereum.v1.ClientState";
+
+#[derive(PartialEq, Clone, Debug, Eq)]
+pub struct ClientState<H> {
+	/// EIP-155 chain id of the execution chain
+	pub chain_id: u64,
+	/// Root of the genesis validators, part of the signing domain
+	pub genesis_validators_root: Root,
+	/// Genesis time of the beacon chain in seconds
+	pub genesis_time: u64,
+	/// Fork versions and activation epochs of the beacon chain
+	pub fork_parameters: ForkParameters,
+	/// Duration after which the client expires if it hasn't been updated
+	pub trusting_period: Duration,
+	/// Address of the IBC handler contract whose storage is proven
+	pub ibc_handler_address: [u8; 20],
+	/// Storage slot of the IBC handler's commitments mapping
+	pub ibc_commitments_slot: Root,
+	/// Slot of the latest finalized beacon block header
+	pub latest_slot: u64,
+	/// Execution block number of the latest finalized beacon block header
+	pub latest_execution_block_number: u64,
+	/// Sync committee of the period of the latest finalized header
+	pub current_sync_committee: SyncCommittee,
+	/// Sync committee of the next period, if known
+	pub next_sync_committee: Option<SyncCommittee>,
+	/// Block height when the client was frozen due to a misbehaviour
+	pub frozen_height: Option<Height>,
+	/// phantom type.
+	pub _phantom: PhantomData<H>,
+}
+
+impl<H: Clone> Protobuf<RawClientState> for ClientState<H> {}
+
+impl<H> ClientState<H> {
+	pub fn client_type() -> ClientType {
+		"xx-ethereum".to_string()
+	}
+
+	/// The height of the client is the latest finalized execution block number.
+	pub fn latest_height(&self) -> Height {
+		Height::new(0, self.latest_execution_block_number)
+	}
+
+	pub fn chain_id(&self) -> ChainId {
+		ChainId::new("ethereum".to_string(), self.chain_id)
+	}
+
+	/// Verify that the client is at a sufficient height and unfrozen at the given height
+	pub fn verify_height(&self, height: Height) -> Result<(), Error> {
+		let latest_height = self.latest_height();
+		if latest_height < height {
+			return Err(Error::Custom(format!(
+				"Insufficient height, known height: {latest_height}, given height: {height}"
+			)))
+		}
+
+		match self.frozen_height {
+			Some(frozen_height) if frozen_height <= height =>
+				Err(Error::Custom(format!("Client has been frozen at height {frozen_height}"))),
+			_ => Ok(()),
+		}
+	}
+
+	pub fn with_frozen_height(self, h: Height) -> Result<Self, Error> {
+		if h == Height::zero() {
+			return Err(Error::Custom(
+				"ClientState frozen height must be greater than zero".to_string(),
+			))
+		}
+		Ok(Self { frozen_height: Some(h), ..self })
+	}
+}
+
+impl<H> ibc::core::ics02_client::client_state::ClientState for ClientState<H>
+where
+	H: HostFunctions,
+{
+	/// Ethereum clients aren't upgraded through IBC, hard forks only need new fork parameters.
+	type UpgradeOptions = ();
+	type ClientDef = EthereumClient<H>;
+
+	fn chain_id(&self) -> ChainId {
+		self.chain_id()
+	}
+
+	fn client_def(&self) -> Self::ClientDef {
+		EthereumClient::default()
+	}
+
+	fn client_type(&self) -> ClientType {
+		Self::client_type()
+	}
+
+	fn latest_height(&self) -> Height {
+		self.latest_height()
+	}
+
+	fn frozen_height(&self) -> Option<Height> {
+		self.frozen_height
+	}
+
+	fn upgrade(
+		self,
+		_upgrade_height: Height,
+		_upgrade_options: (),
+		_chain_id: ChainId,
+	) -> Result<Self, Ics02Error> {
+		Ok(self)
+	}
+
+	fn expired(&self, elapsed: Duration) -> bool {
+		elapsed > self.trusting_period
+	}
+
+	fn encode_to_vec(&self) -> Vec<u8> {
+		self.encode_vec()
+	}
+}
+
+impl<H> TryFrom<RawClientState> for ClientState<H> {
+	type Error = Error;
+
+	fn try_from(raw: RawClientState) -> Result<Self, Self::Error> {
+		let genesis_validators_root = raw.genesis_validators_root.try_into().map_err(|_| {
+			Error::Custom("Invalid client state: invalid genesis validators root".into())
+		})?;
+		let ibc_handler_address = raw.ibc_handler_address.try_into().map_err(|_| {
+			Error::Custom("Invalid client state: invalid IBC handler address".into())
+		})?;
+		let ibc_commitments_slot = raw.ibc_commitments_slot.try_into().map_err(|_| {
+			Error::Custom("Invalid client state: invalid IBC commitments slot".into())
+		})?;
+		let fork_parameters = raw
+			.fork_parameters
+			.ok_or_else(|| Error::Custom("Invalid client state: missing fork parameters".into()))?
+			.try_into()?;
+		let current_sync_committee = raw
+			.current_sync_committee
+			.ok_or_else(|| {
+				Error::Custom("Invalid client state: missing current sync committee".into())
+			})?
+			.try_into()?;
+		let next_sync_committee =
+			raw.next_sync_committee.map(SyncCommittee::try_from).transpose()?;
+		let frozen_height = raw.frozen_height.map(|height| Height::new(0, height));
+
+		Ok(Self {
+			chain_id: raw.chain_id,
+			genesis_validators_root,
+			genesis_time: raw.genesis_time,
+			fork_parameters,
+			trusting_period: Duration::from_secs(raw.trusting_period),
+			ibc_handler_address,
+			ibc_commitments_slot,
+			latest_slot: raw.latest_slot,
+			latest_execution_block_number: raw.latest_execution_block_number,
+			current_sync_committee,
+			next_sync_committee,
+			frozen_height,
+			_phantom: Default::default(),
+		})
+	}
+}
+
+impl<H> From<ClientState<H>> for RawClientState {
+	fn from(client_state: ClientState<H>) -> Self {
+		RawClientState {
+			chain_id: client_state.chain_id,
+			genesis_validators_root: client_state.genesis_validators_root.to_vec(),
+			genesis_time: client_state.genesis_time,
+			fork_parameters: Some(client_state.fork_parameters.into()),
+			trusting_period: client_state.trusting_period.as_secs(),
+			ibc_handler_address: client_state.ibc_handler_address.to_vec(),
+			ibc_commitments_slot: client_state.ibc_commitments_slot.to_vec(),
+			latest_slot: client_state.latest_slot,
+			latest_execution_block_number: client_state.latest_execution_block_number,
+			current_sync_committee: Some(client_state.current_sync_committee.into()),
+			next_sync_committee: client_state.next_sync_committee.map(Into::into),
+			frozen_height: client_state.frozen_height.map(|height| height.revision_height),
+		}
+	}
+}
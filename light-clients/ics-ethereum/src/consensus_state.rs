@@ -0,0 +1,81 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::Error, proto::ConsensusState as RawConsensusState, ssz::Root};
+use alloc::{format, vec::Vec};
+use core::convert::Infallible;
+use ibc::{core::ics23_commitment::commitment::CommitmentRoot, timestamp::Timestamp};
+use tendermint_proto::Protobuf;
+
+/// Protobuf type url for Ethereum Consensus State
+pub const ETHEREUM_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.lightclients.ethereum.v1.ConsensusState";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsensusState {
+	/// Timestamp of the finalized execution payload
+	pub timestamp: Timestamp,
+	/// State root of the finalized execution payload
+	pub root: CommitmentRoot,
+	/// Slot of the finalized beacon block header
+	pub slot: u64,
+}
+
+impl ConsensusState {
+	pub fn new(state_root: Root, timestamp: u64, slot: u64) -> Result<Self, Error> {
+		let timestamp = Timestamp::from_nanoseconds(timestamp.saturating_mul(1_000_000_000))
+			.map_err(|e| Error::Custom(format!("Invalid timestamp: {e}")))?;
+		Ok(Self { timestamp, root: state_root.to_vec().into(), slot })
+	}
+}
+
+impl ibc::core::ics02_client::client_consensus::ConsensusState for ConsensusState {
+	type Error = Infallible;
+
+	fn root(&self) -> &CommitmentRoot {
+		&self.root
+	}
+
+	fn timestamp(&self) -> Timestamp {
+		self.timestamp
+	}
+
+	fn encode_to_vec(&self) -> Vec<u8> {
+		self.encode_vec()
+	}
+}
+
+impl Protobuf<RawConsensusState> for ConsensusState {}
+
+impl TryFrom<RawConsensusState> for ConsensusState {
+	type Error = Error;
+
+	fn try_from(raw: RawConsensusState) -> Result<Self, Self::Error> {
+		let state_root = raw
+			.state_root
+			.try_into()
+			.map_err(|_| Error::Custom("Invalid consensus state: invalid state root".into()))?;
+		Self::new(state_root, raw.timestamp, raw.slot)
+	}
+}
+
+impl From<ConsensusState> for RawConsensusState {
+	fn from(value: ConsensusState) -> Self {
+		RawConsensusState {
+			timestamp: value.timestamp.nanoseconds() / 1_000_000_000,
+			state_root: value.root.into_vec(),
+			slot: value.slot,
+		}
+	}
+}
@@ -0,0 +1,59 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Ethereum IBC light client protocol implementation, following the beacon chain's sync committee
+//! light client protocol.
+//!
+//! Updates carry a beacon block header signed by the sync committee, the finalized header in its
+//! state and the execution payload of the finalized header. Every finalized execution payload
+//! becomes a consensus state at the execution block number, whose state root is used to verify
+//! account and storage proofs of the IBC handler contract.
+
+extern crate alloc;
+
+use core::fmt::Debug;
+
+pub mod beacon;
+pub mod client_def;
+pub mod client_message;
+pub mod client_state;
+pub mod consensus_state;
+pub mod error;
+#[cfg(any(test, feature = "mocks"))]
+pub mod mock;
+pub mod proto;
+pub mod ssz;
+pub mod trie;
+
+#[cfg(test)]
+mod tests;
+
+/// Host functions that allow the light client perform cryptographic operations in native.
+pub trait HostFunctions: Clone + Send + Sync + Eq + Debug + Default {
+	/// Sha2-256 hashing implementation, used for SSZ merkleization.
+	fn sha256(message: &[u8]) -> [u8; 32];
+
+	/// Keccak-256 hashing implementation, used for execution layer merkle-patricia tries.
+	fn keccak_256(message: &[u8]) -> [u8; 32];
+
+	/// Verify an aggregate BLS12-381 signature of `message` by all of the given public keys.
+	fn bls_fast_aggregate_verify(
+		public_keys: &[[u8; 48]],
+		message: &[u8; 32],
+		signature: &[u8; 96],
+	) -> bool;
+}
@@ -0,0 +1,208 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Light client updates signed by BLS12-381 sync committees, for tests and benchmarks.
+
+use crate::{
+	beacon::{
+		BeaconBlockHeader, ExecutionPayloadProof, Fork, ForkParameters, SyncAggregate,
+		SyncCommittee, EPOCHS_PER_SYNC_COMMITTEE_PERIOD, SLOTS_PER_EPOCH, SYNC_COMMITTEE_SIZE,
+	},
+	client_message::Header,
+	client_state::ClientState,
+	ssz::{self, Root, DOMAIN_SYNC_COMMITTEE},
+	HostFunctions,
+};
+use alloc::{vec, vec::Vec};
+use core::time::Duration;
+use milagro_bls::{AggregatePublicKey, AggregateSignature, PublicKey, SecretKey, Signature};
+
+/// Number of slots a sync committee serves for.
+pub const SLOTS_PER_PERIOD: u64 = SLOTS_PER_EPOCH * EPOCHS_PER_SYNC_COMMITTEE_PERIOD;
+/// Genesis validators root of the mock chain.
+pub const GENESIS_VALIDATORS_ROOT: Root = [0xab; 32];
+/// Number of distinct validators in a sync committee. Members cycle through them, validators may
+/// sit in a sync committee more than once, which keeps key derivation cheap.
+const DISTINCT_VALIDATORS: usize = 8;
+
+/// Secret key of the validator at `index` of the sync committee identified by `seed`.
+fn secret_key(seed: u8, index: usize) -> SecretKey {
+	let mut bytes = [0u8; 32];
+	bytes[30] = seed;
+	bytes[31] = (index % DISTINCT_VALIDATORS) as u8 + 1;
+	SecretKey::from_bytes(&bytes).expect("non zero scalar below the curve order; qed")
+}
+
+/// Sync committee identified by `seed`.
+pub fn sync_committee(seed: u8) -> SyncCommittee {
+	let validators = (0..DISTINCT_VALIDATORS)
+		.map(|i| PublicKey::from_secret_key(&secret_key(seed, i)))
+		.collect::<Vec<_>>();
+	let members = (0..SYNC_COMMITTEE_SIZE)
+		.map(|i| &validators[i % DISTINCT_VALIDATORS])
+		.collect::<Vec<_>>();
+	let aggregate_pubkey = AggregatePublicKey::aggregate(&members)
+		.expect("public keys derived from valid secret keys; qed")
+		.into_public_key();
+	SyncCommittee {
+		pubkeys: members.iter().map(|key| key.as_bytes()).collect(),
+		aggregate_pubkey: aggregate_pubkey.as_bytes(),
+	}
+}
+
+/// Aggregate signature of `message` by the first `signers` members of the sync committee
+/// identified by `seed`.
+pub fn sign(seed: u8, signers: usize, message: &[u8; 32]) -> [u8; 96] {
+	let signatures = (0..DISTINCT_VALIDATORS)
+		.map(|i| Signature::new(message, &secret_key(seed, i)))
+		.collect::<Vec<_>>();
+	let mut aggregate = AggregateSignature::new();
+	for i in 0..signers {
+		aggregate.add(&signatures[i % DISTINCT_VALIDATORS]);
+	}
+	aggregate.as_bytes()
+}
+
+/// Fork schedule with every fork up to capella active from genesis.
+pub fn fork_parameters() -> ForkParameters {
+	let fork = |version: u8, epoch| Fork { version: [version, 0, 0, 0], epoch };
+	ForkParameters {
+		genesis_fork_version: [0; 4],
+		altair: fork(1, 0),
+		bellatrix: fork(2, 0),
+		capella: fork(3, 0),
+		deneb: fork(4, u64::MAX),
+		electra: fork(5, u64::MAX),
+	}
+}
+
+/// Client state at `latest_slot`, trusting the sync committees identified by `1` and `2`.
+pub fn client_state<H>(latest_slot: u64) -> ClientState<H> {
+	ClientState {
+		chain_id: 1,
+		genesis_validators_root: GENESIS_VALIDATORS_ROOT,
+		genesis_time: 1_606_824_023,
+		fork_parameters: fork_parameters(),
+		trusting_period: Duration::from_secs(14 * 24 * 60 * 60),
+		ibc_handler_address: [0x11; 20],
+		ibc_commitments_slot: [0; 32],
+		latest_slot,
+		latest_execution_block_number: 100,
+		current_sync_committee: sync_committee(1),
+		next_sync_committee: Some(sync_committee(2)),
+		frozen_height: None,
+		_phantom: Default::default(),
+	}
+}
+
+/// Layers of the merkle tree of the given leaves, from the leaves to the root.
+pub fn merkle_tree<H: HostFunctions>(leaves: Vec<Root>) -> Vec<Vec<Root>> {
+	let mut layers = vec![leaves];
+	while layers.last().expect("layers start with the leaves; qed").len() > 1 {
+		let layer = layers
+			.last()
+			.expect("layers start with the leaves; qed")
+			.chunks(2)
+			.map(|pair| ssz::hash::<H>(&pair[0], &pair[1]))
+			.collect();
+		layers.push(layer);
+	}
+	layers
+}
+
+/// Merkle branch of the leaf at `index` in the tree with the given layers.
+pub fn merkle_branch(layers: &[Vec<Root>], mut index: usize) -> Vec<Root> {
+	let mut branch = vec![];
+	for layer in &layers[..layers.len() - 1] {
+		branch.push(layer[index ^ 1]);
+		index /= 2;
+	}
+	branch
+}
+
+/// Builds a light client update finalizing a block at `finalized_slot` with the given execution
+/// block number, signed by the first `signers` members of the sync committee identified by
+/// `signing_committee`.
+pub fn light_client_update<H: HostFunctions>(
+	finalized_slot: u64,
+	block_number: u64,
+	state_root: Root,
+	next_sync_committee: &SyncCommittee,
+	signing_committee: u8,
+	signers: usize,
+) -> Header {
+	// execution payload with its state root, block number and timestamp
+	let mut payload = vec![Root::default(); 16];
+	payload[2] = state_root;
+	payload[6] = ssz::uint64_root(block_number);
+	payload[9] = ssz::uint64_root(1_700_000_000 + block_number * 12);
+	let payload = merkle_tree::<H>(payload);
+	let mut body = vec![Root::default(); 16];
+	body[9] = payload.last().expect("merkle tree has a root; qed")[0];
+	let body = merkle_tree::<H>(body);
+	let finalized_execution = ExecutionPayloadProof {
+		state_root,
+		state_root_branch: merkle_branch(&payload, 2),
+		block_number,
+		block_number_branch: merkle_branch(&payload, 6),
+		timestamp: 1_700_000_000 + block_number * 12,
+		timestamp_branch: merkle_branch(&payload, 9),
+		execution_branch: merkle_branch(&body, 9),
+	};
+	let finalized_header = BeaconBlockHeader {
+		slot: finalized_slot,
+		proposer_index: 7,
+		body_root: body.last().expect("merkle tree has a root; qed")[0],
+		..Default::default()
+	};
+
+	// attested state with the finalized checkpoint and the next sync committee
+	let epoch = ssz::uint64_root(finalized_slot / SLOTS_PER_EPOCH);
+	let mut state = vec![Root::default(); 32];
+	state[20] = ssz::hash::<H>(&epoch, &finalized_header.hash_tree_root::<H>());
+	state[23] = next_sync_committee.hash_tree_root::<H>();
+	let state = merkle_tree::<H>(state);
+	let mut finality_branch = vec![epoch];
+	finality_branch.extend(merkle_branch(&state, 20));
+	let attested_header = BeaconBlockHeader {
+		slot: finalized_slot + 2 * SLOTS_PER_EPOCH,
+		proposer_index: 9,
+		state_root: state.last().expect("merkle tree has a root; qed")[0],
+		..Default::default()
+	};
+
+	let signature_slot = attested_header.slot + 1;
+	let fork_version = fork_parameters().fork_version(attested_header.slot / SLOTS_PER_EPOCH);
+	let domain =
+		ssz::compute_domain::<H>(DOMAIN_SYNC_COMMITTEE, fork_version, &GENESIS_VALIDATORS_ROOT);
+	let signing_root =
+		ssz::compute_signing_root::<H>(&attested_header.hash_tree_root::<H>(), &domain);
+	let mut sync_committee_bits = vec![0u8; SYNC_COMMITTEE_SIZE / 8];
+	for i in 0..signers {
+		sync_committee_bits[i / 8] |= 1 << (i % 8);
+	}
+	let sync_committee_signature = sign(signing_committee, signers, &signing_root);
+
+	Header {
+		attested_header,
+		next_sync_committee: Some(next_sync_committee.clone()),
+		next_sync_committee_branch: merkle_branch(&state, 23),
+		finalized_header,
+		finality_branch,
+		finalized_execution,
+		sync_aggregate: SyncAggregate { sync_committee_bits, sync_committee_signature },
+		signature_slot,
+	}
+}
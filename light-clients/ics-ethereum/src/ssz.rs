@@ -0,0 +1,119 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SSZ merkleization of the beacon chain types verified by the light client.
+
+use crate::HostFunctions;
+use alloc::vec::Vec;
+
+/// A 32 byte SSZ chunk or hash tree root.
+pub type Root = [u8; 32];
+
+/// Domain type of sync committee signatures.
+pub const DOMAIN_SYNC_COMMITTEE: [u8; 4] = [7, 0, 0, 0];
+
+/// Hash of two sibling nodes.
+pub fn hash<H: HostFunctions>(left: &Root, right: &Root) -> Root {
+	let mut preimage = [0u8; 64];
+	preimage[..32].copy_from_slice(left);
+	preimage[32..].copy_from_slice(right);
+	H::sha256(&preimage)
+}
+
+/// Merkleizes the given chunks, padding them with zero chunks to the next power of two.
+pub fn merkleize<H: HostFunctions>(chunks: &[Root]) -> Root {
+	let mut layer = chunks.to_vec();
+	let width = layer.len().next_power_of_two().max(1);
+	layer.resize(width, Root::default());
+	while layer.len() > 1 {
+		layer = layer.chunks(2).map(|pair| hash::<H>(&pair[0], &pair[1])).collect();
+	}
+	layer[0]
+}
+
+/// Hash tree root of a `uint64`.
+pub fn uint64_root(value: u64) -> Root {
+	let mut root = Root::default();
+	root[..8].copy_from_slice(&value.to_le_bytes());
+	root
+}
+
+/// Hash tree root of a fixed length byte vector, such as a BLS public key.
+pub fn bytes_root<H: HostFunctions>(bytes: &[u8]) -> Root {
+	let chunks = bytes
+		.chunks(32)
+		.map(|chunk| {
+			let mut root = Root::default();
+			root[..chunk.len()].copy_from_slice(chunk);
+			root
+		})
+		.collect::<Vec<_>>();
+	merkleize::<H>(&chunks)
+}
+
+/// Root of the tree with `leaf` at the given generalized index and the given merkle branch, returns
+/// `None` if the branch doesn't have the depth of the index.
+pub fn merkle_root<H: HostFunctions>(
+	leaf: &Root,
+	branch: &[Root],
+	generalized_index: u64,
+) -> Option<Root> {
+	let depth = 63 - generalized_index.leading_zeros() as usize;
+	if branch.len() != depth {
+		return None
+	}
+	let index = generalized_index - (1 << depth);
+	let root = branch.iter().enumerate().fold(*leaf, |value, (i, node)| {
+		if (index >> i) & 1 == 1 {
+			hash::<H>(node, &value)
+		} else {
+			hash::<H>(&value, node)
+		}
+	});
+	Some(root)
+}
+
+/// Checks that `leaf` is at the given generalized index of the tree with the given root.
+pub fn is_valid_merkle_branch<H: HostFunctions>(
+	leaf: &Root,
+	branch: &[Root],
+	generalized_index: u64,
+	root: &Root,
+) -> bool {
+	merkle_root::<H>(leaf, branch, generalized_index).as_ref() == Some(root)
+}
+
+/// Signing domain for the given domain type, fork version and genesis validators root.
+pub fn compute_domain<H: HostFunctions>(
+	domain_type: [u8; 4],
+	fork_version: [u8; 4],
+	genesis_validators_root: &Root,
+) -> Root {
+	let fork_data_root = hash::<H>(&bytes_root::<H>(&fork_version), genesis_validators_root);
+	let mut domain = Root::default();
+	domain[..4].copy_from_slice(&domain_type);
+	domain[4..].copy_from_slice(&fork_data_root[..28]);
+	domain
+}
+
+/// Signing root of an object in the given domain.
+pub fn compute_signing_root<H: HostFunctions>(object_root: &Root, domain: &Root) -> Root {
+	hash::<H>(object_root, domain)
+}
+
+/// Converts the nodes of a merkle branch to chunks.
+pub fn branch_from_bytes(branch: Vec<Vec<u8>>) -> Option<Vec<Root>> {
+	branch.into_iter().map(|node| node.try_into().ok()).collect()
+}
@@ -0,0 +1,304 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	client_def::{
+		apply_header, commitment_slot, verify_header, verify_misbehaviour, verify_storage,
+	},
+	client_message::{ClientMessage, Misbehaviour},
+	client_state::ClientState,
+	consensus_state::ConsensusState,
+	error::Error,
+	mock::{client_state, light_client_update, sync_committee, SLOTS_PER_PERIOD},
+	proto::StorageProof,
+	ssz::Root,
+	HostFunctions,
+};
+use ibc::{
+	core::{
+		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+		ics24_host::{
+			identifier::{ChannelId, PortId},
+			path::{CommitmentsPath, ReceiptsPath},
+			Path,
+		},
+	},
+	Height,
+};
+use prost::Message;
+use rlp::RlpStream;
+use sha2::Digest;
+use tendermint_proto::Protobuf;
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+struct HostFunctionsManager;
+
+impl HostFunctions for HostFunctionsManager {
+	fn sha256(message: &[u8]) -> [u8; 32] {
+		sha2::Sha256::digest(message).into()
+	}
+
+	fn keccak_256(message: &[u8]) -> [u8; 32] {
+		sha3::Keccak256::digest(message).into()
+	}
+
+	fn bls_fast_aggregate_verify(
+		public_keys: &[[u8; 48]],
+		message: &[u8; 32],
+		signature: &[u8; 96],
+	) -> bool {
+		let public_keys = match public_keys
+			.iter()
+			.map(|key| milagro_bls::PublicKey::from_bytes(key))
+			.collect::<Result<Vec<_>, _>>()
+		{
+			Ok(public_keys) => public_keys,
+			Err(_) => return false,
+		};
+		let signature = match milagro_bls::AggregateSignature::from_bytes(signature) {
+			Ok(signature) => signature,
+			Err(_) => return false,
+		};
+		signature.fast_aggregate_verify(message, &public_keys.iter().collect::<Vec<_>>())
+	}
+}
+
+type H = HostFunctionsManager;
+
+#[test]
+fn test_verify_and_apply_header() {
+	let client_state = client_state::<H>(SLOTS_PER_PERIOD + 64);
+	let header =
+		light_client_update::<H>(SLOTS_PER_PERIOD + 128, 120, [3; 32], &sync_committee(2), 1, 400);
+
+	verify_header(&client_state, &header).unwrap();
+	let (client_state, height, consensus_state) = apply_header(client_state, header).unwrap();
+
+	assert_eq!(height, Height::new(0, 120));
+	assert_eq!(client_state.latest_height(), height);
+	assert_eq!(client_state.latest_slot, SLOTS_PER_PERIOD + 128);
+	assert_eq!(client_state.current_sync_committee, sync_committee(1));
+	assert_eq!(consensus_state.root, CommitmentRoot::from(vec![3; 32]));
+	assert_eq!(consensus_state.timestamp.nanoseconds(), (1_700_000_000 + 120 * 12) * 1_000_000_000);
+}
+
+#[test]
+fn test_header_rotates_sync_committees() {
+	let client_state = client_state::<H>(SLOTS_PER_PERIOD + 64);
+	// finalized in the next period, signed by the next sync committee
+	let header = light_client_update::<H>(
+		2 * SLOTS_PER_PERIOD + 32,
+		120,
+		[3; 32],
+		&sync_committee(3),
+		2,
+		512,
+	);
+
+	verify_header(&client_state, &header).unwrap();
+	let (client_state, ..) = apply_header(client_state, header).unwrap();
+
+	assert_eq!(client_state.current_sync_committee, sync_committee(2));
+	assert_eq!(client_state.next_sync_committee, Some(sync_committee(3)));
+
+	// the rotated out committee can't sign updates anymore
+	let header = light_client_update::<H>(
+		2 * SLOTS_PER_PERIOD + 96,
+		130,
+		[4; 32],
+		&sync_committee(3),
+		1,
+		512,
+	);
+	assert!(matches!(verify_header(&client_state, &header), Err(Error::InvalidSignature)));
+}
+
+#[test]
+fn test_header_requires_supermajority_participation() {
+	let client_state = client_state::<H>(SLOTS_PER_PERIOD + 64);
+	let header =
+		light_client_update::<H>(SLOTS_PER_PERIOD + 128, 120, [3; 32], &sync_committee(2), 1, 341);
+
+	assert!(verify_header(&client_state, &header).is_err());
+}
+
+#[test]
+fn test_header_rejects_invalid_proofs() {
+	let client_state = client_state::<H>(SLOTS_PER_PERIOD + 64);
+	let valid =
+		light_client_update::<H>(SLOTS_PER_PERIOD + 128, 120, [3; 32], &sync_committee(2), 1, 400);
+
+	// the attested header isn't the one the sync committee signed
+	let mut header = valid.clone();
+	header.attested_header.proposer_index += 1;
+	assert!(matches!(verify_header(&client_state, &header), Err(Error::InvalidSignature)));
+
+	let mut header = valid.clone();
+	header.finalized_header.proposer_index += 1;
+	assert!(matches!(verify_header(&client_state, &header), Err(Error::InvalidMerkleBranch(_))));
+
+	let mut header = valid.clone();
+	header.finalized_execution.state_root = [4; 32];
+	assert!(matches!(verify_header(&client_state, &header), Err(Error::InvalidMerkleBranch(_))));
+
+	let mut header = valid.clone();
+	header.finalized_execution.block_number += 1;
+	assert!(matches!(verify_header(&client_state, &header), Err(Error::InvalidMerkleBranch(_))));
+
+	// the known next sync committee can't be replaced
+	let mut header = valid.clone();
+	header.next_sync_committee = Some(sync_committee(4));
+	assert!(verify_header(&client_state, &header).is_err());
+
+	// headers must be newer than the latest one
+	let header =
+		light_client_update::<H>(SLOTS_PER_PERIOD + 64, 120, [3; 32], &sync_committee(2), 1, 400);
+	assert!(verify_header(&client_state, &header).is_err());
+
+	let frozen = client_state.with_frozen_height(Height::new(0, 100)).unwrap();
+	assert!(matches!(verify_header(&frozen, &valid), Err(Error::Frozen)));
+}
+
+#[test]
+fn test_misbehaviour() {
+	let client_state = client_state::<H>(SLOTS_PER_PERIOD + 64);
+	let first_header =
+		light_client_update::<H>(SLOTS_PER_PERIOD + 128, 120, [3; 32], &sync_committee(2), 1, 400);
+	let second_header =
+		light_client_update::<H>(SLOTS_PER_PERIOD + 128, 120, [4; 32], &sync_committee(2), 1, 400);
+
+	let misbehaviour =
+		Misbehaviour { first_header: first_header.clone(), second_header: second_header.clone() };
+	verify_misbehaviour(&client_state, &misbehaviour).unwrap();
+
+	let misbehaviour =
+		Misbehaviour { first_header: first_header.clone(), second_header: first_header };
+	assert!(verify_misbehaviour(&client_state, &misbehaviour).is_err());
+
+	let third_header =
+		light_client_update::<H>(SLOTS_PER_PERIOD + 160, 121, [4; 32], &sync_committee(2), 1, 400);
+	let misbehaviour = Misbehaviour { first_header: second_header, second_header: third_header };
+	assert!(verify_misbehaviour(&client_state, &misbehaviour).is_err());
+}
+
+#[test]
+fn test_client_message_encoding() {
+	let header =
+		light_client_update::<H>(SLOTS_PER_PERIOD + 128, 120, [3; 32], &sync_committee(2), 1, 400);
+	let message = ClientMessage::Header(header);
+	let decoded = ClientMessage::decode_vec(&message.encode_vec()).unwrap();
+	assert_eq!(decoded, message);
+
+	let client_state = client_state::<H>(SLOTS_PER_PERIOD + 64);
+	let decoded = ClientState::<H>::decode_vec(&client_state.encode_vec()).unwrap();
+	assert_eq!(decoded, client_state);
+
+	let consensus_state = ConsensusState::new([3; 32], 1_700_000_000, 64).unwrap();
+	let decoded = ConsensusState::decode_vec(&consensus_state.encode_vec()).unwrap();
+	assert_eq!(decoded, consensus_state);
+}
+
+/// A trie holding a single leaf, returns its root and the proof of the leaf.
+fn single_leaf_trie(key: &[u8; 32], value: &[u8]) -> (Root, Vec<Vec<u8>>) {
+	// even length leaf path: flag byte 0x20 followed by the key
+	let mut path = vec![0x20];
+	path.extend_from_slice(key);
+	let mut stream = RlpStream::new_list(2);
+	stream.append(&path).append(&value.to_vec());
+	let node = stream.out().to_vec();
+	(H::keccak_256(&node), vec![node])
+}
+
+fn storage_proof(
+	client_state: &ClientState<H>,
+	prefix: &CommitmentPrefix,
+	path: Path,
+	value: &[u8],
+) -> (Root, CommitmentProofBytes) {
+	let slot = commitment_slot(client_state, prefix, path);
+	let stored_value = rlp::encode(&H::keccak_256(value).to_vec()).to_vec();
+	let (storage_root, storage_proof) = single_leaf_trie(&H::keccak_256(&slot), &stored_value);
+
+	let mut account = RlpStream::new_list(4);
+	account
+		.append(&1u64)
+		.append(&0u64)
+		.append(&storage_root.to_vec())
+		.append(&[0u8; 32].to_vec());
+	let account_key = H::keccak_256(&client_state.ibc_handler_address);
+	let (state_root, account_proof) = single_leaf_trie(&account_key, &account.out());
+
+	let proof = StorageProof { account_proof, storage_proof }.encode_to_vec();
+	(state_root, proof.try_into().unwrap())
+}
+
+#[test]
+fn test_verify_storage() {
+	let client_state = client_state::<H>(SLOTS_PER_PERIOD + 64);
+	let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+	let path = CommitmentsPath {
+		port_id: PortId::transfer(),
+		channel_id: ChannelId::new(0),
+		sequence: 1.into(),
+	};
+	let (state_root, proof) =
+		storage_proof(&client_state, &prefix, path.clone().into(), b"commitment");
+	let root = CommitmentRoot::from(state_root.to_vec());
+
+	verify_storage::<H, _>(
+		&client_state,
+		&prefix,
+		&proof,
+		&root,
+		path.clone(),
+		Some(b"commitment".to_vec()),
+	)
+	.unwrap();
+	assert!(verify_storage::<H, _>(
+		&client_state,
+		&prefix,
+		&proof,
+		&root,
+		path.clone(),
+		Some(b"other".to_vec())
+	)
+	.is_err());
+	// the commitment exists, so its absence can't be proven
+	assert!(verify_storage::<H, _>(&client_state, &prefix, &proof, &root, path, None).is_err());
+
+	// the proof shows that nothing is stored at a different path
+	let receipt = ReceiptsPath {
+		port_id: PortId::transfer(),
+		channel_id: ChannelId::new(0),
+		sequence: 1.into(),
+	};
+	verify_storage::<H, _>(&client_state, &prefix, &proof, &root, receipt, None).unwrap();
+
+	let other_root = CommitmentRoot::from(vec![0; 32]);
+	let path = CommitmentsPath {
+		port_id: PortId::transfer(),
+		channel_id: ChannelId::new(0),
+		sequence: 1.into(),
+	};
+	assert!(verify_storage::<H, _>(
+		&client_state,
+		&prefix,
+		&proof,
+		&other_root,
+		path,
+		Some(b"commitment".to_vec())
+	)
+	.is_err());
+}
@@ -0,0 +1,126 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of execution layer merkle-patricia trie proofs, as returned by `eth_getProof`.
+
+use crate::{error::Error, HostFunctions};
+use alloc::{collections::BTreeMap, format, vec::Vec};
+use rlp::Rlp;
+
+/// Looks up `key` in the trie with the given root, returns `None` if the proof shows that the key
+/// isn't in the trie.
+pub fn get<H: HostFunctions>(
+	root: &[u8; 32],
+	key: &[u8],
+	proof: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>, Error> {
+	let nodes = proof
+		.iter()
+		.map(|node| (H::keccak_256(node), &node[..]))
+		.collect::<BTreeMap<_, _>>();
+	let nibbles = key.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect::<Vec<_>>();
+
+	let mut node = *nodes
+		.get(root)
+		.ok_or_else(|| Error::InvalidStorageProof("Missing root node".into()))?;
+	let mut offset = 0;
+	loop {
+		let rlp = Rlp::new(node);
+		match rlp.item_count()? {
+			// branch node
+			17 => {
+				if offset == nibbles.len() {
+					let value = rlp.at(16)?.data()?;
+					return Ok((!value.is_empty()).then(|| value.to_vec()))
+				}
+				match child(&nodes, rlp.at(nibbles[offset] as usize)?)? {
+					Some(next) => node = next,
+					None => return Ok(None),
+				}
+				offset += 1;
+			},
+			// leaf or extension node
+			2 => {
+				let (is_leaf, path) = decode_compact_path(rlp.at(0)?.data()?)?;
+				let remaining = &nibbles[offset..];
+				if is_leaf {
+					if remaining != &path[..] {
+						return Ok(None)
+					}
+					return Ok(Some(rlp.at(1)?.data()?.to_vec()))
+				}
+				if !remaining.starts_with(&path) {
+					return Ok(None)
+				}
+				offset += path.len();
+				match child(&nodes, rlp.at(1)?)? {
+					Some(next) => node = next,
+					None => return Ok(None),
+				}
+			},
+			count =>
+				Err(Error::InvalidStorageProof(format!("Invalid trie node with {count} items")))?,
+		}
+	}
+}
+
+/// Resolves a reference to a child node, which is either the hash of a node in the proof or the
+/// node itself if its encoding is shorter than 32 bytes.
+fn child<'a>(
+	nodes: &BTreeMap<[u8; 32], &'a [u8]>,
+	reference: Rlp<'a>,
+) -> Result<Option<&'a [u8]>, Error> {
+	if reference.is_list() {
+		return Ok(Some(reference.as_raw()))
+	}
+	let data = reference.data()?;
+	if data.is_empty() {
+		return Ok(None)
+	}
+	let hash: [u8; 32] = data
+		.try_into()
+		.map_err(|_| Error::InvalidStorageProof("Invalid trie node reference".into()))?;
+	nodes
+		.get(&hash)
+		.copied()
+		.map(Some)
+		.ok_or_else(|| Error::InvalidStorageProof("Missing trie node".into()))
+}
+
+/// Decodes the hex-prefix encoded path of a leaf or extension node into nibbles.
+fn decode_compact_path(path: &[u8]) -> Result<(bool, Vec<u8>), Error> {
+	let first = *path
+		.first()
+		.ok_or_else(|| Error::InvalidStorageProof("Empty node path".into()))?;
+	let flag = first >> 4;
+	let is_leaf = flag & 2 == 2;
+	let mut nibbles = Vec::with_capacity(path.len() * 2);
+	// odd length paths carry their first nibble in the flag byte
+	if flag & 1 == 1 {
+		nibbles.push(first & 0x0f);
+	}
+	nibbles.extend(path[1..].iter().flat_map(|byte| [byte >> 4, byte & 0x0f]));
+	Ok((is_leaf, nibbles))
+}
+
+/// Storage root of an RLP encoded account.
+pub fn account_storage_root(account: &[u8]) -> Result<[u8; 32], Error> {
+	// accounts are encoded as [nonce, balance, storage_root, code_hash]
+	Rlp::new(account)
+		.at(2)?
+		.data()?
+		.try_into()
+		.map_err(|_| Error::InvalidStorageProof("Invalid account storage root".into()))
+}
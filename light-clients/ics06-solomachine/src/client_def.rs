@@ -0,0 +1,501 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	client_message::{ClientMessage, Header, Misbehaviour, SignatureAndData},
+	client_state::ClientState,
+	consensus_state::ConsensusState,
+	error::Error,
+	HostFunctions,
+};
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec,
+	vec::Vec,
+};
+use core::{fmt::Write, marker::PhantomData};
+use ibc::{
+	core::{
+		ics02_client::{
+			client_consensus::ConsensusState as _,
+			client_def::{ClientDef, ConsensusUpdateResult},
+			client_state::ClientState as _,
+			error::Error as Ics02Error,
+		},
+		ics03_connection::connection::ConnectionEnd,
+		ics04_channel::{
+			channel::ChannelEnd,
+			commitment::{AcknowledgementCommitment, PacketCommitment},
+			packet::Sequence,
+		},
+		ics23_commitment::{
+			commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+			merkle::apply_prefix,
+		},
+		ics24_host::{
+			identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			path::{
+				AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath,
+				CommitmentsPath, ConnectionsPath, ReceiptsPath, SeqRecvsPath,
+			},
+			Path,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	Height,
+};
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::lightclients::solomachine::v2::{
+		ChannelStateData, ClientStateData, ConnectionStateData, ConsensusStateData, DataType,
+		HeaderData, NextSequenceRecvData, PacketAcknowledgementData, PacketCommitmentData,
+		PacketReceiptAbsenceData, SignBytes, TimestampedSignatureData,
+	},
+};
+use prost::Message;
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct SoloMachineClient<H>(PhantomData<H>);
+
+impl<H> ClientDef for SoloMachineClient<H>
+where
+	H: HostFunctions,
+{
+	type ClientMessage = ClientMessage;
+	type ClientState = ClientState<H>;
+	type ConsensusState = ConsensusState;
+
+	fn verify_client_message<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<(), Ics02Error> {
+		match client_message {
+			ClientMessage::Header(header) => verify_header(&client_state, &header)?,
+			ClientMessage::Misbehaviour(misbehaviour) =>
+				verify_misbehaviour(&client_state, &misbehaviour)?,
+		}
+
+		Ok(())
+	}
+
+	fn update_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
+		let header = match client_message {
+			ClientMessage::Header(header) => header,
+			_ => unreachable!("02-client will check for Header before calling update_state; qed"),
+		};
+		let client_state = apply_header(client_state, header);
+		let cs = Ctx::AnyConsensusState::wrap(&client_state.consensus_state).ok_or_else(|| {
+			Ics02Error::unknown_consensus_state_type("Ctx::AnyConsensusState".to_string())
+		})?;
+		Ok((client_state, ConsensusUpdateResult::Single(cs)))
+	}
+
+	fn update_state_on_misbehaviour(
+		&self,
+		client_state: Self::ClientState,
+		_client_message: Self::ClientMessage,
+	) -> Result<Self::ClientState, Ics02Error> {
+		Ok(client_state.frozen())
+	}
+
+	/// Misbehaviour is verified in [`ClientDef::verify_client_message`], a valid header can't
+	/// conflict with the current state since it is signed at the current sequence.
+	fn check_for_misbehaviour<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		_client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<bool, Ics02Error> {
+		Ok(matches!(client_message, ClientMessage::Misbehaviour(_)))
+	}
+
+	fn verify_upgrade_and_update_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		_old_client_state: &Self::ClientState,
+		_upgrade_client_state: &Self::ClientState,
+		_upgrade_consensus_state: &Self::ConsensusState,
+		_proof_upgrade_client: Vec<u8>,
+		_proof_upgrade_consensus_state: Vec<u8>,
+	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
+		Err(Error::Custom("Solo machine clients can't be upgraded".to_string()).into())
+	}
+
+	/// Every proof is signed at the current sequence, so it is consumed once the proof is verified.
+	fn client_state_after_proof(
+		&self,
+		client_state: &Self::ClientState,
+	) -> Result<Option<Self::ClientState>, Ics02Error> {
+		Ok(Some(consume_sequence(client_state)))
+	}
+
+	fn verify_client_consensus_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		client_id: &ClientId,
+		consensus_height: Height,
+		expected_consensus_state: &Ctx::AnyConsensusState,
+	) -> Result<(), Ics02Error> {
+		let path = ClientConsensusStatePath {
+			client_id: client_id.clone(),
+			epoch: consensus_height.revision_number,
+			height: consensus_height.revision_height,
+		};
+		let consensus_state =
+			Any::decode(&*expected_consensus_state.encode_to_vec()).map_err(Error::from)?;
+		verify_signature(client_state, height, prefix, proof, path, |path| {
+			let data = ConsensusStateData { path, consensus_state: Some(consensus_state) };
+			(DataType::ConsensusState, data.encode_to_vec())
+		})
+	}
+
+	fn verify_connection_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		connection_id: &ConnectionId,
+		expected_connection_end: &ConnectionEnd,
+	) -> Result<(), Ics02Error> {
+		let path = ConnectionsPath(connection_id.clone());
+		verify_signature(client_state, height, prefix, proof, path, |path| {
+			let data = ConnectionStateData {
+				path,
+				connection: Some(expected_connection_end.clone().into()),
+			};
+			(DataType::ConnectionState, data.encode_to_vec())
+		})
+	}
+
+	fn verify_channel_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		expected_channel_end: &ChannelEnd,
+	) -> Result<(), Ics02Error> {
+		let path = ChannelEndsPath(port_id.clone(), *channel_id);
+		verify_signature(client_state, height, prefix, proof, path, |path| {
+			let data =
+				ChannelStateData { path, channel: Some(expected_channel_end.clone().into()) };
+			(DataType::ChannelState, data.encode_to_vec())
+		})
+	}
+
+	fn verify_client_full_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		client_id: &ClientId,
+		expected_client_state: &Ctx::AnyClientState,
+	) -> Result<(), Ics02Error> {
+		let path = ClientStatePath(client_id.clone());
+		let expected_client_state =
+			Any::decode(&*expected_client_state.encode_to_vec()).map_err(Error::from)?;
+		verify_signature(client_state, height, prefix, proof, path, |path| {
+			let data = ClientStateData { path, client_state: Some(expected_client_state) };
+			(DataType::ClientState, data.encode_to_vec())
+		})
+	}
+
+	fn verify_packet_data<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		commitment: PacketCommitment,
+	) -> Result<(), Ics02Error> {
+		let path = CommitmentsPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		let prefix = connection_end.counterparty().prefix();
+		verify_signature(client_state, height, prefix, proof, path, |path| {
+			let data = PacketCommitmentData { path, commitment: commitment.into_vec() };
+			(DataType::PacketCommitment, data.encode_to_vec())
+		})
+	}
+
+	fn verify_packet_acknowledgement<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		ack: AcknowledgementCommitment,
+	) -> Result<(), Ics02Error> {
+		let path = AcksPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		let prefix = connection_end.counterparty().prefix();
+		verify_signature(client_state, height, prefix, proof, path, |path| {
+			let data = PacketAcknowledgementData { path, acknowledgement: ack.into_vec() };
+			(DataType::PacketAcknowledgement, data.encode_to_vec())
+		})
+	}
+
+	fn verify_next_sequence_recv<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Result<(), Ics02Error> {
+		let path = SeqRecvsPath(port_id.clone(), *channel_id);
+		let prefix = connection_end.counterparty().prefix();
+		verify_signature(client_state, height, prefix, proof, path, |path| {
+			let data = NextSequenceRecvData { path, next_seq_recv: sequence.into() };
+			(DataType::NextSequenceRecv, data.encode_to_vec())
+		})
+	}
+
+	fn verify_packet_receipt_absence<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Result<(), Ics02Error> {
+		let path = ReceiptsPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		let prefix = connection_end.counterparty().prefix();
+		verify_signature(client_state, height, prefix, proof, path, |path| {
+			let data = PacketReceiptAbsenceData { path };
+			(DataType::PacketReceiptAbsence, data.encode_to_vec())
+		})
+	}
+}
+
+/// Verify that the header is signed by the current public key at the current sequence.
+pub fn verify_header<H: HostFunctions>(
+	client_state: &ClientState<H>,
+	header: &Header,
+) -> Result<(), Error> {
+	if client_state.is_frozen {
+		Err(Error::Frozen)?
+	}
+	if header.sequence != client_state.sequence {
+		Err(Error::Custom(format!(
+			"Header sequence {} doesn't match the client sequence {}",
+			header.sequence, client_state.sequence
+		)))?
+	}
+	let consensus_state = &client_state.consensus_state;
+	if header.timestamp.nanoseconds() < consensus_state.timestamp.nanoseconds() {
+		Err(Error::Custom(format!(
+			"Header timestamp {} is older than the consensus state timestamp {}",
+			header.timestamp, consensus_state.timestamp
+		)))?
+	}
+	let data = HeaderData {
+		new_pub_key: Some(header.new_public_key.clone().into()),
+		new_diversifier: header.new_diversifier.clone(),
+	};
+	let sign_bytes = sign_bytes(
+		header.sequence,
+		header.timestamp.nanoseconds(),
+		consensus_state.diversifier.clone(),
+		DataType::Header,
+		data.encode_to_vec(),
+	);
+	consensus_state.public_key.verify::<H>(&sign_bytes, &header.signature)
+}
+
+/// Bump the client sequence and replace the consensus state with the public key and diversifier
+/// of a verified header.
+pub fn apply_header<H>(client_state: ClientState<H>, header: Header) -> ClientState<H> {
+	ClientState {
+		sequence: client_state.sequence + 1,
+		consensus_state: ConsensusState::new(
+			header.new_public_key,
+			header.new_diversifier,
+			header.timestamp,
+		),
+		..client_state
+	}
+}
+
+/// Bump the client sequence after a proof signed at the current sequence was verified.
+pub fn consume_sequence<H: Clone>(client_state: &ClientState<H>) -> ClientState<H> {
+	ClientState { sequence: client_state.sequence + 1, ..client_state.clone() }
+}
+
+/// Verify that both signatures of the misbehaviour were produced by the current public key over
+/// different data of the same type at the same sequence.
+pub fn verify_misbehaviour<H: HostFunctions>(
+	client_state: &ClientState<H>,
+	misbehaviour: &Misbehaviour,
+) -> Result<(), Error> {
+	if client_state.is_frozen {
+		Err(Error::Frozen)?
+	}
+	if misbehaviour.signature_one.signature == misbehaviour.signature_two.signature {
+		Err(Error::Custom("Misbehaviour signatures can't be equal".to_string()))?
+	}
+	if misbehaviour.signature_one.data_type != misbehaviour.signature_two.data_type {
+		Err(Error::Custom("Misbehaviour signatures must be over the same data type".to_string()))?
+	}
+	if misbehaviour.signature_one.data == misbehaviour.signature_two.data {
+		Err(Error::Custom("Misbehaviour signatures must be over different data".to_string()))?
+	}
+	let verify = |signature: &SignatureAndData| {
+		let sign_bytes = sign_bytes(
+			misbehaviour.sequence,
+			signature.timestamp.nanoseconds(),
+			client_state.consensus_state.diversifier.clone(),
+			signature.data_type,
+			signature.data.clone(),
+		);
+		client_state
+			.consensus_state
+			.public_key
+			.verify::<H>(&sign_bytes, &signature.signature)
+	};
+	verify(&misbehaviour.signature_one)?;
+	verify(&misbehaviour.signature_two)
+}
+
+/// The bytes a solo machine signs, for both headers and proofs.
+pub fn sign_bytes(
+	sequence: u64,
+	timestamp: u64,
+	diversifier: String,
+	data_type: DataType,
+	data: Vec<u8>,
+) -> Vec<u8> {
+	SignBytes { sequence, timestamp, diversifier, data_type: data_type as i32, data }
+		.encode_to_vec()
+}
+
+/// Merkle path of `path` under `prefix`, formatted the same way as ibc-go's `MerklePath.String`.
+pub fn merkle_path<P: Into<Path>>(prefix: &CommitmentPrefix, path: P) -> Vec<u8> {
+	let merkle_path = apply_prefix(prefix, vec![path.into().to_string()]);
+	let mut formatted = String::new();
+	for key in merkle_path.key_path {
+		formatted.push('/');
+		path_escape(&key, &mut formatted);
+	}
+	formatted.into_bytes()
+}
+
+/// Percent encodes a path segment, matching go's `url.PathEscape`.
+fn path_escape(segment: &str, out: &mut String) {
+	for byte in segment.bytes() {
+		match byte {
+			b'A'..=b'Z' |
+			b'a'..=b'z' |
+			b'0'..=b'9' |
+			b'-' |
+			b'_' |
+			b'.' |
+			b'~' |
+			b'$' |
+			b'&' |
+			b'+' |
+			b':' |
+			b'=' |
+			b'@' => out.push(byte as char),
+			_ => {
+				let _ = write!(out, "%{:02X}", byte);
+			},
+		}
+	}
+}
+
+/// Verify that `proof` is a signature by the solo machine over the data built by `data` for the
+/// merkle path of `path`, at the current sequence.
+pub(crate) fn verify_signature<H, P, F>(
+	client_state: &ClientState<H>,
+	height: Height,
+	prefix: &CommitmentPrefix,
+	proof: &CommitmentProofBytes,
+	path: P,
+	data: F,
+) -> Result<(), Ics02Error>
+where
+	H: HostFunctions,
+	P: Into<Path>,
+	F: FnOnce(Vec<u8>) -> (DataType, Vec<u8>),
+{
+	client_state.verify_height(height)?;
+	let proof = TimestampedSignatureData::decode(proof.as_bytes()).map_err(Error::from)?;
+	let consensus_state = &client_state.consensus_state;
+	if proof.timestamp < consensus_state.timestamp.nanoseconds() {
+		Err(Error::Custom(format!(
+			"Proof timestamp {} is older than the consensus state timestamp {}",
+			proof.timestamp, consensus_state.timestamp
+		)))?
+	}
+	let (data_type, data) = data(merkle_path(prefix, path));
+	let sign_bytes = sign_bytes(
+		client_state.sequence,
+		proof.timestamp,
+		consensus_state.diversifier.clone(),
+		data_type,
+		data,
+	);
+	consensus_state.public_key.verify::<H>(&sign_bytes, &proof.signature_data)?;
+	Ok(())
+}
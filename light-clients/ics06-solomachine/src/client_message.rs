@@ -0,0 +1,233 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::Error, public_key::PublicKey};
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use core::str::FromStr;
+use ibc::{core::ics24_host::identifier::ClientId, timestamp::Timestamp};
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::lightclients::solomachine::v2::{
+		DataType, Header as RawHeader, Misbehaviour as RawMisbehaviour,
+		SignatureAndData as RawSignatureAndData,
+	},
+};
+use tendermint_proto::Protobuf;
+
+/// Protobuf type url for the SoloMachine ClientMessage, wraps a header or a misbehaviour.
+pub const SOLOMACHINE_CLIENT_MESSAGE_TYPE_URL: &str =
+	"/ibc.lightclients.solomachine.v2.ClientMessage";
+pub const SOLOMACHINE_HEADER_TYPE_URL: &str = "/ibc.lightclients.solomachine.v2.Header";
+pub const SOLOMACHINE_MISBEHAVIOUR_TYPE_URL: &str = "/ibc.lightclients.solomachine.v2.Misbehaviour";
+
+/// Signed by the current public key to advance the sequence and rotate the public key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+	/// Must equal the client sequence
+	pub sequence: u64,
+	pub timestamp: Timestamp,
+	/// Signature over the header sign bytes
+	pub signature: Vec<u8>,
+	pub new_public_key: PublicKey,
+	pub new_diversifier: String,
+}
+
+/// A signature over some data of the given type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignatureAndData {
+	pub signature: Vec<u8>,
+	pub data_type: DataType,
+	pub data: Vec<u8>,
+	pub timestamp: Timestamp,
+}
+
+/// Two different signatures by the solo machine at the same sequence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Misbehaviour {
+	pub client_id: ClientId,
+	pub sequence: u64,
+	pub signature_one: SignatureAndData,
+	pub signature_two: SignatureAndData,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClientMessage {
+	Header(Header),
+	Misbehaviour(Misbehaviour),
+}
+
+impl ibc::core::ics02_client::client_message::ClientMessage for ClientMessage {
+	fn encode_to_vec(&self) -> Vec<u8> {
+		self.encode_vec()
+	}
+}
+
+impl Protobuf<Any> for ClientMessage {}
+
+impl TryFrom<Any> for ClientMessage {
+	type Error = Error;
+
+	fn try_from(any: Any) -> Result<Self, Self::Error> {
+		let msg = match &*any.type_url {
+			SOLOMACHINE_HEADER_TYPE_URL => Self::Header(
+				Header::decode_vec(&any.value).map_err(|e| Error::Custom(format!("{e:?}")))?,
+			),
+			SOLOMACHINE_MISBEHAVIOUR_TYPE_URL => Self::Misbehaviour(
+				Misbehaviour::decode_vec(&any.value)
+					.map_err(|e| Error::Custom(format!("{e:?}")))?,
+			),
+			_ => Err(Error::Custom(format!("Unknown type: {}", any.type_url)))?,
+		};
+
+		Ok(msg)
+	}
+}
+
+impl From<ClientMessage> for Any {
+	fn from(msg: ClientMessage) -> Self {
+		match msg {
+			ClientMessage::Header(header) => Any {
+				type_url: SOLOMACHINE_HEADER_TYPE_URL.to_string(),
+				value: header.encode_vec(),
+			},
+			ClientMessage::Misbehaviour(misbehaviour) => Any {
+				type_url: SOLOMACHINE_MISBEHAVIOUR_TYPE_URL.to_string(),
+				value: misbehaviour.encode_vec(),
+			},
+		}
+	}
+}
+
+fn timestamp(nanoseconds: u64) -> Result<Timestamp, Error> {
+	if nanoseconds == 0 {
+		Err(Error::Custom("Timestamp can't be zero".into()))?
+	}
+	Timestamp::from_nanoseconds(nanoseconds)
+		.map_err(|e| Error::Custom(format!("Invalid timestamp: {e}")))
+}
+
+impl Protobuf<RawHeader> for Header {}
+
+impl TryFrom<RawHeader> for Header {
+	type Error = Error;
+
+	fn try_from(raw: RawHeader) -> Result<Self, Self::Error> {
+		if raw.sequence == 0 {
+			Err(Error::Custom("Invalid header: sequence can't be zero".into()))?
+		}
+		if raw.signature.is_empty() {
+			Err(Error::Custom("Invalid header: missing signature".into()))?
+		}
+		let new_public_key = raw
+			.new_public_key
+			.ok_or_else(|| Error::Custom("Invalid header: missing new public key".into()))?
+			.try_into()?;
+
+		Ok(Self {
+			sequence: raw.sequence,
+			timestamp: timestamp(raw.timestamp)?,
+			signature: raw.signature,
+			new_public_key,
+			new_diversifier: raw.new_diversifier,
+		})
+	}
+}
+
+impl From<Header> for RawHeader {
+	fn from(header: Header) -> Self {
+		RawHeader {
+			sequence: header.sequence,
+			timestamp: header.timestamp.nanoseconds(),
+			signature: header.signature,
+			new_public_key: Some(header.new_public_key.into()),
+			new_diversifier: header.new_diversifier,
+		}
+	}
+}
+
+impl TryFrom<RawSignatureAndData> for SignatureAndData {
+	type Error = Error;
+
+	fn try_from(raw: RawSignatureAndData) -> Result<Self, Self::Error> {
+		let data_type = match DataType::from_i32(raw.data_type) {
+			None | Some(DataType::UninitializedUnspecified) =>
+				Err(Error::Custom(format!("Invalid data type: {}", raw.data_type)))?,
+			Some(data_type) => data_type,
+		};
+		if raw.signature.is_empty() || raw.data.is_empty() {
+			Err(Error::Custom("Signature and data can't be empty".into()))?
+		}
+
+		Ok(Self {
+			signature: raw.signature,
+			data_type,
+			data: raw.data,
+			timestamp: timestamp(raw.timestamp)?,
+		})
+	}
+}
+
+impl From<SignatureAndData> for RawSignatureAndData {
+	fn from(value: SignatureAndData) -> Self {
+		RawSignatureAndData {
+			signature: value.signature,
+			data_type: value.data_type as i32,
+			data: value.data,
+			timestamp: value.timestamp.nanoseconds(),
+		}
+	}
+}
+
+impl Protobuf<RawMisbehaviour> for Misbehaviour {}
+
+impl TryFrom<RawMisbehaviour> for Misbehaviour {
+	type Error = Error;
+
+	fn try_from(raw: RawMisbehaviour) -> Result<Self, Self::Error> {
+		if raw.sequence == 0 {
+			Err(Error::Custom("Invalid misbehaviour: sequence can't be zero".into()))?
+		}
+		let client_id = ClientId::from_str(&raw.client_id)
+			.map_err(|e| Error::Custom(format!("Invalid misbehaviour: {e}")))?;
+		let signature_one: SignatureAndData = raw
+			.signature_one
+			.ok_or_else(|| Error::Custom("Invalid misbehaviour: missing signature one".into()))?
+			.try_into()?;
+		let signature_two: SignatureAndData = raw
+			.signature_two
+			.ok_or_else(|| Error::Custom("Invalid misbehaviour: missing signature two".into()))?
+			.try_into()?;
+		if signature_one.signature == signature_two.signature {
+			Err(Error::Custom("Invalid misbehaviour: signatures can't be equal".into()))?
+		}
+
+		Ok(Self { client_id, sequence: raw.sequence, signature_one, signature_two })
+	}
+}
+
+impl From<Misbehaviour> for RawMisbehaviour {
+	fn from(value: Misbehaviour) -> Self {
+		RawMisbehaviour {
+			client_id: value.client_id.to_string(),
+			sequence: value.sequence,
+			signature_one: Some(value.signature_one.into()),
+			signature_two: Some(value.signature_two.into()),
+		}
+	}
+}
@@ -0,0 +1,163 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	client_def::SoloMachineClient, consensus_state::ConsensusState, error::Error, HostFunctions,
+};
+use alloc::{format, string::ToString, vec::Vec};
+use core::{marker::PhantomData, time::Duration};
+use ibc::{
+	core::{
+		ics02_client::{client_state::ClientType, error::Error as Ics02Error},
+		ics24_host::identifier::ChainId,
+	},
+	Height,
+};
+use ibc_proto::ibc::lightclients::solomachine::v2::ClientState as RawClientState;
+use tendermint_proto::Protobuf;
+
+/// Protobuf type url for SoloMachine ClientState
+pub const SOLOMACHINE_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.solomachine.v2.ClientState";
+
+#[derive(PartialEq, Clone, Debug, Eq)]
+pub struct ClientState<H> {
+	/// Sequence the next header or proof is signed at, also the latest height of the client
+	pub sequence: u64,
+	/// Whether the client was frozen due to a misbehaviour
+	pub is_frozen: bool,
+	/// Current public key, diversifier and timestamp of the solo machine
+	pub consensus_state: ConsensusState,
+	/// Whether governance may replace the client once it is frozen
+	pub allow_update_after_proposal: bool,
+	/// phantom type.
+	pub _phantom: PhantomData<H>,
+}
+
+impl<H: Clone> Protobuf<RawClientState> for ClientState<H> {}
+
+impl<H> ClientState<H> {
+	pub fn client_type() -> ClientType {
+		"06-solomachine".to_string()
+	}
+
+	/// Solo machines have no revisions, the height of the client is its sequence.
+	pub fn latest_height(&self) -> Height {
+		Height::new(0, self.sequence)
+	}
+
+	pub fn frozen_height(&self) -> Option<Height> {
+		self.is_frozen.then(|| self.latest_height())
+	}
+
+	/// Verify that the client is unfrozen and that proofs for `height` are signed at its current
+	/// sequence.
+	pub fn verify_height(&self, height: Height) -> Result<(), Error> {
+		if self.is_frozen {
+			return Err(Error::Frozen)
+		}
+		if height != self.latest_height() {
+			return Err(Error::Custom(format!(
+				"Proofs must be signed at the client sequence {}, given height: {height}",
+				self.sequence
+			)))
+		}
+		Ok(())
+	}
+
+	pub fn frozen(self) -> Self {
+		Self { is_frozen: true, ..self }
+	}
+}
+
+impl<H> ibc::core::ics02_client::client_state::ClientState for ClientState<H>
+where
+	H: HostFunctions,
+{
+	/// Solo machines can't be upgraded, they are replaced by creating a new client.
+	type UpgradeOptions = ();
+	type ClientDef = SoloMachineClient<H>;
+
+	/// The diversifier is the closest a solo machine has to a chain id.
+	fn chain_id(&self) -> ChainId {
+		ChainId::from_string(&self.consensus_state.diversifier)
+	}
+
+	fn client_def(&self) -> Self::ClientDef {
+		SoloMachineClient::default()
+	}
+
+	fn client_type(&self) -> ClientType {
+		Self::client_type()
+	}
+
+	fn latest_height(&self) -> Height {
+		self.latest_height()
+	}
+
+	fn frozen_height(&self) -> Option<Height> {
+		self.frozen_height()
+	}
+
+	fn upgrade(
+		self,
+		_upgrade_height: Height,
+		_upgrade_options: (),
+		_chain_id: ChainId,
+	) -> Result<Self, Ics02Error> {
+		Ok(self)
+	}
+
+	/// Solo machine signatures don't go stale, so the client never expires.
+	fn expired(&self, _elapsed: Duration) -> bool {
+		false
+	}
+
+	fn encode_to_vec(&self) -> Vec<u8> {
+		self.encode_vec()
+	}
+}
+
+impl<H> TryFrom<RawClientState> for ClientState<H> {
+	type Error = Error;
+
+	fn try_from(raw: RawClientState) -> Result<Self, Self::Error> {
+		if raw.sequence == 0 {
+			Err(Error::Custom("Invalid client state: sequence can't be zero".into()))?
+		}
+		let consensus_state = raw
+			.consensus_state
+			.ok_or_else(|| Error::Custom("Invalid client state: missing consensus state".into()))?
+			.try_into()?;
+
+		Ok(Self {
+			sequence: raw.sequence,
+			is_frozen: raw.is_frozen,
+			consensus_state,
+			allow_update_after_proposal: raw.allow_update_after_proposal,
+			_phantom: Default::default(),
+		})
+	}
+}
+
+impl<H> From<ClientState<H>> for RawClientState {
+	fn from(client_state: ClientState<H>) -> Self {
+		RawClientState {
+			sequence: client_state.sequence,
+			is_frozen: client_state.is_frozen,
+			consensus_state: Some(client_state.consensus_state.into()),
+			allow_update_after_proposal: client_state.allow_update_after_proposal,
+		}
+	}
+}
@@ -0,0 +1,88 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::Error, public_key::PublicKey};
+use alloc::{format, string::String, vec::Vec};
+use core::convert::Infallible;
+use ibc::{core::ics23_commitment::commitment::CommitmentRoot, timestamp::Timestamp};
+use ibc_proto::ibc::lightclients::solomachine::v2::ConsensusState as RawConsensusState;
+use tendermint_proto::Protobuf;
+
+/// Protobuf type url for SoloMachine Consensus State
+pub const SOLOMACHINE_CONSENSUS_STATE_TYPE_URL: &str =
+	"/ibc.lightclients.solomachine.v2.ConsensusState";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsensusState {
+	/// Public key the solo machine signs with
+	pub public_key: PublicKey,
+	/// Arbitrary string included in the sign bytes, lets a single key be used with several
+	/// clients without their signatures being interchangeable
+	pub diversifier: String,
+	/// Timestamp of the last update, signatures must not be older than it
+	pub timestamp: Timestamp,
+	/// Solo machines don't commit to their state, this is always empty
+	pub root: CommitmentRoot,
+}
+
+impl ConsensusState {
+	pub fn new(public_key: PublicKey, diversifier: String, timestamp: Timestamp) -> Self {
+		Self { public_key, diversifier, timestamp, root: CommitmentRoot::from(Vec::new()) }
+	}
+}
+
+impl ibc::core::ics02_client::client_consensus::ConsensusState for ConsensusState {
+	type Error = Infallible;
+
+	fn root(&self) -> &CommitmentRoot {
+		&self.root
+	}
+
+	fn timestamp(&self) -> Timestamp {
+		self.timestamp
+	}
+
+	fn encode_to_vec(&self) -> Vec<u8> {
+		self.encode_vec()
+	}
+}
+
+impl Protobuf<RawConsensusState> for ConsensusState {}
+
+impl TryFrom<RawConsensusState> for ConsensusState {
+	type Error = Error;
+
+	fn try_from(raw: RawConsensusState) -> Result<Self, Self::Error> {
+		let public_key = raw
+			.public_key
+			.ok_or_else(|| Error::Custom("Invalid consensus state: missing public key".into()))?
+			.try_into()?;
+		let timestamp = Timestamp::from_nanoseconds(raw.timestamp).map_err(|e| {
+			Error::Custom(format!("Invalid consensus state: invalid timestamp {e}"))
+		})?;
+
+		Ok(Self::new(public_key, raw.diversifier, timestamp))
+	}
+}
+
+impl From<ConsensusState> for RawConsensusState {
+	fn from(value: ConsensusState) -> Self {
+		RawConsensusState {
+			public_key: Some(value.public_key.into()),
+			diversifier: value.diversifier,
+			timestamp: value.timestamp.nanoseconds(),
+		}
+	}
+}
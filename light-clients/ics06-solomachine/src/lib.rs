@@ -0,0 +1,54 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! ICS-06: Solo machine IBC light client protocol implementation.
+//!
+//! A solo machine is a single signer, such as an off-chain process or a wallet, whose state is
+//! attested to by signing it. The client tracks the signer's public key and a sequence number:
+//! headers are signed at the current sequence, bump it by one and can rotate the public key, and
+//! two different signatures over the same sequence freeze the client.
+//!
+//! Proofs are signatures over the sign bytes of the claimed value at the client's current sequence.
+//! Since proof verification can't update the client state, the sequence is only advanced by
+//! headers and a signer should update the client before signing a conflicting value.
+
+extern crate alloc;
+
+use core::fmt::Debug;
+
+pub mod client_def;
+pub mod client_message;
+pub mod client_state;
+pub mod consensus_state;
+pub mod error;
+pub mod public_key;
+
+#[cfg(test)]
+mod tests;
+
+/// Host functions that allow the light client verify signatures in native.
+pub trait HostFunctions: Clone + Send + Sync + Eq + Debug + Default {
+	/// Sha2-256 hashing implementation, secp256k1 signatures are over the hash of the sign bytes.
+	fn sha2_256(message: &[u8]) -> [u8; 32];
+
+	/// Verify an ed25519 signature of `message`.
+	fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool;
+
+	/// Verify a non-recoverable secp256k1 signature of a 32 byte message hash against a compressed
+	/// public key.
+	fn secp256k1_verify(signature: &[u8; 64], message: &[u8; 32], public_key: &[u8; 33]) -> bool;
+}
@@ -0,0 +1,105 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::Error, HostFunctions};
+use alloc::{format, string::ToString, vec::Vec};
+use ibc_proto::{
+	cosmos::tx::signing::v1beta1::signature_descriptor::{data::Sum, Data as RawSignatureData},
+	google::protobuf::Any,
+};
+use prost::Message;
+
+/// Protobuf type url for ed25519 public keys
+pub const ED25519_PUBLIC_KEY_TYPE_URL: &str = "/cosmos.crypto.ed25519.PubKey";
+/// Protobuf type url for secp256k1 public keys
+pub const SECP256K1_PUBLIC_KEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PubKey";
+
+/// Wire format of both cosmos public key types.
+#[derive(Clone, PartialEq, Message)]
+struct RawPublicKey {
+	#[prost(bytes = "vec", tag = "1")]
+	key: Vec<u8>,
+}
+
+/// Public key of the solo machine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PublicKey {
+	Ed25519([u8; 32]),
+	/// Compressed secp256k1 public key
+	Secp256k1([u8; 33]),
+}
+
+impl PublicKey {
+	/// Verify the signature of `sign_bytes`. `signature` is the protobuf encoded signature data of
+	/// a single signer, as produced by cosmos-sdk keyrings.
+	pub fn verify<H: HostFunctions>(
+		&self,
+		sign_bytes: &[u8],
+		signature: &[u8],
+	) -> Result<(), Error> {
+		let signature = decode_signature(signature)?;
+		let valid = match self {
+			PublicKey::Ed25519(public_key) => H::ed25519_verify(&signature, sign_bytes, public_key),
+			PublicKey::Secp256k1(public_key) =>
+				H::secp256k1_verify(&signature, &H::sha2_256(sign_bytes), public_key),
+		};
+		if !valid {
+			Err(Error::InvalidSignature(format!("Signature doesn't match public key {:?}", self)))?
+		}
+
+		Ok(())
+	}
+}
+
+/// Extract the signature of a single signer from the protobuf encoded signature data.
+fn decode_signature(signature: &[u8]) -> Result<[u8; 64], Error> {
+	let data = RawSignatureData::decode(signature)?;
+	let signature = match data.sum {
+		Some(Sum::Single(single)) => single.signature,
+		Some(Sum::Multi(_)) =>
+			Err(Error::InvalidSignature("Multisig signatures are not supported".to_string()))?,
+		None => Err(Error::InvalidSignature("Missing signature".to_string()))?,
+	};
+	let len = signature.len();
+	signature
+		.try_into()
+		.map_err(|_| Error::InvalidSignature(format!("Invalid signature length: {len}")))
+}
+
+impl TryFrom<Any> for PublicKey {
+	type Error = Error;
+
+	fn try_from(any: Any) -> Result<Self, Self::Error> {
+		let key = RawPublicKey::decode(&*any.value)?.key;
+		let len = key.len();
+		let public_key = match &*any.type_url {
+			ED25519_PUBLIC_KEY_TYPE_URL => key.try_into().map(PublicKey::Ed25519),
+			SECP256K1_PUBLIC_KEY_TYPE_URL => key.try_into().map(PublicKey::Secp256k1),
+			_ => Err(Error::Custom(format!("Unknown public key type: {}", any.type_url)))?,
+		};
+
+		public_key.map_err(|_| Error::Custom(format!("Invalid public key length: {len}")))
+	}
+}
+
+impl From<PublicKey> for Any {
+	fn from(public_key: PublicKey) -> Self {
+		let (type_url, key) = match public_key {
+			PublicKey::Ed25519(key) => (ED25519_PUBLIC_KEY_TYPE_URL, key.to_vec()),
+			PublicKey::Secp256k1(key) => (SECP256K1_PUBLIC_KEY_TYPE_URL, key.to_vec()),
+		};
+		Any { type_url: type_url.to_string(), value: RawPublicKey { key }.encode_to_vec() }
+	}
+}
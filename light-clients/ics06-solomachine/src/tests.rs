@@ -0,0 +1,355 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	client_def::{
+		apply_header, consume_sequence, merkle_path, sign_bytes, verify_header,
+		verify_misbehaviour, verify_signature,
+	},
+	client_message::{Header, Misbehaviour, SignatureAndData},
+	client_state::ClientState,
+	consensus_state::ConsensusState,
+	public_key::PublicKey,
+	HostFunctions,
+};
+use core::str::FromStr;
+use ibc::{
+	core::{
+		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes},
+		ics24_host::{
+			identifier::{ChannelId, ClientId, PortId},
+			path::{ClientStatePath, CommitmentsPath},
+		},
+	},
+	timestamp::Timestamp,
+	Height,
+};
+use ibc_proto::{
+	cosmos::tx::signing::v1beta1::signature_descriptor::{
+		data::{Single, Sum},
+		Data,
+	},
+	ibc::lightclients::solomachine::v2::{
+		DataType, HeaderData, PacketCommitmentData, TimestampedSignatureData,
+	},
+};
+use prost::Message;
+use sp_core::{ecdsa, ed25519, Pair};
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+struct HostFunctionsManager;
+
+impl HostFunctions for HostFunctionsManager {
+	fn sha2_256(message: &[u8]) -> [u8; 32] {
+		sp_core::hashing::sha2_256(message)
+	}
+
+	fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool {
+		ed25519::Pair::verify(
+			&ed25519::Signature::from_raw(*signature),
+			message,
+			&ed25519::Public::from_raw(*public_key),
+		)
+	}
+
+	fn secp256k1_verify(signature: &[u8; 64], message: &[u8; 32], public_key: &[u8; 33]) -> bool {
+		let public_key = ecdsa::Public::from_raw(*public_key);
+		(0..2).any(|recovery_id| {
+			let mut raw = [0u8; 65];
+			raw[..64].copy_from_slice(signature);
+			raw[64] = recovery_id;
+			ecdsa::Pair::verify_prehashed(&ecdsa::Signature::from_raw(raw), message, &public_key)
+		})
+	}
+}
+
+const DIVERSIFIER: &str = "solo";
+
+fn timestamp(seconds: u64) -> Timestamp {
+	Timestamp::from_nanoseconds(seconds * 1_000_000_000).unwrap()
+}
+
+fn encode_signature(signature: &[u8]) -> Vec<u8> {
+	Data { sum: Some(Sum::Single(Single { mode: 1, signature: signature.to_vec() })) }
+		.encode_to_vec()
+}
+
+fn sign(pair: &ed25519::Pair, message: &[u8]) -> Vec<u8> {
+	encode_signature(&pair.sign(message).0)
+}
+
+fn client_state(pair: &ed25519::Pair) -> ClientState<HostFunctionsManager> {
+	ClientState {
+		sequence: 1,
+		is_frozen: false,
+		consensus_state: ConsensusState::new(
+			PublicKey::Ed25519(pair.public().0),
+			DIVERSIFIER.to_string(),
+			timestamp(1),
+		),
+		allow_update_after_proposal: false,
+		_phantom: Default::default(),
+	}
+}
+
+fn header(
+	signer: &ed25519::Pair,
+	sequence: u64,
+	new_public_key: PublicKey,
+	new_diversifier: &str,
+) -> Header {
+	let data = HeaderData {
+		new_pub_key: Some(new_public_key.clone().into()),
+		new_diversifier: new_diversifier.to_string(),
+	};
+	let sign_bytes = sign_bytes(
+		sequence,
+		timestamp(2).nanoseconds(),
+		DIVERSIFIER.to_string(),
+		DataType::Header,
+		data.encode_to_vec(),
+	);
+	Header {
+		sequence,
+		timestamp: timestamp(2),
+		signature: sign(signer, &sign_bytes),
+		new_public_key,
+		new_diversifier: new_diversifier.to_string(),
+	}
+}
+
+fn signature_and_data(pair: &ed25519::Pair, sequence: u64, data: &[u8]) -> SignatureAndData {
+	let sign_bytes = sign_bytes(
+		sequence,
+		timestamp(2).nanoseconds(),
+		DIVERSIFIER.to_string(),
+		DataType::PacketCommitment,
+		data.to_vec(),
+	);
+	SignatureAndData {
+		signature: sign(pair, &sign_bytes),
+		data_type: DataType::PacketCommitment,
+		data: data.to_vec(),
+		timestamp: timestamp(2),
+	}
+}
+
+#[test]
+fn header_updates_sequence_and_rotates_public_key() {
+	let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+	let new_pair = ecdsa::Pair::from_seed(&[2u8; 32]);
+	let new_public_key = PublicKey::Secp256k1(new_pair.public().0);
+	let client_state = client_state(&pair);
+
+	let header = header(&pair, 1, new_public_key.clone(), "rotated");
+	verify_header(&client_state, &header).unwrap();
+	let client_state = apply_header(client_state, header);
+
+	assert_eq!(client_state.sequence, 2);
+	assert_eq!(client_state.latest_height(), Height::new(0, 2));
+	assert_eq!(
+		client_state.consensus_state,
+		ConsensusState::new(new_public_key, "rotated".to_string(), timestamp(2))
+	);
+
+	// the new secp256k1 key signs the next header
+	let data = HeaderData {
+		new_pub_key: Some(PublicKey::Ed25519(pair.public().0).into()),
+		new_diversifier: DIVERSIFIER.to_string(),
+	};
+	let sign_bytes = sign_bytes(
+		2,
+		timestamp(3).nanoseconds(),
+		"rotated".to_string(),
+		DataType::Header,
+		data.encode_to_vec(),
+	);
+	let signature = new_pair.sign_prehashed(&sp_core::hashing::sha2_256(&sign_bytes));
+	let header = Header {
+		sequence: 2,
+		timestamp: timestamp(3),
+		signature: encode_signature(&signature.0[..64]),
+		new_public_key: PublicKey::Ed25519(pair.public().0),
+		new_diversifier: DIVERSIFIER.to_string(),
+	};
+	verify_header(&client_state, &header).unwrap();
+}
+
+#[test]
+fn header_with_invalid_signature_or_sequence_is_rejected() {
+	let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+	let other = ed25519::Pair::from_seed(&[3u8; 32]);
+	let client_state = client_state(&pair);
+	let new_public_key = PublicKey::Ed25519(pair.public().0);
+
+	let header_by_other = header(&other, 1, new_public_key.clone(), DIVERSIFIER);
+	assert!(verify_header(&client_state, &header_by_other).is_err());
+
+	let stale_header = header(&pair, 2, new_public_key.clone(), DIVERSIFIER);
+	assert!(verify_header(&client_state, &stale_header).is_err());
+
+	let header = header(&pair, 1, new_public_key, DIVERSIFIER);
+	assert!(verify_header(&client_state.frozen(), &header).is_err());
+}
+
+#[test]
+fn duplicate_signatures_are_misbehaviour() {
+	let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+	let other = ed25519::Pair::from_seed(&[3u8; 32]);
+	let client_state = client_state(&pair);
+
+	let misbehaviour = Misbehaviour {
+		client_id: ClientId::from_str("06-solomachine-0").unwrap(),
+		sequence: 1,
+		signature_one: signature_and_data(&pair, 1, b"commitment one"),
+		signature_two: signature_and_data(&pair, 1, b"commitment two"),
+	};
+	verify_misbehaviour(&client_state, &misbehaviour).unwrap();
+	assert!(verify_misbehaviour(&client_state.clone().frozen(), &misbehaviour).is_err());
+
+	let forged = Misbehaviour {
+		signature_two: signature_and_data(&other, 1, b"commitment two"),
+		..misbehaviour
+	};
+	assert!(verify_misbehaviour(&client_state, &forged).is_err());
+}
+
+#[test]
+fn misbehaviour_requires_conflicting_data_of_the_same_type() {
+	let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+	let client_state = client_state(&pair);
+	let signature = signature_and_data(&pair, 1, b"commitment");
+
+	// the same data signed twice isn't a conflict
+	let same_data = Misbehaviour {
+		client_id: ClientId::from_str("06-solomachine-0").unwrap(),
+		sequence: 1,
+		signature_one: signature.clone(),
+		signature_two: SignatureAndData { signature: vec![0u8; 64], ..signature.clone() },
+	};
+	assert!(verify_misbehaviour(&client_state, &same_data).is_err());
+
+	let sign_bytes = sign_bytes(
+		1,
+		timestamp(2).nanoseconds(),
+		DIVERSIFIER.to_string(),
+		DataType::ConsensusState,
+		b"consensus state".to_vec(),
+	);
+	let other_type = SignatureAndData {
+		signature: sign(&pair, &sign_bytes),
+		data_type: DataType::ConsensusState,
+		data: b"consensus state".to_vec(),
+		timestamp: timestamp(2),
+	};
+	let different_types = Misbehaviour { signature_two: other_type, ..same_data };
+	assert!(verify_misbehaviour(&client_state, &different_types).is_err());
+}
+
+#[test]
+fn proofs_over_different_paths_are_not_misbehaviour() {
+	let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+	let client_state = client_state(&pair);
+
+	// each verified proof consumes a sequence, so honest proofs never share one
+	let first = signature_and_data(&pair, client_state.sequence, b"commitment one");
+	let client_state = consume_sequence(&client_state);
+	assert_eq!(client_state.sequence, 2);
+	let second = signature_and_data(&pair, client_state.sequence, b"commitment two");
+
+	let misbehaviour = Misbehaviour {
+		client_id: ClientId::from_str("06-solomachine-0").unwrap(),
+		sequence: 1,
+		signature_one: first,
+		signature_two: second,
+	};
+	assert!(verify_misbehaviour(&client_state, &misbehaviour).is_err());
+}
+
+#[test]
+fn packet_commitment_proof_is_verified_at_current_sequence() {
+	let pair = ed25519::Pair::from_seed(&[1u8; 32]);
+	let client_state = client_state(&pair);
+	let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+	let path = CommitmentsPath {
+		port_id: PortId::transfer(),
+		channel_id: ChannelId::new(0),
+		sequence: 1.into(),
+	};
+	let commitment = vec![7u8; 32];
+
+	let data = PacketCommitmentData {
+		path: merkle_path(&prefix, path.clone()),
+		commitment: commitment.clone(),
+	};
+	let sign_bytes = sign_bytes(
+		1,
+		timestamp(2).nanoseconds(),
+		DIVERSIFIER.to_string(),
+		DataType::PacketCommitment,
+		data.encode_to_vec(),
+	);
+	let proof = TimestampedSignatureData {
+		signature_data: sign(&pair, &sign_bytes),
+		timestamp: timestamp(2).nanoseconds(),
+	};
+	let proof = CommitmentProofBytes::try_from(proof.encode_to_vec()).unwrap();
+	let data = |path| {
+		let data = PacketCommitmentData { path, commitment: commitment.clone() };
+		(DataType::PacketCommitment, data.encode_to_vec())
+	};
+
+	verify_signature(&client_state, Height::new(0, 1), &prefix, &proof, path.clone(), data)
+		.unwrap();
+	assert!(verify_signature(
+		&client_state,
+		Height::new(0, 2),
+		&prefix,
+		&proof,
+		path.clone(),
+		data
+	)
+	.is_err());
+	let tampered = |path| {
+		let data = PacketCommitmentData { path, commitment: vec![8u8; 32] };
+		(DataType::PacketCommitment, data.encode_to_vec())
+	};
+	assert!(verify_signature(
+		&client_state,
+		Height::new(0, 1),
+		&prefix,
+		&proof,
+		path.clone(),
+		tampered
+	)
+	.is_err());
+
+	// the proof is single use, it doesn't verify against the client after it was consumed
+	let client_state = consume_sequence(&client_state);
+	assert!(
+		verify_signature(&client_state, Height::new(0, 2), &prefix, &proof, path, data).is_err()
+	);
+}
+
+#[test]
+fn merkle_path_is_escaped_like_ibc_go() {
+	let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+	let path = ClientStatePath(ClientId::from_str("07-tendermint-0").unwrap());
+
+	assert_eq!(
+		merkle_path(&prefix, path),
+		b"/ibc/clients%2F07-tendermint-0%2FclientState".to_vec()
+	);
+}
@@ -281,8 +281,8 @@ where
 		upgrade_height: Height,
 		upgrade_options: UpgradeOptions,
 		chain_id: ChainId,
-	) -> Self {
-		self.upgrade(upgrade_height, upgrade_options, chain_id)
+	) -> Result<Self, Ics02Error> {
+		Ok(self.upgrade(upgrade_height, upgrade_options, chain_id))
 	}
 
 	fn expired(&self, elapsed: Duration) -> bool {
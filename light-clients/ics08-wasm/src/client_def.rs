@@ -0,0 +1,435 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	client_message::ClientMessage,
+	client_state::ClientState,
+	consensus_state::ConsensusState,
+	contract::{
+		ContractCall, ContractClientState, NoHostContext, UpdateStateResult, WasmExecutor,
+		WasmHostContext,
+	},
+	error::Error,
+};
+use alloc::{string::ToString, vec::Vec};
+use core::marker::PhantomData;
+use ibc::{
+	core::{
+		ics02_client::{
+			client_consensus::ConsensusState as _,
+			client_def::{ClientDef, ConsensusUpdateResult},
+			client_state::ClientState as _,
+			error::Error as Ics02Error,
+		},
+		ics03_connection::connection::ConnectionEnd,
+		ics04_channel::{
+			channel::ChannelEnd,
+			commitment::{AcknowledgementCommitment, PacketCommitment},
+			packet::Sequence,
+		},
+		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+		ics24_host::{
+			identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			path::{
+				AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath,
+				CommitmentsPath, ConnectionsPath, ReceiptsPath, SeqRecvsPath,
+			},
+			Path,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	Height,
+};
+use light_client_common::verify_delay_passed;
+use tendermint_proto::Protobuf;
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct WasmClient<T>(PhantomData<T>);
+
+/// Exposes the ibc context of the client being executed to the wasm code.
+struct ReaderHostContext<'a, Ctx> {
+	ctx: &'a Ctx,
+	client_id: &'a ClientId,
+}
+
+impl<'a, Ctx: ReaderContext> WasmHostContext for ReaderHostContext<'a, Ctx> {
+	fn client_id(&self) -> Option<&ClientId> {
+		Some(self.client_id)
+	}
+
+	fn consensus_state(&self, height: Height) -> Option<Vec<u8>> {
+		let consensus_state = self.ctx.maybe_consensus_state(self.client_id, height).ok()??;
+		consensus_state.downcast::<ConsensusState>().map(|cs| cs.data)
+	}
+
+	fn host_timestamp(&self) -> u64 {
+		self.ctx.host_timestamp().nanoseconds()
+	}
+}
+
+impl<H: WasmExecutor> WasmClient<H> {
+	/// Have the wasm code verify the membership of `value` at `path`, or its absence if `value`
+	/// is `None`.
+	fn verify<P: Into<Path>>(
+		client_state: &ClientState<H>,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		path: P,
+		value: Option<Vec<u8>>,
+	) -> Result<(), Error> {
+		client_state.verify_height(height)?;
+		let path: Path = path.into();
+		let call = match value {
+			Some(value) => ContractCall::VerifyMembership {
+				client_state: client_state.data.clone(),
+				height: height.into(),
+				prefix: prefix.as_bytes().to_vec(),
+				proof: proof.as_bytes().to_vec(),
+				root: root.as_bytes().to_vec(),
+				path: path.to_string(),
+				value,
+			},
+			None => ContractCall::VerifyNonMembership {
+				client_state: client_state.data.clone(),
+				height: height.into(),
+				prefix: prefix.as_bytes().to_vec(),
+				proof: proof.as_bytes().to_vec(),
+				root: root.as_bytes().to_vec(),
+				path: path.to_string(),
+			},
+		};
+		H::call::<()>(&client_state.code_id, &NoHostContext, &call)
+	}
+
+	/// Convert the consensus states returned from the wasm code.
+	fn consensus_states<Ctx: ReaderContext>(
+		result: UpdateStateResult,
+	) -> Result<Vec<(Height, Ctx::AnyConsensusState)>, Error> {
+		result
+			.consensus_states
+			.into_iter()
+			.map(|state| {
+				let (height, consensus_state) = ConsensusState::from_contract(state)?;
+				let wrapped = Ctx::AnyConsensusState::wrap(&consensus_state)
+					.expect("AnyConsenusState is type checked; qed");
+				Ok((height, wrapped))
+			})
+			.collect()
+	}
+}
+
+impl<H> ClientDef for WasmClient<H>
+where
+	H: WasmExecutor,
+{
+	type ClientMessage = ClientMessage;
+	type ClientState = ClientState<H>;
+	type ConsensusState = ConsensusState;
+
+	fn verify_client_message<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		client_id: ClientId,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<(), Ics02Error> {
+		let host = ReaderHostContext { ctx, client_id: &client_id };
+		let call = ContractCall::VerifyClientMessage {
+			client_state: client_state.data,
+			client_message: client_message.data,
+		};
+		H::call::<()>(&client_state.code_id, &host, &call)?;
+		Ok(())
+	}
+
+	fn update_state<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		client_id: ClientId,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
+		let host = ReaderHostContext { ctx, client_id: &client_id };
+		let call = ContractCall::UpdateState {
+			client_state: client_state.data.clone(),
+			client_message: client_message.data,
+		};
+		let result = H::call::<UpdateStateResult>(&client_state.code_id, &host, &call)?;
+		let client_state = client_state.with_contract_state(result.client_state.clone());
+		let consensus_states = Self::consensus_states::<Ctx>(result)?;
+
+		Ok((client_state, ConsensusUpdateResult::Batch(consensus_states)))
+	}
+
+	fn update_state_on_misbehaviour(
+		&self,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<Self::ClientState, Ics02Error> {
+		let call = ContractCall::UpdateStateOnMisbehaviour {
+			client_state: client_state.data.clone(),
+			client_message: client_message.data,
+		};
+		let state = H::call::<ContractClientState>(&client_state.code_id, &NoHostContext, &call)?;
+		let latest_height = client_state.latest_height;
+		let client_state = client_state.with_contract_state(state);
+		if client_state.frozen_height.is_some() {
+			return Ok(client_state)
+		}
+		// the host makes sure the client is frozen even if the code didn't freeze it.
+		Ok(client_state.with_frozen_height(latest_height)?)
+	}
+
+	fn check_for_misbehaviour<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		client_id: ClientId,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<bool, Ics02Error> {
+		let host = ReaderHostContext { ctx, client_id: &client_id };
+		let call = ContractCall::CheckForMisbehaviour {
+			client_state: client_state.data,
+			client_message: client_message.data,
+		};
+		Ok(H::call::<bool>(&client_state.code_id, &host, &call)?)
+	}
+
+	fn verify_upgrade_and_update_state<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		client_id: ClientId,
+		old_client_state: &Self::ClientState,
+		upgrade_client_state: &Self::ClientState,
+		upgrade_consensus_state: &Self::ConsensusState,
+		proof_upgrade_client: Vec<u8>,
+		proof_upgrade_consensus_state: Vec<u8>,
+	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
+		if upgrade_client_state.code_id != old_client_state.code_id {
+			Err(Error::Custom("Upgrading the wasm code of a client is not supported".into()))?
+		}
+		let host = ReaderHostContext { ctx, client_id: &client_id };
+		let call = ContractCall::VerifyUpgradeAndUpdateState {
+			client_state: old_client_state.data.clone(),
+			upgrade_client_state: upgrade_client_state.data.clone(),
+			upgrade_consensus_state: upgrade_consensus_state.data.clone(),
+			proof_upgrade_client,
+			proof_upgrade_consensus_state,
+		};
+		let result = H::call::<UpdateStateResult>(&old_client_state.code_id, &host, &call)?;
+		let client_state =
+			old_client_state.clone().with_contract_state(result.client_state.clone());
+		let (_, consensus_state) = Self::consensus_states::<Ctx>(result)?
+			.pop()
+			.ok_or_else(|| Error::Contract("No consensus state returned for upgrade".into()))?;
+
+		Ok((client_state, ConsensusUpdateResult::Single(consensus_state)))
+	}
+
+	fn verify_client_consensus_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		client_id: &ClientId,
+		consensus_height: Height,
+		expected_consensus_state: &Ctx::AnyConsensusState,
+	) -> Result<(), Ics02Error> {
+		let path = ClientConsensusStatePath {
+			client_id: client_id.clone(),
+			epoch: consensus_height.revision_number,
+			height: consensus_height.revision_height,
+		};
+		let value = expected_consensus_state.encode_to_vec();
+		Self::verify(client_state, height, prefix, proof, root, path, Some(value))?;
+		Ok(())
+	}
+
+	fn verify_connection_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		connection_id: &ConnectionId,
+		expected_connection_end: &ConnectionEnd,
+	) -> Result<(), Ics02Error> {
+		let path = ConnectionsPath(connection_id.clone());
+		let value = expected_connection_end.encode_vec();
+		Self::verify(client_state, height, prefix, proof, root, path, Some(value))?;
+		Ok(())
+	}
+
+	fn verify_channel_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		expected_channel_end: &ChannelEnd,
+	) -> Result<(), Ics02Error> {
+		let path = ChannelEndsPath(port_id.clone(), *channel_id);
+		let value = expected_channel_end.encode_vec();
+		Self::verify(client_state, height, prefix, proof, root, path, Some(value))?;
+		Ok(())
+	}
+
+	fn verify_client_full_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		client_id: &ClientId,
+		expected_client_state: &Ctx::AnyClientState,
+	) -> Result<(), Ics02Error> {
+		let path = ClientStatePath(client_id.clone());
+		let value = expected_client_state.encode_to_vec();
+		Self::verify(client_state, height, prefix, proof, root, path, Some(value))?;
+		Ok(())
+	}
+
+	fn verify_packet_data<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		commitment: PacketCommitment,
+	) -> Result<(), Ics02Error> {
+		verify_delay_passed::<H, _>(ctx, height, connection_end).map_err(Error::Anyhow)?;
+
+		let commitment_path =
+			CommitmentsPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		Self::verify(
+			client_state,
+			height,
+			connection_end.counterparty().prefix(),
+			proof,
+			root,
+			commitment_path,
+			Some(commitment.into_vec()),
+		)?;
+		Ok(())
+	}
+
+	fn verify_packet_acknowledgement<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		ack: AcknowledgementCommitment,
+	) -> Result<(), Ics02Error> {
+		verify_delay_passed::<H, _>(ctx, height, connection_end).map_err(Error::Anyhow)?;
+
+		let ack_path = AcksPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		Self::verify(
+			client_state,
+			height,
+			connection_end.counterparty().prefix(),
+			proof,
+			root,
+			ack_path,
+			Some(ack.into_vec()),
+		)?;
+		Ok(())
+	}
+
+	fn verify_next_sequence_recv<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Result<(), Ics02Error> {
+		verify_delay_passed::<H, _>(ctx, height, connection_end).map_err(Error::Anyhow)?;
+
+		let seq_bytes = codec::Encode::encode(&u64::from(sequence));
+		let seq_path = SeqRecvsPath(port_id.clone(), *channel_id);
+		Self::verify(
+			client_state,
+			height,
+			connection_end.counterparty().prefix(),
+			proof,
+			root,
+			seq_path,
+			Some(seq_bytes),
+		)?;
+		Ok(())
+	}
+
+	fn verify_packet_receipt_absence<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Result<(), Ics02Error> {
+		verify_delay_passed::<H, _>(ctx, height, connection_end).map_err(Error::Anyhow)?;
+
+		let receipt_path =
+			ReceiptsPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		Self::verify(
+			client_state,
+			height,
+			connection_end.counterparty().prefix(),
+			proof,
+			root,
+			receipt_path,
+			None,
+		)?;
+		Ok(())
+	}
+}
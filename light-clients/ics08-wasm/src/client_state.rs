@@ -0,0 +1,229 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	client_def::WasmClient,
+	contract::{ContractCall, ContractClientState, ContractHeight, NoHostContext, WasmExecutor},
+	error::Error,
+	proto::{ClientState as RawClientState, Height as RawHeight},
+};
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use core::{marker::PhantomData, time::Duration};
+use ibc::{
+	core::{
+		ics02_client::{client_state::ClientType, error::Error as Ics02Error},
+		ics24_host::identifier::ChainId,
+	},
+	Height,
+};
+use tendermint_proto::Protobuf;
+
+/// Protobuf type url for Wasm ClientState
+pub const WASM_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.ClientState";
+
+#[derive(PartialEq, Clone, Debug, Default, Eq)]
+pub struct ClientState<H> {
+	/// Client state of the light client implemented by the wasm code
+	pub data: Vec<u8>,
+	/// Hash of the light client wasm code
+	pub code_id: Vec<u8>,
+	/// Latest height of the light client
+	pub latest_height: Height,
+	/// Block height when the client was frozen due to a misbehaviour
+	pub frozen_height: Option<Height>,
+	/// phantom type.
+	pub _phantom: PhantomData<H>,
+}
+
+impl<H: Clone> Protobuf<RawClientState> for ClientState<H> {}
+
+impl<H> ClientState<H> {
+	pub fn client_type() -> ClientType {
+		"08-wasm".to_string()
+	}
+
+	pub fn latest_height(&self) -> Height {
+		self.latest_height
+	}
+
+	pub fn frozen_height(&self) -> Option<Height> {
+		self.frozen_height
+	}
+
+	/// Verify that the client is at a sufficient height and unfrozen at the given height
+	pub fn verify_height(&self, height: Height) -> Result<(), Error> {
+		if self.latest_height < height {
+			return Err(Error::Custom(format!(
+				"Insufficient height, known height: {}, given height: {height}",
+				self.latest_height
+			)))
+		}
+
+		match self.frozen_height {
+			Some(frozen_height) if frozen_height <= height =>
+				Err(Error::Custom(format!("Client has been frozen at height {frozen_height}"))),
+			_ => Ok(()),
+		}
+	}
+
+	/// Replace the wrapped client state with the one returned from the wasm code.
+	pub fn with_contract_state(self, state: ContractClientState) -> Self {
+		Self {
+			data: state.data,
+			latest_height: state.latest_height.into(),
+			frozen_height: state.frozen_height.map(Into::into),
+			..self
+		}
+	}
+
+	/// Check a new client state against the code it wraps, the heights cached by the host must
+	/// match the client state of the code and the client can't be frozen.
+	pub fn validate(&self) -> Result<(), Error>
+	where
+		H: WasmExecutor,
+	{
+		if self.latest_height == Height::zero() {
+			Err(Error::Custom("Client state latest height must be greater than zero".into()))?
+		}
+		if self.frozen_height.is_some() {
+			Err(Error::Custom("Client state must not be frozen".into()))?
+		}
+		let call = ContractCall::LatestHeight { client_state: self.data.clone() };
+		let latest_height: Height =
+			H::call::<ContractHeight>(&self.code_id, &NoHostContext, &call)?.into();
+		if latest_height != self.latest_height {
+			Err(Error::Custom(format!(
+				"Client state latest height {} doesn't match the height {latest_height} of the \
+				 wrapped client state",
+				self.latest_height
+			)))?
+		}
+		Ok(())
+	}
+
+	pub fn with_frozen_height(self, h: Height) -> Result<Self, Error> {
+		if h == Height::zero() {
+			return Err(Error::Custom(
+				"ClientState frozen height must be greater than zero".to_string(),
+			))
+		}
+		Ok(Self { frozen_height: Some(h), ..self })
+	}
+}
+
+impl<H> ibc::core::ics02_client::client_state::ClientState for ClientState<H>
+where
+	H: WasmExecutor,
+{
+	/// Opaque upgrade options, passed as is to the wasm code.
+	type UpgradeOptions = Vec<u8>;
+	type ClientDef = WasmClient<H>;
+
+	fn chain_id(&self) -> ChainId {
+		let call = ContractCall::ChainId { client_state: self.data.clone() };
+		H::call::<String>(&self.code_id, &NoHostContext, &call)
+			.map(|id| ChainId::from_string(&id))
+			.unwrap_or_default()
+	}
+
+	fn client_def(&self) -> Self::ClientDef {
+		WasmClient::default()
+	}
+
+	fn client_type(&self) -> ClientType {
+		Self::client_type()
+	}
+
+	fn latest_height(&self) -> Height {
+		self.latest_height()
+	}
+
+	fn frozen_height(&self) -> Option<Height> {
+		self.frozen_height()
+	}
+
+	fn upgrade(
+		self,
+		upgrade_height: Height,
+		upgrade_options: Vec<u8>,
+		chain_id: ChainId,
+	) -> Result<Self, Ics02Error> {
+		let call = ContractCall::Upgrade {
+			client_state: self.data.clone(),
+			upgrade_height: upgrade_height.into(),
+			upgrade_options,
+			chain_id: chain_id.to_string(),
+		};
+		let state = H::call::<ContractClientState>(&self.code_id, &NoHostContext, &call)?;
+		Ok(self.with_contract_state(state))
+	}
+
+	fn expired(&self, elapsed: Duration) -> bool {
+		let call = ContractCall::Expired {
+			client_state: self.data.clone(),
+			elapsed_nanos: u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX),
+		};
+		// A failed call, e.g. one that ran out of gas, doesn't mean the trusting period has
+		// passed, the client message verification in the code will reject the update instead.
+		H::call::<bool>(&self.code_id, &NoHostContext, &call).unwrap_or(false)
+	}
+
+	fn encode_to_vec(&self) -> Vec<u8> {
+		self.encode_vec()
+	}
+}
+
+impl<H> TryFrom<RawClientState> for ClientState<H> {
+	type Error = Error;
+
+	fn try_from(raw: RawClientState) -> Result<Self, Self::Error> {
+		let latest_height = raw
+			.latest_height
+			.map(|h| Height::new(h.revision_number, h.revision_height))
+			.ok_or_else(|| Error::Custom("Invalid client state: missing latest height".into()))?;
+		if raw.code_id.len() != 32 {
+			Err(Error::Custom(format!("Invalid code id length: {}", raw.code_id.len())))?
+		}
+
+		Ok(Self {
+			data: raw.data,
+			code_id: raw.code_id,
+			latest_height,
+			frozen_height: raw
+				.frozen_height
+				.map(|h| Height::new(h.revision_number, h.revision_height)),
+			_phantom: Default::default(),
+		})
+	}
+}
+
+impl<H> From<ClientState<H>> for RawClientState {
+	fn from(client_state: ClientState<H>) -> Self {
+		let into_raw = |h: Height| RawHeight {
+			revision_number: h.revision_number,
+			revision_height: h.revision_height,
+		};
+		RawClientState {
+			data: client_state.data,
+			code_id: client_state.code_id,
+			latest_height: Some(into_raw(client_state.latest_height)),
+			frozen_height: client_state.frozen_height.map(into_raw),
+		}
+	}
+}
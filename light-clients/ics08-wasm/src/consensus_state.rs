@@ -0,0 +1,82 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	contract::ContractConsensusState, error::Error, proto::ConsensusState as RawConsensusState,
+};
+use alloc::{format, vec::Vec};
+use core::convert::Infallible;
+use ibc::{core::ics23_commitment::commitment::CommitmentRoot, timestamp::Timestamp, Height};
+use tendermint_proto::Protobuf;
+
+/// Protobuf type url for Wasm Consensus State
+pub const WASM_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.ConsensusState";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsensusState {
+	/// Consensus state of the light client implemented by the wasm code
+	pub data: Vec<u8>,
+	pub timestamp: Timestamp,
+	pub root: CommitmentRoot,
+}
+
+impl ConsensusState {
+	/// Convert a consensus state returned from the wasm code.
+	pub fn from_contract(state: ContractConsensusState) -> Result<(Height, Self), Error> {
+		let timestamp = Timestamp::from_nanoseconds(state.timestamp)
+			.map_err(|e| Error::Custom(format!("Invalid consensus state timestamp: {e}")))?;
+		Ok((state.height.into(), Self { data: state.data, timestamp, root: state.root.into() }))
+	}
+}
+
+impl ibc::core::ics02_client::client_consensus::ConsensusState for ConsensusState {
+	type Error = Infallible;
+
+	fn root(&self) -> &CommitmentRoot {
+		&self.root
+	}
+
+	fn timestamp(&self) -> Timestamp {
+		self.timestamp
+	}
+
+	fn encode_to_vec(&self) -> Vec<u8> {
+		self.encode_vec()
+	}
+}
+
+impl Protobuf<RawConsensusState> for ConsensusState {}
+
+impl TryFrom<RawConsensusState> for ConsensusState {
+	type Error = Error;
+
+	fn try_from(raw: RawConsensusState) -> Result<Self, Self::Error> {
+		let timestamp = Timestamp::from_nanoseconds(raw.timestamp).map_err(|e| {
+			Error::Custom(format!("Invalid consensus state: invalid timestamp {e}"))
+		})?;
+
+		Ok(Self { data: raw.data, timestamp, root: raw.root.into() })
+	}
+}
+
+impl From<ConsensusState> for RawConsensusState {
+	fn from(value: ConsensusState) -> Self {
+		RawConsensusState {
+			data: value.data,
+			timestamp: value.timestamp.nanoseconds(),
+			root: value.root.into_vec(),
+		}
+	}
+}
@@ -0,0 +1,224 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The interface between the host and the light client wasm code.
+//!
+//! The code must import its memory as `env.memory` and export a single [`DISPATCH_EXPORT`]
+//! function taking no arguments. The SCALE encoded [`ContractCall`] is copied into the code's
+//! memory with the [`host_fn::INPUT_LEN`] and [`host_fn::INPUT`] imports, and the code writes the
+//! SCALE encoded result of the call with [`host_fn::SET_OUTPUT`] before returning [`RETURN_OK`].
+//! On [`RETURN_ERR`] the output is interpreted as a utf-8 error message. All imports live in the
+//! `env` module.
+
+use crate::error::Error;
+use alloc::{string::String, vec::Vec};
+use codec::{Decode, Encode};
+use core::fmt::Debug;
+use ibc::{core::ics24_host::identifier::ClientId, Height};
+
+/// Name of the function every light client wasm code must export.
+pub const DISPATCH_EXPORT: &str = "dispatch";
+/// Returned from [`DISPATCH_EXPORT`] when the call succeeded.
+pub const RETURN_OK: i32 = 0;
+/// Returned from [`DISPATCH_EXPORT`] when the call failed.
+pub const RETURN_ERR: i32 = 1;
+
+/// Names of the functions the host provides to the light client wasm code.
+pub mod host_fn {
+	/// `fn() -> i32`, length of the encoded [`ContractCall`](super::ContractCall).
+	pub const INPUT_LEN: &str = "ibc_input_len";
+	/// `fn(out_ptr: i32)`, copies the encoded [`ContractCall`](super::ContractCall) to `out_ptr`.
+	pub const INPUT: &str = "ibc_input";
+	/// `fn(ptr: i32, len: i32)`, sets the output of the call.
+	pub const SET_OUTPUT: &str = "ibc_set_output";
+	/// `fn(revision_number: i64, revision_height: i64) -> i32`, loads the opaque consensus state
+	/// of this client at the given height into the read buffer, returns its length or `-1`.
+	pub const CONSENSUS_STATE: &str = "ibc_consensus_state";
+	/// `fn(key_ptr: i32, key_len: i32) -> i32`, loads the value stored under `key` in this
+	/// client's private storage into the read buffer, returns its length or `-1`.
+	pub const STORAGE_GET: &str = "ibc_storage_get";
+	/// `fn(key_ptr: i32, key_len: i32, value_ptr: i32, value_len: i32)`, writes to this client's
+	/// private storage.
+	pub const STORAGE_SET: &str = "ibc_storage_set";
+	/// `fn(out_ptr: i32)`, copies the read buffer to `out_ptr`.
+	pub const READ_BUFFER: &str = "ibc_read_buffer";
+	/// `fn() -> i64`, host timestamp in nanoseconds.
+	pub const HOST_TIMESTAMP: &str = "ibc_host_timestamp";
+	/// `fn(sig_ptr: i32, msg_ptr: i32, msg_len: i32, pub_key_ptr: i32) -> i32`, returns `1` if
+	/// the 64 byte signature is valid for the message and 32 byte public key.
+	pub const ED25519_VERIFY: &str = "ibc_ed25519_verify";
+	/// `fn(data_ptr: i32, data_len: i32, out_ptr: i32)`, writes the 32 byte blake2-256 hash of
+	/// the data to `out_ptr`.
+	pub const BLAKE2_256: &str = "ibc_blake2_256";
+}
+
+/// Height as seen by the light client wasm code.
+#[derive(Encode, Decode, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ContractHeight {
+	pub revision_number: u64,
+	pub revision_height: u64,
+}
+
+impl From<Height> for ContractHeight {
+	fn from(height: Height) -> Self {
+		Self { revision_number: height.revision_number, revision_height: height.revision_height }
+	}
+}
+
+impl From<ContractHeight> for Height {
+	fn from(height: ContractHeight) -> Self {
+		Height::new(height.revision_number, height.revision_height)
+	}
+}
+
+/// Calls the host makes into the light client wasm code, every variant documents the type the
+/// code must return.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub enum ContractCall {
+	/// Returns `String`, the chain id of the counterparty.
+	ChainId { client_state: Vec<u8> },
+	/// Returns `bool`, whether the client is expired after `elapsed_nanos` since the latest
+	/// consensus state.
+	Expired { client_state: Vec<u8>, elapsed_nanos: u64 },
+	/// Returns [`ContractClientState`].
+	Upgrade {
+		client_state: Vec<u8>,
+		upgrade_height: ContractHeight,
+		upgrade_options: Vec<u8>,
+		chain_id: String,
+	},
+	/// Returns `()`.
+	VerifyClientMessage { client_state: Vec<u8>, client_message: Vec<u8> },
+	/// Returns `bool`, whether the message is a proof of misbehaviour.
+	CheckForMisbehaviour { client_state: Vec<u8>, client_message: Vec<u8> },
+	/// Returns [`UpdateStateResult`].
+	UpdateState { client_state: Vec<u8>, client_message: Vec<u8> },
+	/// Returns [`ContractClientState`], which should be frozen.
+	UpdateStateOnMisbehaviour { client_state: Vec<u8>, client_message: Vec<u8> },
+	/// Returns [`UpdateStateResult`] with a single consensus state.
+	VerifyUpgradeAndUpdateState {
+		client_state: Vec<u8>,
+		upgrade_client_state: Vec<u8>,
+		upgrade_consensus_state: Vec<u8>,
+		proof_upgrade_client: Vec<u8>,
+		proof_upgrade_consensus_state: Vec<u8>,
+	},
+	/// Returns `()`, verifies that `value` is stored under `prefix ++ path` in `root`.
+	VerifyMembership {
+		client_state: Vec<u8>,
+		height: ContractHeight,
+		prefix: Vec<u8>,
+		proof: Vec<u8>,
+		root: Vec<u8>,
+		path: String,
+		value: Vec<u8>,
+	},
+	/// Returns `()`, verifies that nothing is stored under `prefix ++ path` in `root`.
+	VerifyNonMembership {
+		client_state: Vec<u8>,
+		height: ContractHeight,
+		prefix: Vec<u8>,
+		proof: Vec<u8>,
+		root: Vec<u8>,
+		path: String,
+	},
+	/// Returns [`ContractHeight`], the latest height of the client state.
+	LatestHeight { client_state: Vec<u8> },
+}
+
+/// Client state returned by the light client wasm code, the heights are cached in the host's
+/// client state so it doesn't need to call into the code for them.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct ContractClientState {
+	pub data: Vec<u8>,
+	pub latest_height: ContractHeight,
+	pub frozen_height: Option<ContractHeight>,
+}
+
+/// Consensus state returned by the light client wasm code.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct ContractConsensusState {
+	pub height: ContractHeight,
+	pub data: Vec<u8>,
+	/// Timestamp in nanoseconds
+	pub timestamp: u64,
+	pub root: Vec<u8>,
+}
+
+/// Result of [`ContractCall::UpdateState`] & [`ContractCall::VerifyUpgradeAndUpdateState`].
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct UpdateStateResult {
+	pub client_state: ContractClientState,
+	pub consensus_states: Vec<ContractConsensusState>,
+}
+
+/// Host state the light client wasm code can read during a call.
+pub trait WasmHostContext {
+	/// The client the code is executing for, `None` for calls that aren't tied to a stored client.
+	fn client_id(&self) -> Option<&ClientId>;
+
+	/// Opaque consensus state of the client at the given height.
+	fn consensus_state(&self, height: Height) -> Option<Vec<u8>>;
+
+	/// Host timestamp in nanoseconds
+	fn host_timestamp(&self) -> u64;
+}
+
+/// Host context for calls that don't have access to the ibc context.
+pub struct NoHostContext;
+
+impl WasmHostContext for NoHostContext {
+	fn client_id(&self) -> Option<&ClientId> {
+		None
+	}
+
+	fn consensus_state(&self, _height: Height) -> Option<Vec<u8>> {
+		None
+	}
+
+	fn host_timestamp(&self) -> u64 {
+		0
+	}
+}
+
+/// Runs light client wasm code, implemented by the host.
+pub trait WasmExecutor: Clone + Debug + Default + Eq + Send + Sync + 'static {
+	/// Execute `call` with the code identified by `code_id` and return the encoded output.
+	fn execute(
+		code_id: &[u8],
+		host: &dyn WasmHostContext,
+		call: &ContractCall,
+	) -> Result<Vec<u8>, Error>;
+
+	/// Execute `call` and decode its output.
+	fn call<R: Decode>(
+		code_id: &[u8],
+		host: &dyn WasmHostContext,
+		call: &ContractCall,
+	) -> Result<R, Error> {
+		let output = Self::execute(code_id, host, call)?;
+		Ok(R::decode(&mut &output[..])?)
+	}
+}
+
+impl WasmExecutor for () {
+	fn execute(
+		_code_id: &[u8],
+		_host: &dyn WasmHostContext,
+		_call: &ContractCall,
+	) -> Result<Vec<u8>, Error> {
+		Err(Error::Executor("No wasm executor configured".into()))
+	}
+}
@@ -0,0 +1,35 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! ICS-08: Wasm IBC light client protocol implementation.
+//!
+//! The verification logic of the light client lives in a wasm blob stored on the host chain, this
+//! crate only holds the opaque client & consensus states and forwards every [`ClientDef`] call to
+//! a [`WasmExecutor`] which runs the code identified by the client state's `code_id`.
+//!
+//! [`ClientDef`]: ibc::core::ics02_client::client_def::ClientDef
+//! [`WasmExecutor`]: contract::WasmExecutor
+
+extern crate alloc;
+
+pub mod client_def;
+pub mod client_message;
+pub mod client_state;
+pub mod consensus_state;
+pub mod contract;
+pub mod error;
+pub mod proto;
@@ -0,0 +1,186 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dispatch of the calls pallet-ibc makes into the GRANDPA client.
+
+use crate::host::{self, WasmHost};
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use codec::{Decode, Encode};
+use core::{str::FromStr, time::Duration};
+use ibc::core::{
+	ics02_client::client_consensus::ConsensusState as _,
+	ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+	ics24_host::Path,
+};
+use ics08_wasm::contract::{
+	ContractCall, ContractClientState, ContractConsensusState, ContractHeight, UpdateStateResult,
+	RETURN_ERR, RETURN_OK,
+};
+use ics10_grandpa::{
+	client_def::GrandpaClient, client_message::ClientMessage, client_state::ClientState,
+	consensus_state::ConsensusState,
+};
+use light_client_common::{verify_membership, verify_non_membership, HostFunctions};
+use tendermint_proto::Protobuf;
+
+type Client = GrandpaClient<WasmHost>;
+type Hasher = <WasmHost as HostFunctions>::BlakeTwo256;
+
+#[no_mangle]
+pub extern "C" fn dispatch() -> i32 {
+	let input = host::input();
+	let result = ContractCall::decode(&mut &input[..])
+		.map_err(|e| format!("Failed to decode call: {e:?}"))
+		.and_then(execute);
+	match result {
+		Ok(output) => {
+			host::set_output(&output);
+			RETURN_OK
+		},
+		Err(e) => {
+			host::set_output(e.as_bytes());
+			RETURN_ERR
+		},
+	}
+}
+
+fn decode_client_state(data: &[u8]) -> Result<ClientState<WasmHost>, String> {
+	ClientState::decode_vec(data).map_err(|e| format!("Failed to decode client state: {e}"))
+}
+
+fn decode_client_message(data: &[u8]) -> Result<ClientMessage, String> {
+	ClientMessage::decode_vec(data).map_err(|e| format!("Failed to decode client message: {e}"))
+}
+
+fn contract_client_state(client_state: &ClientState<WasmHost>) -> ContractClientState {
+	ContractClientState {
+		data: client_state.encode_vec(),
+		latest_height: client_state.latest_height().into(),
+		frozen_height: client_state.frozen_height.map(Into::into),
+	}
+}
+
+fn execute(call: ContractCall) -> Result<Vec<u8>, String> {
+	let output = match call {
+		ContractCall::ChainId { client_state } =>
+			decode_client_state(&client_state)?.chain_id().to_string().encode(),
+		ContractCall::LatestHeight { client_state } =>
+			ContractHeight::from(decode_client_state(&client_state)?.latest_height()).encode(),
+		ContractCall::Expired { client_state, elapsed_nanos } =>
+			decode_client_state(&client_state)?
+				.expired(Duration::from_nanos(elapsed_nanos))
+				.encode(),
+		ContractCall::Upgrade { .. } | ContractCall::VerifyUpgradeAndUpdateState { .. } =>
+			Err("Client upgrades are not supported".to_string())?,
+		ContractCall::VerifyClientMessage { client_state, client_message } => {
+			Client::verify_message(
+				decode_client_state(&client_state)?,
+				decode_client_message(&client_message)?,
+			)
+			.map_err(|e| e.to_string())?;
+			().encode()
+		},
+		ContractCall::CheckForMisbehaviour { client_state, client_message } => {
+			let client_state = decode_client_state(&client_state)?;
+			Client::detect_misbehaviour(
+				&client_state,
+				decode_client_message(&client_message)?,
+				|height| {
+					host::consensus_state(height)
+						.map(|data| ConsensusState::decode_vec(&data))
+						.transpose()
+						.map_err(|e| ics10_grandpa::error::Error::Custom(e.to_string()))
+				},
+			)
+			.map_err(|e| e.to_string())?
+			.encode()
+		},
+		ContractCall::UpdateState { client_state, client_message } => {
+			let (client_state, consensus_states) = Client::apply_header(
+				decode_client_state(&client_state)?,
+				decode_client_message(&client_message)?,
+			)
+			.map_err(|e| e.to_string())?;
+			let consensus_states = consensus_states
+				.into_iter()
+				.map(|(height, consensus_state)| ContractConsensusState {
+					height: height.into(),
+					timestamp: consensus_state.timestamp().nanoseconds(),
+					root: consensus_state.root.as_bytes().to_vec(),
+					data: consensus_state.encode_vec(),
+				})
+				.collect();
+			UpdateStateResult {
+				client_state: contract_client_state(&client_state),
+				consensus_states,
+			}
+			.encode()
+		},
+		ContractCall::UpdateStateOnMisbehaviour { client_state, .. } => {
+			let client_state = decode_client_state(&client_state)?;
+			let latest_height = client_state.latest_height();
+			let client_state =
+				client_state.with_frozen_height(latest_height).map_err(|e| e.to_string())?;
+			contract_client_state(&client_state).encode()
+		},
+		ContractCall::VerifyMembership {
+			client_state,
+			height,
+			prefix,
+			proof,
+			root,
+			path,
+			value,
+		} => {
+			let (prefix, proof, root, path) =
+				verify_args(&client_state, height, prefix, proof, root, &path)?;
+			verify_membership::<Hasher, _>(&prefix, &proof, &root, path, value)
+				.map_err(|e| e.to_string())?;
+			().encode()
+		},
+		ContractCall::VerifyNonMembership { client_state, height, prefix, proof, root, path } => {
+			let (prefix, proof, root, path) =
+				verify_args(&client_state, height, prefix, proof, root, &path)?;
+			verify_non_membership::<Hasher, _>(&prefix, &proof, &root, path)
+				.map_err(|e| e.to_string())?;
+			().encode()
+		},
+	};
+
+	Ok(output)
+}
+
+/// Check the proof height against the client state and parse the arguments of a membership
+/// verification.
+fn verify_args(
+	client_state: &[u8],
+	height: ContractHeight,
+	prefix: Vec<u8>,
+	proof: Vec<u8>,
+	root: Vec<u8>,
+	path: &str,
+) -> Result<(CommitmentPrefix, CommitmentProofBytes, CommitmentRoot, Path), String> {
+	decode_client_state(client_state)?
+		.verify_height(height.into())
+		.map_err(|e| e.to_string())?;
+	let prefix = CommitmentPrefix::try_from(prefix).map_err(|e| e.to_string())?;
+	let proof = CommitmentProofBytes::try_from(proof).map_err(|e| e.to_string())?;
+	let path = Path::from_str(path).map_err(|e| e.to_string())?;
+	Ok((prefix, proof, root.into(), path))
+}
@@ -0,0 +1,149 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bindings to the functions pallet-ibc provides to light client code, see
+//! [`ics08_wasm::contract::host_fn`].
+
+use alloc::{vec, vec::Vec};
+use codec::{Decode, Encode};
+use ibc::Height;
+use ics10_grandpa::client_message::RelayChainHeader;
+use primitive_types::H256;
+use sp_runtime::traits::BlakeTwo256;
+
+mod ffi {
+	extern "C" {
+		pub fn ibc_input_len() -> i32;
+		pub fn ibc_input(out_ptr: *mut u8);
+		pub fn ibc_set_output(ptr: *const u8, len: i32);
+		pub fn ibc_consensus_state(revision_number: i64, revision_height: i64) -> i32;
+		pub fn ibc_storage_get(key_ptr: *const u8, key_len: i32) -> i32;
+		pub fn ibc_storage_set(
+			key_ptr: *const u8,
+			key_len: i32,
+			value_ptr: *const u8,
+			value_len: i32,
+		);
+		pub fn ibc_read_buffer(out_ptr: *mut u8);
+		pub fn ibc_ed25519_verify(
+			sig_ptr: *const u8,
+			msg_ptr: *const u8,
+			msg_len: i32,
+			pub_key_ptr: *const u8,
+		) -> i32;
+		pub fn ibc_blake2_256(data_ptr: *const u8, data_len: i32, out_ptr: *mut u8);
+	}
+}
+
+/// Maximum number of relay chain header hashes to keep in the client storage (oldest pruned
+/// first), same as pallet-ibc keeps for native GRANDPA clients.
+const HEADER_HASHES_CACHE_SIZE: usize = 500;
+/// Client storage key of the relay chain header hashes.
+const HEADER_HASHES_KEY: &[u8] = b"HeaderHashes";
+
+/// Read a value the host loaded into the read buffer.
+fn read_buffer(len: i32) -> Option<Vec<u8>> {
+	if len < 0 {
+		return None
+	}
+	let mut value = vec![0u8; len as usize];
+	unsafe { ffi::ibc_read_buffer(value.as_mut_ptr()) };
+	Some(value)
+}
+
+/// The encoded call the code is executing.
+pub fn input() -> Vec<u8> {
+	let mut input = vec![0u8; unsafe { ffi::ibc_input_len() } as usize];
+	unsafe { ffi::ibc_input(input.as_mut_ptr()) };
+	input
+}
+
+/// Set the output of the call.
+pub fn set_output(output: &[u8]) {
+	unsafe { ffi::ibc_set_output(output.as_ptr(), output.len() as i32) }
+}
+
+/// Opaque consensus state of the executing client at the given height.
+pub fn consensus_state(height: Height) -> Option<Vec<u8>> {
+	let len = unsafe {
+		ffi::ibc_consensus_state(height.revision_number as i64, height.revision_height as i64)
+	};
+	read_buffer(len)
+}
+
+fn storage_get(key: &[u8]) -> Option<Vec<u8>> {
+	read_buffer(unsafe { ffi::ibc_storage_get(key.as_ptr(), key.len() as i32) })
+}
+
+fn storage_set(key: &[u8], value: &[u8]) {
+	unsafe {
+		ffi::ibc_storage_set(key.as_ptr(), key.len() as i32, value.as_ptr(), value.len() as i32)
+	}
+}
+
+/// Blake2-256 hash computed by the host.
+pub fn blake2_256_host(data: &[u8]) -> [u8; 32] {
+	let mut out = [0u8; 32];
+	unsafe { ffi::ibc_blake2_256(data.as_ptr(), data.len() as i32, out.as_mut_ptr()) };
+	out
+}
+
+/// Host functions for the GRANDPA client, backed by the imports of the wasm code.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WasmHost;
+
+impl light_client_common::HostFunctions for WasmHost {
+	type BlakeTwo256 = BlakeTwo256;
+}
+
+impl grandpa_client_primitives::HostFunctions for WasmHost {
+	type Header = RelayChainHeader;
+
+	fn ed25519_verify(
+		sig: &sp_core::ed25519::Signature,
+		msg: &[u8],
+		pub_key: &sp_core::ed25519::Public,
+	) -> bool {
+		unsafe {
+			ffi::ibc_ed25519_verify(
+				sig.0.as_ptr(),
+				msg.as_ptr(),
+				msg.len() as i32,
+				pub_key.0.as_ptr(),
+			) == 1
+		}
+	}
+
+	fn insert_relay_header_hashes(new_hashes: &[H256]) {
+		if new_hashes.is_empty() {
+			return
+		}
+		let mut hashes = storage_get(HEADER_HASHES_KEY)
+			.and_then(|value| Vec::<H256>::decode(&mut &value[..]).ok())
+			.unwrap_or_default();
+		hashes.extend_from_slice(new_hashes);
+		if hashes.len() > HEADER_HASHES_CACHE_SIZE {
+			hashes.drain(..hashes.len() - HEADER_HASHES_CACHE_SIZE);
+		}
+		storage_set(HEADER_HASHES_KEY, &hashes.encode());
+	}
+
+	fn contains_relay_header_hash(hash: H256) -> bool {
+		storage_get(HEADER_HASHES_KEY)
+			.and_then(|value| Vec::<H256>::decode(&mut &value[..]).ok())
+			.map(|hashes| hashes.contains(&hash))
+			.unwrap_or_default()
+	}
+}
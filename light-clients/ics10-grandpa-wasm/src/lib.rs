@@ -0,0 +1,37 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(feature = "std"), feature(alloc_error_handler))]
+
+//! The ICS-10 GRANDPA light client built as ICS-08 wasm code.
+//!
+//! With the `std` feature this crate only exposes the wasm blob built by `substrate-wasm-builder`,
+//! the blob implements the interface described in [`ics08_wasm::contract`] on top of the
+//! `ics10-grandpa` client.
+
+// Make the WASM binary available.
+#[cfg(feature = "std")]
+include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+mod contract;
+#[cfg(not(feature = "std"))]
+mod host;
+#[cfg(not(feature = "std"))]
+mod runtime;
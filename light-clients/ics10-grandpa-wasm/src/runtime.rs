@@ -0,0 +1,107 @@
+// Copyright (C) 2022 ComposableFi.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Allocator, panic handler and the sp-io host functions used by the client. The host functions
+//! are linked in place of the `ext_*` imports the runtime interface would otherwise require, they
+//! follow the runtime interface ABI: slices are passed as a `u64` holding the pointer in the low
+//! and the length in the high 32 bits, fixed size arrays are returned as a pointer to a leaked
+//! allocation.
+
+use crate::host;
+use alloc::boxed::Box;
+use core::{
+	alloc::{GlobalAlloc, Layout},
+	arch::wasm32,
+	panic::PanicInfo,
+};
+
+const PAGE_SIZE: usize = 64 * 1024;
+
+/// Every call instantiates the code in fresh memory, so nothing is ever freed.
+struct BumpAllocator;
+
+static mut NEXT: usize = 0;
+
+extern "C" {
+	/// Start of the heap, exported by the linker.
+	static __heap_base: u8;
+}
+
+unsafe impl GlobalAlloc for BumpAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		if NEXT == 0 {
+			NEXT = &__heap_base as *const u8 as usize;
+		}
+		let start = (NEXT + layout.align() - 1) & !(layout.align() - 1);
+		let end = start + layout.size();
+		let available = wasm32::memory_size(0) * PAGE_SIZE;
+		if end > available {
+			let pages = (end - available + PAGE_SIZE - 1) / PAGE_SIZE;
+			if wasm32::memory_grow(0, pages) == usize::MAX {
+				return core::ptr::null_mut()
+			}
+		}
+		NEXT = end;
+		start as *mut u8
+	}
+
+	unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
+}
+
+#[global_allocator]
+static ALLOCATOR: BumpAllocator = BumpAllocator;
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+	wasm32::unreachable()
+}
+
+#[alloc_error_handler]
+fn oom(_layout: Layout) -> ! {
+	wasm32::unreachable()
+}
+
+unsafe fn slice<'a>(data: u64) -> &'a [u8] {
+	core::slice::from_raw_parts(data as u32 as *const u8, (data >> 32) as usize)
+}
+
+fn leak<const N: usize>(hash: [u8; N]) -> u32 {
+	Box::into_raw(Box::new(hash)) as u32
+}
+
+#[no_mangle]
+unsafe extern "C" fn ext_hashing_blake2_128_version_1(data: u64) -> u32 {
+	leak(sp_core_hashing::blake2_128(slice(data)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn ext_hashing_blake2_256_version_1(data: u64) -> u32 {
+	leak(host::blake2_256_host(slice(data)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn ext_hashing_twox_64_version_1(data: u64) -> u32 {
+	leak(sp_core_hashing::twox_64(slice(data)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn ext_hashing_twox_128_version_1(data: u64) -> u32 {
+	leak(sp_core_hashing::twox_128(slice(data)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn ext_hashing_twox_256_version_1(data: u64) -> u32 {
+	leak(sp_core_hashing::twox_256(slice(data)))
+}
@@ -19,13 +19,16 @@ use ibc::core::ics02_client::{
 };
 
 use crate::client_message::{ClientMessage, RelayChainHeader};
-use alloc::{format, string::ToString, vec, vec::Vec};
+use alloc::{collections::BTreeSet, format, string::ToString, vec, vec::Vec};
 use codec::Decode;
 use core::marker::PhantomData;
 use finality_grandpa::Chain;
 use grandpa_client_primitives::{
-	justification::{find_scheduled_change, AncestryChain, GrandpaJustification},
-	ParachainHeadersWithFinalityProof,
+	justification::{
+		check_equivocation_proof, find_enacted_forced_change, find_scheduled_change, AncestryChain,
+		GrandpaJustification,
+	},
+	ParachainHeaderProofs, ParachainHeadersWithFinalityProof,
 };
 use ibc::{
 	core::{
@@ -65,32 +68,36 @@ const CONSENSUS_STATE_UPGRADE_PATH: &[u8] = b"consensus-state-upgrade-path";
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
 pub struct GrandpaClient<T>(PhantomData<T>);
 
-impl<H> ClientDef for GrandpaClient<H>
+impl<H> GrandpaClient<H>
 where
 	H: grandpa_client_primitives::HostFunctions<Header = RelayChainHeader>,
 {
-	type ClientMessage = ClientMessage;
-	type ClientState = ClientState<H>;
-	type ConsensusState = ConsensusState;
-
-	fn verify_client_message<Ctx: ReaderContext>(
-		&self,
-		_ctx: &Ctx,
-		_client_id: ClientId,
-		client_state: Self::ClientState,
-		client_message: Self::ClientMessage,
-	) -> Result<(), Ics02Error> {
+	/// Verify a header or misbehaviour against the client state. Unlike
+	/// [`ClientDef::verify_client_message`] this doesn't need the ibc context, so it can also be
+	/// used when the client is compiled to wasm.
+	pub fn verify_message(
+		client_state: ClientState<H>,
+		client_message: ClientMessage,
+	) -> Result<(), Error> {
 		match client_message {
 			ClientMessage::Header(header) => {
 				let headers_with_finality_proof = ParachainHeadersWithFinalityProof {
 					finality_proof: header.finality_proof,
 					parachain_headers: header.parachain_headers,
+					authority_set_changes: header.authority_set_changes,
 				};
 
-				grandpa_client::verify_parachain_headers_with_grandpa_finality_proof::<
-					RelayChainHeader,
-					H,
-				>(client_state.into(), headers_with_finality_proof)
+				if client_state.standalone {
+					grandpa_client::verify_standalone_headers_with_grandpa_finality_proof::<
+						RelayChainHeader,
+						H,
+					>(client_state.into(), headers_with_finality_proof)
+				} else {
+					grandpa_client::verify_parachain_headers_with_grandpa_finality_proof::<
+						RelayChainHeader,
+						H,
+					>(client_state.into(), headers_with_finality_proof)
+				}
 				.map_err(Error::GrandpaPrimitives)?;
 			},
 			ClientMessage::Misbehaviour(misbehavior) => {
@@ -190,30 +197,99 @@ where
 
 				// whoops equivocation is valid.
 			},
+			ClientMessage::Equivocation(proof) => {
+				// we only know the authorities of the current set.
+				if proof.set_id != client_state.current_set_id {
+					Err(Error::Custom(format!(
+						"Equivocation is for authority set {}, expected {}",
+						proof.set_id, client_state.current_set_id
+					)))?
+				}
+
+				let mut offenders = BTreeSet::new();
+				for equivocation in proof.equivocations {
+					let offender = equivocation.offender().clone();
+					if !client_state.current_authorities.iter().any(|(id, _)| *id == offender) {
+						Err(Error::Custom(format!(
+							"Equivocating {offender:?} is not an authority"
+						)))?
+					}
+					check_equivocation_proof::<H, _, _>(proof.set_id, equivocation)
+						.map_err(|e| Error::Custom(format!("Invalid equivocation proof: {e}")))?;
+					offenders.insert(offender);
+				}
+
+				// GRANDPA is only safe while less than a third of the weight is faulty, fewer
+				// equivocations are slashed on the relay chain but can't fork finality.
+				let total_weight = client_state
+					.current_authorities
+					.iter()
+					.map(|(_, weight)| *weight as u128)
+					.sum::<u128>();
+				let equivocating_weight = client_state
+					.current_authorities
+					.iter()
+					.filter(|(id, _)| offenders.contains(id))
+					.map(|(_, weight)| *weight as u128)
+					.sum::<u128>();
+				if equivocating_weight * 3 < total_weight {
+					Err(Error::Custom(format!(
+						"Equivocating authorities hold {equivocating_weight} of {total_weight} weight, less than a third"
+					)))?
+				}
+			},
 		}
 
 		Ok(())
 	}
 
-	fn update_state<Ctx: ReaderContext>(
-		&self,
-		_ctx: &Ctx,
-		_client_id: ClientId,
-		mut client_state: Self::ClientState,
-		client_message: Self::ClientMessage,
-	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
+	/// Apply a verified header to the client state, returns the updated client state and the
+	/// consensus states for the finalized parachain headers.
+	pub fn apply_header(
+		mut client_state: ClientState<H>,
+		client_message: ClientMessage,
+	) -> Result<(ClientState<H>, Vec<(Height, ConsensusState)>), Error> {
 		let header = match client_message {
 			ClientMessage::Header(header) => header,
 			_ => unreachable!(
 				"02-client will check for misbehaviour before calling update_state; qed"
 			),
 		};
-		let ancestry =
-			AncestryChain::<RelayChainHeader>::new(&header.finality_proof.unknown_headers);
+		let ancestry = header.ancestry();
 		let mut consensus_states = vec![];
 
 		let from = client_state.latest_relay_hash;
 
+		// hand over the authority sets before the finalized block.
+		for authority_set_change in &header.authority_set_changes {
+			let target = ancestry.header(&authority_set_change.block).ok_or_else(|| {
+				Error::Custom(format!("[update_state] Authority set change target not found!"))
+			})?;
+			let forced_change = find_enacted_forced_change(
+				&ancestry,
+				client_state.latest_relay_hash,
+				authority_set_change.block,
+			)
+			.map_err(|e| Error::Custom(format!("[update_state] {e}")))?;
+			let scheduled_change = find_scheduled_change(target);
+			if forced_change.is_none() && scheduled_change.is_none() {
+				Err(Error::Custom(format!(
+					"[update_state] Authority set change wasn't scheduled or forced!"
+				)))?
+			}
+			if target.number <= client_state.latest_relay_height {
+				Err(Ics02Error::implementation_specific(format!(
+					"Light client can only be updated to new relay chain height."
+				)))?
+			}
+			client_state.latest_relay_hash = authority_set_change.block;
+			client_state.latest_relay_height = target.number;
+			for change in forced_change.into_iter().chain(scheduled_change) {
+				client_state.current_set_id += 1;
+				client_state.current_authorities = change.next_authorities;
+			}
+		}
+
 		let finalized = ancestry
 			.ancestry(from, header.finality_proof.block)
 			.map_err(|_| Error::Custom(format!("[update_state] Invalid ancestry!")))?;
@@ -230,14 +306,9 @@ where
 			let header = ancestry.header(&relay_hash).ok_or_else(|| {
 				Error::Custom(format!("No relay chain header found for hash: {relay_hash:?}"))
 			})?;
-			let (height, consensus_state) = ConsensusState::from_header::<H>(
-				parachain_header_proof,
-				client_state.para_id,
-				header.state_root.clone(),
-			)?;
-			let wrapped = Ctx::AnyConsensusState::wrap(&consensus_state)
-				.expect("AnyConsenusState is type checked; qed");
-			consensus_states.push((height, wrapped));
+			let (height, consensus_state) =
+				Self::consensus_state_from_header(&client_state, header, parachain_header_proof)?;
+			consensus_states.push((height, consensus_state));
 		}
 
 		// updates
@@ -272,37 +343,34 @@ where
 			client_state.latest_para_height = *max_height
 		}
 
+		let forced_change = find_enacted_forced_change(
+			&ancestry,
+			client_state.latest_relay_hash,
+			header.finality_proof.block,
+		)
+		.map_err(|e| Error::Custom(format!("[update_state] {e}")))?;
 		client_state.latest_relay_hash = header.finality_proof.block;
 		client_state.latest_relay_height = target.number;
 
-		if let Some(scheduled_change) = find_scheduled_change(target) {
+		for change in forced_change.into_iter().chain(find_scheduled_change(target)) {
 			client_state.current_set_id += 1;
-			client_state.current_authorities = scheduled_change.next_authorities;
+			client_state.current_authorities = change.next_authorities;
 		}
 
 		H::insert_relay_header_hashes(&finalized);
 
-		Ok((client_state, ConsensusUpdateResult::Batch(consensus_states)))
+		Ok((client_state, consensus_states))
 	}
 
-	fn update_state_on_misbehaviour(
-		&self,
-		mut client_state: Self::ClientState,
-		_client_message: Self::ClientMessage,
-	) -> Result<Self::ClientState, Ics02Error> {
-		client_state.frozen_height =
-			Some(Height::new(client_state.para_id as u64, client_state.latest_para_height as u64));
-		Ok(client_state)
-	}
-
-	fn check_for_misbehaviour<Ctx: ReaderContext>(
-		&self,
-		ctx: &Ctx,
-		client_id: ClientId,
-		client_state: Self::ClientState,
-		client_message: Self::ClientMessage,
-	) -> Result<bool, Ics02Error> {
-		if matches!(client_message, ClientMessage::Misbehaviour(_)) {
+	/// Check a client message for misbehaviour, `stored_consensus_state` returns the consensus
+	/// state the client already has at a height.
+	pub fn detect_misbehaviour(
+		client_state: &ClientState<H>,
+		client_message: ClientMessage,
+		stored_consensus_state: impl Fn(Height) -> Result<Option<ConsensusState>, Error>,
+	) -> Result<bool, Error> {
+		if matches!(client_message, ClientMessage::Misbehaviour(_) | ClientMessage::Equivocation(_))
+		{
 			return Ok(true)
 		}
 
@@ -312,31 +380,22 @@ where
 			ClientMessage::Header(header) => header,
 			_ => unreachable!("We've checked for misbehavior in line 180; qed"),
 		};
-		let ancestry =
-			AncestryChain::<RelayChainHeader>::new(&header.finality_proof.unknown_headers);
+		let ancestry = header.ancestry();
 
 		for (relay_hash, parachain_header_proof) in header.parachain_headers {
 			let header = ancestry.header(&relay_hash).ok_or_else(|| {
 				Error::Custom(format!("No relay chain header found for hash: {relay_hash:?}"))
 			})?;
 
-			let (height, consensus_state) = ConsensusState::from_header::<H>(
-				parachain_header_proof,
-				client_state.para_id,
-				header.state_root.clone(),
-			)?;
-
-			match ctx.maybe_consensus_state(&client_id, height)? {
-				Some(cs) => {
-					let cs: ConsensusState = cs
-						.downcast()
-						.ok_or(Ics02Error::client_args_type_mismatch(client_state.client_type()))?;
+			let (height, consensus_state) =
+				Self::consensus_state_from_header(client_state, header, parachain_header_proof)?;
 
+			match stored_consensus_state(height)? {
+				Some(cs) =>
 					if cs != consensus_state {
 						// Houston we have a problem
 						return Ok(true)
-					}
-				},
+					},
 				None => {},
 			};
 		}
@@ -344,6 +403,97 @@ where
 		Ok(false)
 	}
 
+	/// Consensus state of a header proven by a finality proof. For standalone chains this is the
+	/// finalized header itself, otherwise the parachain header in the state of the relay chain
+	/// header.
+	fn consensus_state_from_header(
+		client_state: &ClientState<H>,
+		header: &RelayChainHeader,
+		header_proof: ParachainHeaderProofs,
+	) -> Result<(Height, ConsensusState), Error> {
+		if client_state.standalone {
+			ConsensusState::from_standalone_header(
+				header,
+				header_proof,
+				client_state.revision_number(),
+			)
+		} else {
+			ConsensusState::from_header::<H>(
+				header_proof,
+				client_state.para_id,
+				header.state_root.clone(),
+			)
+		}
+	}
+}
+
+impl<H> ClientDef for GrandpaClient<H>
+where
+	H: grandpa_client_primitives::HostFunctions<Header = RelayChainHeader>,
+{
+	type ClientMessage = ClientMessage;
+	type ClientState = ClientState<H>;
+	type ConsensusState = ConsensusState;
+
+	fn verify_client_message<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<(), Ics02Error> {
+		Self::verify_message(client_state, client_message)?;
+		Ok(())
+	}
+
+	fn update_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
+		let (client_state, consensus_states) = Self::apply_header(client_state, client_message)?;
+		let consensus_states = consensus_states
+			.into_iter()
+			.map(|(height, consensus_state)| {
+				let wrapped = Ctx::AnyConsensusState::wrap(&consensus_state)
+					.expect("AnyConsenusState is type checked; qed");
+				(height, wrapped)
+			})
+			.collect();
+
+		Ok((client_state, ConsensusUpdateResult::Batch(consensus_states)))
+	}
+
+	fn update_state_on_misbehaviour(
+		&self,
+		mut client_state: Self::ClientState,
+		_client_message: Self::ClientMessage,
+	) -> Result<Self::ClientState, Ics02Error> {
+		client_state.frozen_height = Some(client_state.latest_height());
+		Ok(client_state)
+	}
+
+	fn check_for_misbehaviour<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		client_id: ClientId,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<bool, Ics02Error> {
+		let misbehaviour = Self::detect_misbehaviour(&client_state, client_message, |height| {
+			ctx.maybe_consensus_state(&client_id, height)?
+				.map(|cs| {
+					cs.downcast::<ConsensusState>().ok_or_else(|| {
+						Ics02Error::client_args_type_mismatch(client_state.client_type()).into()
+					})
+				})
+				.transpose()
+		})?;
+		Ok(misbehaviour)
+	}
+
 	fn verify_upgrade_and_update_state<Ctx: ReaderContext>(
 		&self,
 		ctx: &Ctx,
@@ -354,10 +504,7 @@ where
 		proof_upgrade_client: Vec<u8>,
 		proof_upgrade_consensus_state: Vec<u8>,
 	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
-		let height = Height::new(
-			old_client_state.para_id as u64,
-			old_client_state.latest_para_height as u64,
-		);
+		let height = old_client_state.latest_height();
 
 		let consenus_state = ctx.consensus_state(&client_id, height)?
 			.downcast::<Self::ConsensusState>()
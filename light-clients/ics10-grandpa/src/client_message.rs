@@ -16,15 +16,18 @@
 use crate::{
 	error::Error,
 	proto::{
-		self, client_message, ClientMessage as RawClientMessage, Header as RawHeader,
-		Misbehaviour as RawMisbehaviour,
+		self, client_message, ClientMessage as RawClientMessage, Equivocation as RawEquivocation,
+		Header as RawHeader, Misbehaviour as RawMisbehaviour,
 	},
 };
 use alloc::{collections::BTreeMap, vec::Vec};
 use anyhow::anyhow;
 use codec::{Decode, Encode};
-use grandpa_client_primitives::{FinalityProof, ParachainHeaderProofs};
+use grandpa_client_primitives::{
+	justification::AncestryChain, FinalityProof, ParachainHeaderProofs,
+};
 use primitive_types::H256;
+use sp_finality_grandpa::Equivocation;
 use sp_runtime::traits::BlakeTwo256;
 use tendermint_proto::Protobuf;
 
@@ -44,6 +47,22 @@ pub struct Header {
 	/// finalzed at the relay chain height. We check for this parachain header finalization
 	/// via state proofs. Also contains extrinsic proof for timestamp.
 	pub parachain_headers: BTreeMap<H256, ParachainHeaderProofs>,
+	/// Finality proofs for the relay chain blocks enacting the authority set changes before the
+	/// block finalized by `finality_proof`, ordered by block number.
+	pub authority_set_changes: Vec<FinalityProof<RelayChainHeader>>,
+}
+
+impl Header {
+	/// The relay chain headers of the authority set change proofs and the finality proof.
+	pub fn ancestry(&self) -> AncestryChain<RelayChainHeader> {
+		let headers = self
+			.authority_set_changes
+			.iter()
+			.chain(core::iter::once(&self.finality_proof))
+			.flat_map(|proof| proof.unknown_headers.iter().cloned())
+			.collect::<Vec<_>>();
+		AncestryChain::new(&headers)
+	}
 }
 
 /// Misbehaviour type for GRANDPA. If both first and second proofs are valid
@@ -57,6 +76,16 @@ pub struct Misbehaviour {
 	pub second_finality_proof: FinalityProof<RelayChainHeader>,
 }
 
+/// GRANDPA authorities voting for two different blocks in the same round, the client is only
+/// frozen if they hold at least a third of the weight of the authority set.
+#[derive(Clone, Debug)]
+pub struct EquivocationProof {
+	/// Authority set the authorities voted in
+	pub set_id: u64,
+	/// The conflicting votes of each authority
+	pub equivocations: Vec<Equivocation<H256, u32>>,
+}
+
 /// [`ClientMessage`] for Ics10-GRANDPA
 #[derive(Clone, Debug)]
 pub enum ClientMessage {
@@ -64,6 +93,8 @@ pub enum ClientMessage {
 	Header(Header),
 	/// This is for submitting misbehaviors.
 	Misbehaviour(Misbehaviour),
+	/// This is for reporting equivocating authorities of the current set.
+	Equivocation(EquivocationProof),
 }
 
 impl ibc::core::ics02_client::client_message::ClientMessage for ClientMessage {
@@ -83,14 +114,16 @@ impl TryFrom<RawClientMessage> for ClientMessage {
 			.ok_or_else(|| anyhow!("Must supply either Header or Misbehaviour type!"))?
 		{
 			client_message::Message::Header(raw_header) => {
-				let finality_proof = raw_header
-					.finality_proof
-					.ok_or_else(|| anyhow!("Grandpa finality proof is required!"))?;
-				let block = if finality_proof.block.len() == 32 {
-					H256::from_slice(&*finality_proof.block)
-				} else {
-					Err(anyhow!("Invalid hash type with length: {}", finality_proof.block.len()))?
-				};
+				let finality_proof = finality_proof_from_raw(
+					raw_header
+						.finality_proof
+						.ok_or_else(|| anyhow!("Grandpa finality proof is required!"))?,
+				)?;
+				let authority_set_changes = raw_header
+					.authority_set_changes
+					.into_iter()
+					.map(finality_proof_from_raw)
+					.collect::<Result<_, Error>>()?;
 
 				let parachain_headers = raw_header
 					.parachain_headers
@@ -117,22 +150,10 @@ impl TryFrom<RawClientMessage> for ClientMessage {
 					})
 					.collect::<Result<_, Error>>()?;
 
-				let unknown_headers = finality_proof
-					.unknown_headers
-					.into_iter()
-					.map(|h| {
-						let header = codec::Decode::decode(&mut &h[..])?;
-						Ok(header)
-					})
-					.collect::<Result<_, Error>>()?;
-
 				ClientMessage::Header(Header {
-					finality_proof: FinalityProof {
-						block,
-						justification: finality_proof.justification,
-						unknown_headers,
-					},
+					finality_proof,
 					parachain_headers,
+					authority_set_changes,
 				})
 			},
 			client_message::Message::Misbehaviour(raw_misbehaviour) =>
@@ -144,6 +165,11 @@ impl TryFrom<RawClientMessage> for ClientMessage {
 						&mut &*raw_misbehaviour.second_finality_proof,
 					)?,
 				}),
+			client_message::Message::Equivocation(raw_equivocation) =>
+				ClientMessage::Equivocation(EquivocationProof {
+					set_id: raw_equivocation.set_id,
+					equivocations: Decode::decode(&mut &*raw_equivocation.equivocations)?,
+				}),
 		};
 
 		Ok(message)
@@ -166,21 +192,16 @@ impl From<ClientMessage> for RawClientMessage {
 						}),
 					})
 					.collect();
-				let finality_proof = proto::FinalityProof {
-					block: header.finality_proof.block.as_bytes().to_vec(),
-					justification: header.finality_proof.justification,
-					unknown_headers: header
-						.finality_proof
-						.unknown_headers
-						.into_iter()
-						.map(|h| h.encode())
-						.collect(),
-				};
 
 				RawClientMessage {
 					message: Some(client_message::Message::Header(RawHeader {
-						finality_proof: Some(finality_proof),
+						finality_proof: Some(header.finality_proof.into()),
 						parachain_headers,
+						authority_set_changes: header
+							.authority_set_changes
+							.into_iter()
+							.map(Into::into)
+							.collect(),
 					})),
 				}
 			},
@@ -190,6 +211,47 @@ impl From<ClientMessage> for RawClientMessage {
 					second_finality_proof: misbehaviior.second_finality_proof.encode(),
 				})),
 			},
+			ClientMessage::Equivocation(proof) => RawClientMessage {
+				message: Some(client_message::Message::Equivocation(RawEquivocation {
+					set_id: proof.set_id,
+					equivocations: proof.equivocations.encode(),
+				})),
+			},
 		}
 	}
 }
+
+impl From<FinalityProof<RelayChainHeader>> for proto::FinalityProof {
+	fn from(finality_proof: FinalityProof<RelayChainHeader>) -> Self {
+		proto::FinalityProof {
+			block: finality_proof.block.as_bytes().to_vec(),
+			justification: finality_proof.justification,
+			unknown_headers: finality_proof
+				.unknown_headers
+				.into_iter()
+				.map(|h| h.encode())
+				.collect(),
+		}
+	}
+}
+
+fn finality_proof_from_raw(
+	finality_proof: proto::FinalityProof,
+) -> Result<FinalityProof<RelayChainHeader>, Error> {
+	let block = if finality_proof.block.len() == 32 {
+		H256::from_slice(&*finality_proof.block)
+	} else {
+		Err(anyhow!("Invalid hash type with length: {}", finality_proof.block.len()))?
+	};
+
+	let unknown_headers = finality_proof
+		.unknown_headers
+		.into_iter()
+		.map(|h| {
+			let header = codec::Decode::decode(&mut &h[..])?;
+			Ok(header)
+		})
+		.collect::<Result<_, Error>>()?;
+
+	Ok(FinalityProof { block, justification: finality_proof.justification, unknown_headers })
+}
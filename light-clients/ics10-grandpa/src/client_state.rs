@@ -23,7 +23,10 @@ use alloc::{format, string::ToString, vec::Vec};
 use anyhow::anyhow;
 use core::{marker::PhantomData, time::Duration};
 use ibc::{
-	core::{ics02_client::client_state::ClientType, ics24_host::identifier::ChainId},
+	core::{
+		ics02_client::{client_state::ClientType, error::Error as Ics02Error},
+		ics24_host::identifier::ChainId,
+	},
 	Height,
 };
 use light_client_common::RelayChain;
@@ -50,6 +53,12 @@ pub struct ClientState<H> {
 	pub latest_para_height: u32,
 	/// ParaId of associated parachain
 	pub para_id: u32,
+	/// Whether the client tracks a standalone chain that runs its own GRANDPA. The relay chain
+	/// fields then refer to the standalone chain itself, `latest_para_height` tracks its latest
+	/// finalized header with a consensus state and `para_id` is `0`.
+	pub standalone: bool,
+	/// Revision number of the heights of a standalone chain, see [`ClientState::revision_number`].
+	pub standalone_revision_number: u64,
 	/// Id of the current authority set.
 	pub current_set_id: u64,
 	/// authorities for the current round
@@ -81,7 +90,7 @@ pub struct UpgradeOptions {
 impl<H: Clone> ClientState<H> {
 	/// Verify that the client is at a sufficient height and unfrozen at the given height
 	pub fn verify_height(&self, height: Height) -> Result<(), Error> {
-		let latest_para_height = Height::new(self.para_id.into(), self.latest_para_height.into());
+		let latest_para_height = self.latest_height();
 		if latest_para_height < height {
 			return Err(Error::Custom(format!(
 				"Insufficient height, known height: {latest_para_height}, given height: {height}"
@@ -97,12 +106,22 @@ impl<H: Clone> ClientState<H> {
 }
 
 impl<H> ClientState<H> {
+	/// Revision number of the client heights, the para id for parachains and the configured
+	/// revision number for standalone chains.
+	pub fn revision_number(&self) -> u64 {
+		if self.standalone {
+			self.standalone_revision_number
+		} else {
+			self.para_id.into()
+		}
+	}
+
 	pub fn latest_height(&self) -> Height {
-		Height::new(self.para_id.into(), self.latest_para_height.into())
+		Height::new(self.revision_number(), self.latest_para_height.into())
 	}
 
 	pub fn chain_id(&self) -> ChainId {
-		ChainId::new(self.relay_chain.to_string(), self.para_id as u64)
+		ChainId::new(self.relay_chain.to_string(), self.revision_number())
 	}
 
 	pub fn client_type() -> ClientType {
@@ -174,8 +193,8 @@ where
 		upgrade_height: Height,
 		upgrade_options: UpgradeOptions,
 		chain_id: ChainId,
-	) -> Self {
-		self.upgrade(upgrade_height, upgrade_options, chain_id)
+	) -> Result<Self, Ics02Error> {
+		Ok(self.upgrade(upgrade_height, upgrade_options, chain_id))
 	}
 
 	fn expired(&self, elapsed: Duration) -> bool {
@@ -209,11 +228,15 @@ impl<H> TryFrom<RawClientState> for ClientState<H> {
 		fixed_bytes.copy_from_slice(&*raw.latest_relay_hash);
 		let latest_relay_hash = H256::from(fixed_bytes);
 
+		let revision_number = if raw.standalone { raw.revision_number } else { raw.para_id.into() };
+
 		Ok(Self {
-			frozen_height: raw.frozen_height.map(|height| Height::new(raw.para_id.into(), height)),
+			frozen_height: raw.frozen_height.map(|height| Height::new(revision_number, height)),
 			relay_chain,
 			latest_para_height: raw.latest_para_height,
 			para_id: raw.para_id,
+			standalone: raw.standalone,
+			standalone_revision_number: if raw.standalone { raw.revision_number } else { 0 },
 			current_set_id: raw.current_set_id,
 			current_authorities,
 			latest_relay_hash,
@@ -244,6 +267,8 @@ impl<H> From<ClientState<H>> for RawClientState {
 					weight,
 				})
 				.collect(),
+			standalone: client_state.standalone,
+			revision_number: client_state.standalone_revision_number,
 		}
 	}
 }
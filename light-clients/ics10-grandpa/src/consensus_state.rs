@@ -69,11 +69,7 @@ impl ConsensusState {
 			generic::Header::<u32, BlakeTwo256>::decode(&mut &parachain_header_bytes[..])?;
 		let root = parachain_header.state_root.0.to_vec();
 
-		let timestamp = decode_timestamp_extrinsic(&parachain_header_proof.extrinsic)?;
-		let duration = core::time::Duration::from_millis(timestamp);
-		let timestamp = Timestamp::from_nanoseconds(duration.as_nanos().saturated_into::<u64>())?
-			.into_tm_time()
-			.ok_or_else(|| anyhow!("Error decoding Timestamp, timestamp cannot be zero"))?;
+		let timestamp = timestamp_from_extrinsic(&parachain_header_proof.extrinsic)?;
 
 		Ok((
 			Height::new(para_id as u64, parachain_header.number as u64),
@@ -82,6 +78,34 @@ impl ConsensusState {
 	}
 }
 
+impl ConsensusState {
+	/// Consensus state of a finalized header of a standalone chain, which has no parachain header
+	/// to prove since the header itself is finalized by GRANDPA.
+	pub fn from_standalone_header(
+		header: &generic::Header<u32, BlakeTwo256>,
+		header_proof: ParachainHeaderProofs,
+		revision_number: u64,
+	) -> Result<(Height, Self), Error> {
+		let root = header.state_root.0.to_vec();
+		let timestamp = timestamp_from_extrinsic(&header_proof.extrinsic)?;
+
+		Ok((
+			Height::new(revision_number, header.number as u64),
+			Self { root: root.into(), timestamp },
+		))
+	}
+}
+
+/// Decodes the timestamp inherent of a header into a tendermint [`Time`].
+fn timestamp_from_extrinsic(extrinsic: &Vec<u8>) -> Result<Time, Error> {
+	let timestamp = decode_timestamp_extrinsic(extrinsic)?;
+	let duration = core::time::Duration::from_millis(timestamp);
+	let timestamp = Timestamp::from_nanoseconds(duration.as_nanos().saturated_into::<u64>())?
+		.into_tm_time()
+		.ok_or_else(|| anyhow!("Error decoding Timestamp, timestamp cannot be zero"))?;
+	Ok(timestamp)
+}
+
 impl ibc::core::ics02_client::client_consensus::ConsensusState for ConsensusState {
 	type Error = Infallible;
 
@@ -138,6 +138,8 @@ async fn test_continuous_update_of_grandpa_client() {
 			frozen_height: None,
 			latest_para_height: decoded_para_head.number,
 			para_id: prover.para_id,
+			standalone: false,
+			standalone_revision_number: 0,
 			current_set_id: client_state.current_set_id,
 			current_authorities: client_state.current_authorities,
 			_phantom: Default::default(),
@@ -294,3 +296,462 @@ async fn test_continuous_update_of_grandpa_client() {
 		}
 	}
 }
+
+#[test]
+fn test_standalone_chain_headers_update_client_state() {
+	use crate::client_def::GrandpaClient;
+	use codec::Encode;
+	use grandpa_client_primitives::FinalityProof;
+	use sp_core::{ed25519, Pair};
+	use sp_finality_grandpa::AuthorityId;
+	use sp_runtime::traits::Header as _;
+
+	let set_id = 1;
+	let round = 1;
+
+	let (extrinsic, extrinsics_root, extrinsic_proof) = timestamp_extrinsic_with_proof();
+
+	let genesis = RelayChainHeader {
+		parent_hash: Default::default(),
+		number: 1,
+		state_root: Default::default(),
+		extrinsics_root,
+		digest: Default::default(),
+	};
+	let mut headers = vec![];
+	let mut parent_hash = genesis.hash();
+	for number in 2..=4 {
+		let header = RelayChainHeader {
+			parent_hash,
+			number,
+			state_root: H256::repeat_byte(number as u8),
+			extrinsics_root,
+			digest: Default::default(),
+		};
+		parent_hash = header.hash();
+		headers.push(header);
+	}
+	let target = headers.last().unwrap().clone();
+
+	let authorities = (1..=3)
+		.map(|i| ed25519::Pair::from_string(&format!("//{i}"), None).unwrap())
+		.collect::<Vec<_>>();
+	let justification = signed_justification(&target, round, set_id, &authorities);
+
+	let headers_with_proof = standalone_header_proofs(&headers, &extrinsic, &extrinsic_proof);
+	let client_message = ClientMessage::Header(Header {
+		finality_proof: FinalityProof {
+			block: target.hash(),
+			justification: justification.encode(),
+			unknown_headers: headers.clone(),
+		},
+		parachain_headers: headers_with_proof,
+		authority_set_changes: vec![],
+	});
+
+	let client_state = ClientState::<HostFunctionsManager> {
+		relay_chain: Default::default(),
+		latest_relay_hash: genesis.hash(),
+		latest_relay_height: genesis.number,
+		frozen_height: None,
+		latest_para_height: genesis.number,
+		para_id: 0,
+		standalone: true,
+		standalone_revision_number: 1,
+		current_set_id: set_id,
+		current_authorities: authorities
+			.iter()
+			.map(|pair| (AuthorityId::from(pair.public()), 1))
+			.collect(),
+		_phantom: Default::default(),
+	};
+
+	// headers of a standalone chain have no parachain header to prove
+	let parachain_client_state = ClientState { standalone: false, ..client_state.clone() };
+	assert!(GrandpaClient::<HostFunctionsManager>::verify_message(
+		parachain_client_state,
+		client_message.clone()
+	)
+	.is_err());
+
+	GrandpaClient::<HostFunctionsManager>::verify_message(
+		client_state.clone(),
+		client_message.clone(),
+	)
+	.unwrap();
+	let (client_state, consensus_states) =
+		GrandpaClient::<HostFunctionsManager>::apply_header(client_state, client_message).unwrap();
+
+	assert_eq!(client_state.latest_height(), Height::new(1, 4));
+	assert_eq!(client_state.latest_relay_hash, target.hash());
+	assert_eq!(consensus_states.len(), headers.len());
+	for header in &headers {
+		let (_, consensus_state) = consensus_states
+			.iter()
+			.find(|(height, ..)| *height == Height::new(1, header.number as u64))
+			.unwrap();
+		assert_eq!(consensus_state.root.as_bytes(), header.state_root.as_bytes());
+	}
+}
+
+/// Timestamp extrinsic shared by the headers of the standalone chain tests, with the extrinsics
+/// root of a block containing only this extrinsic and its proof against that root.
+fn timestamp_extrinsic_with_proof() -> (Vec<u8>, H256, Vec<Vec<u8>>) {
+	use codec::{Compact, Encode};
+	use sp_runtime::traits::BlakeTwo256;
+	use sp_trie::{generate_trie_proof, LayoutV0, MemoryDB, TrieDBMut, TrieMut};
+
+	let mut db = MemoryDB::<BlakeTwo256>::default();
+	let mut extrinsic = (1u8, 0u8, Compact(1_650_894_363_000u64)).encode();
+	extrinsic.insert(0, 0);
+	extrinsic.insert(0, 0);
+	let key = Compact(0u32).encode();
+	let extrinsics_root = {
+		let mut root = Default::default();
+		let mut trie = TrieDBMut::<LayoutV0<BlakeTwo256>>::new(&mut db, &mut root);
+		trie.insert(&key, &extrinsic).unwrap();
+		*trie.root()
+	};
+	let extrinsic_proof =
+		generate_trie_proof::<LayoutV0<BlakeTwo256>, _, _, _>(&db, extrinsics_root, vec![&key])
+			.unwrap();
+
+	(extrinsic, extrinsics_root, extrinsic_proof)
+}
+
+/// Proofs of the timestamp extrinsic of every header of a standalone chain, which have no state
+/// proof since the headers are finalized themselves.
+fn standalone_header_proofs(
+	headers: &[RelayChainHeader],
+	extrinsic: &[u8],
+	extrinsic_proof: &[Vec<u8>],
+) -> std::collections::BTreeMap<H256, ParachainHeaderProofs> {
+	use sp_runtime::traits::Header as _;
+
+	headers
+		.iter()
+		.map(|header| {
+			let proofs = ParachainHeaderProofs {
+				state_proof: vec![],
+				extrinsic: extrinsic.to_vec(),
+				extrinsic_proof: extrinsic_proof.to_vec(),
+			};
+			(header.hash(), proofs)
+		})
+		.collect()
+}
+
+/// Justification for `target` with precommits from all of the given authorities.
+fn signed_justification(
+	target: &RelayChainHeader,
+	round: u64,
+	set_id: u64,
+	authorities: &[sp_core::ed25519::Pair],
+) -> GrandpaJustification<RelayChainHeader> {
+	use finality_grandpa::{Precommit, SignedPrecommit};
+	use grandpa_client_primitives::Commit;
+	use sp_core::Pair;
+	use sp_finality_grandpa::{AuthorityId, AuthoritySignature};
+	use sp_runtime::traits::Header as _;
+
+	let precommit = Precommit { target_hash: target.hash(), target_number: target.number };
+	let message = finality_grandpa::Message::Precommit(precommit.clone());
+	let payload = sp_finality_grandpa::localized_payload(round, set_id, &message);
+	let precommits = authorities
+		.iter()
+		.map(|pair| SignedPrecommit {
+			precommit: precommit.clone(),
+			signature: AuthoritySignature::from(pair.sign(&payload)),
+			id: AuthorityId::from(pair.public()),
+		})
+		.collect();
+
+	GrandpaJustification::<RelayChainHeader> {
+		round,
+		commit: Commit::<RelayChainHeader> {
+			target_hash: target.hash(),
+			target_number: target.number,
+			precommits,
+		},
+		votes_ancestries: vec![],
+	}
+}
+
+#[test]
+fn test_authority_set_changes_in_single_update() {
+	use crate::client_def::GrandpaClient;
+	use codec::Encode;
+	use grandpa_client_primitives::FinalityProof;
+	use sp_core::{ed25519, Pair};
+	use sp_finality_grandpa::{AuthorityId, ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
+	use sp_runtime::{
+		generic::{Digest, DigestItem},
+		traits::Header as _,
+	};
+
+	let round = 1;
+	let authority_sets = [1..=3, 4..=6, 7..=9]
+		.into_iter()
+		.map(|seeds| {
+			seeds
+				.map(|i| ed25519::Pair::from_string(&format!("//{i}"), None).unwrap())
+				.collect::<Vec<_>>()
+		})
+		.collect::<Vec<_>>();
+	let authority_list = |pairs: &[ed25519::Pair]| {
+		pairs
+			.iter()
+			.map(|pair| (AuthorityId::from(pair.public()), 1))
+			.collect::<Vec<_>>()
+	};
+
+	let (extrinsic, extrinsics_root, extrinsic_proof) = timestamp_extrinsic_with_proof();
+
+	// blocks 4 and 7 hand over to the next authority set, block 9 is finalized by the last one.
+	let genesis = RelayChainHeader {
+		parent_hash: Default::default(),
+		number: 1,
+		state_root: Default::default(),
+		extrinsics_root,
+		digest: Default::default(),
+	};
+	let mut headers = vec![];
+	let mut parent_hash = genesis.hash();
+	for number in 2..=9 {
+		let next_authorities = match number {
+			4 => Some(authority_list(&authority_sets[1])),
+			7 => Some(authority_list(&authority_sets[2])),
+			_ => None,
+		};
+		let logs = next_authorities
+			.map(|next_authorities| {
+				let log = ConsensusLog::<u32>::ScheduledChange(ScheduledChange {
+					next_authorities,
+					delay: 0,
+				});
+				DigestItem::Consensus(GRANDPA_ENGINE_ID, log.encode())
+			})
+			.into_iter()
+			.collect();
+		let header = RelayChainHeader {
+			parent_hash,
+			number,
+			state_root: H256::repeat_byte(number as u8),
+			extrinsics_root,
+			digest: Digest { logs },
+		};
+		parent_hash = header.hash();
+		headers.push(header);
+	}
+
+	let finality_proof = |set_id: u64, range: std::ops::Range<usize>| {
+		let unknown_headers = headers[range].to_vec();
+		let target = unknown_headers.last().unwrap();
+		FinalityProof {
+			block: target.hash(),
+			justification: signed_justification(
+				target,
+				round,
+				set_id,
+				&authority_sets[set_id as usize],
+			)
+			.encode(),
+			unknown_headers,
+		}
+	};
+	let headers_with_proof = standalone_header_proofs(&headers, &extrinsic, &extrinsic_proof);
+	let header = Header {
+		finality_proof: finality_proof(2, 6..8),
+		parachain_headers: headers_with_proof,
+		authority_set_changes: vec![finality_proof(0, 0..3), finality_proof(1, 3..6)],
+	};
+
+	let client_state = ClientState::<HostFunctionsManager> {
+		relay_chain: Default::default(),
+		latest_relay_hash: genesis.hash(),
+		latest_relay_height: genesis.number,
+		frozen_height: None,
+		latest_para_height: genesis.number,
+		para_id: 0,
+		standalone: true,
+		standalone_revision_number: 1,
+		current_set_id: 0,
+		current_authorities: authority_list(&authority_sets[0]),
+		_phantom: Default::default(),
+	};
+
+	// the finalized block can't be justified without handing over the authority sets first.
+	let missing_handover = Header {
+		authority_set_changes: vec![header.authority_set_changes[0].clone()],
+		..header.clone()
+	};
+	assert!(GrandpaClient::<HostFunctionsManager>::verify_message(
+		client_state.clone(),
+		ClientMessage::Header(missing_handover)
+	)
+	.is_err());
+
+	let client_message = ClientMessage::Header(header);
+	GrandpaClient::<HostFunctionsManager>::verify_message(
+		client_state.clone(),
+		client_message.clone(),
+	)
+	.unwrap();
+	let (client_state, consensus_states) =
+		GrandpaClient::<HostFunctionsManager>::apply_header(client_state, client_message).unwrap();
+
+	assert_eq!(client_state.current_set_id, 2);
+	assert_eq!(client_state.current_authorities, authority_list(&authority_sets[2]));
+	assert_eq!(client_state.latest_relay_hash, headers.last().unwrap().hash());
+	assert_eq!(client_state.latest_height(), Height::new(1, 9));
+	// headers finalized by the authority set change proofs are also proven.
+	assert_eq!(consensus_states.len(), headers.len());
+}
+
+#[test]
+fn test_forced_authority_set_change_in_single_update() {
+	use crate::client_def::GrandpaClient;
+	use codec::Encode;
+	use grandpa_client_primitives::FinalityProof;
+	use sp_core::{ed25519, Pair};
+	use sp_finality_grandpa::{AuthorityId, ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
+	use sp_runtime::{
+		generic::{Digest, DigestItem},
+		traits::Header as _,
+	};
+
+	let round = 1;
+	let authority_sets = [1..=3, 4..=6, 7..=9]
+		.into_iter()
+		.map(|seeds| {
+			seeds
+				.map(|i| ed25519::Pair::from_string(&format!("//{i}"), None).unwrap())
+				.collect::<Vec<_>>()
+		})
+		.collect::<Vec<_>>();
+	let authority_list = |pairs: &[ed25519::Pair]| {
+		pairs
+			.iter()
+			.map(|pair| (AuthorityId::from(pair.public()), 1))
+			.collect::<Vec<_>>()
+	};
+
+	let (extrinsic, extrinsics_root, extrinsic_proof) = timestamp_extrinsic_with_proof();
+
+	// block 4 hands over to the second authority set, which stalls. Block 6 forces the third
+	// authority set, enacted at block 7, which finalizes everything up to block 9.
+	let genesis = RelayChainHeader {
+		parent_hash: Default::default(),
+		number: 1,
+		state_root: Default::default(),
+		extrinsics_root,
+		digest: Default::default(),
+	};
+	let mut headers = vec![];
+	let mut parent_hash = genesis.hash();
+	for number in 2..=9 {
+		let log = match number {
+			4 => Some(ConsensusLog::<u32>::ScheduledChange(ScheduledChange {
+				next_authorities: authority_list(&authority_sets[1]),
+				delay: 0,
+			})),
+			6 => Some(ConsensusLog::<u32>::ForcedChange(
+				4,
+				ScheduledChange { next_authorities: authority_list(&authority_sets[2]), delay: 1 },
+			)),
+			_ => None,
+		};
+		let logs = log
+			.map(|log| DigestItem::Consensus(GRANDPA_ENGINE_ID, log.encode()))
+			.into_iter()
+			.collect();
+		let header = RelayChainHeader {
+			parent_hash,
+			number,
+			state_root: H256::repeat_byte(number as u8),
+			extrinsics_root,
+			digest: Digest { logs },
+		};
+		parent_hash = header.hash();
+		headers.push(header);
+	}
+
+	let finality_proof = |set_id: u64, signers: usize, range: std::ops::Range<usize>| {
+		let unknown_headers = headers[range].to_vec();
+		let target = unknown_headers.last().unwrap();
+		FinalityProof {
+			block: target.hash(),
+			justification: signed_justification(
+				target,
+				round,
+				set_id,
+				&authority_sets[signers],
+			)
+			.encode(),
+			unknown_headers,
+		}
+	};
+	let headers_with_proof = standalone_header_proofs(&headers, &extrinsic, &extrinsic_proof);
+	let header = Header {
+		finality_proof: finality_proof(2, 2, 6..8),
+		parachain_headers: headers_with_proof,
+		authority_set_changes: vec![finality_proof(0, 0, 0..3), finality_proof(2, 2, 3..6)],
+	};
+
+	let client_state = ClientState::<HostFunctionsManager> {
+		relay_chain: Default::default(),
+		latest_relay_hash: genesis.hash(),
+		latest_relay_height: genesis.number,
+		frozen_height: None,
+		latest_para_height: genesis.number,
+		para_id: 0,
+		standalone: true,
+		standalone_revision_number: 1,
+		current_set_id: 0,
+		current_authorities: authority_list(&authority_sets[0]),
+		_phantom: Default::default(),
+	};
+
+	// the stalled authority set can't justify blocks past the forced change.
+	let stalled_set = Header {
+		authority_set_changes: vec![
+			header.authority_set_changes[0].clone(),
+			finality_proof(1, 1, 3..6),
+		],
+		..header.clone()
+	};
+	assert!(GrandpaClient::<HostFunctionsManager>::verify_message(
+		client_state.clone(),
+		ClientMessage::Header(stalled_set)
+	)
+	.is_err());
+
+	// the forced authority set only takes over once the change is enacted.
+	let not_enacted = Header {
+		authority_set_changes: vec![
+			header.authority_set_changes[0].clone(),
+			finality_proof(2, 2, 3..5),
+		],
+		..header.clone()
+	};
+	assert!(GrandpaClient::<HostFunctionsManager>::verify_message(
+		client_state.clone(),
+		ClientMessage::Header(not_enacted)
+	)
+	.is_err());
+
+	let client_message = ClientMessage::Header(header);
+	GrandpaClient::<HostFunctionsManager>::verify_message(
+		client_state.clone(),
+		client_message.clone(),
+	)
+	.unwrap();
+	let (client_state, consensus_states) =
+		GrandpaClient::<HostFunctionsManager>::apply_header(client_state, client_message).unwrap();
+
+	assert_eq!(client_state.current_set_id, 2);
+	assert_eq!(client_state.current_authorities, authority_list(&authority_sets[2]));
+	assert_eq!(client_state.latest_relay_hash, headers.last().unwrap().hash());
+	assert_eq!(client_state.latest_height(), Height::new(1, 9));
+	assert_eq!(consensus_states.len(), headers.len());
+}
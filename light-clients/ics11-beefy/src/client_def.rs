@@ -14,7 +14,8 @@
 // limitations under the License.
 
 use beefy_light_client_primitives::{
-	ClientState as LightClientState, ParachainHeader, ParachainsUpdateProof,
+	ClientState as LightClientState, ParachainHeader, ParachainsUpdateProof, RelayChainHeader,
+	RelayChainUpdateProof,
 };
 use codec::{Decode, Encode};
 use core::{fmt::Debug, marker::PhantomData};
@@ -23,7 +24,9 @@ use primitive_types::H256;
 use tendermint_proto::Protobuf;
 
 use crate::{
-	client_message::ClientMessage, client_state::ClientState, consensus_state::ConsensusState,
+	client_message::{BeefyHeader, ClientMessage, MmrUpdate},
+	client_state::ClientState,
+	consensus_state::ConsensusState,
 	error::Error,
 };
 use ibc::{
@@ -84,13 +87,86 @@ where
 				};
 				// If mmr update exists verify it and return the new light client state
 				// or else return existing light client state
-				let light_client_state = if let Some(mmr_update) = header.mmr_update_proof {
-					beefy_client::verify_mmr_root_with_proof::<H>(light_client_state, mmr_update)
-						.map_err(Error::from)?
-				} else {
-					light_client_state
+				let light_client_state = match header.mmr_update_proof {
+					Some(MmrUpdate::Full(mmr_update)) =>
+						beefy_client::verify_mmr_root_with_proof::<H>(
+							light_client_state,
+							mmr_update,
+						)
+						.map_err(Error::from)?,
+					Some(MmrUpdate::Sampled(_)) if client_state.signature_sample_size == 0 =>
+						Err(Error::Custom(
+							"Sampled mmr updates are disabled for this client".to_string(),
+						))?,
+					Some(MmrUpdate::Sampled(mmr_update)) =>
+						beefy_client::verify_mmr_root_with_sampled_proof::<H>(
+							light_client_state,
+							mmr_update,
+							client_state.signature_sample_size,
+						)
+						.map_err(Error::from)?,
+					None => light_client_state,
 				};
 
+				if client_state.track_relay_chain && header.headers_with_proof.is_some() {
+					Err(Error::Custom(
+						"Client tracking the relay chain can't be updated with parachain headers"
+							.to_string(),
+					))?
+				}
+				if !client_state.track_relay_chain &&
+					header.relay_chain_headers_with_proof.is_some()
+				{
+					Err(Error::Custom(
+						"Client tracking a parachain can't be updated with relay chain headers"
+							.to_string(),
+					))?
+				}
+
+				// Extract relay chain headers from the beefy header if they exist
+				if let Some(headers_with_proof) = header.relay_chain_headers_with_proof {
+					let mut leaf_indices = vec![];
+					let relay_chain_headers = headers_with_proof
+						.headers
+						.into_iter()
+						.map(|header| {
+							let leaf_index =
+								client_state.to_leaf_index(header.relay_chain_header.number + 1);
+							leaf_indices.push(leaf_index as u64);
+							RelayChainHeader {
+								relay_chain_header: header.relay_chain_header.encode(),
+								mmr_leaf: header.mmr_leaf,
+								extrinsic_proof: header.extrinsic_proof,
+								timestamp_extrinsic: header.timestamp_extrinsic,
+							}
+						})
+						.collect::<Vec<_>>();
+
+					let leaf_count = (client_state
+						.to_leaf_index(light_client_state.latest_beefy_height) +
+						1) as u64;
+
+					let relay_chain_update_proof = RelayChainUpdateProof {
+						relay_chain_headers,
+						mmr_proof: BatchProof {
+							leaf_indices,
+							leaf_count,
+							items: headers_with_proof
+								.mmr_proofs
+								.into_iter()
+								.map(|item| H256::decode(&mut &*item))
+								.collect::<Result<Vec<_>, _>>()
+								.map_err(Error::from)?,
+						},
+					};
+
+					beefy_client::verify_relay_chain_headers::<H>(
+						light_client_state.clone(),
+						relay_chain_update_proof,
+					)
+					.map_err(Error::from)?
+				}
+
 				// Extract parachain headers from the beefy header if they exist
 				if let Some(headers_with_proof) = header.headers_with_proof {
 					let mut leaf_indices = vec![];
@@ -164,29 +240,20 @@ where
 		let mut client_state = client_state.from_header(header.clone()).map_err(Error::from)?;
 		let mut latest_para_height = client_state.latest_para_height;
 
-		if let Some(parachain_headers) = header.headers_with_proof {
-			for header in parachain_headers.headers {
-				// Skip genesis block of parachains since it has no timestamp or ibc root
-				if header.parachain_header.number == 0 {
-					continue
-				}
-				if latest_para_height < header.parachain_header.number {
-					latest_para_height = header.parachain_header.number;
-				}
-				let height =
-					Height::new(client_state.para_id as u64, header.parachain_header.number as u64);
-				// Skip duplicate consensus states
-				if ctx.consensus_state(&client_id, height).is_ok() {
-					continue
-				}
-				parachain_cs_states.push((
-					height,
-					Ctx::AnyConsensusState::wrap(
-						&ConsensusState::from_header(header).map_err(Error::from)?,
-					)
-					.ok_or_else(|| Error::Custom("Ctx::AnyConsensusState".to_string()))?,
-				))
+		for (number, consensus_state) in header_consensus_states(header)? {
+			if latest_para_height < number {
+				latest_para_height = number;
+			}
+			let height = Height::new(client_state.para_id as u64, number as u64);
+			// Skip duplicate consensus states
+			if ctx.consensus_state(&client_id, height).is_ok() {
+				continue
 			}
+			parachain_cs_states.push((
+				height,
+				Ctx::AnyConsensusState::wrap(&consensus_state)
+					.ok_or_else(|| Error::Custom("Ctx::AnyConsensusState".to_string()))?,
+			))
 		}
 
 		client_state.latest_para_height = latest_para_height;
@@ -215,30 +282,23 @@ where
 			ClientMessage::Header(header) => {
 				// we simply check that this update doesn't include competing consensus states for
 				// heights we already processed.
-				if let Some(parachain_headers) = header.headers_with_proof {
-					for header in parachain_headers.headers {
-						let height = Height::new(
-							client_state.para_id as u64,
-							header.parachain_header.number as u64,
-						);
-
-						let consensus_state =
-							ConsensusState::from_header(header).map_err(Error::from)?;
-						match ctx.maybe_consensus_state(&client_id, height)? {
-							Some(cs) => {
-								let cs: ConsensusState =
-									cs.downcast().ok_or(Ics02Error::client_args_type_mismatch(
-										client_state.client_type().to_owned(),
-									))?;
-
-								if cs != consensus_state {
-									// Houston we have a problem
-									return Ok(true)
-								}
-							},
-							None => {},
-						};
-					}
+				for (number, consensus_state) in header_consensus_states(header)? {
+					let height = Height::new(client_state.para_id as u64, number as u64);
+
+					match ctx.maybe_consensus_state(&client_id, height)? {
+						Some(cs) => {
+							let cs: ConsensusState =
+								cs.downcast().ok_or(Ics02Error::client_args_type_mismatch(
+									client_state.client_type().to_owned(),
+								))?;
+
+							if cs != consensus_state {
+								// Houston we have a problem
+								return Ok(true)
+							}
+						},
+						None => {},
+					};
 				}
 			},
 			// todo: Beefy protocol hasn't yet defined it's equivocation protocol
@@ -465,3 +525,23 @@ where
 		Ok(())
 	}
 }
+
+/// Block numbers and consensus states of the parachain or relay chain headers of a
+/// [`BeefyHeader`], skipping genesis blocks since they have no timestamp or ibc root.
+fn header_consensus_states(header: BeefyHeader) -> Result<Vec<(u32, ConsensusState)>, Error> {
+	let parachain_headers = header
+		.headers_with_proof
+		.into_iter()
+		.flat_map(|headers| headers.headers)
+		.filter(|header| header.parachain_header.number != 0)
+		.map(|header| Ok((header.parachain_header.number, ConsensusState::from_header(header)?)));
+	let relay_chain_headers = header
+		.relay_chain_headers_with_proof
+		.into_iter()
+		.flat_map(|headers| headers.headers)
+		.filter(|header| header.relay_chain_header.number != 0)
+		.map(|header| {
+			Ok((header.relay_chain_header.number, ConsensusState::from_relay_chain_header(header)?))
+		});
+	parachain_headers.chain(relay_chain_headers).collect()
+}
@@ -22,14 +22,15 @@ use crate::{
 		BeefyMmrLeafPartial as RawBeefyMmrLeafPartial, ClientMessage as RawClientMessage,
 		ClientStateUpdateProof as RawMmrUpdateProof, Commitment as RawCommitment,
 		CommitmentSignature, ConsensusStateUpdateProof, Header as RawBeefyHeader,
-		Misbehaviour as RawMisbehaviour, PayloadItem, SignedCommitment as RawSignedCommitment,
+		Misbehaviour as RawMisbehaviour, PayloadItem, RelayChainHeader as RawRelayChainHeader,
+		RelayChainUpdateProof as RawRelayChainUpdateProof, SignedCommitment as RawSignedCommitment,
 	},
 };
 use alloc::{format, vec, vec::Vec};
 use anyhow::anyhow;
 use beefy_light_client_primitives::{
-	BeefyNextAuthoritySet, Hash, MmrUpdateProof, PartialMmrLeaf, SignatureWithAuthorityIndex,
-	SignedCommitment,
+	BeefyNextAuthoritySet, Hash, MmrUpdateProof, PartialMmrLeaf, SampledMmrUpdateProof,
+	SampledSignedCommitment, SignatureWithAuthorityIndex, SignedCommitment,
 };
 use beefy_primitives::{
 	known_payload_ids::MMR_ROOT_ID,
@@ -51,7 +52,78 @@ pub const BEEFY_CLIENT_MESSAGE_TYPE_URL: &str = "/ibc.lightclients.beefy.v1.Clie
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct BeefyHeader {
 	pub headers_with_proof: Option<ParachainHeadersWithProof>,
-	pub mmr_update_proof: Option<MmrUpdateProof>, // Proof for updating the latest mmr root hash
+	pub mmr_update_proof: Option<MmrUpdate>, // Proof for updating the latest mmr root hash
+	pub relay_chain_headers_with_proof: Option<RelayChainHeadersWithProof>,
+}
+
+/// Proof for updating the latest mmr root hash
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MmrUpdate {
+	/// Carries the signatures of all the authorities that signed the commitment
+	Full(MmrUpdateProof),
+	/// Only carries the signatures of a sample of the authorities that signed the commitment
+	Sampled(SampledMmrUpdateProof),
+}
+
+impl MmrUpdate {
+	/// The commitment signed by the beefy authorities
+	pub fn commitment(&self) -> &Commitment<u32> {
+		match self {
+			MmrUpdate::Full(proof) => &proof.signed_commitment.commitment,
+			MmrUpdate::Sampled(proof) => &proof.signed_commitment.commitment,
+		}
+	}
+
+	/// The latest mmr leaf
+	pub fn latest_mmr_leaf(&self) -> &MmrLeaf<u32, H256, H256, H256> {
+		match self {
+			MmrUpdate::Full(proof) => &proof.latest_mmr_leaf,
+			MmrUpdate::Sampled(proof) => &proof.latest_mmr_leaf,
+		}
+	}
+
+	/// Splits the update into the fields shared by both variants and the signers bitfield, which
+	/// is empty for [`MmrUpdate::Full`].
+	fn into_parts(self) -> (MmrUpdateProof, Vec<u8>) {
+		match self {
+			MmrUpdate::Full(proof) => (proof, vec![]),
+			MmrUpdate::Sampled(proof) => (
+				MmrUpdateProof {
+					signed_commitment: SignedCommitment {
+						commitment: proof.signed_commitment.commitment,
+						signatures: proof.signed_commitment.signatures,
+					},
+					latest_mmr_leaf: proof.latest_mmr_leaf,
+					mmr_proof: proof.mmr_proof,
+					authority_proof: proof.authority_proof,
+				},
+				proof.signed_commitment.signers,
+			),
+		}
+	}
+
+	/// Inverse of [`MmrUpdate::into_parts`]
+	fn from_parts(proof: MmrUpdateProof, signers: Vec<u8>) -> Self {
+		if signers.is_empty() {
+			return MmrUpdate::Full(proof)
+		}
+		MmrUpdate::Sampled(SampledMmrUpdateProof {
+			signed_commitment: SampledSignedCommitment {
+				commitment: proof.signed_commitment.commitment,
+				signers,
+				signatures: proof.signed_commitment.signatures,
+			},
+			latest_mmr_leaf: proof.latest_mmr_leaf,
+			mmr_proof: proof.mmr_proof,
+			authority_proof: proof.authority_proof,
+		})
+	}
+}
+
+impl From<MmrUpdateProof> for MmrUpdate {
+	fn from(proof: MmrUpdateProof) -> Self {
+		MmrUpdate::Full(proof)
+	}
 }
 
 /// [`ClientMessage`] for ICS11-BEEFY
@@ -70,6 +142,24 @@ pub struct ParachainHeadersWithProof {
 	pub mmr_size: u64,                 // The latest mmr size
 }
 
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RelayChainHeadersWithProof {
+	pub headers: Vec<RelayChainHeader>, // contains the relay chain headers
+	pub mmr_proofs: Vec<Vec<u8>>,       // mmr proofs for the leaves of these headers' children
+	pub mmr_size: u64,                  // The latest mmr size
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, codec::Encode, codec::Decode)]
+pub struct RelayChainHeader {
+	pub relay_chain_header: SubstrateHeader<u32, BlakeTwo256>,
+	/// The mmr leaf appended by the child of this header
+	pub mmr_leaf: MmrLeaf<u32, H256, H256, H256>,
+	/// Trie merkle proof of inclusion of the set timestamp extrinsic in header.extrinsic_root
+	pub extrinsic_proof: Vec<Vec<u8>>,
+	/// this already encodes the actual extrinsic
+	pub timestamp_extrinsic: Vec<u8>,
+}
+
 impl ibc::core::ics02_client::client_message::ClientMessage for ClientMessage {
 	fn encode_to_vec(&self) -> Vec<u8> {
 		self.encode_vec()
@@ -103,6 +193,51 @@ pub fn merge_leaf_version(major: u8, minor: u8) -> u8 {
 	(major << 5) + minor
 }
 
+fn mmr_leaf_from_raw(mmr_leaf: &RawBeefyMmrLeaf) -> Result<MmrLeaf<u32, H256, H256, H256>, Error> {
+	let beefy_next_authority_set = mmr_leaf
+		.beefy_next_authority_set
+		.as_ref()
+		.ok_or_else(|| Error::Custom(format!("Beefy Next Authority set is missing")))?;
+
+	Ok(MmrLeaf {
+		version: {
+			let (major, minor) = split_leaf_version(mmr_leaf.version.saturated_into::<u8>());
+			MmrLeafVersion::new(major, minor)
+		},
+		parent_number_and_hash: {
+			let parent_number = mmr_leaf.parent_number;
+			let parent_hash = H256::decode(&mut mmr_leaf.parent_hash.as_slice())
+				.map_err(|e| Error::Custom(format!("{e}")))?;
+			(parent_number, parent_hash)
+		},
+		beefy_next_authority_set: BeefyNextAuthoritySet {
+			id: beefy_next_authority_set.id,
+			len: beefy_next_authority_set.len,
+			root: H256::decode(&mut beefy_next_authority_set.authority_root.as_slice())
+				.map_err(|e| Error::Custom(format!("{e}")))?,
+		},
+		leaf_extra: H256::decode(&mut mmr_leaf.parachain_heads.as_slice())
+			.map_err(|e| Error::Custom(format!("{e}")))?,
+	})
+}
+
+fn mmr_leaf_to_raw(mmr_leaf: MmrLeaf<u32, H256, H256, H256>) -> RawBeefyMmrLeaf {
+	RawBeefyMmrLeaf {
+		version: {
+			let (major, minor) = mmr_leaf.version.split();
+			merge_leaf_version(major, minor) as u32
+		},
+		parent_number: mmr_leaf.parent_number_and_hash.0,
+		parent_hash: mmr_leaf.parent_number_and_hash.1.encode(),
+		beefy_next_authority_set: Some(RawBeefyAuthoritySet {
+			id: mmr_leaf.beefy_next_authority_set.id,
+			len: mmr_leaf.beefy_next_authority_set.len,
+			authority_root: mmr_leaf.beefy_next_authority_set.root.encode(),
+		}),
+		parachain_heads: mmr_leaf.leaf_extra.encode(),
+	}
+}
+
 impl TryFrom<RawClientMessage> for ClientMessage {
 	type Error = Error;
 
@@ -245,40 +380,13 @@ impl TryFrom<RawClientMessage> for ClientMessage {
 						.mmr_leaf
 						.as_ref()
 						.ok_or_else(|| Error::Custom(format!("Mmr Leaf is missing")))?;
-					let beefy_next_authority_set =
-						mmr_leaf.beefy_next_authority_set.as_ref().ok_or_else(|| {
-							Error::Custom(format!("Beefy Next Authority set is missing"))
-						})?;
 
-					Some(MmrUpdateProof {
+					let mmr_update_proof = MmrUpdateProof {
 						signed_commitment: SignedCommitment {
 							commitment: Commitment { payload, block_number, validator_set_id },
 							signatures,
 						},
-						latest_mmr_leaf: MmrLeaf {
-							version: {
-								let (major, minor) =
-									split_leaf_version(mmr_leaf.version.saturated_into::<u8>());
-								MmrLeafVersion::new(major, minor)
-							},
-							parent_number_and_hash: {
-								let parent_number = mmr_leaf.parent_number;
-								let parent_hash =
-									H256::decode(&mut mmr_leaf.parent_hash.as_slice())
-										.map_err(|e| Error::Custom(format!("{e}")))?;
-								(parent_number, parent_hash)
-							},
-							beefy_next_authority_set: BeefyNextAuthoritySet {
-								id: beefy_next_authority_set.id,
-								len: beefy_next_authority_set.len,
-								root: H256::decode(
-									&mut beefy_next_authority_set.authority_root.as_slice(),
-								)
-								.map_err(|e| Error::Custom(format!("{e}")))?,
-							},
-							leaf_extra: H256::decode(&mut mmr_leaf.parachain_heads.as_slice())
-								.map_err(|e| Error::Custom(format!("{e}")))?,
-						},
+						latest_mmr_leaf: mmr_leaf_from_raw(mmr_leaf)?,
 						mmr_proof: Proof {
 							leaf_index: mmr_update.mmr_leaf_index,
 							leaf_count: mmr_update.mmr_leaf_index + 1,
@@ -306,12 +414,48 @@ impl TryFrom<RawClientMessage> for ClientMessage {
 								Ok(dest)
 							})
 							.collect::<Result<Vec<_>, Error>>()?,
-					})
+					};
+					Some(MmrUpdate::from_parts(mmr_update_proof, mmr_update.signers))
 				} else {
 					None
 				};
 
-				ClientMessage::Header(BeefyHeader { headers_with_proof, mmr_update_proof })
+				let relay_chain_headers_with_proof = raw_header
+					.relay_chain
+					.map(|relay_chain_update| {
+						let headers = relay_chain_update
+							.relay_chain_headers
+							.into_iter()
+							.map(|raw_header| {
+								Ok(RelayChainHeader {
+									relay_chain_header: SubstrateHeader::decode(
+										&mut &raw_header.relay_chain_header[..],
+									)?,
+									mmr_leaf: mmr_leaf_from_raw(
+										raw_header.mmr_leaf.as_ref().ok_or_else(|| {
+											Error::Custom(format!(
+												"Invalid header, missing mmr_leaf"
+											))
+										})?,
+									)?,
+									extrinsic_proof: raw_header.extrinsic_proof,
+									timestamp_extrinsic: raw_header.timestamp_extrinsic,
+								})
+							})
+							.collect::<Result<Vec<_>, Error>>()?;
+						Ok::<_, Error>(RelayChainHeadersWithProof {
+							headers,
+							mmr_proofs: relay_chain_update.mmr_proofs,
+							mmr_size: relay_chain_update.mmr_size,
+						})
+					})
+					.transpose()?;
+
+				ClientMessage::Header(BeefyHeader {
+					headers_with_proof,
+					mmr_update_proof,
+					relay_chain_headers_with_proof,
+				})
 			},
 			client_message::Message::Misbehaviour(_) => ClientMessage::Misbehaviour(()),
 		};
@@ -380,29 +524,9 @@ impl From<ClientMessage> for RawClientMessage {
 						}
 					}),
 					client_state: if let Some(mmr_update) = beefy_header.mmr_update_proof {
+						let (mmr_update, signers) = mmr_update.into_parts();
 						Some(RawMmrUpdateProof {
-							mmr_leaf: Some(RawBeefyMmrLeaf {
-								version: {
-									let (major, minor) = mmr_update.latest_mmr_leaf.version.split();
-									merge_leaf_version(major, minor) as u32
-								},
-								parent_number: mmr_update.latest_mmr_leaf.parent_number_and_hash.0,
-								parent_hash: mmr_update
-									.latest_mmr_leaf
-									.parent_number_and_hash
-									.1
-									.encode(),
-								beefy_next_authority_set: Some(RawBeefyAuthoritySet {
-									id: mmr_update.latest_mmr_leaf.beefy_next_authority_set.id,
-									len: mmr_update.latest_mmr_leaf.beefy_next_authority_set.len,
-									authority_root: mmr_update
-										.latest_mmr_leaf
-										.beefy_next_authority_set
-										.root
-										.encode(),
-								}),
-								parachain_heads: mmr_update.latest_mmr_leaf.leaf_extra.encode(),
-							}),
+							mmr_leaf: Some(mmr_leaf_to_raw(mmr_update.latest_mmr_leaf)),
 							mmr_leaf_index: mmr_update.mmr_proof.leaf_index,
 							mmr_proof: mmr_update
 								.mmr_proof
@@ -446,10 +570,27 @@ impl From<ClientMessage> for RawClientMessage {
 								.into_iter()
 								.map(|item| item.to_vec())
 								.collect(),
+							signers,
 						})
 					} else {
 						None
 					},
+					relay_chain: beefy_header.relay_chain_headers_with_proof.map(|headers| {
+						RawRelayChainUpdateProof {
+							relay_chain_headers: headers
+								.headers
+								.into_iter()
+								.map(|header| RawRelayChainHeader {
+									relay_chain_header: header.relay_chain_header.encode(),
+									mmr_leaf: Some(mmr_leaf_to_raw(header.mmr_leaf)),
+									extrinsic_proof: header.extrinsic_proof,
+									timestamp_extrinsic: header.timestamp_extrinsic,
+								})
+								.collect(),
+							mmr_proofs: headers.mmr_proofs,
+							mmr_size: headers.mmr_size,
+						}
+					}),
 				})),
 			},
 			ClientMessage::Misbehaviour(_) => RawClientMessage {
@@ -29,7 +29,10 @@ use crate::{client_message::BeefyHeader, error::Error};
 
 use crate::client_def::BeefyClient;
 use ibc::{
-	core::{ics02_client::client_state::ClientType, ics24_host::identifier::ChainId},
+	core::{
+		ics02_client::{client_state::ClientType, error::Error as Ics02Error},
+		ics24_host::identifier::ChainId,
+	},
 	timestamp::Timestamp,
 	Height,
 };
@@ -61,6 +64,14 @@ pub struct ClientState<H> {
 	pub authority: BeefyNextAuthoritySet<H256>,
 	/// authorities for the next round
 	pub next_authority_set: BeefyNextAuthoritySet<H256>,
+	/// Number of signatures sampled from the signers of a commitment in sampled mmr updates,
+	/// sampled mmr updates are rejected if zero or below
+	/// [`beefy_light_client_primitives::min_signature_sample_size`] of the signing authority set.
+	pub signature_sample_size: u32,
+	/// Whether the client tracks the relay chain itself instead of a parachain. Its consensus
+	/// states are then the relay chain's state roots and `latest_para_height` tracks the latest
+	/// relay chain header with a consensus state.
+	pub track_relay_chain: bool,
 	/// Phantom type
 	pub _phantom: PhantomData<H>,
 }
@@ -105,6 +116,8 @@ impl<H: Clone> ClientState<H> {
 			relay_chain,
 			latest_para_height,
 			para_id,
+			signature_sample_size: 0,
+			track_relay_chain: false,
 			_phantom: PhantomData,
 		})
 	}
@@ -122,22 +135,19 @@ impl<H: Clone> ClientState<H> {
 		let mut authority_changed = false;
 		let (mmr_root_hash, latest_beefy_height, next_authority_set) =
 			if let Some(mmr_update) = header.mmr_update_proof {
-				if mmr_update.signed_commitment.commitment.validator_set_id ==
-					self.next_authority_set.id
-				{
+				let commitment = mmr_update.commitment();
+				if commitment.validator_set_id == self.next_authority_set.id {
 					authority_changed = true;
 				}
 				(
 					H256::from_slice(
-						mmr_update
-							.signed_commitment
-							.commitment
+						commitment
 							.payload
 							.get_raw(&MMR_ROOT_ID)
 							.ok_or_else(|| Error::Custom("Invalid header".into()))?,
 					),
-					mmr_update.signed_commitment.commitment.block_number,
-					mmr_update.latest_mmr_leaf.beefy_next_authority_set,
+					commitment.block_number,
+					mmr_update.latest_mmr_leaf().beefy_next_authority_set.clone(),
 				)
 			} else {
 				(self.mmr_root_hash, self.latest_beefy_height, self.next_authority_set)
@@ -272,8 +282,8 @@ where
 		upgrade_height: Height,
 		upgrade_options: UpgradeOptions,
 		chain_id: ChainId,
-	) -> Self {
-		self.upgrade(upgrade_height, upgrade_options, chain_id)
+	) -> Result<Self, Ics02Error> {
+		Ok(self.upgrade(upgrade_height, upgrade_options, chain_id))
 	}
 
 	fn expired(&self, elapsed: Duration) -> bool {
@@ -326,6 +336,8 @@ impl<H> TryFrom<RawClientState> for ClientState<H> {
 			relay_chain,
 			latest_para_height: raw.latest_para_height,
 			para_id: raw.para_id,
+			signature_sample_size: raw.signature_sample_size,
+			track_relay_chain: raw.track_relay_chain,
 			_phantom: Default::default(),
 		})
 	}
@@ -353,6 +365,8 @@ impl<H> From<ClientState<H>> for RawClientState {
 			relay_chain: client_state.relay_chain as i32,
 			para_id: client_state.para_id,
 			latest_para_height: client_state.latest_para_height,
+			signature_sample_size: client_state.signature_sample_size,
+			track_relay_chain: client_state.track_relay_chain,
 		}
 	}
 }
@@ -22,7 +22,10 @@ use tendermint_proto::{google::protobuf as tpb, Protobuf};
 
 use crate::proto::ConsensusState as RawConsensusState;
 
-use crate::{client_message::ParachainHeader, error::Error};
+use crate::{
+	client_message::{ParachainHeader, RelayChainHeader},
+	error::Error,
+};
 use ibc::{core::ics23_commitment::commitment::CommitmentRoot, timestamp::Timestamp};
 use light_client_common::decode_timestamp_extrinsic;
 
@@ -41,10 +44,23 @@ impl ConsensusState {
 	}
 
 	pub fn from_header(header: ParachainHeader) -> Result<Self, Error> {
-		use sp_runtime::SaturatedConversion;
 		let root = header.parachain_header.state_root.0.to_vec();
+		Self::from_timestamp_extrinsic(root, header.timestamp_extrinsic)
+	}
+
+	/// Consensus state of a relay chain header, for clients tracking the relay chain.
+	pub fn from_relay_chain_header(header: RelayChainHeader) -> Result<Self, Error> {
+		let root = header.relay_chain_header.state_root.0.to_vec();
+		Self::from_timestamp_extrinsic(root, header.timestamp_extrinsic)
+	}
+
+	fn from_timestamp_extrinsic(
+		root: Vec<u8>,
+		timestamp_extrinsic: Vec<u8>,
+	) -> Result<Self, Error> {
+		use sp_runtime::SaturatedConversion;
 
-		let timestamp = decode_timestamp_extrinsic(&header.timestamp_extrinsic)?;
+		let timestamp = decode_timestamp_extrinsic(&timestamp_extrinsic)?;
 		let duration = core::time::Duration::from_millis(timestamp);
 		let timestamp = Timestamp::from_nanoseconds(duration.as_nanos().saturated_into::<u64>())?
 			.into_tm_time()
@@ -257,7 +257,8 @@ async fn test_continuous_update_of_beefy_client() {
 				mmr_proofs: batch_proof.items.into_iter().map(|item| item.encode()).collect(),
 				mmr_size,
 			}),
-			mmr_update_proof: Some(mmr_update),
+			mmr_update_proof: Some(mmr_update.into()),
+			relay_chain_headers_with_proof: None,
 		};
 
 		let msg = MsgUpdateAnyClient {
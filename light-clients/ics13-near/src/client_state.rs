@@ -17,7 +17,10 @@ use super::types::{CryptoHash, LightClientBlockView, ValidatorStakeView};
 use crate::client_def::{HostFunctionsTrait, NearClient};
 use ibc::{
 	core::{
-		ics02_client::client_state::{ClientState, ClientType},
+		ics02_client::{
+			client_state::{ClientState, ClientType},
+			error::Error as Ics02Error,
+		},
 		ics24_host::identifier::ChainId,
 	},
 	prelude::*,
@@ -90,9 +93,9 @@ impl<H: HostFunctionsTrait> ClientState for NearClientState<H> {
 		_upgrade_height: Height,
 		_upgrade_options: Self::UpgradeOptions,
 		_chain_id: ChainId,
-	) -> Self {
+	) -> Result<Self, Ics02Error> {
 		// TODO: validate this -- not sure how to process the given parameters in this case
-		self
+		Ok(self)
 	}
 
 	fn expired(&self, _elapsed: Duration) -> bool {
@@ -29,12 +29,7 @@ mod weights;
 pub mod xcm_config;
 
 use codec::Encode;
-use core::str::FromStr;
 use cumulus_pallet_parachain_system::RelayNumberStrictlyIncreases;
-use ibc::core::{
-	ics24_host::identifier::PortId,
-	ics26_routing::context::{Module, ModuleId},
-};
 use orml_traits::asset_registry::AssetProcessor;
 use pallet_ibc::light_client_common::RelayChain;
 use smallvec::smallvec;
@@ -82,7 +77,7 @@ use sp_runtime::traits::AccountIdConversion;
 use weights::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight};
 
 // XCM Imports
-use pallet_ibc::routing::ModuleRouter;
+use pallet_ibc::routing::PortRegistryRouter;
 use xcm::latest::prelude::BodyId;
 use xcm_executor::XcmExecutor;
 
@@ -132,6 +127,7 @@ pub type SignedExtra = (
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
 	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_ibc::CheckRedundantRelay<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -147,8 +143,12 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
+/// Storage migrations run on runtime upgrade
+pub type Migrations = pallet_ibc::migrations::Migrations<Runtime>;
+
 /// Handles converting a weight scalar to a fee value, based on the scale and granularity of the
 /// node's balance type.
 ///
@@ -530,34 +530,15 @@ parameter_types! {
 	pub const ExpectedBlockTime: u64 = MILLISECS_PER_BLOCK as u64;
 	pub const RelayChainId: RelayChain = RelayChain::Rococo;
 	pub const SpamProtectionDeposit: Balance = 1_000_000_000_000;
+	pub const SpamDepositExpiry: BlockNumber = 7 * DAYS;
+	pub const ConsensusStatesToRetain: u32 = 64;
+	pub const MaxIbcPruningWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 10;
 	pub const NativeAssetId: AssetId = 1;
 	pub const MinimumConnectionDelay: u64 = 0; // well we don't need the security tbh.
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Default)]
-pub struct Router {
-	pallet_ibc_ping: pallet_ibc_ping::IbcModule<Runtime>,
-}
-
-impl ModuleRouter for Router {
-	fn get_route_mut(&mut self, module_id: &ModuleId) -> Option<&mut dyn Module> {
-		match module_id.as_ref() {
-			pallet_ibc_ping::MODULE_ID => Some(&mut self.pallet_ibc_ping),
-			_ => None,
-		}
-	}
-
-	fn has_route(module_id: &ModuleId) -> bool {
-		matches!(module_id.as_ref(), pallet_ibc_ping::MODULE_ID)
-	}
-
-	fn lookup_module_by_port(port_id: &PortId) -> Option<ModuleId> {
-		match port_id.as_str() {
-			pallet_ibc_ping::PORT_ID => ModuleId::from_str(pallet_ibc_ping::MODULE_ID).ok(),
-			_ => None,
-		}
-	}
-}
+/// Ibc applications routed through the pallet-ibc port registry
+pub type Router = PortRegistryRouter<(pallet_ibc_ping::IbcModule<Runtime>,)>;
 
 pub struct IbcDenomToAssetIdConversion;
 
@@ -693,6 +674,11 @@ impl pallet_ibc::Config for Runtime {
 	type AdminOrigin = EnsureRoot<AccountId>;
 	type SentryOrigin = EnsureRoot<AccountId>;
 	type SpamProtectionDeposit = SpamProtectionDeposit;
+	type SpamDepositExpiry = SpamDepositExpiry;
+	type ConsensusStatesToRetain = ConsensusStatesToRetain;
+	type MaxPruningWeight = MaxIbcPruningWeight;
+	type IcqCallback = ();
+	type XcmTransfer = xcm_config::IbcXcmTransfer;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -891,16 +877,24 @@ impl_runtime_apis! {
 			Ibc::consensus_state(client_id, revision_number, revision_height, latest_cs).ok()
 		}
 
-		fn clients() -> Option<Vec<(Vec<u8>, Vec<u8>)>> {
-			Some(Ibc::clients())
+		fn clients(pagination: Option<ibc_primitives::PageRequest>) -> Option<ibc_primitives::QueryClientStatesResponse> {
+			Some(Ibc::clients(pagination))
+		}
+
+		fn consensus_states(client_id: Vec<u8>, pagination: Option<ibc_primitives::PageRequest>) -> Option<ibc_primitives::QueryConsensusStatesResponse> {
+			Ibc::consensus_states(client_id, pagination).ok()
+		}
+
+		fn client_connections(client_id: Vec<u8>) -> Option<ibc_primitives::QueryClientConnectionsResponse> {
+			Some(Ibc::client_connections(client_id))
 		}
 
 		fn connection(connection_id: Vec<u8>) -> Option<ibc_primitives::QueryConnectionResponse>{
 			Ibc::connection(connection_id).ok()
 		}
 
-		fn connections() -> Option<ibc_primitives::QueryConnectionsResponse> {
-			Ibc::connections().ok()
+		fn connections(pagination: Option<ibc_primitives::PageRequest>) -> Option<ibc_primitives::QueryConnectionsResponse> {
+			Ibc::connections(pagination).ok()
 		}
 
 		fn connection_using_client(client_id: Vec<u8>) -> Option<Vec<ibc_primitives::IdentifiedConnection>>{
@@ -919,20 +913,24 @@ impl_runtime_apis! {
 			Ibc::channel_client(channel_id, port_id).ok()
 		}
 
-		fn connection_channels(connection_id: Vec<u8>) -> Option<ibc_primitives::QueryChannelsResponse> {
-			Ibc::connection_channels(connection_id).ok()
+		fn connection_channels(connection_id: Vec<u8>, pagination: Option<ibc_primitives::PageRequest>) -> Option<ibc_primitives::QueryChannelsResponse> {
+			Ibc::connection_channels(connection_id, pagination).ok()
+		}
+
+		fn channels(pagination: Option<ibc_primitives::PageRequest>) -> Option<ibc_primitives::QueryChannelsResponse> {
+			Ibc::channels(pagination).ok()
 		}
 
-		fn channels() -> Option<ibc_primitives::QueryChannelsResponse> {
-			Ibc::channels().ok()
+		fn packet_commitments(channel_id: Vec<u8>, port_id: Vec<u8>, pagination: Option<ibc_primitives::PageRequest>) -> Option<ibc_primitives::QueryPacketCommitmentsResponse> {
+			Ibc::packet_commitments(channel_id, port_id, pagination).ok()
 		}
 
-		fn packet_commitments(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryPacketCommitmentsResponse> {
-			Ibc::packet_commitments(channel_id, port_id).ok()
+		fn packet_acknowledgements(channel_id: Vec<u8>, port_id: Vec<u8>, pagination: Option<ibc_primitives::PageRequest>) -> Option<ibc_primitives::QueryPacketAcknowledgementsResponse>{
+			Ibc::packet_acknowledgements(channel_id, port_id, pagination).ok()
 		}
 
-		fn packet_acknowledgements(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryPacketAcknowledgementsResponse>{
-			Ibc::packet_acknowledgements(channel_id, port_id).ok()
+		fn packet_receipts(channel_id: Vec<u8>, port_id: Vec<u8>, pagination: Option<ibc_primitives::PageRequest>) -> Option<ibc_primitives::QueryPacketReceiptsResponse> {
+			Ibc::packet_receipts(channel_id, port_id, pagination).ok()
 		}
 
 		fn unreceived_packets(channel_id: Vec<u8>, port_id: Vec<u8>, seqs: Vec<u64>) -> Option<Vec<u64>> {
@@ -992,6 +990,24 @@ impl_runtime_apis! {
 				}).flatten().collect()
 			}
 		}
+
+		fn block_packet_records() -> Vec<ibc_primitives::PacketRecord> {
+			frame_system::Pallet::<Self>::read_events_no_consensus().into_iter().filter_map(|e| {
+				let frame_system::EventRecord{ event, ..} = *e;
+				match event {
+					Event::Ibc(pallet_ibc::Event::PacketRecorded{ record }) => Some(record),
+					_ => None
+				}
+			}).collect()
+		}
+
+		fn spam_deposit(client_or_connection_id: Vec<u8>) -> Option<ibc_primitives::SpamDepositStatus> {
+			Ibc::spam_deposit(client_or_connection_id)
+		}
+
+		fn allowed_clients() -> Option<Vec<ibc_primitives::AllowedClient>> {
+			Ibc::allowed_clients()
+		}
 	}
 
 	impl<Call, AccountId> simnode_apis::CreateTransactionApi<Block, AccountId, Call> for Runtime
@@ -1015,6 +1031,7 @@ impl_runtime_apis! {
 				frame_system::CheckNonce::<Runtime>::from(nonce),
 				frame_system::CheckWeight::<Runtime>::new(),
 				pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+				pallet_ibc::CheckRedundantRelay::<Runtime>::new(),
 			);
 			let signature = MultiSignature::from(sr25519::Signature([0_u8;64]));
 			let address = AccountIdLookup::unlookup(signer.into());
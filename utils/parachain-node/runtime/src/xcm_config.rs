@@ -13,33 +13,42 @@
 // limitations under the License.
 
 use super::{
-	AccountId, Balances, Call, Event, Origin, ParachainInfo, ParachainSystem, PolkadotXcm, Runtime,
-	WeightToFee, XcmpQueue,
+	AccountId, AssetId, Assets, Balance, Balances, Call, Event, NativeAssetId, Origin,
+	ParachainInfo, ParachainSystem, PolkadotXcm, Runtime, WeightToFee, XcmpQueue,
 };
 use core::marker::PhantomData;
 use frame_support::{
 	log, match_types, parameter_types,
-	traits::{Everything, Nothing},
+	traits::{Everything, Get, Nothing, PalletInfoAccess},
 	weights::Weight,
 };
+use pallet_ibc::ics20::xcm::{XcmDestination, XcmTransfer};
 use pallet_xcm::XcmPassthrough;
 use polkadot_parachain::primitives::Sibling;
 use polkadot_runtime_common::impls::ToAuthor;
+use sp_runtime::DispatchError;
 use xcm::latest::prelude::*;
 use xcm_builder::{
-	AccountId32Aliases, AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom, CurrencyAdapter,
-	EnsureXcmOrigin, FixedWeightBounds, IsConcrete, LocationInverter, NativeAsset, ParentIsPreset,
+	AccountId32Aliases, AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom,
+	AsPrefixedGeneralIndex, ConvertedConcreteAssetId, CurrencyAdapter, EnsureXcmOrigin,
+	FixedWeightBounds, FungiblesAdapter, IsConcrete, LocationInverter, NativeAsset, ParentIsPreset,
 	RelayChainAsNative, SiblingParachainAsNative, SiblingParachainConvertsVia,
 	SignedAccountId32AsNative, SignedToAccountId32, SovereignSignedViaLocation, TakeWeightCredit,
 	UsingComponents,
 };
-use xcm_executor::{traits::ShouldExecute, XcmExecutor};
+use xcm_executor::{
+	traits::{JustTry, ShouldExecute, WeightBounds},
+	XcmExecutor,
+};
 
 parameter_types! {
 	pub const RelayLocation: MultiLocation = MultiLocation::parent();
 	pub const RelayNetwork: NetworkId = NetworkId::Any;
 	pub RelayChainOrigin: Origin = cumulus_pallet_xcm::Origin::Relay.into();
 	pub Ancestry: MultiLocation = Parachain(ParachainInfo::parachain_id().into()).into();
+	pub AssetsPalletLocation: MultiLocation =
+		PalletInstance(<Assets as PalletInfoAccess>::index() as u8).into();
+	pub CheckingAccount: AccountId = PolkadotXcm::check_account();
 }
 
 /// Type for specifying how a `MultiLocation` can be converted into an `AccountId`. This is used
@@ -68,6 +77,30 @@ pub type LocalAssetTransactor = CurrencyAdapter<
 	(),
 >;
 
+/// Means for transacting the assets of `pallet-assets`, which include ibc vouchers.
+pub type FungiblesTransactor = FungiblesAdapter<
+	// Use this fungibles implementation:
+	Assets,
+	// Use this asset when its location is a general index under the assets pallet:
+	ConvertedConcreteAssetId<
+		AssetId,
+		Balance,
+		AsPrefixedGeneralIndex<AssetsPalletLocation, AssetId, JustTry>,
+		JustTry,
+	>,
+	// Convert an XCM MultiLocation into a local account id:
+	LocationToAccountId,
+	// Our chain's account ID type (we can't get away without mentioning it explicitly):
+	AccountId,
+	// We don't track any teleports of `Assets`.
+	Nothing,
+	// The account used to check teleports, unused since no teleports are tracked.
+	CheckingAccount,
+>;
+
+/// Means for transacting assets on this chain.
+pub type AssetTransactors = (LocalAssetTransactor, FungiblesTransactor);
+
 /// This is the type we use to convert an (incoming) XCM origin into a local `Origin` instance,
 /// ready for dispatching a transaction with Xcm's `Transact`. There is an `OriginKind` which can
 /// biases the kind of local `Origin` it will become.
@@ -181,7 +214,7 @@ impl xcm_executor::Config for XcmConfig {
 	type Call = Call;
 	type XcmSender = XcmRouter;
 	// How to withdraw and deposit an asset.
-	type AssetTransactor = LocalAssetTransactor;
+	type AssetTransactor = AssetTransactors;
 	type OriginConverter = XcmOriginToTransactDispatchOrigin;
 	type IsReserve = NativeAsset;
 	type IsTeleporter = (); // Teleporting is disabled.
@@ -233,3 +266,59 @@ impl cumulus_pallet_xcm::Config for Runtime {
 	type Event = Event;
 	type XcmExecutor = XcmExecutor<XcmConfig>;
 }
+
+/// Forwards tokens received over ibc to sibling parachains, by executing a reserve transfer on
+/// behalf of the packet receiver.
+pub struct IbcXcmTransfer;
+
+impl XcmTransfer<Runtime> for IbcXcmTransfer {
+	fn reserve_transfer(
+		from: AccountId,
+		asset_id: AssetId,
+		amount: Balance,
+		destination: XcmDestination<AccountId>,
+	) -> Result<(), DispatchError> {
+		// The native currency is matched as the relay chain token, so this chain isn't its reserve.
+		if asset_id == NativeAssetId::get() {
+			return Err(DispatchError::Other("Native asset can't be forwarded over xcm"))
+		}
+		let location = AssetsPalletLocation::get()
+			.pushed_with_interior(GeneralIndex(asset_id))
+			.map_err(|_| DispatchError::Other("Invalid asset location"))?;
+		let asset: MultiAsset = (location, amount).into();
+		let dest = MultiLocation::new(1, X1(Parachain(destination.para_id)));
+		// Execution on the destination is paid for with the forwarded tokens.
+		let fees = asset
+			.clone()
+			.reanchored(&dest, &Ancestry::get())
+			.map_err(|_| DispatchError::Other("Failed to reanchor asset"))?;
+		let beneficiary: MultiLocation =
+			AccountId32 { network: NetworkId::Any, id: destination.beneficiary.into() }.into();
+		let mut message = Xcm(vec![
+			WithdrawAsset(asset.clone().into()),
+			TransferReserveAsset {
+				assets: asset.into(),
+				dest,
+				xcm: Xcm(vec![
+					BuyExecution { fees, weight_limit: Unlimited },
+					DepositAsset { assets: Wild(All), max_assets: 1, beneficiary },
+				]),
+			},
+		]);
+		let weight = <XcmConfig as xcm_executor::Config>::Weigher::weight(&mut message)
+			.map_err(|_| DispatchError::Other("Failed to weigh xcm message"))?;
+		let origin: MultiLocation = AccountId32 { network: NetworkId::Any, id: from.into() }.into();
+
+		XcmExecutor::<XcmConfig>::execute_xcm_in_credit(origin, message, weight, weight)
+			.ensure_complete()
+			.map_err(|e| {
+				log::trace!(target: "pallet_ibc", "Failed to forward tokens over xcm: {:?}", e);
+				DispatchError::Other("Xcm execution failed")
+			})
+	}
+
+	fn weight() -> Weight {
+		// `WithdrawAsset` and `TransferReserveAsset` are executed locally.
+		UnitWeightCost::get().saturating_mul(2)
+	}
+}
@@ -244,5 +244,6 @@ fn testnet_genesis(
 		ibc: parachain_runtime::IbcConfig {
 			assets: vec![AssetConfig { id: 1, denom: b"UNIT".to_vec() }],
 		},
+		ibc_ping: Default::default(),
 	}
 }
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf};
 
 /// Sub-commands supported by the collator.
 #[derive(Debug, clap::Subcommand)]
@@ -76,6 +76,23 @@ pub struct Cli {
 	#[clap(long)]
 	pub no_hardware_benchmarks: bool,
 
+	/// Index IBC packets from block events into the local offchain database, starting at this
+	/// block.
+	///
+	/// When set, `ibc_querySendPackets`, `ibc_queryRecvPackets` and `ibc_queryEvents` are served
+	/// from the index instead of offchain indexed storage, which is only populated on nodes that
+	/// ran with offchain indexing enabled from genesis.
+	///
+	/// Packets are only recorded in the events of blocks built by a runtime with version 2 of the
+	/// IBC runtime api onwards, packets sent or received in earlier blocks are missing from the
+	/// index whatever block it starts at.
+	#[clap(long)]
+	pub ibc_packet_indexer_from: Option<u32>,
+
+	/// Serve the ibc-go gRPC query services on this address, e.g. `127.0.0.1:9090`.
+	#[clap(long)]
+	pub ibc_grpc_addr: Option<SocketAddr>,
+
 	/// Relay chain arguments
 	#[clap(raw = true)]
 	pub relay_chain_args: Vec<String>,
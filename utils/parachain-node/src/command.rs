@@ -323,6 +323,8 @@ pub fn run() -> Result<()> {
 					collator_options,
 					id,
 					hwbench,
+					cli.ibc_packet_indexer_from,
+					cli.ibc_grpc_addr,
 				)
 				.await
 				.map(|r| r.0)
@@ -15,7 +15,7 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
 // std
-use std::{sync::Arc, time::Duration};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 // rpc
 use jsonrpsee::RpcModule;
@@ -39,7 +39,11 @@ use cumulus_relay_chain_interface::{RelayChainError, RelayChainInterface, RelayC
 use cumulus_relay_chain_rpc_interface::RelayChainRPCInterface;
 
 // Substrate Imports
-use sc_client_api::ExecutorProvider;
+use ibc_rpc::{
+	indexer::{OffchainPacketStore, PacketIndexer, PacketStore},
+	IbcRpcHandler,
+};
+use sc_client_api::{Backend, ExecutorProvider};
 use sc_executor::NativeElseWasmExecutor;
 use sc_network::NetworkService;
 use sc_service::{Configuration, PartialComponents, TFullBackend, TFullClient, TaskManager};
@@ -215,6 +219,8 @@ async fn start_node_impl<RuntimeApi, Executor, RB, BIQ, BIC>(
 	build_import_queue: BIQ,
 	build_consensus: BIC,
 	hwbench: Option<sc_sysinfo::HwBench>,
+	ibc_packet_indexer_from: Option<u32>,
+	ibc_grpc_addr: Option<SocketAddr>,
 ) -> sc_service::error::Result<(
 	TaskManager,
 	Arc<TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<Executor>>>,
@@ -315,17 +321,48 @@ where
 			warp_sync: None,
 		})?;
 
+	let packet_store = match ibc_packet_indexer_from {
+		Some(from) => {
+			let offchain_storage = backend
+				.offchain_storage()
+				.ok_or("IBC packet indexer requires the offchain database")?;
+			let packet_store: Arc<dyn PacketStore> =
+				Arc::new(OffchainPacketStore::new(offchain_storage));
+			let indexer = PacketIndexer::<_, _, AssetId>::new(client.clone(), packet_store.clone());
+			task_manager.spawn_handle().spawn("ibc-packet-indexer", None, indexer.run(from));
+			Some(packet_store)
+		},
+		None => None,
+	};
+
+	if let Some(addr) = ibc_grpc_addr {
+		let mut handler =
+			IbcRpcHandler::new(client.clone(), parachain_config.chain_spec.properties());
+		if let Some(packet_store) = packet_store.clone() {
+			handler = handler.with_packet_store(packet_store);
+		}
+		task_manager.spawn_handle().spawn("ibc-grpc", None, async move {
+			log::info!("IBC gRPC server listening on {}", addr);
+			if let Err(e) = ibc_rpc::grpc::serve::<_, _, AssetId>(addr, handler).await {
+				log::error!("IBC gRPC server failed: {}", e);
+			}
+		});
+	}
+
 	let rpc_builder = {
 		let client = client.clone();
 		let transaction_pool = transaction_pool.clone();
 		let chain_props = parachain_config.chain_spec.properties();
+		let packet_store = packet_store.clone();
 
-		Box::new(move |deny_unsafe, _| {
+		Box::new(move |deny_unsafe, subscription_executor| {
 			let deps = crate::rpc::FullDeps {
 				client: client.clone(),
 				pool: transaction_pool.clone(),
 				chain_props: chain_props.clone(),
 				deny_unsafe,
+				packet_store: packet_store.clone(),
+				subscription_executor,
 			};
 
 			crate::rpc::create_full(deps).map_err(Into::into)
@@ -468,6 +505,8 @@ pub async fn start_parachain_node(
 	collator_options: CollatorOptions,
 	id: ParaId,
 	hwbench: Option<sc_sysinfo::HwBench>,
+	ibc_packet_indexer_from: Option<u32>,
+	ibc_grpc_addr: Option<SocketAddr>,
 ) -> sc_service::error::Result<(
 	TaskManager,
 	Arc<TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<TemplateRuntimeExecutor>>>,
@@ -543,6 +582,8 @@ pub async fn start_parachain_node(
 			))
 		},
 		hwbench,
+		ibc_packet_indexer_from,
+		ibc_grpc_addr,
 	)
 	.await
 }
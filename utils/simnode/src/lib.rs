@@ -81,6 +81,7 @@ impl ChainInfo for ParachainRuntimeChainInfo {
 			frame_system::CheckNonce::<Self::Runtime>::from(nonce),
 			frame_system::CheckWeight::<Self::Runtime>::new(),
 			pallet_transaction_payment::ChargeTransactionPayment::<Self::Runtime>::from(0),
+			pallet_ibc::CheckRedundantRelay::<Self::Runtime>::new(),
 		)
 	}
 }
@@ -91,12 +92,15 @@ mod tests {
 	use frame_benchmarking::frame_support::codec::Encode;
 	use grandpa_client_primitives::{justification::GrandpaJustification, Commit, FinalityProof};
 	use ibc::{
+		applications::transfer::{packet::PacketData, Amount, PrefixedCoin, PrefixedDenom},
 		core::{
 			ics02_client::{
 				context::ClientTypes,
 				msgs::{create_client::MsgCreateAnyClient, update_client::MsgUpdateAnyClient},
 			},
-			ics24_host::identifier::ClientId,
+			ics04_channel::packet::Packet,
+			ics24_host::identifier::{ChannelId, ClientId, PortId},
+			ics26_routing::context::{Module, ModuleOutputBuilder},
 		},
 		protobuf::Protobuf,
 		signer::Signer,
@@ -106,9 +110,11 @@ mod tests {
 	use ics10_grandpa::client_message::RelayChainHeader;
 	use pallet_ibc::{
 		light_clients::{AnyClient, AnyClientMessage, AnyClientState, AnyConsensusState},
+		routing::Context,
 		Any,
 	};
-	use sp_runtime::{testing::H256, SaturatedConversion};
+	use parachain_runtime::Runtime;
+	use sp_runtime::{testing::H256, AccountId32, SaturatedConversion};
 	use std::str::FromStr;
 	use substrate_simnode::ChainInfo;
 
@@ -139,6 +145,7 @@ mod tests {
 				frozen_height: None,
 				latest_para_height: 10,
 				para_id: 100,
+				standalone: false,
 				current_set_id: 1,
 				current_authorities: Default::default(),
 				_phantom: Default::default(),
@@ -169,6 +176,7 @@ mod tests {
 					unknown_headers: Default::default(),
 				},
 				parachain_headers: Default::default(),
+				authority_set_changes: Default::default(),
 			};
 			let msg_create_client = MsgCreateAnyClient::<LocalClientTypes> {
 				client_state: AnyClientState::Grandpa(client_state),
@@ -228,4 +236,61 @@ mod tests {
 		})
 		.unwrap();
 	}
+
+	#[test]
+	fn should_not_credit_tokens_when_xcm_forwarding_fails() {
+		substrate_simnode::parachain_node::<ParachainRuntimeChainInfo, _, _>(|node| async move {
+			node.with_state(None, || {
+				pallet_ibc::Params::<Runtime>::put(pallet_ibc::PalletParams {
+					send_enabled: true,
+					receive_enabled: true,
+				});
+				let receiver = AccountId32::new([1; 32]);
+				// There's no hrmp channel to this parachain, so the xcm can't be delivered.
+				let memo = format!(
+					r#"{{"xcm":{{"para_id":3000,"beneficiary":"0x{}"}}}}"#,
+					hex::encode([2u8; 32])
+				);
+				let packet_data = PacketData {
+					token: PrefixedCoin {
+						denom: PrefixedDenom::from_str("uatom").unwrap(),
+						amount: Amount::from_str("1000000000000").unwrap(),
+					},
+					sender: Signer::from_str("alice").unwrap(),
+					receiver: Signer::from_str(&format!("0x{}", hex::encode(&receiver))).unwrap(),
+					memo,
+				};
+				let packet = Packet {
+					sequence: 1u64.into(),
+					source_port: PortId::transfer(),
+					source_channel: ChannelId::new(1),
+					destination_port: PortId::transfer(),
+					destination_channel: ChannelId::new(0),
+					data: serde_json::to_vec(&packet_data).unwrap(),
+					timeout_height: Default::default(),
+					timeout_timestamp: Default::default(),
+				};
+
+				let ctx = Context::<Runtime>::default();
+				let mut output = ModuleOutputBuilder::new();
+				pallet_ibc::ics20::IbcModule::<Runtime>::default()
+					.on_recv_packet(
+						&ctx,
+						&mut output,
+						&packet,
+						&Signer::from_str("relayer").unwrap(),
+					)
+					.unwrap();
+
+				// Minting the voucher was rolled back together with the failed xcm transfer, an
+				// error acknowledgement refunds the sender instead.
+				assert!(pallet_ibc::IbcDenoms::<Runtime>::get(
+					b"transfer/channel-0/uatom".to_vec()
+				)
+				.is_none());
+			});
+			Ok(())
+		})
+		.unwrap();
+	}
 }